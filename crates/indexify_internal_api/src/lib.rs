@@ -89,6 +89,8 @@ pub struct Index {
     pub extractor_name: ExtractorName,
     pub graph_name: ExtractionGraphName,
     pub visibility: bool,
+    #[serde(default)]
+    pub attribute_schema: IndexAttributeSchema,
 }
 
 impl Index {
@@ -108,6 +110,12 @@ impl Index {
         )
     }
 
+    /// Schema of the embeddings the index was created for, if it is a vector
+    /// index
+    pub fn embedding_schema(&self) -> Option<EmbeddingSchema> {
+        serde_json::from_str(&self.schema).ok()
+    }
+
     pub fn build_table_name(&self, output_name: &String) -> String {
         format!(
             "{}.{}.{}.{}",
@@ -129,6 +137,7 @@ impl Hash for Index {
 impl From<Index> for indexify_coordinator::Index {
     fn from(value: Index) -> Self {
         Self {
+            attribute_schema: value.attribute_schema.to_json_string(),
             name: value.name,
             table_name: value.table_name,
             schema: value.schema,
@@ -152,12 +161,74 @@ impl From<indexify_coordinator::Index> for Index {
             namespace: value.namespace,
             graph_name: value.graph_name,
             visibility: false,
+            attribute_schema: IndexAttributeSchema::from_json_str(&value.attribute_schema)
+                .unwrap_or_default(),
         };
         index.id = index.id();
         index
     }
 }
 
+/// What an index does with a metadata attribute of its embeddings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, SmartDefault, ToSchema)]
+#[serde(default)]
+pub struct AttributeOptions {
+    /// stored is true when the attribute is written to the vector database
+    #[default = true]
+    pub stored: bool,
+
+    /// filterable is true when searches can filter on the attribute
+    #[default = true]
+    pub filterable: bool,
+
+    /// returned is true when the attribute is part of search results
+    #[default = true]
+    pub returned: bool,
+}
+
+/// Options of the metadata attributes of an index. Attributes which are not
+/// listed are stored, filterable and returned, which is how indexes created
+/// without a schema behave.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct IndexAttributeSchema {
+    #[serde(default)]
+    pub attributes: BTreeMap<String, AttributeOptions>,
+}
+
+impl IndexAttributeSchema {
+    pub fn options(&self, attribute: &str) -> AttributeOptions {
+        self.attributes.get(attribute).copied().unwrap_or_default()
+    }
+
+    pub fn is_stored(&self, attribute: &str) -> bool {
+        self.options(attribute).stored
+    }
+
+    pub fn is_filterable(&self, attribute: &str) -> bool {
+        self.options(attribute).filterable
+    }
+
+    pub fn is_returned(&self, attribute: &str) -> bool {
+        self.options(attribute).returned
+    }
+
+    /// Encodes the schema for the coordinator protocol, an empty string is
+    /// the empty schema
+    pub fn to_json_string(&self) -> String {
+        if self.attributes.is_empty() {
+            return "".to_string();
+        }
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json_str(value: &str) -> Result<Self> {
+        if value.is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(value).map_err(|e| anyhow!("invalid attribute schema: {}", e))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EmbeddingSchema {
     pub dim: usize,
@@ -184,9 +255,15 @@ pub type ExtractorName = String;
 pub struct ExtractorDescription {
     pub name: ExtractorName,
     pub description: String,
+    /// JSON schema of the input params the extractor expects, which the
+    /// input params of extraction policies can be validated against
     pub input_params: serde_json::Value,
     pub outputs: HashMap<String, OutputSchema>,
     pub input_mime_types: Vec<String>,
+    /// Resource requirements an executor must satisfy to run this extractor,
+    /// e.g. `gpu: "true"` or `memory_gb: "16"`.
+    #[serde(default)]
+    pub requirements: HashMap<String, String>,
 }
 
 impl From<ExtractorDescription> for indexify_coordinator::Extractor {
@@ -214,6 +291,7 @@ impl From<ExtractorDescription> for indexify_coordinator::Extractor {
             embedding_schemas,
             input_mime_types: value.input_mime_types,
             metadata_schemas,
+            requirements: value.requirements,
         }
     }
 }
@@ -297,6 +375,7 @@ impl From<indexify_coordinator::Extractor> for ExtractorDescription {
             input_params: serde_json::from_str(&value.input_params).unwrap(),
             outputs: output_schema,
             input_mime_types: value.input_mime_types,
+            requirements: value.requirements,
         }
     }
 }
@@ -436,9 +515,29 @@ pub struct Task {
     pub namespace: String,
     pub content_metadata: ContentMetadata,
     pub input_params: serde_json::Value,
+    // version of the extraction policy the task was created from
+    #[serde(default)]
+    pub extraction_policy_version: u64,
+    // version of the extraction policy the executor ran the task with, recorded when it finishes
+    #[serde(default)]
+    pub resolved_policy_version: Option<u64>,
     #[schema(value_type = internal_api::TaskOutcome)]
     pub outcome: TaskOutcome,
     pub index_tables: Vec<String>, // list of index tables that this content may be present in
+    // ids of the content extracted by the task, recorded when it finishes
+    #[serde(default)]
+    pub output_content_ids: Vec<String>,
+    // resources reported by the executor when the task finishes
+    #[serde(default)]
+    pub usage: TaskUsage,
+}
+
+/// Resources an executor reports for running a task, 0 if unknown
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, ToSchema, Default)]
+#[schema(as = internal_api::TaskUsage)]
+pub struct TaskUsage {
+    pub vectors_produced: u64,
+    pub execution_time_ms: u64,
 }
 
 impl Task {
@@ -460,8 +559,110 @@ impl Task {
             namespace: content_metadata.namespace.clone(),
             content_metadata: content_metadata.clone(),
             input_params: serde_json::Value::Null,
+            extraction_policy_version: extraction_policy.version,
+            resolved_policy_version: None,
             outcome: TaskOutcome::Unknown,
             index_tables: Vec::new(),
+            output_content_ids: Vec::new(),
+            usage: TaskUsage::default(),
+        }
+    }
+}
+
+/// Where a task is in its lifecycle. Tasks are created unassigned, assigned
+/// to an executor and finished by it, or moved back to the unassigned queue
+/// when the executor can't run them.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq)]
+pub enum TaskLifecycleState {
+    Unassigned,
+    Assigned { executor_id: String },
+    Finished { outcome: TaskOutcome },
+}
+
+impl TaskLifecycleState {
+    /// Whether a task can move from this state to the other
+    pub fn can_transition_to(&self, to: &TaskLifecycleState) -> bool {
+        match (self, to) {
+            (TaskLifecycleState::Unassigned, TaskLifecycleState::Assigned { .. }) => true,
+            (TaskLifecycleState::Assigned { .. }, TaskLifecycleState::Unassigned) => true,
+            (TaskLifecycleState::Assigned { .. }, TaskLifecycleState::Finished { outcome }) => {
+                *outcome != TaskOutcome::Unknown
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Counters for the tasks of one extractor in a namespace that finished on a
+/// given day. Days are counted from the unix epoch in UTC.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq, Default)]
+pub struct UsageStats {
+    pub namespace: String,
+    pub extractor: String,
+    pub day: u64,
+    pub tasks: u64,
+    pub content_bytes: u64,
+    pub chunks: u64,
+    pub vectors: u64,
+    pub execution_time_ms: u64,
+}
+
+impl UsageStats {
+    pub fn new(namespace: &str, extractor: &str, day: u64) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            extractor: extractor.to_string(),
+            day,
+            ..Default::default()
+        }
+    }
+
+    /// Keys sort by namespace and then by day so a day range of a namespace
+    /// can be read with a prefix scan
+    pub fn key(&self) -> String {
+        Self::key_prefix(&self.namespace, self.day) + &self.extractor
+    }
+
+    pub fn key_prefix(namespace: &str, day: u64) -> String {
+        format!("{}::{:010}::", namespace, day)
+    }
+
+    /// Adds the resources of a finished task to the counters
+    pub fn record(&mut self, task: &Task) {
+        self.tasks += 1;
+        self.content_bytes += task.content_metadata.size_bytes;
+        self.chunks += task.output_content_ids.len() as u64;
+        self.vectors += task.usage.vectors_produced;
+        self.execution_time_ms += task.usage.execution_time_ms;
+    }
+}
+
+impl From<UsageStats> for indexify_coordinator::UsageStats {
+    fn from(value: UsageStats) -> Self {
+        Self {
+            namespace: value.namespace,
+            extractor: value.extractor,
+            day: value.day,
+            tasks: value.tasks,
+            content_bytes: value.content_bytes,
+            chunks: value.chunks,
+            vectors: value.vectors,
+            execution_time_ms: value.execution_time_ms,
+        }
+    }
+}
+
+impl From<indexify_coordinator::UsageStats> for UsageStats {
+    fn from(value: indexify_coordinator::UsageStats) -> Self {
+        Self {
+            namespace: value.namespace,
+            extractor: value.extractor,
+            day: value.day,
+            tasks: value.tasks,
+            content_bytes: value.content_bytes,
+            chunks: value.chunks,
+            vectors: value.vectors,
+            execution_time_ms: value.execution_time_ms,
         }
     }
 }
@@ -490,6 +691,9 @@ impl From<Task> for indexify_coordinator::Task {
             output_index_mapping: value.output_index_table_mapping,
             outcome: outcome as i32,
             index_tables: value.index_tables,
+            extraction_policy_version: value.extraction_policy_version,
+            content_url: String::new(),
+            content_url_expires_at: 0,
         }
     }
 }
@@ -516,6 +720,13 @@ pub struct GarbageCollectionTask {
     pub blob_store_path: String,
     pub assigned_to: Option<String>,
     pub task_type: ServerTaskType,
+    /// Reason the content was tombstoned, for tasks deleting content
+    #[serde(default)]
+    pub tombstone_reason: Option<TombstoneReason>,
+    /// Output tables the vectors of the content were already removed from
+    /// by a previous attempt of the task
+    #[serde(default)]
+    pub completed_output_tables: HashSet<String>,
 }
 
 impl GarbageCollectionTask {
@@ -529,6 +740,7 @@ impl GarbageCollectionTask {
         namespace.hash(&mut hasher);
         content_metadata.id.hash(&mut hasher);
         let id = format!("{:x}", hasher.finish());
+        let tombstone_reason = content_metadata.reason_tombstoned();
         Self {
             namespace: namespace.to_string(),
             id,
@@ -540,6 +752,8 @@ impl GarbageCollectionTask {
             blob_store_path: content_metadata.storage_url,
             assigned_to: None,
             task_type,
+            tombstone_reason,
+            completed_output_tables: HashSet::new(),
         }
     }
 }
@@ -553,6 +767,8 @@ impl From<GarbageCollectionTask> for indexify_coordinator::GcTask {
             output_tables: value.output_tables.into_iter().collect::<Vec<String>>(),
             blob_store_path: value.blob_store_path,
             task_type: value.task_type as i32,
+            tombstone_reason: value.tombstone_reason.map(Into::into),
+            completed_output_tables: value.completed_output_tables.into_iter().collect(),
         }
     }
 }
@@ -633,6 +849,74 @@ pub struct ExtractionPolicy {
     // The source of the content this policy will match against. Will either be the graph id or a
     // parent policy id
     pub content_source: ExtractionPolicyContentSource,
+    // Attribute schema of the indexes created for the embedding outputs
+    #[serde(default)]
+    pub index_attributes: IndexAttributeSchema,
+    // Incremented every time the input params of the policy are updated
+    #[serde(default = "default_extraction_policy_version")]
+    pub version: u64,
+    // Versions of the content the policy runs on
+    #[serde(default)]
+    pub version_strategy: ExtractionPolicyVersionStrategy,
+}
+
+fn default_extraction_policy_version() -> u64 {
+    1
+}
+
+/// Versions of content an extraction policy creates tasks for, when a new
+/// version of the content is ingested
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Default,
+    EnumString,
+    Display,
+    ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ExtractionPolicyVersionStrategy {
+    /// Runs on the latest version only. Tasks of older versions which are
+    /// still waiting for an executor are cancelled.
+    #[default]
+    LatestOnly,
+    /// Runs on every version, each version records the policy as applied
+    EveryVersion,
+    /// Runs once on the first version and never on later ones
+    FirstVersionOnly,
+}
+
+/// Health of an extraction policy, derived from the executors which are
+/// currently able to run its extractor
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Default,
+    EnumString,
+    Display,
+    ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ExtractionPolicyStatus {
+    #[default]
+    Active,
+    NoExecutors,
+    /// The extractor has no executors and the scheduler stopped creating
+    /// tasks for it, as it has reached the pending task cap
+    Paused,
+    IncompatibleOutput,
 }
 
 impl From<ExtractionPolicy> for indexify_coordinator::ExtractionPolicy {
@@ -650,6 +934,10 @@ impl From<ExtractionPolicy> for indexify_coordinator::ExtractionPolicy {
             content_source: value.content_source.into(),
             graph_name: value.graph_name,
             output_table_mapping: value.output_table_mapping,
+            status: ExtractionPolicyStatus::default().to_string(),
+            index_attributes: value.index_attributes.to_json_string(),
+            version: value.version,
+            version_strategy: value.version_strategy.to_string(),
         }
     }
 }
@@ -709,10 +997,65 @@ impl ExtractionPolicyBuilder {
             input_params: self.input_params.clone().unwrap_or_default(),
             output_table_mapping,
             content_source,
+            index_attributes: self.index_attributes.clone().unwrap_or_default(),
+            version: default_extraction_policy_version(),
+            version_strategy: self.version_strategy.unwrap_or_default(),
         })
     }
 }
 
+/// Separator between the id and the version of content ids in their string
+/// form, and in the keys of versioned content by default
+pub const DEFAULT_CONTENT_VERSION_SEPARATOR: &str = "::v";
+
+/// Separator between the id and the version in the keys of versioned content,
+/// as configured for the state store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentVersionSeparator(String);
+
+impl ContentVersionSeparator {
+    pub fn new(separator: &str) -> Result<Self> {
+        if separator.is_empty() {
+            return Err(anyhow!("content version separator can't be empty"));
+        }
+        if separator.ends_with(|c: char| c.is_ascii_digit()) {
+            return Err(anyhow!(
+                "content version separator can't end with a digit: {}",
+                separator
+            ));
+        }
+        Ok(Self(separator.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Key of the content with the id and version, when it isn't the latest
+    /// version
+    pub fn key(&self, id: &ContentMetadataId) -> String {
+        id.to_string_with_separator(&self.0)
+    }
+
+    /// Whether a content table key is the key of a version other than the
+    /// latest one
+    pub fn is_versioned_key(&self, key: &str) -> bool {
+        key.contains(&self.0)
+    }
+}
+
+impl Default for ContentVersionSeparator {
+    fn default() -> Self {
+        Self(DEFAULT_CONTENT_VERSION_SEPARATOR.to_string())
+    }
+}
+
+impl Display for ContentVersionSeparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ContentMetadataId {
     pub id: String,
@@ -762,36 +1105,62 @@ impl ContentMetadataId {
             version,
         }
     }
-}
 
-impl Display for ContentMetadataId {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}::v{}", self.id, self.version)
+    /// Derives the id of the first version of content from its namespace and
+    /// hash, so the same content ingested twice gets the same id and is
+    /// deduplicated. The id is a 64 bit hash like the ids of extraction
+    /// graphs and policies. Distinct namespace and hash pairs are unlikely
+    /// to collide, but if they do the second content is stored as a new
+    /// version of the first, since content with a known id and a different
+    /// hash is an update.
+    pub fn deterministic(namespace: &str, content_hash: &str) -> Self {
+        let mut s = DefaultHasher::new();
+        namespace.hash(&mut s);
+        content_hash.hash(&mut s);
+        Self::new(&format!("{:x}", s.finish()))
     }
-}
 
-impl TryFrom<&String> for ContentMetadataId {
-    type Error = anyhow::Error;
+    pub fn to_string_with_separator(&self, separator: &str) -> String {
+        format!("{}{}{}", self.id, separator, self.version)
+    }
 
-    fn try_from(value: &String) -> Result<Self> {
+    /// Parses `{id}{separator}{version}`. The last occurrence of the separator
+    /// is used, so ids containing the separator still parse.
+    pub fn parse_with_separator(value: &str, separator: &str) -> Result<Self> {
         if value.is_empty() {
             return Ok(Self {
                 id: "".to_string(),
                 version: 0,
             });
         }
-
-        let parts: Vec<&str> = value.split("::v").collect();
-        if parts.len() != 2 {
-            return Err(anyhow!("Invalid ContentMetadataId"));
-        }
+        let (id, version) = value
+            .rsplit_once(separator)
+            .ok_or_else(|| anyhow!("Invalid ContentMetadataId"))?;
         Ok(Self {
-            id: parts[0].to_string(),
-            version: parts[1].parse()?,
+            id: id.to_string(),
+            version: version.parse()?,
         })
     }
 }
 
+impl Display for ContentMetadataId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.to_string_with_separator(DEFAULT_CONTENT_VERSION_SEPARATOR)
+        )
+    }
+}
+
+impl TryFrom<&String> for ContentMetadataId {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &String) -> Result<Self> {
+        Self::parse_with_separator(value, DEFAULT_CONTENT_VERSION_SEPARATOR)
+    }
+}
+
 impl TryFrom<String> for ContentMetadataId {
     type Error = anyhow::Error;
 
@@ -869,6 +1238,157 @@ impl Display for ContentSource {
     }
 }
 
+/// Name of the vector attribute the principals allowed to read a chunk are
+/// stored under. Users can't filter on it or write it.
+pub const ACL_ATTRIBUTE: &str = "__acl";
+
+/// Users and groups allowed to read a piece of content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+pub struct ContentAcl {
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+impl ContentAcl {
+    /// The users and groups of the ACL as `user:<name>` and `group:<name>`,
+    /// as stored in vector attributes
+    pub fn principals(&self) -> Vec<String> {
+        self.users
+            .iter()
+            .map(|user| format!("user:{}", user))
+            .chain(self.groups.iter().map(|group| format!("group:{}", group)))
+            .collect()
+    }
+}
+
+impl From<indexify_coordinator::ContentAcl> for ContentAcl {
+    fn from(value: indexify_coordinator::ContentAcl) -> Self {
+        Self {
+            users: value.users,
+            groups: value.groups,
+        }
+    }
+}
+
+impl From<ContentAcl> for indexify_coordinator::ContentAcl {
+    fn from(value: ContentAcl) -> Self {
+        Self {
+            users: value.users,
+            groups: value.groups,
+        }
+    }
+}
+
+/// The user a request is made on behalf of and the groups the user belongs
+/// to, resolved by the caller
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessContext {
+    pub user: Option<String>,
+    pub groups: Vec<String>,
+}
+
+impl AccessContext {
+    /// Whether content with the ACL can be read. Content without an ACL can
+    /// be read when allow_unlabeled is set.
+    pub fn permits(&self, acl: Option<&ContentAcl>, allow_unlabeled: bool) -> bool {
+        let Some(acl) = acl else {
+            return allow_unlabeled;
+        };
+        self.user
+            .as_ref()
+            .is_some_and(|user| acl.users.contains(user)) ||
+            self.groups.iter().any(|group| acl.groups.contains(group))
+    }
+
+    /// Whether the content or, for content extracted from another, its root
+    /// can be read. Content has its own ACL when it was set at ingestion,
+    /// otherwise the ACL of the root applies.
+    pub fn permits_content(
+        &self,
+        content: &ContentMetadata,
+        root: Option<&ContentMetadata>,
+        allow_unlabeled: bool,
+    ) -> bool {
+        let acl = content
+            .acl
+            .as_ref()
+            .or_else(|| root.and_then(|root| root.acl.as_ref()));
+        self.permits(acl, allow_unlabeled)
+    }
+}
+
+/// Why content was tombstoned, recorded with the content so the decision to
+/// restore it can be made before it is garbage collected. The detail is free
+/// text given by whoever tombstoned the content.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[schema(as=internal_api::TombstoneReason)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum TombstoneReason {
+    /// Content tombstoned before reasons were recorded
+    #[default]
+    Unknown,
+    UserRequest(String),
+    NamespaceDeletion(String),
+    RetentionPolicy(String),
+    /// A newer version of the content replaced it
+    Superseded(String),
+}
+
+impl TombstoneReason {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TombstoneReason::Unknown => "unknown",
+            TombstoneReason::UserRequest(_) => "user_request",
+            TombstoneReason::NamespaceDeletion(_) => "namespace_deletion",
+            TombstoneReason::RetentionPolicy(_) => "retention_policy",
+            TombstoneReason::Superseded(_) => "superseded",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            TombstoneReason::Unknown => "",
+            TombstoneReason::UserRequest(detail) |
+            TombstoneReason::NamespaceDeletion(detail) |
+            TombstoneReason::RetentionPolicy(detail) |
+            TombstoneReason::Superseded(detail) => detail,
+        }
+    }
+}
+
+impl Display for TombstoneReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.detail().is_empty() {
+            write!(f, "{}", self.kind())
+        } else {
+            write!(f, "{}: {}", self.kind(), self.detail())
+        }
+    }
+}
+
+impl From<TombstoneReason> for indexify_coordinator::TombstoneReason {
+    fn from(value: TombstoneReason) -> Self {
+        Self {
+            kind: value.kind().to_string(),
+            detail: value.detail().to_string(),
+        }
+    }
+}
+
+impl From<indexify_coordinator::TombstoneReason> for TombstoneReason {
+    fn from(value: indexify_coordinator::TombstoneReason) -> Self {
+        match value.kind.as_str() {
+            "user_request" => TombstoneReason::UserRequest(value.detail),
+            "namespace_deletion" => TombstoneReason::NamespaceDeletion(value.detail),
+            "retention_policy" => TombstoneReason::RetentionPolicy(value.detail),
+            "superseded" => TombstoneReason::Superseded(value.detail),
+            _ => TombstoneReason::Unknown,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub struct ContentMetadata {
     pub id: ContentMetadataId,
@@ -889,6 +1409,12 @@ pub struct ContentMetadata {
     pub extraction_policy_ids: HashMap<ExtractionPolicyId, u64>, /*  map of completion time for
                                                                   * each extraction policy id */
     pub extraction_graph_names: Vec<ExtractionGraphName>,
+    /// Users and groups allowed to read the content, set at ingestion
+    #[serde(default)]
+    pub acl: Option<ContentAcl>,
+    /// Set when the content is tombstoned
+    #[serde(default)]
+    pub tombstone_reason: Option<TombstoneReason>,
 }
 
 impl ContentMetadata {
@@ -900,18 +1426,337 @@ impl ContentMetadata {
     // children are stored with id as key (children always have version 1 and
     // are never overwritten). Overwritten or deleted roots keys are
     // formed from id and version.
-    pub fn id_key(&self) -> String {
+    pub fn id_key(&self, separator: &ContentVersionSeparator) -> String {
         if self.latest {
             self.id.id.clone()
         } else {
-            format!("{}::v{}", self.id.id, self.id.version)
+            separator.key(&self.id)
         }
     }
 
-    pub fn make_id_key(id: &str, version: Option<u64>) -> String {
+    pub fn make_id_key(
+        id: &str,
+        version: Option<u64>,
+        separator: &ContentVersionSeparator,
+    ) -> String {
         match version {
             None => id.to_string(),
-            Some(v) => format!("{}::v{}", id, v),
+            Some(v) => separator.key(&ContentMetadataId::new_with_version(id, v)),
+        }
+    }
+
+    /// Reason the content was tombstoned, Unknown for content tombstoned
+    /// before reasons were recorded and None for live content
+    pub fn reason_tombstoned(&self) -> Option<TombstoneReason> {
+        match &self.tombstone_reason {
+            Some(reason) => Some(reason.clone()),
+            None => self.tombstoned.then(TombstoneReason::default),
+        }
+    }
+
+    /// Time of the last change to the content, in seconds. Content has no
+    /// update time of its own, so this is when its version was created or
+    /// when an extraction policy last finished on it, whichever is later.
+    pub fn updated_at(&self) -> i64 {
+        self.extraction_policy_ids
+            .values()
+            .map(|completed_at| *completed_at as i64)
+            .fold(self.created_at, i64::max)
+    }
+}
+
+/// Selects content by the state of its lifecycle, a state left unset matches
+/// content in either state
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ContentQuery {
+    /// Content is finalized once none of its tasks are pending
+    pub finalized: Option<bool>,
+    pub tombstoned: Option<bool>,
+    /// Most content returned, all of it when 0
+    pub limit: usize,
+}
+
+/// Key a client uses to look up content it may have ingested before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentLookupKey {
+    Id(String),
+    Hash(String),
+}
+
+/// Result of looking up content by the id or hash a client knows it by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ContentExistence {
+    Unknown,
+    Exists {
+        content_id: String,
+        version: u64,
+        hash: String,
+    },
+    Tombstoned {
+        content_id: String,
+    },
+}
+
+impl From<ContentExistence> for indexify_coordinator::ContentExistsResult {
+    fn from(value: ContentExistence) -> Self {
+        match value {
+            ContentExistence::Unknown => Self {
+                status: "unknown".to_string(),
+                ..Default::default()
+            },
+            ContentExistence::Exists {
+                content_id,
+                version,
+                hash,
+            } => Self {
+                status: "exists".to_string(),
+                content_id,
+                version,
+                hash,
+            },
+            ContentExistence::Tombstoned { content_id } => Self {
+                status: "tombstoned".to_string(),
+                content_id,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl TryFrom<indexify_coordinator::ContentExistsResult> for ContentExistence {
+    type Error = anyhow::Error;
+
+    fn try_from(value: indexify_coordinator::ContentExistsResult) -> Result<Self> {
+        match value.status.as_str() {
+            "unknown" => Ok(ContentExistence::Unknown),
+            "exists" => Ok(ContentExistence::Exists {
+                content_id: value.content_id,
+                version: value.version,
+                hash: value.hash,
+            }),
+            "tombstoned" => Ok(ContentExistence::Tombstoned {
+                content_id: value.content_id,
+            }),
+            status => Err(anyhow!("unknown content existence status: {}", status)),
+        }
+    }
+}
+
+/// Named search configuration stored per namespace. Searches referencing the
+/// profile use its settings unless the request overrides them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct SearchProfile {
+    pub namespace: String,
+    pub name: String,
+    pub index: IndexName,
+    pub filters: Vec<String>,
+    pub k: Option<u64>,
+    pub min_score: Option<f32>,
+}
+
+impl SearchProfile {
+    pub fn key(&self) -> String {
+        Self::key_from(&self.namespace, &self.name)
+    }
+
+    pub fn key_from(namespace: &str, name: &str) -> String {
+        format!("{}::{}", namespace, name)
+    }
+}
+
+impl From<SearchProfile> for indexify_coordinator::SearchProfile {
+    fn from(value: SearchProfile) -> Self {
+        Self {
+            namespace: value.namespace,
+            name: value.name,
+            index: value.index,
+            filters: value.filters,
+            k: value.k.unwrap_or_default(),
+            min_score: value.min_score.unwrap_or_default(),
+            invalid_reason: "".to_string(),
+        }
+    }
+}
+
+impl From<indexify_coordinator::SearchProfile> for SearchProfile {
+    fn from(value: indexify_coordinator::SearchProfile) -> Self {
+        Self {
+            namespace: value.namespace,
+            name: value.name,
+            index: value.index,
+            filters: value.filters,
+            k: (value.k > 0).then_some(value.k),
+            min_score: (value.min_score != 0.0).then_some(value.min_score),
+        }
+    }
+}
+
+/// Whether a search result was relevant to the query
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackVerdict {
+    Positive,
+    Negative,
+}
+
+/// Feedback on a result of a search. The query is identified by its hash, the
+/// query hash of the search log entry when the search was logged. Feedback
+/// is kept when the content it refers to is deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct SearchFeedback {
+    pub id: String,
+    pub namespace: String,
+    pub query_hash: String,
+    pub content_id: String,
+    pub chunk_id: Option<String>,
+    pub verdict: FeedbackVerdict,
+    pub note: Option<String>,
+    pub created_at: u64,
+    pub actor: Option<String>,
+}
+
+impl SearchFeedback {
+    /// Feedback is keyed by namespace and creation time so it is listed in
+    /// the order it was given
+    pub fn key(&self) -> String {
+        format!(
+            "{}{}",
+            Self::key_prefix(&self.namespace, self.created_at),
+            self.id
+        )
+    }
+
+    pub fn key_prefix(namespace: &str, created_at: u64) -> String {
+        format!("{}::{:020}::", namespace, created_at)
+    }
+
+    pub fn namespace_prefix(namespace: &str) -> String {
+        format!("{}::", namespace)
+    }
+}
+
+impl From<SearchFeedback> for indexify_coordinator::SearchFeedback {
+    fn from(value: SearchFeedback) -> Self {
+        Self {
+            id: value.id,
+            namespace: value.namespace,
+            query_hash: value.query_hash,
+            content_id: value.content_id,
+            chunk_id: value.chunk_id.unwrap_or_default(),
+            positive: value.verdict == FeedbackVerdict::Positive,
+            note: value.note.unwrap_or_default(),
+            created_at: value.created_at,
+            actor: value.actor.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<indexify_coordinator::SearchFeedback> for SearchFeedback {
+    fn from(value: indexify_coordinator::SearchFeedback) -> Self {
+        Self {
+            id: value.id,
+            namespace: value.namespace,
+            query_hash: value.query_hash,
+            content_id: value.content_id,
+            chunk_id: (!value.chunk_id.is_empty()).then_some(value.chunk_id),
+            verdict: if value.positive {
+                FeedbackVerdict::Positive
+            } else {
+                FeedbackVerdict::Negative
+            },
+            note: (!value.note.is_empty()).then_some(value.note),
+            created_at: value.created_at,
+            actor: (!value.actor.is_empty()).then_some(value.actor),
+        }
+    }
+}
+
+/// Selects a page of the feedback of a namespace
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchFeedbackQuery {
+    pub namespace: String,
+    /// Only the feedback on this content when set
+    pub content_id: Option<String>,
+    /// Inclusive lower bound of the creation time
+    pub start_time: Option<u64>,
+    /// Exclusive upper bound of the creation time
+    pub end_time: Option<u64>,
+    /// Key of the last feedback of the previous page
+    pub cursor: Option<String>,
+    pub limit: usize,
+}
+
+/// Number of positive and negative feedback given on search results of a
+/// piece of content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+pub struct SearchFeedbackCounts {
+    pub positive: u64,
+    pub negative: u64,
+}
+
+/// A webhook receiving the events of the indexes of a namespace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Subscription {
+    pub id: String,
+    pub namespace: String,
+    pub url: String,
+    pub created_at: u64,
+    /// Sequence of the next delivery enqueued for the subscription
+    #[serde(default)]
+    pub next_sequence: u64,
+}
+
+impl From<Subscription> for indexify_coordinator::Subscription {
+    fn from(value: Subscription) -> Self {
+        Self {
+            id: value.id,
+            namespace: value.namespace,
+            url: value.url,
+            created_at: value.created_at,
+        }
+    }
+}
+
+/// An event queued for delivery to a subscription. The deliveries of a
+/// subscription are keyed by their sequence, so they are attempted in the
+/// order they were enqueued, also after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Delivery {
+    pub subscription_id: String,
+    /// Assigned when the delivery is enqueued
+    pub sequence: u64,
+    pub payload: String,
+    pub enqueued_at: u64,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+}
+
+impl Delivery {
+    pub fn key(&self) -> String {
+        Self::key_from(&self.subscription_id, self.sequence)
+    }
+
+    pub fn key_from(subscription_id: &str, sequence: u64) -> String {
+        //  Zero padded so keys sort by sequence
+        format!("{}::{:020}", subscription_id, sequence)
+    }
+
+    pub fn key_prefix(subscription_id: &str) -> String {
+        format!("{}::", subscription_id)
+    }
+}
+
+impl From<Delivery> for indexify_coordinator::Delivery {
+    fn from(value: Delivery) -> Self {
+        Self {
+            subscription_id: value.subscription_id,
+            sequence: value.sequence,
+            payload: value.payload,
+            enqueued_at: value.enqueued_at,
+            attempts: value.attempts,
+            last_error: value.last_error.unwrap_or_default(),
         }
     }
 }
@@ -935,6 +1780,8 @@ impl From<ContentMetadata> for indexify_coordinator::ContentMetadata {
             hash: value.hash,
             extraction_policy_ids: value.extraction_policy_ids,
             extraction_graph_names: value.extraction_graph_names,
+            tombstone_reason: value.reason_tombstoned().map(Into::into),
+            acl: value.acl.map(Into::into),
         }
     }
 }
@@ -967,10 +1814,12 @@ impl From<indexify_coordinator::ContentMetadata> for ContentMetadata {
             namespace: value.namespace,
             source: value.source.into(),
             size_bytes: value.size_bytes,
-            tombstoned: false,
+            tombstoned: value.tombstone_reason.is_some(),
             hash: value.hash,
             extraction_policy_ids: value.extraction_policy_ids,
             extraction_graph_names: value.extraction_graph_names,
+            acl: value.acl.map(Into::into),
+            tombstone_reason: value.tombstone_reason.map(Into::into),
         }
     }
 }
@@ -999,6 +1848,8 @@ impl Default for ContentMetadata {
             tombstoned: false,
             hash: "test_hash".to_string(),
             extraction_graph_names: vec![],
+            acl: None,
+            tombstone_reason: None,
         }
     }
 }
@@ -1009,6 +1860,10 @@ pub struct ExecutorMetadata {
     pub last_seen: u64,
     pub addr: String,
     pub extractors: Vec<ExtractorDescription>,
+    /// Resources advertised by the executor, matched against extractor
+    /// requirements during scheduling.
+    #[serde(default)]
+    pub capabilities: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1055,6 +1910,12 @@ pub type NamespaceName = String;
 pub struct Namespace {
     pub name: NamespaceName,
     pub extraction_graphs: Vec<ExtractionGraph>,
+    /// Inconsistencies found while reading the namespace, e.g. extraction
+    /// policies referenced by the reverse index which don't exist.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub retention: RetentionRules,
 }
 
 impl From<Namespace> for indexify_coordinator::Namespace {
@@ -1066,6 +1927,45 @@ impl From<Namespace> for indexify_coordinator::Namespace {
                 .into_iter()
                 .map(|g| g.into())
                 .collect(),
+            warnings: value.warnings,
+            retention: Some(value.retention.into()),
+        }
+    }
+}
+
+/// Rules expiring the root content of a namespace, along with its trees. Root
+/// content expires once it is older than the max age, or once it isn't among
+/// the newest max items roots of the namespace. Content never expires when
+/// no rule is set.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RetentionRules {
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    #[serde(default)]
+    pub max_items: Option<u64>,
+}
+
+impl RetentionRules {
+    pub fn is_empty(&self) -> bool {
+        self.max_age_secs.is_none() && self.max_items.is_none()
+    }
+}
+
+//  Rules which aren't set are 0 in the proto
+impl From<RetentionRules> for indexify_coordinator::RetentionRules {
+    fn from(value: RetentionRules) -> Self {
+        indexify_coordinator::RetentionRules {
+            max_age_secs: value.max_age_secs.unwrap_or_default(),
+            max_items: value.max_items.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<indexify_coordinator::RetentionRules> for RetentionRules {
+    fn from(value: indexify_coordinator::RetentionRules) -> Self {
+        RetentionRules {
+            max_age_secs: (value.max_age_secs > 0).then_some(value.max_age_secs),
+            max_items: (value.max_items > 0).then_some(value.max_items),
         }
     }
 }
@@ -1078,6 +1978,10 @@ pub enum ChangeType {
     ExecutorRemoved,
     ContentUpdated,
     TaskCompleted { root_content_id: ContentMetadataId },
+    ExtractorAvailabilityChanged { available: bool },
+    // object id is the extraction policy whose extractor output no longer
+    // matches its indexes, or matches them again
+    OutputCompatibilityChanged { compatible: bool },
 }
 
 impl fmt::Display for ChangeType {
@@ -1093,6 +1997,12 @@ impl fmt::Display for ChangeType {
             } => {
                 write!(f, "TaskCompleted(content_id: {})", content_id)
             }
+            ChangeType::ExtractorAvailabilityChanged { available } => {
+                write!(f, "ExtractorAvailabilityChanged(available: {})", available)
+            }
+            ChangeType::OutputCompatibilityChanged { compatible } => {
+                write!(f, "OutputCompatibilityChanged(compatible: {})", compatible)
+            }
         }
     }
 }
@@ -1253,6 +2163,9 @@ impl From<SchemaColumnType> for SchemaColumn {
     }
 }
 
+/// Table name of the schema every namespace is created with
+pub const DEFAULT_SCHEMA_NAME: &str = "default";
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct StructuredDataSchema {
     pub id: String,
@@ -1272,6 +2185,12 @@ impl StructuredDataSchema {
         }
     }
 
+    /// Returns the empty schema a namespace is created with when no schema
+    /// is given. Its id only depends on the namespace.
+    pub fn empty_for(namespace: &str) -> Self {
+        Self::new(DEFAULT_SCHEMA_NAME, namespace)
+    }
+
     pub fn merge(&mut self, other: HashMap<String, SchemaColumn>) -> Self {
         for (column_name, column) in other {
             self.columns.insert(column_name, column);
@@ -1323,6 +2242,129 @@ impl StructuredDataSchema {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_content_id() {
+        let id = ContentMetadataId::deterministic("namespace", "hash");
+        assert_eq!(id, ContentMetadataId::deterministic("namespace", "hash"));
+        assert_eq!(id.version, 1);
+        assert!(!id.id.is_empty());
+        assert_ne!(
+            id,
+            ContentMetadataId::deterministic("namespace", "other_hash")
+        );
+        assert_ne!(
+            id,
+            ContentMetadataId::deterministic("other_namespace", "hash")
+        );
+        //  The parts aren't simply concatenated
+        assert_ne!(
+            ContentMetadataId::deterministic("ab", "c"),
+            ContentMetadataId::deterministic("a", "bc")
+        );
+    }
+
+    #[test]
+    fn test_content_id_round_trip_with_separator() {
+        for separator in [DEFAULT_CONTENT_VERSION_SEPARATOR, "#ver#", "|"] {
+            let id = ContentMetadataId::new_with_version("abc", 12);
+            let key = id.to_string_with_separator(separator);
+            assert_eq!(key, format!("abc{}12", separator));
+            assert_eq!(
+                ContentMetadataId::parse_with_separator(&key, separator).unwrap(),
+                id
+            );
+        }
+
+        //  ids containing the separator still parse
+        let id = ContentMetadataId::new_with_version("a#ver#b", 3);
+        let key = id.to_string_with_separator("#ver#");
+        assert_eq!(
+            ContentMetadataId::parse_with_separator(&key, "#ver#").unwrap(),
+            id
+        );
+
+        assert!(ContentMetadataId::parse_with_separator("abc::v12", "#ver#").is_err());
+    }
+
+    #[test]
+    fn test_invalid_content_version_separator() {
+        assert!(ContentVersionSeparator::new("").is_err());
+        assert!(ContentVersionSeparator::new("::v1").is_err());
+        assert_eq!(
+            ContentVersionSeparator::new("#ver#")
+                .unwrap()
+                .key(&ContentMetadataId::new_with_version("abc", 12)),
+            "abc#ver#12"
+        );
+    }
+
+    #[test]
+    fn test_access_context_permits() {
+        let acl = ContentAcl {
+            users: vec!["alice".to_string()],
+            groups: vec!["finance".to_string()],
+        };
+        let user = |name: &str, groups: &[&str]| AccessContext {
+            user: Some(name.to_string()),
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+        };
+        assert!(user("alice", &[]).permits(Some(&acl), false));
+        assert!(user("bob", &["finance"]).permits(Some(&acl), false));
+        assert!(!user("bob", &["sales"]).permits(Some(&acl), true));
+        assert_eq!(
+            acl.principals(),
+            vec!["user:alice".to_string(), "group:finance".to_string()]
+        );
+
+        //  Unlabeled content is readable depending on the configuration
+        assert!(user("bob", &[]).permits(None, true));
+        assert!(!user("bob", &[]).permits(None, false));
+
+        //  Extracted content without its own ACL uses the ACL of the root
+        let root = ContentMetadata {
+            acl: Some(acl.clone()),
+            ..Default::default()
+        };
+        let child = ContentMetadata::default();
+        assert!(user("alice", &[]).permits_content(&child, Some(&root), false));
+        assert!(!user("bob", &[]).permits_content(&child, Some(&root), true));
+    }
+
+    #[test]
+    fn test_tombstone_reason() {
+        let reason = TombstoneReason::UserRequest("duplicate upload".to_string());
+        let json = serde_json::to_value(&reason).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "user_request", "detail": "duplicate upload"})
+        );
+        assert_eq!(
+            serde_json::from_value::<TombstoneReason>(json).unwrap(),
+            reason
+        );
+        assert_eq!(
+            TombstoneReason::from(indexify_coordinator::TombstoneReason::from(reason.clone())),
+            reason
+        );
+
+        //  Content tombstoned before reasons were recorded has no reason stored
+        let mut json = serde_json::to_value(ContentMetadata {
+            tombstoned: true,
+            ..Default::default()
+        })
+        .unwrap();
+        json.as_object_mut().unwrap().remove("tombstone_reason");
+        let content: ContentMetadata = serde_json::from_value(json).unwrap();
+        assert_eq!(content.tombstone_reason, None);
+        assert_eq!(content.reason_tombstoned(), Some(TombstoneReason::Unknown));
+        assert_eq!(ContentMetadata::default().reason_tombstoned(), None);
+    }
+}
+
 //#[cfg(test)]
 //mod test {
 //    use super::*;
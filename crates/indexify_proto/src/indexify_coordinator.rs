@@ -4,6 +4,8 @@
 pub struct GetContentMetadataRequest {
     #[prost(string, repeated, tag = "1")]
     pub content_list: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(bool, tag = "2")]
+    pub include_tombstoned: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -25,6 +27,128 @@ pub struct GetContentTreeMetadataResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RebuildContentTreeRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub content_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub index: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RebuildContentTreeResponse {
+    #[prost(uint64, tag = "1")]
+    pub task_count: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReprocessStateChangeRequest {
+    #[prost(uint64, tag = "1")]
+    pub state_change_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReprocessStateChangeResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DiscardStateChangeRequest {
+    #[prost(uint64, tag = "1")]
+    pub state_change_id: u64,
+    /// Why the change is discarded, logged by the coordinator
+    #[prost(string, tag = "2")]
+    pub note: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DiscardStateChangeResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HoldContentTasksRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub content_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// how long executor heartbeats leave the tasks of the content out, 0
+    /// releases them
+    #[prost(uint64, tag = "2")]
+    pub hold_ms: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HoldContentTasksResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContentTaskAssignmentsRequest {
+    #[prost(string, tag = "1")]
+    pub content_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContentTaskAssignment {
+    #[prost(message, optional, tag = "1")]
+    pub task: ::core::option::Option<Task>,
+    #[prost(string, tag = "2")]
+    pub executor_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub executor_addr: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetContentTaskAssignmentsResponse {
+    /// false until every extraction policy matching the content has a task
+    /// assigned to an executor
+    #[prost(bool, tag = "1")]
+    pub assigned: bool,
+    #[prost(message, repeated, tag = "2")]
+    pub assignments: ::prost::alloc::vec::Vec<ContentTaskAssignment>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContentExistsEntry {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContentExistsRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub entries: ::prost::alloc::vec::Vec<ContentExistsEntry>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContentExistsResult {
+    #[prost(string, tag = "1")]
+    pub status: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub content_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub version: u64,
+    #[prost(string, tag = "4")]
+    pub hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContentExistsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<ContentExistsResult>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetReferencedStorageUrlsRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub storage_urls: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetReferencedStorageUrlsResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub storage_urls: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateTaskRequest {
     #[prost(string, tag = "1")]
     pub executor_id: ::prost::alloc::string::String,
@@ -32,6 +156,16 @@ pub struct UpdateTaskRequest {
     pub task_id: ::prost::alloc::string::String,
     #[prost(enumeration = "TaskOutcome", tag = "3")]
     pub outcome: i32,
+    #[prost(string, repeated, tag = "4")]
+    pub output_content_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// version of the extraction policy the task ran with, 0 if unknown
+    #[prost(uint64, tag = "5")]
+    pub policy_version: u64,
+    /// resources used to run the task, 0 if unknown
+    #[prost(uint64, tag = "6")]
+    pub vectors_produced: u64,
+    #[prost(uint64, tag = "7")]
+    pub execution_time_ms: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -99,6 +233,84 @@ pub struct ListIndexesResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListNamespaceIndexTablesRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IndexTableNames {
+    #[prost(string, repeated, tag = "1")]
+    pub table_names: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListNamespaceIndexTablesResponse {
+    #[prost(map = "string, message", tag = "1")]
+    pub namespaces: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        IndexTableNames,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetNamespaceOverviewRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NamespaceIndexOverview {
+    #[prost(message, optional, tag = "1")]
+    pub index: ::core::option::Option<Index>,
+    #[prost(message, optional, tag = "2")]
+    pub policy: ::core::option::Option<ExtractionPolicy>,
+    #[prost(message, optional, tag = "3")]
+    pub extractor: ::core::option::Option<Extractor>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetNamespaceOverviewResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub indexes: ::prost::alloc::vec::Vec<NamespaceIndexOverview>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UsageStats {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub extractor: ::prost::alloc::string::String,
+    /// days since the unix epoch
+    #[prost(uint64, tag = "3")]
+    pub day: u64,
+    #[prost(uint64, tag = "4")]
+    pub tasks: u64,
+    #[prost(uint64, tag = "5")]
+    pub content_bytes: u64,
+    #[prost(uint64, tag = "6")]
+    pub chunks: u64,
+    #[prost(uint64, tag = "7")]
+    pub vectors: u64,
+    #[prost(uint64, tag = "8")]
+    pub execution_time_ms: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUsageStatsRequest {
+    /// all namespaces if empty
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub from_day: u64,
+    #[prost(uint64, tag = "3")]
+    pub to_day: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUsageStatsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub stats: ::prost::alloc::vec::Vec<UsageStats>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetIndexRequest {
     #[prost(string, tag = "1")]
     pub namespace: ::prost::alloc::string::String,
@@ -113,6 +325,162 @@ pub struct GetIndexResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchProfile {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub index: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "4")]
+    pub filters: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// 0 means the profile doesn't set k
+    #[prost(uint64, tag = "5")]
+    pub k: u64,
+    /// 0 means the profile doesn't set a minimum score
+    #[prost(float, tag = "6")]
+    pub min_score: f32,
+    /// set when the profile can't be used, e.g. its index no longer exists
+    #[prost(string, tag = "7")]
+    pub invalid_reason: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSearchProfileRequest {
+    #[prost(message, optional, tag = "1")]
+    pub profile: ::core::option::Option<SearchProfile>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSearchProfileResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSearchProfileRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSearchProfileResponse {
+    #[prost(message, optional, tag = "1")]
+    pub profile: ::core::option::Option<SearchProfile>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSearchProfilesRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSearchProfilesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub profiles: ::prost::alloc::vec::Vec<SearchProfile>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteSearchProfileRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteSearchProfileResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchFeedback {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub query_hash: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub content_id: ::prost::alloc::string::String,
+    /// empty when the feedback isn't about a chunk of the content
+    #[prost(string, tag = "5")]
+    pub chunk_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "6")]
+    pub positive: bool,
+    #[prost(string, tag = "7")]
+    pub note: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "8")]
+    pub created_at: u64,
+    #[prost(string, tag = "9")]
+    pub actor: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSearchFeedbackRequest {
+    /// the id and creation time are assigned by the coordinator
+    #[prost(message, optional, tag = "1")]
+    pub feedback: ::core::option::Option<SearchFeedback>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSearchFeedbackResponse {
+    #[prost(message, optional, tag = "1")]
+    pub feedback: ::core::option::Option<SearchFeedback>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSearchFeedbackRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    /// empty lists the feedback on all content
+    #[prost(string, tag = "2")]
+    pub content_id: ::prost::alloc::string::String,
+    /// inclusive, 0 means no lower bound
+    #[prost(uint64, tag = "3")]
+    pub start_time: u64,
+    /// exclusive, 0 means no upper bound
+    #[prost(uint64, tag = "4")]
+    pub end_time: u64,
+    /// next_cursor of the previous page, empty for the first page
+    #[prost(string, tag = "5")]
+    pub cursor: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub limit: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSearchFeedbackResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub feedback: ::prost::alloc::vec::Vec<SearchFeedback>,
+    /// empty when there are no more pages
+    #[prost(string, tag = "2")]
+    pub next_cursor: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSearchFeedbackCountsRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "2")]
+    pub content_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchFeedbackCounts {
+    #[prost(string, tag = "1")]
+    pub content_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub positive: u64,
+    #[prost(uint64, tag = "3")]
+    pub negative: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSearchFeedbackCountsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub counts: ::prost::alloc::vec::Vec<SearchFeedbackCounts>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateIndexesStateRequest {
     #[prost(message, repeated, tag = "1")]
     pub indexes: ::prost::alloc::vec::Vec<Index>,
@@ -137,6 +505,8 @@ pub struct Index {
     pub extractor: ::prost::alloc::string::String,
     #[prost(string, tag = "7")]
     pub graph_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub attribute_schema: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -179,6 +549,11 @@ pub struct RegisterExecutorRequest {
     pub addr: ::prost::alloc::string::String,
     #[prost(message, repeated, tag = "3")]
     pub extractors: ::prost::alloc::vec::Vec<Extractor>,
+    #[prost(map = "string, string", tag = "4")]
+    pub capabilities: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -228,6 +603,10 @@ pub struct GcTaskAcknowledgement {
     pub completed: bool,
     #[prost(string, tag = "3")]
     pub ingestion_server_id: ::prost::alloc::string::String,
+    /// Output tables whose vectors were removed, reported for tasks which
+    /// aren't completed yet so they are skipped when the task is resumed
+    #[prost(string, repeated, tag = "4")]
+    pub completed_output_tables: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -244,6 +623,10 @@ pub struct GcTask {
     pub blob_store_path: ::prost::alloc::string::String,
     #[prost(enumeration = "GcTaskType", tag = "7")]
     pub task_type: i32,
+    #[prost(message, optional, tag = "8")]
+    pub tombstone_reason: ::core::option::Option<TombstoneReason>,
+    #[prost(string, repeated, tag = "9")]
+    pub completed_output_tables: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -252,6 +635,9 @@ pub struct HeartbeatRequest {
     pub executor_id: ::prost::alloc::string::String,
     #[prost(int64, tag = "2")]
     pub pending_tasks: i64,
+    /// time of the executor's clock, informational only, 0 if not reported
+    #[prost(uint64, tag = "3")]
+    pub ts_secs: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -289,6 +675,16 @@ pub struct Task {
     /// list of all tables that the content may belong to
     #[prost(string, repeated, tag = "10")]
     pub index_tables: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// version of the extraction policy the task was created from
+    #[prost(uint64, tag = "11")]
+    pub extraction_policy_version: u64,
+    /// signed url the content can be downloaded from directly, empty when url
+    /// signing is disabled or the content isn't stored in blob storage
+    #[prost(string, tag = "12")]
+    pub content_url: ::prost::alloc::string::String,
+    /// unix timestamp in seconds after which content_url is rejected
+    #[prost(uint64, tag = "13")]
+    pub content_url_expires_at: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -320,6 +716,11 @@ pub struct Extractor {
     >,
     #[prost(string, repeated, tag = "6")]
     pub input_mime_types: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "7")]
+    pub requirements: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -335,30 +736,182 @@ pub struct GetNamespaceResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListContentRequest {
+pub struct CloneNamespaceRequest {
     #[prost(string, tag = "1")]
-    pub namespace: ::prost::alloc::string::String,
-    #[prost(string, tag = "2")]
     pub source: ::prost::alloc::string::String,
-    #[prost(string, tag = "3")]
-    pub parent_id: ::prost::alloc::string::String,
-    #[prost(map = "string, string", tag = "4")]
-    pub labels_eq: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListContentResponse {
+pub struct CloneNamespaceResponse {
     #[prost(message, repeated, tag = "1")]
-    pub content_list: ::prost::alloc::vec::Vec<ContentMetadata>,
+    pub indexes: ::prost::alloc::vec::Vec<Index>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetExtractionPolicyRequest {
+pub struct SampleNamespaceContentRequest {
     #[prost(string, tag = "1")]
-    pub extraction_policy_id: ::prost::alloc::string::String,
+    pub source: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub sample_size: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SampleNamespaceContentResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub content_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetNamespaceAliasRequest {
+    #[prost(string, tag = "1")]
+    pub alias: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetNamespaceAliasResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResolveNamespaceRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResolveNamespaceResponse {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Subscription {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub created_at: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Delivery {
+    #[prost(string, tag = "1")]
+    pub subscription_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub sequence: u64,
+    #[prost(string, tag = "3")]
+    pub payload: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "4")]
+    pub enqueued_at: u64,
+    #[prost(uint32, tag = "5")]
+    pub attempts: u32,
+    #[prost(string, tag = "6")]
+    pub last_error: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSubscriptionRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub url: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateSubscriptionResponse {
+    #[prost(message, optional, tag = "1")]
+    pub subscription: ::core::option::Option<Subscription>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSubscriptionsRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSubscriptionsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub subscriptions: ::prost::alloc::vec::Vec<Subscription>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteSubscriptionRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub subscription_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteSubscriptionResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListDeadLettersRequest {
+    #[prost(string, tag = "1")]
+    pub subscription_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListDeadLettersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub deliveries: ::prost::alloc::vec::Vec<Delivery>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RequeueDeadLettersRequest {
+    #[prost(string, tag = "1")]
+    pub subscription_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RequeueDeadLettersResponse {
+    #[prost(uint64, tag = "1")]
+    pub requeued: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnqueueIndexEventsRequest {
+    /// JSON encoded index events, in the order they were published
+    #[prost(string, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnqueueIndexEventsResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListContentRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub parent_id: ::prost::alloc::string::String,
+    #[prost(map = "string, string", tag = "4")]
+    pub labels_eq: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListContentResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub content_list: ::prost::alloc::vec::Vec<ContentMetadata>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetExtractionPolicyRequest {
+    #[prost(string, tag = "1")]
+    pub extraction_policy_id: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -368,6 +921,20 @@ pub struct GetExtractionPolicyResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateExtractionPolicyParamsRequest {
+    #[prost(string, tag = "1")]
+    pub extraction_policy_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub input_params: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateExtractionPolicyParamsResponse {
+    #[prost(message, optional, tag = "1")]
+    pub policy: ::core::option::Option<ExtractionPolicy>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListExtractionPoliciesRequest {
     #[prost(string, tag = "1")]
     pub namespace: ::prost::alloc::string::String,
@@ -438,6 +1005,14 @@ pub struct ExtractionPolicy {
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    #[prost(string, tag = "9")]
+    pub status: ::prost::alloc::string::String,
+    #[prost(string, tag = "10")]
+    pub index_attributes: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "11")]
+    pub version: u64,
+    #[prost(string, tag = "12")]
+    pub version_strategy: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -459,6 +1034,10 @@ pub struct ExtractionPolicyRequest {
     pub content_source: ::prost::alloc::string::String,
     #[prost(int64, tag = "7")]
     pub created_at: i64,
+    #[prost(string, tag = "8")]
+    pub index_attributes: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub version_strategy: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -546,6 +1125,28 @@ pub struct ContentMetadata {
     pub root_content_id: ::prost::alloc::string::String,
     #[prost(string, repeated, tag = "14")]
     pub extraction_graph_names: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "15")]
+    pub acl: ::core::option::Option<ContentAcl>,
+    #[prost(message, optional, tag = "16")]
+    pub tombstone_reason: ::core::option::Option<TombstoneReason>,
+}
+#[derive(serde::Deserialize, serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContentAcl {
+    #[prost(string, repeated, tag = "1")]
+    pub users: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "2")]
+    pub groups: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(serde::Deserialize, serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TombstoneReason {
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub detail: ::prost::alloc::string::String,
 }
 #[derive(serde::Deserialize, serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -553,12 +1154,54 @@ pub struct ContentMetadata {
 pub struct CreateContentRequest {
     #[prost(message, optional, tag = "2")]
     pub content: ::core::option::Option<ContentMetadata>,
+    /// requests with the same key in the namespace create the content once,
+    /// replays return the content created by the first request
+    #[prost(string, tag = "3")]
+    pub idempotency_key: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateContentResponse {
     #[prost(enumeration = "CreateContentStatus", tag = "2")]
     pub status: i32,
+    /// id of the content created by the first request with the idempotency key
+    #[prost(string, tag = "3")]
+    pub content_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetIngestionKeyRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub key: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RefreshContentUrlRequest {
+    #[prost(string, tag = "1")]
+    pub executor_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub task_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RefreshContentUrlResponse {
+    #[prost(string, tag = "1")]
+    pub content_url: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub content_url_expires_at: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetIngestionKeyResponse {
+    /// false if the key is unknown or expired
+    #[prost(bool, tag = "1")]
+    pub found: bool,
+    #[prost(string, tag = "2")]
+    pub content_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "CreateContentStatus", tag = "3")]
+    pub status: i32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -567,6 +1210,8 @@ pub struct TombstoneContentRequest {
     pub namespace: ::prost::alloc::string::String,
     #[prost(string, repeated, tag = "2")]
     pub content_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -578,9 +1223,32 @@ pub struct Namespace {
     pub name: ::prost::alloc::string::String,
     #[prost(message, repeated, tag = "2")]
     pub extraction_graphs: ::prost::alloc::vec::Vec<ExtractionGraph>,
+    #[prost(string, repeated, tag = "3")]
+    pub warnings: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "4")]
+    pub retention: ::core::option::Option<RetentionRules>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetentionRules {
+    #[prost(uint64, tag = "1")]
+    pub max_age_secs: u64,
+    #[prost(uint64, tag = "2")]
+    pub max_items: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetNamespaceRetentionRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub retention: ::core::option::Option<RetentionRules>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetNamespaceRetentionResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetSchemaRequest {
     #[prost(string, tag = "1")]
     pub namespace: ::prost::alloc::string::String,
@@ -777,6 +1445,31 @@ pub struct UpdateLabelsRequest {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateLabelsResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerInfoRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerInfoResponse {
+    #[prost(string, tag = "1")]
+    pub server_version: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "2")]
+    pub api_versions: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Capabilities of the server which clients can check before using them
+    #[prost(string, repeated, tag = "3")]
+    pub features: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateExecutorExtractorRequest {
+    #[prost(string, tag = "1")]
+    pub executor_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub extractor: ::core::option::Option<Extractor>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateExecutorExtractorResponse {}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum TaskOutcome {
@@ -1063,11 +1756,11 @@ pub mod coordinator_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_content(
+        pub async fn get_search_feedback_counts(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListContentRequest>,
+            request: impl tonic::IntoRequest<super::GetSearchFeedbackCountsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListContentResponse>,
+            tonic::Response<super::GetSearchFeedbackCountsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1081,23 +1774,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListContent",
+                "/indexify_coordinator.CoordinatorService/GetSearchFeedbackCounts",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListContent",
+                        "GetSearchFeedbackCounts",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn create_extraction_graph(
+        pub async fn list_search_feedback(
             &mut self,
-            request: impl tonic::IntoRequest<super::CreateExtractionGraphRequest>,
+            request: impl tonic::IntoRequest<super::ListSearchFeedbackRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::CreateExtractionGraphResponse>,
+            tonic::Response<super::ListSearchFeedbackResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1111,23 +1804,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/CreateExtractionGraph",
+                "/indexify_coordinator.CoordinatorService/ListSearchFeedback",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "CreateExtractionGraph",
+                        "ListSearchFeedback",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_extraction_policy(
+        pub async fn create_search_feedback(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetExtractionPolicyRequest>,
+            request: impl tonic::IntoRequest<super::CreateSearchFeedbackRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetExtractionPolicyResponse>,
+            tonic::Response<super::CreateSearchFeedbackResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1141,23 +1834,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetExtractionPolicy",
+                "/indexify_coordinator.CoordinatorService/CreateSearchFeedback",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetExtractionPolicy",
+                        "CreateSearchFeedback",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_extraction_policies(
+        pub async fn set_namespace_retention(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListExtractionPoliciesRequest>,
+            request: impl tonic::IntoRequest<super::SetNamespaceRetentionRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListExtractionPoliciesResponse>,
+            tonic::Response<super::SetNamespaceRetentionResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1171,23 +1864,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListExtractionPolicies",
+                "/indexify_coordinator.CoordinatorService/SetNamespaceRetention",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListExtractionPolicies",
+                        "SetNamespaceRetention",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn create_ns(
+        pub async fn list_namespace_index_tables(
             &mut self,
-            request: impl tonic::IntoRequest<super::CreateNamespaceRequest>,
+            request: impl tonic::IntoRequest<super::ListNamespaceIndexTablesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::CreateNamespaceResponse>,
+            tonic::Response<super::ListNamespaceIndexTablesResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1201,23 +1894,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/CreateNS",
+                "/indexify_coordinator.CoordinatorService/ListNamespaceIndexTables",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "CreateNS",
+                        "ListNamespaceIndexTables",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_ns(
+        pub async fn enqueue_index_events(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListNamespaceRequest>,
+            request: impl tonic::IntoRequest<super::EnqueueIndexEventsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListNamespaceResponse>,
+            tonic::Response<super::EnqueueIndexEventsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1231,20 +1924,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListNS",
+                "/indexify_coordinator.CoordinatorService/EnqueueIndexEvents",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
-                    GrpcMethod::new("indexify_coordinator.CoordinatorService", "ListNS"),
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "EnqueueIndexEvents",
+                    ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_ns(
+        pub async fn requeue_dead_letters(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetNamespaceRequest>,
+            request: impl tonic::IntoRequest<super::RequeueDeadLettersRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetNamespaceResponse>,
+            tonic::Response<super::RequeueDeadLettersResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1258,20 +1954,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetNS",
+                "/indexify_coordinator.CoordinatorService/RequeueDeadLetters",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
-                    GrpcMethod::new("indexify_coordinator.CoordinatorService", "GetNS"),
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "RequeueDeadLetters",
+                    ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_extractors(
+        pub async fn list_dead_letters(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListExtractorsRequest>,
+            request: impl tonic::IntoRequest<super::ListDeadLettersRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListExtractorsResponse>,
+            tonic::Response<super::ListDeadLettersResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1285,23 +1984,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListExtractors",
+                "/indexify_coordinator.CoordinatorService/ListDeadLetters",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListExtractors",
+                        "ListDeadLetters",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn register_executor(
+        pub async fn delete_subscription(
             &mut self,
-            request: impl tonic::IntoRequest<super::RegisterExecutorRequest>,
+            request: impl tonic::IntoRequest<super::DeleteSubscriptionRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RegisterExecutorResponse>,
+            tonic::Response<super::DeleteSubscriptionResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1315,23 +2014,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/RegisterExecutor",
+                "/indexify_coordinator.CoordinatorService/DeleteSubscription",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "RegisterExecutor",
+                        "DeleteSubscription",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn register_ingestion_server(
+        pub async fn list_subscriptions(
             &mut self,
-            request: impl tonic::IntoRequest<super::RegisterIngestionServerRequest>,
+            request: impl tonic::IntoRequest<super::ListSubscriptionsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RegisterIngestionServerResponse>,
+            tonic::Response<super::ListSubscriptionsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1345,23 +2044,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/RegisterIngestionServer",
+                "/indexify_coordinator.CoordinatorService/ListSubscriptions",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "RegisterIngestionServer",
+                        "ListSubscriptions",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn remove_ingestion_server(
+        pub async fn create_subscription(
             &mut self,
-            request: impl tonic::IntoRequest<super::RemoveIngestionServerRequest>,
+            request: impl tonic::IntoRequest<super::CreateSubscriptionRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RemoveIngestionServerResponse>,
+            tonic::Response<super::CreateSubscriptionResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1375,23 +2074,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/RemoveIngestionServer",
+                "/indexify_coordinator.CoordinatorService/CreateSubscription",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "RemoveIngestionServer",
+                        "CreateSubscription",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn create_gc_tasks(
+        pub async fn sample_namespace_content(
             &mut self,
-            request: impl tonic::IntoRequest<super::CreateGcTasksRequest>,
+            request: impl tonic::IntoRequest<super::SampleNamespaceContentRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::CreateGcTasksResponse>,
+            tonic::Response<super::SampleNamespaceContentResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1405,25 +2104,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/CreateGCTasks",
+                "/indexify_coordinator.CoordinatorService/SampleNamespaceContent",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "CreateGCTasks",
+                        "SampleNamespaceContent",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn gc_tasks_stream(
+        pub async fn resolve_namespace(
             &mut self,
-            request: impl tonic::IntoStreamingRequest<
-                Message = super::GcTaskAcknowledgement,
-            >,
+            request: impl tonic::IntoRequest<super::ResolveNamespaceRequest>,
         ) -> std::result::Result<
-            tonic::Response<tonic::codec::Streaming<super::CoordinatorCommand>>,
+            tonic::Response<super::ResolveNamespaceResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1437,23 +2134,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GCTasksStream",
+                "/indexify_coordinator.CoordinatorService/ResolveNamespace",
             );
-            let mut req = request.into_streaming_request();
+            let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GCTasksStream",
+                        "ResolveNamespace",
                     ),
                 );
-            self.inner.streaming(req, path, codec).await
+            self.inner.unary(req, path, codec).await
         }
-        pub async fn heartbeat(
+        pub async fn set_namespace_alias(
             &mut self,
-            request: impl tonic::IntoStreamingRequest<Message = super::HeartbeatRequest>,
+            request: impl tonic::IntoRequest<super::SetNamespaceAliasRequest>,
         ) -> std::result::Result<
-            tonic::Response<tonic::codec::Streaming<super::HeartbeatResponse>>,
+            tonic::Response<super::SetNamespaceAliasResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1467,23 +2164,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/Heartbeat",
+                "/indexify_coordinator.CoordinatorService/SetNamespaceAlias",
             );
-            let mut req = request.into_streaming_request();
+            let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "Heartbeat",
+                        "SetNamespaceAlias",
                     ),
                 );
-            self.inner.streaming(req, path, codec).await
+            self.inner.unary(req, path, codec).await
         }
-        pub async fn list_indexes(
+        pub async fn clone_namespace(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListIndexesRequest>,
+            request: impl tonic::IntoRequest<super::CloneNamespaceRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListIndexesResponse>,
+            tonic::Response<super::CloneNamespaceResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1497,23 +2194,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListIndexes",
+                "/indexify_coordinator.CoordinatorService/CloneNamespace",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListIndexes",
+                        "CloneNamespace",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_index(
+        pub async fn refresh_content_url(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetIndexRequest>,
+            request: impl tonic::IntoRequest<super::RefreshContentUrlRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetIndexResponse>,
+            tonic::Response<super::RefreshContentUrlResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1527,23 +2224,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetIndex",
+                "/indexify_coordinator.CoordinatorService/RefreshContentUrl",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetIndex",
+                        "RefreshContentUrl",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_indexes_state(
+        pub async fn get_ingestion_key(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateIndexesStateRequest>,
+            request: impl tonic::IntoRequest<super::GetIngestionKeyRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::UpdateIndexesStateResponse>,
+            tonic::Response<super::GetIngestionKeyResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1557,23 +2254,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/UpdateIndexesState",
+                "/indexify_coordinator.CoordinatorService/GetIngestionKey",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "UpdateIndexesState",
+                        "GetIngestionKey",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_extractor_coordinates(
+        pub async fn get_usage_stats(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetExtractorCoordinatesRequest>,
+            request: impl tonic::IntoRequest<super::GetUsageStatsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetExtractorCoordinatesResponse>,
+            tonic::Response<super::GetUsageStatsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1587,23 +2284,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetExtractorCoordinates",
+                "/indexify_coordinator.CoordinatorService/GetUsageStats",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetExtractorCoordinates",
+                        "GetUsageStats",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_task(
+        pub async fn get_namespace_overview(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateTaskRequest>,
+            request: impl tonic::IntoRequest<super::GetNamespaceOverviewRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::UpdateTaskResponse>,
+            tonic::Response<super::GetNamespaceOverviewResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1617,23 +2314,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/UpdateTask",
+                "/indexify_coordinator.CoordinatorService/GetNamespaceOverview",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "UpdateTask",
+                        "GetNamespaceOverview",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_state_changes(
+        pub async fn update_extraction_policy_params(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListStateChangesRequest>,
+            request: impl tonic::IntoRequest<super::UpdateExtractionPolicyParamsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListStateChangesResponse>,
+            tonic::Response<super::UpdateExtractionPolicyParamsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1647,23 +2344,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListStateChanges",
+                "/indexify_coordinator.CoordinatorService/UpdateExtractionPolicyParams",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListStateChanges",
+                        "UpdateExtractionPolicyParams",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_tasks(
+        pub async fn rebuild_content_tree(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListTasksRequest>,
+            request: impl tonic::IntoRequest<super::RebuildContentTreeRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListTasksResponse>,
+            tonic::Response<super::RebuildContentTreeResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1677,23 +2374,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListTasks",
+                "/indexify_coordinator.CoordinatorService/RebuildContentTree",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListTasks",
+                        "RebuildContentTree",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_schema(
+        pub async fn reprocess_state_change(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetSchemaRequest>,
+            request: impl tonic::IntoRequest<super::ReprocessStateChangeRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetSchemaResponse>,
+            tonic::Response<super::ReprocessStateChangeResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1707,23 +2404,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetSchema",
+                "/indexify_coordinator.CoordinatorService/ReprocessStateChange",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetSchema",
+                        "ReprocessStateChange",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_schemas(
+        pub async fn discard_state_change(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetAllSchemaRequest>,
+            request: impl tonic::IntoRequest<super::DiscardStateChangeRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetAllSchemaResponse>,
+            tonic::Response<super::DiscardStateChangeResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1737,23 +2434,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListSchemas",
+                "/indexify_coordinator.CoordinatorService/DiscardStateChange",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListSchemas",
+                        "DiscardStateChange",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_raft_metrics_snapshot(
+        pub async fn hold_content_tasks(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetRaftMetricsSnapshotRequest>,
+            request: impl tonic::IntoRequest<super::HoldContentTasksRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RaftMetricsSnapshotResponse>,
+            tonic::Response<super::HoldContentTasksResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1767,23 +2464,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetRaftMetricsSnapshot",
+                "/indexify_coordinator.CoordinatorService/HoldContentTasks",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetRaftMetricsSnapshot",
+                        "HoldContentTasks",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_all_task_assignments(
+        pub async fn get_content_task_assignments(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetAllTaskAssignmentRequest>,
+            request: impl tonic::IntoRequest<super::GetContentTaskAssignmentsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::TaskAssignments>,
+            tonic::Response<super::GetContentTaskAssignmentsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1797,23 +2494,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetAllTaskAssignments",
+                "/indexify_coordinator.CoordinatorService/GetContentTaskAssignments",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetAllTaskAssignments",
+                        "GetContentTaskAssignments",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_task(
+        pub async fn update_executor_extractor(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetTaskRequest>,
+            request: impl tonic::IntoRequest<super::UpdateExecutorExtractorRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetTaskResponse>,
+            tonic::Response<super::UpdateExecutorExtractorResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1827,20 +2524,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetTask",
+                "/indexify_coordinator.CoordinatorService/UpdateExecutorExtractor",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
-                    GrpcMethod::new("indexify_coordinator.CoordinatorService", "GetTask"),
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "UpdateExecutorExtractor",
+                    ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_ingestion_info(
+        pub async fn get_server_info(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetIngestionInfoRequest>,
+            request: impl tonic::IntoRequest<super::GetServerInfoRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetIngestionInfoResponse>,
+            tonic::Response<super::GetServerInfoResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1854,23 +2554,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/GetIngestionInfo",
+                "/indexify_coordinator.CoordinatorService/GetServerInfo",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "GetIngestionInfo",
+                        "GetServerInfo",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn wait_content_extraction(
+        pub async fn get_referenced_storage_urls(
             &mut self,
-            request: impl tonic::IntoRequest<super::WaitContentExtractionRequest>,
+            request: impl tonic::IntoRequest<super::GetReferencedStorageUrlsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::WaitContentExtractionResponse>,
+            tonic::Response<super::GetReferencedStorageUrlsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1884,23 +2584,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/WaitContentExtraction",
+                "/indexify_coordinator.CoordinatorService/GetReferencedStorageUrls",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "WaitContentExtraction",
+                        "GetReferencedStorageUrls",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_active_contents(
+        pub async fn delete_search_profile(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListActiveContentsRequest>,
+            request: impl tonic::IntoRequest<super::DeleteSearchProfileRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListActiveContentsResponse>,
+            tonic::Response<super::DeleteSearchProfileResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1914,23 +2614,23 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/ListActiveContents",
+                "/indexify_coordinator.CoordinatorService/DeleteSearchProfile",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "ListActiveContents",
+                        "DeleteSearchProfile",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        pub async fn update_labels(
+        pub async fn list_search_profiles(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateLabelsRequest>,
+            request: impl tonic::IntoRequest<super::ListSearchProfilesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::UpdateLabelsResponse>,
+            tonic::Response<super::ListSearchProfilesResponse>,
             tonic::Status,
         > {
             self.inner
@@ -1944,367 +2644,2981 @@ pub mod coordinator_service_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/indexify_coordinator.CoordinatorService/UpdateLabels",
+                "/indexify_coordinator.CoordinatorService/ListSearchProfiles",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "indexify_coordinator.CoordinatorService",
-                        "UpdateLabels",
+                        "ListSearchProfiles",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod coordinator_service_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with CoordinatorServiceServer.
-    #[async_trait]
-    pub trait CoordinatorService: Send + Sync + 'static {
-        async fn create_content(
-            &self,
-            request: tonic::Request<super::CreateContentRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::CreateContentResponse>,
-            tonic::Status,
-        >;
-        async fn tombstone_content(
-            &self,
-            request: tonic::Request<super::TombstoneContentRequest>,
+        pub async fn get_search_profile(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSearchProfileRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::TombstoneContentResponse>,
+            tonic::Response<super::GetSearchProfileResponse>,
             tonic::Status,
-        >;
-        async fn get_content_metadata(
-            &self,
-            request: tonic::Request<super::GetContentMetadataRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetSearchProfile",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetSearchProfile",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_search_profile(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateSearchProfileRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetContentMetadataResponse>,
+            tonic::Response<super::CreateSearchProfileResponse>,
             tonic::Status,
-        >;
-        async fn get_content_tree_metadata(
-            &self,
-            request: tonic::Request<super::GetContentTreeMetadataRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/CreateSearchProfile",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "CreateSearchProfile",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn content_exists(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ContentExistsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetContentTreeMetadataResponse>,
+            tonic::Response<super::ContentExistsResponse>,
             tonic::Status,
-        >;
-        async fn list_content(
-            &self,
-            request: tonic::Request<super::ListContentRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ContentExists",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ContentExists",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_content(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListContentRequest>,
         ) -> std::result::Result<
             tonic::Response<super::ListContentResponse>,
             tonic::Status,
-        >;
-        async fn create_extraction_graph(
-            &self,
-            request: tonic::Request<super::CreateExtractionGraphRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListContent",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListContent",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_extraction_graph(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateExtractionGraphRequest>,
         ) -> std::result::Result<
             tonic::Response<super::CreateExtractionGraphResponse>,
             tonic::Status,
-        >;
-        async fn get_extraction_policy(
-            &self,
-            request: tonic::Request<super::GetExtractionPolicyRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::GetExtractionPolicyResponse>,
-            tonic::Status,
-        >;
-        async fn list_extraction_policies(
-            &self,
-            request: tonic::Request<super::ListExtractionPoliciesRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/CreateExtractionGraph",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "CreateExtractionGraph",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_extraction_policy(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetExtractionPolicyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetExtractionPolicyResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetExtractionPolicy",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetExtractionPolicy",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_extraction_policies(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListExtractionPoliciesRequest>,
         ) -> std::result::Result<
             tonic::Response<super::ListExtractionPoliciesResponse>,
             tonic::Status,
-        >;
-        async fn create_ns(
-            &self,
-            request: tonic::Request<super::CreateNamespaceRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListExtractionPolicies",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListExtractionPolicies",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_ns(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateNamespaceRequest>,
         ) -> std::result::Result<
             tonic::Response<super::CreateNamespaceResponse>,
             tonic::Status,
-        >;
-        async fn list_ns(
-            &self,
-            request: tonic::Request<super::ListNamespaceRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/CreateNS",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "CreateNS",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_ns(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListNamespaceRequest>,
         ) -> std::result::Result<
             tonic::Response<super::ListNamespaceResponse>,
             tonic::Status,
-        >;
-        async fn get_ns(
-            &self,
-            request: tonic::Request<super::GetNamespaceRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListNS",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("indexify_coordinator.CoordinatorService", "ListNS"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_ns(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetNamespaceRequest>,
         ) -> std::result::Result<
             tonic::Response<super::GetNamespaceResponse>,
             tonic::Status,
-        >;
-        async fn list_extractors(
-            &self,
-            request: tonic::Request<super::ListExtractorsRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetNS",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("indexify_coordinator.CoordinatorService", "GetNS"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_extractors(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListExtractorsRequest>,
         ) -> std::result::Result<
             tonic::Response<super::ListExtractorsResponse>,
             tonic::Status,
-        >;
-        async fn register_executor(
-            &self,
-            request: tonic::Request<super::RegisterExecutorRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListExtractors",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListExtractors",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn register_executor(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RegisterExecutorRequest>,
         ) -> std::result::Result<
             tonic::Response<super::RegisterExecutorResponse>,
             tonic::Status,
-        >;
-        async fn register_ingestion_server(
-            &self,
-            request: tonic::Request<super::RegisterIngestionServerRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/RegisterExecutor",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "RegisterExecutor",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn register_ingestion_server(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RegisterIngestionServerRequest>,
         ) -> std::result::Result<
             tonic::Response<super::RegisterIngestionServerResponse>,
             tonic::Status,
-        >;
-        async fn remove_ingestion_server(
-            &self,
-            request: tonic::Request<super::RemoveIngestionServerRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::RemoveIngestionServerResponse>,
-            tonic::Status,
-        >;
-        async fn create_gc_tasks(
-            &self,
-            request: tonic::Request<super::CreateGcTasksRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::CreateGcTasksResponse>,
-            tonic::Status,
-        >;
-        /// Server streaming response type for the GCTasksStream method.
-        type GCTasksStreamStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::CoordinatorCommand, tonic::Status>,
-            >
-            + Send
-            + 'static;
-        async fn gc_tasks_stream(
-            &self,
-            request: tonic::Request<tonic::Streaming<super::GcTaskAcknowledgement>>,
-        ) -> std::result::Result<
-            tonic::Response<Self::GCTasksStreamStream>,
-            tonic::Status,
-        >;
-        /// Server streaming response type for the Heartbeat method.
-        type HeartbeatStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::HeartbeatResponse, tonic::Status>,
-            >
-            + Send
-            + 'static;
-        async fn heartbeat(
-            &self,
-            request: tonic::Request<tonic::Streaming<super::HeartbeatRequest>>,
-        ) -> std::result::Result<tonic::Response<Self::HeartbeatStream>, tonic::Status>;
-        async fn list_indexes(
-            &self,
-            request: tonic::Request<super::ListIndexesRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/RegisterIngestionServer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "RegisterIngestionServer",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn remove_ingestion_server(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RemoveIngestionServerRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListIndexesResponse>,
+            tonic::Response<super::RemoveIngestionServerResponse>,
             tonic::Status,
-        >;
-        async fn get_index(
-            &self,
-            request: tonic::Request<super::GetIndexRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/RemoveIngestionServer",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "RemoveIngestionServer",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_gc_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateGcTasksRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetIndexResponse>,
+            tonic::Response<super::CreateGcTasksResponse>,
             tonic::Status,
-        >;
-        async fn update_indexes_state(
-            &self,
-            request: tonic::Request<super::UpdateIndexesStateRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/CreateGCTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "CreateGCTasks",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn gc_tasks_stream(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<
+                Message = super::GcTaskAcknowledgement,
+            >,
         ) -> std::result::Result<
-            tonic::Response<super::UpdateIndexesStateResponse>,
+            tonic::Response<tonic::codec::Streaming<super::CoordinatorCommand>>,
             tonic::Status,
-        >;
-        async fn get_extractor_coordinates(
-            &self,
-            request: tonic::Request<super::GetExtractorCoordinatesRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GCTasksStream",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GCTasksStream",
+                    ),
+                );
+            self.inner.streaming(req, path, codec).await
+        }
+        pub async fn heartbeat(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::HeartbeatRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetExtractorCoordinatesResponse>,
+            tonic::Response<tonic::codec::Streaming<super::HeartbeatResponse>>,
             tonic::Status,
-        >;
-        async fn update_task(
-            &self,
-            request: tonic::Request<super::UpdateTaskRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/Heartbeat",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "Heartbeat",
+                    ),
+                );
+            self.inner.streaming(req, path, codec).await
+        }
+        pub async fn list_indexes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListIndexesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::UpdateTaskResponse>,
+            tonic::Response<super::ListIndexesResponse>,
             tonic::Status,
-        >;
-        async fn list_state_changes(
-            &self,
-            request: tonic::Request<super::ListStateChangesRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListIndexes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListIndexes",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetIndexRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListStateChangesResponse>,
+            tonic::Response<super::GetIndexResponse>,
             tonic::Status,
-        >;
-        async fn list_tasks(
-            &self,
-            request: tonic::Request<super::ListTasksRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetIndex",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetIndex",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_indexes_state(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateIndexesStateRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListTasksResponse>,
+            tonic::Response<super::UpdateIndexesStateResponse>,
             tonic::Status,
-        >;
-        async fn get_schema(
-            &self,
-            request: tonic::Request<super::GetSchemaRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/UpdateIndexesState",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "UpdateIndexesState",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_extractor_coordinates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetExtractorCoordinatesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetSchemaResponse>,
+            tonic::Response<super::GetExtractorCoordinatesResponse>,
             tonic::Status,
-        >;
-        async fn list_schemas(
-            &self,
-            request: tonic::Request<super::GetAllSchemaRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetExtractorCoordinates",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetExtractorCoordinates",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn update_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateTaskRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetAllSchemaResponse>,
+            tonic::Response<super::UpdateTaskResponse>,
             tonic::Status,
-        >;
-        async fn get_raft_metrics_snapshot(
-            &self,
-            request: tonic::Request<super::GetRaftMetricsSnapshotRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/UpdateTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "UpdateTask",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_state_changes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListStateChangesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RaftMetricsSnapshotResponse>,
+            tonic::Response<super::ListStateChangesResponse>,
             tonic::Status,
-        >;
-        async fn get_all_task_assignments(
-            &self,
-            request: tonic::Request<super::GetAllTaskAssignmentRequest>,
-        ) -> std::result::Result<tonic::Response<super::TaskAssignments>, tonic::Status>;
-        async fn get_task(
-            &self,
-            request: tonic::Request<super::GetTaskRequest>,
-        ) -> std::result::Result<tonic::Response<super::GetTaskResponse>, tonic::Status>;
-        async fn get_ingestion_info(
-            &self,
-            request: tonic::Request<super::GetIngestionInfoRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListStateChanges",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListStateChanges",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTasksRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetIngestionInfoResponse>,
+            tonic::Response<super::ListTasksResponse>,
             tonic::Status,
-        >;
-        async fn wait_content_extraction(
-            &self,
-            request: tonic::Request<super::WaitContentExtractionRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListTasks",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_schema(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSchemaRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::WaitContentExtractionResponse>,
+            tonic::Response<super::GetSchemaResponse>,
             tonic::Status,
-        >;
-        async fn list_active_contents(
-            &self,
-            request: tonic::Request<super::ListActiveContentsRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetSchema",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetSchema",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_schemas(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetAllSchemaRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListActiveContentsResponse>,
+            tonic::Response<super::GetAllSchemaResponse>,
             tonic::Status,
-        >;
-        async fn update_labels(
-            &self,
-            request: tonic::Request<super::UpdateLabelsRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListSchemas",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListSchemas",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_raft_metrics_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetRaftMetricsSnapshotRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::UpdateLabelsResponse>,
+            tonic::Response<super::RaftMetricsSnapshotResponse>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct CoordinatorServiceServer<T: CoordinatorService> {
-        inner: _Inner<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    struct _Inner<T>(Arc<T>);
-    impl<T: CoordinatorService> CoordinatorServiceServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
-        }
-        pub fn from_arc(inner: Arc<T>) -> Self {
-            let inner = _Inner(inner);
-            Self {
-                inner,
-                accept_compression_encodings: Default::default(),
-                send_compression_encodings: Default::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            }
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetRaftMetricsSnapshot",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetRaftMetricsSnapshot",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
-        where
-            F: tonic::service::Interceptor,
-        {
-            InterceptedService::new(Self::new(inner), interceptor)
+        pub async fn get_all_task_assignments(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetAllTaskAssignmentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::TaskAssignments>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetAllTaskAssignments",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetAllTaskAssignments",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
-        /// Enable decompressing requests with the given encoding.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.accept_compression_encodings.enable(encoding);
-            self
+        pub async fn get_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetTaskResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetTask",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("indexify_coordinator.CoordinatorService", "GetTask"),
+                );
+            self.inner.unary(req, path, codec).await
         }
-        /// Compress responses with the given encoding, if the client supports it.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.send_compression_encodings.enable(encoding);
-            self
+        pub async fn get_ingestion_info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetIngestionInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetIngestionInfoResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/GetIngestionInfo",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "GetIngestionInfo",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.max_decoding_message_size = Some(limit);
-            self
+        pub async fn wait_content_extraction(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WaitContentExtractionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WaitContentExtractionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/WaitContentExtraction",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "WaitContentExtraction",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.max_encoding_message_size = Some(limit);
-            self
+        pub async fn list_active_contents(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListActiveContentsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListActiveContentsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/ListActiveContents",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "ListActiveContents",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
-    }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for CoordinatorServiceServer<T>
-    where
-        T: CoordinatorService,
-        B: Body + Send + 'static,
-        B::Error: Into<StdError> + Send + 'static,
-    {
-        type Response = http::Response<tonic::body::BoxBody>;
-        type Error = std::convert::Infallible;
-        type Future = BoxFuture<Self::Response, Self::Error>;
-        fn poll_ready(
+        pub async fn update_labels(
             &mut self,
-            _cx: &mut Context<'_>,
-        ) -> Poll<std::result::Result<(), Self::Error>> {
-            Poll::Ready(Ok(()))
+            request: impl tonic::IntoRequest<super::UpdateLabelsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateLabelsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_coordinator.CoordinatorService/UpdateLabels",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "indexify_coordinator.CoordinatorService",
+                        "UpdateLabels",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
-        fn call(&mut self, req: http::Request<B>) -> Self::Future {
-            let inner = self.inner.clone();
-            match req.uri().path() {
-                "/indexify_coordinator.CoordinatorService/CreateContent" => {
+    }
+}
+/// Generated server implementations.
+pub mod coordinator_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with CoordinatorServiceServer.
+    #[async_trait]
+    pub trait CoordinatorService: Send + Sync + 'static {
+        async fn create_content(
+            &self,
+            request: tonic::Request<super::CreateContentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateContentResponse>,
+            tonic::Status,
+        >;
+        async fn tombstone_content(
+            &self,
+            request: tonic::Request<super::TombstoneContentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::TombstoneContentResponse>,
+            tonic::Status,
+        >;
+        async fn get_content_metadata(
+            &self,
+            request: tonic::Request<super::GetContentMetadataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContentMetadataResponse>,
+            tonic::Status,
+        >;
+        async fn get_content_tree_metadata(
+            &self,
+            request: tonic::Request<super::GetContentTreeMetadataRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContentTreeMetadataResponse>,
+            tonic::Status,
+        >;
+        async fn get_search_feedback_counts(
+            &self,
+            request: tonic::Request<super::GetSearchFeedbackCountsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSearchFeedbackCountsResponse>,
+            tonic::Status,
+        >;
+        async fn list_search_feedback(
+            &self,
+            request: tonic::Request<super::ListSearchFeedbackRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSearchFeedbackResponse>,
+            tonic::Status,
+        >;
+        async fn create_search_feedback(
+            &self,
+            request: tonic::Request<super::CreateSearchFeedbackRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateSearchFeedbackResponse>,
+            tonic::Status,
+        >;
+        async fn set_namespace_retention(
+            &self,
+            request: tonic::Request<super::SetNamespaceRetentionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetNamespaceRetentionResponse>,
+            tonic::Status,
+        >;
+        async fn list_namespace_index_tables(
+            &self,
+            request: tonic::Request<super::ListNamespaceIndexTablesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListNamespaceIndexTablesResponse>,
+            tonic::Status,
+        >;
+        async fn enqueue_index_events(
+            &self,
+            request: tonic::Request<super::EnqueueIndexEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EnqueueIndexEventsResponse>,
+            tonic::Status,
+        >;
+        async fn requeue_dead_letters(
+            &self,
+            request: tonic::Request<super::RequeueDeadLettersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RequeueDeadLettersResponse>,
+            tonic::Status,
+        >;
+        async fn list_dead_letters(
+            &self,
+            request: tonic::Request<super::ListDeadLettersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListDeadLettersResponse>,
+            tonic::Status,
+        >;
+        async fn delete_subscription(
+            &self,
+            request: tonic::Request<super::DeleteSubscriptionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteSubscriptionResponse>,
+            tonic::Status,
+        >;
+        async fn list_subscriptions(
+            &self,
+            request: tonic::Request<super::ListSubscriptionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSubscriptionsResponse>,
+            tonic::Status,
+        >;
+        async fn create_subscription(
+            &self,
+            request: tonic::Request<super::CreateSubscriptionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateSubscriptionResponse>,
+            tonic::Status,
+        >;
+        async fn sample_namespace_content(
+            &self,
+            request: tonic::Request<super::SampleNamespaceContentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SampleNamespaceContentResponse>,
+            tonic::Status,
+        >;
+        async fn resolve_namespace(
+            &self,
+            request: tonic::Request<super::ResolveNamespaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResolveNamespaceResponse>,
+            tonic::Status,
+        >;
+        async fn set_namespace_alias(
+            &self,
+            request: tonic::Request<super::SetNamespaceAliasRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetNamespaceAliasResponse>,
+            tonic::Status,
+        >;
+        async fn clone_namespace(
+            &self,
+            request: tonic::Request<super::CloneNamespaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CloneNamespaceResponse>,
+            tonic::Status,
+        >;
+        async fn refresh_content_url(
+            &self,
+            request: tonic::Request<super::RefreshContentUrlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RefreshContentUrlResponse>,
+            tonic::Status,
+        >;
+        async fn get_ingestion_key(
+            &self,
+            request: tonic::Request<super::GetIngestionKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetIngestionKeyResponse>,
+            tonic::Status,
+        >;
+        async fn get_usage_stats(
+            &self,
+            request: tonic::Request<super::GetUsageStatsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetUsageStatsResponse>,
+            tonic::Status,
+        >;
+        async fn get_namespace_overview(
+            &self,
+            request: tonic::Request<super::GetNamespaceOverviewRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetNamespaceOverviewResponse>,
+            tonic::Status,
+        >;
+        async fn update_extraction_policy_params(
+            &self,
+            request: tonic::Request<super::UpdateExtractionPolicyParamsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateExtractionPolicyParamsResponse>,
+            tonic::Status,
+        >;
+        async fn rebuild_content_tree(
+            &self,
+            request: tonic::Request<super::RebuildContentTreeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RebuildContentTreeResponse>,
+            tonic::Status,
+        >;
+        async fn reprocess_state_change(
+            &self,
+            request: tonic::Request<super::ReprocessStateChangeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReprocessStateChangeResponse>,
+            tonic::Status,
+        >;
+        async fn discard_state_change(
+            &self,
+            request: tonic::Request<super::DiscardStateChangeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DiscardStateChangeResponse>,
+            tonic::Status,
+        >;
+        async fn hold_content_tasks(
+            &self,
+            request: tonic::Request<super::HoldContentTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HoldContentTasksResponse>,
+            tonic::Status,
+        >;
+        async fn get_content_task_assignments(
+            &self,
+            request: tonic::Request<super::GetContentTaskAssignmentsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetContentTaskAssignmentsResponse>,
+            tonic::Status,
+        >;
+        async fn update_executor_extractor(
+            &self,
+            request: tonic::Request<super::UpdateExecutorExtractorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateExecutorExtractorResponse>,
+            tonic::Status,
+        >;
+        async fn get_server_info(
+            &self,
+            request: tonic::Request<super::GetServerInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerInfoResponse>,
+            tonic::Status,
+        >;
+        async fn get_referenced_storage_urls(
+            &self,
+            request: tonic::Request<super::GetReferencedStorageUrlsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetReferencedStorageUrlsResponse>,
+            tonic::Status,
+        >;
+        async fn delete_search_profile(
+            &self,
+            request: tonic::Request<super::DeleteSearchProfileRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteSearchProfileResponse>,
+            tonic::Status,
+        >;
+        async fn list_search_profiles(
+            &self,
+            request: tonic::Request<super::ListSearchProfilesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSearchProfilesResponse>,
+            tonic::Status,
+        >;
+        async fn get_search_profile(
+            &self,
+            request: tonic::Request<super::GetSearchProfileRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSearchProfileResponse>,
+            tonic::Status,
+        >;
+        async fn create_search_profile(
+            &self,
+            request: tonic::Request<super::CreateSearchProfileRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateSearchProfileResponse>,
+            tonic::Status,
+        >;
+        async fn content_exists(
+            &self,
+            request: tonic::Request<super::ContentExistsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ContentExistsResponse>,
+            tonic::Status,
+        >;
+        async fn list_content(
+            &self,
+            request: tonic::Request<super::ListContentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListContentResponse>,
+            tonic::Status,
+        >;
+        async fn create_extraction_graph(
+            &self,
+            request: tonic::Request<super::CreateExtractionGraphRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateExtractionGraphResponse>,
+            tonic::Status,
+        >;
+        async fn get_extraction_policy(
+            &self,
+            request: tonic::Request<super::GetExtractionPolicyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetExtractionPolicyResponse>,
+            tonic::Status,
+        >;
+        async fn list_extraction_policies(
+            &self,
+            request: tonic::Request<super::ListExtractionPoliciesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListExtractionPoliciesResponse>,
+            tonic::Status,
+        >;
+        async fn create_ns(
+            &self,
+            request: tonic::Request<super::CreateNamespaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateNamespaceResponse>,
+            tonic::Status,
+        >;
+        async fn list_ns(
+            &self,
+            request: tonic::Request<super::ListNamespaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListNamespaceResponse>,
+            tonic::Status,
+        >;
+        async fn get_ns(
+            &self,
+            request: tonic::Request<super::GetNamespaceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetNamespaceResponse>,
+            tonic::Status,
+        >;
+        async fn list_extractors(
+            &self,
+            request: tonic::Request<super::ListExtractorsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListExtractorsResponse>,
+            tonic::Status,
+        >;
+        async fn register_executor(
+            &self,
+            request: tonic::Request<super::RegisterExecutorRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterExecutorResponse>,
+            tonic::Status,
+        >;
+        async fn register_ingestion_server(
+            &self,
+            request: tonic::Request<super::RegisterIngestionServerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterIngestionServerResponse>,
+            tonic::Status,
+        >;
+        async fn remove_ingestion_server(
+            &self,
+            request: tonic::Request<super::RemoveIngestionServerRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RemoveIngestionServerResponse>,
+            tonic::Status,
+        >;
+        async fn create_gc_tasks(
+            &self,
+            request: tonic::Request<super::CreateGcTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateGcTasksResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the GCTasksStream method.
+        type GCTasksStreamStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::CoordinatorCommand, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn gc_tasks_stream(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::GcTaskAcknowledgement>>,
+        ) -> std::result::Result<
+            tonic::Response<Self::GCTasksStreamStream>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the Heartbeat method.
+        type HeartbeatStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::HeartbeatResponse, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn heartbeat(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::HeartbeatRequest>>,
+        ) -> std::result::Result<tonic::Response<Self::HeartbeatStream>, tonic::Status>;
+        async fn list_indexes(
+            &self,
+            request: tonic::Request<super::ListIndexesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexesResponse>,
+            tonic::Status,
+        >;
+        async fn get_index(
+            &self,
+            request: tonic::Request<super::GetIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetIndexResponse>,
+            tonic::Status,
+        >;
+        async fn update_indexes_state(
+            &self,
+            request: tonic::Request<super::UpdateIndexesStateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateIndexesStateResponse>,
+            tonic::Status,
+        >;
+        async fn get_extractor_coordinates(
+            &self,
+            request: tonic::Request<super::GetExtractorCoordinatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetExtractorCoordinatesResponse>,
+            tonic::Status,
+        >;
+        async fn update_task(
+            &self,
+            request: tonic::Request<super::UpdateTaskRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateTaskResponse>,
+            tonic::Status,
+        >;
+        async fn list_state_changes(
+            &self,
+            request: tonic::Request<super::ListStateChangesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListStateChangesResponse>,
+            tonic::Status,
+        >;
+        async fn list_tasks(
+            &self,
+            request: tonic::Request<super::ListTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTasksResponse>,
+            tonic::Status,
+        >;
+        async fn get_schema(
+            &self,
+            request: tonic::Request<super::GetSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSchemaResponse>,
+            tonic::Status,
+        >;
+        async fn list_schemas(
+            &self,
+            request: tonic::Request<super::GetAllSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetAllSchemaResponse>,
+            tonic::Status,
+        >;
+        async fn get_raft_metrics_snapshot(
+            &self,
+            request: tonic::Request<super::GetRaftMetricsSnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RaftMetricsSnapshotResponse>,
+            tonic::Status,
+        >;
+        async fn get_all_task_assignments(
+            &self,
+            request: tonic::Request<super::GetAllTaskAssignmentRequest>,
+        ) -> std::result::Result<tonic::Response<super::TaskAssignments>, tonic::Status>;
+        async fn get_task(
+            &self,
+            request: tonic::Request<super::GetTaskRequest>,
+        ) -> std::result::Result<tonic::Response<super::GetTaskResponse>, tonic::Status>;
+        async fn get_ingestion_info(
+            &self,
+            request: tonic::Request<super::GetIngestionInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetIngestionInfoResponse>,
+            tonic::Status,
+        >;
+        async fn wait_content_extraction(
+            &self,
+            request: tonic::Request<super::WaitContentExtractionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WaitContentExtractionResponse>,
+            tonic::Status,
+        >;
+        async fn list_active_contents(
+            &self,
+            request: tonic::Request<super::ListActiveContentsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListActiveContentsResponse>,
+            tonic::Status,
+        >;
+        async fn update_labels(
+            &self,
+            request: tonic::Request<super::UpdateLabelsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::UpdateLabelsResponse>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct CoordinatorServiceServer<T: CoordinatorService> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: CoordinatorService> CoordinatorServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for CoordinatorServiceServer<T>
+    where
+        T: CoordinatorService,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/indexify_coordinator.CoordinatorService/CreateContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateContentSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::CreateContentRequest>
+                    for CreateContentSvc<T> {
+                        type Response = super::CreateContentResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::create_content(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/TombstoneContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct TombstoneContentSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::TombstoneContentRequest>
+                    for TombstoneContentSvc<T> {
+                        type Response = super::TombstoneContentResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TombstoneContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::tombstone_content(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = TombstoneContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetContentMetadata" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContentMetadataSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetContentMetadataRequest>
+                    for GetContentMetadataSvc<T> {
+                        type Response = super::GetContentMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetContentMetadataRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_content_metadata(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetContentMetadataSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetContentTreeMetadata" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetContentTreeMetadataSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetContentTreeMetadataRequest>
+                    for GetContentTreeMetadataSvc<T> {
+                        type Response = super::GetContentTreeMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetContentTreeMetadataRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_content_tree_metadata(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetContentTreeMetadataSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetSearchFeedbackCounts" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSearchFeedbackCountsSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetSearchFeedbackCountsRequest>
+                    for GetSearchFeedbackCountsSvc<T> {
+                        type Response = super::GetSearchFeedbackCountsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSearchFeedbackCountsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_search_feedback_counts(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSearchFeedbackCountsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ListSearchFeedback" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListSearchFeedbackSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ListSearchFeedbackRequest>
+                    for ListSearchFeedbackSvc<T> {
+                        type Response = super::ListSearchFeedbackResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListSearchFeedbackRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::list_search_feedback(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListSearchFeedbackSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/CreateSearchFeedback" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateSearchFeedbackSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::CreateSearchFeedbackRequest>
+                    for CreateSearchFeedbackSvc<T> {
+                        type Response = super::CreateSearchFeedbackResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateSearchFeedbackRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::create_search_feedback(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateSearchFeedbackSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/SetNamespaceRetention" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetNamespaceRetentionSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::SetNamespaceRetentionRequest>
+                    for SetNamespaceRetentionSvc<T> {
+                        type Response = super::SetNamespaceRetentionResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetNamespaceRetentionRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::set_namespace_retention(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetNamespaceRetentionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ListNamespaceIndexTables" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListNamespaceIndexTablesSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ListNamespaceIndexTablesRequest>
+                    for ListNamespaceIndexTablesSvc<T> {
+                        type Response = super::ListNamespaceIndexTablesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListNamespaceIndexTablesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::list_namespace_index_tables(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListNamespaceIndexTablesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/EnqueueIndexEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct EnqueueIndexEventsSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::EnqueueIndexEventsRequest>
+                    for EnqueueIndexEventsSvc<T> {
+                        type Response = super::EnqueueIndexEventsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EnqueueIndexEventsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::enqueue_index_events(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = EnqueueIndexEventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/RequeueDeadLetters" => {
+                    #[allow(non_camel_case_types)]
+                    struct RequeueDeadLettersSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::RequeueDeadLettersRequest>
+                    for RequeueDeadLettersSvc<T> {
+                        type Response = super::RequeueDeadLettersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RequeueDeadLettersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::requeue_dead_letters(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RequeueDeadLettersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ListDeadLetters" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListDeadLettersSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ListDeadLettersRequest>
+                    for ListDeadLettersSvc<T> {
+                        type Response = super::ListDeadLettersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListDeadLettersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::list_dead_letters(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListDeadLettersSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/DeleteSubscription" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteSubscriptionSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::DeleteSubscriptionRequest>
+                    for DeleteSubscriptionSvc<T> {
+                        type Response = super::DeleteSubscriptionResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteSubscriptionRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::delete_subscription(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteSubscriptionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ListSubscriptions" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListSubscriptionsSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ListSubscriptionsRequest>
+                    for ListSubscriptionsSvc<T> {
+                        type Response = super::ListSubscriptionsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListSubscriptionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::list_subscriptions(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListSubscriptionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/CreateSubscription" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateSubscriptionSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::CreateSubscriptionRequest>
+                    for CreateSubscriptionSvc<T> {
+                        type Response = super::CreateSubscriptionResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateSubscriptionRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::create_subscription(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateSubscriptionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/SampleNamespaceContent" => {
+                    #[allow(non_camel_case_types)]
+                    struct SampleNamespaceContentSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::SampleNamespaceContentRequest>
+                    for SampleNamespaceContentSvc<T> {
+                        type Response = super::SampleNamespaceContentResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SampleNamespaceContentRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::sample_namespace_content(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SampleNamespaceContentSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ResolveNamespace" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResolveNamespaceSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ResolveNamespaceRequest>
+                    for ResolveNamespaceSvc<T> {
+                        type Response = super::ResolveNamespaceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResolveNamespaceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::resolve_namespace(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ResolveNamespaceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/SetNamespaceAlias" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetNamespaceAliasSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::SetNamespaceAliasRequest>
+                    for SetNamespaceAliasSvc<T> {
+                        type Response = super::SetNamespaceAliasResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetNamespaceAliasRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::set_namespace_alias(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetNamespaceAliasSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/CloneNamespace" => {
+                    #[allow(non_camel_case_types)]
+                    struct CloneNamespaceSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::CloneNamespaceRequest>
+                    for CloneNamespaceSvc<T> {
+                        type Response = super::CloneNamespaceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CloneNamespaceRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::clone_namespace(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CloneNamespaceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/RefreshContentUrl" => {
+                    #[allow(non_camel_case_types)]
+                    struct RefreshContentUrlSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::RefreshContentUrlRequest>
+                    for RefreshContentUrlSvc<T> {
+                        type Response = super::RefreshContentUrlResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RefreshContentUrlRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::refresh_content_url(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RefreshContentUrlSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetIngestionKey" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetIngestionKeySvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetIngestionKeyRequest>
+                    for GetIngestionKeySvc<T> {
+                        type Response = super::GetIngestionKeyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetIngestionKeyRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_ingestion_key(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetIngestionKeySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetUsageStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetUsageStatsSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetUsageStatsRequest>
+                    for GetUsageStatsSvc<T> {
+                        type Response = super::GetUsageStatsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetUsageStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_usage_stats(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetUsageStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetNamespaceOverview" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetNamespaceOverviewSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetNamespaceOverviewRequest>
+                    for GetNamespaceOverviewSvc<T> {
+                        type Response = super::GetNamespaceOverviewResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetNamespaceOverviewRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_namespace_overview(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetNamespaceOverviewSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/UpdateExtractionPolicyParams" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateExtractionPolicyParamsSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::UpdateExtractionPolicyParamsRequest>
+                    for UpdateExtractionPolicyParamsSvc<T> {
+                        type Response = super::UpdateExtractionPolicyParamsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateExtractionPolicyParamsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::update_extraction_policy_params(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateExtractionPolicyParamsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/RebuildContentTree" => {
+                    #[allow(non_camel_case_types)]
+                    struct RebuildContentTreeSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::RebuildContentTreeRequest>
+                    for RebuildContentTreeSvc<T> {
+                        type Response = super::RebuildContentTreeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RebuildContentTreeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::rebuild_content_tree(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RebuildContentTreeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ReprocessStateChange" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReprocessStateChangeSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ReprocessStateChangeRequest>
+                    for ReprocessStateChangeSvc<T> {
+                        type Response = super::ReprocessStateChangeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReprocessStateChangeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::reprocess_state_change(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReprocessStateChangeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/DiscardStateChange" => {
+                    #[allow(non_camel_case_types)]
+                    struct DiscardStateChangeSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::DiscardStateChangeRequest>
+                    for DiscardStateChangeSvc<T> {
+                        type Response = super::DiscardStateChangeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DiscardStateChangeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::discard_state_change(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DiscardStateChangeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/HoldContentTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct HoldContentTasksSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::HoldContentTasksRequest>
+                    for HoldContentTasksSvc<T> {
+                        type Response = super::HoldContentTasksResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HoldContentTasksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::hold_content_tasks(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = HoldContentTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetContentTaskAssignments" => {
                     #[allow(non_camel_case_types)]
-                    struct CreateContentSvc<T: CoordinatorService>(pub Arc<T>);
+                    struct GetContentTaskAssignmentsSvc<T: CoordinatorService>(pub Arc<T>);
                     impl<
                         T: CoordinatorService,
-                    > tonic::server::UnaryService<super::CreateContentRequest>
-                    for CreateContentSvc<T> {
-                        type Response = super::CreateContentResponse;
+                    > tonic::server::UnaryService<super::GetContentTaskAssignmentsRequest>
+                    for GetContentTaskAssignmentsSvc<T> {
+                        type Response = super::GetContentTaskAssignmentsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CreateContentRequest>,
+                            request: tonic::Request<super::GetContentTaskAssignmentsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as CoordinatorService>::create_content(&inner, request)
+                                <T as CoordinatorService>::get_content_task_assignments(
+                                        &inner,
+                                        request,
+                                    )
                                     .await
                             };
                             Box::pin(fut)
@@ -2317,7 +5631,7 @@ pub mod coordinator_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = CreateContentSvc(inner);
+                        let method = GetContentTaskAssignmentsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -2333,25 +5647,25 @@ pub mod coordinator_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/indexify_coordinator.CoordinatorService/TombstoneContent" => {
+                "/indexify_coordinator.CoordinatorService/UpdateExecutorExtractor" => {
                     #[allow(non_camel_case_types)]
-                    struct TombstoneContentSvc<T: CoordinatorService>(pub Arc<T>);
+                    struct UpdateExecutorExtractorSvc<T: CoordinatorService>(pub Arc<T>);
                     impl<
                         T: CoordinatorService,
-                    > tonic::server::UnaryService<super::TombstoneContentRequest>
-                    for TombstoneContentSvc<T> {
-                        type Response = super::TombstoneContentResponse;
+                    > tonic::server::UnaryService<super::UpdateExecutorExtractorRequest>
+                    for UpdateExecutorExtractorSvc<T> {
+                        type Response = super::UpdateExecutorExtractorResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::TombstoneContentRequest>,
+                            request: tonic::Request<super::UpdateExecutorExtractorRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as CoordinatorService>::tombstone_content(
+                                <T as CoordinatorService>::update_executor_extractor(
                                         &inner,
                                         request,
                                     )
@@ -2367,7 +5681,7 @@ pub mod coordinator_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = TombstoneContentSvc(inner);
+                        let method = UpdateExecutorExtractorSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -2383,25 +5697,25 @@ pub mod coordinator_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/indexify_coordinator.CoordinatorService/GetContentMetadata" => {
+                "/indexify_coordinator.CoordinatorService/GetServerInfo" => {
                     #[allow(non_camel_case_types)]
-                    struct GetContentMetadataSvc<T: CoordinatorService>(pub Arc<T>);
+                    struct GetServerInfoSvc<T: CoordinatorService>(pub Arc<T>);
                     impl<
                         T: CoordinatorService,
-                    > tonic::server::UnaryService<super::GetContentMetadataRequest>
-                    for GetContentMetadataSvc<T> {
-                        type Response = super::GetContentMetadataResponse;
+                    > tonic::server::UnaryService<super::GetServerInfoRequest>
+                    for GetServerInfoSvc<T> {
+                        type Response = super::GetServerInfoResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetContentMetadataRequest>,
+                            request: tonic::Request<super::GetServerInfoRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as CoordinatorService>::get_content_metadata(
+                                <T as CoordinatorService>::get_server_info(
                                         &inner,
                                         request,
                                     )
@@ -2417,7 +5731,7 @@ pub mod coordinator_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = GetContentMetadataSvc(inner);
+                        let method = GetServerInfoSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -2433,25 +5747,25 @@ pub mod coordinator_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/indexify_coordinator.CoordinatorService/GetContentTreeMetadata" => {
+                "/indexify_coordinator.CoordinatorService/GetReferencedStorageUrls" => {
                     #[allow(non_camel_case_types)]
-                    struct GetContentTreeMetadataSvc<T: CoordinatorService>(pub Arc<T>);
+                    struct GetReferencedStorageUrlsSvc<T: CoordinatorService>(pub Arc<T>);
                     impl<
                         T: CoordinatorService,
-                    > tonic::server::UnaryService<super::GetContentTreeMetadataRequest>
-                    for GetContentTreeMetadataSvc<T> {
-                        type Response = super::GetContentTreeMetadataResponse;
+                    > tonic::server::UnaryService<super::GetReferencedStorageUrlsRequest>
+                    for GetReferencedStorageUrlsSvc<T> {
+                        type Response = super::GetReferencedStorageUrlsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetContentTreeMetadataRequest>,
+                            request: tonic::Request<super::GetReferencedStorageUrlsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as CoordinatorService>::get_content_tree_metadata(
+                                <T as CoordinatorService>::get_referenced_storage_urls(
                                         &inner,
                                         request,
                                     )
@@ -2467,7 +5781,257 @@ pub mod coordinator_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = GetContentTreeMetadataSvc(inner);
+                        let method = GetReferencedStorageUrlsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/DeleteSearchProfile" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteSearchProfileSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::DeleteSearchProfileRequest>
+                    for DeleteSearchProfileSvc<T> {
+                        type Response = super::DeleteSearchProfileResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteSearchProfileRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::delete_search_profile(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteSearchProfileSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ListSearchProfiles" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListSearchProfilesSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ListSearchProfilesRequest>
+                    for ListSearchProfilesSvc<T> {
+                        type Response = super::ListSearchProfilesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListSearchProfilesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::list_search_profiles(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListSearchProfilesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/GetSearchProfile" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSearchProfileSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::GetSearchProfileRequest>
+                    for GetSearchProfileSvc<T> {
+                        type Response = super::GetSearchProfileResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSearchProfileRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::get_search_profile(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSearchProfileSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/CreateSearchProfile" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateSearchProfileSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::CreateSearchProfileRequest>
+                    for CreateSearchProfileSvc<T> {
+                        type Response = super::CreateSearchProfileResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateSearchProfileRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::create_search_profile(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateSearchProfileSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_coordinator.CoordinatorService/ContentExists" => {
+                    #[allow(non_camel_case_types)]
+                    struct ContentExistsSvc<T: CoordinatorService>(pub Arc<T>);
+                    impl<
+                        T: CoordinatorService,
+                    > tonic::server::UnaryService<super::ContentExistsRequest>
+                    for ContentExistsSvc<T> {
+                        type Response = super::ContentExistsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ContentExistsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as CoordinatorService>::content_exists(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ContentExistsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -164,6 +164,50 @@ pub mod raft_api_client {
                 .insert(GrpcMethod::new("indexify_raft.RaftApi", "InstallSnapshot"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn get_snapshot_chunks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RaftRequest>,
+        ) -> std::result::Result<tonic::Response<super::RaftReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_raft.RaftApi/GetSnapshotChunks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("indexify_raft.RaftApi", "GetSnapshotChunks"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn install_snapshot_chunks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RaftRequest>,
+        ) -> std::result::Result<tonic::Response<super::RaftReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/indexify_raft.RaftApi/InstallSnapshotChunks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("indexify_raft.RaftApi", "InstallSnapshotChunks"));
+            self.inner.unary(req, path, codec).await
+        }
         pub async fn vote(
             &mut self,
             request: impl tonic::IntoRequest<super::RaftRequest>,
@@ -229,6 +273,14 @@ pub mod raft_api_server {
             &self,
             request: tonic::Request<super::RaftRequest>,
         ) -> std::result::Result<tonic::Response<super::RaftReply>, tonic::Status>;
+        async fn get_snapshot_chunks(
+            &self,
+            request: tonic::Request<super::RaftRequest>,
+        ) -> std::result::Result<tonic::Response<super::RaftReply>, tonic::Status>;
+        async fn install_snapshot_chunks(
+            &self,
+            request: tonic::Request<super::RaftRequest>,
+        ) -> std::result::Result<tonic::Response<super::RaftReply>, tonic::Status>;
         async fn vote(
             &self,
             request: tonic::Request<super::RaftRequest>,
@@ -449,6 +501,94 @@ pub mod raft_api_server {
                     };
                     Box::pin(fut)
                 }
+                "/indexify_raft.RaftApi/GetSnapshotChunks" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSnapshotChunksSvc<T: RaftApi>(pub Arc<T>);
+                    impl<T: RaftApi> tonic::server::UnaryService<super::RaftRequest>
+                    for GetSnapshotChunksSvc<T> {
+                        type Response = super::RaftReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RaftRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RaftApi>::get_snapshot_chunks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSnapshotChunksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/indexify_raft.RaftApi/InstallSnapshotChunks" => {
+                    #[allow(non_camel_case_types)]
+                    struct InstallSnapshotChunksSvc<T: RaftApi>(pub Arc<T>);
+                    impl<T: RaftApi> tonic::server::UnaryService<super::RaftRequest>
+                    for InstallSnapshotChunksSvc<T> {
+                        type Response = super::RaftReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RaftRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RaftApi>::install_snapshot_chunks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = InstallSnapshotChunksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/indexify_raft.RaftApi/Vote" => {
                     #[allow(non_camel_case_types)]
                     struct VoteSvc<T: RaftApi>(pub Arc<T>);
@@ -13,7 +13,7 @@ use smart_default::SmartDefault;
 use strum::{Display, EnumString};
 use utoipa::{IntoParams, ToSchema};
 
-use crate::{api_utils, metadata_storage, vectordbs};
+use crate::{api_utils, metadata_storage, search_log, vectordbs};
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ExtractionGraph {
@@ -44,11 +44,22 @@ pub struct ExtractionPolicy {
     pub id: String,
     pub extractor: String,
     pub name: String,
-    #[serde(default, deserialize_with = "api_utils::deserialize_labels_eq_filter")]
+    #[serde(
+        default,
+        deserialize_with = "api_utils::deserialize_policy_labels_filter"
+    )]
     pub filters_eq: Option<HashMap<String, String>>,
     pub input_params: Option<serde_json::Value>,
     pub content_source: Option<String>,
     pub graph_name: String,
+    #[serde(default)]
+    pub status: internal_api::ExtractionPolicyStatus,
+    #[serde(default)]
+    pub index_attributes: internal_api::IndexAttributeSchema,
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default)]
+    pub version_strategy: internal_api::ExtractionPolicyVersionStrategy,
 }
 
 impl From<indexify_coordinator::ExtractionPolicy> for ExtractionPolicy {
@@ -61,6 +72,13 @@ impl From<indexify_coordinator::ExtractionPolicy> for ExtractionPolicy {
             input_params: Some(serde_json::from_str(&value.input_params).unwrap()),
             content_source: Some(value.content_source),
             graph_name: value.graph_name,
+            status: value.status.parse().unwrap_or_default(),
+            index_attributes: internal_api::IndexAttributeSchema::from_json_str(
+                &value.index_attributes,
+            )
+            .unwrap_or_default(),
+            version: value.version,
+            version_strategy: value.version_strategy.parse().unwrap_or_default(),
         }
     }
 }
@@ -69,6 +87,10 @@ impl From<indexify_coordinator::ExtractionPolicy> for ExtractionPolicy {
 pub struct DataNamespace {
     pub name: String,
     pub extraction_graphs: Vec<ExtractionGraph>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub retention: RetentionRules,
 }
 
 impl From<indexify_coordinator::Namespace> for DataNamespace {
@@ -80,15 +102,52 @@ impl From<indexify_coordinator::Namespace> for DataNamespace {
                 .into_iter()
                 .map(Into::into)
                 .collect(),
+            warnings: value.warnings,
+            retention: value.retention.unwrap_or_default().into(),
         }
     }
 }
 
+/// Rules expiring the root content of a namespace along with its trees, by
+/// the age of the content or by the number of newer roots. Content whose
+/// tasks are unfinished expires once they finish.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RetentionRules {
+    /// Seconds since the content was created after which it expires
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    /// Number of newest roots which are kept
+    #[serde(default)]
+    pub max_items: Option<u64>,
+}
+
+impl From<indexify_coordinator::RetentionRules> for RetentionRules {
+    fn from(value: indexify_coordinator::RetentionRules) -> Self {
+        let rules: internal_api::RetentionRules = value.into();
+        Self {
+            max_age_secs: rules.max_age_secs,
+            max_items: rules.max_items,
+        }
+    }
+}
+
+impl From<RetentionRules> for indexify_coordinator::RetentionRules {
+    fn from(value: RetentionRules) -> Self {
+        internal_api::RetentionRules {
+            max_age_secs: value.max_age_secs,
+            max_items: value.max_items,
+        }
+        .into()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, SmartDefault, ToSchema)]
 pub struct CreateNamespace {
     pub name: String,
     pub extraction_graphs: Vec<ExtractionGraph>,
     pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub retention: RetentionRules,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -104,6 +163,35 @@ pub struct ListNamespacesResponse {
     pub namespaces: Vec<DataNamespace>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CloneNamespaceRequest {
+    /// Name of the namespace the configuration is copied into
+    pub name: String,
+    /// Number of root contents of the namespace, picked at random, which are
+    /// copied into the clone and extracted by its policies
+    #[serde(default)]
+    pub sample_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CloneNamespaceResponse {
+    pub namespace: String,
+    pub indexes: Vec<String>,
+    /// Ids of the content sampled into the clone
+    pub sampled_content: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetNamespaceAliasRequest {
+    pub namespace: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetNamespaceAliasResponse {
+    pub alias: String,
+    pub namespace: String,
+}
+
 #[derive(Display, EnumString, Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(rename = "distance")]
 pub enum IndexDistance {
@@ -146,10 +234,22 @@ impl From<vectordbs::IndexDistance> for IndexDistance {
 pub struct ExtractionPolicyRequest {
     pub extractor: String,
     pub name: String,
-    #[serde(default, deserialize_with = "api_utils::deserialize_labels_eq_filter")]
+    #[serde(
+        default,
+        deserialize_with = "api_utils::deserialize_policy_labels_filter"
+    )]
     pub filters_eq: Option<HashMap<String, String>>,
     pub input_params: Option<serde_json::Value>,
     pub content_source: Option<String>,
+    /// Which metadata attributes the indexes of the policy store, filter on
+    /// and return. Attributes which are not listed are stored, filterable and
+    /// returned.
+    #[serde(default)]
+    pub index_attributes: internal_api::IndexAttributeSchema,
+    /// Versions of the content the policy runs on: latest_only, the default,
+    /// every_version or first_version_only
+    #[serde(default)]
+    pub version_strategy: internal_api::ExtractionPolicyVersionStrategy,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
@@ -164,11 +264,20 @@ pub struct Text {
     pub text: String,
     #[serde(default)]
     pub labels: HashMap<String, String>,
+    /// Users and groups allowed to read the text and its extracted content
+    #[serde(default)]
+    pub acl: Option<internal_api::ContentAcl>,
+    /// Texts added again with the same key return the content created the
+    /// first time. Derived from the Idempotency-Key header when not set.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TextAddRequest {
     pub documents: Vec<Text>,
+    /// Extracts texts under the size limit synchronously, the response lists
+    /// the content derived from them
     pub sync: Option<bool>,
     pub extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
 }
@@ -185,6 +294,9 @@ pub struct UpdateContentResponse {}
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TombstoneContentRequest {
     pub content_ids: Vec<String>,
+    /// Recorded with the tombstoned content
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -257,9 +369,17 @@ pub struct ListExtractorsResponse {
 #[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
 pub struct TextAdditionResponse {
     pub content_ids: Vec<String>,
+    /// Ids of the content derived from each added text, keyed by the id of
+    /// the text. Only set when the texts were extracted synchronously.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub derived_content_ids: HashMap<String, Vec<String>>,
+    /// Reason the texts were handed over to the asynchronous pipeline when
+    /// synchronous extraction was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_fallback: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Index {
     pub name: String,
     pub embedding_schema: EmbeddingSchema,
@@ -287,14 +407,316 @@ pub struct ListIndexesResponse {
     pub indexes: Vec<Index>,
 }
 
+/// An index of a namespace together with the extraction policy and extractor
+/// producing its embeddings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IndexOverview {
+    pub index: Index,
+    pub extraction_graph: String,
+    /// Name of the extraction policy, missing when the policy doesn't exist
+    pub extraction_policy: Option<String>,
+    /// Name of the extractor, missing when the extractor isn't registered
+    pub extractor: Option<String>,
+    /// Number of vectors in the index, missing when the vector database
+    /// couldn't be queried
+    pub vector_count: Option<u64>,
+    /// Seconds since epoch of the last vector write seen by this server
+    pub last_write_at: Option<u64>,
+    /// Set when the index references a policy or extractor which is missing
+    pub degraded: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NamespaceOverview {
+    pub indexes: Vec<IndexOverview>,
+}
+
+/// Resources used by finished tasks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct UsageCounters {
+    pub tasks: u64,
+    pub content_bytes: u64,
+    pub chunks: u64,
+    pub vectors: u64,
+    pub execution_time_ms: u64,
+}
+
+impl UsageCounters {
+    pub fn add(&mut self, other: &UsageCounters) {
+        self.tasks += other.tasks;
+        self.content_bytes += other.content_bytes;
+        self.chunks += other.chunks;
+        self.vectors += other.vectors;
+        self.execution_time_ms += other.execution_time_ms;
+    }
+}
+
+impl From<&indexify_coordinator::UsageStats> for UsageCounters {
+    fn from(value: &indexify_coordinator::UsageStats) -> Self {
+        Self {
+            tasks: value.tasks,
+            content_bytes: value.content_bytes,
+            chunks: value.chunks,
+            vectors: value.vectors,
+            execution_time_ms: value.execution_time_ms,
+        }
+    }
+}
+
+/// Usage of an extractor in a namespace on a day
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UsageBucket {
+    pub date: String,
+    pub extractor: String,
+    #[serde(flatten)]
+    pub counters: UsageCounters,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NamespaceUsageResponse {
+    pub namespace: String,
+    pub from: String,
+    pub to: String,
+    pub buckets: Vec<UsageBucket>,
+    pub total: UsageCounters,
+}
+
+impl NamespaceUsageResponse {
+    /// One row per bucket, with a header row
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("date,extractor,tasks,content_bytes,chunks,vectors,execution_time_ms\n");
+        for bucket in &self.buckets {
+            let extractor = if bucket.extractor.contains([',', '"', '\n']) {
+                format!("\"{}\"", bucket.extractor.replace('"', "\"\""))
+            } else {
+                bucket.extractor.clone()
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                bucket.date,
+                extractor,
+                bucket.counters.tasks,
+                bucket.counters.content_bytes,
+                bucket.counters.chunks,
+                bucket.counters.vectors,
+                bucket.counters.execution_time_ms
+            ));
+        }
+        csv
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NamespaceUsageTotals {
+    pub namespace: String,
+    #[serde(flatten)]
+    pub counters: UsageCounters,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UsageRollupResponse {
+    pub from: String,
+    pub to: String,
+    pub namespaces: Vec<NamespaceUsageTotals>,
+    pub total: UsageCounters,
+}
+
+/// Dates are `YYYY-MM-DD` in UTC and both ends are inclusive. The range
+/// defaults to the last 30 days.
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct UsageParams {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// `json` or `csv`
+    pub format: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, IntoParams, ToSchema)]
 pub struct SearchRequest {
+    /// Index to search, may be omitted when the profile sets it
+    #[serde(default)]
     pub index: String,
     pub query: String,
     pub k: Option<u64>,
     #[serde(default)]
     pub filters: Vec<String>,
+    /// Filter expression combining comparisons with AND, OR and parentheses,
+    /// e.g. `mime_type = 'text/plain' AND (lang = en OR lang = fr)`. Replaces
+    /// `filters` and the filters of the profile when set.
+    pub filter: Option<String>,
     pub include_content: Option<bool>,
+    /// Drops results scoring below this value
+    pub min_score: Option<f32>,
+    /// Name of a search profile whose settings apply unless set on the request
+    pub profile: Option<String>,
+    /// Expression the results are re-ranked by, combining the similarity with
+    /// label signals, e.g. `0.8*similarity + 0.2*recency(labels.updated_at,
+    /// half_life=30d) + boost(source=="handbook", 0.1)`
+    pub scoring: Option<String>,
+    /// Includes the breakdown of the scoring expression and the filter
+    /// conditions each result satisfied
+    pub explain: Option<bool>,
+    /// User the search is made on behalf of. Only content whose ACL permits
+    /// the user or one of the groups is returned.
+    pub as_user: Option<String>,
+    /// Groups of the user the search is made on behalf of
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Embeds the query even if its embedding is cached
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+impl SearchRequest {
+    /// The access context the results are checked against, None when the
+    /// search isn't made on behalf of a user
+    pub fn access_context(&self) -> Option<internal_api::AccessContext> {
+        access_context(self.as_user.clone(), self.groups.clone())
+    }
+}
+
+/// Query parameters of content reads made on behalf of a user. Only content
+/// whose ACL permits the user or one of the groups is returned.
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct AccessParams {
+    pub as_user: Option<String>,
+    /// Comma separated groups of the user
+    pub groups: Option<String>,
+}
+
+impl AccessParams {
+    pub fn access_context(&self) -> Option<internal_api::AccessContext> {
+        let groups = self
+            .groups
+            .iter()
+            .flat_map(|groups| groups.split(','))
+            .map(|group| group.trim().to_string())
+            .filter(|group| !group.is_empty())
+            .collect();
+        access_context(self.as_user.clone(), groups)
+    }
+}
+
+fn access_context(
+    user: Option<String>,
+    groups: Vec<String>,
+) -> Option<internal_api::AccessContext> {
+    if user.is_none() && groups.is_empty() {
+        return None;
+    }
+    Some(internal_api::AccessContext { user, groups })
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateSearchProfile {
+    pub name: String,
+    pub index: String,
+    #[serde(default)]
+    pub filters: Vec<String>,
+    pub k: Option<u64>,
+    pub min_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SearchProfile {
+    pub name: String,
+    pub index: String,
+    pub filters: Vec<String>,
+    pub k: Option<u64>,
+    pub min_score: Option<f32>,
+    /// Set when the profile can't be used, e.g. its index no longer exists
+    pub invalid_reason: Option<String>,
+}
+
+impl From<indexify_coordinator::SearchProfile> for SearchProfile {
+    fn from(value: indexify_coordinator::SearchProfile) -> Self {
+        let invalid_reason =
+            (!value.invalid_reason.is_empty()).then(|| value.invalid_reason.clone());
+        let profile: internal_api::SearchProfile = value.into();
+        Self {
+            name: profile.name,
+            index: profile.index,
+            filters: profile.filters,
+            k: profile.k,
+            min_score: profile.min_score,
+            invalid_reason,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListSearchProfilesResponse {
+    pub profiles: Vec<SearchProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateSubscriptionRequest {
+    /// Url the index events of the namespace are posted to
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Subscription {
+    pub id: String,
+    pub namespace: String,
+    pub url: String,
+    pub created_at: u64,
+}
+
+impl From<indexify_coordinator::Subscription> for Subscription {
+    fn from(value: indexify_coordinator::Subscription) -> Self {
+        Self {
+            id: value.id,
+            namespace: value.namespace,
+            url: value.url,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListSubscriptionsResponse {
+    pub subscriptions: Vec<Subscription>,
+}
+
+/// A delivery which failed too many times, or for too long, to be retried
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetter {
+    pub sequence: u64,
+    pub payload: String,
+    pub enqueued_at: u64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl From<indexify_coordinator::Delivery> for DeadLetter {
+    fn from(value: indexify_coordinator::Delivery) -> Self {
+        Self {
+            sequence: value.sequence,
+            payload: value.payload,
+            enqueued_at: value.enqueued_at,
+            attempts: value.attempts,
+            last_error: (!value.last_error.is_empty()).then_some(value.last_error),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListDeadLettersResponse {
+    pub dead_letters: Vec<DeadLetter>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RequeueDeadLettersResponse {
+    pub requeued: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DiscardStateChangeRequest {
+    /// Why the state change is discarded, logged by the coordinator
+    pub note: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -352,6 +774,50 @@ pub struct DocumentFragment {
     pub labels: HashMap<String, String>,
     pub root_content_metadata: Option<ContentMetadata>,
     pub content_metadata: ContentMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_explanation: Option<ScoreExplanation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_filters: Vec<MatchedFilter>,
+}
+
+/// A filter condition a search result satisfied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MatchedFilter {
+    pub key: String,
+    /// `=` or `!=`
+    pub operator: String,
+    pub value: String,
+    /// Value of the result's attribute, missing when a `!=` condition
+    /// matched a result without the attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute_value: Option<serde_json::Value>,
+}
+
+impl From<vectordbs::MatchedFilter> for MatchedFilter {
+    fn from(matched: vectordbs::MatchedFilter) -> Self {
+        Self {
+            key: matched.filter.key,
+            operator: matched.filter.operator.to_string(),
+            value: matched.filter.value,
+            attribute_value: matched.attribute_value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScoreTerm {
+    pub expression: String,
+    pub value: f64,
+}
+
+/// Breakdown of the score of a search result ranked by a scoring expression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScoreExplanation {
+    /// Similarity returned by the index
+    pub similarity: f64,
+    pub score: f64,
+    /// Contribution of each term of the expression's top level sum
+    pub terms: Vec<ScoreTerm>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
@@ -373,7 +839,16 @@ impl IndexifyAPIError {
         }
     }
 
+    /// Errors of the coordinator which isn't ready yet are retriable, they
+    /// are returned as 503
     pub fn internal_error(e: anyhow::Error) -> Self {
+        let unavailable = e
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<tonic::Status>())
+            .any(|status| status.code() == tonic::Code::Unavailable);
+        if unavailable {
+            return Self::new(StatusCode::SERVICE_UNAVAILABLE, e.to_string().as_str());
+        }
         Self::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string().as_str())
     }
 
@@ -415,6 +890,11 @@ pub struct ContentMetadata {
     pub source: String,
     pub size: u64,
     pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acl: Option<internal_api::ContentAcl>,
+    /// Set on tombstoned content, which is only returned when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tombstone_reason: Option<internal_api::TombstoneReason>,
 }
 
 impl From<indexify_coordinator::ContentMetadata> for ContentMetadata {
@@ -433,6 +913,8 @@ impl From<indexify_coordinator::ContentMetadata> for ContentMetadata {
             size: value.size_bytes,
             hash: value.hash,
             extraction_graph_names: value.extraction_graph_names,
+            acl: value.acl.map(Into::into),
+            tombstone_reason: value.tombstone_reason.map(Into::into),
         }
     }
 }
@@ -453,6 +935,8 @@ impl From<indexify_internal_api::ContentMetadata> for ContentMetadata {
             size: value.size_bytes,
             hash: value.hash,
             extraction_graph_names: value.extraction_graph_names,
+            tombstone_reason: value.reason_tombstoned(),
+            acl: value.acl,
         }
     }
 }
@@ -531,6 +1015,10 @@ pub struct ContentWithId {
     pub id: String,
     pub content: Content,
     pub extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
+    #[serde(default)]
+    pub acl: Option<internal_api::ContentAcl>,
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 #[serde_as]
@@ -623,6 +1111,12 @@ pub struct BeginExtractedContentIngest {
     pub task_id: String,
     pub executor_id: String,
     pub task_outcome: internal_api::TaskOutcome,
+    /// Version of the extraction policy the executor ran the task with
+    #[serde(default)]
+    pub policy_version: Option<u64>,
+    /// Time the executor spent running the task
+    #[serde(default)]
+    pub execution_time_ms: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
@@ -636,9 +1130,21 @@ pub struct FinishExtractedContentIngest {
     pub num_extracted_content: u64,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+pub struct GetContentMetadataParams {
+    /// Return the content even if it was tombstoned
+    #[serde(default)]
+    pub include_tombstoned: bool,
+    /// Return the counts of the search feedback given on the content
+    #[serde(default)]
+    pub include_feedback: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GetContentMetadataResponse {
     pub content_metadata: ContentMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<internal_api::SearchFeedbackCounts>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -646,11 +1152,102 @@ pub struct GetContentTreeMetadataResponse {
     pub content_tree_metadata: Vec<ContentMetadata>,
 }
 
+#[derive(Debug, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct RebuildContentTreeParams {
+    /// Name of the index to rebuild the content tree in
+    pub index: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RebuildContentTreeResponse {
+    /// Number of extraction tasks created for the content tree
+    pub task_count: u64,
+}
+
+/// Identifies content by the id it was ingested with or by the hash of its
+/// bytes. The id is used when both are set.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct ContentExistsEntry {
+    pub id: Option<String>,
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ContentExistsRequest {
+    pub entries: Vec<ContentExistsEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ContentExistsResponse {
+    pub results: Vec<internal_api::ContentExistence>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GetExtractedMetadataResponse {
     pub extracted_metadata: Vec<ExtractedMetadata>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchLogParams {
+    pub limit: Option<usize>,
+}
+
+/// Feedback on a search result. The query is identified either by the query
+/// hash of its search log entry or by its text, which is hashed the same way.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateSearchFeedback {
+    pub query_hash: Option<String>,
+    pub query: Option<String>,
+    pub content_id: String,
+    pub chunk_id: Option<String>,
+    pub verdict: internal_api::FeedbackVerdict,
+    pub note: Option<String>,
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+pub struct ListSearchFeedbackParams {
+    /// Only list the feedback on this content
+    pub content_id: Option<String>,
+    /// Only list feedback given at or after this time, in seconds since epoch
+    pub start_time: Option<u64>,
+    /// Only list feedback given before this time, in seconds since epoch
+    pub end_time: Option<u64>,
+    /// The next_cursor of the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListSearchFeedbackResponse {
+    pub feedback: Vec<internal_api::SearchFeedback>,
+    /// Set when there may be more feedback, pass it to get the next page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchLogResponse {
+    pub entries: Vec<search_log::SearchLogEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplaySearchLogResponse {
+    pub results: Vec<search_log::SearchReplayResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlushQueryEmbeddingCacheResponse {
+    pub flushed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NamespaceVectorTotalsResponse {
+    pub totals: HashMap<String, u64>,
+    /// Index table name -> error counting its vectors, these indexes are left
+    /// out of the totals
+    pub errors: HashMap<String, String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListTasks {
     pub extraction_policy: Option<String>,
@@ -718,6 +1315,8 @@ pub struct IngestRemoteFile {
     pub mime_type: String,
     pub labels: HashMap<String, String>,
     pub extraction_graph_names: Vec<String>,
+    #[serde(default)]
+    pub acl: Option<internal_api::ContentAcl>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -725,6 +1324,31 @@ pub struct IngestRemoteFileResponse {
     pub content_id: String,
 }
 
+/// Urls the server fetches content from, the content of every url gets the
+/// same labels and extraction graphs
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FetchContentRequest {
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    pub extraction_graph_names: Vec<String>,
+    #[serde(default)]
+    pub acl: Option<internal_api::ContentAcl>,
+}
+
+/// Either the id of the content created from the url or why it failed
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FetchUrlResult {
+    pub url: String,
+    pub content_id: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FetchContentResponse {
+    pub results: Vec<FetchUrlResult>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskAssignments {
     pub assignments: HashMap<String, String>,
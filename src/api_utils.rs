@@ -233,6 +233,28 @@ where
 pub fn deserialize_labels_eq_filter<'de, D>(
     deserializer: D,
 ) -> Result<Option<HashMap<String, String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    labels_filter(deserializer, false)
+}
+
+/// Deserializes the label filters of extraction policies, which are like
+/// labels_eq filters except that a label can match any of several values
+/// separated by `|`, e.g. `language:eng|deu`
+pub fn deserialize_policy_labels_filter<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    labels_filter(deserializer, true)
+}
+
+fn labels_filter<'de, D>(
+    deserializer: D,
+    alternatives: bool,
+) -> Result<Option<HashMap<String, String>>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -270,8 +292,17 @@ where
         }
         validate_label_key(key.as_str())
             .map_err(|e| err_formatter("key invalid".to_string(), e.to_string()))?;
-        validate_label_value(value.as_str())
-            .map_err(|e| err_formatter("value invalid".to_string(), e.to_string()))?;
+        let valid = if alternatives && value.contains('|') {
+            value.split('|').try_for_each(|alternative| {
+                if alternative.is_empty() {
+                    return Err(anyhow!("alternative values must not be empty"));
+                }
+                validate_label_value(alternative)
+            })
+        } else {
+            validate_label_value(value.as_str())
+        };
+        valid.map_err(|e| err_formatter("value invalid".to_string(), e.to_string()))?;
 
         labels_eq.insert(key, value);
     }
@@ -394,6 +425,47 @@ mod test_deserialize_labels_eq_filter {
         assert_eq!(query.0, expected_query.0);
     }
 
+    /// 6. Extraction policies can match any of several values
+    #[test]
+    fn test_policy_alternatives() {
+        let request: crate::api::ExtractionPolicyRequest =
+            serde_json::from_value(serde_json::json!({
+                "extractor": "extractor",
+                "name": "policy",
+                "filters_eq": "language:eng|deu,source:web",
+                "input_params": null,
+                "content_source": null,
+            }))
+            .unwrap();
+        assert_eq!(
+            request.filters_eq,
+            Some(HashMap::from([
+                ("language".to_string(), "eng|deu".to_string()),
+                ("source".to_string(), "web".to_string()),
+            ]))
+        );
+
+        //  Every alternative must be a valid value
+        for filter in ["language:eng|", "language:|deu", "language:eng||deu"] {
+            let request: Result<crate::api::ExtractionPolicyRequest, _> =
+                serde_json::from_value(serde_json::json!({
+                    "extractor": "extractor",
+                    "name": "policy",
+                    "filters_eq": filter,
+                    "input_params": null,
+                    "content_source": null,
+                }));
+            assert!(request.is_err(), "filter should be invalid: {}", filter);
+        }
+
+        //  Content is listed by exact labels
+        let query_str: Uri = "http://example.com/path?labels_eq=language:eng%7Cdeu"
+            .parse()
+            .unwrap();
+        let query: Result<Query<ListContentFilters>, _> = Query::try_from_uri(&query_str);
+        assert!(query.is_err());
+    }
+
     /// INVALID - each of these throws error
     #[test]
     fn test_invalid() {
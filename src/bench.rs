@@ -0,0 +1,488 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use indexify_internal_api as internal_api;
+use internal_api::{
+    ContentMetadata,
+    ContentMetadataId,
+    ContentSource,
+    ExtractionGraph,
+    ExtractionPolicy,
+    ExtractionPolicyContentSource,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::{
+    coordinator::Coordinator,
+    utils::timestamp_secs,
+    vectordbs::{CreateIndexParams, IndexDistance, VectorChunk, VectorDBTS},
+};
+
+/// Extractor the policies of the workload run, it's never run since the
+/// workload only measures until tasks are created
+pub const BENCH_EXTRACTOR: &str = "indexify/bench-noop";
+
+const BENCH_EXECUTOR: &str = "bench_executor";
+
+const BENCH_GRAPH: &str = "bench";
+
+/// Latency of applying the metadata of ingested content through raft
+pub const INGESTION_APPLY: &str = "ingestion_apply";
+
+/// Latency from the content being applied until its tasks are created
+pub const TASK_SCHEDULING: &str = "task_scheduling";
+
+pub const SEARCH: &str = "search";
+
+/// Number of results of each search of the workload
+const SEARCH_K: u64 = 10;
+
+/// Shape of the synthetic workload of a benchmark run. Runs with the same
+/// config generate the same workload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchConfig {
+    pub namespaces: usize,
+    pub content_per_namespace: usize,
+    /// Number of policies of the extraction graph of each namespace
+    pub policies: usize,
+    pub searches_per_namespace: usize,
+    /// Content sizes are spread evenly across orders of magnitude between
+    /// these bounds, most content being small like real uploads
+    pub min_content_bytes: u64,
+    pub max_content_bytes: u64,
+    pub embedding_dim: usize,
+    pub seed: u64,
+    /// Longest wait for the tasks of a piece of content to be created
+    pub scheduling_timeout_ms: u64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            namespaces: 2,
+            content_per_namespace: 100,
+            policies: 2,
+            searches_per_namespace: 50,
+            min_content_bytes: 1024,
+            max_content_bytes: 10 * 1024 * 1024,
+            embedding_dim: 384,
+            seed: 0,
+            scheduling_timeout_ms: 10_000,
+        }
+    }
+}
+
+/// Content, embeddings and search queries of a namespace of the workload
+#[derive(Debug, Clone)]
+pub struct NamespaceWorkload {
+    pub namespace: String,
+    pub graph: ExtractionGraph,
+    pub content: Vec<ContentMetadata>,
+    pub embeddings: Vec<Vec<f32>>,
+    pub queries: Vec<Vec<f32>>,
+}
+
+impl NamespaceWorkload {
+    pub fn index_name(&self) -> String {
+        format!("{}.{}", self.namespace, BENCH_GRAPH)
+    }
+}
+
+/// Synthetic workload generated from a `BenchConfig`
+#[derive(Debug, Clone)]
+pub struct Workload {
+    pub namespaces: Vec<NamespaceWorkload>,
+}
+
+impl Workload {
+    pub fn generate(config: &BenchConfig) -> Workload {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let created_at = timestamp_secs() as i64;
+        let namespaces = (0..config.namespaces)
+            .map(|i| {
+                let namespace = format!("bench_{}", i);
+                let graph = bench_graph(&namespace, config.policies);
+                let content = (0..config.content_per_namespace)
+                    .map(|j| {
+                        let id = format!("{}_content_{}", namespace, j);
+                        ContentMetadata {
+                            id: ContentMetadataId::new(&id),
+                            parent_id: None,
+                            root_content_id: None,
+                            latest: true,
+                            namespace: namespace.clone(),
+                            name: id.clone(),
+                            content_type: "text/plain".to_string(),
+                            labels: HashMap::new(),
+                            storage_url: String::new(),
+                            created_at,
+                            source: ContentSource::Ingestion,
+                            size_bytes: content_size(
+                                &mut rng,
+                                config.min_content_bytes,
+                                config.max_content_bytes,
+                            ),
+                            extraction_policy_ids: HashMap::new(),
+                            tombstoned: false,
+                            hash: id,
+                            extraction_graph_names: vec![graph.name.clone()],
+                            acl: None,
+                            tombstone_reason: None,
+                        }
+                    })
+                    .collect();
+                let embeddings = (0..config.content_per_namespace)
+                    .map(|_| random_embedding(&mut rng, config.embedding_dim))
+                    .collect();
+                let queries = (0..config.searches_per_namespace)
+                    .map(|_| random_embedding(&mut rng, config.embedding_dim))
+                    .collect();
+                NamespaceWorkload {
+                    namespace,
+                    graph,
+                    content,
+                    embeddings,
+                    queries,
+                }
+            })
+            .collect();
+        Workload { namespaces }
+    }
+}
+
+fn bench_graph(namespace: &str, policies: usize) -> ExtractionGraph {
+    let extraction_policies = (0..policies)
+        .map(|i| {
+            let name = format!("policy_{}", i);
+            ExtractionPolicy {
+                id: ExtractionPolicy::create_id(BENCH_GRAPH, &name, namespace),
+                graph_name: BENCH_GRAPH.to_string(),
+                name,
+                namespace: namespace.to_string(),
+                extractor: BENCH_EXTRACTOR.to_string(),
+                filters: HashMap::new(),
+                input_params: json!({}),
+                output_table_mapping: HashMap::new(),
+                content_source: ExtractionPolicyContentSource::Ingestion,
+                index_attributes: Default::default(),
+                version: 1,
+                version_strategy: Default::default(),
+            }
+        })
+        .collect();
+    ExtractionGraph {
+        id: ExtractionGraph::create_id(BENCH_GRAPH, namespace),
+        namespace: namespace.to_string(),
+        name: BENCH_GRAPH.to_string(),
+        extraction_policies,
+    }
+}
+
+fn noop_extractor() -> internal_api::ExtractorDescription {
+    internal_api::ExtractorDescription {
+        name: BENCH_EXTRACTOR.to_string(),
+        description: "does nothing, used by benchmark runs".to_string(),
+        input_params: json!({}),
+        outputs: HashMap::new(),
+        input_mime_types: vec!["*/*".to_string()],
+        requirements: HashMap::new(),
+    }
+}
+
+/// Size drawn from a log-uniform distribution between the bounds
+fn content_size(rng: &mut StdRng, min: u64, max: u64) -> u64 {
+    let (min, max) = (min.max(1), max.max(min).max(1));
+    let size = rng
+        .gen_range((min as f64).ln()..=(max as f64).ln())
+        .exp()
+        .round() as u64;
+    size.clamp(min, max)
+}
+
+fn random_embedding(rng: &mut StdRng, dim: usize) -> Vec<f32> {
+    (0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect()
+}
+
+/// Latency percentiles of a stage of the workload, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    fn from_samples(samples: &mut [Duration]) -> LatencySummary {
+        samples.sort();
+        let percentile = |p: f64| {
+            //  Nearest rank
+            let rank = ((p * samples.len() as f64).ceil() as usize).max(1);
+            samples
+                .get(rank - 1)
+                .map_or(0.0, |sample| sample.as_nanos() as f64 / 1e6)
+        };
+        LatencySummary {
+            count: samples.len(),
+            p50_ms: percentile(0.5),
+            p95_ms: percentile(0.95),
+            p99_ms: percentile(0.99),
+            max_ms: percentile(1.0),
+        }
+    }
+}
+
+/// Latencies measured during a run, by stage
+#[derive(Debug, Default)]
+pub struct LatencySamples {
+    samples: BTreeMap<&'static str, Vec<Duration>>,
+    errors: BTreeMap<&'static str, usize>,
+}
+
+impl LatencySamples {
+    pub fn record(&mut self, stage: &'static str, latency: Duration) {
+        self.samples.entry(stage).or_default().push(latency);
+    }
+
+    /// Times the future as a sample of the stage, failures are counted
+    /// rather than timed
+    pub async fn time<T>(
+        &mut self,
+        stage: &'static str,
+        future: impl Future<Output = Result<T>>,
+    ) -> Option<T> {
+        let start = Instant::now();
+        match future.await {
+            Ok(value) => {
+                self.record(stage, start.elapsed());
+                Some(value)
+            }
+            Err(e) => {
+                warn!("bench {} failed: {}", stage, e);
+                *self.errors.entry(stage).or_default() += 1;
+                None
+            }
+        }
+    }
+
+    pub fn summaries(&mut self) -> BTreeMap<String, LatencySummary> {
+        self.samples
+            .iter_mut()
+            .map(|(stage, samples)| (stage.to_string(), LatencySummary::from_samples(samples)))
+            .collect()
+    }
+}
+
+/// Machine readable result of a benchmark run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub config: BenchConfig,
+    pub elapsed_ms: f64,
+    pub stages: BTreeMap<String, LatencySummary>,
+    /// Number of failed operations, by stage
+    pub errors: BTreeMap<String, usize>,
+}
+
+/// Runs the workload of the config against the coordinator and the vector
+/// database. Only the metadata of content is ingested, content isn't
+/// written to blob storage and its tasks never run.
+pub async fn run(
+    coordinator: &Coordinator,
+    vector_db: VectorDBTS,
+    config: BenchConfig,
+) -> Result<BenchReport> {
+    let start = Instant::now();
+    let workload = Workload::generate(&config);
+    let mut samples = LatencySamples::default();
+    coordinator
+        .register_executor("localhost:0", BENCH_EXECUTOR, vec![noop_extractor()])
+        .await?;
+    let scheduling_timeout = Duration::from_millis(config.scheduling_timeout_ms);
+
+    for workload in &workload.namespaces {
+        info!("running bench workload of namespace {}", workload.namespace);
+        coordinator.create_namespace(&workload.namespace).await?;
+        if !workload.graph.extraction_policies.is_empty() {
+            coordinator
+                .create_extraction_graph(workload.graph.clone())
+                .await?;
+        }
+        vector_db
+            .create_index(CreateIndexParams {
+                vectordb_index_name: workload.index_name(),
+                vector_dim: config.embedding_dim as u64,
+                distance: IndexDistance::Cosine,
+                unique_params: None,
+            })
+            .await?;
+
+        for (content, embedding) in workload.content.iter().zip(&workload.embeddings) {
+            let applied = samples
+                .time(
+                    INGESTION_APPLY,
+                    coordinator.create_content_metadata(vec![content.clone()]),
+                )
+                .await;
+            if applied.is_none() {
+                continue;
+            }
+            if !workload.graph.extraction_policies.is_empty() {
+                samples
+                    .time(
+                        TASK_SCHEDULING,
+                        wait_for_tasks(coordinator, &content.id, scheduling_timeout),
+                    )
+                    .await;
+            }
+            let chunk = VectorChunk::new(
+                content.id.id.clone(),
+                embedding.clone(),
+                HashMap::new(),
+                None,
+                content,
+            );
+            vector_db
+                .add_embedding(&workload.index_name(), vec![chunk])
+                .await?;
+        }
+
+        for query in &workload.queries {
+            samples
+                .time(
+                    SEARCH,
+                    vector_db.search(workload.index_name(), query.clone(), SEARCH_K, vec![], true),
+                )
+                .await;
+        }
+    }
+
+    Ok(BenchReport {
+        config,
+        elapsed_ms: start.elapsed().as_nanos() as f64 / 1e6,
+        stages: samples.summaries(),
+        errors: samples
+            .errors
+            .iter()
+            .map(|(stage, count)| (stage.to_string(), *count))
+            .collect(),
+    })
+}
+
+/// Runs the scheduler until the content has tasks. The bench executor never
+/// finishes them, so they stay pending.
+async fn wait_for_tasks(
+    coordinator: &Coordinator,
+    content_id: &ContentMetadataId,
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        coordinator.run_scheduler().await?;
+        if !coordinator
+            .shared_state
+            .are_content_tasks_completed(content_id)
+            .await
+        {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(anyhow!("no tasks were created for content {}", content_id));
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{test_utils::RaftTestCluster, vectordbs::in_memory::InMemoryDb};
+
+    #[test]
+    fn test_workload_is_deterministic() {
+        let config = BenchConfig {
+            namespaces: 2,
+            content_per_namespace: 20,
+            min_content_bytes: 100,
+            max_content_bytes: 1_000_000,
+            ..Default::default()
+        };
+        let workload = Workload::generate(&config);
+        let sizes = |workload: &Workload| -> Vec<u64> {
+            workload
+                .namespaces
+                .iter()
+                .flat_map(|ns| ns.content.iter().map(|c| c.size_bytes))
+                .collect()
+        };
+        assert_eq!(sizes(&workload), sizes(&Workload::generate(&config)));
+        assert_eq!(sizes(&workload).len(), 40);
+        assert!(sizes(&workload)
+            .iter()
+            .all(|size| (100..=1_000_000).contains(size)));
+        assert_eq!(workload.namespaces[1].graph.extraction_policies.len(), 2);
+        assert_eq!(workload.namespaces[0].queries.len(), 50);
+        assert_eq!(workload.namespaces[0].queries[0].len(), 384);
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+        let summary = LatencySummary::from_samples(&mut samples);
+        assert_eq!(
+            summary,
+            LatencySummary {
+                count: 100,
+                p50_ms: 50.0,
+                p95_ms: 95.0,
+                p99_ms: 99.0,
+                max_ms: 100.0,
+            }
+        );
+        assert_eq!(LatencySummary::from_samples(&mut []).p99_ms, 0.0);
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_bench_smoke() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let coordinator = cluster._get_coordinator_node(0)?;
+        let config = BenchConfig {
+            namespaces: 1,
+            content_per_namespace: 3,
+            policies: 1,
+            searches_per_namespace: 2,
+            embedding_dim: 4,
+            ..Default::default()
+        };
+        let report = run(&coordinator, Arc::new(InMemoryDb::new()), config.clone()).await?;
+
+        assert_eq!(report.config, config);
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        let counts: Vec<(&str, usize)> = report
+            .stages
+            .iter()
+            .map(|(stage, summary)| (stage.as_str(), summary.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(INGESTION_APPLY, 3), (SEARCH, 2), (TASK_SCHEDULING, 3)]
+        );
+        let report = serde_json::to_value(&report)?;
+        for field in ["p50_ms", "p95_ms", "p99_ms", "max_ms"] {
+            assert!(report["stages"][SEARCH][field].is_number());
+        }
+        Ok(())
+    }
+}
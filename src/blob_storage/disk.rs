@@ -13,6 +13,10 @@ use tokio::{
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use super::{
+    listing_page,
+    BlobListing,
+    BlobObject,
+    BlobStorageLister,
     BlobStoragePartWriter,
     BlobStorageReader,
     BlobStorageWriter,
@@ -175,6 +179,37 @@ impl BlobStoragePartWriter for DiskStorage {
     }
 }
 
+#[async_trait]
+impl BlobStorageLister for DiskStorage {
+    async fn list(&self, cursor: Option<&str>, limit: usize) -> Result<BlobListing> {
+        let mut objects = Vec::new();
+        for entry in std::fs::read_dir(&self.config.path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            // Skips the directory of files which are still being written
+            if !metadata.is_file() {
+                continue;
+            }
+            let key = entry.file_name().to_string_lossy().to_string();
+            if cursor.is_some_and(|cursor| key.as_str() <= cursor) {
+                continue;
+            }
+            let last_modified = metadata
+                .modified()?
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs();
+            objects.push(BlobObject {
+                url: format!("file://{}/{}", self.config.path, key),
+                key,
+                last_modified,
+            });
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        objects.truncate(limit + 1);
+        Ok(listing_page(objects, limit))
+    }
+}
+
 pub struct DiskFileReader {}
 impl DiskFileReader {
     pub fn new() -> Self {
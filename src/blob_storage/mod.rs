@@ -17,6 +17,8 @@ use crate::server_config::ServerConfig;
 pub mod disk;
 pub mod http;
 pub mod s3;
+pub mod scavenger;
+pub mod signing;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct S3Config {
@@ -56,6 +58,42 @@ pub trait BlobStoragePartWriter {
     async fn writer(&self, key: &str) -> Result<StoragePartWriter>;
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobObject {
+    pub key: String,
+    /// The url content metadata refers to the blob with
+    pub url: String,
+    /// Unix timestamp in seconds of the last write of the blob
+    pub last_modified: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct BlobListing {
+    pub objects: Vec<BlobObject>,
+    /// Key to continue listing after, None once every blob has been listed
+    pub next_cursor: Option<String>,
+}
+
+#[async_trait]
+pub trait BlobStorageLister {
+    /// Lists up to `limit` blobs whose keys come after `cursor`, in key order
+    async fn list(&self, cursor: Option<&str>, limit: usize) -> Result<BlobListing>;
+}
+
+/// Builds a listing page from up to `limit + 1` blobs in key order
+fn listing_page(mut objects: Vec<BlobObject>, limit: usize) -> BlobListing {
+    let next_cursor = if objects.len() > limit {
+        objects.truncate(limit);
+        objects.last().map(|object| object.key.clone())
+    } else {
+        None
+    };
+    BlobListing {
+        objects,
+        next_cursor,
+    }
+}
+
 type BlobStorageReaderTS = Arc<dyn BlobStorageReader + Sync + Send>;
 
 pub trait BlobStorageReader {
@@ -187,6 +225,25 @@ impl BlobStorageWriter for BlobStorage {
     }
 }
 
+#[async_trait]
+impl BlobStorageLister for BlobStorage {
+    async fn list(&self, cursor: Option<&str>, limit: usize) -> Result<BlobListing> {
+        if let Some(s3) = self.config.s3.as_ref() {
+            return self.s3_storage(s3)?.list(cursor, limit).await;
+        }
+        disk::DiskStorage::new(
+            self.config
+                .disk
+                .clone()
+                .unwrap_or_else(|| DiskStorageConfig {
+                    path: "blobs".to_string(),
+                }),
+        )?
+        .list(cursor, limit)
+        .await
+    }
+}
+
 fn parse_s3_url(s3_url: &str) -> Result<(&str, &str), &str> {
     let Some(("s3", url)) = s3_url.split_once("://") else {
         return Err("Invalid S3 URL format");
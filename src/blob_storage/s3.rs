@@ -11,7 +11,16 @@ use object_store::{
 use tokio::{io::AsyncWriteExt, sync::mpsc};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
-use super::{BlobStoragePartWriter, BlobStorageReader, BlobStorageWriter, StoragePartWriter};
+use super::{
+    listing_page,
+    BlobListing,
+    BlobObject,
+    BlobStorageLister,
+    BlobStoragePartWriter,
+    BlobStorageReader,
+    BlobStorageWriter,
+    StoragePartWriter,
+};
 use crate::{blob_storage::PutResult, server_config::ServerConfig};
 
 pub struct S3Storage {
@@ -70,6 +79,29 @@ impl BlobStoragePartWriter for S3Storage {
     }
 }
 
+#[async_trait]
+impl BlobStorageLister for S3Storage {
+    async fn list(&self, cursor: Option<&str>, limit: usize) -> Result<BlobListing> {
+        //  S3 lists keys in lexicographic order
+        let stream = match cursor {
+            Some(cursor) => self.client.list_with_offset(None, &cursor.into()),
+            None => self.client.list(None),
+        };
+        let metas = stream.take(limit + 1).collect::<Vec<_>>().await;
+        let mut objects = Vec::with_capacity(metas.len());
+        for meta in metas {
+            let meta = meta.map_err(|e| anyhow!("unable to list blobs: {}", e))?;
+            let key = meta.location.to_string();
+            objects.push(BlobObject {
+                url: format!("s3://{}/{}", self.bucket, key),
+                key,
+                last_modified: meta.last_modified.timestamp().max(0) as u64,
+            });
+        }
+        Ok(listing_page(objects, limit))
+    }
+}
+
 pub struct S3FileReader {
     client: Arc<dyn ObjectStore>,
     key: String,
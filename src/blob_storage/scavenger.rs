@@ -0,0 +1,352 @@
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use indexify_proto::indexify_coordinator::GetReferencedStorageUrlsRequest;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+use super::{BlobStorageLister, BlobStorageWriter};
+use crate::{coordinator_client::CoordinatorClient, server_config::BlobScavengerConfig};
+
+/// Looks up which blobs are referenced by content
+#[async_trait]
+pub trait StorageUrlReferences {
+    /// Returns the storage urls referenced by any version of any content,
+    /// including tombstoned content which has not been garbage collected
+    async fn referenced(&self, storage_urls: Vec<String>) -> Result<HashSet<String>>;
+}
+
+#[async_trait]
+impl StorageUrlReferences for CoordinatorClient {
+    async fn referenced(&self, storage_urls: Vec<String>) -> Result<HashSet<String>> {
+        let referenced = self
+            .get()
+            .await?
+            .get_referenced_storage_urls(GetReferencedStorageUrlsRequest { storage_urls })
+            .await?
+            .into_inner()
+            .storage_urls;
+        Ok(referenced.into_iter().collect())
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ScavengeReport {
+    pub scanned: usize,
+    /// Urls of the blobs which are older than the safety window and not
+    /// referenced by any content
+    pub orphaned: Vec<String>,
+    pub deleted: usize,
+}
+
+/// Finds blobs which no content refers to, left behind when a server stops
+/// between uploading a blob and creating its content. Orphaned blobs are
+/// reported, and deleted when the configuration allows it.
+pub struct BlobScavenger<S, R> {
+    config: BlobScavengerConfig,
+    storage: Arc<S>,
+    references: Arc<R>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl<S, R> BlobScavenger<S, R>
+where
+    S: BlobStorageLister + BlobStorageWriter + Send + Sync + 'static,
+    R: StorageUrlReferences + Send + Sync + 'static,
+{
+    pub fn new(config: BlobScavengerConfig, storage: Arc<S>, references: Arc<R>) -> Self {
+        Self {
+            config,
+            storage,
+            references,
+        }
+    }
+
+    fn load_cursor(&self) -> Result<Option<String>> {
+        match std::fs::read_to_string(&self.config.cursor_path) {
+            Ok(cursor) if !cursor.is_empty() => Ok(Some(cursor)),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_cursor(&self, cursor: Option<&str>) -> Result<()> {
+        match cursor {
+            Some(cursor) => {
+                // Written to a temporary file first so a crash never leaves a
+                // partial cursor behind
+                let tmp_path = format!("{}.tmp", self.config.cursor_path);
+                std::fs::write(&tmp_path, cursor)?;
+                std::fs::rename(&tmp_path, &self.config.cursor_path)?;
+            }
+            None => {
+                if let Err(e) = std::fs::remove_file(&self.config.cursor_path) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        return Err(e.into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the page of blobs after the persisted cursor and moves the
+    /// cursor past it. Returns false once the pass over blob storage is
+    /// complete.
+    pub async fn scan_page(&self, now: u64, report: &mut ScavengeReport) -> Result<bool> {
+        let cursor = self.load_cursor()?;
+        let listing = self
+            .storage
+            .list(cursor.as_deref(), self.config.page_size)
+            .await?;
+        let urls = listing.objects.iter().map(|o| o.url.clone()).collect();
+        let referenced = self.references.referenced(urls).await?;
+        for object in &listing.objects {
+            report.scanned += 1;
+            if referenced.contains(&object.url) ||
+                object.last_modified + self.config.safety_window_secs > now
+            {
+                continue;
+            }
+            report.orphaned.push(object.url.clone());
+            if !self.config.delete {
+                info!("found orphaned blob {}", object.url);
+                continue;
+            }
+            match self.storage.delete(&object.url).await {
+                Ok(()) => {
+                    info!("deleted orphaned blob {}", object.url);
+                    report.deleted += 1;
+                }
+                Err(e) => warn!("unable to delete orphaned blob {}: {}", object.url, e),
+            }
+        }
+        self.save_cursor(listing.next_cursor.as_deref())?;
+        if !listing.objects.is_empty() && self.config.max_blobs_per_sec > 0 {
+            let secs = listing.objects.len() as f64 / self.config.max_blobs_per_sec as f64;
+            tokio::time::sleep(Duration::from_secs_f64(secs)).await;
+        }
+        Ok(listing.next_cursor.is_some())
+    }
+
+    /// Runs a pass over blob storage, resuming from the persisted cursor
+    pub async fn run_pass(&self) -> Result<ScavengeReport> {
+        let mut report = ScavengeReport::default();
+        while self.scan_page(now_secs(), &mut report).await? {}
+        info!(
+            "blob scavenger pass complete, scanned: {}, orphaned: {}, deleted: {}",
+            report.scanned,
+            report.orphaned.len(),
+            report.deleted
+        );
+        Ok(report)
+    }
+
+    pub fn start(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let interval = Duration::from_secs(self.config.interval_secs);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    result = self.run_pass() => {
+                        if let Err(e) = result {
+                            error!("blob scavenger pass failed: {}", e);
+                        }
+                    }
+                    _ = shutdown_rx.changed() => {
+                        info!("shutting down blob scavenger");
+                        return;
+                    }
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = shutdown_rx.changed() => {
+                        info!("shutting down blob scavenger");
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::blob_storage::{listing_page, BlobListing, BlobObject, PutResult};
+
+    const NOW: u64 = 1_000_000;
+
+    #[derive(Default)]
+    struct FakeBlobStore {
+        objects: Mutex<Vec<BlobObject>>,
+    }
+
+    impl FakeBlobStore {
+        fn add(&self, key: &str, last_modified: u64) {
+            self.objects.lock().unwrap().push(BlobObject {
+                key: key.to_string(),
+                url: format!("file:///blobs/{}", key),
+                last_modified,
+            });
+        }
+
+        fn urls(&self) -> Vec<String> {
+            self.objects
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|o| o.url.clone())
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl BlobStorageLister for FakeBlobStore {
+        async fn list(&self, cursor: Option<&str>, limit: usize) -> Result<BlobListing> {
+            let mut objects: Vec<_> = self
+                .objects
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|o| cursor.map_or(true, |cursor| o.key.as_str() > cursor))
+                .cloned()
+                .collect();
+            objects.sort_by(|a, b| a.key.cmp(&b.key));
+            objects.truncate(limit + 1);
+            Ok(listing_page(objects, limit))
+        }
+    }
+
+    #[async_trait]
+    impl BlobStorageWriter for FakeBlobStore {
+        async fn put(
+            &self,
+            key: &str,
+            _data: impl futures::Stream<Item = Result<Bytes>> + Send + Unpin,
+        ) -> Result<PutResult> {
+            Err(anyhow::anyhow!("the scavenger doesn't write blobs: {}", key))
+        }
+
+        async fn delete(&self, key: &str) -> Result<()> {
+            self.objects.lock().unwrap().retain(|o| o.url != key);
+            Ok(())
+        }
+    }
+
+    struct FakeReferences(HashSet<String>);
+
+    #[async_trait]
+    impl StorageUrlReferences for FakeReferences {
+        async fn referenced(&self, storage_urls: Vec<String>) -> Result<HashSet<String>> {
+            Ok(storage_urls
+                .into_iter()
+                .filter(|url| self.0.contains(url))
+                .collect())
+        }
+    }
+
+    fn fake_store() -> Arc<FakeBlobStore> {
+        let store = Arc::new(FakeBlobStore::default());
+        //  Referenced blobs are never orphaned, however old they are
+        store.add("referenced-old", 0);
+        store.add("referenced-tombstoned", 0);
+        store.add("orphaned-recent", NOW - 10);
+        store.add("orphaned-old", NOW - 1000);
+        store
+    }
+
+    fn scavenger(
+        store: Arc<FakeBlobStore>,
+        delete: bool,
+        cursor_path: &str,
+    ) -> BlobScavenger<FakeBlobStore, FakeReferences> {
+        let references = FakeReferences(HashSet::from([
+            "file:///blobs/referenced-old".to_string(),
+            "file:///blobs/referenced-tombstoned".to_string(),
+        ]));
+        let config = BlobScavengerConfig {
+            enabled: true,
+            delete,
+            safety_window_secs: 100,
+            page_size: 3,
+            max_blobs_per_sec: 0,
+            cursor_path: cursor_path.to_string(),
+            ..Default::default()
+        };
+        BlobScavenger::new(config, store, Arc::new(references))
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_only_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let cursor_path = dir.path().join("cursor").to_string_lossy().to_string();
+        let store = fake_store();
+        let scavenger = scavenger(store.clone(), false, &cursor_path);
+
+        let mut report = ScavengeReport::default();
+        while scavenger.scan_page(NOW, &mut report).await.unwrap() {}
+        assert_eq!(report.scanned, 4);
+        assert_eq!(report.orphaned, vec!["file:///blobs/orphaned-old"]);
+        assert_eq!(report.deleted, 0);
+        assert_eq!(store.urls().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_delete_resumes_from_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let cursor_path = dir.path().join("cursor").to_string_lossy().to_string();
+        let store = fake_store();
+
+        let mut report = ScavengeReport::default();
+        let more = scavenger(store.clone(), true, &cursor_path)
+            .scan_page(NOW, &mut report)
+            .await
+            .unwrap();
+        assert!(more);
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.orphaned, vec!["file:///blobs/orphaned-old"]);
+        assert_eq!(report.deleted, 1);
+        assert_eq!(
+            std::fs::read_to_string(&cursor_path).unwrap(),
+            "referenced-old"
+        );
+
+        //  A new scavenger, as after a restart, continues after the cursor
+        let mut report = ScavengeReport::default();
+        let more = scavenger(store.clone(), true, &cursor_path)
+            .scan_page(NOW, &mut report)
+            .await
+            .unwrap();
+        assert!(!more);
+        assert_eq!(report.scanned, 1);
+        assert!(report.orphaned.is_empty());
+        assert!(std::fs::metadata(&cursor_path).is_err());
+
+        let mut urls = store.urls();
+        urls.sort();
+        assert_eq!(
+            urls,
+            vec![
+                "file:///blobs/orphaned-recent",
+                "file:///blobs/referenced-old",
+                "file:///blobs/referenced-tombstoned",
+            ]
+        );
+    }
+}
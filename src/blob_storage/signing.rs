@@ -0,0 +1,290 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use object_store::{aws::AmazonS3Builder, path::Path, signer::Signer};
+use sha2::{Digest, Sha256};
+
+use super::parse_s3_url;
+use crate::server_config::{BlobUrlSigningConfig, ServerConfig};
+
+const HMAC_BLOCK_SIZE: usize = 64;
+
+/// Path of the blob server of ingestion servers, which serves blobs stored
+/// on local disk to the holders of signed urls
+pub const BLOB_SERVER_PATH: &str = "/blobs";
+
+/// A url the content of a blob can be downloaded from until `expires_at`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedUrl {
+    pub url: String,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("signed url expired")]
+    Expired,
+    #[error("invalid signature")]
+    Invalid,
+}
+
+/// Signs the storage urls of blobs so executors download them directly from
+/// blob storage instead of through an ingestion server. Blobs on S3 get
+/// presigned S3 urls, blobs on local disk get urls of the blob server signed
+/// with the configured key.
+#[derive(Clone)]
+pub struct BlobUrlSigner {
+    config: BlobUrlSigningConfig,
+    s3_region: String,
+}
+
+impl Debug for BlobUrlSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobUrlSigner")
+            .field("key", &"<hidden>")
+            .field("ttl_secs", &self.config.ttl_secs)
+            .field("blob_server_url", &self.config.blob_server_url)
+            .finish()
+    }
+}
+
+impl BlobUrlSigner {
+    pub fn new(config: BlobUrlSigningConfig, s3_region: &str) -> Self {
+        Self {
+            config,
+            s3_region: s3_region.to_string(),
+        }
+    }
+
+    /// Returns the signer of the server, None when url signing is disabled
+    pub fn from_server_config(config: &ServerConfig) -> Option<Self> {
+        let signing = config.blob_url_signing.clone()?;
+        let s3_region = config
+            .blob_storage
+            .s3
+            .as_ref()
+            .map(|s3| s3.region.as_str())
+            .unwrap_or("us-east-1");
+        Some(Self::new(signing, s3_region))
+    }
+
+    /// Signs the storage url for downloads until `now` plus the configured
+    /// ttl. Returns None for content which isn't stored in blob storage, such
+    /// as content ingested from http urls.
+    pub async fn sign(&self, storage_url: &str, now: u64) -> Result<Option<SignedUrl>> {
+        let expires_at = now + self.config.ttl_secs;
+        if storage_url.starts_with("s3://") {
+            let (bucket, key) = parse_s3_url(storage_url)
+                .map_err(|err| anyhow!("unable to parse s3 url: {}", err))?;
+            let url = AmazonS3Builder::from_env()
+                .with_region(self.s3_region.as_str())
+                .with_bucket_name(bucket)
+                .build()
+                .context("unable to build S3 builder")?
+                .signed_url(
+                    reqwest::Method::GET,
+                    &Path::from(key),
+                    Duration::from_secs(self.config.ttl_secs),
+                )
+                .await?;
+            return Ok(Some(SignedUrl {
+                url: url.to_string(),
+                expires_at,
+            }));
+        }
+        if storage_url.starts_with("file://") {
+            let url = url::Url::parse_with_params(
+                &format!(
+                    "{}{}",
+                    self.config.blob_server_url.trim_end_matches('/'),
+                    BLOB_SERVER_PATH
+                ),
+                &[
+                    ("url", storage_url.to_string()),
+                    ("expires", expires_at.to_string()),
+                    ("signature", self.signature(storage_url, expires_at)),
+                ],
+            )?;
+            return Ok(Some(SignedUrl {
+                url: url.to_string(),
+                expires_at,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Verifies a url of the blob server signed by `sign`
+    pub fn verify(
+        &self,
+        storage_url: &str,
+        expires_at: u64,
+        signature: &str,
+        now: u64,
+    ) -> Result<(), SignatureError> {
+        let expected = self.signature(storage_url, expires_at);
+        //  Compared without returning early so the time taken doesn't reveal
+        //  how much of the signature matched
+        let matches = expected.len() == signature.len() &&
+            expected
+                .bytes()
+                .zip(signature.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b)) ==
+                0;
+        if !matches {
+            return Err(SignatureError::Invalid);
+        }
+        if now >= expires_at {
+            return Err(SignatureError::Expired);
+        }
+        Ok(())
+    }
+
+    fn signature(&self, storage_url: &str, expires_at: u64) -> String {
+        let message = format!("{}\n{}", storage_url, expires_at);
+        format!(
+            "{:x}",
+            hmac_sha256(self.config.key.as_bytes(), message.as_bytes())
+        )
+    }
+}
+
+/// HMAC-SHA256 as defined in RFC 2104
+fn hmac_sha256(key: &[u8], message: &[u8]) -> sha2::digest::Output<Sha256> {
+    let mut block = [0u8; HMAC_BLOCK_SIZE];
+    if key.len() > HMAC_BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> BlobUrlSigner {
+        BlobUrlSigner::new(
+            BlobUrlSigningConfig {
+                key: "test-key".to_string(),
+                ttl_secs: 3600,
+                blob_server_url: "http://localhost:8900/".to_string(),
+            },
+            "us-east-1",
+        )
+    }
+
+    fn query(url: &str) -> (String, u64, String) {
+        let url = url::Url::parse(url).unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+                .unwrap()
+        };
+        (
+            param("url"),
+            param("expires").parse().unwrap(),
+            param("signature"),
+        )
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        //  Test case 2 of RFC 4231
+        assert_eq!(
+            format!(
+                "{:x}",
+                hmac_sha256(b"Jefe", b"what do ya want for nothing?")
+            ),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_signed_url() {
+        let signer = signer();
+        let signed = signer
+            .sign("file:///tmp/blobs/content_1", 1000)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(signed.expires_at, 4600);
+        assert!(signed.url.starts_with("http://localhost:8900/blobs?"));
+
+        let (storage_url, expires_at, signature) = query(&signed.url);
+        assert_eq!(storage_url, "file:///tmp/blobs/content_1");
+        assert_eq!(expires_at, 4600);
+        assert_eq!(
+            signer.verify(&storage_url, expires_at, &signature, 1000),
+            Ok(())
+        );
+
+        //  The url, the expiry and the signature can't be changed
+        assert_eq!(
+            signer.verify("file:///tmp/blobs/content_2", expires_at, &signature, 1000),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            signer.verify(&storage_url, expires_at + 3600, &signature, 1000),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            signer.verify(&storage_url, expires_at, &signature[1..], 1000),
+            Err(SignatureError::Invalid)
+        );
+
+        //  Urls signed with another key are rejected
+        let other = BlobUrlSigner::new(
+            BlobUrlSigningConfig {
+                key: "other-key".to_string(),
+                ..signer.config.clone()
+            },
+            "us-east-1",
+        );
+        assert_eq!(
+            other.verify(&storage_url, expires_at, &signature, 1000),
+            Err(SignatureError::Invalid)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_signed_url() {
+        let signer = signer();
+        let signed = signer
+            .sign("file:///tmp/blobs/content_1", 1000)
+            .await
+            .unwrap()
+            .unwrap();
+        let (storage_url, expires_at, signature) = query(&signed.url);
+        assert_eq!(
+            signer.verify(&storage_url, expires_at, &signature, 4599),
+            Ok(())
+        );
+        assert_eq!(
+            signer.verify(&storage_url, expires_at, &signature, 4600),
+            Err(SignatureError::Expired)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_content_is_not_signed() {
+        assert_eq!(
+            signer()
+                .sign("https://example.com/file.pdf", 1000)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
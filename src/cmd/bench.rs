@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use clap::Args as ClapArgs;
+
+use super::GlobalArgs;
+use crate::{
+    bench::{self, BenchConfig},
+    coordinator::Coordinator,
+    coordinator_client::CoordinatorClient,
+    garbage_collector::GarbageCollector,
+    prelude::*,
+    server_config::ServerConfig,
+    state::App,
+    vectordbs::in_memory::InMemoryDb,
+};
+
+/// Runs a synthetic workload against a coordinator started in process, with
+/// its state in a new temporary directory, and reports latency percentiles
+#[derive(Debug, ClapArgs)]
+pub struct Args {
+    /// path to the server config file, the defaults when not given
+    #[arg(long, short = 'c')]
+    config_path: Option<String>,
+
+    /// path to a json file with the shape of the workload
+    #[arg(long, short = 'w')]
+    workload_path: Option<String>,
+
+    /// path the json report is written to, stdout when not given
+    #[arg(long, short = 'o')]
+    output_path: Option<String>,
+}
+
+impl Args {
+    pub async fn run(self, _: GlobalArgs) {
+        let Self {
+            config_path,
+            workload_path,
+            output_path,
+        } = self;
+
+        let mut config = if let Some(config_path) = config_path {
+            ServerConfig::from_path(&config_path)
+                .unwrap_or_else(|e| panic!("failed to load config file `{}`: {}", config_path, e))
+        } else {
+            ServerConfig::default()
+        };
+        let bench_config = if let Some(workload_path) = workload_path {
+            std::fs::read_to_string(&workload_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|workload| Ok(serde_json::from_str(&workload)?))
+                .unwrap_or_else(|e| panic!("failed to load workload `{}`: {}", workload_path, e))
+        } else {
+            BenchConfig::default()
+        };
+        let state_path = std::env::temp_dir().join(format!("indexify-bench-{}", nanoid::nanoid!()));
+        config.state_store.path = Some(state_path.to_string_lossy().to_string());
+        let config = Arc::new(config);
+
+        info!(
+            "starting bench coordinator, state in {}",
+            state_path.display()
+        );
+        let garbage_collector = GarbageCollector::new();
+        let shared_state = App::new(
+            config.clone(),
+            None,
+            garbage_collector.clone(),
+            &config.coordinator_addr,
+            Arc::new(crate::metrics::init_provider()),
+        )
+        .await
+        .expect("failed to create bench coordinator state");
+        shared_state
+            .initialize_raft()
+            .await
+            .expect("failed to initialize raft");
+        let coordinator = Coordinator::new(
+            shared_state,
+            CoordinatorClient::new(config.clone()),
+            garbage_collector,
+        );
+
+        let report = bench::run(&coordinator, Arc::new(InMemoryDb::new()), bench_config)
+            .await
+            .expect("failed to run bench");
+        let report = serde_json::to_string_pretty(&report).expect("failed to encode report");
+        match output_path {
+            Some(output_path) => std::fs::write(&output_path, report)
+                .unwrap_or_else(|e| panic!("failed to write report `{}`: {}", output_path, e)),
+            None => println!("{}", report),
+        }
+        if let Err(e) = std::fs::remove_dir_all(&state_path) {
+            warn!(
+                "unable to remove bench state {}: {}",
+                state_path.display(),
+                e
+            );
+        }
+    }
+}
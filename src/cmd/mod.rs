@@ -1,5 +1,6 @@
 use clap::{Args, Parser, Subcommand};
 
+mod bench;
 mod coordinator;
 mod init_compose;
 mod init_config;
@@ -28,6 +29,8 @@ pub enum Commands {
     Coordinator(coordinator::Args),
     InitConfig(init_config::Args),
     InitCompose(init_compose::Args),
+    /// Measure latencies of a synthetic workload
+    Bench(bench::Args),
 }
 
 /// The main CLI struct. This is the root of the CLI tree.
@@ -49,6 +52,7 @@ impl Cli {
             Commands::Coordinator(args) => args.run(self.global_args).await,
             Commands::InitConfig(args) => args.run(self.global_args).await,
             Commands::InitCompose(args) => args.run(self.global_args).await,
+            Commands::Bench(args) => args.run(self.global_args).await,
         }
     }
 }
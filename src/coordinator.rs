@@ -1,7 +1,8 @@
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet},
     hash::{Hash, Hasher},
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, UNIX_EPOCH},
     vec,
 };
 
@@ -11,33 +12,79 @@ use indexify_proto::indexify_coordinator::{self, CreateContentStatus};
 use internal_api::{
     ContentMetadataId,
     ExtractionGraph,
+    ExtractionGraphBuilder,
+    ExtractionPolicyBuilder,
     ExtractionPolicyId,
+    ExtractionPolicyStatus,
     GarbageCollectionTask,
     OutputSchema,
     ServerTaskType,
     StateChange,
+    StateChangeId,
     StructuredDataSchema,
+    TombstoneReason,
 };
+use rand::seq::IteratorRandom;
 use tokio::sync::{broadcast, watch::Receiver};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::{
+    blob_storage::signing::SignedUrl,
     coordinator_client::CoordinatorClient,
     coordinator_filters::*,
     forwardable_coordinator::ForwardableCoordinator,
     garbage_collector::GarbageCollector,
+    index_events::IndexEvent,
     metrics::Timer,
     scheduler::Scheduler,
-    state::{store::requests::StateChangeProcessed, RaftMetrics, SharedState},
+    state::{
+        store::{
+            requests::StateChangeProcessed,
+            state_machine_objects::{IndexOverview, IngestionKeyRecord},
+        },
+        RaftMetrics,
+        SharedState,
+    },
     task_allocator::TaskAllocator,
-    utils,
+    utils::{Clock, SystemClock},
+    vectordbs::Filter,
 };
 
+/// Number of search feedback entries in a page unless a limit is requested
+pub const DEFAULT_SEARCH_FEEDBACK_PAGE_SIZE: usize = 100;
+
+/// Largest page of search feedback which can be requested
+pub const MAX_SEARCH_FEEDBACK_PAGE_SIZE: usize = 1000;
+
+/// Largest difference between the time an executor reports and the clock of
+/// the coordinator before the executor is flagged as having a skewed clock
+pub const MAX_EXECUTOR_CLOCK_SKEW_SECS: u64 = 60;
+
+/// Number of times the scheduler fails to handle a state change before it
+/// skips the change, until the change is reprocessed or discarded. Changes
+/// are only skipped when the state change admin endpoints are enabled.
+pub const MAX_STATE_CHANGE_ATTEMPTS: u32 = 5;
+
+/// Last recorded executor availability of an extractor, along with the time
+/// a different availability was first observed if one is pending.
+struct ExtractorAvailability {
+    available: bool,
+    pending_since: Option<u64>,
+}
+
 pub struct Coordinator {
     pub shared_state: SharedState,
     scheduler: Scheduler,
     garbage_collector: Arc<GarbageCollector>,
     forwardable_coordinator: ForwardableCoordinator,
+    extractor_availability: Mutex<HashMap<String, ExtractorAvailability>>,
+    executor_clock_skews: Mutex<HashMap<String, i64>>,
+    // Failed attempts to handle each state change
+    state_change_failures: Mutex<HashMap<StateChangeId, u32>>,
+    // Content whose tasks are left out of executor heartbeats, along with the
+    // time the hold expires
+    held_content: Mutex<HashMap<String, Instant>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl Coordinator {
@@ -45,15 +92,36 @@ impl Coordinator {
         shared_state: SharedState,
         coordinator_client: CoordinatorClient,
         garbage_collector: Arc<GarbageCollector>,
+    ) -> Arc<Self> {
+        Self::new_with_clock(
+            shared_state,
+            coordinator_client,
+            garbage_collector,
+            Arc::new(SystemClock),
+        )
+    }
+
+    /// Creates a coordinator which takes the time for its scheduling decisions
+    /// from `clock`
+    pub fn new_with_clock(
+        shared_state: SharedState,
+        coordinator_client: CoordinatorClient,
+        garbage_collector: Arc<GarbageCollector>,
+        clock: Arc<dyn Clock>,
     ) -> Arc<Self> {
         let task_allocator = TaskAllocator::new(shared_state.clone());
-        let scheduler = Scheduler::new(shared_state.clone(), task_allocator);
+        let scheduler = Scheduler::new(shared_state.clone(), task_allocator, clock.clone());
         let forwardable_coordinator = ForwardableCoordinator::new(coordinator_client);
         Arc::new(Self {
             shared_state,
             scheduler,
             garbage_collector,
             forwardable_coordinator,
+            extractor_availability: Mutex::new(HashMap::new()),
+            executor_clock_skews: Mutex::new(HashMap::new()),
+            state_change_failures: Mutex::new(HashMap::new()),
+            held_content: Mutex::new(HashMap::new()),
+            clock,
         })
     }
 
@@ -116,6 +184,32 @@ impl Coordinator {
         self.shared_state.get_extraction_policy(&id)
     }
 
+    /// Replaces the input params of an extraction policy after validating
+    /// them against its extractor. Tasks which reference the previous
+    /// version keep running with it unless their executor fetches the
+    /// latest version.
+    pub async fn update_extraction_policy_params(
+        &self,
+        id: ExtractionPolicyId,
+        input_params: serde_json::Value,
+    ) -> Result<internal_api::ExtractionPolicy> {
+        let extraction_policy = self.shared_state.get_extraction_policy(&id)?;
+        let extractor = self.get_extractor(&extraction_policy.extractor)?;
+        extractor.validate_input_params(&input_params)?;
+        self.shared_state
+            .update_extraction_policy_params(&id, input_params)
+            .await?;
+        self.shared_state.get_extraction_policy(&id)
+    }
+
+    /// Deletes an extraction policy, tasks already created for it keep
+    /// running
+    pub async fn delete_extraction_policy(&self, namespace: &str, id: &str) -> Result<()> {
+        self.shared_state
+            .delete_extraction_policy(namespace, id)
+            .await
+    }
+
     pub async fn list_policies(
         &self,
         namespace: &str,
@@ -123,11 +217,120 @@ impl Coordinator {
         self.shared_state.list_extraction_policy(namespace).await
     }
 
+    pub async fn get_policy_status(&self, extractor: &str) -> Result<ExtractionPolicyStatus> {
+        let executors = self
+            .shared_state
+            .get_executors_for_extractor(extractor)
+            .await?;
+        if executors.is_empty() {
+            if self
+                .scheduler
+                .pending_task_cap_reached(extractor, 0)
+                .await?
+            {
+                return Ok(ExtractionPolicyStatus::Paused);
+            }
+            return Ok(ExtractionPolicyStatus::NoExecutors);
+        }
+        Ok(ExtractionPolicyStatus::Active)
+    }
+
+    /// Status of an extraction policy. Policies whose extractor output no
+    /// longer matches their indexes are reported as incompatible before the
+    /// availability of executors is considered.
+    pub async fn get_extraction_policy_status(
+        &self,
+        policy: &internal_api::ExtractionPolicy,
+    ) -> Result<ExtractionPolicyStatus> {
+        if !self
+            .scheduler
+            .incompatible_outputs(policy)
+            .await?
+            .is_empty()
+        {
+            return Ok(ExtractionPolicyStatus::IncompatibleOutput);
+        }
+        self.get_policy_status(&policy.extractor).await
+    }
+
+    pub async fn set_policy_status(
+        &self,
+        policies: &mut [indexify_coordinator::ExtractionPolicy],
+    ) -> Result<()> {
+        for policy in policies {
+            let extraction_policy = self.shared_state.get_extraction_policy(&policy.id)?;
+            policy.status = self
+                .get_extraction_policy_status(&extraction_policy)
+                .await?
+                .to_string();
+        }
+        Ok(())
+    }
+
+    /// Records a state change for every extractor whose executor availability
+    /// has differed from the last recorded value for at least the configured
+    /// debounce period. The first observation of an extractor is recorded
+    /// without a state change.
+    pub async fn check_extractor_availability(&self) -> Result<()> {
+        let now = self.clock.now_secs();
+        let mut observed = Vec::new();
+        for extractor in self.shared_state.list_extractors().await? {
+            let available = !self
+                .shared_state
+                .get_executors_for_extractor(&extractor.name)
+                .await?
+                .is_empty();
+            observed.push((extractor.name, available));
+        }
+        let debounce_secs = self.shared_state.policy_status_config.debounce_secs;
+        let mut state_changes = Vec::new();
+        {
+            let mut availability = self.extractor_availability.lock().unwrap();
+            for (extractor, available) in observed {
+                let entry =
+                    availability
+                        .entry(extractor.clone())
+                        .or_insert(ExtractorAvailability {
+                            available,
+                            pending_since: None,
+                        });
+                if entry.available == available {
+                    entry.pending_since = None;
+                    continue;
+                }
+                let since = *entry.pending_since.get_or_insert(now);
+                if now.saturating_sub(since) < debounce_secs {
+                    continue;
+                }
+                info!(
+                    "extractor {} availability changed, available: {}",
+                    extractor, available
+                );
+                entry.available = available;
+                entry.pending_since = None;
+                state_changes.push(StateChange::new(
+                    extractor,
+                    internal_api::ChangeType::ExtractorAvailabilityChanged { available },
+                    now,
+                ));
+            }
+        }
+        if state_changes.is_empty() {
+            return Ok(());
+        }
+        self.shared_state
+            .mark_change_events_as_processed(vec![], state_changes)
+            .await
+    }
+
     pub async fn update_task(
         &self,
         task_id: &str,
         executor_id: &str,
         outcome: internal_api::TaskOutcome,
+        output_content_ids: Vec<String>,
+        policy_version: Option<u64>,
+        usage: internal_api::TaskUsage,
     ) -> Result<()> {
         info!(
             "updating task: {}, executor_id: {}, outcome: {:?}",
@@ -135,12 +338,35 @@ impl Coordinator {
         );
         let mut task = self.shared_state.task_with_id(task_id).await?;
         task.outcome = outcome;
+        task.output_content_ids = output_content_ids;
+        task.usage = usage;
+        //  Embedded input params are the ones of the version the task was created from
+        task.resolved_policy_version = policy_version.or_else(|| {
+            self.shared_state
+                .embed_task_input_params
+                .then_some(task.extraction_policy_version)
+        });
+        //  The time of the update decides the day the usage of the task is counted on
+        let update_time = UNIX_EPOCH + Duration::from_secs(self.clock.now_secs());
         self.shared_state
-            .update_task(task, Some(executor_id.to_string()))
+            .update_task_at(task, Some(executor_id.to_string()), update_time)
             .await?;
         Ok(())
     }
 
+    /// Returns the usage counters of a namespace, or of all namespaces when
+    /// none is given, for the days in the inclusive range
+    pub fn get_usage_stats(
+        &self,
+        namespace: Option<&str>,
+        from_day: u64,
+        to_day: u64,
+    ) -> Result<Vec<internal_api::UsageStats>> {
+        self.shared_state
+            .state_machine
+            .get_usage_stats(namespace, from_day, to_day)
+    }
+
     pub async fn update_gc_task(
         &self,
         gc_task_id: &str,
@@ -152,6 +378,23 @@ impl Coordinator {
         Ok(())
     }
 
+    /// Records the output tables a garbage collection task removed the
+    /// vectors from and sends the task again, so that it's resumed without
+    /// them
+    pub async fn resume_gc_task(
+        &self,
+        gc_task_id: &str,
+        completed_output_tables: Vec<String>,
+    ) -> Result<()> {
+        let mut gc_task = self.shared_state.gc_task_with_id(gc_task_id).await?;
+        gc_task.outcome = internal_api::TaskOutcome::Unknown;
+        gc_task
+            .completed_output_tables
+            .extend(completed_output_tables);
+        self.shared_state.update_gc_task(gc_task).await?;
+        Ok(())
+    }
+
     pub async fn create_namespace(&self, namespace: &str) -> Result<()> {
         match self.shared_state.namespace(namespace).await {
             Result::Ok(Some(_)) => {
@@ -172,20 +415,214 @@ impl Coordinator {
         self.shared_state.namespace(namespace).await
     }
 
+    pub async fn set_namespace_retention(
+        &self,
+        namespace: &str,
+        rules: internal_api::RetentionRules,
+    ) -> Result<()> {
+        self.shared_state
+            .set_namespace_retention(namespace, rules)
+            .await
+    }
+
+    /// Tombstones the root content expired by the retention rules of the
+    /// namespaces, along with its trees. Roots whose trees have unfinished
+    /// tasks are deferred to a later sweep. The trees are tombstoned in
+    /// batches and the expired roots are found again on every sweep, so a
+    /// sweep which stops midway is resumed by the next one.
+    pub async fn sweep_retention(&self) -> Result<RetentionSweep> {
+        let config = &self.shared_state.retention_config;
+        let now = self.clock.now_secs();
+        let mut sweep = RetentionSweep::default();
+        for (namespace, rules) in self.shared_state.get_namespace_retention_rules()? {
+            let roots = self.shared_state.list_live_roots(&namespace).await?;
+            let mut expired: BTreeMap<String, Vec<ContentMetadataId>> = BTreeMap::new();
+            for (root, rule) in expired_roots(roots, &rules, now, config.grace_period_secs) {
+                if self.shared_state.has_unfinished_tasks(root.get_root_id()) {
+                    sweep.deferred.push(root.id.id);
+                    continue;
+                }
+                expired.entry(rule).or_default().push(root.id);
+            }
+            for (rule, roots) in expired {
+                for batch in roots.chunks(config.batch_size.max(1)) {
+                    self.shared_state
+                        .tombstone_content_batch_with_version(
+                            batch,
+                            TombstoneReason::RetentionPolicy(rule.clone()),
+                            vec![],
+                        )
+                        .await?;
+                    info!(
+                        "expired {} content trees of namespace {} by {}",
+                        batch.len(),
+                        namespace,
+                        rule
+                    );
+                    sweep.expired.extend(batch.iter().map(|id| id.id.clone()));
+                }
+            }
+        }
+        Ok(sweep)
+    }
+
     pub async fn list_extractors(&self) -> Result<Vec<internal_api::ExtractorDescription>> {
         self.shared_state.list_extractors().await
     }
 
-    pub async fn heartbeat(&self, executor_id: &str) -> Result<Vec<indexify_coordinator::Task>> {
-        let tasks = self
+    /// Returns tasks for the executor, leaving out the tasks of held content.
+    /// The time the executor reports is only checked for skew, liveness is
+    /// tracked by the coordinator.
+    pub async fn heartbeat(
+        &self,
+        executor_id: &str,
+        reported_ts_secs: u64,
+    ) -> Result<Vec<indexify_coordinator::Task>> {
+        self.check_executor_clock(executor_id, reported_ts_secs);
+        let mut tasks = self
             .shared_state
             .tasks_for_executor(executor_id, Some(10))
             .await?;
-        let tasks = tasks
-            .into_iter()
-            .map(|task| -> Result<indexify_coordinator::Task> { Ok(task.into()) })
-            .collect::<Result<Vec<_>>>()?;
-        Ok(tasks)
+        {
+            let held_content = self.held_content.lock().unwrap();
+            let now = Instant::now();
+            tasks.retain(|task| {
+                !held_content
+                    .get(&task.content_metadata.id.id)
+                    .is_some_and(|until| *until > now)
+            });
+        }
+        let mut tasks_with_urls = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let signed_url = self.sign_content_url(&task).await?;
+            let mut task: indexify_coordinator::Task = task.into();
+            if let Some(signed_url) = signed_url {
+                task.content_url = signed_url.url;
+                task.content_url_expires_at = signed_url.expires_at;
+            }
+            tasks_with_urls.push(task);
+        }
+        Ok(tasks_with_urls)
+    }
+
+    /// Flags the executor when the time it reported is off the coordinator's
+    /// clock by more than `MAX_EXECUTOR_CLOCK_SKEW_SECS`. Executors which
+    /// don't report their time send 0 and are never flagged.
+    fn check_executor_clock(&self, executor_id: &str, reported_ts_secs: u64) {
+        if reported_ts_secs == 0 {
+            return;
+        }
+        let skew = reported_ts_secs as i64 - self.clock.now_secs() as i64;
+        let mut skews = self.executor_clock_skews.lock().unwrap();
+        if skew.unsigned_abs() <= MAX_EXECUTOR_CLOCK_SKEW_SECS {
+            skews.remove(executor_id);
+        } else if skews.insert(executor_id.to_string(), skew).is_none() {
+            warn!(
+                "clock of executor {} is {}s off the coordinator's clock",
+                executor_id, skew
+            );
+        }
+    }
+
+    /// Executors flagged for a skewed clock, with the seconds their clock was
+    /// ahead of the coordinator's, negative when behind
+    pub fn executor_clock_skews(&self) -> HashMap<String, i64> {
+        self.executor_clock_skews.lock().unwrap().clone()
+    }
+
+    /// Signs the url of the content of the task, None when url signing is
+    /// disabled or the content isn't stored in blob storage
+    async fn sign_content_url(&self, task: &internal_api::Task) -> Result<Option<SignedUrl>> {
+        let Some(signer) = &self.shared_state.blob_url_signer else {
+            return Ok(None);
+        };
+        signer
+            .sign(&task.content_metadata.storage_url, self.clock.now_secs())
+            .await
+    }
+
+    /// Signs a new url for the content of a task assigned to the executor,
+    /// for executors whose url expired before they got to download it
+    pub async fn refresh_content_url(&self, executor_id: &str, task_id: &str) -> Result<SignedUrl> {
+        let assignments = self.shared_state.task_assignments().await?;
+        if assignments.get(task_id).map(|id| id.as_str()) != Some(executor_id) {
+            return Err(anyhow!(
+                "task {} is not assigned to executor {}",
+                task_id,
+                executor_id
+            ));
+        }
+        if self.shared_state.blob_url_signer.is_none() {
+            return Err(anyhow!("blob url signing is not enabled"));
+        }
+        let task = self.shared_state.task_with_id(task_id).await?;
+        self.sign_content_url(&task)
+            .await?
+            .ok_or_else(|| anyhow!("content of task {} is not stored in blob storage", task_id))
+    }
+
+    /// Leaves the tasks of the content out of executor heartbeats for `hold`,
+    /// so that the ingestion server can run them on the executors they are
+    /// assigned to itself. A zero hold releases the tasks.
+    pub fn hold_content_tasks(&self, content_ids: Vec<String>, hold: Duration) {
+        let mut held_content = self.held_content.lock().unwrap();
+        let now = Instant::now();
+        held_content.retain(|_, until| *until > now);
+        for content_id in content_ids {
+            if hold.is_zero() {
+                held_content.remove(&content_id);
+            } else {
+                held_content.insert(content_id, now + hold);
+            }
+        }
+    }
+
+    /// Unfinished tasks of the latest version of the content along with the
+    /// executors they are assigned to. Returns None until every extraction
+    /// policy matching the content, which hasn't been applied to it yet, has
+    /// a task assigned to an executor.
+    pub async fn content_task_assignments(
+        &self,
+        content_id: &str,
+    ) -> Result<Option<Vec<(internal_api::Task, internal_api::ExecutorMetadata)>>> {
+        let Some(content) = self
+            .shared_state
+            .state_machine
+            .get_latest_version_of_content(content_id)?
+        else {
+            return Ok(None);
+        };
+        let extraction_policies = self
+            .shared_state
+            .match_extraction_policies_for_content(&content)
+            .await?;
+        let mut pending_tasks = self
+            .shared_state
+            .state_machine
+            .get_pending_tasks_for_content_version(&content.id)
+            .await;
+        let task_assignments = self.shared_state.task_assignments().await?;
+        let mut assignments = Vec::new();
+        for extraction_policy in extraction_policies {
+            if content
+                .extraction_policy_ids
+                .contains_key(&extraction_policy.id)
+            {
+                continue;
+            }
+            let Some(task_ids) = pending_tasks.remove(&extraction_policy.id) else {
+                return Ok(None);
+            };
+            for task_id in task_ids {
+                let Some(executor_id) = task_assignments.get(&task_id) else {
+                    return Ok(None);
+                };
+                let task = self.shared_state.task_with_id(&task_id).await?;
+                let executor = self.shared_state.get_executor_by_id(executor_id).await?;
+                assignments.push((task, executor));
+            }
+        }
+        Ok(Some(assignments))
     }
 
     pub async fn all_task_assignments(&self) -> Result<HashMap<String, String>> {
@@ -214,6 +651,10 @@ impl Coordinator {
 
     pub async fn remove_executor(&self, executor_id: &str) -> Result<()> {
         info!("removing executor: {}", executor_id);
+        self.executor_clock_skews
+            .lock()
+            .unwrap()
+            .remove(executor_id);
         self.shared_state.remove_executor(executor_id).await?;
         Ok(())
     }
@@ -222,6 +663,18 @@ impl Coordinator {
         self.shared_state.list_indexes(namespace).await
     }
 
+    pub async fn list_namespace_index_tables(&self) -> Result<HashMap<String, HashSet<String>>> {
+        self.shared_state.list_namespace_index_tables().await
+    }
+
+    /// Returns the indexes of the namespace joined with the extraction policy
+    /// and extractor backing them. Missing references are left as `None`.
+    pub fn get_namespace_index_overview(&self, namespace: &str) -> Result<Vec<IndexOverview>> {
+        self.shared_state
+            .state_machine
+            .get_namespace_index_overview(namespace)
+    }
+
     pub async fn get_index(&self, namespace: &str, name: &str) -> Result<internal_api::Index> {
         let mut s = DefaultHasher::new();
         namespace.hash(&mut s);
@@ -230,6 +683,141 @@ impl Coordinator {
         self.shared_state.get_index(&id).await
     }
 
+    /// Recreates the extraction tasks of the policy which feeds the index for
+    /// the content tree of `content_id`. The vectors of the tree are expected
+    /// to be deleted from the index by the caller. Returns the number of
+    /// tasks created.
+    pub async fn rebuild_content_tree(
+        &self,
+        namespace: &str,
+        content_id: &str,
+        index_name: &str,
+    ) -> Result<usize> {
+        let index = self.get_index(namespace, index_name).await?;
+        let extraction_policy =
+            self.shared_state
+                .get_extraction_policy(&internal_api::ExtractionPolicy::create_id(
+                    &index.graph_name,
+                    &index.extraction_policy_name,
+                    namespace,
+                ))?;
+        let content_tree = self.shared_state.get_content_tree_metadata(content_id)?;
+        if content_tree.is_empty() {
+            return Err(anyhow!("content {} not found", content_id));
+        }
+        if content_tree.iter().any(|c| c.namespace != namespace) {
+            return Err(anyhow!(
+                "content {} not found in namespace {}",
+                content_id,
+                namespace
+            ));
+        }
+        self.scheduler
+            .rebuild_content_tree(&extraction_policy, &content_tree)
+            .await
+    }
+
+    /// Stores a search profile after checking that its index exists and its
+    /// filters parse. Profile filters are merged per key with the filters of
+    /// a request, so they can't have alternatives.
+    pub async fn create_search_profile(&self, profile: internal_api::SearchProfile) -> Result<()> {
+        if profile.name.is_empty() {
+            return Err(anyhow!("search profile name can't be empty"));
+        }
+        for filter in &profile.filters {
+            Filter::parse(filter)?;
+        }
+        self.get_index(&profile.namespace, &profile.index)
+            .await
+            .map_err(|_| anyhow!("index {} not found", profile.index))?;
+        self.shared_state.set_search_profile(profile).await
+    }
+
+    /// Returns a search profile along with the reason it can't be used, if
+    /// any. Profiles are kept when the index they reference goes away and are
+    /// reported as invalid instead.
+    pub async fn get_search_profile(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<(internal_api::SearchProfile, Option<String>)>> {
+        let Some(profile) = self.shared_state.get_search_profile(namespace, name)? else {
+            return Ok(None);
+        };
+        let invalid_reason = self.search_profile_invalid_reason(&profile).await;
+        Ok(Some((profile, invalid_reason)))
+    }
+
+    pub async fn list_search_profiles(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<(internal_api::SearchProfile, Option<String>)>> {
+        let mut profiles = Vec::new();
+        for profile in self.shared_state.list_search_profiles(namespace)? {
+            let invalid_reason = self.search_profile_invalid_reason(&profile).await;
+            profiles.push((profile, invalid_reason));
+        }
+        Ok(profiles)
+    }
+
+    pub async fn delete_search_profile(&self, namespace: &str, name: &str) -> Result<()> {
+        self.shared_state
+            .delete_search_profile(namespace, name)
+            .await
+    }
+
+    /// Stores feedback on a search result, with a new id and the current time
+    pub async fn create_search_feedback(
+        &self,
+        mut feedback: internal_api::SearchFeedback,
+    ) -> Result<internal_api::SearchFeedback> {
+        if feedback.query_hash.is_empty() {
+            return Err(anyhow!("search feedback needs the hash of the query"));
+        }
+        if feedback.content_id.is_empty() {
+            return Err(anyhow!("search feedback needs the id of the content"));
+        }
+        feedback.id = nanoid::nanoid!(16);
+        feedback.created_at = self.clock.now_secs();
+        self.shared_state
+            .create_search_feedback(feedback.clone())
+            .await?;
+        Ok(feedback)
+    }
+
+    /// Returns a page of feedback and the cursor of the next page. Pages have
+    /// DEFAULT_SEARCH_FEEDBACK_PAGE_SIZE entries unless the query sets a
+    /// limit, of at most MAX_SEARCH_FEEDBACK_PAGE_SIZE.
+    pub fn list_search_feedback(
+        &self,
+        mut query: internal_api::SearchFeedbackQuery,
+    ) -> Result<(Vec<internal_api::SearchFeedback>, Option<String>)> {
+        query.limit = match query.limit {
+            0 => DEFAULT_SEARCH_FEEDBACK_PAGE_SIZE,
+            limit => limit.min(MAX_SEARCH_FEEDBACK_PAGE_SIZE),
+        };
+        self.shared_state.list_search_feedback(&query)
+    }
+
+    pub fn get_search_feedback_counts(
+        &self,
+        namespace: &str,
+        content_ids: &HashSet<String>,
+    ) -> Result<HashMap<String, internal_api::SearchFeedbackCounts>> {
+        self.shared_state
+            .get_search_feedback_counts(namespace, content_ids)
+    }
+
+    async fn search_profile_invalid_reason(
+        &self,
+        profile: &internal_api::SearchProfile,
+    ) -> Option<String> {
+        self.get_index(&profile.namespace, &profile.index)
+            .await
+            .is_err()
+            .then(|| format!("index {} not found", profile.index))
+    }
+
     pub async fn update_indexes_state(&self, indexes: Vec<internal_api::Index>) -> Result<()> {
         self.shared_state.set_indexes(indexes).await
     }
@@ -252,14 +840,41 @@ impl Coordinator {
         addr: &str,
         executor_id: &str,
         extractors: Vec<internal_api::ExtractorDescription>,
+    ) -> Result<()> {
+        self.register_executor_with_capabilities(addr, executor_id, extractors, HashMap::new())
+            .await
+    }
+
+    pub async fn register_executor_with_capabilities(
+        &self,
+        addr: &str,
+        executor_id: &str,
+        extractors: Vec<internal_api::ExtractorDescription>,
+        capabilities: HashMap<String, String>,
     ) -> Result<()> {
         let _ = self
             .shared_state
-            .register_executor(addr, executor_id, extractors)
+            .register_executor_at(
+                addr,
+                executor_id,
+                extractors,
+                capabilities,
+                self.clock.now_secs(),
+            )
             .await;
         Ok(())
     }
 
+    pub async fn update_executor_extractor(
+        &self,
+        executor_id: &str,
+        extractor: internal_api::ExtractorDescription,
+    ) -> Result<()> {
+        self.shared_state
+            .update_executor_extractor(executor_id, extractor)
+            .await
+    }
+
     pub async fn register_ingestion_server(&self, ingestion_server_id: &str) -> Result<()> {
         if let Some(forward_to_leader) = self.shared_state.ensure_leader().await? {
             let leader_node_id = forward_to_leader
@@ -303,14 +918,32 @@ impl Coordinator {
         Ok(())
     }
 
+    /// Returns the latest version of each content. Tombstoned content is only
+    /// returned with `include_tombstoned`, along with the reason it was
+    /// tombstoned.
     pub async fn get_content_metadata(
         &self,
         content_ids: Vec<String>,
+        include_tombstoned: bool,
     ) -> Result<Vec<indexify_coordinator::ContentMetadata>> {
-        let content = self
+        let mut content = self
             .shared_state
-            .get_content_metadata_batch(content_ids)
+            .get_content_metadata_batch(content_ids.clone())
             .await?;
+        if include_tombstoned {
+            let found: HashSet<String> = content.iter().map(|c| c.id.id.clone()).collect();
+            for content_id in content_ids.iter().filter(|id| !found.contains(*id)) {
+                if let Some(tombstoned) = self
+                    .shared_state
+                    .state_machine
+                    .get_latest_tombstoned_version(content_id)?
+                {
+                    content.push(tombstoned);
+                }
+            }
+        } else {
+            content.retain(|c| !c.tombstoned);
+        }
         let content = self.internal_content_metadata_to_external(content)?;
         Ok(content)
     }
@@ -347,6 +980,21 @@ impl Coordinator {
         Ok(content_tree)
     }
 
+    pub fn check_content_exists(
+        &self,
+        namespace: &str,
+        keys: &[internal_api::ContentLookupKey],
+    ) -> Result<Vec<internal_api::ContentExistence>> {
+        self.shared_state.check_content_exists(namespace, keys)
+    }
+
+    pub fn get_referenced_storage_urls(
+        &self,
+        storage_urls: &HashSet<String>,
+    ) -> Result<HashSet<String>> {
+        self.shared_state.get_referenced_storage_urls(storage_urls)
+    }
+
     pub fn get_extractor(
         &self,
         extractor_name: &str,
@@ -376,6 +1024,7 @@ impl Coordinator {
                             extractor_name: extractor.name.clone(),
                             graph_name: extraction_graph.name.clone(),
                             visibility: false,
+                            attribute_schema: extraction_policy.index_attributes.clone(),
                         };
                         index_to_create.name = index_to_create.build_name(&output_name);
                         index_to_create.table_name = index_to_create.build_table_name(&output_name);
@@ -398,33 +1047,232 @@ impl Coordinator {
         Ok(indexes_to_create)
     }
 
-    pub async fn create_content_tree_tasks(
+    /// Copies the extraction graphs of a namespace, with the extraction
+    /// policies, schemas and indexes they create, into a new namespace so
+    /// changes to them can be tried out before clients are moved over with
+    /// an alias. Content isn't copied, see `sample_content`.
+    pub async fn clone_namespace(
         &self,
-        content_tree: Vec<internal_api::ContentMetadata>,
-        state_change: StateChange,
-    ) -> Result<()> {
-        let mut output_tables = HashMap::new();
-
-        for content_metadata in &content_tree {
-            if content_metadata.extraction_policy_ids.keys().len() == 0 {
-                continue;
-            }
-            let applied_extraction_policy_ids: HashSet<String> = content_metadata
-                .extraction_policy_ids
-                .clone()
-                .into_iter()
-                .filter(|(_, completion_time)| *completion_time > 0)
-                .map(|(extraction_policy_id, _)| extraction_policy_id)
-                .collect();
+        source: &str,
+        name: &str,
+    ) -> Result<Vec<internal_api::Index>> {
+        let source_namespace = self
+            .shared_state
+            .namespace(source)
+            .await?
+            .ok_or_else(|| anyhow!("namespace {} not found", source))?;
+        if self.shared_state.namespace(name).await?.is_some() {
+            return Err(anyhow!("namespace {} already exists", name));
+        }
+        //  Build every graph before writing anything, so a policy whose
+        //  extractor is gone fails the clone before the namespace is created
+        let graphs = source_namespace
+            .extraction_graphs
+            .iter()
+            .map(|graph| self.clone_extraction_graph(graph, name))
+            .collect::<Result<Vec<_>>>()?;
+        self.shared_state.create_namespace(name).await?;
+        let mut indexes = Vec::new();
+        for graph in graphs {
+            indexes.extend(self.create_extraction_graph(graph).await?);
+        }
+        info!("cloned namespace {} into {}", source, name);
+        Ok(indexes)
+    }
 
-            if applied_extraction_policy_ids.is_empty() {
-                continue;
+    fn clone_extraction_graph(
+        &self,
+        graph: &ExtractionGraph,
+        namespace: &str,
+    ) -> Result<ExtractionGraph> {
+        let mut extraction_policies = Vec::new();
+        for policy in &graph.extraction_policies {
+            let extractor = self.get_extractor(&policy.extractor)?;
+            let cloned = ExtractionPolicyBuilder::default()
+                .namespace(namespace.to_string())
+                .name(policy.name.clone())
+                .extractor(policy.extractor.clone())
+                .filters(policy.filters.clone())
+                .input_params(policy.input_params.clone())
+                .content_source(policy.content_source.clone())
+                .index_attributes(policy.index_attributes.clone())
+                .build(&graph.name, extractor)?;
+            extraction_policies.push(cloned);
+        }
+        ExtractionGraphBuilder::default()
+            .id(ExtractionGraph::create_id(&graph.name, namespace))
+            .namespace(namespace.to_string())
+            .name(graph.name.clone())
+            .extraction_policies(extraction_policies)
+            .build()
+    }
+
+    /// Ingests copies of up to `sample_size` live roots of the source
+    /// namespace, picked at random, into a clone of it and returns their ids.
+    /// The cloned policies run over the copies and their outputs land in the
+    /// cloned indexes. The copies point at the blobs of the originals.
+    pub async fn sample_content(
+        &self,
+        source: &str,
+        namespace: &str,
+        sample_size: usize,
+    ) -> Result<Vec<String>> {
+        if sample_size == 0 {
+            return Ok(Vec::new());
+        }
+        let roots = self
+            .shared_state
+            .list_content(source, "", |content| {
+                content.parent_id.is_none() && content.latest && !content.tombstoned
+            })
+            .await?;
+        let copies: Vec<internal_api::ContentMetadata> = roots
+            .into_iter()
+            .choose_multiple(&mut rand::thread_rng(), sample_size)
+            .into_iter()
+            .map(|content| internal_api::ContentMetadata {
+                id: ContentMetadataId::new(&nanoid::nanoid!(16)),
+                namespace: namespace.to_string(),
+                extraction_policy_ids: HashMap::new(),
+                ..content
+            })
+            .collect();
+        let ids = copies.iter().map(|content| content.id.id.clone()).collect();
+        if !copies.is_empty() {
+            self.create_content_metadata(copies).await?;
+        }
+        Ok(ids)
+    }
+
+    pub async fn set_namespace_alias(&self, alias: &str, namespace: &str) -> Result<()> {
+        self.shared_state
+            .set_namespace_alias(alias, namespace)
+            .await
+    }
+
+    pub fn resolve_namespace(&self, name: &str) -> Result<String> {
+        self.shared_state.resolve_namespace(name)
+    }
+
+    pub async fn create_subscription(
+        &self,
+        namespace: &str,
+        url: &str,
+    ) -> Result<internal_api::Subscription> {
+        let parsed = url::Url::parse(url).map_err(|e| anyhow!("invalid url {}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(anyhow!(
+                "invalid url {}: only http and https are supported",
+                url
+            ));
+        }
+        let subscription = internal_api::Subscription {
+            id: nanoid::nanoid!(16),
+            namespace: namespace.to_string(),
+            url: url.to_string(),
+            created_at: self.clock.now_secs(),
+            next_sequence: 0,
+        };
+        self.shared_state
+            .create_subscription(subscription.clone())
+            .await?;
+        info!(
+            "created subscription {} of namespace {} delivering to {}",
+            subscription.id, namespace, url
+        );
+        Ok(subscription)
+    }
+
+    pub fn list_subscriptions(&self, namespace: &str) -> Result<Vec<internal_api::Subscription>> {
+        self.shared_state.list_subscriptions(namespace)
+    }
+
+    /// Deletes the subscription along with the deliveries queued for it
+    pub async fn delete_subscription(&self, namespace: &str, subscription_id: &str) -> Result<()> {
+        self.get_subscription(namespace, subscription_id)?;
+        self.shared_state.delete_subscription(subscription_id).await
+    }
+
+    fn get_subscription(
+        &self,
+        namespace: &str,
+        subscription_id: &str,
+    ) -> Result<internal_api::Subscription> {
+        self.shared_state
+            .get_subscription(subscription_id)?
+            .filter(|subscription| namespace.is_empty() || subscription.namespace == namespace)
+            .ok_or_else(|| anyhow!("subscription {} not found", subscription_id))
+    }
+
+    pub fn list_dead_letters(&self, subscription_id: &str) -> Result<Vec<internal_api::Delivery>> {
+        self.get_subscription("", subscription_id)?;
+        self.shared_state.list_dead_letters(subscription_id)
+    }
+
+    /// Moves the dead letters of the subscription back to its queue and
+    /// returns how many were moved
+    pub async fn requeue_dead_letters(&self, subscription_id: &str) -> Result<usize> {
+        self.get_subscription("", subscription_id)?;
+        self.shared_state
+            .requeue_dead_letters(subscription_id)
+            .await
+    }
+
+    /// Queues the index events for delivery to the subscriptions of the
+    /// namespaces of their indexes. Index names start with the name of their
+    /// namespace, e.g. `namespace.graph.policy.output`.
+    pub async fn enqueue_index_events(&self, events: Vec<String>) -> Result<()> {
+        let subscriptions = self.shared_state.list_subscriptions("")?;
+        let now = self.clock.now_secs();
+        let mut deliveries = Vec::new();
+        for payload in events {
+            let event: IndexEvent = serde_json::from_str(&payload)
+                .map_err(|e| anyhow!("invalid index event {}: {}", payload, e))?;
+            for subscription in &subscriptions {
+                if !event
+                    .index
+                    .starts_with(&format!("{}.", subscription.namespace))
+                {
+                    continue;
+                }
+                deliveries.push(internal_api::Delivery {
+                    subscription_id: subscription.id.clone(),
+                    payload: payload.clone(),
+                    enqueued_at: now,
+                    ..Default::default()
+                });
+            }
+        }
+        self.shared_state.enqueue_deliveries(deliveries).await
+    }
+
+    pub async fn create_content_tree_tasks(
+        &self,
+        content_tree: Vec<internal_api::ContentMetadata>,
+        state_change: StateChange,
+    ) -> Result<()> {
+        let mut output_tables = HashMap::new();
+
+        for content_metadata in &content_tree {
+            if content_metadata.extraction_policy_ids.keys().len() == 0 {
+                continue;
+            }
+            let applied_extraction_policy_ids: HashSet<String> = content_metadata
+                .extraction_policy_ids
+                .clone()
+                .into_iter()
+                .filter(|(_, completion_time)| *completion_time > 0)
+                .map(|(extraction_policy_id, _)| extraction_policy_id)
+                .collect();
+
+            if applied_extraction_policy_ids.is_empty() {
+                continue;
             }
             let applied_extraction_policies = self
                 .shared_state
                 .get_extraction_policies_from_ids(applied_extraction_policy_ids)
                 .await?;
-            for applied_extraction_policy in applied_extraction_policies.clone().unwrap() {
+            for applied_extraction_policy in applied_extraction_policies.found {
                 output_tables.insert(
                     content_metadata.id.clone(),
                     applied_extraction_policy
@@ -520,9 +1368,13 @@ impl Coordinator {
         self.shared_state
             .tombstone_content_batch_with_version(
                 &[content_metadata.id.clone()],
+                TombstoneReason::Superseded(format!(
+                    "replaced by version {}",
+                    root_content_id.version
+                )),
                 vec![StateChangeProcessed {
                     state_change_id: change.id,
-                    processed_at: utils::timestamp_secs(),
+                    processed_at: self.clock.now_secs(),
                 }],
             )
             .await?;
@@ -533,43 +1385,135 @@ impl Coordinator {
     pub async fn run_scheduler(&self) -> Result<()> {
         let _timer = Timer::start(&self.shared_state.metrics.scheduler_invocations);
 
+        //  The reverse index sizes are checked once per scheduler run rather
+        // than on every ingestion request
+        self.shared_state.check_reverse_index_limits();
+
         let state_changes = self.shared_state.unprocessed_state_change_events().await?;
         for change in state_changes {
-            debug!(
-                "processing change event: {}, type: {}, id: {}",
-                change.id, change.change_type, change.object_id
-            );
+            let state_change_id = change.id;
+            if self.is_state_change_parked(&state_change_id) {
+                continue;
+            }
+            if let Err(err) = self.process_state_change(change).await {
+                self.record_state_change_failure(state_change_id, &err);
+                return Err(err);
+            }
+            self.clear_state_change_failures(&state_change_id);
+        }
+        Ok(())
+    }
 
-            match change.change_type {
-                indexify_internal_api::ChangeType::TombstoneContentTree => {
-                    let _ = self
-                        .handle_tombstone_content_tree_state_change(change)
-                        .await?;
-                    continue;
-                }
-                indexify_internal_api::ChangeType::TaskCompleted {
-                    ref root_content_id,
-                } => {
-                    self.handle_task_completion_state_change(
-                        change.clone(),
-                        root_content_id.clone(),
-                    )
-                    .await?;
-                    continue;
-                }
-                indexify_internal_api::ChangeType::ExecutorAdded => {
-                    self.scheduler.redistribute_tasks(&change).await?
-                }
-                indexify_internal_api::ChangeType::NewContent => {
-                    self.scheduler.create_new_tasks(change).await?
-                }
-                indexify_internal_api::ChangeType::ExecutorRemoved => {
-                    self.scheduler.handle_executor_removed(change).await?
-                }
-                indexify_internal_api::ChangeType::ContentUpdated => {
-                    self.handle_content_updated(change).await?
-                }
+    async fn process_state_change(&self, change: StateChange) -> Result<()> {
+        debug!(
+            "processing change event: {}, type: {}, id: {}",
+            change.id, change.change_type, change.object_id
+        );
+
+        let creates_tasks = matches!(
+            change.change_type,
+            indexify_internal_api::ChangeType::NewContent |
+                indexify_internal_api::ChangeType::ContentUpdated
+        );
+        if creates_tasks && self.shared_state.defer_task_creation(&change) {
+            return Ok(());
+        }
+
+        match change.change_type {
+            indexify_internal_api::ChangeType::TombstoneContentTree => {
+                self.handle_tombstone_content_tree_state_change(change)
+                    .await?
+            }
+            indexify_internal_api::ChangeType::TaskCompleted {
+                ref root_content_id,
+            } => {
+                self.handle_task_completion_state_change(change.clone(), root_content_id.clone())
+                    .await?
+            }
+            indexify_internal_api::ChangeType::ExecutorAdded => {
+                self.scheduler.redistribute_tasks(&change).await?
+            }
+            indexify_internal_api::ChangeType::NewContent => {
+                self.scheduler.create_new_tasks(change).await?
+            }
+            indexify_internal_api::ChangeType::ExecutorRemoved => {
+                self.scheduler.handle_executor_removed(change).await?
             }
+            indexify_internal_api::ChangeType::ContentUpdated => {
+                self.handle_content_updated(change).await?
+            }
+            indexify_internal_api::ChangeType::ExtractorAvailabilityChanged { .. } |
+            indexify_internal_api::ChangeType::OutputCompatibilityChanged { .. } => {
+                self.shared_state
+                    .mark_change_events_as_processed(vec![change], Vec::new())
+                    .await?
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts a failed attempt to handle the state change, the scheduler
+    /// skips it once it failed `MAX_STATE_CHANGE_ATTEMPTS` times
+    fn record_state_change_failure(&self, state_change_id: StateChangeId, err: &anyhow::Error) {
+        //  Nobody could reprocess a skipped change, it is retried instead
+        if !self.shared_state.state_change_admin {
+            return;
+        }
+        let mut failures = self.state_change_failures.lock().unwrap();
+        let attempts = failures.entry(state_change_id).or_default();
+        *attempts += 1;
+        if *attempts == MAX_STATE_CHANGE_ATTEMPTS {
+            warn!(
+                "skipping state change {} after {} failed attempts, last error: {:?}",
+                state_change_id, attempts, err
+            );
+        }
+    }
+
+    fn is_state_change_parked(&self, state_change_id: &StateChangeId) -> bool {
+        self.state_change_failures
+            .lock()
+            .unwrap()
+            .get(state_change_id)
+            .is_some_and(|attempts| *attempts >= MAX_STATE_CHANGE_ATTEMPTS)
+    }
+
+    /// Forgets the failed attempts to handle a state change, so the scheduler
+    /// no longer skips it
+    pub fn clear_state_change_failures(&self, state_change_id: &StateChangeId) {
+        self.state_change_failures
+            .lock()
+            .unwrap()
+            .remove(state_change_id);
+    }
+
+    /// Makes the scheduler handle a state change again, whether it was
+    /// processed or skipped after failing too many times
+    pub async fn reprocess_state_change(&self, state_change_id: StateChangeId) -> Result<()> {
+        self.ensure_state_change_admin()?;
+        self.clear_state_change_failures(&state_change_id);
+        self.shared_state
+            .reprocess_state_change(state_change_id)
+            .await
+    }
+
+    /// Marks a state change processed without handling it
+    pub async fn discard_state_change(
+        &self,
+        state_change_id: StateChangeId,
+        note: &str,
+    ) -> Result<()> {
+        self.ensure_state_change_admin()?;
+        self.shared_state
+            .discard_state_change(state_change_id, note)
+            .await?;
+        self.clear_state_change_failures(&state_change_id);
+        Ok(())
+    }
+
+    fn ensure_state_change_admin(&self) -> Result<()> {
+        if !self.shared_state.state_change_admin {
+            return Err(anyhow!("the state change admin endpoints are not enabled"));
         }
         Ok(())
     }
@@ -586,12 +1530,61 @@ impl Coordinator {
         &self,
         content_list: Vec<indexify_internal_api::ContentMetadata>,
     ) -> Result<Vec<CreateContentStatus>> {
+        self.check_ingestion_throttle(&content_list)?;
         self.shared_state.create_content_batch(content_list).await
     }
 
-    pub async fn tombstone_content_metadatas(&self, content_ids: &[String]) -> Result<()> {
+    /// Creates the content of an ingestion request with an idempotency key
+    /// and returns the record of the first request made with the key, which
+    /// is this one unless the key was used before and hasn't expired
+    pub async fn create_content_with_ingestion_key(
+        &self,
+        content: indexify_internal_api::ContentMetadata,
+        key: &str,
+    ) -> Result<IngestionKeyRecord> {
+        self.check_ingestion_throttle(std::slice::from_ref(&content))?;
+        self.shared_state
+            .create_content_with_ingestion_key(content, key, self.clock.now_secs())
+            .await
+    }
+
+    /// Returns the record of an ingestion key unless it expired
+    pub fn get_ingestion_key(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> Result<Option<IngestionKeyRecord>> {
+        let now = self.clock.now_secs();
+        Ok(self
+            .shared_state
+            .state_machine
+            .get_ingestion_key(namespace, key)?
+            .filter(|record| !record.expired(now)))
+    }
+
+    fn check_ingestion_throttle(
+        &self,
+        content_list: &[indexify_internal_api::ContentMetadata],
+    ) -> Result<()> {
+        //  Only new root content is throttled, content extracted from content
+        // already in the system is needed to drain the reverse indexes
+        let over_soft_limit = self.shared_state.reverse_index_limits.throttled_indexes();
+        if !over_soft_limit.is_empty() && content_list.iter().any(|c| c.parent_id.is_none()) {
+            return Err(anyhow!(
+                "content ingestion is throttled, reverse indexes over their soft limit: {}",
+                over_soft_limit.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn tombstone_content_metadatas(
+        &self,
+        content_ids: &[String],
+        reason: TombstoneReason,
+    ) -> Result<()> {
         self.shared_state
-            .tombstone_content_batch(content_ids)
+            .tombstone_content_batch(content_ids, reason)
             .await?;
         Ok(())
     }
@@ -628,38 +1621,137 @@ impl Coordinator {
     }
 }
 
+/// Outcome of a retention sweep
+#[derive(Debug, Default)]
+pub struct RetentionSweep {
+    /// Roots whose trees were tombstoned
+    pub expired: Vec<String>,
+    /// Expired roots left for a later sweep, their trees have unfinished
+    /// tasks
+    pub deferred: Vec<String>,
+}
+
+/// Returns the roots expired by the rules along with the rule expiring them.
+/// Roots younger than the grace period never expire.
+fn expired_roots(
+    mut roots: Vec<internal_api::ContentMetadata>,
+    rules: &internal_api::RetentionRules,
+    now: u64,
+    grace_period_secs: u64,
+) -> Vec<(internal_api::ContentMetadata, String)> {
+    //  Newest first, the position of a root is the number of newer roots
+    roots.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.id.id.cmp(&b.id.id))
+    });
+    roots
+        .into_iter()
+        .enumerate()
+        .filter_map(|(position, root)| {
+            let age = now.saturating_sub(root.created_at.max(0) as u64);
+            if age < grace_period_secs {
+                return None;
+            }
+            let rule = match (rules.max_age_secs, rules.max_items) {
+                (Some(max_age_secs), _) if age > max_age_secs => {
+                    format!("max age of {}s", max_age_secs)
+                }
+                (_, Some(max_items)) if position as u64 >= max_items => {
+                    format!("max items of {}", max_items)
+                }
+                _ => return None,
+            };
+            Some((root, rule))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, fs, sync::Arc, time::Duration, vec};
+    use std::{
+        collections::{HashMap, HashSet},
+        fs,
+        sync::Arc,
+        time::Duration,
+        vec,
+    };
 
     use indexify_internal_api as internal_api;
     use indexify_proto::indexify_coordinator::CreateContentStatus;
-    use internal_api::{ContentMetadataId, ContentSource, TaskOutcome};
+    use internal_api::{
+        ChangeType,
+        ContentMetadataId,
+        ContentSource,
+        EmbeddingSchema,
+        ExtractionPolicyStatus,
+        ExtractionPolicyVersionStrategy,
+        OutputSchema,
+        ServerTaskType,
+        StateChange,
+        StateChangeId,
+        TaskOutcome,
+        TombstoneReason,
+    };
+    use serde_json::json;
 
-    use super::Coordinator;
+    use super::{Coordinator, MAX_STATE_CHANGE_ATTEMPTS};
     use crate::{
+        blob_storage::signing::SignatureError,
         coordinator_client::CoordinatorClient,
         garbage_collector::GarbageCollector,
-        server_config::ServerConfig,
-        state::App,
-        test_util::db_utils::{
-            complete_task,
-            create_content_for_task,
-            create_test_extraction_graph,
-            create_test_extraction_graph_with_children,
-            mock_extractor,
-            next_child,
-            perform_all_tasks,
-            perform_task,
-            test_mock_content_metadata,
-            Parent::{Child, Root},
-            DEFAULT_TEST_NAMESPACE,
+        server_config::{
+            BlobUrlSigningConfig,
+            PolicyStatusConfig,
+            RetentionConfig,
+            ReverseIndexLimitsConfig,
+            ServerConfig,
+            StateChangeAdminConfig,
+        },
+        state::{content_events::ContentEvent, reverse_index_limits::ReverseIndexLimitEvent, App},
+        task_allocator::{
+            planner::{
+                CandidateEvaluation,
+                CandidateRejection,
+                SchedulingExplanation,
+                UnmetRequirement,
+            },
+            TaskAllocator,
+        },
+        test_util::{
+            db_utils::{
+                complete_task,
+                create_content_for_task,
+                create_test_extraction_graph,
+                create_test_extraction_graph_with_children,
+                mock_extractor,
+                next_child,
+                perform_all_tasks,
+                perform_task,
+                test_mock_content_metadata,
+                Parent::{Child, Root},
+                DEFAULT_TEST_EXTRACTOR,
+                DEFAULT_TEST_NAMESPACE,
+            },
+            simulation::{ManualClock, ScenarioBuilder},
         },
         test_utils::RaftTestCluster,
+        utils::{Clock, SystemClock, SECS_PER_DAY},
     };
 
     async fn setup_coordinator() -> (Arc<Coordinator>, Arc<App>) {
-        let config = Arc::new(ServerConfig::default());
+        setup_coordinator_with_config(ServerConfig::default()).await
+    }
+
+    async fn setup_coordinator_with_config(config: ServerConfig) -> (Arc<Coordinator>, Arc<App>) {
+        setup_coordinator_with_clock(config, Arc::new(SystemClock)).await
+    }
+
+    async fn setup_coordinator_with_clock(
+        config: ServerConfig,
+        clock: Arc<dyn Clock>,
+    ) -> (Arc<Coordinator>, Arc<App>) {
+        let config = Arc::new(config);
         let _ = fs::remove_dir_all(config.state_store.clone().path.unwrap());
         let garbage_collector = GarbageCollector::new();
         let coordinator_client = CoordinatorClient::new(Arc::clone(&config));
@@ -673,10 +1765,11 @@ mod tests {
         .await
         .unwrap();
         shared_state.initialize_raft().await.unwrap();
-        let coordinator = crate::coordinator::Coordinator::new(
+        let coordinator = crate::coordinator::Coordinator::new_with_clock(
             shared_state.clone(),
             coordinator_client,
             garbage_collector,
+            clock,
         );
         (coordinator, shared_state)
     }
@@ -967,6 +2060,167 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_clone_namespace() -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8956", "executor", vec![mock_extractor()])
+            .await?;
+        let mut eg = create_test_extraction_graph("graph", vec!["embeddings", "filtered"]);
+        eg.extraction_policies[1].filters =
+            HashMap::from([("language".to_string(), "eng".to_string())]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("root", "", &eg.name)])
+            .await?;
+
+        let indexes = coordinator
+            .clone_namespace(DEFAULT_TEST_NAMESPACE, "staging")
+            .await?;
+
+        //  The policies are the same, with ids and tables of the clone
+        let clone = coordinator.get_namespace("staging").await?.unwrap();
+        assert_eq!(clone.extraction_graphs.len(), 1);
+        let graph = &clone.extraction_graphs[0];
+        assert_eq!(graph.name, eg.name);
+        assert_ne!(graph.id, eg.id);
+        for policy in &eg.extraction_policies {
+            let cloned = graph
+                .extraction_policies
+                .iter()
+                .find(|cloned| cloned.name == policy.name)
+                .unwrap();
+            assert_ne!(cloned.id, policy.id);
+            assert_eq!(cloned.namespace, "staging");
+            assert_eq!(cloned.extractor, policy.extractor);
+            assert_eq!(cloned.filters, policy.filters);
+            assert_eq!(cloned.input_params, policy.input_params);
+            assert_eq!(cloned.content_source, policy.content_source);
+            assert!(cloned
+                .output_table_mapping
+                .values()
+                .all(|table| table.starts_with("staging.")));
+        }
+
+        //  So are the indexes and schemas
+        let index_sources = |indexes: Vec<internal_api::Index>| {
+            let mut sources = indexes
+                .into_iter()
+                .map(|index| (index.extraction_policy_name, index.graph_name, index.schema))
+                .collect::<Vec<_>>();
+            sources.sort();
+            sources
+        };
+        assert_eq!(indexes.len(), 2);
+        assert!(indexes.iter().all(|index| index.namespace == "staging"));
+        assert_eq!(
+            index_sources(coordinator.list_indexes("staging").await?),
+            index_sources(coordinator.list_indexes(DEFAULT_TEST_NAMESPACE).await?)
+        );
+        let schemas = |schemas: Vec<internal_api::StructuredDataSchema>| {
+            let mut schemas = schemas
+                .into_iter()
+                .map(|schema| (schema.extraction_graph_name, schema.columns))
+                .collect::<Vec<_>>();
+            schemas.sort_by(|a, b| a.0.cmp(&b.0));
+            schemas
+        };
+        assert_eq!(
+            schemas(coordinator.list_schemas("staging").await?),
+            schemas(coordinator.list_schemas(DEFAULT_TEST_NAMESPACE).await?)
+        );
+
+        //  Content isn't copied
+        let content = coordinator
+            .list_content("staging", "", "", &HashMap::new())
+            .await?;
+        assert!(content.is_empty());
+
+        //  A namespace can't be cloned over an existing one
+        assert!(coordinator
+            .clone_namespace(DEFAULT_TEST_NAMESPACE, "staging")
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_sample_content_into_clone() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8956", "executor", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph("graph", vec!["embeddings"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        let roots: Vec<_> = (0..5)
+            .map(|i| internal_api::ContentMetadata {
+                latest: true,
+                storage_url: format!("file:///blobs/root_{}", i),
+                ..test_mock_content_metadata(&format!("root_{}", i), "", &eg.name)
+            })
+            .collect();
+        coordinator.create_content_metadata(roots.clone()).await?;
+        coordinator.run_scheduler().await?;
+        coordinator
+            .clone_namespace(DEFAULT_TEST_NAMESPACE, "staging")
+            .await?;
+
+        let sampled = coordinator
+            .sample_content(DEFAULT_TEST_NAMESPACE, "staging", 2)
+            .await?;
+        assert_eq!(sampled.len(), 2);
+
+        //  The copies are ingested into the clone and point at the blobs of
+        //  the originals
+        let copies = coordinator
+            .list_content("staging", "", "", &HashMap::new())
+            .await?;
+        assert_eq!(copies.len(), 2);
+        for copy in &copies {
+            assert!(sampled.contains(&copy.id.id));
+            assert!(roots
+                .iter()
+                .any(|root| root.storage_url == copy.storage_url && root.id != copy.id));
+            assert_eq!(copy.extraction_graph_names, vec![eg.name.clone()]);
+        }
+
+        //  The cloned policy extracts them, the originals are left alone
+        coordinator.run_scheduler().await?;
+        let staging_policy = internal_api::ExtractionPolicy::create_id(
+            &eg.name,
+            &eg.extraction_policies[0].name,
+            "staging",
+        );
+        let tasks = shared_state.tasks_for_executor("executor", None).await?;
+        let mut extracted: Vec<_> = tasks
+            .iter()
+            .filter(|task| task.extraction_policy_id == staging_policy)
+            .map(|task| task.content_metadata.id.id.clone())
+            .collect();
+        extracted.sort();
+        let mut expected = sampled.clone();
+        expected.sort();
+        assert_eq!(extracted, expected);
+        assert_eq!(
+            tasks
+                .iter()
+                .filter(|task| task.extraction_policy_id == eg.extraction_policies[0].id)
+                .count(),
+            5
+        );
+
+        //  Samples are capped by the content there is
+        let sampled = coordinator
+            .sample_content(DEFAULT_TEST_NAMESPACE, "staging", 10)
+            .await?;
+        assert_eq!(sampled.len(), 5);
+        Ok(())
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_create_and_read_content_tree() -> Result<(), anyhow::Error> {
@@ -1112,12 +2366,13 @@ mod tests {
         coordinator
             .create_content_metadata(vec![child_content_2_1.clone()])
             .await?;
+        coordinator.run_scheduler().await?;
 
         coordinator
-            .tombstone_content_metadatas(&[
-                parent_content.id.id.clone(),
-                parent_content_2.id.id.clone(),
-            ])
+            .tombstone_content_metadatas(
+                &[parent_content.id.id.clone(), parent_content_2.id.id.clone()],
+                TombstoneReason::UserRequest("duplicate upload".to_string()),
+            )
             .await?;
 
         //  Check that content has been correctly tombstoned
@@ -1127,12 +2382,14 @@ mod tests {
         let content_tree_2 = coordinator
             .shared_state
             .get_content_tree_metadata(&parent_content_2.id.id)?;
+        let reason = TombstoneReason::UserRequest("duplicate upload".to_string());
         for content in &content_tree {
             assert!(
                 content.tombstoned,
                 "Content {} is not tombstoned",
                 content.id.id
             );
+            assert_eq!(content.tombstone_reason, Some(reason.clone()));
         }
         for content in content_tree_2 {
             assert!(
@@ -1140,6 +2397,28 @@ mod tests {
                 "Content {} is not tombstoned",
                 content.id.id
             );
+            assert_eq!(content.tombstone_reason, Some(reason.clone()));
+        }
+
+        //  Tombstoned content is only read when asked for, with the reason
+        let ids = vec![parent_content.id.id.clone(), child_content_1.id.id.clone()];
+        assert!(coordinator
+            .get_content_metadata(ids.clone(), false)
+            .await?
+            .is_empty());
+        let content = coordinator.get_content_metadata(ids, true).await?;
+        assert_eq!(content.len(), 2);
+        for content in content {
+            assert_eq!(content.tombstone_reason, Some(reason.clone().into()));
+        }
+
+        //  The gc tasks deleting the content carry the reason
+        coordinator.run_scheduler().await?;
+        let tasks = coordinator.shared_state.list_all_gc_tasks().await?;
+        assert_eq!(tasks.len(), 6);
+        for task in tasks {
+            assert_eq!(task.task_type, ServerTaskType::Delete);
+            assert_eq!(task.tombstone_reason, Some(reason.clone()));
         }
         Ok(())
     }
@@ -1201,7 +2480,10 @@ mod tests {
 
         coordinator
             .shared_state
-            .tombstone_content_batch(&[parent_content.id.id.clone()])
+            .tombstone_content_batch(
+                &[parent_content.id.id.clone()],
+                TombstoneReason::UserRequest(String::new()),
+            )
             .await?;
 
         //  after tombstone
@@ -1221,25 +2503,398 @@ mod tests {
     }
 
     #[tokio::test]
-    // #[tracing_test::traced_test]
-    async fn test_gc_tasks_creation() -> Result<(), anyhow::Error> {
-        let (coordinator, _) = setup_coordinator().await;
-
-        //  Add a namespace
+    #[tracing_test::traced_test]
+    async fn test_usage_stats_across_day_boundary() -> Result<(), anyhow::Error> {
+        let clock = Arc::new(ManualClock::default());
+        let (coordinator, shared_state) =
+            setup_coordinator_with_clock(ServerConfig::default(), clock.clone()).await;
         coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
-
-        //  Create an extractor
-        let executor_id_1 = "test_executor_id_1";
-        let extractor_1 = mock_extractor();
+        let executor_id = "test_executor_id";
         coordinator
-            .register_executor("localhost:8956", executor_id_1, vec![extractor_1.clone()])
+            .register_executor("localhost:8956", executor_id, vec![mock_extractor()])
             .await?;
-
-        //  Create an extraction graph
-        let eg =
-            create_test_extraction_graph("extraction_graph_id_1", vec!["extraction_policy_id_1"]);
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
         coordinator.create_extraction_graph(eg.clone()).await?;
-        coordinator.run_scheduler().await?;
+
+        let mut contents = Vec::new();
+        for id in ["content_1", "content_2"] {
+            let mut content = test_mock_content_metadata(id, "", &eg.name);
+            content.size_bytes = 100;
+            contents.push(content);
+        }
+        coordinator.create_content_metadata(contents).await?;
+        coordinator.run_scheduler().await?;
+        let mut tasks = shared_state.tasks_for_executor(executor_id, None).await?;
+        assert_eq!(tasks.len(), 2);
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+
+        //  One task finishes in the last second of a day and the other in the first
+        //  second of the next day
+        let day = 19782;
+        let usage = internal_api::TaskUsage {
+            vectors_produced: 3,
+            execution_time_ms: 50,
+        };
+        let finish_times = [(day + 1) * SECS_PER_DAY - 1, (day + 1) * SECS_PER_DAY];
+        for (task, now) in tasks.iter().zip(finish_times) {
+            clock.set(now);
+            coordinator
+                .update_task(
+                    &task.id,
+                    executor_id,
+                    internal_api::TaskOutcome::Success,
+                    vec!["chunk_1".to_string(), "chunk_2".to_string()],
+                    None,
+                    usage,
+                )
+                .await?;
+        }
+        //  A replayed completion is not counted again
+        coordinator
+            .update_task(
+                &tasks[1].id,
+                executor_id,
+                internal_api::TaskOutcome::Success,
+                vec![],
+                None,
+                usage,
+            )
+            .await?;
+
+        let stats = coordinator.get_usage_stats(Some(DEFAULT_TEST_NAMESPACE), day, day + 1)?;
+        assert_eq!(stats.len(), 2);
+        for (bucket, expected_day) in stats.iter().zip([day, day + 1]) {
+            assert_eq!(bucket.day, expected_day);
+            assert_eq!(bucket.extractor, DEFAULT_TEST_EXTRACTOR);
+            assert_eq!(bucket.tasks, 1);
+            assert_eq!(bucket.content_bytes, 100);
+            assert_eq!(bucket.chunks, 2);
+            assert_eq!(bucket.vectors, 3);
+            assert_eq!(bucket.execution_time_ms, 50);
+        }
+        assert_eq!(
+            coordinator.get_usage_stats(Some(DEFAULT_TEST_NAMESPACE), day + 1, day + 1)?,
+            stats[1..].to_vec()
+        );
+        assert_eq!(coordinator.get_usage_stats(None, day, day + 1)?, stats);
+        assert!(coordinator
+            .get_usage_stats(Some("other_namespace"), day, day + 1)?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_ingestion_key_replay_and_expiry() -> Result<(), anyhow::Error> {
+        let clock = Arc::new(ManualClock::default());
+        clock.set(1000);
+        let config = ServerConfig {
+            ingestion_idempotency_key_ttl_secs: 60,
+            ..Default::default()
+        };
+        let (coordinator, shared_state) = setup_coordinator_with_clock(config, clock.clone()).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+
+        let record = coordinator
+            .create_content_with_ingestion_key(
+                test_mock_content_metadata("content_1", "", &eg.name),
+                "key",
+            )
+            .await?;
+        assert_eq!(record.content_id, "content_1");
+        assert!(record.created);
+        assert_eq!(record.expires_at, 1060);
+
+        //  A replay returns the first result without creating the new content
+        clock.set(1059);
+        let replay = coordinator
+            .create_content_with_ingestion_key(
+                test_mock_content_metadata("content_2", "", &eg.name),
+                "key",
+            )
+            .await?;
+        assert_eq!(replay, record);
+        assert!(shared_state
+            .get_content_metadata_batch(vec!["content_2".to_string()])
+            .await?
+            .is_empty());
+        assert_eq!(
+            coordinator.get_ingestion_key(DEFAULT_TEST_NAMESPACE, "key")?,
+            Some(record.clone())
+        );
+
+        //  Keys are scoped per namespace
+        assert_eq!(
+            coordinator.get_ingestion_key("other_namespace", "key")?,
+            None
+        );
+
+        //  Once the key expired the content is created
+        clock.set(1060);
+        assert_eq!(
+            coordinator.get_ingestion_key(DEFAULT_TEST_NAMESPACE, "key")?,
+            None
+        );
+        let record = coordinator
+            .create_content_with_ingestion_key(
+                test_mock_content_metadata("content_2", "", &eg.name),
+                "key",
+            )
+            .await?;
+        assert_eq!(record.content_id, "content_2");
+        assert!(record.created);
+        assert_eq!(record.expires_at, 1120);
+        assert_eq!(
+            shared_state
+                .get_content_metadata_batch(vec!["content_2".to_string()])
+                .await?
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_concurrent_ingestion_key_creates_once() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+
+        let (first, second) = tokio::join!(
+            coordinator.create_content_with_ingestion_key(
+                test_mock_content_metadata("content_1", "", &eg.name),
+                "key",
+            ),
+            coordinator.create_content_with_ingestion_key(
+                test_mock_content_metadata("content_2", "", &eg.name),
+                "key",
+            ),
+        );
+        let (first, second) = (first?, second?);
+        assert_eq!(first, second);
+        let content = shared_state
+            .get_content_metadata_batch(vec!["content_1".to_string(), "content_2".to_string()])
+            .await?;
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0].id.id, first.content_id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_executor_clock_skew() -> Result<(), anyhow::Error> {
+        let clock = Arc::new(ManualClock::default());
+        clock.set(10_000);
+        let (coordinator, shared_state) =
+            setup_coordinator_with_clock(ServerConfig::default(), clock.clone()).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        for executor_id in ["skewed_executor", "executor"] {
+            coordinator
+                .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+                .await?;
+        }
+
+        //  Only the executor whose clock is an hour ahead is flagged
+        coordinator.heartbeat("skewed_executor", 13_600).await?;
+        coordinator.heartbeat("executor", 10_005).await?;
+        coordinator.heartbeat("executor", 0).await?;
+        assert_eq!(
+            coordinator.executor_clock_skews(),
+            HashMap::from([("skewed_executor".to_string(), 3600)])
+        );
+
+        //  Both executors were seen at the time of the coordinator
+        for executor_id in ["skewed_executor", "executor"] {
+            let executor = shared_state.get_executor_by_id(executor_id).await?;
+            assert_eq!(executor.last_seen, 10_000);
+        }
+
+        //  Policies are applied at the time of the coordinator too
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("content_1", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        assert_eq!(tasks.len(), 1);
+        let assignments = shared_state.task_assignments().await?;
+        clock.set(10_100);
+        coordinator
+            .update_task(
+                &tasks[0].id,
+                &assignments[&tasks[0].id],
+                internal_api::TaskOutcome::Success,
+                vec![],
+                None,
+                Default::default(),
+            )
+            .await?;
+        let content = shared_state
+            .get_content_metadata_batch(vec!["content_1".to_string()])
+            .await?;
+        assert_eq!(
+            content[0]
+                .extraction_policy_ids
+                .values()
+                .collect::<Vec<_>>(),
+            vec![&10_100]
+        );
+
+        //  The flag is cleared once the clock is fixed
+        coordinator.heartbeat("skewed_executor", 10_100).await?;
+        assert!(coordinator.executor_clock_skews().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_refresh_content_url() -> Result<(), anyhow::Error> {
+        let clock = Arc::new(ManualClock::default());
+        clock.set(1000);
+        let config = ServerConfig {
+            blob_url_signing: Some(BlobUrlSigningConfig {
+                key: "test-key".to_string(),
+                ttl_secs: 60,
+                blob_server_url: "http://localhost:8900".to_string(),
+            }),
+            ..Default::default()
+        };
+        let (coordinator, shared_state) = setup_coordinator_with_clock(config, clock.clone()).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator
+            .create_content_metadata(vec![internal_api::ContentMetadata {
+                storage_url: "file:///tmp/blobs/content_1".to_string(),
+                ..test_mock_content_metadata("content_1", "", &eg.name)
+            }])
+            .await?;
+        coordinator.run_scheduler().await?;
+
+        //  Fetched tasks carry a signed url of their content
+        let tasks = coordinator.heartbeat("test_executor_id", 0).await?;
+        assert_eq!(tasks.len(), 1);
+        let task = &tasks[0];
+        assert_eq!(task.content_url_expires_at, 1060);
+        let signer = shared_state.blob_url_signer.clone().unwrap();
+        let verify = |url: &str, now: u64| {
+            let url = url::Url::parse(url).unwrap();
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.to_string())
+                    .unwrap()
+            };
+            signer.verify(
+                &param("url"),
+                param("expires").parse().unwrap(),
+                &param("signature"),
+                now,
+            )
+        };
+        assert_eq!(verify(&task.content_url, 1000), Ok(()));
+
+        //  Once the url expired the executor gets a new one
+        clock.set(1100);
+        assert_eq!(
+            verify(&task.content_url, 1100),
+            Err(SignatureError::Expired)
+        );
+        let refreshed = coordinator
+            .refresh_content_url("test_executor_id", &task.id)
+            .await?;
+        assert_eq!(refreshed.expires_at, 1160);
+        assert_eq!(verify(&refreshed.url, 1100), Ok(()));
+
+        //  Only the executor the task is assigned to can refresh its url
+        assert!(coordinator
+            .refresh_content_url("other_executor_id", &task.id)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hold_content_tasks() -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+
+        //  Content without tasks yet has no assignments
+        coordinator.hold_content_tasks(vec!["content_1".to_string()], Duration::from_secs(60));
+        assert!(coordinator
+            .content_task_assignments("content_1")
+            .await?
+            .is_none());
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("content_1", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+
+        //  The task of held content is assigned but not handed out
+        let assignments = coordinator
+            .content_task_assignments("content_1")
+            .await?
+            .unwrap();
+        assert_eq!(assignments.len(), 1);
+        let (task, executor) = &assignments[0];
+        assert_eq!(task.content_metadata.id.id, "content_1");
+        assert_eq!(executor.id, "test_executor_id");
+        assert_eq!(executor.addr, "localhost:8950");
+        assert!(coordinator
+            .heartbeat("test_executor_id", 0)
+            .await?
+            .is_empty());
+
+        //  Released tasks are handed out again
+        coordinator.hold_content_tasks(vec!["content_1".to_string()], Duration::ZERO);
+        let tasks = coordinator.heartbeat("test_executor_id", 0).await?;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, task.id);
+
+        //  Holds expire on their own
+        coordinator.hold_content_tasks(vec!["content_1".to_string()], Duration::from_millis(50));
+        assert!(coordinator
+            .heartbeat("test_executor_id", 0)
+            .await?
+            .is_empty());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(coordinator.heartbeat("test_executor_id", 0).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    // #[tracing_test::traced_test]
+    async fn test_gc_tasks_creation() -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator().await;
+
+        //  Add a namespace
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        //  Create an extractor
+        let executor_id_1 = "test_executor_id_1";
+        let extractor_1 = mock_extractor();
+        coordinator
+            .register_executor("localhost:8956", executor_id_1, vec![extractor_1.clone()])
+            .await?;
+
+        //  Create an extraction graph
+        let eg =
+            create_test_extraction_graph("extraction_graph_id_1", vec!["extraction_policy_id_1"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator.run_scheduler().await?;
 
         //  Build a content tree where the parent content id is the root
         let parent_content = test_mock_content_metadata("test_parent_id", "", &eg.name);
@@ -1276,13 +2931,19 @@ mod tests {
                     &task.id,
                     "test_executor_id",
                     internal_api::TaskOutcome::Success,
+                    vec![],
+                    None,
+                    internal_api::TaskUsage::default(),
                 )
                 .await?;
         }
 
         //  create a state change for tombstoning the content tree
         coordinator
-            .tombstone_content_metadatas(&[parent_content.id.id])
+            .tombstone_content_metadatas(
+                &[parent_content.id.id],
+                TombstoneReason::UserRequest(String::new()),
+            )
             .await?;
         coordinator.run_scheduler().await?;
 
@@ -1303,6 +2964,78 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_rebuild_content_tree() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let executor_id = "test_executor_id";
+        coordinator
+            .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+            .await?;
+
+        //  The second policy extracts from the output of the first one
+        let eg = create_test_extraction_graph_with_children(
+            "extraction_graph_1",
+            vec!["policy_1", "policy_2"],
+            &[Root, Child(0)],
+        );
+        let indexes = coordinator.create_extraction_graph(eg.clone()).await?;
+        let index = indexes
+            .iter()
+            .find(|index| index.extraction_policy_name == "policy_2")
+            .unwrap();
+        let policy_1 = &eg.extraction_policies[0].id;
+        let policy_2 = &eg.extraction_policies[1].id;
+
+        let root = test_mock_content_metadata("root", "", &eg.name);
+        coordinator.create_content_metadata(vec![root]).await?;
+        let mut child_id = 1;
+        perform_all_tasks(&coordinator, executor_id, &mut child_id).await?;
+
+        //  The tree is root -> 1 (policy_1) -> 2 (policy_2)
+        let policies_applied = |id: &str| {
+            shared_state
+                .state_machine
+                .get_latest_version_of_content(id)
+                .map(|content| content.unwrap().extraction_policy_ids)
+        };
+        assert!(policies_applied("root")?[policy_1] > 0);
+        assert!(policies_applied("1")?[policy_2] > 0);
+        assert!(shared_state.list_all_unfinished_tasks().await?.is_empty());
+
+        //  Rebuilding twice leaves the same state behind
+        for _ in 0..2 {
+            let task_count = coordinator
+                .rebuild_content_tree(DEFAULT_TEST_NAMESPACE, "root", &index.name)
+                .await?;
+            assert_eq!(task_count, 1);
+
+            let root_policies = policies_applied("root")?;
+            assert!(root_policies[policy_1] > 0);
+            assert!(!root_policies.contains_key(policy_2));
+            assert_eq!(policies_applied("1")?[policy_2], 0);
+
+            let tasks = shared_state.list_all_unfinished_tasks().await?;
+            assert_eq!(tasks.len(), 1);
+            assert_eq!(tasks[0].content_metadata.id.id, "1");
+            assert_eq!(&tasks[0].extraction_policy_id, policy_2);
+            assert_eq!(
+                shared_state
+                    .tasks_for_executor(executor_id, None)
+                    .await?
+                    .len(),
+                1
+            );
+        }
+
+        assert!(coordinator
+            .rebuild_content_tree(DEFAULT_TEST_NAMESPACE, "unknown", &index.name)
+            .await
+            .is_err());
+        Ok(())
+    }
+
     use futures::FutureExt;
     use tokio::select;
 
@@ -1467,6 +3200,11 @@ mod tests {
         // the previous tree should be deleted after all tasks for new root are complete
         assert!(prev_tree.iter().all(|c| c.tombstoned));
         assert!(tree.iter().all(|c| !c.tombstoned));
+        let superseded = TombstoneReason::Superseded("replaced by version 2".to_string());
+        assert!(prev_tree
+            .iter()
+            .all(|c| c.tombstone_reason == Some(superseded.clone())));
+        assert!(tree.iter().all(|c| c.tombstone_reason.is_none()));
 
         let tasks = coordinator.shared_state.list_all_gc_tasks().await?;
         assert_eq!(
@@ -1476,6 +3214,10 @@ mod tests {
                 .count(),
             7
         );
+        assert!(tasks
+            .iter()
+            .filter(|task| task.task_type == ServerTaskType::Delete)
+            .all(|task| task.tombstone_reason == Some(superseded.clone())));
 
         for task in tasks {
             if task.outcome == TaskOutcome::Unknown {
@@ -1702,6 +3444,7 @@ mod tests {
         let (coordinator, _) = setup_coordinator().await;
         let namespace = "namespace";
         coordinator.create_namespace(namespace).await?;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
         //  Create an executor and associated extractor
         let extractor = mock_extractor();
@@ -1742,4 +3485,1661 @@ mod tests {
         assert_eq!(tasks.len() + unassigned_tasks.len(), 1);
         Ok(())
     }
+
+    async fn availability_changes(shared_state: &App) -> Result<Vec<bool>, anyhow::Error> {
+        let mut changes = shared_state.list_state_changes().await?;
+        changes.sort_by_key(|change| change.id);
+        Ok(changes
+            .into_iter()
+            .filter_map(|change| match change.change_type {
+                ChangeType::ExtractorAvailabilityChanged { available } => Some(available),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn compatibility_changes(shared_state: &App) -> Result<Vec<bool>, anyhow::Error> {
+        let mut changes = shared_state.list_state_changes().await?;
+        changes.sort_by_key(|change| change.id);
+        Ok(changes
+            .into_iter()
+            .filter_map(|change| match change.change_type {
+                ChangeType::OutputCompatibilityChanged { compatible } => Some(compatible),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_incompatible_extractor_output() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8956", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let indexes = coordinator.create_extraction_graph(eg.clone()).await?;
+        let policy = shared_state.get_extraction_policy(&eg.extraction_policies[0].id)?;
+        assert_eq!(
+            coordinator.get_extraction_policy_status(&policy).await?,
+            ExtractionPolicyStatus::Active
+        );
+
+        //  The extractor is registered again with a different embedding dimension
+        let new_schema = EmbeddingSchema {
+            dim: 768,
+            distance: "cosine".to_string(),
+        };
+        let mut extractor = mock_extractor();
+        extractor.outputs.insert(
+            "test_output".to_string(),
+            OutputSchema::Embedding(new_schema.clone()),
+        );
+        coordinator
+            .register_executor("localhost:8957", "test_executor_id_2", vec![extractor])
+            .await?;
+        let incompatible = coordinator.scheduler.incompatible_outputs(&policy).await?;
+        assert_eq!(incompatible.len(), 1);
+        assert_eq!(incompatible[0].output_name, "test_output");
+        assert_eq!(incompatible[0].index_schema.dim, 384);
+        assert_eq!(incompatible[0].output_schema.dim, 768);
+        assert_eq!(
+            coordinator.get_extraction_policy_status(&policy).await?,
+            ExtractionPolicyStatus::IncompatibleOutput
+        );
+
+        //  No tasks are created and the change is recorded once
+        for id in ["content_1", "content_2"] {
+            coordinator
+                .create_content_metadata(vec![test_mock_content_metadata(id, "", &eg.name)])
+                .await?;
+            coordinator.run_scheduler().await?;
+        }
+        assert!(shared_state
+            .unfinished_tasks_by_extractor(DEFAULT_TEST_EXTRACTOR)
+            .await?
+            .is_empty());
+        assert_eq!(compatibility_changes(&shared_state).await?, vec![false]);
+
+        //  Recreating the index for the new dimension resumes task creation
+        let mut index = indexes[0].clone();
+        index.schema = serde_json::to_value(new_schema).unwrap().to_string();
+        coordinator.update_indexes_state(vec![index]).await?;
+        assert_eq!(
+            coordinator.get_extraction_policy_status(&policy).await?,
+            ExtractionPolicyStatus::Active
+        );
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("content_3", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+        assert_eq!(
+            shared_state
+                .unfinished_tasks_by_extractor(DEFAULT_TEST_EXTRACTOR)
+                .await?
+                .len(),
+            1
+        );
+        assert_eq!(
+            compatibility_changes(&shared_state).await?,
+            vec![false, true]
+        );
+
+        coordinator.run_scheduler().await?;
+        assert!(shared_state
+            .unprocessed_state_change_events()
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_extractor_availability_state_changes() -> Result<(), anyhow::Error> {
+        let config = ServerConfig {
+            policy_status: PolicyStatusConfig {
+                debounce_secs: 10,
+                max_pending_tasks_without_executors: None,
+            },
+            ..Default::default()
+        };
+        let executor_id = "test_executor_id";
+        let mut sim = ScenarioBuilder::new()
+            .config(config)
+            .executor(0, executor_id, vec![mock_extractor()])
+            .check_extractor_availability(100)
+            .executor_dies(101, executor_id)
+            .check_extractor_availability(105)
+            .check_extractor_availability(110)
+            .check_extractor_availability(115)
+            .executor(116, executor_id, vec![mock_extractor()])
+            .check_extractor_availability(120)
+            .executor_dies(121, executor_id)
+            .check_extractor_availability(125)
+            .check_extractor_availability(140)
+            .executor(141, executor_id, vec![mock_extractor()])
+            .check_extractor_availability(150)
+            .check_extractor_availability(160)
+            .build()
+            .await?;
+        let coordinator = sim.coordinator.clone();
+        let shared_state = sim.shared_state.clone();
+
+        // The first observation is recorded without a state change
+        sim.run_until(100).await?;
+        assert!(availability_changes(&shared_state).await?.is_empty());
+        assert_eq!(
+            coordinator
+                .get_policy_status(DEFAULT_TEST_EXTRACTOR)
+                .await?,
+            ExtractionPolicyStatus::Active
+        );
+
+        sim.run_until(101).await?;
+        assert_eq!(
+            coordinator
+                .get_policy_status(DEFAULT_TEST_EXTRACTOR)
+                .await?,
+            ExtractionPolicyStatus::NoExecutors
+        );
+
+        // The transition is only recorded once it outlasts the debounce period
+        sim.run_until(110).await?;
+        assert!(availability_changes(&shared_state).await?.is_empty());
+        sim.run_until(115).await?;
+        assert_eq!(availability_changes(&shared_state).await?, vec![false]);
+
+        // A flap shorter than the debounce period is not recorded
+        sim.run_until(140).await?;
+        assert_eq!(availability_changes(&shared_state).await?, vec![false]);
+
+        sim.run_until(160).await?;
+        assert_eq!(
+            availability_changes(&shared_state).await?,
+            vec![false, true]
+        );
+
+        // The availability state changes are marked processed by the scheduler
+        assert!(shared_state
+            .unprocessed_state_change_events()
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reregistered_executor_load_resets() -> Result<(), anyhow::Error> {
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        let mut builder = ScenarioBuilder::new()
+            .executor(0, "executor_1", vec![mock_extractor()])
+            .extraction_graph(0, eg.clone());
+        for id in ["test1", "test2"] {
+            builder = builder.content(10, test_mock_content_metadata(id, "", &eg.name));
+        }
+        let mut sim = builder.build().await?;
+        let coordinator = sim.coordinator.clone();
+        let shared_state = sim.shared_state.clone();
+        let running_task_count = || {
+            let shared_state = shared_state.clone();
+            async move {
+                shared_state
+                    .get_executor_running_task_count()
+                    .await
+                    .get("executor_1")
+                    .copied()
+            }
+        };
+
+        let snapshot = sim.run_until(10).await?;
+        assert_eq!(snapshot.assignments.len(), 2);
+        assert_eq!(running_task_count().await, Some(2));
+
+        //  Updating an extractor of the executor keeps its load
+        coordinator
+            .update_executor_extractor("executor_1", mock_extractor())
+            .await?;
+        assert_eq!(running_task_count().await, Some(2));
+        assert_eq!(shared_state.task_assignments().await?.len(), 2);
+
+        //  The restarted executor starts without tasks and the tasks of its previous
+        //  registration are unassigned until the scheduler runs
+        coordinator
+            .register_executor("localhost:8950", "executor_1", vec![mock_extractor()])
+            .await?;
+        assert_eq!(running_task_count().await, Some(0));
+        assert!(shared_state.task_assignments().await?.is_empty());
+        assert_eq!(shared_state.unassigned_tasks().await?.len(), 2);
+        assert!(shared_state
+            .tasks_for_executor("executor_1", None)
+            .await?
+            .is_empty());
+
+        coordinator.run_scheduler().await?;
+        assert_eq!(running_task_count().await, Some(2));
+        assert_eq!(shared_state.unassigned_tasks().await?.len(), 0);
+        assert_eq!(shared_state.task_assignments().await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reassign_tasks_of_dead_executor() -> Result<(), anyhow::Error> {
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        let mut builder = ScenarioBuilder::new()
+            .executor(0, "executor_1", vec![mock_extractor()])
+            .executor(0, "executor_2", vec![mock_extractor()])
+            .extraction_graph(0, eg.clone());
+        for id in ["test1", "test2", "test3", "test4"] {
+            builder = builder.content(10, test_mock_content_metadata(id, "", &eg.name));
+        }
+        let mut sim = builder.executor_dies(20, "executor_1").build().await?;
+
+        let history = sim.run().await?;
+        let before = history.iter().find(|s| s.time == 10).unwrap();
+        assert_eq!(before.assignments.len(), 4);
+        assert_eq!(before.unassigned, 0);
+
+        let after = history.last().unwrap();
+        assert_eq!(after.time, 20);
+        assert_eq!(after.unassigned, 0);
+        assert_eq!(
+            after.tasks_by_executor(),
+            HashMap::from([("executor_2".to_string(), 4)])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_explain_task_scheduling() -> Result<(), anyhow::Error> {
+        let mut extractor = mock_extractor();
+        extractor.requirements = HashMap::from([
+            ("gpu".to_string(), "a100".to_string()),
+            ("memory_gb".to_string(), "16".to_string()),
+        ]);
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        let mut sim = ScenarioBuilder::new()
+            .executor(0, "executor_0", vec![extractor.clone()])
+            .extraction_graph(0, eg.clone())
+            .executor_dies(5, "executor_0")
+            .content(10, test_mock_content_metadata("test1", "", &eg.name))
+            .executor(20, "executor_1", vec![extractor.clone()])
+            .build()
+            .await?;
+        let coordinator = sim.coordinator.clone();
+        let allocator = TaskAllocator::new(sim.shared_state.clone());
+        assert_eq!(allocator.explain_task("unknown").await?, None);
+
+        sim.run_until(10).await?;
+        let tasks = sim.shared_state.unassigned_tasks().await?;
+        assert_eq!(tasks.len(), 1);
+        let task_id = tasks[0].id.clone();
+        assert_eq!(
+            allocator.explain_task(&task_id).await?,
+            Some(SchedulingExplanation::NoExecutors {
+                extractor: DEFAULT_TEST_EXTRACTOR.to_string(),
+            })
+        );
+
+        //  Executors which don't meet the requirements of the extractor are
+        //  listed with the requirements they don't meet
+        sim.run_until(20).await?;
+        coordinator
+            .register_executor_with_capabilities(
+                "localhost:8951",
+                "executor_2",
+                vec![extractor.clone()],
+                HashMap::from([
+                    ("gpu".to_string(), "a100".to_string()),
+                    ("memory_gb".to_string(), "8".to_string()),
+                ]),
+            )
+            .await?;
+        let unmet = |name: &str, required: &str, available: Option<&str>| UnmetRequirement {
+            name: name.to_string(),
+            required: required.to_string(),
+            available: available.map(str::to_string),
+        };
+        let executor_1 = CandidateEvaluation {
+            executor_id: "executor_1".to_string(),
+            running_task_count: 0,
+            rejection: Some(CandidateRejection::UnmetRequirements {
+                requirements: vec![unmet("gpu", "a100", None), unmet("memory_gb", "16", None)],
+            }),
+        };
+        let executor_2 = CandidateEvaluation {
+            executor_id: "executor_2".to_string(),
+            running_task_count: 0,
+            rejection: Some(CandidateRejection::UnmetRequirements {
+                requirements: vec![unmet("memory_gb", "16", Some("8"))],
+            }),
+        };
+        assert_eq!(
+            allocator.explain_task(&task_id).await?,
+            Some(SchedulingExplanation::NoEligibleExecutors {
+                extractor: DEFAULT_TEST_EXTRACTOR.to_string(),
+                candidates: vec![executor_1.clone(), executor_2.clone()],
+            })
+        );
+
+        //  An eligible executor registered since the scheduler last ran
+        coordinator
+            .register_executor_with_capabilities(
+                "localhost:8952",
+                "executor_3",
+                vec![extractor.clone()],
+                HashMap::from([
+                    ("gpu".to_string(), "a100".to_string()),
+                    ("memory_gb".to_string(), "32".to_string()),
+                ]),
+            )
+            .await?;
+        let executor_3 = CandidateEvaluation {
+            executor_id: "executor_3".to_string(),
+            running_task_count: 0,
+            rejection: None,
+        };
+        assert_eq!(
+            allocator.explain_task(&task_id).await?,
+            Some(SchedulingExplanation::Assignable {
+                executor_id: "executor_3".to_string(),
+                candidates: vec![executor_1, executor_2, executor_3],
+            })
+        );
+        //  Explaining doesn't assign the task
+        assert_eq!(sim.shared_state.unassigned_tasks().await?.len(), 1);
+
+        coordinator.run_scheduler().await?;
+        assert_eq!(
+            allocator.explain_task(&task_id).await?,
+            Some(SchedulingExplanation::Assigned {
+                executor_id: "executor_3".to_string(),
+            })
+        );
+
+        complete_task(&coordinator, &tasks[0], "executor_3").await?;
+        assert_eq!(
+            allocator.explain_task(&task_id).await?,
+            Some(SchedulingExplanation::Finished {
+                outcome: TaskOutcome::Success,
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_fair_share_across_executors() -> Result<(), anyhow::Error> {
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        let mut builder = ScenarioBuilder::new()
+            .executor(0, "executor_1", vec![mock_extractor()])
+            .executor(0, "executor_2", vec![mock_extractor()])
+            .extraction_graph(0, eg.clone());
+        for (time, id) in [(10, "test1"), (11, "test2"), (12, "test3"), (13, "test4")] {
+            builder = builder.content(time, test_mock_content_metadata(id, "", &eg.name));
+        }
+        let mut sim = builder.build().await?;
+
+        let history = sim.run().await?;
+        // Every new task goes to the executor with the fewest running tasks
+        for snapshot in history.iter().filter(|s| s.time > 10) {
+            let counts = snapshot.tasks_by_executor();
+            let min = counts.values().min().unwrap();
+            let max = counts.values().max().unwrap();
+            assert!(
+                max - min <= 1,
+                "unbalanced at {}: {:?}",
+                snapshot.time,
+                counts
+            );
+        }
+        assert_eq!(
+            history.last().unwrap().tasks_by_executor(),
+            HashMap::from([("executor_1".to_string(), 2), ("executor_2".to_string(), 2)])
+        );
+        Ok(())
+    }
+
+    /// Updates the input params of the policy after its task was created and
+    /// then finishes the task. Returns the task as created and as finished,
+    /// and the updated policy.
+    async fn update_policy_before_task_runs(
+        embed_task_input_params: bool,
+        reported_policy_version: Option<u64>,
+    ) -> Result<
+        (
+            internal_api::Task,
+            internal_api::Task,
+            internal_api::ExtractionPolicy,
+        ),
+        anyhow::Error,
+    > {
+        let config = ServerConfig {
+            embed_task_input_params,
+            ..Default::default()
+        };
+        let (coordinator, shared_state) = setup_coordinator_with_config(config).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let executor_id = "test_executor_id";
+        coordinator
+            .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+            .await?;
+
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("test1", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+        let tasks = shared_state.list_all_unfinished_tasks().await?;
+        assert_eq!(tasks.len(), 1);
+        let created = tasks[0].clone();
+
+        let policy = coordinator
+            .update_extraction_policy_params(
+                created.extraction_policy_id.clone(),
+                json!({"chunk_size": 100}),
+            )
+            .await?;
+        let graph = shared_state
+            .get_extraction_graphs_by_name(DEFAULT_TEST_NAMESPACE, &[eg.name.clone()])?
+            .pop()
+            .flatten()
+            .unwrap();
+        assert_eq!(graph.extraction_policies, vec![policy.clone()]);
+
+        coordinator
+            .update_task(
+                &created.id,
+                executor_id,
+                TaskOutcome::Success,
+                vec![],
+                reported_policy_version,
+                internal_api::TaskUsage::default(),
+            )
+            .await?;
+        let finished = shared_state.task_with_id(&created.id).await?;
+        Ok((created, finished, policy))
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_policy_update_with_embedded_input_params() -> Result<(), anyhow::Error> {
+        let (created, finished, policy) = update_policy_before_task_runs(true, None).await?;
+        assert_eq!(policy.version, 2);
+        assert_eq!(policy.input_params, json!({"chunk_size": 100}));
+
+        //  The task runs with the params of the version it was created from
+        assert_eq!(created.input_params, json!({}));
+        assert_eq!(created.extraction_policy_version, 1);
+        assert_eq!(finished.resolved_policy_version, Some(1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_policy_update_with_policy_reference() -> Result<(), anyhow::Error> {
+        //  The executor fetches the policy when it runs the task and reports
+        //  the version it got
+        let (created, finished, policy) = update_policy_before_task_runs(false, Some(2)).await?;
+        assert_eq!(policy.version, 2);
+
+        assert_eq!(created.input_params, serde_json::Value::Null);
+        assert_eq!(created.extraction_policy_version, 1);
+        assert_eq!(finished.resolved_policy_version, Some(policy.version));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_delete_extraction_policy() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph(
+            "extraction_graph_1",
+            vec!["extraction_policy_1", "extraction_policy_2"],
+        );
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("test1", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+        assert_eq!(shared_state.list_all_unfinished_tasks().await?.len(), 2);
+
+        let deleted = eg.extraction_policies[0].id.clone();
+        coordinator
+            .delete_extraction_policy(DEFAULT_TEST_NAMESPACE, &deleted)
+            .await?;
+        let namespace = coordinator
+            .get_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?
+            .unwrap();
+        let policies: Vec<_> = namespace.extraction_graphs[0]
+            .extraction_policies
+            .iter()
+            .map(|policy| policy.name.clone())
+            .collect();
+        assert_eq!(policies, vec!["extraction_policy_2"]);
+        assert!(namespace.warnings.is_empty());
+        assert!(coordinator.get_extraction_policy(deleted.clone()).is_err());
+        let mapping = shared_state
+            .state_machine
+            .get_latest_policy_mapping("test1")?
+            .unwrap();
+        assert!(!mapping.contains_key(&deleted));
+        assert!(mapping.contains_key(&eg.extraction_policies[1].id));
+
+        //  Tasks already created are kept, new content only gets tasks of the
+        //  remaining policy
+        assert_eq!(shared_state.list_all_unfinished_tasks().await?.len(), 2);
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("test2", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+        let tasks = shared_state.list_all_unfinished_tasks().await?;
+        let new_tasks: Vec<_> = tasks
+            .iter()
+            .filter(|task| task.content_metadata.id.id == "test2")
+            .collect();
+        assert_eq!(new_tasks.len(), 1);
+        assert_eq!(
+            new_tasks[0].extraction_policy_id,
+            eg.extraction_policies[1].id
+        );
+
+        //  Deleting it again fails
+        assert!(coordinator
+            .delete_extraction_policy(DEFAULT_TEST_NAMESPACE, &deleted)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_delete_extraction_policy_used_as_content_source() -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        let mut eg = create_test_extraction_graph(
+            "extraction_graph_1",
+            vec!["extraction_policy_1", "extraction_policy_2"],
+        );
+        eg.extraction_policies[1].content_source =
+            internal_api::ExtractionPolicyContentSource::ExtractionPolicyName(
+                "extraction_policy_1".to_string(),
+            );
+        coordinator.create_extraction_graph(eg.clone()).await?;
+
+        let err = coordinator
+            .delete_extraction_policy(DEFAULT_TEST_NAMESPACE, &eg.extraction_policies[0].id)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("extraction_policy_2"), "{}", err);
+
+        //  The policy at the end of the chain can be deleted, then the first
+        coordinator
+            .delete_extraction_policy(DEFAULT_TEST_NAMESPACE, &eg.extraction_policies[1].id)
+            .await?;
+        coordinator
+            .delete_extraction_policy(DEFAULT_TEST_NAMESPACE, &eg.extraction_policies[0].id)
+            .await?;
+        let namespace = coordinator
+            .get_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?
+            .unwrap();
+        assert!(namespace.extraction_graphs[0]
+            .extraction_policies
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_policy_dependency_graph() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        //  The policy extracting from the output of the other one is listed
+        //  first
+        let mut eg = create_test_extraction_graph(
+            "extraction_graph_1",
+            vec!["extraction_policy_1", "extraction_policy_2"],
+        );
+        eg.extraction_policies[0].content_source =
+            internal_api::ExtractionPolicyContentSource::ExtractionPolicyName(
+                "extraction_policy_2".to_string(),
+            );
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        let first = eg.extraction_policies[1].id.clone();
+        let second = eg.extraction_policies[0].id.clone();
+
+        let graph = shared_state
+            .state_machine
+            .get_policy_dependency_graph(DEFAULT_TEST_NAMESPACE)?;
+        let graph: HashMap<_, _> = graph.into_iter().collect();
+        assert_eq!(graph[&first], Vec::<String>::new());
+        assert_eq!(graph[&second], vec![first.clone()]);
+        assert_eq!(
+            shared_state.get_policy_execution_order(DEFAULT_TEST_NAMESPACE)?,
+            vec![first, second]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_pending_task_cap_without_executors() -> Result<(), anyhow::Error> {
+        let config = ServerConfig {
+            policy_status: PolicyStatusConfig {
+                debounce_secs: 10,
+                max_pending_tasks_without_executors: Some(2),
+            },
+            ..Default::default()
+        };
+        let (coordinator, shared_state) = setup_coordinator_with_config(config).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let executor_id = "test_executor_id";
+        coordinator
+            .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+            .await?;
+        coordinator.run_scheduler().await?;
+
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator.run_scheduler().await?;
+
+        coordinator.remove_executor(executor_id).await?;
+        coordinator.run_scheduler().await?;
+
+        for id in ["test1", "test2"] {
+            coordinator
+                .create_content_metadata(vec![test_mock_content_metadata(id, "", &eg.name)])
+                .await?;
+            coordinator.run_scheduler().await?;
+        }
+        assert_eq!(
+            coordinator
+                .get_policy_status(DEFAULT_TEST_EXTRACTOR)
+                .await?,
+            ExtractionPolicyStatus::Paused
+        );
+
+        //  The content past the cap waits for an executor
+        coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("test3", "", &eg.name)])
+            .await?;
+        coordinator.run_scheduler().await?;
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        assert_eq!(tasks.len(), 2);
+        let is_deferred = || {
+            let shared_state = shared_state.clone();
+            async move {
+                Ok::<_, anyhow::Error>(
+                    shared_state
+                        .unprocessed_state_change_events()
+                        .await?
+                        .iter()
+                        .any(|change| change.object_id == "test3"),
+                )
+            }
+        };
+        assert!(is_deferred().await?);
+
+        coordinator
+            .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+            .await?;
+        coordinator.run_scheduler().await?;
+        assert_eq!(
+            coordinator
+                .get_policy_status(DEFAULT_TEST_EXTRACTOR)
+                .await?,
+            ExtractionPolicyStatus::Active
+        );
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        assert_eq!(tasks.len(), 3);
+        assert!(!is_deferred().await?);
+        Ok(())
+    }
+
+    /// Creates a graph with a single policy using the version strategy. The
+    /// executor of the extractor is removed so that tasks stay unassigned.
+    async fn setup_version_strategy(
+        version_strategy: ExtractionPolicyVersionStrategy,
+    ) -> Result<(Arc<Coordinator>, internal_api::ExtractionGraph), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let executor_id = "test_executor_id";
+        coordinator
+            .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+            .await?;
+        coordinator.run_scheduler().await?;
+
+        let mut eg =
+            create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        eg.extraction_policies[0].version_strategy = version_strategy;
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator.run_scheduler().await?;
+
+        coordinator.remove_executor(executor_id).await?;
+        coordinator.run_scheduler().await?;
+        Ok((coordinator, eg))
+    }
+
+    /// Ingests the next version of the content and returns the versions the
+    /// tasks are for, sorted
+    async fn ingest_content_version(
+        coordinator: &Coordinator,
+        eg: &internal_api::ExtractionGraph,
+        version: u64,
+    ) -> Result<Vec<u64>, anyhow::Error> {
+        let mut content = test_mock_content_metadata("test_content", "", &eg.name);
+        content.hash = format!("test_content_{}", version);
+        coordinator.create_content_metadata(vec![content]).await?;
+        coordinator.run_scheduler().await?;
+        let latest = coordinator
+            .shared_state
+            .state_machine
+            .get_latest_version_of_content("test_content")?
+            .unwrap();
+        assert_eq!(latest.id.version, version);
+
+        let mut versions: Vec<u64> = coordinator
+            .list_tasks(DEFAULT_TEST_NAMESPACE, None)
+            .await?
+            .iter()
+            .map(|task| task.content_metadata.id.version)
+            .collect();
+        versions.sort();
+        Ok(versions)
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_version_strategy_latest_only() -> Result<(), anyhow::Error> {
+        let (coordinator, eg) =
+            setup_version_strategy(ExtractionPolicyVersionStrategy::LatestOnly).await?;
+
+        //  Unassigned tasks of older versions are cancelled by newer versions
+        assert_eq!(ingest_content_version(&coordinator, &eg, 1).await?, vec![1]);
+        assert_eq!(ingest_content_version(&coordinator, &eg, 2).await?, vec![2]);
+        assert_eq!(ingest_content_version(&coordinator, &eg, 3).await?, vec![3]);
+
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        let task_ids: HashSet<String> = tasks.iter().map(|task| task.id.clone()).collect();
+        assert_eq!(coordinator.shared_state.unassigned_tasks().await?.len(), 1);
+        assert_eq!(
+            coordinator
+                .shared_state
+                .unfinished_tasks_by_extractor(DEFAULT_TEST_EXTRACTOR)
+                .await?,
+            task_ids
+        );
+        for version in [1, 2] {
+            let previous = coordinator
+                .shared_state
+                .state_machine
+                .get_content_by_id_and_version(&ContentMetadataId::new_with_version(
+                    "test_content",
+                    version,
+                ))
+                .await?
+                .unwrap();
+            assert!(previous.extraction_policy_ids.is_empty());
+            assert!(
+                coordinator
+                    .shared_state
+                    .are_content_tasks_completed(&previous.id)
+                    .await
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_version_strategy_every_version() -> Result<(), anyhow::Error> {
+        let (coordinator, eg) =
+            setup_version_strategy(ExtractionPolicyVersionStrategy::EveryVersion).await?;
+        let policy_id = &eg.extraction_policies[0].id;
+
+        assert_eq!(ingest_content_version(&coordinator, &eg, 1).await?, vec![1]);
+        assert_eq!(
+            ingest_content_version(&coordinator, &eg, 2).await?,
+            vec![1, 2]
+        );
+        assert_eq!(
+            ingest_content_version(&coordinator, &eg, 3).await?,
+            vec![1, 2, 3]
+        );
+
+        //  Each task marks the policy as applied on the version it ran on
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        for task in &tasks {
+            complete_task(&coordinator, task, "test_executor_id").await?;
+        }
+        for version in [1, 2] {
+            let previous = coordinator
+                .shared_state
+                .state_machine
+                .get_content_by_id_and_version(&ContentMetadataId::new_with_version(
+                    "test_content",
+                    version,
+                ))
+                .await?
+                .unwrap();
+            assert!(previous.extraction_policy_ids[policy_id] > 0);
+        }
+        let latest = coordinator
+            .shared_state
+            .state_machine
+            .get_latest_version_of_content("test_content")?
+            .unwrap();
+        assert_eq!(latest.id.version, 3);
+        assert!(latest.extraction_policy_ids[policy_id] > 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_version_strategy_first_version_only() -> Result<(), anyhow::Error> {
+        let (coordinator, eg) =
+            setup_version_strategy(ExtractionPolicyVersionStrategy::FirstVersionOnly).await?;
+        let policy_id = &eg.extraction_policies[0].id;
+
+        assert_eq!(ingest_content_version(&coordinator, &eg, 1).await?, vec![1]);
+        assert_eq!(ingest_content_version(&coordinator, &eg, 2).await?, vec![1]);
+        assert_eq!(ingest_content_version(&coordinator, &eg, 3).await?, vec![1]);
+
+        //  The task of the first version isn't superseded and, once it finished,
+        //  later versions still don't run the policy
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        complete_task(&coordinator, &tasks[0], "test_executor_id").await?;
+        assert_eq!(ingest_content_version(&coordinator, &eg, 4).await?, vec![1]);
+        let first = coordinator
+            .shared_state
+            .state_machine
+            .get_content_by_id_and_version(&ContentMetadataId::new_with_version("test_content", 1))
+            .await?
+            .unwrap();
+        assert!(first.extraction_policy_ids[policy_id] > 0);
+        let latest = coordinator
+            .shared_state
+            .state_machine
+            .get_latest_version_of_content("test_content")?
+            .unwrap();
+        assert!(!latest.extraction_policy_ids.contains_key(policy_id));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reverse_index_limits() -> Result<(), anyhow::Error> {
+        let config = ServerConfig {
+            reverse_index_limits: ReverseIndexLimitsConfig {
+                soft_limit_entries: Some(2),
+                unassigned_tasks_hard_limit: Some(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (coordinator, shared_state) = setup_coordinator_with_config(config).await;
+        let mut events = shared_state.reverse_index_limits.subscribe();
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        //  Without executors the tasks created stay unassigned
+        let executor_id = "test_executor_id";
+        coordinator
+            .register_executor("localhost:8950", executor_id, vec![mock_extractor()])
+            .await?;
+        coordinator.run_scheduler().await?;
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+        coordinator.remove_executor(executor_id).await?;
+        coordinator.run_scheduler().await?;
+
+        for id in ["test1", "test2", "test3"] {
+            coordinator
+                .create_content_metadata(vec![test_mock_content_metadata(id, "", &eg.name)])
+                .await?;
+            coordinator.run_scheduler().await?;
+        }
+
+        //  The third content went over the soft limit of the content table and
+        // its tasks were deferred as there were already two unassigned tasks
+        let tasks = coordinator.list_tasks(DEFAULT_TEST_NAMESPACE, None).await?;
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(shared_state.unassigned_tasks().await?.len(), 2);
+        let unprocessed = shared_state.unprocessed_state_change_events().await?;
+        assert!(unprocessed.iter().any(|change| change.object_id == "test3"));
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert!(received.iter().any(|event| matches!(
+            event,
+            ReverseIndexLimitEvent::SoftLimitExceeded { size }
+                if size.name == "content_namespace_table" && size.entries == 3
+        )));
+        assert!(received.iter().any(|event| matches!(
+            event,
+            ReverseIndexLimitEvent::TaskCreationDeferred {
+                unassigned_tasks: 2,
+                ..
+            }
+        )));
+
+        //  New root content is rejected while throttled
+        assert!(coordinator
+            .create_content_metadata(vec![test_mock_content_metadata("test4", "", &eg.name)])
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_search_profiles() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph("extraction_graph_1", vec!["extraction_policy_1"]);
+        let indexes = coordinator.create_extraction_graph(eg.clone()).await?;
+
+        let profile = internal_api::SearchProfile {
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            name: "profile_1".to_string(),
+            index: indexes[0].name.clone(),
+            filters: vec!["lang=en".to_string()],
+            k: Some(3),
+            min_score: None,
+        };
+        coordinator.create_search_profile(profile.clone()).await?;
+
+        //  Profiles referencing unknown indexes or with unparseable filters are
+        //  rejected
+        let unknown_index = internal_api::SearchProfile {
+            index: "unknown".to_string(),
+            ..profile.clone()
+        };
+        assert!(coordinator
+            .create_search_profile(unknown_index.clone())
+            .await
+            .is_err());
+        let bad_filter = internal_api::SearchProfile {
+            filters: vec!["lang".to_string()],
+            ..profile.clone()
+        };
+        assert!(coordinator.create_search_profile(bad_filter).await.is_err());
+
+        //  A profile whose index went away is kept and reported as invalid
+        let orphaned = internal_api::SearchProfile {
+            name: "profile_2".to_string(),
+            ..unknown_index
+        };
+        shared_state.set_search_profile(orphaned.clone()).await?;
+
+        let (stored, invalid_reason) = coordinator
+            .get_search_profile(DEFAULT_TEST_NAMESPACE, "profile_1")
+            .await?
+            .unwrap();
+        assert_eq!(stored, profile);
+        assert_eq!(invalid_reason, None);
+
+        let profiles = coordinator
+            .list_search_profiles(DEFAULT_TEST_NAMESPACE)
+            .await?;
+        assert_eq!(
+            profiles,
+            vec![
+                (profile, None),
+                (orphaned, Some("index unknown not found".to_string()))
+            ]
+        );
+
+        coordinator
+            .delete_search_profile(DEFAULT_TEST_NAMESPACE, "profile_1")
+            .await?;
+        assert!(coordinator
+            .get_search_profile(DEFAULT_TEST_NAMESPACE, "profile_1")
+            .await?
+            .is_none());
+        assert_eq!(
+            coordinator
+                .list_search_profiles(DEFAULT_TEST_NAMESPACE)
+                .await?
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_index_events_are_routed_to_subscriptions() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator.create_namespace("other").await?;
+        assert!(coordinator
+            .create_subscription(DEFAULT_TEST_NAMESPACE, "ftp://localhost/events")
+            .await
+            .is_err());
+        let subscription = coordinator
+            .create_subscription(DEFAULT_TEST_NAMESPACE, "http://localhost:9000/events")
+            .await?;
+        let other = coordinator
+            .create_subscription("other", "http://localhost:9001/events")
+            .await?;
+
+        let event = |index: &str, sequence: u64| {
+            serde_json::to_string(&IndexEvent {
+                index: index.to_string(),
+                sequence,
+                kind: crate::index_events::IndexEventKind::Created,
+            })
+            .unwrap()
+        };
+        let events = vec![
+            event(
+                &format!("{}.graph.policy.embedding", DEFAULT_TEST_NAMESPACE),
+                1,
+            ),
+            event("other.graph.policy.embedding", 1),
+            event(
+                &format!("{}.graph.policy.embedding", DEFAULT_TEST_NAMESPACE),
+                2,
+            ),
+        ];
+        coordinator.enqueue_index_events(events.clone()).await?;
+
+        let pending = shared_state.list_pending_deliveries(&subscription.id, None)?;
+        assert_eq!(
+            pending
+                .iter()
+                .map(|d| d.payload.clone())
+                .collect::<Vec<_>>(),
+            vec![events[0].clone(), events[2].clone()]
+        );
+        assert_eq!(
+            pending.iter().map(|d| d.sequence).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        let pending = shared_state.list_pending_deliveries(&other.id, None)?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].payload, events[1]);
+
+        //  Subscriptions can only be deleted from their namespace
+        assert!(coordinator
+            .delete_subscription("other", &subscription.id)
+            .await
+            .is_err());
+        coordinator
+            .delete_subscription(DEFAULT_TEST_NAMESPACE, &subscription.id)
+            .await?;
+        assert_eq!(
+            coordinator.list_subscriptions(DEFAULT_TEST_NAMESPACE)?,
+            vec![]
+        );
+        assert!(shared_state
+            .list_pending_deliveries(&subscription.id, None)?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_namespace_index_overview() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .register_executor("localhost:8950", "test_executor_id", vec![mock_extractor()])
+            .await?;
+
+        //  The policy of this graph creates an index from the extractor output
+        let eg = create_test_extraction_graph("graph_1", vec!["policy_1"]);
+        coordinator.create_extraction_graph(eg.clone()).await?;
+
+        //  An index referencing a policy which doesn't exist
+        let dangling_graph = create_test_extraction_graph("graph_2", vec![]);
+        let mut dangling_index = internal_api::Index {
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            name: "graph_2.missing_policy.test_output".to_string(),
+            table_name: "graph_2.missing_policy.test_output".to_string(),
+            extraction_policy_name: "missing_policy".to_string(),
+            extractor_name: "missing_extractor".to_string(),
+            graph_name: "graph_2".to_string(),
+            visibility: true,
+            ..Default::default()
+        };
+        dangling_index.id = dangling_index.id();
+        shared_state
+            .create_extraction_graph(
+                dangling_graph.clone(),
+                internal_api::StructuredDataSchema::new(
+                    &dangling_graph.name,
+                    &dangling_graph.namespace,
+                ),
+                vec![dangling_index],
+            )
+            .await?;
+
+        let overview = coordinator.get_namespace_index_overview(DEFAULT_TEST_NAMESPACE)?;
+        assert_eq!(overview.len(), 2);
+
+        let healthy = overview
+            .iter()
+            .find(|o| o.index.graph_name == "graph_1")
+            .unwrap();
+        assert_eq!(
+            healthy.extraction_policy.as_ref().map(|p| p.name.as_str()),
+            Some("policy_1")
+        );
+        assert_eq!(
+            healthy.extractor.as_ref().map(|e| e.name.as_str()),
+            Some(mock_extractor().name.as_str())
+        );
+
+        let dangling = overview
+            .iter()
+            .find(|o| o.index.graph_name == "graph_2")
+            .unwrap();
+        assert_eq!(dangling.index.extraction_policy_name, "missing_policy");
+        assert!(dangling.extraction_policy.is_none());
+        assert!(dangling.extractor.is_none());
+
+        //  Other namespaces don't see the indexes
+        assert!(coordinator
+            .get_namespace_index_overview("other_namespace")?
+            .is_empty());
+        Ok(())
+    }
+
+    async fn setup_retention(
+        clock: Arc<ManualClock>,
+        rules: internal_api::RetentionRules,
+    ) -> Result<Arc<Coordinator>, anyhow::Error> {
+        let config = ServerConfig {
+            retention: RetentionConfig {
+                batch_size: 2,
+                grace_period_secs: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (coordinator, _) = setup_coordinator_with_clock(config, clock).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator
+            .set_namespace_retention(DEFAULT_TEST_NAMESPACE, rules)
+            .await?;
+        let namespace = coordinator
+            .get_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?
+            .unwrap();
+        assert_eq!(namespace.retention, rules);
+        Ok(coordinator)
+    }
+
+    /// Creates root content created at the times, processing the new content
+    /// unless `pending`
+    async fn create_roots(
+        coordinator: &Coordinator,
+        roots: &[(&str, i64)],
+        pending: bool,
+    ) -> Result<(), anyhow::Error> {
+        for (id, created_at) in roots {
+            let mut content = test_mock_content_metadata(id, "", "graph");
+            content.created_at = *created_at;
+            let mut child = test_mock_content_metadata(&format!("{}_child", id), id, "graph");
+            child.parent_id = Some(content.id.clone());
+            child.created_at = 0;
+            coordinator.create_content_metadata(vec![content]).await?;
+            coordinator.create_content_metadata(vec![child]).await?;
+        }
+        if !pending {
+            coordinator.run_scheduler().await?;
+        }
+        Ok(())
+    }
+
+    fn tombstone_reason(coordinator: &Coordinator, id: &str) -> Option<TombstoneReason> {
+        let tree = coordinator
+            .shared_state
+            .get_content_tree_metadata(id)
+            .unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].tombstone_reason, tree[1].tombstone_reason);
+        tree[0].tombstone_reason.clone()
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_retention_max_age() -> Result<(), anyhow::Error> {
+        let clock = Arc::new(ManualClock::default());
+        let rules = internal_api::RetentionRules {
+            max_age_secs: Some(100),
+            max_items: None,
+        };
+        let coordinator = setup_retention(clock.clone(), rules).await?;
+        create_roots(&coordinator, &[("a", 0), ("b", 50), ("c", 150)], false).await?;
+        //  The tasks of d aren't created yet
+        create_roots(&coordinator, &[("d", 0)], true).await?;
+
+        clock.set(200);
+        let sweep = coordinator.sweep_retention().await?;
+        assert_eq!(sweep.expired, vec!["a", "b"]);
+        assert_eq!(sweep.deferred, vec!["d"]);
+        let expired = Some(TombstoneReason::RetentionPolicy(
+            "max age of 100s".to_string(),
+        ));
+        assert_eq!(tombstone_reason(&coordinator, "a"), expired);
+        assert_eq!(tombstone_reason(&coordinator, "b"), expired);
+        assert_eq!(tombstone_reason(&coordinator, "c"), None);
+        assert_eq!(tombstone_reason(&coordinator, "d"), None);
+
+        //  Expired roots are reported through the state changes
+        let tombstoned: HashSet<String> = coordinator
+            .shared_state
+            .list_state_changes()
+            .await?
+            .into_iter()
+            .filter(|change| change.change_type == ChangeType::TombstoneContentTree)
+            .map(|change| change.object_id)
+            .collect();
+        assert_eq!(tombstoned.len(), 2);
+
+        //  The deferred root expires once its content is processed
+        coordinator.run_scheduler().await?;
+        let sweep = coordinator.sweep_retention().await?;
+        assert_eq!(sweep.expired, vec!["d"]);
+        assert!(sweep.deferred.is_empty());
+        assert_eq!(tombstone_reason(&coordinator, "d"), expired);
+
+        //  Nothing is left to expire
+        let sweep = coordinator.sweep_retention().await?;
+        assert!(sweep.expired.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_retention_max_items() -> Result<(), anyhow::Error> {
+        let clock = Arc::new(ManualClock::default());
+        let rules = internal_api::RetentionRules {
+            max_age_secs: None,
+            max_items: Some(2),
+        };
+        let coordinator = setup_retention(clock.clone(), rules).await?;
+        create_roots(
+            &coordinator,
+            &[("a", 10), ("b", 20), ("c", 30), ("d", 40), ("e", 195)],
+            false,
+        )
+        .await?;
+
+        //  e is the newest but within the grace period, a and b are the oldest
+        //  beyond the newest 2
+        clock.set(200);
+        let sweep = coordinator.sweep_retention().await?;
+        let mut expired = sweep.expired.clone();
+        expired.sort();
+        assert_eq!(expired, vec!["a", "b", "c"]);
+        let reason = Some(TombstoneReason::RetentionPolicy(
+            "max items of 2".to_string(),
+        ));
+        for id in ["a", "b", "c"] {
+            assert_eq!(tombstone_reason(&coordinator, id), reason);
+        }
+        assert_eq!(tombstone_reason(&coordinator, "d"), None);
+        assert_eq!(tombstone_reason(&coordinator, "e"), None);
+
+        //  Tombstoned roots no longer count
+        let sweep = coordinator.sweep_retention().await?;
+        assert!(sweep.expired.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_label_updates_publish_metadata_changed() -> Result<(), anyhow::Error> {
+        let (coordinator, shared_state) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let mut content = test_mock_content_metadata("root", "", "graph");
+        content.labels = HashMap::from([
+            ("author".to_string(), "a".to_string()),
+            ("lang".to_string(), "en".to_string()),
+        ]);
+        coordinator.create_content_metadata(vec![content]).await?;
+        let mut events = shared_state.content_events.subscribe();
+
+        //  Only the label whose value changed is reported
+        coordinator
+            .update_labels(
+                DEFAULT_TEST_NAMESPACE,
+                "root",
+                HashMap::from([
+                    ("author".to_string(), "b".to_string()),
+                    ("lang".to_string(), "en".to_string()),
+                ]),
+            )
+            .await?;
+        assert_eq!(
+            events.try_recv()?,
+            ContentEvent::MetadataChanged {
+                content_id: "root".to_string(),
+                changed_keys: vec!["author".to_string()],
+            }
+        );
+
+        //  Nothing is published when no label changes
+        coordinator
+            .update_labels(
+                DEFAULT_TEST_NAMESPACE,
+                "root",
+                HashMap::from([("author".to_string(), "b".to_string())]),
+            )
+            .await?;
+        assert!(events.try_recv().is_err());
+        Ok(())
+    }
+
+    fn search_feedback(
+        content_id: &str,
+        verdict: internal_api::FeedbackVerdict,
+    ) -> internal_api::SearchFeedback {
+        internal_api::SearchFeedback {
+            id: "".to_string(),
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            query_hash: crate::search_log::hash_query("query"),
+            content_id: content_id.to_string(),
+            chunk_id: None,
+            verdict,
+            note: None,
+            created_at: 0,
+            actor: Some("reviewer".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_search_feedback() -> Result<(), anyhow::Error> {
+        use internal_api::FeedbackVerdict::{Negative, Positive};
+
+        let clock = Arc::new(ManualClock::default());
+        let (coordinator, _) =
+            setup_coordinator_with_clock(ServerConfig::default(), clock.clone()).await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let content = test_mock_content_metadata("content_1", "", "graph_1");
+        coordinator
+            .create_content_metadata(vec![content.clone()])
+            .await?;
+
+        //  Feedback at times 100, 101, ..., alternating between two contents
+        let mut created = Vec::new();
+        for (i, (content_id, verdict)) in [
+            ("content_1", Positive),
+            ("content_2", Negative),
+            ("content_1", Positive),
+            ("content_2", Positive),
+            ("content_1", Negative),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            clock.set(100 + i as u64);
+            let feedback = coordinator
+                .create_search_feedback(search_feedback(content_id, verdict))
+                .await?;
+            assert!(!feedback.id.is_empty());
+            assert_eq!(feedback.created_at, 100 + i as u64);
+            created.push(feedback);
+        }
+        let mut missing_query = search_feedback("content_1", Positive);
+        missing_query.query_hash = "".to_string();
+        assert!(coordinator
+            .create_search_feedback(missing_query)
+            .await
+            .is_err());
+
+        let list = |query: internal_api::SearchFeedbackQuery| {
+            let (feedback, cursor) = coordinator.list_search_feedback(query).unwrap();
+            let ids: Vec<String> = feedback.into_iter().map(|f| f.id).collect();
+            (ids, cursor)
+        };
+        let ids = |indexes: &[usize]| -> Vec<String> {
+            indexes.iter().map(|i| created[*i].id.clone()).collect()
+        };
+        let query = internal_api::SearchFeedbackQuery {
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(list(query.clone()), (ids(&[0, 1, 2, 3, 4]), None));
+        assert_eq!(
+            list(internal_api::SearchFeedbackQuery {
+                content_id: Some("content_2".to_string()),
+                ..query.clone()
+            }),
+            (ids(&[1, 3]), None)
+        );
+        assert_eq!(
+            list(internal_api::SearchFeedbackQuery {
+                start_time: Some(101),
+                end_time: Some(104),
+                ..query.clone()
+            }),
+            (ids(&[1, 2, 3]), None)
+        );
+
+        //  Pages of two of the feedback on content_1 and content_2
+        let (page, cursor) = list(internal_api::SearchFeedbackQuery {
+            limit: 2,
+            ..query.clone()
+        });
+        assert_eq!(page, ids(&[0, 1]));
+        let (page, cursor) = list(internal_api::SearchFeedbackQuery {
+            limit: 2,
+            cursor,
+            ..query.clone()
+        });
+        assert_eq!(page, ids(&[2, 3]));
+        let (page, cursor) = list(internal_api::SearchFeedbackQuery {
+            limit: 2,
+            cursor,
+            ..query.clone()
+        });
+        assert_eq!((page, cursor), (ids(&[4]), None));
+
+        //  Feedback stays readable after its content is deleted
+        coordinator
+            .tombstone_content_metadatas(
+                &[content.id.id.clone()],
+                TombstoneReason::UserRequest("deleted".to_string()),
+            )
+            .await?;
+        assert_eq!(
+            list(internal_api::SearchFeedbackQuery {
+                content_id: Some("content_1".to_string()),
+                ..query.clone()
+            }),
+            (ids(&[0, 2, 4]), None)
+        );
+
+        let counts = coordinator.get_search_feedback_counts(
+            DEFAULT_TEST_NAMESPACE,
+            &HashSet::from([
+                "content_1".to_string(),
+                "content_2".to_string(),
+                "content_3".to_string(),
+            ]),
+        )?;
+        let counts_of =
+            |positive, negative| internal_api::SearchFeedbackCounts { positive, negative };
+        assert_eq!(counts["content_1"], counts_of(2, 1));
+        assert_eq!(counts["content_2"], counts_of(1, 1));
+        assert_eq!(counts["content_3"], counts_of(0, 0));
+        Ok(())
+    }
+
+    /// Publishes the completion of the tasks of the second version of content
+    /// without a first version, which the scheduler fails to handle
+    async fn create_failing_state_change(
+        coordinator: &Coordinator,
+        content_id: &str,
+    ) -> Result<StateChangeId, anyhow::Error> {
+        let change = StateChange::new(
+            content_id.to_string(),
+            ChangeType::TaskCompleted {
+                root_content_id: ContentMetadataId::new_with_version(content_id, 2),
+            },
+            0,
+        );
+        coordinator
+            .shared_state
+            .mark_change_events_as_processed(vec![], vec![change])
+            .await?;
+        let change = coordinator
+            .shared_state
+            .unprocessed_state_change_events()
+            .await?
+            .into_iter()
+            .find(|change| change.object_id == content_id)
+            .unwrap();
+        Ok(change.id)
+    }
+
+    async fn setup_coordinator_with_state_change_admin() -> (Arc<Coordinator>, Arc<App>) {
+        setup_coordinator_with_config(ServerConfig {
+            state_change_admin: Some(StateChangeAdminConfig {
+                api_key: "admin-key".to_string(),
+            }),
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn is_unprocessed(coordinator: &Coordinator, state_change_id: StateChangeId) -> bool {
+        coordinator
+            .shared_state
+            .state_machine
+            .get_unprocessed_state_changes()
+            .await
+            .contains(&state_change_id)
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reprocess_state_change() -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator_with_state_change_admin().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator.run_scheduler().await?;
+
+        let state_change_id = create_failing_state_change(&coordinator, "content_a").await?;
+        for _ in 0..MAX_STATE_CHANGE_ATTEMPTS {
+            assert!(coordinator.run_scheduler().await.is_err());
+        }
+        //  The change is skipped once it failed too many times
+        assert!(coordinator.is_state_change_parked(&state_change_id));
+        coordinator.run_scheduler().await?;
+
+        //  The version the handler failed to find is ingested
+        for version in 1..=2 {
+            let mut content = test_mock_content_metadata("content_a", "", "");
+            content.extraction_graph_names = vec![];
+            content.hash = format!("content_a_{}", version);
+            coordinator.create_content_metadata(vec![content]).await?;
+        }
+        coordinator.run_scheduler().await?;
+        assert!(is_unprocessed(&coordinator, state_change_id).await);
+
+        coordinator.reprocess_state_change(state_change_id).await?;
+        assert!(!coordinator.is_state_change_parked(&state_change_id));
+        coordinator.run_scheduler().await?;
+        assert!(!is_unprocessed(&coordinator, state_change_id).await);
+        let superseded = coordinator
+            .shared_state
+            .state_machine
+            .get_content_by_id_and_version(&ContentMetadataId::new_with_version("content_a", 1))
+            .await?
+            .unwrap();
+        assert!(superseded.tombstoned);
+
+        //  Changes which were processed are marked unprocessed again
+        coordinator.reprocess_state_change(state_change_id).await?;
+        assert!(is_unprocessed(&coordinator, state_change_id).await);
+        let changes = coordinator.shared_state.list_state_changes().await?;
+        let change = changes.iter().find(|c| c.id == state_change_id).unwrap();
+        assert_eq!(change.processed_at, None);
+
+        assert!(coordinator
+            .reprocess_state_change(StateChangeId::new(u64::MAX))
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_discard_state_change() -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator_with_state_change_admin().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator.run_scheduler().await?;
+
+        let state_change_id = create_failing_state_change(&coordinator, "content_b").await?;
+        assert!(coordinator.run_scheduler().await.is_err());
+
+        coordinator
+            .discard_state_change(state_change_id, "content_b was deleted by hand")
+            .await?;
+        assert!(!is_unprocessed(&coordinator, state_change_id).await);
+        let changes = coordinator.shared_state.list_state_changes().await?;
+        let change = changes.iter().find(|c| c.id == state_change_id).unwrap();
+        assert!(change.processed_at.is_some());
+        coordinator.run_scheduler().await?;
+
+        assert!(coordinator
+            .discard_state_change(StateChangeId::new(u64::MAX), "missing")
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_failing_state_change_retried_without_state_change_admin(
+    ) -> Result<(), anyhow::Error> {
+        let (coordinator, _) = setup_coordinator().await;
+        coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        coordinator.run_scheduler().await?;
+
+        //  The change is never skipped, nobody could reprocess it
+        let state_change_id = create_failing_state_change(&coordinator, "content_c").await?;
+        for _ in 0..=MAX_STATE_CHANGE_ATTEMPTS {
+            assert!(coordinator.run_scheduler().await.is_err());
+        }
+        assert!(!coordinator.is_state_change_parked(&state_change_id));
+        assert!(is_unprocessed(&coordinator, state_change_id).await);
+
+        assert!(coordinator
+            .reprocess_state_change(state_change_id)
+            .await
+            .is_err());
+        assert!(coordinator
+            .discard_state_change(state_change_id, "content_c was deleted by hand")
+            .await
+            .is_err());
+        assert!(is_unprocessed(&coordinator, state_change_id).await);
+        Ok(())
+    }
 }
@@ -222,3 +222,33 @@ mod test_extractor_mimetype_filter {
         assert!(!res);
     }
 }
+
+/// Checks if the value of a label matches the value of an extraction policy
+/// filter. The filter value lists the accepted values separated by `|`, e.g.
+/// `eng|deu` matches content labeled with either language. Content without
+/// the label doesn't match.
+pub fn matches_policy_label(filter_value: &str, label_value: Option<&String>) -> bool {
+    label_value.map_or(false, |label_value| {
+        filter_value
+            .split('|')
+            .any(|accepted| accepted == label_value)
+    })
+}
+
+#[cfg(test)]
+mod test_policy_label_filter {
+    use super::*;
+
+    #[test]
+    fn test_matches_policy_label() {
+        let label = |value: &str| Some(value.to_string());
+        assert!(matches_policy_label("eng", label("eng").as_ref()));
+        assert!(!matches_policy_label("eng", label("deu").as_ref()));
+        assert!(matches_policy_label("eng|deu", label("deu").as_ref()));
+        assert!(!matches_policy_label("eng|deu", label("fra").as_ref()));
+        assert!(!matches_policy_label("eng|deu", label("eng|deu").as_ref()));
+        assert!(matches_policy_label("", label("").as_ref()));
+        assert!(!matches_policy_label("eng", None));
+        assert!(!matches_policy_label("", None));
+    }
+}
@@ -11,20 +11,30 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
-use axum::{extract::State, routing::get};
+use axum::{
+    extract::{Path, State},
+    routing::get,
+};
 use futures::StreamExt;
 use hyper::StatusCode;
 use indexify_internal_api as internal_api;
 use indexify_proto::indexify_coordinator::{
     self,
     coordinator_service_server::CoordinatorService,
+    ContentExistsRequest,
+    ContentExistsResponse,
     CoordinatorCommand,
     CreateContentRequest,
     CreateContentResponse,
+    CreateContentStatus,
     CreateExtractionGraphRequest,
     CreateExtractionGraphResponse,
     CreateGcTasksRequest,
     CreateGcTasksResponse,
+    CreateSearchProfileRequest,
+    CreateSearchProfileResponse,
+    DeleteSearchProfileRequest,
+    DeleteSearchProfileResponse,
     GcTask,
     GcTaskAcknowledgement,
     GetAllSchemaRequest,
@@ -39,11 +49,19 @@ use indexify_proto::indexify_coordinator::{
     GetIndexResponse,
     GetIngestionInfoRequest,
     GetIngestionInfoResponse,
+    GetNamespaceOverviewRequest,
+    GetNamespaceOverviewResponse,
     GetRaftMetricsSnapshotRequest,
+    GetReferencedStorageUrlsRequest,
+    GetReferencedStorageUrlsResponse,
     GetSchemaRequest,
     GetSchemaResponse,
+    GetSearchProfileRequest,
+    GetSearchProfileResponse,
     GetTaskRequest,
     GetTaskResponse,
+    GetUsageStatsRequest,
+    GetUsageStatsResponse,
     HeartbeatRequest,
     HeartbeatResponse,
     ListActiveContentsRequest,
@@ -56,9 +74,12 @@ use indexify_proto::indexify_coordinator::{
     ListExtractorsResponse,
     ListIndexesRequest,
     ListIndexesResponse,
+    ListSearchProfilesRequest,
+    ListSearchProfilesResponse,
     ListStateChangesRequest,
     ListTasksRequest,
     ListTasksResponse,
+    NamespaceIndexOverview,
     RaftMetricsSnapshotResponse,
     RegisterExecutorRequest,
     RegisterExecutorResponse,
@@ -70,6 +91,10 @@ use indexify_proto::indexify_coordinator::{
     TombstoneContentRequest,
     TombstoneContentResponse,
     Uint64List,
+    UpdateExecutorExtractorRequest,
+    UpdateExecutorExtractorResponse,
+    UpdateExtractionPolicyParamsRequest,
+    UpdateExtractionPolicyParamsResponse,
     UpdateIndexesStateRequest,
     UpdateIndexesStateResponse,
     UpdateTaskRequest,
@@ -77,7 +102,13 @@ use indexify_proto::indexify_coordinator::{
     WaitContentExtractionRequest,
     WaitContentExtractionResponse,
 };
-use internal_api::{ExtractionGraph, ExtractionGraphBuilder, ExtractionPolicyBuilder, StateChange};
+use internal_api::{
+    ExtractionGraph,
+    ExtractionGraphBuilder,
+    ExtractionPolicyBuilder,
+    StateChange,
+    StateChangeId,
+};
 use itertools::Itertools;
 use opentelemetry::{
     global,
@@ -105,9 +136,17 @@ use crate::{
     api::IndexifyAPIError,
     coordinator::Coordinator,
     coordinator_client::CoordinatorClient,
+    delivery::DeliveryWorker,
     garbage_collector::GarbageCollector,
     server_config::ServerConfig,
-    state::{self, grpc_config::GrpcConfig},
+    state::{
+        self,
+        grpc_config::GrpcConfig,
+        readiness::{Readiness, ReadinessComponent, ReadinessReport},
+        store::{migrations::MigrationInfo, state_machine_objects::IngestionKeyRecord},
+    },
+    task_allocator::{planner::SchedulingExplanation, TaskAllocator},
+    tls,
     tonic_streamer::DropReceiver,
 };
 
@@ -120,9 +159,51 @@ pub struct ExtractionPolicyCreationResult {
     extractors: Vec<internal_api::ExtractorDescription>,
 }
 
+fn search_profile_to_proto(
+    (profile, invalid_reason): (internal_api::SearchProfile, Option<String>),
+) -> indexify_coordinator::SearchProfile {
+    indexify_coordinator::SearchProfile {
+        invalid_reason: invalid_reason.unwrap_or_default(),
+        ..profile.into()
+    }
+}
+
+fn ingestion_key_status(record: &IngestionKeyRecord) -> CreateContentStatus {
+    if record.created {
+        CreateContentStatus::Created
+    } else {
+        CreateContentStatus::Duplicate
+    }
+}
+
+/// Versions of the coordinator API which this server implements
+const SUPPORTED_API_VERSIONS: [&str; 1] = ["v1"];
+
+/// Capabilities which clients can check for before relying on them
+const SERVER_FEATURES: [&str; 6] = [
+    "content_exists",
+    "index_attribute_schema",
+    "ingestion_keys",
+    "referenced_storage_urls",
+    "search_profiles",
+    "update_labels",
+];
+
+fn server_info() -> indexify_coordinator::GetServerInfoResponse {
+    indexify_coordinator::GetServerInfoResponse {
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        api_versions: SUPPORTED_API_VERSIONS
+            .iter()
+            .map(|v| v.to_string())
+            .collect(),
+        features: SERVER_FEATURES.iter().map(|f| f.to_string()).collect(),
+    }
+}
+
 pub struct CoordinatorServiceServer {
     coordinator: Arc<Coordinator>,
     shutdown_rx: Receiver<()>,
+    verify_executor_identity: bool,
 }
 
 struct MetadataMap<'a>(&'a reqwest::header::HeaderMap);
@@ -140,7 +221,35 @@ impl<'a> Extractor for MetadataMap<'a> {
 // How often we expect the executor to send us heartbeats.
 const EXECUTOR_HEARTBEAT_PERIOD: Duration = Duration::new(5, 0);
 
+// How often the leader checks whether extractors have executors available.
+const EXTRACTOR_AVAILABILITY_CHECK_PERIOD: Duration = Duration::new(5, 0);
+
 impl CoordinatorServiceServer {
+    async fn set_namespace_policy_status(
+        &self,
+        namespace: &mut indexify_coordinator::Namespace,
+    ) -> Result<(), tonic::Status> {
+        for graph in &mut namespace.extraction_graphs {
+            self.coordinator
+                .set_policy_status(&mut graph.extraction_policies)
+                .await
+                .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn verify_executor<T>(
+        &self,
+        request: &tonic::Request<T>,
+        executor_id: &str,
+    ) -> Result<(), tonic::Status> {
+        if !self.verify_executor_identity {
+            return Ok(());
+        }
+        let peer_certs = request.peer_certs();
+        tls::verify_executor_identity(peer_certs.as_deref().map(|c| c.as_slice()), executor_id)
+    }
+
     fn create_extraction_policies_for_graph(
         &self,
         extraction_graph: &CreateExtractionGraphRequest,
@@ -157,6 +266,19 @@ impl CoordinatorServiceServer {
         for (_, policy_request) in name_to_policy_mapping.iter() {
             let input_params = serde_json::from_str(&policy_request.input_params)
                 .map_err(|e| anyhow!(format!("unable to parse input_params: {}", e)))?;
+            let index_attributes = internal_api::IndexAttributeSchema::from_json_str(
+                &policy_request.index_attributes,
+            )?;
+            let version_strategy = if policy_request.version_strategy.is_empty() {
+                internal_api::ExtractionPolicyVersionStrategy::default()
+            } else {
+                policy_request.version_strategy.parse().map_err(|_| {
+                    anyhow!(
+                        "unknown version strategy: {}",
+                        policy_request.version_strategy
+                    )
+                })?
+            };
             let extractor = self.coordinator.get_extractor(&policy_request.extractor)?;
             let content_source = if policy_request.content_source.eq("") {
                 internal_api::ExtractionPolicyContentSource::Ingestion
@@ -182,6 +304,8 @@ impl CoordinatorServiceServer {
                 .filters(policy_request.filters.clone())
                 .input_params(input_params)
                 .content_source(content_source)
+                .index_attributes(index_attributes)
+                .version_strategy(version_strategy)
                 .build(&extraction_graph.name, extractor.clone())
                 .map_err(|e| anyhow!(e))?;
             extraction_policies.push(policy.clone());
@@ -203,11 +327,23 @@ impl CoordinatorService for CoordinatorServiceServer {
         &self,
         request: tonic::Request<CreateContentRequest>,
     ) -> Result<tonic::Response<CreateContentResponse>, tonic::Status> {
+        let request = request.into_inner();
         let content_meta = request
-            .into_inner()
             .content
             .ok_or(tonic::Status::aborted("content is missing"))?;
         let content_meta: indexify_internal_api::ContentMetadata = content_meta.into();
+        if !request.idempotency_key.is_empty() {
+            let record = self
+                .coordinator
+                .create_content_with_ingestion_key(content_meta, &request.idempotency_key)
+                .await
+                .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+            return Ok(tonic::Response::new(CreateContentResponse {
+                status: ingestion_key_status(&record) as i32,
+                content_id: record.content_id,
+            }));
+        }
+        let content_id = content_meta.id.id.clone();
         let content_list = vec![content_meta];
         let statuses = self
             .coordinator
@@ -219,9 +355,30 @@ impl CoordinatorService for CoordinatorServiceServer {
                 .first()
                 .ok_or_else(|| tonic::Status::aborted("result invalid"))?
                 as i32,
+            content_id,
         }))
     }
 
+    async fn get_ingestion_key(
+        &self,
+        request: tonic::Request<indexify_coordinator::GetIngestionKeyRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::GetIngestionKeyResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let record = self
+            .coordinator
+            .get_ingestion_key(&request.namespace, &request.key)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        let response = match record {
+            Some(record) => indexify_coordinator::GetIngestionKeyResponse {
+                found: true,
+                status: ingestion_key_status(&record) as i32,
+                content_id: record.content_id,
+            },
+            None => indexify_coordinator::GetIngestionKeyResponse::default(),
+        };
+        Ok(tonic::Response::new(response))
+    }
+
     async fn update_labels(
         &self,
         request: tonic::Request<indexify_coordinator::UpdateLabelsRequest>,
@@ -236,6 +393,13 @@ impl CoordinatorService for CoordinatorServiceServer {
         ))
     }
 
+    async fn get_server_info(
+        &self,
+        _request: tonic::Request<indexify_coordinator::GetServerInfoRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::GetServerInfoResponse>, tonic::Status> {
+        Ok(tonic::Response::new(server_info()))
+    }
+
     async fn tombstone_content(
         &self,
         request: tonic::Request<TombstoneContentRequest>,
@@ -243,7 +407,10 @@ impl CoordinatorService for CoordinatorServiceServer {
         let req = request.into_inner();
         let content_ids = req.content_ids;
         self.coordinator
-            .tombstone_content_metadatas(&content_ids)
+            .tombstone_content_metadatas(
+                &content_ids,
+                internal_api::TombstoneReason::UserRequest(req.reason),
+            )
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
         Ok(tonic::Response::new(TombstoneContentResponse {}))
@@ -338,8 +505,36 @@ impl CoordinatorService for CoordinatorServiceServer {
             .coordinator
             .get_extraction_policy(request.extraction_policy_id)
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        let mut policies = vec![extraction_policy.into()];
+        self.coordinator
+            .set_policy_status(&mut policies)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
         Ok(tonic::Response::new(GetExtractionPolicyResponse {
-            policy: Some(extraction_policy.into()),
+            policy: policies.pop(),
+        }))
+    }
+
+    async fn update_extraction_policy_params(
+        &self,
+        request: tonic::Request<UpdateExtractionPolicyParamsRequest>,
+    ) -> Result<tonic::Response<UpdateExtractionPolicyParamsResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let input_params = serde_json::from_str(&request.input_params).map_err(|e| {
+            tonic::Status::invalid_argument(format!("unable to parse input_params: {}", e))
+        })?;
+        let extraction_policy = self
+            .coordinator
+            .update_extraction_policy_params(request.extraction_policy_id, input_params)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        let mut policies = vec![extraction_policy.into()];
+        self.coordinator
+            .set_policy_status(&mut policies)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(UpdateExtractionPolicyParamsResponse {
+            policy: policies.pop(),
         }))
     }
 
@@ -353,7 +548,11 @@ impl CoordinatorService for CoordinatorServiceServer {
             .list_policies(&request.namespace)
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
-        let policies = extraction_policies.into_iter().map(|p| p.into()).collect();
+        let mut policies: Vec<_> = extraction_policies.into_iter().map(|p| p.into()).collect();
+        self.coordinator
+            .set_policy_status(&mut policies)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
         Ok(tonic::Response::new(ListExtractionPoliciesResponse {
             policies,
         }))
@@ -385,7 +584,11 @@ impl CoordinatorService for CoordinatorServiceServer {
             .list_namespaces()
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
-        let namespaces = namespaces.into_iter().map(|n| n.into()).collect();
+        let mut namespaces: Vec<indexify_coordinator::Namespace> =
+            namespaces.into_iter().map(|n| n.into()).collect();
+        for namespace in &mut namespaces {
+            self.set_namespace_policy_status(namespace).await?;
+        }
         Ok(tonic::Response::new(
             indexify_coordinator::ListNamespaceResponse { namespaces },
         ))
@@ -402,14 +605,177 @@ impl CoordinatorService for CoordinatorServiceServer {
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?
             .ok_or_else(|| tonic::Status::not_found("namespace not found"))?;
+        let mut namespace: indexify_coordinator::Namespace = namespace.into();
+        self.set_namespace_policy_status(&mut namespace).await?;
 
         Ok(tonic::Response::new(
             indexify_coordinator::GetNamespaceResponse {
-                namespace: Some(namespace.into()),
+                namespace: Some(namespace),
+            },
+        ))
+    }
+
+    async fn clone_namespace(
+        &self,
+        request: tonic::Request<indexify_coordinator::CloneNamespaceRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::CloneNamespaceResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let indexes = self
+            .coordinator
+            .clone_namespace(&request.source, &request.name)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::CloneNamespaceResponse {
+                indexes: indexes.into_iter().map(Into::into).collect(),
+            },
+        ))
+    }
+
+    async fn sample_namespace_content(
+        &self,
+        request: tonic::Request<indexify_coordinator::SampleNamespaceContentRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::SampleNamespaceContentResponse>, tonic::Status>
+    {
+        let request = request.into_inner();
+        let content_ids = self
+            .coordinator
+            .sample_content(
+                &request.source,
+                &request.namespace,
+                request.sample_size as usize,
+            )
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::SampleNamespaceContentResponse { content_ids },
+        ))
+    }
+
+    async fn set_namespace_alias(
+        &self,
+        request: tonic::Request<indexify_coordinator::SetNamespaceAliasRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::SetNamespaceAliasResponse>, tonic::Status>
+    {
+        let request = request.into_inner();
+        self.coordinator
+            .set_namespace_alias(&request.alias, &request.namespace)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::SetNamespaceAliasResponse {},
+        ))
+    }
+
+    async fn resolve_namespace(
+        &self,
+        request: tonic::Request<indexify_coordinator::ResolveNamespaceRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::ResolveNamespaceResponse>, tonic::Status>
+    {
+        let namespace = self
+            .coordinator
+            .resolve_namespace(&request.into_inner().name)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::ResolveNamespaceResponse { namespace },
+        ))
+    }
+
+    async fn create_subscription(
+        &self,
+        request: tonic::Request<indexify_coordinator::CreateSubscriptionRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::CreateSubscriptionResponse>, tonic::Status>
+    {
+        let request = request.into_inner();
+        let subscription = self
+            .coordinator
+            .create_subscription(&request.namespace, &request.url)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::CreateSubscriptionResponse {
+                subscription: Some(subscription.into()),
+            },
+        ))
+    }
+
+    async fn list_subscriptions(
+        &self,
+        request: tonic::Request<indexify_coordinator::ListSubscriptionsRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::ListSubscriptionsResponse>, tonic::Status>
+    {
+        let subscriptions = self
+            .coordinator
+            .list_subscriptions(&request.into_inner().namespace)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::ListSubscriptionsResponse {
+                subscriptions: subscriptions.into_iter().map(Into::into).collect(),
+            },
+        ))
+    }
+
+    async fn delete_subscription(
+        &self,
+        request: tonic::Request<indexify_coordinator::DeleteSubscriptionRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::DeleteSubscriptionResponse>, tonic::Status>
+    {
+        let request = request.into_inner();
+        self.coordinator
+            .delete_subscription(&request.namespace, &request.subscription_id)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::DeleteSubscriptionResponse {},
+        ))
+    }
+
+    async fn list_dead_letters(
+        &self,
+        request: tonic::Request<indexify_coordinator::ListDeadLettersRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::ListDeadLettersResponse>, tonic::Status> {
+        let deliveries = self
+            .coordinator
+            .list_dead_letters(&request.into_inner().subscription_id)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::ListDeadLettersResponse {
+                deliveries: deliveries.into_iter().map(Into::into).collect(),
             },
         ))
     }
 
+    async fn requeue_dead_letters(
+        &self,
+        request: tonic::Request<indexify_coordinator::RequeueDeadLettersRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::RequeueDeadLettersResponse>, tonic::Status>
+    {
+        let requeued = self
+            .coordinator
+            .requeue_dead_letters(&request.into_inner().subscription_id)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::RequeueDeadLettersResponse {
+                requeued: requeued as u64,
+            },
+        ))
+    }
+
+    async fn enqueue_index_events(
+        &self,
+        request: tonic::Request<indexify_coordinator::EnqueueIndexEventsRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::EnqueueIndexEventsResponse>, tonic::Status>
+    {
+        self.coordinator
+            .enqueue_index_events(request.into_inner().events)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::EnqueueIndexEventsResponse {},
+        ))
+    }
+
     async fn list_extractors(
         &self,
         _request: tonic::Request<ListExtractorsRequest>,
@@ -431,6 +797,7 @@ impl CoordinatorService for CoordinatorServiceServer {
         &self,
         request: tonic::Request<RegisterExecutorRequest>,
     ) -> Result<tonic::Response<RegisterExecutorResponse>, tonic::Status> {
+        self.verify_executor(&request, &request.get_ref().executor_id)?;
         let request = request.into_inner();
 
         let extractors = request
@@ -441,7 +808,12 @@ impl CoordinatorService for CoordinatorServiceServer {
 
         let _resp = self
             .coordinator
-            .register_executor(&request.addr, &request.executor_id, extractors)
+            .register_executor_with_capabilities(
+                &request.addr,
+                &request.executor_id,
+                extractors,
+                request.capabilities,
+            )
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
 
@@ -450,6 +822,23 @@ impl CoordinatorService for CoordinatorServiceServer {
         }))
     }
 
+    async fn update_executor_extractor(
+        &self,
+        request: tonic::Request<UpdateExecutorExtractorRequest>,
+    ) -> Result<tonic::Response<UpdateExecutorExtractorResponse>, tonic::Status> {
+        self.verify_executor(&request, &request.get_ref().executor_id)?;
+        let request = request.into_inner();
+        let extractor: internal_api::ExtractorDescription = request
+            .extractor
+            .ok_or_else(|| tonic::Status::invalid_argument("extractor is required"))?
+            .into();
+        self.coordinator
+            .update_executor_extractor(&request.executor_id, extractor)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(UpdateExecutorExtractorResponse {}))
+    }
+
     async fn register_ingestion_server(
         &self,
         request: tonic::Request<RegisterIngestionServerRequest>,
@@ -530,14 +919,26 @@ impl CoordinatorService for CoordinatorServiceServer {
                                     continue;
                                 }
 
-                                tracing::info!(
-                                    "Received gc task acknowledgement {:?}, marking the gc task as complete",
-                                    task_ack
-                                );
-                                if let Err(e) = coordinator_clone
-                                .update_gc_task(&task_ack.task_id, task_ack.completed.into())
-                                .await
-                                {
+                                //  Tasks which aren't completed are resumed, without
+                                //  the output tables they completed
+                                let result = if task_ack.completed {
+                                    tracing::info!(
+                                        "Received gc task acknowledgement {:?}, marking the gc task as complete",
+                                        task_ack
+                                    );
+                                    coordinator_clone
+                                        .update_gc_task(&task_ack.task_id, internal_api::TaskOutcome::Success)
+                                        .await
+                                } else {
+                                    tracing::info!(
+                                        "Received gc task acknowledgement {:?}, resuming the gc task",
+                                        task_ack
+                                    );
+                                    coordinator_clone
+                                        .resume_gc_task(&task_ack.task_id, task_ack.completed_output_tables)
+                                        .await
+                                };
+                                if let Err(e) = result {
                                     tracing::error!(
                                         "Error updating GC task with id {}: {}",
                                         task_ack.task_id,
@@ -598,6 +999,11 @@ impl CoordinatorService for CoordinatorServiceServer {
         &self,
         request: tonic::Request<Streaming<HeartbeatRequest>>,
     ) -> Result<tonic::Response<Self::HeartbeatStream>, tonic::Status> {
+        let peer_certs = if self.verify_executor_identity {
+            Some(request.peer_certs().unwrap_or_default())
+        } else {
+            None
+        };
         let mut in_stream = request.into_inner();
         let (tx, rx) = mpsc::channel(4);
         let rx = DropReceiver { inner: rx };
@@ -622,15 +1028,27 @@ impl CoordinatorService for CoordinatorServiceServer {
                             info!("error receiving heartbeat request: {:?}", err);
                             break;
                         }
+                        let reported_ts_secs = frame
+                            .as_ref()
+                            .and_then(|frame| frame.as_ref().ok())
+                            .map(|hb_request| hb_request.ts_secs)
+                            .unwrap_or_default();
                         // We could have used Option<> here but it would be inconvenient to dereference
                         // it every time we need to use it below
                         if executor_id.is_none() {
                             if let Some(Ok(hb_request)) = frame {
+                                if let Some(peer_certs) = &peer_certs {
+                                    if let Err(status) = tls::verify_executor_identity(Some(peer_certs.as_slice()), &hb_request.executor_id) {
+                                        warn!("rejecting heartbeat from executor {}: {}", hb_request.executor_id, status.message());
+                                        let _ = tx.send(Err(status)).await;
+                                        break;
+                                    }
+                                }
                                 executor_id.replace(hb_request.executor_id.clone());
                             }
                         }
                         if let Some(executor_id) = executor_id.clone() {
-                            let tasks = coordinator.heartbeat(&executor_id).await;
+                            let tasks = coordinator.heartbeat(&executor_id, reported_ts_secs).await;
                             match tasks {
                                 Err(err) => {
                                     if let Err(err) = tx.send(Err(tonic::Status::internal(err.to_string()))).await {
@@ -674,16 +1092,47 @@ impl CoordinatorService for CoordinatorServiceServer {
         &self,
         request: tonic::Request<UpdateTaskRequest>,
     ) -> Result<tonic::Response<UpdateTaskResponse>, tonic::Status> {
+        self.verify_executor(&request, &request.get_ref().executor_id)?;
         let request = request.into_inner();
         let outcome: internal_api::TaskOutcome = request.outcome().into();
         let _ = self
             .coordinator
-            .update_task(&request.task_id, &request.executor_id, outcome)
+            .update_task(
+                &request.task_id,
+                &request.executor_id,
+                outcome,
+                request.output_content_ids,
+                (request.policy_version > 0).then_some(request.policy_version),
+                internal_api::TaskUsage {
+                    vectors_produced: request.vectors_produced,
+                    execution_time_ms: request.execution_time_ms,
+                },
+            )
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
         Ok(tonic::Response::new(UpdateTaskResponse {}))
     }
 
+    async fn refresh_content_url(
+        &self,
+        request: tonic::Request<indexify_coordinator::RefreshContentUrlRequest>,
+    ) -> Result<tonic::Response<indexify_coordinator::RefreshContentUrlResponse>, tonic::Status>
+    {
+        self.verify_executor(&request, &request.get_ref().executor_id)?;
+        let request = request.into_inner();
+        let signed_url = self
+            .coordinator
+            .refresh_content_url(&request.executor_id, &request.task_id)
+            .await
+            .map_err(|e| tonic::Status::failed_precondition(e.to_string()))?;
+        Ok(tonic::Response::new(
+            indexify_coordinator::RefreshContentUrlResponse {
+                content_url: signed_url.url,
+                content_url_expires_at: signed_url.expires_at,
+            },
+        ))
+    }
+
     async fn list_indexes(
         &self,
         request: Request<ListIndexesRequest>,
@@ -701,6 +1150,82 @@ impl CoordinatorService for CoordinatorServiceServer {
         Ok(tonic::Response::new(ListIndexesResponse { indexes }))
     }
 
+    async fn list_namespace_index_tables(
+        &self,
+        _request: Request<indexify_coordinator::ListNamespaceIndexTablesRequest>,
+    ) -> Result<Response<indexify_coordinator::ListNamespaceIndexTablesResponse>, Status> {
+        let namespaces = self
+            .coordinator
+            .list_namespace_index_tables()
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?
+            .into_iter()
+            .map(|(namespace, tables)| {
+                (
+                    namespace,
+                    indexify_coordinator::IndexTableNames {
+                        table_names: tables.into_iter().collect(),
+                    },
+                )
+            })
+            .collect();
+        Ok(Response::new(
+            indexify_coordinator::ListNamespaceIndexTablesResponse { namespaces },
+        ))
+    }
+
+    async fn set_namespace_retention(
+        &self,
+        request: Request<indexify_coordinator::SetNamespaceRetentionRequest>,
+    ) -> Result<Response<indexify_coordinator::SetNamespaceRetentionResponse>, Status> {
+        let request = request.into_inner();
+        let rules = request.retention.unwrap_or_default().into();
+        self.coordinator
+            .set_namespace_retention(&request.namespace, rules)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::SetNamespaceRetentionResponse {},
+        ))
+    }
+
+    async fn get_namespace_overview(
+        &self,
+        request: Request<GetNamespaceOverviewRequest>,
+    ) -> Result<Response<GetNamespaceOverviewResponse>, Status> {
+        let request = request.into_inner();
+        let overview = self
+            .coordinator
+            .get_namespace_index_overview(&request.namespace)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        let indexes = overview
+            .into_iter()
+            .map(|o| NamespaceIndexOverview {
+                index: Some(o.index.into()),
+                policy: o.extraction_policy.map(|p| p.into()),
+                extractor: o.extractor.map(|e| e.into()),
+            })
+            .collect();
+        Ok(tonic::Response::new(GetNamespaceOverviewResponse {
+            indexes,
+        }))
+    }
+
+    async fn get_usage_stats(
+        &self,
+        request: Request<GetUsageStatsRequest>,
+    ) -> Result<Response<GetUsageStatsResponse>, Status> {
+        let request = request.into_inner();
+        let namespace = (!request.namespace.is_empty()).then_some(request.namespace.as_str());
+        let stats = self
+            .coordinator
+            .get_usage_stats(namespace, request.from_day, request.to_day)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(GetUsageStatsResponse {
+            stats: stats.into_iter().map(|s| s.into()).collect(),
+        }))
+    }
+
     async fn get_index(
         &self,
         request: Request<GetIndexRequest>,
@@ -716,6 +1241,134 @@ impl CoordinatorService for CoordinatorServiceServer {
         }))
     }
 
+    async fn create_search_profile(
+        &self,
+        request: Request<CreateSearchProfileRequest>,
+    ) -> Result<Response<CreateSearchProfileResponse>, Status> {
+        let profile = request
+            .into_inner()
+            .profile
+            .ok_or_else(|| tonic::Status::invalid_argument("search profile is missing"))?;
+        self.coordinator
+            .create_search_profile(profile.into())
+            .await
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        Ok(tonic::Response::new(CreateSearchProfileResponse {}))
+    }
+
+    async fn get_search_profile(
+        &self,
+        request: Request<GetSearchProfileRequest>,
+    ) -> Result<Response<GetSearchProfileResponse>, Status> {
+        let request = request.into_inner();
+        let profile = self
+            .coordinator
+            .get_search_profile(&request.namespace, &request.name)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(GetSearchProfileResponse {
+            profile: profile.map(search_profile_to_proto),
+        }))
+    }
+
+    async fn list_search_profiles(
+        &self,
+        request: Request<ListSearchProfilesRequest>,
+    ) -> Result<Response<ListSearchProfilesResponse>, Status> {
+        let request = request.into_inner();
+        let profiles = self
+            .coordinator
+            .list_search_profiles(&request.namespace)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(ListSearchProfilesResponse {
+            profiles: profiles.into_iter().map(search_profile_to_proto).collect(),
+        }))
+    }
+
+    async fn delete_search_profile(
+        &self,
+        request: Request<DeleteSearchProfileRequest>,
+    ) -> Result<Response<DeleteSearchProfileResponse>, Status> {
+        let request = request.into_inner();
+        self.coordinator
+            .delete_search_profile(&request.namespace, &request.name)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(tonic::Response::new(DeleteSearchProfileResponse {}))
+    }
+
+    async fn create_search_feedback(
+        &self,
+        request: Request<indexify_coordinator::CreateSearchFeedbackRequest>,
+    ) -> Result<Response<indexify_coordinator::CreateSearchFeedbackResponse>, Status> {
+        let feedback = request
+            .into_inner()
+            .feedback
+            .ok_or_else(|| tonic::Status::invalid_argument("search feedback is missing"))?;
+        let feedback = self
+            .coordinator
+            .create_search_feedback(feedback.into())
+            .await
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::CreateSearchFeedbackResponse {
+                feedback: Some(feedback.into()),
+            },
+        ))
+    }
+
+    async fn list_search_feedback(
+        &self,
+        request: Request<indexify_coordinator::ListSearchFeedbackRequest>,
+    ) -> Result<Response<indexify_coordinator::ListSearchFeedbackResponse>, Status> {
+        let request = request.into_inner();
+        let query = internal_api::SearchFeedbackQuery {
+            namespace: request.namespace,
+            content_id: (!request.content_id.is_empty()).then_some(request.content_id),
+            start_time: (request.start_time > 0).then_some(request.start_time),
+            end_time: (request.end_time > 0).then_some(request.end_time),
+            cursor: (!request.cursor.is_empty()).then_some(request.cursor),
+            limit: request.limit as usize,
+        };
+        let (feedback, next_cursor) = self
+            .coordinator
+            .list_search_feedback(query)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::ListSearchFeedbackResponse {
+                feedback: feedback.into_iter().map(Into::into).collect(),
+                next_cursor: next_cursor.unwrap_or_default(),
+            },
+        ))
+    }
+
+    async fn get_search_feedback_counts(
+        &self,
+        request: Request<indexify_coordinator::GetSearchFeedbackCountsRequest>,
+    ) -> Result<Response<indexify_coordinator::GetSearchFeedbackCountsResponse>, Status> {
+        let request = request.into_inner();
+        let content_ids = request.content_ids.into_iter().collect();
+        let counts = self
+            .coordinator
+            .get_search_feedback_counts(&request.namespace, &content_ids)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::GetSearchFeedbackCountsResponse {
+                counts: counts
+                    .into_iter()
+                    .map(
+                        |(content_id, counts)| indexify_coordinator::SearchFeedbackCounts {
+                            content_id,
+                            positive: counts.positive,
+                            negative: counts.negative,
+                        },
+                    )
+                    .collect(),
+            },
+        ))
+    }
+
     async fn update_indexes_state(
         &self,
         request: Request<UpdateIndexesStateRequest>,
@@ -761,7 +1414,7 @@ impl CoordinatorService for CoordinatorServiceServer {
         let req = req.into_inner();
         let content_metadata = self
             .coordinator
-            .get_content_metadata(req.content_list)
+            .get_content_metadata(req.content_list, req.include_tombstoned)
             .await
             .map_err(|e| tonic::Status::aborted(e.to_string()))?;
         Ok(Response::new(
@@ -821,6 +1474,138 @@ impl CoordinatorService for CoordinatorServiceServer {
         ))
     }
 
+    async fn rebuild_content_tree(
+        &self,
+        req: Request<indexify_coordinator::RebuildContentTreeRequest>,
+    ) -> Result<Response<indexify_coordinator::RebuildContentTreeResponse>, Status> {
+        let req = req.into_inner();
+        let task_count = self
+            .coordinator
+            .rebuild_content_tree(&req.namespace, &req.content_id, &req.index)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::RebuildContentTreeResponse {
+                task_count: task_count as u64,
+            },
+        ))
+    }
+
+    async fn reprocess_state_change(
+        &self,
+        req: Request<indexify_coordinator::ReprocessStateChangeRequest>,
+    ) -> Result<Response<indexify_coordinator::ReprocessStateChangeResponse>, Status> {
+        let req = req.into_inner();
+        self.coordinator
+            .reprocess_state_change(StateChangeId::new(req.state_change_id))
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::ReprocessStateChangeResponse {},
+        ))
+    }
+
+    async fn discard_state_change(
+        &self,
+        req: Request<indexify_coordinator::DiscardStateChangeRequest>,
+    ) -> Result<Response<indexify_coordinator::DiscardStateChangeResponse>, Status> {
+        let req = req.into_inner();
+        self.coordinator
+            .discard_state_change(StateChangeId::new(req.state_change_id), &req.note)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(
+            indexify_coordinator::DiscardStateChangeResponse {},
+        ))
+    }
+
+    async fn hold_content_tasks(
+        &self,
+        req: Request<indexify_coordinator::HoldContentTasksRequest>,
+    ) -> Result<Response<indexify_coordinator::HoldContentTasksResponse>, Status> {
+        let req = req.into_inner();
+        self.coordinator
+            .hold_content_tasks(req.content_ids, Duration::from_millis(req.hold_ms));
+        Ok(Response::new(
+            indexify_coordinator::HoldContentTasksResponse {},
+        ))
+    }
+
+    async fn get_content_task_assignments(
+        &self,
+        req: Request<indexify_coordinator::GetContentTaskAssignmentsRequest>,
+    ) -> Result<Response<indexify_coordinator::GetContentTaskAssignmentsResponse>, Status> {
+        let req = req.into_inner();
+        let assignments = self
+            .coordinator
+            .content_task_assignments(&req.content_id)
+            .await
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        let response = match assignments {
+            Some(assignments) => indexify_coordinator::GetContentTaskAssignmentsResponse {
+                assigned: true,
+                assignments: assignments
+                    .into_iter()
+                    .map(
+                        |(task, executor)| indexify_coordinator::ContentTaskAssignment {
+                            task: Some(task.into()),
+                            executor_id: executor.id,
+                            executor_addr: executor.addr,
+                        },
+                    )
+                    .collect(),
+            },
+            None => indexify_coordinator::GetContentTaskAssignmentsResponse {
+                assigned: false,
+                assignments: vec![],
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn content_exists(
+        &self,
+        req: Request<ContentExistsRequest>,
+    ) -> Result<Response<ContentExistsResponse>, Status> {
+        let req = req.into_inner();
+        let keys = req
+            .entries
+            .into_iter()
+            .map(|entry| {
+                if !entry.id.is_empty() {
+                    Ok(internal_api::ContentLookupKey::Id(entry.id))
+                } else if !entry.hash.is_empty() {
+                    Ok(internal_api::ContentLookupKey::Hash(entry.hash))
+                } else {
+                    Err(tonic::Status::invalid_argument(
+                        "each entry needs either an id or a hash",
+                    ))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let results = self
+            .coordinator
+            .check_content_exists(&req.namespace, &keys)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(ContentExistsResponse {
+            results: results.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_referenced_storage_urls(
+        &self,
+        req: Request<GetReferencedStorageUrlsRequest>,
+    ) -> Result<Response<GetReferencedStorageUrlsResponse>, Status> {
+        let storage_urls = req.into_inner().storage_urls.into_iter().collect();
+        let referenced = self
+            .coordinator
+            .get_referenced_storage_urls(&storage_urls)
+            .map_err(|e| tonic::Status::aborted(e.to_string()))?;
+        Ok(Response::new(GetReferencedStorageUrlsResponse {
+            storage_urls: referenced.into_iter().collect(),
+        }))
+    }
+
     async fn list_state_changes(
         &self,
         _req: Request<ListStateChangesRequest>,
@@ -1013,8 +1798,115 @@ async fn metrics_handler(
     )))
 }
 
+/// Reports whether this node serves requests, along with the readiness of
+/// each of its components. Responds with 503 until the node is ready.
+async fn health(State(app): State<Arc<state::App>>) -> (StatusCode, axum::Json<ReadinessReport>) {
+    let report = app.readiness.report();
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, axum::Json(report))
+}
+
+/// Lists the migrations of the state store of this node and whether they
+/// have been applied
+async fn list_migrations(
+    State(app): State<Arc<state::App>>,
+) -> Result<axum::Json<Vec<MigrationInfo>>, IndexifyAPIError> {
+    let migrations = app
+        .state_machine
+        .list_migrations()
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(axum::Json(migrations))
+}
+
+/// Explains why a task is or isn't assigned to an executor
+async fn explain_task_scheduling(
+    Path(task_id): Path<String>,
+    State(app): State<Arc<state::App>>,
+) -> Result<axum::Json<SchedulingExplanation>, IndexifyAPIError> {
+    TaskAllocator::new(app)
+        .explain_task(&task_id)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?
+        .map(axum::Json)
+        .ok_or_else(|| {
+            IndexifyAPIError::new(
+                StatusCode::NOT_FOUND,
+                &format!("task {} not found", task_id),
+            )
+        })
+}
+
 use std::borrow::Cow;
 
+/// Components which must be ready for the coordinator to serve the RPC of
+/// the path
+fn required_readiness(path: &str) -> &'static [ReadinessComponent] {
+    match path.rsplit('/').next().unwrap_or_default() {
+        "GetServerInfo" | "GetRaftMetricsSnapshot" => &[],
+        "RegisterExecutor" | "UpdateExecutorExtractor" | "Heartbeat" | "UpdateTask" => &[
+            ReadinessComponent::StateMachine,
+            ReadinessComponent::Scheduler,
+        ],
+        _ => &[ReadinessComponent::StateMachine],
+    }
+}
+
+/// Rejects the RPCs which need components which aren't ready with
+/// `Unavailable`
+#[derive(Clone)]
+struct ReadinessLayer {
+    readiness: Arc<Readiness>,
+}
+
+impl<S> Layer<S> for ReadinessLayer {
+    type Service = ReadinessWrapper<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ReadinessWrapper {
+            inner,
+            readiness: self.readiness.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ReadinessWrapper<S> {
+    inner: S,
+    readiness: Arc<Readiness>,
+}
+
+impl<S, ReqBody> Service<tonic::codegen::http::request::Request<ReqBody>> for ReadinessWrapper<S>
+where
+    S: Service<
+            tonic::codegen::http::request::Request<ReqBody>,
+            Response = tonic::codegen::http::response::Response<BoxBody>,
+        > + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: tonic::codegen::http::request::Request<ReqBody>) -> Self::Future {
+        if let Err(status) = self.readiness.check(required_readiness(req.uri().path())) {
+            return Box::pin(async move { Ok(status.to_http()) });
+        }
+        let mut inner = self.inner.clone();
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 struct TraceLayer {
     name: &'static str,
@@ -1139,6 +2031,12 @@ where
 fn start_server(app: &CoordinatorServer) -> Result<JoinHandle<Result<()>>> {
     let server = axum::Router::new()
         .route("/metrics", get(metrics_handler))
+        .route("/health", get(health))
+        .route("/migrations", get(list_migrations))
+        .route(
+            "/tasks/:task_id/scheduling_explain",
+            get(explain_task_scheduling),
+        )
         .with_state(app.shared_state.clone());
     let addr: SocketAddr = format!(
         "{}:{}",
@@ -1190,9 +2088,16 @@ impl CoordinatorServer {
 
     pub async fn run(&self) -> Result<(), anyhow::Error> {
         let (shutdown_tx, shutdown_rx) = watch::channel(());
+        let verify_executor_identity = self
+            .config
+            .coordinator_tls
+            .as_ref()
+            .map(|tls| tls.api && tls.ca_file.is_some() && tls.verify_executor_identity)
+            .unwrap_or(false);
         let svc = CoordinatorServiceServer {
             coordinator: self.coordinator.clone(),
             shutdown_rx: shutdown_rx.clone(),
+            verify_executor_identity,
         };
         let srvr =
             indexify_coordinator::coordinator_service_server::CoordinatorServiceServer::new(svc)
@@ -1210,6 +2115,14 @@ impl CoordinatorServer {
         if let Err(e) = start_server(self) {
             error!("unable to start metrics server: {}", e);
         }
+        let delivery_worker = Arc::new(DeliveryWorker::new(
+            self.shared_state.clone(),
+            self.shared_state.delivery_config.clone(),
+        ));
+        delivery_worker.start(
+            shutdown_rx.clone(),
+            self.coordinator.get_leader_change_watcher(),
+        );
         tokio::spawn(async move {
             let _ = run_scheduler(
                 shutdown_rx,
@@ -1224,6 +2137,9 @@ impl CoordinatorServer {
             .layer(TraceLayer {
                 name: "indexify-coordinator-grpc",
             })
+            .layer(ReadinessLayer {
+                readiness: self.shared_state.readiness.clone(),
+            })
             .into_inner();
 
         if let Some(tls_config) = self.config.coordinator_tls.as_ref() {
@@ -1301,12 +2217,46 @@ async fn run_scheduler(
     coordinator: Arc<Coordinator>,
 ) -> Result<()> {
     let is_leader = AtomicBool::new(false);
+    let mut availability_interval = tokio::time::interval(EXTRACTOR_AVAILABILITY_CHECK_PERIOD);
+    let readiness = coordinator.shared_state.readiness.clone();
+    let mut readiness_rx = readiness.subscribe();
+    let mut retention_interval = tokio::time::interval(Duration::from_secs(
+        coordinator
+            .shared_state
+            .retention_config
+            .sweep_interval_secs
+            .max(1),
+    ));
 
     loop {
         tokio::select! {
-            _ = state_watcher_rx.changed() => {
+            _ = availability_interval.tick() => {
                 if is_leader.load(Ordering::Relaxed) {
-                   let _state_change = state_watcher_rx.borrow_and_update().clone();
+                    if let Err(err) = coordinator.check_extractor_availability().await {
+                        error!("error checking extractor availability: {:?}", err);
+                    }
+                }
+            },
+            _ = retention_interval.tick() => {
+                if is_leader.load(Ordering::Relaxed) &&
+                    readiness.is_ready(ReadinessComponent::StateMachine) {
+                    match coordinator.sweep_retention().await {
+                        Ok(sweep) if !sweep.deferred.is_empty() => info!(
+                            "deferred expiring {} content trees with unfinished tasks",
+                            sweep.deferred.len()
+                        ),
+                        Ok(_) => {}
+                        Err(err) => error!("error sweeping expired content: {:?}", err),
+                    }
+                }
+            },
+            _ = state_watcher_rx.changed() => {
+                if is_leader.load(Ordering::Relaxed) &&
+                    readiness.is_ready(ReadinessComponent::StateMachine) {
+                   let state_change = state_watcher_rx.borrow_and_update().clone();
+                   //  A change published again was reprocessed, possibly through
+                   //  another node, and is retried even if it was skipped
+                   coordinator.clear_state_change_failures(&state_change.id);
                    if let Err(err) = coordinator.run_scheduler().await {
                           error!("error processing and distributing work: {:?}", err);
                    }
@@ -1320,12 +2270,38 @@ async fn run_scheduler(
                 let leader_state = *leader_changed.borrow_and_update();
                 info!("leader changed detected: {:?}", leader_state);
                 is_leader.store(leader_state, std::sync::atomic::Ordering::Relaxed);
+                catch_up_scheduler(&coordinator, leader_state).await;
+            }
+            _ = readiness_rx.changed() => {
+                catch_up_scheduler(&coordinator, is_leader.load(Ordering::Relaxed)).await;
             }
         }
     }
     Ok(())
 }
 
+/// Updates the readiness of the scheduler. The scheduler of the leader is
+/// ready once it ran a pass over the state changes left unprocessed while
+/// the state machine wasn't ready.
+async fn catch_up_scheduler(coordinator: &Coordinator, is_leader: bool) {
+    let readiness = &coordinator.shared_state.readiness;
+    if !readiness.is_ready(ReadinessComponent::StateMachine) {
+        readiness.scheduler_waiting("the state machine isn't ready");
+        return;
+    }
+    if !is_leader {
+        readiness.scheduler_standby();
+        return;
+    }
+    if readiness.is_ready(ReadinessComponent::Scheduler) {
+        return;
+    }
+    match coordinator.run_scheduler().await {
+        Ok(_) => readiness.scheduler_ready(),
+        Err(err) => error!("error catching up the scheduler: {:?}", err),
+    }
+}
+
 #[tracing::instrument]
 async fn shutdown_signal(shutdown_tx: Sender<()>) {
     let ctrl_c = async {
@@ -1354,3 +2330,91 @@ async fn shutdown_signal(shutdown_tx: Sender<()>) {
     shutdown_tx.send(()).unwrap();
     info!("signal received, shutting down server gracefully");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::RaftTestCluster;
+
+    async fn wait_until_ready(readiness: &Readiness) {
+        let mut changes_rx = readiness.subscribe();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !readiness.is_ready(ReadinessComponent::StateMachine) {
+                changes_rx.changed().await.unwrap();
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_server_info() {
+        let info = server_info();
+        assert_eq!(info.server_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.api_versions, vec!["v1"]);
+        assert!(info.features.contains(&"search_profiles".to_string()));
+    }
+
+    #[test]
+    fn test_required_readiness() {
+        let path = |rpc: &str| format!("/indexify_coordinator.CoordinatorService/{}", rpc);
+        assert!(required_readiness(&path("GetServerInfo")).is_empty());
+        assert_eq!(
+            required_readiness(&path("ListContent")),
+            &[ReadinessComponent::StateMachine]
+        );
+        assert_eq!(
+            required_readiness(&path("Heartbeat")),
+            &[
+                ReadinessComponent::StateMachine,
+                ReadinessComponent::Scheduler
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_rejected_until_replayed() -> Result<()> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        wait_until_ready(&node.readiness).await;
+
+        //  Replay up to entries which aren't written yet
+        let applied = node
+            .forwardable_raft
+            .raft
+            .metrics()
+            .borrow()
+            .last_applied
+            .map(|log_id| log_id.index)
+            .unwrap_or_default();
+        node.track_replay(Some(applied + 2));
+        let inner = tower::service_fn(|_req: tonic::codegen::http::Request<()>| async {
+            Ok::<_, std::convert::Infallible>(tonic::codegen::http::Response::new(
+                tonic::body::empty_body(),
+            ))
+        });
+        let mut service = ReadinessLayer {
+            readiness: node.readiness.clone(),
+        }
+        .layer(inner);
+        let request = |rpc: &str| {
+            tonic::codegen::http::Request::builder()
+                .uri(format!("/indexify_coordinator.CoordinatorService/{}", rpc))
+                .body(())
+                .unwrap()
+        };
+
+        let response = service.call(request("ListContent")).await.unwrap();
+        assert_eq!(response.headers()["grpc-status"], "14");
+        let response = service.call(request("GetServerInfo")).await.unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+
+        node.create_namespace("namespace-1").await?;
+        node.create_namespace("namespace-2").await?;
+        wait_until_ready(&node.readiness).await;
+        let response = service.call(request("ListContent")).await.unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+        Ok(())
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     hash::{DefaultHasher, Hash, Hasher},
     str::FromStr,
-    sync::Arc,
-    time::SystemTime,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{anyhow, Result};
@@ -16,19 +16,24 @@ use itertools::Itertools;
 use mime::Mime;
 use nanoid::nanoid;
 use sha2::{Digest, Sha256};
-use tracing::{error, info};
+use tokio::sync::{broadcast, watch};
+use tracing::{error, info, warn};
 
 use crate::{
     api::{self, BeginExtractedContentIngest, ExtractionGraphRequest},
     blob_storage::{BlobStorage, BlobStorageWriter, PutResult, StoragePartWriter},
     coordinator_client::CoordinatorClient,
     grpc_helper::GrpcHelper,
+    language_detection::LanguageDetector,
     metadata_storage::{
         query_engine::{run_query, StructuredDataRow},
         ExtractedMetadata,
         MetadataReaderTS,
         MetadataStorageTS,
     },
+    search_log::{self, diff_results, SearchLogEntry, SearchReplayResult},
+    url_fetch::UrlFetcher,
+    utils::day_to_date,
     vector_index::{ScoredText, VectorIndexManager},
 };
 
@@ -45,12 +50,103 @@ fn index_in_features(
     })
 }
 
+/// Extraction policies by id. Tasks reference the version of the policy
+/// they were created from, a cached policy older than that version is
+/// fetched again.
+#[derive(Default)]
+struct ExtractionPolicyCache {
+    policies: Mutex<HashMap<String, api::ExtractionPolicy>>,
+}
+
+/// Result of a garbage collection task run by `perform_gc_tasks`
+#[derive(Debug)]
+pub enum GcTaskResult {
+    Completed,
+    /// Vectors couldn't be removed from some of the output tables, the task
+    /// is resumed without the tables which are completed
+    Incomplete {
+        completed_output_tables: Vec<String>,
+    },
+    Failed(anyhow::Error),
+}
+
+/// Content ids whose vectors are removed from each index by the garbage
+/// collection tasks which delete content, skipping the output tables that
+/// previous attempts of the tasks completed
+fn gc_vector_deletions(gc_tasks: &[&indexify_coordinator::GcTask]) -> HashMap<String, Vec<String>> {
+    let mut deletions: HashMap<String, Vec<String>> = HashMap::new();
+    for gc_task in gc_tasks {
+        for table in &gc_task.output_tables {
+            if !gc_task.completed_output_tables.contains(table) {
+                deletions
+                    .entry(table.clone())
+                    .or_default()
+                    .push(gc_task.content_id.clone());
+            }
+        }
+    }
+    deletions
+}
+
+/// Locks of the idempotency keys of the ingestion requests in flight on this
+/// server, so that a retry waits for the result of the first request instead
+/// of writing the content again
+#[derive(Default)]
+struct IngestionKeyLocks {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl IngestionKeyLocks {
+    async fn lock(&self, namespace: &str, key: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            //  Drop the locks which are neither held nor waited for
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks
+                .entry(format!("{}::{}", namespace, key))
+                .or_default()
+                .clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+impl ExtractionPolicyCache {
+    fn get(&self, id: &str, min_version: u64) -> Option<api::ExtractionPolicy> {
+        let policies = self.policies.lock().unwrap();
+        policies
+            .get(id)
+            .filter(|policy| policy.version >= min_version)
+            .cloned()
+    }
+
+    fn insert(&self, policy: api::ExtractionPolicy) {
+        let mut policies = self.policies.lock().unwrap();
+        match policies.get(&policy.id) {
+            //  A concurrent fetch may have cached a newer version already
+            Some(cached) if cached.version > policy.version => {}
+            _ => {
+                policies.insert(policy.id.clone(), policy);
+            }
+        }
+    }
+}
+
+/// How long a namespace overview is served from the cache. Building it reads
+/// every index of the namespace from the coordinator and the vector database.
+const NAMESPACE_OVERVIEW_TTL: Duration = Duration::from_secs(5);
+
 pub struct DataManager {
     pub vector_index_manager: Arc<VectorIndexManager>,
     metadata_index_manager: MetadataStorageTS,
     metadata_reader: MetadataReaderTS,
     blob_storage: Arc<BlobStorage>,
     coordinator_client: Arc<CoordinatorClient>,
+    extraction_policy_cache: ExtractionPolicyCache,
+    namespace_overviews: Mutex<HashMap<String, (Instant, api::NamespaceOverview)>>,
+    ingestion_key_locks: IngestionKeyLocks,
+    language_detector: LanguageDetector,
+    url_fetcher: UrlFetcher,
 }
 
 impl fmt::Debug for DataManager {
@@ -67,27 +163,57 @@ impl DataManager {
         blob_storage: Arc<BlobStorage>,
         coordinator_client: Arc<CoordinatorClient>,
     ) -> Self {
+        let language_detector =
+            LanguageDetector::new(coordinator_client.config.language_detection.clone());
+        let url_fetcher = UrlFetcher::new(coordinator_client.config.url_fetch.clone());
         DataManager {
             vector_index_manager,
             metadata_index_manager,
             metadata_reader,
             blob_storage,
             coordinator_client,
+            extraction_policy_cache: ExtractionPolicyCache::default(),
+            namespace_overviews: Mutex::new(HashMap::new()),
+            ingestion_key_locks: IngestionKeyLocks::default(),
+            language_detector,
+            url_fetcher,
         }
     }
 
+    /// Waits for the other ingestion requests on this server with the same
+    /// idempotency key to finish. Requests made to different servers are
+    /// serialized by the coordinator.
+    pub async fn lock_ingestion_key(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> tokio::sync::OwnedMutexGuard<()> {
+        self.ingestion_key_locks.lock(namespace, key).await
+    }
+
+    /// Returns the id of the content created by the first ingestion request
+    /// with the idempotency key, unless the key expired
+    pub async fn get_ingestion_key(&self, namespace: &str, key: &str) -> Result<Option<String>> {
+        let req = indexify_coordinator::GetIngestionKeyRequest {
+            namespace: namespace.to_string(),
+            key: key.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_ingestion_key(req)
+            .await?
+            .into_inner();
+        Ok(response.found.then_some(response.content_id))
+    }
+
     #[tracing::instrument]
     pub async fn list_namespaces(&self) -> Result<Vec<api::DataNamespace>> {
         let req = indexify_coordinator::ListNamespaceRequest {};
         let response = self.coordinator_client.get().await?.list_ns(req).await?;
         let namespaces = response.into_inner().namespaces;
-        let data_namespaces = namespaces
-            .into_iter()
-            .map(|r| api::DataNamespace {
-                name: r.name,
-                extraction_graphs: r.extraction_graphs.into_iter().map(Into::into).collect(),
-            })
-            .collect();
+        let data_namespaces = namespaces.into_iter().map(Into::into).collect();
         Ok(data_namespaces)
     }
 
@@ -103,6 +229,28 @@ impl DataManager {
             .await?
             .create_ns(request)
             .await?;
+        if namespace.retention != api::RetentionRules::default() {
+            self.set_namespace_retention(&namespace.name, namespace.retention)
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument]
+    pub async fn set_namespace_retention(
+        &self,
+        namespace: &str,
+        retention: api::RetentionRules,
+    ) -> Result<()> {
+        let req = indexify_coordinator::SetNamespaceRetentionRequest {
+            namespace: namespace.to_string(),
+            retention: Some(retention.into()),
+        };
+        self.coordinator_client
+            .get()
+            .await?
+            .set_namespace_retention(req)
+            .await?;
         Ok(())
     }
 
@@ -122,6 +270,92 @@ impl DataManager {
         Ok(namespace.into())
     }
 
+    /// Clones the configuration of the namespace into a new one, creates the
+    /// cloned indexes and then samples content into the clone
+    pub async fn clone_namespace(
+        &self,
+        source: &str,
+        req: api::CloneNamespaceRequest,
+    ) -> Result<api::CloneNamespaceResponse> {
+        let indexes = self
+            .coordinator_client
+            .get()
+            .await?
+            .clone_namespace(indexify_coordinator::CloneNamespaceRequest {
+                source: source.to_string(),
+                name: req.name.clone(),
+            })
+            .await?
+            .into_inner()
+            .indexes;
+        for index in &indexes {
+            let embedding_schema: internal_api::EmbeddingSchema =
+                serde_json::from_str(&index.schema)?;
+            self.vector_index_manager
+                .create_index(&index.table_name, embedding_schema)
+                .await?;
+        }
+        self.metadata_index_manager
+            .create_metadata_table(&req.name)
+            .await?;
+        self.coordinator_client
+            .get()
+            .await?
+            .update_indexes_state(indexify_coordinator::UpdateIndexesStateRequest {
+                indexes: indexes.clone(),
+            })
+            .await?;
+
+        //  Content is sampled once the indexes exist, so the outputs of the
+        //  cloned policies have somewhere to land
+        let sampled_content = if req.sample_size > 0 {
+            self.coordinator_client
+                .get()
+                .await?
+                .sample_namespace_content(indexify_coordinator::SampleNamespaceContentRequest {
+                    source: source.to_string(),
+                    namespace: req.name.clone(),
+                    sample_size: req.sample_size as u64,
+                })
+                .await?
+                .into_inner()
+                .content_ids
+        } else {
+            Vec::new()
+        };
+        Ok(api::CloneNamespaceResponse {
+            namespace: req.name,
+            indexes: indexes.into_iter().map(|index| index.name).collect(),
+            sampled_content,
+        })
+    }
+
+    pub async fn set_namespace_alias(&self, alias: &str, namespace: &str) -> Result<()> {
+        self.coordinator_client
+            .get()
+            .await?
+            .set_namespace_alias(indexify_coordinator::SetNamespaceAliasRequest {
+                alias: alias.to_string(),
+                namespace: namespace.to_string(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the namespace an alias points to, or the name itself when it
+    /// isn't an alias
+    pub async fn resolve_namespace(&self, name: &str) -> Result<String> {
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .resolve_namespace(indexify_coordinator::ResolveNamespaceRequest {
+                name: name.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner().namespace)
+    }
+
     pub async fn get_extraction_policy(&self, id: &str) -> Result<api::ExtractionPolicy> {
         let req = indexify_coordinator::GetExtractionPolicyRequest {
             extraction_policy_id: id.to_string(),
@@ -139,6 +373,21 @@ impl DataManager {
         Ok(policy.into())
     }
 
+    /// Returns the extraction policy at the version referenced by a task or
+    /// a later one, from the cache when it holds a recent enough version
+    pub async fn get_extraction_policy_version(
+        &self,
+        id: &str,
+        version: u64,
+    ) -> Result<api::ExtractionPolicy> {
+        if let Some(policy) = self.extraction_policy_cache.get(id, version) {
+            return Ok(policy);
+        }
+        let policy = self.get_extraction_policy(id).await?;
+        self.extraction_policy_cache.insert(policy.clone());
+        Ok(policy)
+    }
+
     pub async fn create_extraction_graph(
         &self,
         namespace: &str,
@@ -158,6 +407,8 @@ impl DataManager {
                 created_at: SystemTime::now()
                     .duration_since(SystemTime::UNIX_EPOCH)?
                     .as_secs() as i64,
+                index_attributes: ep.index_attributes.to_json_string(),
+                version_strategy: ep.version_strategy.to_string(),
             };
             extraction_policies.push(req);
         }
@@ -228,6 +479,43 @@ impl DataManager {
         Ok(())
     }
 
+    /// Leaves the tasks of the content out of executor heartbeats for `hold`,
+    /// a zero hold releases them
+    pub async fn hold_content_tasks(&self, content_ids: &[String], hold: Duration) -> Result<()> {
+        let req = indexify_coordinator::HoldContentTasksRequest {
+            content_ids: content_ids.to_vec(),
+            hold_ms: hold.as_millis() as u64,
+        };
+        self.coordinator_client
+            .get()
+            .await?
+            .hold_content_tasks(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(())
+    }
+
+    /// Unfinished tasks of the content along with the executors they are
+    /// assigned to, None until every extraction policy matching the content
+    /// has a task assigned to an executor
+    pub async fn content_task_assignments(
+        &self,
+        content_id: &str,
+    ) -> Result<Option<Vec<indexify_coordinator::ContentTaskAssignment>>> {
+        let req = indexify_coordinator::GetContentTaskAssignmentsRequest {
+            content_id: content_id.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_content_task_assignments(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?
+            .into_inner();
+        Ok(response.assigned.then_some(response.assignments))
+    }
+
     pub async fn list_content(
         &self,
         namespace: &str,
@@ -276,11 +564,23 @@ impl DataManager {
         namespace: &str,
         content_list: Vec<api::ContentWithId>,
         extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
-    ) -> Result<()> {
+    ) -> Result<Vec<String>> {
+        let mut content_ids = Vec::new();
         for content_with_id in content_list {
+            let key = content_with_id.idempotency_key.as_deref();
+            let _guard = match key {
+                Some(key) => Some(self.lock_ingestion_key(namespace, key).await),
+                None => None,
+            };
+            if let Some(key) = key {
+                if let Some(content_id) = self.get_ingestion_key(namespace, key).await? {
+                    content_ids.push(content_id);
+                    continue;
+                }
+            }
             let text = content_with_id.content;
             let stream = futures::stream::once(async { Ok(Bytes::from(text.bytes)) });
-            let content_metadata = self
+            let mut content_metadata = self
                 .write_content_bytes(
                     namespace,
                     Box::pin(stream),
@@ -292,33 +592,104 @@ impl DataManager {
                     &extraction_graph_names,
                 )
                 .await?;
+            content_metadata.acl = content_with_id.acl.map(Into::into);
+
+            let storage_url = content_metadata.storage_url.clone();
+            let content_id = self.create_content_metadata(content_metadata, key).await?;
+            if content_id != content_with_id.id {
+                //  A request with the same key on another server was first
+                if let Err(e) = self.delete_file(&storage_url).await {
+                    error!("unable to delete unreferenced file {}: {}", storage_url, e);
+                }
+            }
+            content_ids.push(content_id);
+        }
+        Ok(content_ids)
+    }
+
+    /// Runs a batch of garbage collection tasks and returns the result of
+    /// each task, in the order of the tasks
+    pub async fn perform_gc_tasks(
+        &self,
+        gc_tasks: &[indexify_coordinator::GcTask],
+    ) -> Vec<GcTaskResult> {
+        let delete_tasks: Vec<&indexify_coordinator::GcTask> = gc_tasks
+            .iter()
+            .filter(|gc_task| gc_task.task_type == indexify_coordinator::GcTaskType::Delete as i32)
+            .collect();
+        let mut delete_results = self
+            .delete_content_with_vectors(&delete_tasks)
+            .await
+            .into_iter();
 
-            let req = indexify_coordinator::CreateContentRequest {
-                content: Some(content_metadata),
+        let mut results = Vec::with_capacity(gc_tasks.len());
+        for gc_task in gc_tasks {
+            let result = match gc_task.task_type.try_into() {
+                Ok(indexify_coordinator::GcTaskType::Delete) => {
+                    delete_results.next().unwrap_or_else(|| {
+                        GcTaskResult::Failed(anyhow!(
+                            "no result for the deletion of content {}",
+                            gc_task.content_id
+                        ))
+                    })
+                }
+                Ok(indexify_coordinator::GcTaskType::UpdateLabels) => {
+                    match self.update_index_labels(gc_task).await {
+                        Ok(()) => GcTaskResult::Completed,
+                        Err(e) => GcTaskResult::Failed(e),
+                    }
+                }
+                _ => GcTaskResult::Completed,
             };
-            self.coordinator_client
-                .get()
-                .await?
-                .create_content(GrpcHelper::into_req(req))
-                .await
-                .map_err(|e| {
-                    anyhow!(
-                        "unable to write content metadata to coordinator {}",
-                        e.to_string()
-                    )
-                })?;
+            results.push(result);
         }
-        Ok(())
+        results
     }
 
-    pub async fn perform_gc_task(&self, gc_task: &indexify_coordinator::GcTask) -> Result<()> {
-        match gc_task.task_type.try_into() {
-            Ok(indexify_coordinator::GcTaskType::Delete) => self.delete_content(gc_task).await,
-            Ok(indexify_coordinator::GcTaskType::UpdateLabels) => {
-                self.update_index_labels(gc_task).await
+    /// Deletes the content of garbage collection tasks along with its
+    /// vectors and returns the result of each task, in the order of the
+    /// tasks. The vectors are removed from the output tables first, with one
+    /// delete per index. Content whose vectors couldn't be removed from every
+    /// table keeps its blob and metadata and its task is incomplete, so that
+    /// no vector outlives the content it was extracted from.
+    pub async fn delete_content_with_vectors(
+        &self,
+        gc_tasks: &[&indexify_coordinator::GcTask],
+    ) -> Vec<GcTaskResult> {
+        let index_results = self
+            .vector_index_manager
+            .remove_embeddings_from_indexes(gc_vector_deletions(gc_tasks))
+            .await;
+        for (index, result) in &index_results {
+            if let Err(e) = result {
+                error!("unable to remove vectors from index {}: {}", index, e);
             }
-            _ => Ok(()),
         }
+
+        let mut results = Vec::with_capacity(gc_tasks.len());
+        for gc_task in gc_tasks {
+            let completed_output_tables: Vec<String> = gc_task
+                .output_tables
+                .iter()
+                .filter(|table| {
+                    gc_task.completed_output_tables.contains(table) ||
+                        matches!(index_results.get(*table), Some(Ok(())))
+                })
+                .cloned()
+                .collect();
+            let result = if completed_output_tables.len() < gc_task.output_tables.len() {
+                GcTaskResult::Incomplete {
+                    completed_output_tables,
+                }
+            } else {
+                match self.delete_content(gc_task).await {
+                    Ok(()) => GcTaskResult::Completed,
+                    Err(e) => GcTaskResult::Failed(e),
+                }
+            };
+            results.push(result);
+        }
+        results
     }
 
     #[tracing::instrument]
@@ -333,6 +704,7 @@ impl DataManager {
             .await?
             .get_content_metadata(indexify_coordinator::GetContentMetadataRequest {
                 content_list: vec![gc_task.content_id.clone()],
+                include_tombstoned: false,
             })
             .await?
             .into_inner()
@@ -353,27 +725,35 @@ impl DataManager {
         let new_metadata = DataManager::combine_metadata(metadata, &[], content_metadata_labels);
         for table in &gc_task.output_tables {
             self.vector_index_manager
-                .update_metadata(table, gc_task.content_id.clone(), new_metadata.clone())
+                .update_metadata(
+                    &gc_task.namespace,
+                    table,
+                    gc_task.content_id.clone(),
+                    new_metadata.clone(),
+                )
                 .await?;
         }
         Ok(())
     }
 
+    /// Removes the blob and the metadata of the content of a garbage
+    /// collection task, its vectors are removed by
+    /// `delete_content_with_vectors`
     #[tracing::instrument]
-    pub async fn delete_content(&self, gc_task: &indexify_coordinator::GcTask) -> Result<()> {
+    async fn delete_content(&self, gc_task: &indexify_coordinator::GcTask) -> Result<()> {
+        let reason: internal_api::TombstoneReason =
+            gc_task.tombstone_reason.clone().unwrap_or_default().into();
+        info!(
+            "deleting content {} of namespace {}, tombstoned for {}",
+            gc_task.content_id, gc_task.namespace, reason
+        );
+
         //  Remove content from blob storage
         self.blob_storage.delete(&gc_task.blob_store_path).await?;
 
-        //  Remove features and embeddings from vector stores
-        for table in &gc_task.output_tables {
-            self.vector_index_manager
-                .remove_embedding(table, &gc_task.content_id)
-                .await?;
-        }
-
         //  Remove any metadata
         self.metadata_index_manager
-            .remove_metadata(&gc_task.namespace, &gc_task.content_id)
+            .delete_metadata_for_content(&gc_task.namespace, &gc_task.content_id)
             .await?;
 
         Ok(())
@@ -392,6 +772,8 @@ impl DataManager {
         mime: &str,
         labels: HashMap<String, String>,
         extraction_graph_names: &Vec<internal_api::ExtractionGraphName>,
+        acl: Option<internal_api::ContentAcl>,
+        idempotency_key: Option<&str>,
     ) -> Result<String> {
         if !(["https://", "http://", "s3://", "file://"]
             .iter()
@@ -400,6 +782,15 @@ impl DataManager {
             return Err(anyhow!("invalid file path, must be a url, s3 or file path"));
         }
         let _ = mime::Mime::from_str(mime).map_err(|e| anyhow!("invalid mime type {}", e))?;
+        let _guard = match idempotency_key {
+            Some(key) => Some(self.lock_ingestion_key(namespace, key).await),
+            None => None,
+        };
+        if let Some(key) = idempotency_key {
+            if let Some(content_id) = self.get_ingestion_key(namespace, key).await? {
+                return Ok(content_id);
+            }
+        }
         let current_ts_secs = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)?
             .as_secs();
@@ -419,32 +810,109 @@ impl DataManager {
             extraction_policy_ids: HashMap::new(),
             root_content_id: "".to_string(),
             extraction_graph_names: extraction_graph_names.clone(),
+            acl: acl.map(Into::into),
+            tombstone_reason: None,
         };
-        let req: indexify_coordinator::CreateContentRequest =
-            indexify_coordinator::CreateContentRequest {
-                content: Some(content_metadata),
-            };
-        self.coordinator_client
-            .get()
-            .await?
-            .create_content(GrpcHelper::into_req(req))
+        self.create_content_metadata(content_metadata, idempotency_key)
             .await
-            .map_err(|e| {
-                anyhow!(
-                    "unable to write content metadata to coordinator {}",
-                    e.to_string()
+    }
+
+    /// Fetches the content of each url, stores it and creates content with
+    /// the url as its file name. A url which can't be fetched or stored is
+    /// reported in its result without failing the others.
+    pub async fn fetch_urls(
+        &self,
+        namespace: &str,
+        urls: Vec<String>,
+        labels: HashMap<String, String>,
+        extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
+        acl: Option<internal_api::ContentAcl>,
+    ) -> Vec<api::FetchUrlResult> {
+        let mut results = Vec::new();
+        for url in urls {
+            let result = self
+                .fetch_url(
+                    namespace,
+                    &url,
+                    labels.clone(),
+                    extraction_graph_names.clone(),
+                    acl.clone(),
                 )
-            })?;
-        Ok(id)
+                .await;
+            if let Err(e) = &result {
+                warn!("unable to ingest content from {}: {}", url, e);
+            }
+            results.push(api::FetchUrlResult {
+                url,
+                content_id: result.as_ref().ok().cloned(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+        results
+    }
+
+    async fn fetch_url(
+        &self,
+        namespace: &str,
+        url: &str,
+        labels: HashMap<String, String>,
+        extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
+        acl: Option<internal_api::ContentAcl>,
+    ) -> Result<String> {
+        let fetched = self.url_fetcher.fetch(url).await?;
+        //  The blob keeps the extension of the url, or of the content type
+        let ext = std::path::Path::new(fetched.url.path())
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .or_else(|| {
+                mime_guess::get_mime_extensions(&fetched.mime)?
+                    .first()
+                    .copied()
+            });
+        let name = match ext {
+            Some(ext) => format!("{}.{}", nanoid!(16), ext),
+            None => nanoid!(16),
+        };
+        let bytes = fetched.bytes;
+        let stream = futures::stream::once(async move { Ok(bytes) });
+        let mut content_metadata = self
+            .upload_file(
+                namespace,
+                Box::pin(stream),
+                &name,
+                fetched.mime,
+                labels,
+                None,
+                extraction_graph_names,
+            )
+            .await?;
+        //  The source of content names the extraction policy which produced
+        //  it, so the url is kept as the file name
+        content_metadata.file_name = url.to_string();
+        content_metadata.acl = acl.map(Into::into);
+        self.create_content_metadata(content_metadata, None).await
     }
 
     pub async fn get_content_metadata(
+        &self,
+        namespace: &str,
+        content_ids: Vec<String>,
+    ) -> Result<Vec<api::ContentMetadata>> {
+        self.get_content_metadata_with_tombstoned(namespace, content_ids, false)
+            .await
+    }
+
+    /// Reads content like get_content_metadata, tombstoned content is
+    /// returned with the reason it was tombstoned if `include_tombstoned`
+    pub async fn get_content_metadata_with_tombstoned(
         &self,
         _namespace: &str,
         content_ids: Vec<String>,
+        include_tombstoned: bool,
     ) -> Result<Vec<api::ContentMetadata>> {
         let req = indexify_coordinator::GetContentMetadataRequest {
             content_list: content_ids,
+            include_tombstoned,
         };
         let response = self
             .coordinator_client
@@ -477,6 +945,83 @@ impl DataManager {
         Ok(content_list)
     }
 
+    /// Rebuilds the entries of the content tree in the index. The vectors of
+    /// the tree are deleted before the tasks which extract them again are
+    /// created, so a rebuild which fails half way can be retried. Returns the
+    /// number of tasks created.
+    pub async fn rebuild_content_tree(
+        &self,
+        namespace: &str,
+        content_id: &str,
+        index_name: &str,
+    ) -> Result<u64> {
+        let index = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_index(indexify_coordinator::GetIndexRequest {
+                namespace: namespace.to_string(),
+                name: index_name.to_string(),
+            })
+            .await?
+            .into_inner()
+            .index
+            .ok_or(anyhow!("Index not found"))?;
+        let content_ids = self
+            .get_content_tree_metadata(namespace, content_id.to_string())
+            .await?
+            .into_iter()
+            .map(|content| content.id)
+            .collect::<Vec<_>>();
+        if content_ids.is_empty() {
+            return Err(anyhow!("content {} not found", content_id));
+        }
+        self.vector_index_manager
+            .remove_embeddings(&index.table_name, content_ids)
+            .await?;
+        let req = indexify_coordinator::RebuildContentTreeRequest {
+            namespace: namespace.to_string(),
+            content_id: content_id.to_string(),
+            index: index_name.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .rebuild_content_tree(req)
+            .await?;
+        Ok(response.into_inner().task_count)
+    }
+
+    pub async fn content_exists(
+        &self,
+        namespace: &str,
+        entries: Vec<api::ContentExistsEntry>,
+    ) -> Result<Vec<internal_api::ContentExistence>> {
+        let req = indexify_coordinator::ContentExistsRequest {
+            namespace: namespace.to_string(),
+            entries: entries
+                .into_iter()
+                .map(|entry| indexify_coordinator::ContentExistsEntry {
+                    id: entry.id.unwrap_or_default(),
+                    hash: entry.hash.unwrap_or_default(),
+                })
+                .collect(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .content_exists(req)
+            .await?;
+        response
+            .into_inner()
+            .results
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+
     #[tracing::instrument(skip(self, data))]
     pub async fn upload_file(
         &self,
@@ -523,14 +1068,20 @@ impl DataManager {
         Ok(())
     }
 
+    /// Creates the content and returns its id. With an idempotency key that
+    /// was used before, nothing is created and the id of the content created
+    /// by the first request with the key is returned.
     pub async fn create_content_metadata(
         &self,
         content_metadata: indexify_coordinator::ContentMetadata,
-    ) -> Result<()> {
+        idempotency_key: Option<&str>,
+    ) -> Result<String> {
         let req = indexify_coordinator::CreateContentRequest {
             content: Some(content_metadata),
+            idempotency_key: idempotency_key.unwrap_or_default().to_string(),
         };
-        self.coordinator_client
+        let response = self
+            .coordinator_client
             .get()
             .await?
             .create_content(GrpcHelper::into_req(req))
@@ -541,7 +1092,7 @@ impl DataManager {
                     e.to_string()
                 )
             })?;
-        Ok(())
+        Ok(response.into_inner().content_id)
     }
 
     pub fn make_file_name(file_name: Option<&str>) -> String {
@@ -577,9 +1128,16 @@ impl DataManager {
         let file_name = DataManager::make_file_name(file_name);
 
         let mut hasher = Sha256::new();
+        //  The language is detected from the first bytes of text content
+        let sample_size = self.language_detector.sample_size(namespace, &content_type);
+        let mut sample = Vec::new();
         let hashed_stream = data.map(|item| match item {
             Ok(bytes) => {
                 hasher.update(&bytes);
+                if sample.len() < sample_size {
+                    let len = (sample_size - sample.len()).min(bytes.len());
+                    sample.extend_from_slice(&bytes[..len]);
+                }
                 Ok(bytes)
             }
             Err(e) => Err(e),
@@ -597,6 +1155,10 @@ impl DataManager {
         if original_content_id.is_some() {
             id = original_content_id.unwrap().to_string();
         }
+        let mut labels = labels;
+        if sample_size > 0 {
+            self.language_detector.label(&id, &sample, &mut labels);
+        }
         Ok(indexify_coordinator::ContentMetadata {
             id: id.clone(),
             file_name,
@@ -612,12 +1174,16 @@ impl DataManager {
             hash: content_hash,
             extraction_policy_ids: HashMap::new(),
             extraction_graph_names: extraction_graph_names.to_vec(),
+            acl: None,
+            tombstone_reason: None,
         })
     }
 
     pub async fn finish_extracted_content_write(
         &self,
         begin_ingest: BeginExtractedContentIngest,
+        output_content_ids: Vec<String>,
+        vectors_produced: u64,
     ) -> Result<()> {
         let outcome: indexify_coordinator::TaskOutcome = begin_ingest.task_outcome.into();
 
@@ -625,6 +1191,10 @@ impl DataManager {
             executor_id: begin_ingest.executor_id,
             task_id: begin_ingest.task_id,
             outcome: outcome as i32,
+            output_content_ids,
+            policy_version: begin_ingest.policy_version.unwrap_or_default(),
+            vectors_produced,
+            execution_time_ms: begin_ingest.execution_time_ms.unwrap_or_default(),
         };
         let res = self.coordinator_client.get().await?.update_task(req).await;
         if let Err(err) = res {
@@ -737,7 +1307,12 @@ impl DataManager {
                         index
                     );
                     self.vector_index_manager
-                        .update_metadata(index, content_metadata.id.clone(), new_metadata.clone())
+                        .update_metadata(
+                            &content_metadata.namespace,
+                            index,
+                            content_metadata.id.clone(),
+                            new_metadata.clone(),
+                        )
                         .await?;
                 }
             }
@@ -811,6 +1386,7 @@ impl DataManager {
     ) -> Result<()> {
         let req = indexify_coordinator::CreateContentRequest {
             content: Some(content_metadata.clone()),
+            idempotency_key: String::new(),
         };
         let res = self
             .coordinator_client
@@ -897,15 +1473,505 @@ impl DataManager {
         Ok(api_indexes)
     }
 
+    /// Returns the indexes of the namespace with the extraction policy and
+    /// extractor backing them, the number of vectors and the time of the last
+    /// write. Overviews are cached for `NAMESPACE_OVERVIEW_TTL`.
     #[tracing::instrument]
-    pub async fn search(
+    async fn usage_stats(
         &self,
         namespace: &str,
-        index_name: &str,
-        query: &str,
-        k: u64,
+        from_day: u64,
+        to_day: u64,
+    ) -> Result<Vec<indexify_coordinator::UsageStats>> {
+        let req = indexify_coordinator::GetUsageStatsRequest {
+            namespace: namespace.to_string(),
+            from_day,
+            to_day,
+        };
+        let resp = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_usage_stats(req)
+            .await?
+            .into_inner();
+        Ok(resp.stats)
+    }
+
+    /// Daily usage of each extractor in a namespace, for the days in the
+    /// inclusive range
+    pub async fn namespace_usage(
+        &self,
+        namespace: &str,
+        from_day: u64,
+        to_day: u64,
+    ) -> Result<api::NamespaceUsageResponse> {
+        let stats = self.usage_stats(namespace, from_day, to_day).await?;
+        let mut total = api::UsageCounters::default();
+        let buckets = stats
+            .iter()
+            .map(|s| {
+                let counters = api::UsageCounters::from(s);
+                total.add(&counters);
+                api::UsageBucket {
+                    date: day_to_date(s.day),
+                    extractor: s.extractor.clone(),
+                    counters,
+                }
+            })
+            .collect();
+        Ok(api::NamespaceUsageResponse {
+            namespace: namespace.to_string(),
+            from: day_to_date(from_day),
+            to: day_to_date(to_day),
+            buckets,
+            total,
+        })
+    }
+
+    /// Usage of every namespace of the cluster, for the days in the
+    /// inclusive range
+    pub async fn usage_rollup(
+        &self,
+        from_day: u64,
+        to_day: u64,
+    ) -> Result<api::UsageRollupResponse> {
+        let stats = self.usage_stats("", from_day, to_day).await?;
+        let mut namespaces: BTreeMap<String, api::UsageCounters> = BTreeMap::new();
+        let mut total = api::UsageCounters::default();
+        for s in &stats {
+            let counters = api::UsageCounters::from(s);
+            namespaces
+                .entry(s.namespace.clone())
+                .or_default()
+                .add(&counters);
+            total.add(&counters);
+        }
+        Ok(api::UsageRollupResponse {
+            from: day_to_date(from_day),
+            to: day_to_date(to_day),
+            namespaces: namespaces
+                .into_iter()
+                .map(|(namespace, counters)| api::NamespaceUsageTotals {
+                    namespace,
+                    counters,
+                })
+                .collect(),
+            total,
+        })
+    }
+
+    pub async fn namespace_overview(&self, namespace: &str) -> Result<api::NamespaceOverview> {
+        if let Some((created_at, overview)) =
+            self.namespace_overviews.lock().unwrap().get(namespace)
+        {
+            if created_at.elapsed() < NAMESPACE_OVERVIEW_TTL {
+                return Ok(overview.clone());
+            }
+        }
+        let req = indexify_coordinator::GetNamespaceOverviewRequest {
+            namespace: namespace.to_string(),
+        };
+        let resp = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_namespace_overview(req)
+            .await?
+            .into_inner();
+        let index_events = self.vector_index_manager.index_events();
+        let mut indexes = Vec::new();
+        for entry in resp.indexes {
+            let Some(index) = entry.index else {
+                continue;
+            };
+            let table_name = index.table_name.clone();
+            let graph_name = index.graph_name.clone();
+            let degraded = entry.policy.is_none() || entry.extractor.is_none();
+            let mut warnings = Vec::new();
+            if entry.policy.is_none() {
+                warnings.push(format!(
+                    "extraction policy {} of graph {} not found",
+                    index.extraction_policy, graph_name
+                ));
+            }
+            if entry.extractor.is_none() {
+                warnings.push(format!("extractor {} not registered", index.extractor));
+            }
+            let vector_count = match self.vector_index_manager.num_vectors(&table_name).await {
+                Ok(count) => Some(count),
+                Err(e) => {
+                    warnings.push(format!("unable to count vectors: {}", e));
+                    None
+                }
+            };
+            let last_write_at = index_events
+                .last_write(&table_name)
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            indexes.push(api::IndexOverview {
+                index: index.try_into()?,
+                extraction_graph: graph_name,
+                extraction_policy: entry.policy.map(|p| p.name),
+                extractor: entry.extractor.map(|e| e.name),
+                vector_count,
+                last_write_at,
+                degraded,
+                warnings,
+            });
+        }
+        let overview = api::NamespaceOverview { indexes };
+        self.namespace_overviews
+            .lock()
+            .unwrap()
+            .insert(namespace.to_string(), (Instant::now(), overview.clone()));
+        Ok(overview)
+    }
+
+    #[tracing::instrument]
+    pub async fn create_search_profile(
+        &self,
+        namespace: &str,
+        profile: api::CreateSearchProfile,
+    ) -> Result<()> {
+        let profile = internal_api::SearchProfile {
+            namespace: namespace.to_string(),
+            name: profile.name,
+            index: profile.index,
+            filters: profile.filters,
+            k: profile.k,
+            min_score: profile.min_score,
+        };
+        let req = indexify_coordinator::CreateSearchProfileRequest {
+            profile: Some(profile.into()),
+        };
+        self.coordinator_client
+            .get()
+            .await?
+            .create_search_profile(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(())
+    }
+
+    pub async fn get_search_profile(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<api::SearchProfile>> {
+        let req = indexify_coordinator::GetSearchProfileRequest {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_search_profile(req)
+            .await?;
+        Ok(response.into_inner().profile.map(Into::into))
+    }
+
+    pub async fn list_search_profiles(&self, namespace: &str) -> Result<Vec<api::SearchProfile>> {
+        let req = indexify_coordinator::ListSearchProfilesRequest {
+            namespace: namespace.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .list_search_profiles(req)
+            .await?;
+        Ok(response
+            .into_inner()
+            .profiles
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn delete_search_profile(&self, namespace: &str, name: &str) -> Result<()> {
+        let req = indexify_coordinator::DeleteSearchProfileRequest {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        };
+        self.coordinator_client
+            .get()
+            .await?
+            .delete_search_profile(req)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_search_feedback(
+        &self,
+        namespace: &str,
+        feedback: api::CreateSearchFeedback,
+    ) -> Result<internal_api::SearchFeedback> {
+        let query_hash = match (feedback.query_hash, feedback.query) {
+            (Some(query_hash), _) => query_hash,
+            (None, Some(query)) => search_log::hash_query(&query),
+            (None, None) => return Err(anyhow!("either query_hash or query is required")),
+        };
+        let feedback = internal_api::SearchFeedback {
+            id: "".to_string(),
+            namespace: namespace.to_string(),
+            query_hash,
+            content_id: feedback.content_id,
+            chunk_id: feedback.chunk_id,
+            verdict: feedback.verdict,
+            note: feedback.note,
+            created_at: 0,
+            actor: feedback.actor,
+        };
+        let req = indexify_coordinator::CreateSearchFeedbackRequest {
+            feedback: Some(feedback.into()),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .create_search_feedback(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        response
+            .into_inner()
+            .feedback
+            .map(Into::into)
+            .ok_or_else(|| anyhow!("coordinator didn't return the feedback"))
+    }
+
+    pub async fn list_search_feedback(
+        &self,
+        namespace: &str,
+        params: api::ListSearchFeedbackParams,
+    ) -> Result<api::ListSearchFeedbackResponse> {
+        let req = indexify_coordinator::ListSearchFeedbackRequest {
+            namespace: namespace.to_string(),
+            content_id: params.content_id.unwrap_or_default(),
+            start_time: params.start_time.unwrap_or_default(),
+            end_time: params.end_time.unwrap_or_default(),
+            cursor: params.cursor.unwrap_or_default(),
+            limit: params.limit.unwrap_or_default(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .list_search_feedback(req)
+            .await?
+            .into_inner();
+        Ok(api::ListSearchFeedbackResponse {
+            feedback: response.feedback.into_iter().map(Into::into).collect(),
+            next_cursor: (!response.next_cursor.is_empty()).then_some(response.next_cursor),
+        })
+    }
+
+    pub async fn get_search_feedback_counts(
+        &self,
+        namespace: &str,
+        content_id: &str,
+    ) -> Result<internal_api::SearchFeedbackCounts> {
+        let req = indexify_coordinator::GetSearchFeedbackCountsRequest {
+            namespace: namespace.to_string(),
+            content_ids: vec![content_id.to_string()],
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .get_search_feedback_counts(req)
+            .await?;
+        Ok(response
+            .into_inner()
+            .counts
+            .into_iter()
+            .find(|counts| counts.content_id == content_id)
+            .map(|counts| internal_api::SearchFeedbackCounts {
+                positive: counts.positive,
+                negative: counts.negative,
+            })
+            .unwrap_or_default())
+    }
+
+    pub async fn create_subscription(
+        &self,
+        namespace: &str,
+        url: &str,
+    ) -> Result<api::Subscription> {
+        let req = indexify_coordinator::CreateSubscriptionRequest {
+            namespace: namespace.to_string(),
+            url: url.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .create_subscription(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        response
+            .into_inner()
+            .subscription
+            .map(Into::into)
+            .ok_or_else(|| anyhow!("subscription missing from the response"))
+    }
+
+    pub async fn list_subscriptions(&self, namespace: &str) -> Result<Vec<api::Subscription>> {
+        let req = indexify_coordinator::ListSubscriptionsRequest {
+            namespace: namespace.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .list_subscriptions(req)
+            .await?;
+        Ok(response
+            .into_inner()
+            .subscriptions
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn delete_subscription(&self, namespace: &str, subscription_id: &str) -> Result<()> {
+        let req = indexify_coordinator::DeleteSubscriptionRequest {
+            namespace: namespace.to_string(),
+            subscription_id: subscription_id.to_string(),
+        };
+        self.coordinator_client
+            .get()
+            .await?
+            .delete_subscription(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(())
+    }
+
+    pub async fn list_dead_letters(&self, subscription_id: &str) -> Result<Vec<api::DeadLetter>> {
+        let req = indexify_coordinator::ListDeadLettersRequest {
+            subscription_id: subscription_id.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .list_dead_letters(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(response
+            .into_inner()
+            .deliveries
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn requeue_dead_letters(&self, subscription_id: &str) -> Result<u64> {
+        let req = indexify_coordinator::RequeueDeadLettersRequest {
+            subscription_id: subscription_id.to_string(),
+        };
+        let response = self
+            .coordinator_client
+            .get()
+            .await?
+            .requeue_dead_letters(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(response.into_inner().requeued)
+    }
+
+    pub async fn reprocess_state_change(&self, state_change_id: u64) -> Result<()> {
+        let req = indexify_coordinator::ReprocessStateChangeRequest { state_change_id };
+        self.coordinator_client
+            .get()
+            .await?
+            .reprocess_state_change(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(())
+    }
+
+    pub async fn discard_state_change(&self, state_change_id: u64, note: &str) -> Result<()> {
+        let req = indexify_coordinator::DiscardStateChangeRequest {
+            state_change_id,
+            note: note.to_string(),
+        };
+        self.coordinator_client
+            .get()
+            .await?
+            .discard_state_change(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(())
+    }
+
+    /// Forwards the index events of this server to the coordinator, which
+    /// queues them for delivery to the subscriptions of their namespaces.
+    /// Events published while the coordinator can't be reached are dropped.
+    pub fn start_forwarding_index_events(self: Arc<Self>, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut events_rx = self.vector_index_manager.index_events().subscribe();
+        tokio::spawn(async move {
+            loop {
+                let mut next = tokio::select! {
+                    event = events_rx.recv() => event,
+                    _ = shutdown_rx.changed() => return,
+                };
+                //  Events published since are forwarded in the same request
+                let mut events = Vec::new();
+                loop {
+                    match next {
+                        Ok(event) => match serde_json::to_string(&event) {
+                            Ok(event) => events.push(event),
+                            Err(e) => warn!("unable to serialize index event: {}", e),
+                        },
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => warn!(
+                            "{} index events were dropped before they could be forwarded",
+                            skipped
+                        ),
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    }
+                    next = match events_rx.try_recv() {
+                        Ok(event) => Ok(event),
+                        Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                            Err(broadcast::error::RecvError::Lagged(skipped))
+                        }
+                        Err(_) => break,
+                    };
+                }
+                if events.is_empty() {
+                    continue;
+                }
+                if let Err(e) = self.enqueue_index_events(events).await {
+                    warn!("unable to forward index events to the coordinator: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn enqueue_index_events(&self, events: Vec<String>) -> Result<()> {
+        let req = indexify_coordinator::EnqueueIndexEventsRequest { events };
+        self.coordinator_client
+            .get()
+            .await?
+            .enqueue_index_events(req)
+            .await
+            .map_err(|e| anyhow!(e.message().to_string()))?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn search(
+        &self,
+        namespace: &str,
+        index_name: &str,
+        query: &str,
+        k: u64,
         filters: Vec<String>,
         include_content: bool,
+        access: Option<&internal_api::AccessContext>,
+        no_cache: bool,
     ) -> Result<Vec<ScoredText>> {
         let req = indexify_coordinator::GetIndexRequest {
             namespace: namespace.to_string(),
@@ -921,10 +1987,86 @@ impl DataManager {
             .index
             .ok_or(anyhow!("Index not found"))?;
         self.vector_index_manager
-            .search(index, query, k as usize, filters, include_content)
+            .search(
+                index,
+                query,
+                k as usize,
+                filters,
+                include_content,
+                access,
+                no_cache,
+            )
             .await
     }
 
+    /// Removes the cached embeddings of search queries, which is needed when
+    /// the model of an extractor changed without the schema of its indexes
+    /// changing
+    pub fn flush_query_embedding_cache(&self) -> usize {
+        self.vector_index_manager.flush_query_embedding_cache()
+    }
+
+    /// Returns the number of vectors stored in the indexes of each namespace
+    pub async fn namespace_vector_totals(&self) -> Result<api::NamespaceVectorTotalsResponse> {
+        let req = indexify_coordinator::ListNamespaceIndexTablesRequest {};
+        let namespace_indexes = self
+            .coordinator_client
+            .get()
+            .await?
+            .list_namespace_index_tables(req)
+            .await?
+            .into_inner()
+            .namespaces
+            .into_iter()
+            .map(|(namespace, tables)| (namespace, tables.table_names.into_iter().collect()))
+            .collect();
+        let totals = self
+            .vector_index_manager
+            .namespace_vector_totals(&namespace_indexes)
+            .await;
+        Ok(api::NamespaceVectorTotalsResponse {
+            totals: totals.totals,
+            errors: totals.errors,
+        })
+    }
+
+    /// Re-executes logged searches against the current index and diffs the
+    /// results. Entries logged without the raw query text can't be replayed.
+    pub async fn replay_searches(&self, entries: Vec<SearchLogEntry>) -> Vec<SearchReplayResult> {
+        let mut results = Vec::new();
+        for entry in entries {
+            let Some(query) = entry.query.clone() else {
+                results.push(SearchReplayResult {
+                    entry,
+                    diff: None,
+                    error: Some("query text was not logged".to_string()),
+                });
+                continue;
+            };
+            let replayed = self
+                .search(
+                    &entry.namespace,
+                    &entry.index,
+                    &query,
+                    entry.k,
+                    entry.filters.clone(),
+                    false,
+                    None,
+                    false,
+                )
+                .await;
+            let (diff, error) = match replayed {
+                Ok(replayed) => {
+                    let after: Vec<String> = replayed.into_iter().map(|r| r.content_id).collect();
+                    (Some(diff_results(&entry.result_ids(), &after)), None)
+                }
+                Err(e) => (None, Some(e.to_string())),
+            };
+            results.push(SearchReplayResult { entry, diff, error });
+        }
+        results
+    }
+
     #[tracing::instrument]
     pub async fn metadata_lookup(
         &self,
@@ -975,6 +2117,13 @@ mod tests {
     use serde_json::json;
 
     use super::*;
+    use crate::{
+        blob_storage::{BlobStorageConfig, DiskStorageConfig},
+        metadata_storage::{sqlite::SqliteIndexManager, MetadataStorage},
+        server_config::{GcVectorDeletesConfig, ServerConfig},
+        test_utils::FlakyDb,
+        vectordbs::{in_memory::InMemoryDb, VectorChunk, VectorDb},
+    };
 
     #[test]
     fn test_combine_metadata() {
@@ -1013,4 +2162,210 @@ mod tests {
 
         //assert_eq!(combined, expected);
     }
+
+    #[test]
+    fn test_extraction_policy_cache() {
+        let policy = |version| api::ExtractionPolicy {
+            id: "policy_id".to_string(),
+            extractor: "extractor".to_string(),
+            name: "policy".to_string(),
+            filters_eq: None,
+            input_params: Some(json!({"version": version})),
+            content_source: None,
+            graph_name: "graph".to_string(),
+            status: Default::default(),
+            index_attributes: Default::default(),
+            version,
+            version_strategy: Default::default(),
+        };
+        let cache = ExtractionPolicyCache::default();
+        assert!(cache.get("policy_id", 1).is_none());
+
+        cache.insert(policy(2));
+        assert_eq!(cache.get("policy_id", 1).unwrap().version, 2);
+        assert_eq!(cache.get("policy_id", 2).unwrap().version, 2);
+        //  A task created from a newer version invalidates the cached policy
+        assert!(cache.get("policy_id", 3).is_none());
+
+        cache.insert(policy(3));
+        assert_eq!(cache.get("policy_id", 3).unwrap().version, 3);
+        //  A stale fetch doesn't replace a newer version
+        cache.insert(policy(2));
+        assert_eq!(cache.get("policy_id", 1).unwrap().version, 3);
+    }
+
+    #[test]
+    fn test_gc_vector_deletions() {
+        let gc_task = |content_id: &str, completed: &[&str]| indexify_coordinator::GcTask {
+            content_id: content_id.to_string(),
+            output_tables: vec!["index_1".to_string(), "index_2".to_string()],
+            completed_output_tables: completed.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        let tasks = [
+            gc_task("a", &[]),
+            gc_task("b", &[]),
+            gc_task("c", &["index_1"]),
+        ];
+        let deletions = gc_vector_deletions(&tasks.iter().collect::<Vec<_>>());
+
+        //  One delete per index, without the tables a resumed task completed
+        assert_eq!(
+            deletions,
+            HashMap::from([
+                (
+                    "index_1".to_string(),
+                    vec!["a".to_string(), "b".to_string()]
+                ),
+                (
+                    "index_2".to_string(),
+                    vec!["a".to_string(), "b".to_string(), "c".to_string()]
+                ),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_content_with_vectors() {
+        let config = ServerConfig {
+            gc_vector_deletes: GcVectorDeletesConfig {
+                retry_delay_ms: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let coordinator_client = Arc::new(CoordinatorClient::new(Arc::new(config)));
+        let vector_db = Arc::new(FlakyDb {
+            db: InMemoryDb::new(),
+            failing: Default::default(),
+        });
+        let vector_index_manager = Arc::new(
+            VectorIndexManager::new(coordinator_client.clone(), vector_db.clone()).unwrap(),
+        );
+        let metadata = SqliteIndexManager::new("sqlite::memory:").unwrap();
+        metadata.create_metadata_table("namespace").await.unwrap();
+        let blob_dir = std::env::temp_dir().join(format!("indexify-gc-{}", nanoid!()));
+        std::fs::create_dir_all(&blob_dir).unwrap();
+        let blob_storage = Arc::new(BlobStorage::new_with_config(BlobStorageConfig {
+            s3: None,
+            disk: Some(DiskStorageConfig {
+                path: blob_dir.to_string_lossy().to_string(),
+            }),
+        }));
+        let data_manager = DataManager::new(
+            vector_index_manager,
+            metadata.clone(),
+            metadata.clone(),
+            blob_storage,
+            coordinator_client,
+        );
+
+        let indexes = ["index_1", "index_2"];
+        let mut gc_tasks = Vec::new();
+        for content_id in ["a", "b"] {
+            for index in indexes {
+                vector_db
+                    .add_embedding(
+                        index,
+                        vec![VectorChunk::new(
+                            content_id.to_string(),
+                            vec![1.0, 2.0],
+                            HashMap::new(),
+                            None,
+                            &Default::default(),
+                        )],
+                    )
+                    .await
+                    .unwrap();
+            }
+            metadata
+                .add_metadata(
+                    "namespace",
+                    ExtractedMetadata::new(
+                        content_id,
+                        "",
+                        "source",
+                        json!({"key": "value"}),
+                        "extractor",
+                        "graph",
+                    ),
+                )
+                .await
+                .unwrap();
+            let blob_path = blob_dir.join(content_id);
+            std::fs::write(&blob_path, b"content").unwrap();
+            gc_tasks.push(indexify_coordinator::GcTask {
+                namespace: "namespace".to_string(),
+                content_id: content_id.to_string(),
+                output_tables: indexes.iter().map(|index| index.to_string()).collect(),
+                blob_store_path: format!("file://{}", blob_path.to_string_lossy()),
+                task_type: indexify_coordinator::GcTaskType::Delete as i32,
+                ..Default::default()
+            });
+        }
+        let vectors = |index: &'static str| {
+            let vector_db = vector_db.clone();
+            async move {
+                vector_db
+                    .get_points(index, vec!["a".to_string(), "b".to_string()])
+                    .await
+                    .unwrap()
+                    .len()
+            }
+        };
+        let metadata_rows = || {
+            let metadata = metadata.clone();
+            async move {
+                let mut rows = 0;
+                for content_id in ["a", "b"] {
+                    rows += metadata
+                        .get_metadata_for_content("namespace", content_id)
+                        .await
+                        .unwrap()
+                        .len();
+                }
+                rows
+            }
+        };
+
+        //  The content is kept while vectors of it remain in an index
+        vector_db
+            .failing
+            .lock()
+            .unwrap()
+            .insert("index_2".to_string());
+        let results = data_manager
+            .delete_content_with_vectors(&gc_tasks.iter().collect::<Vec<_>>())
+            .await;
+        assert_eq!(results.len(), 2);
+        for (result, gc_task) in results.into_iter().zip(gc_tasks.iter_mut()) {
+            match result {
+                GcTaskResult::Incomplete {
+                    completed_output_tables,
+                } => {
+                    assert_eq!(completed_output_tables, vec!["index_1".to_string()]);
+                    gc_task.completed_output_tables = completed_output_tables;
+                }
+                result => panic!("unexpected result {:?}", result),
+            }
+        }
+        assert_eq!(vectors("index_1").await, 0);
+        assert_eq!(vectors("index_2").await, 2);
+        assert_eq!(metadata_rows().await, 2);
+        assert!(blob_dir.join("a").exists() && blob_dir.join("b").exists());
+
+        //  Resuming removes the remaining vectors and then the content
+        vector_db.failing.lock().unwrap().clear();
+        let results = data_manager
+            .delete_content_with_vectors(&gc_tasks.iter().collect::<Vec<_>>())
+            .await;
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, GcTaskResult::Completed)));
+        assert_eq!(vectors("index_2").await, 0);
+        assert_eq!(metadata_rows().await, 0);
+        assert!(!blob_dir.join("a").exists() && !blob_dir.join("b").exists());
+
+        let _ = std::fs::remove_dir_all(&blob_dir);
+    }
 }
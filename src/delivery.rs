@@ -0,0 +1,400 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use indexify_internal_api as internal_api;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{server_config::DeliveryConfig, state::SharedState, utils::timestamp_secs};
+
+/// Number of pending deliveries of a subscription read per pass
+const DELIVERY_BATCH_SIZE: usize = 100;
+
+/// Delivers the queued index events to the webhooks of the subscriptions.
+/// The deliveries of a subscription are made one at a time in the order they
+/// were enqueued. A failed delivery is retried with exponential backoff,
+/// holding back the later deliveries of its subscription, until it is moved
+/// to the dead letters.
+pub struct DeliveryWorker {
+    shared_state: SharedState,
+    config: DeliveryConfig,
+    client: reqwest::Client,
+    // Endpoint -> earliest time of the next delivery to it
+    next_delivery_at: Mutex<HashMap<String, Instant>>,
+}
+
+impl DeliveryWorker {
+    pub fn new(shared_state: SharedState, config: DeliveryConfig) -> Self {
+        Self {
+            shared_state,
+            config,
+            client: reqwest::Client::new(),
+            next_delivery_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Delivers the pending deliveries which are due at `now`, concurrently
+    /// across subscriptions, and returns the number delivered
+    pub async fn deliver_pending(&self, now: u64) -> Result<usize> {
+        let subscriptions = self.shared_state.list_subscriptions("")?;
+        let results = join_all(
+            subscriptions
+                .iter()
+                .map(|subscription| self.deliver_subscription(subscription, now)),
+        )
+        .await;
+        let mut delivered = 0;
+        for result in results {
+            delivered += result?;
+        }
+        Ok(delivered)
+    }
+
+    async fn deliver_subscription(
+        &self,
+        subscription: &internal_api::Subscription,
+        now: u64,
+    ) -> Result<usize> {
+        let pending = self
+            .shared_state
+            .list_pending_deliveries(&subscription.id, Some(DELIVERY_BATCH_SIZE))?;
+        let mut delivered = 0;
+        for delivery in pending {
+            if delivery.next_attempt_at > now {
+                break;
+            }
+            self.wait_for_endpoint(&subscription.url).await;
+            match self.post(&subscription.url, &delivery.payload).await {
+                Ok(()) => {
+                    self.shared_state.ack_delivery(&delivery).await?;
+                    delivered += 1;
+                }
+                Err(e) => {
+                    self.record_failure(delivery, e, now).await?;
+                    break;
+                }
+            }
+        }
+        Ok(delivered)
+    }
+
+    async fn post(&self, url: &str, payload: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(payload.to_string())
+            .timeout(Duration::from_millis(self.config.timeout_ms))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("webhook responded with {}", response.status()));
+        }
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        mut delivery: internal_api::Delivery,
+        error: anyhow::Error,
+        now: u64,
+    ) -> Result<()> {
+        delivery.attempts += 1;
+        delivery.last_error = Some(error.to_string());
+        let expired = now.saturating_sub(delivery.enqueued_at) >= self.config.max_age_secs;
+        if delivery.attempts >= self.config.max_attempts || expired {
+            warn!(
+                "delivery {} of subscription {} failed {} times, moving it to the dead letters: {}",
+                delivery.sequence, delivery.subscription_id, delivery.attempts, error
+            );
+            return self.shared_state.dead_letter_delivery(delivery).await;
+        }
+        delivery.next_attempt_at = now + self.backoff_secs(delivery.attempts);
+        self.shared_state.retry_delivery(delivery).await
+    }
+
+    /// Time before the next attempt of a delivery which failed `attempts`
+    /// times
+    fn backoff_secs(&self, attempts: u32) -> u64 {
+        let exponent = attempts.saturating_sub(1).min(32);
+        self.config
+            .initial_backoff_secs
+            .saturating_mul(1 << exponent)
+            .min(self.config.max_backoff_secs)
+    }
+
+    /// Waits until a delivery to the endpoint of the url is within its rate
+    /// limit, and reserves it
+    async fn wait_for_endpoint(&self, url: &str) {
+        if self.config.max_per_second_per_endpoint == 0 {
+            return;
+        }
+        let interval = Duration::from_secs(1) / self.config.max_per_second_per_endpoint;
+        let endpoint = url::Url::parse(url)
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_else(|_| url.to_string());
+        let now = Instant::now();
+        let delivery_at = {
+            let mut next_delivery_at = self.next_delivery_at.lock().unwrap();
+            let next = next_delivery_at.entry(endpoint).or_insert(now);
+            let delivery_at = (*next).max(now);
+            *next = delivery_at + interval;
+            delivery_at
+        };
+        tokio::time::sleep_until(delivery_at.into()).await;
+    }
+
+    /// Delivers the pending deliveries every interval while this node is the
+    /// leader
+    pub fn start(
+        self: Arc<Self>,
+        mut shutdown_rx: watch::Receiver<()>,
+        leader_rx: watch::Receiver<bool>,
+    ) {
+        let interval = Duration::from_millis(self.config.interval_ms.max(10));
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {
+                        if !*leader_rx.borrow() {
+                            continue;
+                        }
+                        if let Err(e) = self.deliver_pending(timestamp_secs()).await {
+                            warn!("unable to deliver pending deliveries: {:?}", e);
+                        }
+                    }
+                    _ = shutdown_rx.changed() => {
+                        info!("shutting down delivery worker");
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use axum::{extract::State, http::StatusCode, routing::post};
+
+    use super::*;
+    use crate::{test_util::db_utils::DEFAULT_TEST_NAMESPACE, test_utils::RaftTestCluster};
+
+    /// A webhook which can be taken down, recording the payloads it received
+    #[derive(Clone, Default)]
+    struct Webhook {
+        down: Arc<AtomicBool>,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Webhook {
+        async fn start() -> (Self, String) {
+            async fn receive(State(webhook): State<Webhook>, body: String) -> StatusCode {
+                if webhook.down.load(Ordering::SeqCst) {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                webhook.received.lock().unwrap().push(body);
+                StatusCode::OK
+            }
+            let webhook = Webhook::default();
+            let router = axum::Router::new()
+                .route("/events", post(receive))
+                .with_state(webhook.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/events", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, router).await });
+            (webhook, url)
+        }
+
+        fn set_down(&self, down: bool) {
+            self.down.store(down, Ordering::SeqCst);
+        }
+
+        fn received(&self) -> Vec<String> {
+            self.received.lock().unwrap().clone()
+        }
+    }
+
+    async fn setup(url: &str) -> Result<(RaftTestCluster, SharedState)> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        node.create_subscription(internal_api::Subscription {
+            id: "subscription".into(),
+            namespace: DEFAULT_TEST_NAMESPACE.into(),
+            url: url.into(),
+            ..Default::default()
+        })
+        .await?;
+        Ok((cluster, node))
+    }
+
+    async fn enqueue(node: &SharedState, payloads: &[&str], enqueued_at: u64) -> Result<()> {
+        node.enqueue_deliveries(
+            payloads
+                .iter()
+                .map(|payload| internal_api::Delivery {
+                    subscription_id: "subscription".into(),
+                    payload: payload.to_string(),
+                    enqueued_at,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+        .await
+    }
+
+    fn pending(node: &SharedState) -> Vec<(String, u32)> {
+        node.list_pending_deliveries("subscription", None)
+            .unwrap()
+            .into_iter()
+            .map(|delivery| (delivery.payload, delivery.attempts))
+            .collect()
+    }
+
+    fn config() -> DeliveryConfig {
+        DeliveryConfig {
+            max_per_second_per_endpoint: 0,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 60,
+            max_attempts: 3,
+            max_age_secs: 3600,
+            timeout_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_recover_in_order() -> Result<()> {
+        let (webhook, url) = Webhook::start().await;
+        let (_cluster, node) = setup(&url).await?;
+        enqueue(&node, &["a", "b", "c"], 1000).await?;
+
+        //  The first delivery fails and holds back the others
+        webhook.set_down(true);
+        let worker = DeliveryWorker::new(node.clone(), config());
+        assert_eq!(worker.deliver_pending(1000).await?, 0);
+        assert_eq!(
+            pending(&node),
+            vec![("a".into(), 1), ("b".into(), 0), ("c".into(), 0)]
+        );
+        let head = node.list_pending_deliveries("subscription", Some(1))?;
+        assert_eq!(head[0].next_attempt_at, 1010);
+        assert!(head[0].last_error.is_some());
+
+        //  It isn't retried before its backoff
+        webhook.set_down(false);
+        assert_eq!(worker.deliver_pending(1005).await?, 0);
+        assert!(webhook.received().is_empty());
+
+        //  The queue outlives the worker, a new one delivers it in order
+        drop(worker);
+        enqueue(&node, &["d"], 1005).await?;
+        let worker = DeliveryWorker::new(node.clone(), config());
+        assert_eq!(worker.deliver_pending(1010).await?, 4);
+        assert_eq!(webhook.received(), vec!["a", "b", "c", "d"]);
+        assert!(pending(&node).is_empty());
+        assert!(node.delivery_queue_stats()?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_deliveries_are_dead_lettered() -> Result<()> {
+        let (webhook, url) = Webhook::start().await;
+        let (_cluster, node) = setup(&url).await?;
+        enqueue(&node, &["a", "b"], 1000).await?;
+        webhook.set_down(true);
+        let worker = DeliveryWorker::new(node.clone(), config());
+
+        //  Retried with exponential backoff until the last attempt
+        worker.deliver_pending(1000).await?;
+        worker.deliver_pending(1010).await?;
+        let head = node.list_pending_deliveries("subscription", Some(1))?;
+        assert_eq!(head[0].attempts, 2);
+        assert_eq!(head[0].next_attempt_at, 1030);
+        worker.deliver_pending(1030).await?;
+        let dead_letters = node.list_dead_letters("subscription")?;
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].payload, "a");
+        assert_eq!(dead_letters[0].attempts, 3);
+        assert_eq!(pending(&node), vec![("b".into(), 0)]);
+
+        //  Deliveries older than the maximum age are dead lettered on their
+        //  next failure
+        worker.deliver_pending(1000 + 3600).await?;
+        assert!(pending(&node).is_empty());
+        assert_eq!(node.list_dead_letters("subscription")?.len(), 2);
+
+        //  Requeued dead letters are delivered in their original order
+        webhook.set_down(false);
+        enqueue(&node, &["c"], 5000).await?;
+        assert_eq!(node.requeue_dead_letters("subscription").await?, 2);
+        assert!(node.list_dead_letters("subscription")?.is_empty());
+        assert_eq!(
+            pending(&node),
+            vec![("a".into(), 0), ("b".into(), 0), ("c".into(), 0)]
+        );
+        assert_eq!(worker.deliver_pending(5000).await?, 3);
+        assert_eq!(webhook.received(), vec!["a", "b", "c"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deleting_subscription_purges_queue() -> Result<()> {
+        let (webhook, url) = Webhook::start().await;
+        let (_cluster, node) = setup(&url).await?;
+        webhook.set_down(true);
+        let worker = DeliveryWorker::new(
+            node.clone(),
+            DeliveryConfig {
+                max_attempts: 1,
+                ..config()
+            },
+        );
+        enqueue(&node, &["a", "b"], 1000).await?;
+        worker.deliver_pending(1000).await?;
+        assert_eq!(node.list_dead_letters("subscription")?.len(), 1);
+        let stats = node.delivery_queue_stats()?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].depth, 1);
+        assert_eq!(stats[0].oldest_enqueued_at, 1000);
+
+        node.delete_subscription("subscription").await?;
+        assert!(node.get_subscription("subscription")?.is_none());
+        assert!(pending(&node).is_empty());
+        assert!(node.list_dead_letters("subscription")?.is_empty());
+        assert!(node.delivery_queue_stats()?.is_empty());
+
+        //  Deliveries enqueued for a deleted subscription are dropped
+        enqueue(&node, &["c"], 1000).await?;
+        assert!(pending(&node).is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_rate_limit() -> Result<()> {
+        let (webhook, url) = Webhook::start().await;
+        let (_cluster, node) = setup(&url).await?;
+        enqueue(&node, &["a", "b", "c"], 1000).await?;
+        let worker = DeliveryWorker::new(
+            node.clone(),
+            DeliveryConfig {
+                max_per_second_per_endpoint: 10,
+                ..config()
+            },
+        );
+        let start = Instant::now();
+        assert_eq!(worker.deliver_pending(1000).await?, 3);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert_eq!(webhook.received(), vec!["a", "b", "c"]);
+        Ok(())
+    }
+}
@@ -32,17 +32,6 @@ impl ExtractorRouter {
         content: Content,
         input_params: Option<serde_json::Value>,
     ) -> Result<ExtractResponse, anyhow::Error> {
-        let request = internal_api::ExtractRequest {
-            content: internal_api::Content {
-                content_type: content.content_type,
-                bytes: content.bytes,
-                features: vec![],
-                labels: HashMap::new(),
-            },
-            extractor_name: extractor_name.to_string(),
-            input_params,
-        };
-
         let req = GetExtractorCoordinatesRequest {
             extractor: extractor_name.to_string(),
         };
@@ -57,10 +46,32 @@ impl ExtractorRouter {
         if addresses.is_empty() {
             return Err(anyhow!("no extractor found"));
         }
-        let extractor_addr = addresses[0].clone();
+        self.extract_content_with_executor(&addresses[0], extractor_name, content, input_params)
+            .await
+    }
+
+    /// Extracts the content with the extractor of the executor at
+    /// `executor_addr`
+    pub async fn extract_content_with_executor(
+        &self,
+        executor_addr: &str,
+        extractor_name: &str,
+        content: Content,
+        input_params: Option<serde_json::Value>,
+    ) -> Result<ExtractResponse, anyhow::Error> {
+        let request = internal_api::ExtractRequest {
+            content: internal_api::Content {
+                content_type: content.content_type,
+                bytes: content.bytes,
+                features: vec![],
+                labels: HashMap::new(),
+            },
+            extractor_name: extractor_name.to_string(),
+            input_params,
+        };
         let resp = self
             .client
-            .post(&format!("http://{}/extract", extractor_addr))
+            .post(&format!("http://{}/extract", executor_addr))
             .json(&request)
             .send()
             .await
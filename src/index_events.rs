@@ -0,0 +1,261 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
+use tracing::info;
+
+use crate::server_config::IndexEventsConfig;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndexEventKind {
+    Created,
+    /// Vectors were added or overwritten. Additions are coalesced, so the
+    /// count covers every addition since the previous event.
+    VectorsAdded {
+        count: u64,
+    },
+    VectorsDeleted {
+        count: u64,
+    },
+    Dropped,
+}
+
+/// A material change of the contents of an index. Sequence numbers increase
+/// by one with every event of an index, they are kept in memory and start
+/// over when the server restarts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEvent {
+    pub index: String,
+    pub sequence: u64,
+    pub kind: IndexEventKind,
+}
+
+#[derive(Default)]
+struct IndexEventsState {
+    sequences: HashMap<String, u64>,
+    // Index -> number of vectors added since the last event
+    pending_adds: HashMap<String, u64>,
+    // Index -> time the last VectorsAdded event was published
+    last_added_event: HashMap<String, Instant>,
+    // Index -> wall clock time of the last vector write
+    last_writes: HashMap<String, SystemTime>,
+}
+
+/// Publishes the lifecycle events of the vector indexes, so that services
+/// caching search results know when to invalidate them. Events are only
+/// published once the write to the vector database succeeded.
+pub struct IndexEvents {
+    coalesce_interval: Duration,
+    state: Mutex<IndexEventsState>,
+    events_tx: broadcast::Sender<IndexEvent>,
+}
+
+impl IndexEvents {
+    pub fn new(config: IndexEventsConfig) -> Self {
+        let (events_tx, _) = broadcast::channel(config.channel_capacity);
+        Self {
+            coalesce_interval: Duration::from_millis(config.coalesce_interval_ms),
+            state: Mutex::new(IndexEventsState::default()),
+            events_tx,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexEvent> {
+        self.events_tx.subscribe()
+    }
+
+    fn send(&self, state: &mut IndexEventsState, index: &str, kind: IndexEventKind) {
+        let sequence = state.sequences.entry(index.to_string()).or_default();
+        *sequence += 1;
+        //  Sending only fails when there are no subscribers
+        let _ = self.events_tx.send(IndexEvent {
+            index: index.to_string(),
+            sequence: *sequence,
+            kind,
+        });
+    }
+
+    /// Publishes the additions to the index which haven't been published yet
+    fn flush_adds(&self, state: &mut IndexEventsState, index: &str, now: Instant) {
+        if let Some(count) = state.pending_adds.remove(index) {
+            self.send(state, index, IndexEventKind::VectorsAdded { count });
+            state.last_added_event.insert(index.to_string(), now);
+        }
+    }
+
+    /// Publishes a change of the index. Pending additions are published
+    /// first so that events are seen in the order the writes happened.
+    fn publish(&self, index: &str, kind: IndexEventKind, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        self.flush_adds(&mut state, index, now);
+        if kind == IndexEventKind::Dropped {
+            state.last_added_event.remove(index);
+            state.last_writes.remove(index);
+        }
+        self.send(&mut state, index, kind);
+    }
+
+    pub fn index_created(&self, index: &str) {
+        self.publish(index, IndexEventKind::Created, Instant::now());
+    }
+
+    pub fn index_dropped(&self, index: &str) {
+        self.publish(index, IndexEventKind::Dropped, Instant::now());
+    }
+
+    pub fn vectors_deleted(&self, index: &str, count: u64) {
+        self.record_write(index);
+        self.publish(
+            index,
+            IndexEventKind::VectorsDeleted { count },
+            Instant::now(),
+        );
+    }
+
+    pub fn vectors_added(&self, index: &str, count: u64) {
+        self.record_write(index);
+        self.vectors_added_at(index, count, Instant::now());
+    }
+
+    fn record_write(&self, index: &str) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .last_writes
+            .insert(index.to_string(), SystemTime::now());
+    }
+
+    /// Returns the time vectors were last added to or deleted from the index
+    /// by this server, if any were since it started.
+    pub fn last_write(&self, index: &str) -> Option<SystemTime> {
+        self.state.lock().unwrap().last_writes.get(index).cloned()
+    }
+
+    /// Records vectors added to the index. An event is published right away
+    /// unless one was published for the index within the coalescing
+    /// interval, in which case the additions are published by `flush_due`.
+    fn vectors_added_at(&self, index: &str, count: u64, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        *state.pending_adds.entry(index.to_string()).or_default() += count;
+        let due = state.last_added_event.get(index).map_or(true, |last| {
+            now.duration_since(*last) >= self.coalesce_interval
+        });
+        if due {
+            self.flush_adds(&mut state, index, now);
+        }
+    }
+
+    /// Publishes the pending additions of every index whose coalescing
+    /// interval has elapsed
+    pub fn flush_due(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let due: Vec<String> = state
+            .pending_adds
+            .keys()
+            .filter(|index| {
+                state.last_added_event.get(*index).map_or(true, |last| {
+                    now.duration_since(*last) >= self.coalesce_interval
+                })
+            })
+            .cloned()
+            .collect();
+        for index in due {
+            self.flush_adds(&mut state, &index, now);
+        }
+    }
+
+    pub fn start(self: Arc<Self>, mut shutdown_rx: watch::Receiver<bool>) {
+        let interval = self.coalesce_interval.max(Duration::from_millis(10));
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {
+                        self.flush_due(Instant::now());
+                    }
+                    _ = shutdown_rx.changed() => {
+                        info!("shutting down index events");
+                        self.flush_due(Instant::now() + self.coalesce_interval);
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_events(coalesce_interval_ms: u64) -> IndexEvents {
+        IndexEvents::new(IndexEventsConfig {
+            coalesce_interval_ms,
+            ..Default::default()
+        })
+    }
+
+    fn drain(rx: &mut broadcast::Receiver<IndexEvent>) -> Vec<(u64, IndexEventKind)> {
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push((event.sequence, event.kind));
+        }
+        events
+    }
+
+    #[test]
+    fn test_additions_are_coalesced() {
+        let events = index_events(1000);
+        let mut rx = events.subscribe();
+        let start = Instant::now();
+
+        //  The first addition is published right away, the next ones within
+        //  the interval are held back
+        events.vectors_added_at("index_1", 1, start);
+        events.vectors_added_at("index_1", 2, start + Duration::from_millis(100));
+        events.vectors_added_at("index_1", 3, start + Duration::from_millis(200));
+        events.vectors_added_at("index_2", 5, start + Duration::from_millis(200));
+        events.flush_due(start + Duration::from_millis(500));
+        assert_eq!(
+            drain(&mut rx),
+            vec![
+                (1, IndexEventKind::VectorsAdded { count: 1 }),
+                (1, IndexEventKind::VectorsAdded { count: 5 }),
+            ]
+        );
+
+        events.flush_due(start + Duration::from_millis(1000));
+        assert_eq!(
+            drain(&mut rx),
+            vec![(2, IndexEventKind::VectorsAdded { count: 5 })]
+        );
+        events.flush_due(start + Duration::from_millis(3000));
+        assert!(drain(&mut rx).is_empty());
+    }
+
+    #[test]
+    fn test_pending_additions_precede_other_events() {
+        let events = index_events(1000);
+        let mut rx = events.subscribe();
+        let start = Instant::now();
+
+        events.index_created("index_1");
+        events.vectors_added_at("index_1", 1, start);
+        events.vectors_added_at("index_1", 4, start + Duration::from_millis(10));
+        events.vectors_deleted("index_1", 2);
+        events.index_dropped("index_1");
+        assert_eq!(
+            drain(&mut rx),
+            vec![
+                (1, IndexEventKind::Created),
+                (2, IndexEventKind::VectorsAdded { count: 1 }),
+                (3, IndexEventKind::VectorsAdded { count: 4 }),
+                (4, IndexEventKind::VectorsDeleted { count: 2 }),
+                (5, IndexEventKind::Dropped),
+            ]
+        );
+    }
+}
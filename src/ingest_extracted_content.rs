@@ -65,6 +65,10 @@ struct ContentStateWriting {
     task: indexify_coordinator::Task,
     root_content_metadata: Option<indexify_internal_api::ContentMetadata>,
     frame_state: FrameState,
+    // ids of the content written for the task so far
+    output_content_ids: Vec<String>,
+    // number of embeddings written for the task so far
+    vectors_produced: u64,
 }
 
 impl ContentStateWriting {
@@ -82,6 +86,8 @@ impl ContentStateWriting {
             task,
             root_content_metadata: root_content,
             frame_state: FrameState::New,
+            output_content_ids: Vec::new(),
+            vectors_produced: 0,
         })
     }
 
@@ -166,8 +172,12 @@ impl ContentStateWriting {
                     .unwrap_or(self.task.content_metadata.clone().unwrap().into());
                 let extraction_policy = state
                     .data_manager
-                    .get_extraction_policy(&self.task.extraction_policy_id)
+                    .get_extraction_policy_version(
+                        &self.task.extraction_policy_id,
+                        self.task.extraction_policy_version,
+                    )
                     .await?;
+                self.vectors_produced += count_embeddings(&payload.features);
                 let content_metadata = indexify_coordinator::ContentMetadata {
                     id: id.clone(),
                     file_name: frame_state.file_name.clone(),
@@ -183,6 +193,9 @@ impl ContentStateWriting {
                     hash: content_hash,
                     extraction_policy_ids: HashMap::new(),
                     extraction_graph_names: vec![extraction_policy.graph_name],
+                    //  Extracted content can be read by whoever can read its parent
+                    acl: self.content_metadata().acl.clone(),
+                    tombstone_reason: None,
                 };
                 state
                     .data_manager
@@ -201,6 +214,7 @@ impl ContentStateWriting {
                     .node_content_bytes_extracted
                     .add(frame_state.file_size, &[]);
                 self.frame_state = FrameState::New;
+                self.output_content_ids.push(id.clone());
                 Ok(id)
             }
         }
@@ -211,6 +225,7 @@ impl ContentStateWriting {
         state: &NamespaceEndpointState,
         payload: ExtractedFeatures,
     ) -> Result<()> {
+        self.vectors_produced += count_embeddings(&payload.features);
         state
             .data_manager
             .write_existing_content_features(
@@ -226,6 +241,13 @@ impl ContentStateWriting {
     }
 }
 
+fn count_embeddings(features: &[Feature]) -> u64 {
+    features
+        .iter()
+        .filter(|f| matches!(f.feature_type, FeatureType::Embedding))
+        .count() as u64
+}
+
 enum ContentState {
     Init,
     Writing(ContentStateWriting),
@@ -293,7 +315,11 @@ impl IngestExtractedContentState {
             ContentState::Writing(s) => {
                 self.state
                     .data_manager
-                    .finish_extracted_content_write(s.ingest_metadata.clone())
+                    .finish_extracted_content_write(
+                        s.ingest_metadata.clone(),
+                        s.output_content_ids.clone(),
+                        s.vectors_produced,
+                    )
                     .await?;
                 self.content_state = ContentState::Init;
                 Ok(())
@@ -304,6 +330,46 @@ impl IngestExtractedContentState {
         }
     }
 
+    /// Ingests the response of an executor which ran the task when asked
+    /// directly, the same way the executor would have ingested the outputs of
+    /// the task. Returns the ids of the content written.
+    pub async fn ingest_extract_response(
+        mut self,
+        payload: BeginExtractedContentIngest,
+        response: indexify_internal_api::ExtractResponse,
+    ) -> Result<Vec<String>> {
+        self.begin(payload).await?;
+        let content_id = match &self.content_state {
+            ContentState::Writing(s) => s.content_metadata().id.clone(),
+            ContentState::Init => return Err(anyhow!("extraction ingest did not begin")),
+        };
+        let mut output_content_ids = Vec::new();
+        for content in response.content {
+            self.begin_multipart_content().await?;
+            self.write_content_frame(ContentFrame {
+                bytes: content.bytes,
+            })
+            .await?;
+            let id = self
+                .finish_content(FinishContent {
+                    content_type: content.content_type,
+                    features: content.features.into_iter().map(Into::into).collect(),
+                    labels: content.labels,
+                })
+                .await?;
+            output_content_ids.push(id);
+        }
+        if !response.features.is_empty() {
+            self.write_features(ExtractedFeatures {
+                content_id,
+                features: response.features.into_iter().map(Into::into).collect(),
+            })
+            .await?;
+        }
+        self.finish().await?;
+        Ok(output_content_ids)
+    }
+
     pub async fn run(
         mut self,
         mut socket: WebSocket<IngestExtractedContentResponse, IngestExtractedContent>,
@@ -377,9 +443,10 @@ impl IngestExtractedContentState {
 #[cfg(test)]
 mod tests {
 
-    use std::sync::Arc;
+    use std::{sync::Arc, time::Duration};
 
     use anyhow::Result;
+    use axum::{extract::State, routing::post, Json};
     use indexify_internal_api::{
         ContentMetadata,
         ExtractedEmbeddings,
@@ -402,14 +469,17 @@ mod tests {
         data_manager::DataManager,
         metadata_storage::{self, MetadataReaderTS, MetadataStorageTS},
         metrics,
+        search_log::SearchLog,
         server::{NamespaceEndpointState, Server},
         server_config::{IndexStoreKind, ServerConfig},
+        sync_extraction::{self, SyncExtraction},
         test_util::db_utils::{
             create_metadata,
             create_test_extraction_graph,
             create_test_extraction_graph_with_children,
             mock_extractor,
             perform_all_tasks,
+            perform_task,
             test_mock_content_metadata,
             wait_changes_processed,
             wait_gc_tasks_completed,
@@ -491,6 +561,11 @@ mod tests {
                 handle,
                 coordinator,
             };
+            test_coordinator
+                .coordinator
+                .create_namespace(DEFAULT_TEST_NAMESPACE)
+                .await
+                .unwrap();
             let extractor = mock_extractor();
             test_coordinator
                 .create_extractor(extractor.clone())
@@ -535,13 +610,11 @@ mod tests {
             &self,
             extraction_graph: ExtractionGraph,
         ) -> Result<()> {
+            let schema =
+                StructuredDataSchema::new(&extraction_graph.name, &extraction_graph.namespace);
             self.coordinator
                 .shared_state
-                .create_extraction_graph(
-                    extraction_graph,
-                    StructuredDataSchema::default(),
-                    Vec::new(),
-                )
+                .create_extraction_graph(extraction_graph, schema, Vec::new())
                 .await?;
             Ok(())
         }
@@ -615,6 +688,9 @@ mod tests {
             content_reader: Arc::new(ContentReader::new(Arc::new(config.clone()))),
             registry: Arc::new(metrics::init_provider()),
             metrics: Arc::new(metrics::server::Metrics::new()),
+            search_log: Arc::new(SearchLog::new(config.search_log.clone())?),
+            sync_extraction: config.sync_extraction.clone(),
+            acl: config.acl.clone(),
         };
         Ok(namespace_endpoint_state)
     }
@@ -645,6 +721,8 @@ mod tests {
             task_id: "test".to_string(),
             executor_id: "test".to_string(),
             task_outcome: TaskOutcome::Success,
+            policy_version: None,
+            execution_time_ms: None,
         };
         ingest_state.begin(payload.clone()).await.unwrap();
         let new_payload = if let ContentState::Writing(s) = &ingest_state.content_state {
@@ -744,6 +822,8 @@ mod tests {
             task_id: "test".to_string(),
             executor_id: "test".to_string(),
             task_outcome: TaskOutcome::Success,
+            policy_version: None,
+            execution_time_ms: None,
         };
 
         ingest_state.begin(payload.clone()).await.unwrap();
@@ -822,6 +902,8 @@ mod tests {
             task_id: "test_1".to_string(),
             executor_id: "test".to_string(),
             task_outcome: TaskOutcome::Success,
+            policy_version: None,
+            execution_time_ms: None,
         };
 
         let mut ingest_state = IngestExtractedContentState::new(state.clone());
@@ -892,6 +974,8 @@ mod tests {
             task_id: "test".to_string(),
             executor_id: "test".to_string(),
             task_outcome: TaskOutcome::Success,
+            policy_version: None,
+            execution_time_ms: None,
         };
 
         ingest_state.begin(payload.clone()).await.unwrap();
@@ -948,6 +1032,8 @@ mod tests {
             task_id: "test_1".to_string(),
             executor_id: "test".to_string(),
             task_outcome: TaskOutcome::Success,
+            policy_version: None,
+            execution_time_ms: None,
         };
 
         let mut ingest_state = IngestExtractedContentState::new(state.clone());
@@ -1175,4 +1261,263 @@ mod tests {
 
         Ok(())
     }
+
+    fn make_test_text(id: &str) -> ContentWithId {
+        ContentWithId {
+            id: id.to_string(),
+            content: Content {
+                content_type: mime::TEXT_PLAIN.to_string(),
+                bytes: b"hello world".to_vec(),
+                labels: HashMap::new(),
+                features: vec![],
+            },
+            extraction_graph_names: vec!["extraction_graph_name".to_string()],
+            acl: None,
+            idempotency_key: None,
+        }
+    }
+
+    /// Starts an executor which answers extraction requests after `delay` with
+    /// the text as a single chunk, returns its address
+    async fn start_executor(delay: Duration) -> String {
+        async fn extract(
+            State(delay): State<Duration>,
+            Json(request): Json<indexify_internal_api::ExtractRequest>,
+        ) -> Json<indexify_internal_api::ExtractResponse> {
+            tokio::time::sleep(delay).await;
+            Json(indexify_internal_api::ExtractResponse {
+                content: vec![request.content],
+                features: vec![],
+            })
+        }
+        let router = axum::Router::new()
+            .route("/extract", post(extract))
+            .with_state(delay);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        addr
+    }
+
+    /// Records written for the extraction of a text, with the ids of the text
+    /// and of the content derived from it left out
+    async fn extraction_records(
+        coordinator: &Coordinator,
+        text_id: &str,
+    ) -> Result<serde_json::Value> {
+        let text = coordinator
+            .shared_state
+            .get_content_metadata_batch(vec![text_id.to_string()])
+            .await?
+            .remove(0);
+        let tasks: Vec<_> = coordinator
+            .shared_state
+            .list_tasks(DEFAULT_TEST_NAMESPACE, None)
+            .await?
+            .into_iter()
+            .filter(|task| task.content_metadata.id.id == text_id)
+            .map(|task| {
+                json!({
+                    "policy": task.extraction_policy_id,
+                    "outcome": task.outcome,
+                    "outputs": task.output_content_ids.len(),
+                })
+            })
+            .collect();
+        let derived: Vec<_> = coordinator
+            .shared_state
+            .get_content_tree_metadata(text_id)?
+            .into_iter()
+            .filter(|content| content.id.id != text_id)
+            .map(|content| {
+                json!({
+                    "parent": content.parent_id.map(|id| id.id == text_id),
+                    "root": content.root_content_id.as_deref() == Some(text_id),
+                    "source": content.source,
+                    "content_type": content.content_type,
+                    "size_bytes": content.size_bytes,
+                    "hash": content.hash,
+                    "labels": content.labels,
+                    "graphs": content.extraction_graph_names,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "policies": text.extraction_policy_ids.keys().collect::<Vec<_>>(),
+            "tasks": tasks,
+            "derived": derived,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_sync_extraction() -> Result<()> {
+        let state = new_endpoint_state().await?;
+        let test_coordinator = TestCoordinator::new().await;
+        let coordinator = test_coordinator.coordinator.clone();
+        let addr = start_executor(Duration::ZERO).await;
+        coordinator
+            .register_executor(&addr, "executor_id", vec![mock_extractor()])
+            .await?;
+
+        let (content_ids, extraction) = sync_extraction::add_texts(
+            &state,
+            DEFAULT_TEST_NAMESPACE,
+            vec![make_test_text("a1")],
+            vec!["extraction_graph_name".to_string()],
+            Duration::from_secs(10),
+        )
+        .await?;
+        assert_eq!(content_ids, vec!["a1".to_string()]);
+        let SyncExtraction::Extracted(derived) = extraction else {
+            panic!("texts were not extracted synchronously: {:?}", extraction);
+        };
+        assert_eq!(derived["a1"].len(), 1);
+
+        //  The chunk returned by the executor is a child of the text
+        let tree = coordinator.shared_state.get_content_tree_metadata("a1")?;
+        assert_eq!(tree.len(), 2);
+        let chunk = tree.iter().find(|c| c.id.id == derived["a1"][0]).unwrap();
+        assert_eq!(chunk.parent_id.as_ref().unwrap().id, "a1");
+        let bytes = state.content_reader.bytes(&chunk.storage_url).await?;
+        assert_eq!(bytes, b"hello world".to_vec());
+
+        //  The task is completed and the policy is stamped on the text
+        let content = test_coordinator
+            .get_internal_content("a1".to_string())
+            .await;
+        assert_eq!(content.extraction_policy_ids.len(), 1);
+        let tasks = coordinator
+            .shared_state
+            .list_tasks(DEFAULT_TEST_NAMESPACE, None)
+            .await?;
+        let task = tasks
+            .iter()
+            .find(|t| t.content_metadata.id.id == "a1")
+            .unwrap();
+        assert_eq!(task.outcome, TaskOutcome::Success);
+        assert_eq!(task.output_content_ids, derived["a1"]);
+
+        test_coordinator.stop().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_extraction_fallback() -> Result<()> {
+        let state = new_endpoint_state().await?;
+        let test_coordinator = TestCoordinator::new().await;
+        let coordinator = test_coordinator.coordinator.clone();
+        let addr = start_executor(Duration::from_secs(5)).await;
+        coordinator
+            .register_executor(&addr, "executor_id", vec![mock_extractor()])
+            .await?;
+
+        let (content_ids, extraction) = sync_extraction::add_texts(
+            &state,
+            DEFAULT_TEST_NAMESPACE,
+            vec![make_test_text("b1")],
+            vec!["extraction_graph_name".to_string()],
+            Duration::from_millis(200),
+        )
+        .await?;
+        assert_eq!(content_ids, vec!["b1".to_string()]);
+        assert_eq!(
+            extraction,
+            SyncExtraction::Fallback(
+                "extraction did not complete within 200ms, continuing asynchronously".to_string()
+            )
+        );
+
+        //  The task is released to the executor, which runs it asynchronously
+        let task = coordinator
+            .heartbeat("executor_id", 0)
+            .await?
+            .into_iter()
+            .find(|t| t.content_metadata.as_ref().unwrap().id == "b1")
+            .unwrap();
+        let task = coordinator.shared_state.task_with_id(&task.id).await?;
+        perform_task(&coordinator, &task, "b2", "executor_id").await?;
+        coordinator.wait_content_extraction("b1").await;
+        let tree = state
+            .data_manager
+            .get_content_tree_metadata(DEFAULT_TEST_NAMESPACE, "b1".to_string())
+            .await?;
+        assert_eq!(tree.len(), 2);
+
+        test_coordinator.stop().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_extraction_matches_async_extraction() -> Result<()> {
+        let state = new_endpoint_state().await?;
+        let test_coordinator = TestCoordinator::new().await;
+        let coordinator = test_coordinator.coordinator.clone();
+        let addr = start_executor(Duration::ZERO).await;
+        coordinator
+            .register_executor(&addr, "executor_id", vec![mock_extractor()])
+            .await?;
+
+        let (_, extraction) = sync_extraction::add_texts(
+            &state,
+            DEFAULT_TEST_NAMESPACE,
+            vec![make_test_text("c1")],
+            vec!["extraction_graph_name".to_string()],
+            Duration::from_secs(10),
+        )
+        .await?;
+        assert!(matches!(extraction, SyncExtraction::Extracted(_)));
+
+        //  The same text extracted by the executor through its heartbeat
+        state
+            .data_manager
+            .add_texts(
+                DEFAULT_TEST_NAMESPACE,
+                vec![make_test_text("d1")],
+                vec!["extraction_graph_name".to_string()],
+            )
+            .await?;
+        let task = loop {
+            let tasks = coordinator.heartbeat("executor_id", 0).await?;
+            if let Some(task) = tasks
+                .into_iter()
+                .find(|t| t.content_metadata.as_ref().unwrap().id == "d1")
+            {
+                break task;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        let mut ingest_state = IngestExtractedContentState::new(state.clone());
+        ingest_state
+            .begin(BeginExtractedContentIngest {
+                task_id: task.id.clone(),
+                executor_id: "executor_id".to_string(),
+                task_outcome: TaskOutcome::Success,
+                policy_version: None,
+                execution_time_ms: None,
+            })
+            .await?;
+        ingest_state.begin_multipart_content().await?;
+        ingest_state
+            .write_content_frame(ContentFrame {
+                bytes: b"hello world".to_vec(),
+            })
+            .await?;
+        ingest_state
+            .finish_content(FinishContent {
+                content_type: mime::TEXT_PLAIN.to_string(),
+                features: vec![],
+                labels: HashMap::new(),
+            })
+            .await?;
+        ingest_state.finish().await?;
+        coordinator.wait_content_extraction("d1").await;
+
+        assert_eq!(
+            extraction_records(&coordinator, "c1").await?,
+            extraction_records(&coordinator, "d1").await?
+        );
+
+        test_coordinator.stop().await;
+        Ok(())
+    }
 }
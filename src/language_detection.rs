@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use tracing::warn;
+
+use crate::server_config::LanguageDetectionConfig;
+
+/// Label ingested text content is labeled with its language, so extraction
+/// policies can be routed on it, e.g. with the filter `language:eng|deu`
+pub const LANGUAGE_LABEL: &str = "language";
+
+/// Detects the language of ingested text content from a sample of its first
+/// bytes. Detection is best effort, content whose language can't be detected
+/// reliably is ingested without the label.
+pub struct LanguageDetector {
+    config: LanguageDetectionConfig,
+}
+
+impl LanguageDetector {
+    pub fn new(config: LanguageDetectionConfig) -> Self {
+        Self { config }
+    }
+
+    /// Number of bytes from the start of the content to sample, 0 when the
+    /// language of the content isn't detected
+    pub fn sample_size(&self, namespace: &str, content_type: &str) -> usize {
+        if !self.config.enabled ||
+            self.config.skip_namespaces.iter().any(|ns| ns == namespace) ||
+            !is_text(content_type)
+        {
+            return 0;
+        }
+        self.config.sample_bytes
+    }
+
+    /// Returns the ISO 639-3 code of the language of the sample, None when it
+    /// can't be detected reliably
+    pub fn detect(&self, sample: &[u8]) -> Option<String> {
+        //  The sample may end in the middle of a character
+        let text = match std::str::from_utf8(sample) {
+            Ok(text) => text,
+            Err(e) => std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        whatlang::detect(text)
+            .filter(|info| info.is_reliable())
+            .map(|info| info.lang().code().to_string())
+    }
+
+    /// Adds the language label to the labels of the content, unless the
+    /// content was labeled with a language when it was ingested
+    pub fn label(&self, content_id: &str, sample: &[u8], labels: &mut HashMap<String, String>) {
+        if labels.contains_key(LANGUAGE_LABEL) {
+            return;
+        }
+        match self.detect(sample) {
+            Some(language) => {
+                labels.insert(LANGUAGE_LABEL.to_string(), language);
+            }
+            None => warn!(
+                "unable to detect the language of content {}, it isn't labeled with a language",
+                content_id
+            ),
+        }
+    }
+}
+
+fn is_text(content_type: &str) -> bool {
+    match content_type.parse::<mime::Mime>() {
+        Ok(mime) => mime.type_() == mime::TEXT || mime.subtype() == mime::JSON,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &str = "The quick brown fox jumps over the lazy dog, and then it runs back \
+                           into the forest where it lives with its family.";
+    const GERMAN: &str = "Der schnelle braune Fuchs springt über den faulen Hund und läuft \
+                          danach zurück in den Wald, wo er mit seiner Familie lebt.";
+
+    fn detector() -> LanguageDetector {
+        LanguageDetector::new(LanguageDetectionConfig {
+            enabled: true,
+            sample_bytes: 1024,
+            skip_namespaces: vec!["skipped".to_string()],
+        })
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let detector = detector();
+        assert_eq!(detector.detect(ENGLISH.as_bytes()), Some("eng".to_string()));
+        assert_eq!(detector.detect(GERMAN.as_bytes()), Some("deu".to_string()));
+
+        //  A sample cut in the middle of a character is detected from the
+        //  characters before it
+        let german = GERMAN.as_bytes();
+        let cut = GERMAN.rfind('ü').unwrap() + 1;
+        assert_eq!(detector.detect(&german[..cut]), Some("deu".to_string()));
+
+        //  Text without a language is left unlabeled
+        assert_eq!(detector.detect(b"12345 67890"), None);
+        assert_eq!(detector.detect(b""), None);
+    }
+
+    #[test]
+    fn test_skip_detection() {
+        let detector = detector();
+        assert_eq!(detector.sample_size("ns", "text/plain"), 1024);
+        assert_eq!(
+            detector.sample_size("ns", "text/markdown; charset=utf-8"),
+            1024
+        );
+        assert_eq!(detector.sample_size("ns", "application/json"), 1024);
+
+        //  Skipped namespaces and binary content
+        assert_eq!(detector.sample_size("skipped", "text/plain"), 0);
+        assert_eq!(detector.sample_size("ns", "application/pdf"), 0);
+        assert_eq!(detector.sample_size("ns", "image/png"), 0);
+        assert_eq!(detector.sample_size("ns", "not a mime type"), 0);
+
+        let disabled = LanguageDetector::new(LanguageDetectionConfig::default());
+        assert_eq!(disabled.sample_size("ns", "text/plain"), 0);
+    }
+
+    #[test]
+    fn test_label() {
+        let detector = detector();
+        let mut labels = HashMap::new();
+        detector.label("content", ENGLISH.as_bytes(), &mut labels);
+        assert_eq!(labels[LANGUAGE_LABEL], "eng");
+
+        //  Languages given at ingestion are kept
+        let mut labels = HashMap::from([(LANGUAGE_LABEL.to_string(), "fra".to_string())]);
+        detector.label("content", ENGLISH.as_bytes(), &mut labels);
+        assert_eq!(labels[LANGUAGE_LABEL], "fra");
+
+        //  Failed detection omits the label
+        let mut labels = HashMap::new();
+        detector.label("content", b"12345", &mut labels);
+        assert!(labels.is_empty());
+    }
+}
@@ -22,22 +22,32 @@ pub mod task_allocator;
 
 mod api;
 mod api_utils;
+mod bench;
 mod blob_storage;
 mod caching;
 mod cmd;
 mod coordinator;
 mod coordinator_client;
 mod data_manager;
+mod delivery;
 mod extractor_router;
 mod forwardable_coordinator;
 mod garbage_collector;
 mod grpc_helper;
+mod index_events;
 mod ingest_extracted_content;
+mod language_detection;
 mod metadata_storage;
+mod query_embedding_cache;
 mod scheduler;
+mod search_log;
+mod search_profiles;
+mod search_scoring;
+mod sync_extraction;
 mod test_util;
 mod tls;
 mod tonic_streamer;
+mod url_fetch;
 mod utils;
 mod vector_index;
 mod vectordbs;
@@ -416,7 +416,7 @@ pub mod coordinator {
         KeyValue,
     };
 
-    use crate::state::store::StateMachineStore;
+    use crate::{state::store::StateMachineStore, utils::timestamp_secs};
 
     #[derive(Debug)]
     pub struct Metrics {
@@ -430,6 +430,11 @@ pub mod coordinator {
         pub content_extracted_bytes: ObservableCounter<u64>,
         pub scheduler_invocations: Histogram<f64>,
         pub tasks_per_executor: ObservableGauge<u64>,
+        pub reverse_index_entries: ObservableGauge<u64>,
+        pub reverse_index_bytes: ObservableGauge<u64>,
+        pub values_too_large: ObservableCounter<u64>,
+        pub pending_deliveries: ObservableGauge<u64>,
+        pub oldest_pending_delivery_age: ObservableGauge<u64>,
     }
 
     impl Metrics {
@@ -615,6 +620,93 @@ pub mod coordinator {
                 .with_description("Number of tasks per executor")
                 .init();
 
+            let reverse_index_entries = meter
+                .u64_observable_gauge("indexify.coordinator.reverse_index_entries")
+                .with_callback({
+                    let app = app.clone();
+                    move |observer| {
+                        for size in app.reverse_index_sizes() {
+                            observer
+                                .observe(size.entries as u64, &[KeyValue::new("index", size.name)]);
+                        }
+                    }
+                })
+                .with_description("Number of entries in each in-memory reverse index")
+                .init();
+
+            let reverse_index_bytes = meter
+                .u64_observable_gauge("indexify.coordinator.reverse_index_bytes")
+                .with_callback({
+                    let app = app.clone();
+                    move |observer| {
+                        for size in app.reverse_index_sizes() {
+                            observer.observe(
+                                size.estimated_bytes as u64,
+                                &[KeyValue::new("index", size.name)],
+                            );
+                        }
+                    }
+                })
+                .with_description("Estimated size in bytes of each in-memory reverse index")
+                .init();
+
+            let prev_value = Arc::new(Mutex::new(0));
+            let values_too_large = meter
+                .u64_observable_counter("indexify.coordinator.values_too_large")
+                .with_callback({
+                    let app = app.clone();
+                    let prev_value = prev_value.clone();
+                    move |observer| {
+                        let mut prev_value = prev_value.lock().unwrap();
+                        let value = app
+                            .data
+                            .indexify_state
+                            .metrics
+                            .lock()
+                            .unwrap()
+                            .values_too_large;
+                        observer.observe(value - *prev_value, &[]);
+                        *prev_value = value;
+                    }
+                })
+                .with_description("Number of state store reads of values above the size limit")
+                .init();
+
+            let pending_deliveries = meter
+                .u64_observable_gauge("indexify.coordinator.pending_deliveries")
+                .with_callback({
+                    let app = app.clone();
+                    move |observer| {
+                        for stats in app.delivery_queue_stats().unwrap_or_default() {
+                            observer.observe(
+                                stats.depth,
+                                &[KeyValue::new("subscription_id", stats.subscription_id)],
+                            );
+                        }
+                    }
+                })
+                .with_description("Number of deliveries waiting for each subscription")
+                .init();
+
+            let oldest_pending_delivery_age = meter
+                .u64_observable_gauge("indexify.coordinator.oldest_pending_delivery_age_secs")
+                .with_callback({
+                    let app = app.clone();
+                    move |observer| {
+                        let now = timestamp_secs();
+                        for stats in app.delivery_queue_stats().unwrap_or_default() {
+                            observer.observe(
+                                now.saturating_sub(stats.oldest_enqueued_at),
+                                &[KeyValue::new("subscription_id", stats.subscription_id)],
+                            );
+                        }
+                    }
+                })
+                .with_description(
+                    "Age in seconds of the oldest delivery waiting for each subscription",
+                )
+                .init();
+
             Metrics {
                 tasks_completed,
                 tasks_errored,
@@ -626,6 +718,11 @@ pub mod coordinator {
                 content_extracted_bytes,
                 scheduler_invocations,
                 tasks_per_executor,
+                reverse_index_entries,
+                reverse_index_bytes,
+                values_too_large,
+                pending_deliveries,
+                oldest_pending_delivery_age,
             }
         }
     }
@@ -683,7 +780,7 @@ pub mod metadata_storage {
 }
 
 pub mod vector_storage {
-    use opentelemetry::metrics::Histogram;
+    use opentelemetry::metrics::{Counter, Histogram, UpDownCounter};
 
     #[derive(Debug)]
     pub struct Metrics {
@@ -695,6 +792,8 @@ pub mod vector_storage {
         pub vector_search_db: Histogram<f64>,
         pub vector_search_retrieve_metadata: Histogram<f64>,
         pub vector_search_retrieve_blob: Histogram<f64>,
+        pub gc_vectors_deleted: Counter<u64>,
+        pub gc_index_deletes_in_flight: UpDownCounter<i64>,
     }
 
     impl Default for Metrics {
@@ -747,6 +846,18 @@ pub mod vector_storage {
                 .with_description("Vector search retrieve content blob latencies in seconds")
                 .init();
 
+            let gc_vectors_deleted = meter
+                .u64_counter("indexify.gc_vectors_deleted")
+                .with_description(
+                    "Content whose vectors were removed from an index by garbage collection",
+                )
+                .init();
+
+            let gc_index_deletes_in_flight = meter
+                .i64_up_down_counter("indexify.gc_index_deletes_in_flight")
+                .with_description("Indexes vectors are being removed from by garbage collection")
+                .init();
+
             Metrics {
                 vector_metadata_update,
                 vector_upsert,
@@ -756,6 +867,8 @@ pub mod vector_storage {
                 vector_search_db,
                 vector_search_retrieve_metadata,
                 vector_search_retrieve_blob,
+                gc_vectors_deleted,
+                gc_index_deletes_in_flight,
             }
         }
     }
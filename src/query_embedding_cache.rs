@@ -0,0 +1,335 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use opentelemetry::metrics::Counter;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::server_config::QueryEmbeddingCacheConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    model: String,
+    query_hash: String,
+}
+
+struct CacheEntry {
+    embedding: Vec<f32>,
+    inserted_at: Instant,
+    // Sequence of the last use of the entry, its key in the LRU order
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    // Sequence of the last use -> key, the first entry is the least recently
+    // used one
+    lru: BTreeMap<u64, CacheKey>,
+    next_sequence: u64,
+    // Model -> metadata of the index the cached embeddings were computed for
+    model_metadata: HashMap<String, String>,
+}
+
+impl CacheState {
+    fn next_sequence(&mut self) -> u64 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        sequence
+    }
+
+    fn touch(&mut self, key: &CacheKey) {
+        let sequence = self.next_sequence();
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.lru.remove(&entry.last_used);
+            entry.last_used = sequence;
+            self.lru.insert(sequence, key.clone());
+        }
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.lru.remove(&entry.last_used);
+        }
+    }
+
+    /// Removes the embeddings of the model, returns the number removed
+    fn flush_model(&mut self, model: &str) -> usize {
+        let keys: Vec<CacheKey> = self
+            .entries
+            .keys()
+            .filter(|key| key.model == model)
+            .cloned()
+            .collect();
+        for key in &keys {
+            self.remove(key);
+        }
+        keys.len()
+    }
+}
+
+/// Caches the embeddings of search queries, so that queries searched again,
+/// e.g. by search UIs refreshing or paginating, aren't embedded by the
+/// extractor every time. Embeddings are keyed by the model, which is the
+/// extractor of the index, and the hash of the normalized query text. The
+/// least recently used embeddings are evicted when the cache is full.
+pub struct QueryEmbeddingCache {
+    config: QueryEmbeddingCacheConfig,
+    state: Mutex<CacheState>,
+    hits: Counter<u64>,
+    misses: Counter<u64>,
+}
+
+impl QueryEmbeddingCache {
+    pub fn new(config: QueryEmbeddingCacheConfig) -> Self {
+        let meter = opentelemetry::global::meter("indexify-index");
+        let hits = meter
+            .u64_counter("indexify.query_embedding_cache_hits")
+            .with_description("Search queries whose embedding was found in the cache")
+            .init();
+        let misses = meter
+            .u64_counter("indexify.query_embedding_cache_misses")
+            .with_description("Search queries which were embedded by the extractor")
+            .init();
+        Self {
+            config,
+            state: Mutex::new(CacheState::default()),
+            hits,
+            misses,
+        }
+    }
+
+    /// Trims the query and collapses its whitespace, queries which only
+    /// differ in case are normalized the same when `lowercase` is set
+    pub fn normalize(&self, query: &str) -> String {
+        let normalized = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        if self.config.lowercase {
+            normalized.to_lowercase()
+        } else {
+            normalized
+        }
+    }
+
+    fn key(&self, model: &str, query: &str) -> CacheKey {
+        CacheKey {
+            model: model.to_string(),
+            query_hash: format!("{:x}", Sha256::digest(self.normalize(query).as_bytes())),
+        }
+    }
+
+    /// Returns the embedding of the query from the cache, or embeds it with
+    /// `embed` and caches the embedding. The cache is bypassed with
+    /// `no_cache`. The embeddings of the model are flushed when the metadata
+    /// of the index differs from the one they were computed for, since the
+    /// index changed models.
+    pub async fn get_or_embed<F, Fut>(
+        &self,
+        model: &str,
+        model_metadata: &str,
+        query: &str,
+        no_cache: bool,
+        embed: F,
+    ) -> Result<Vec<f32>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<f32>>>,
+    {
+        if no_cache || self.config.max_entries == 0 {
+            return embed().await;
+        }
+        if let Some(embedding) = self.get_at(model, model_metadata, query, Instant::now()) {
+            self.hits.add(1, &[]);
+            return Ok(embedding);
+        }
+        self.misses.add(1, &[]);
+        let embedding = embed().await?;
+        self.insert_at(model, query, embedding.clone(), Instant::now());
+        Ok(embedding)
+    }
+
+    fn get_at(
+        &self,
+        model: &str,
+        model_metadata: &str,
+        query: &str,
+        now: Instant,
+    ) -> Option<Vec<f32>> {
+        let mut state = self.state.lock().unwrap();
+        if state.model_metadata.get(model).map(String::as_str) != Some(model_metadata) {
+            let flushed = state.flush_model(model);
+            if flushed > 0 {
+                info!(
+                    "flushed {} query embeddings of {}, the model of the index changed",
+                    flushed, model
+                );
+            }
+            state
+                .model_metadata
+                .insert(model.to_string(), model_metadata.to_string());
+            return None;
+        }
+        let key = self.key(model, query);
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        let inserted_at = state.entries.get(&key)?.inserted_at;
+        if now.duration_since(inserted_at) >= ttl {
+            state.remove(&key);
+            return None;
+        }
+        state.touch(&key);
+        state.entries.get(&key).map(|entry| entry.embedding.clone())
+    }
+
+    fn insert_at(&self, model: &str, query: &str, embedding: Vec<f32>, now: Instant) {
+        let key = self.key(model, query);
+        let mut state = self.state.lock().unwrap();
+        state.remove(&key);
+        while state.entries.len() >= self.config.max_entries {
+            let Some((_, oldest)) = state.lru.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+        let sequence = state.next_sequence();
+        state.lru.insert(sequence, key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                embedding,
+                inserted_at: now,
+                last_used: sequence,
+            },
+        );
+    }
+
+    /// Removes every cached embedding, returns the number removed
+    pub fn flush(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let flushed = state.entries.len();
+        state.entries.clear();
+        state.lru.clear();
+        flushed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn len(cache: &QueryEmbeddingCache) -> usize {
+        cache.state.lock().unwrap().entries.len()
+    }
+
+    fn cache(max_entries: usize, ttl_secs: u64) -> QueryEmbeddingCache {
+        QueryEmbeddingCache::new(QueryEmbeddingCacheConfig {
+            max_entries,
+            ttl_secs,
+            ..Default::default()
+        })
+    }
+
+    /// Embeds the query with an embedder counting its calls
+    async fn embed(
+        cache: &QueryEmbeddingCache,
+        model_metadata: &str,
+        query: &str,
+        no_cache: bool,
+        calls: &AtomicUsize,
+    ) -> Vec<f32> {
+        cache
+            .get_or_embed("minilm", model_metadata, query, no_cache, || async {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                anyhow::Ok(vec![call as f32])
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hits_skip_the_embedder() {
+        let cache = cache(10, 60);
+        let calls = AtomicUsize::new(0);
+        assert_eq!(embed(&cache, "v1", "red shoes", false, &calls).await, [0.0]);
+        assert_eq!(
+            embed(&cache, "v1", "  red   shoes ", false, &calls).await,
+            [0.0]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        //  Case matters unless lowercase is set
+        assert_eq!(embed(&cache, "v1", "Red Shoes", false, &calls).await, [1.0]);
+
+        //  no_cache neither reads nor writes the cache
+        assert_eq!(embed(&cache, "v1", "red shoes", true, &calls).await, [2.0]);
+        assert_eq!(embed(&cache, "v1", "blue shoes", true, &calls).await, [3.0]);
+        assert_eq!(len(&cache), 2);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let cache = cache(10, 60);
+        assert_eq!(cache.normalize(" Red\t shoes\n"), "Red shoes");
+        let cache = QueryEmbeddingCache::new(QueryEmbeddingCacheConfig {
+            lowercase: true,
+            ..Default::default()
+        });
+        assert_eq!(cache.normalize(" Red\t shoes\n"), "red shoes");
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = cache(10, 60);
+        let start = Instant::now();
+        assert_eq!(cache.get_at("minilm", "v1", "query", start), None);
+        cache.insert_at("minilm", "query", vec![1.0], start);
+        assert_eq!(
+            cache.get_at("minilm", "v1", "query", start + Duration::from_secs(59)),
+            Some(vec![1.0])
+        );
+        assert_eq!(
+            cache.get_at("minilm", "v1", "query", start + Duration::from_secs(60)),
+            None
+        );
+        assert_eq!(len(&cache), 0);
+    }
+
+    #[test]
+    fn test_least_recently_used_are_evicted() {
+        let cache = cache(2, 60);
+        let now = Instant::now();
+        assert_eq!(cache.get_at("minilm", "v1", "a", now), None);
+        cache.insert_at("minilm", "a", vec![1.0], now);
+        cache.insert_at("minilm", "b", vec![2.0], now);
+        //  Reading a makes b the least recently used
+        assert_eq!(cache.get_at("minilm", "v1", "a", now), Some(vec![1.0]));
+        cache.insert_at("minilm", "c", vec![3.0], now);
+        assert_eq!(len(&cache), 2);
+        assert_eq!(cache.get_at("minilm", "v1", "b", now), None);
+        assert_eq!(cache.get_at("minilm", "v1", "a", now), Some(vec![1.0]));
+        assert_eq!(cache.get_at("minilm", "v1", "c", now), Some(vec![3.0]));
+    }
+
+    #[tokio::test]
+    async fn test_model_change_flushes_the_model() {
+        let cache = cache(10, 60);
+        let calls = AtomicUsize::new(0);
+        embed(&cache, "v1", "red shoes", false, &calls).await;
+        embed(&cache, "v1", "blue shoes", false, &calls).await;
+        assert_eq!(len(&cache), 2);
+
+        //  The index now stores embeddings of another model
+        assert_eq!(embed(&cache, "v2", "red shoes", false, &calls).await, [2.0]);
+        assert_eq!(len(&cache), 1);
+        assert_eq!(embed(&cache, "v2", "red shoes", false, &calls).await, [2.0]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        assert_eq!(cache.flush(), 1);
+        assert_eq!(embed(&cache, "v2", "red shoes", false, &calls).await, [3.0]);
+    }
+}
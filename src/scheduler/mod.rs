@@ -1,34 +1,159 @@
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt,
     hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
 };
 
 use anyhow::{anyhow, Ok, Result};
 use indexify_internal_api as internal_api;
 use indexify_internal_api::StateChange;
-use internal_api::OutputSchema;
-use tracing::info;
+use internal_api::{
+    ContentMetadataId,
+    EmbeddingSchema,
+    ExtractionPolicyId,
+    ExtractionPolicyVersionStrategy,
+    OutputSchema,
+};
+use tracing::{info, warn};
 
 use crate::{
     state::SharedState,
     task_allocator::{planner::plan::TaskAllocationPlan, TaskAllocator},
-    utils::timestamp_secs,
+    utils::Clock,
 };
 
+/// An embedding output of an extractor whose schema differs from the one
+/// its index was created with, so vectors written by tasks would be
+/// rejected by the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputIncompatibility {
+    pub output_name: String,
+    pub index_name: String,
+    pub index_schema: EmbeddingSchema,
+    pub output_schema: EmbeddingSchema,
+}
+
+impl fmt::Display for OutputIncompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output {} produces {} dimensional {} embeddings but index {} was created for {} dimensional {} embeddings, recreate the index and rebuild its content to resume",
+            self.output_name,
+            self.output_schema.dim,
+            self.output_schema.distance,
+            self.index_name,
+            self.index_schema.dim,
+            self.index_schema.distance
+        )
+    }
+}
+
 pub struct Scheduler {
     shared_state: SharedState,
     task_allocator: TaskAllocator,
+    clock: Arc<dyn Clock>,
+    // policies last seen with outputs which don't match their indexes
+    incompatible_policies: Mutex<HashSet<ExtractionPolicyId>>,
 }
 
 impl Scheduler {
-    pub fn new(shared_state: SharedState, task_allocator: TaskAllocator) -> Self {
+    pub fn new(
+        shared_state: SharedState,
+        task_allocator: TaskAllocator,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
         Scheduler {
             shared_state,
             task_allocator,
+            clock,
+            incompatible_policies: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Compares the embedding outputs of the extractor of a policy, one by
+    /// one, against the indexes created for them. Outputs without an index
+    /// or with an index that doesn't record an embedding schema are skipped.
+    pub async fn incompatible_outputs(
+        &self,
+        policy: &internal_api::ExtractionPolicy,
+    ) -> Result<Vec<OutputIncompatibility>> {
+        let extractor = self.shared_state.extractor_with_name(&policy.extractor)?;
+        let indexes = self.shared_state.list_indexes(&policy.namespace).await?;
+        let mut incompatible = Vec::new();
+        for (output_name, schema) in extractor.outputs {
+            let OutputSchema::Embedding(output_schema) = schema else {
+                continue;
+            };
+            let Some(index) = indexes.iter().find(|i| {
+                i.graph_name == policy.graph_name &&
+                    i.extraction_policy_name == policy.name &&
+                    i.name == i.build_name(&output_name)
+            }) else {
+                continue;
+            };
+            let Some(index_schema) = index.embedding_schema() else {
+                continue;
+            };
+            if index_schema != output_schema {
+                incompatible.push(OutputIncompatibility {
+                    output_name,
+                    index_name: index.name.clone(),
+                    index_schema,
+                    output_schema,
+                });
+            }
+        }
+        incompatible.sort_by(|a, b| a.output_name.cmp(&b.output_name));
+        Ok(incompatible)
+    }
+
+    /// Returns true when tasks can be created for the policy. A state change
+    /// is recorded when the policy becomes incompatible with its indexes and
+    /// when it becomes compatible again.
+    async fn check_output_compatibility(
+        &self,
+        policy: &internal_api::ExtractionPolicy,
+    ) -> Result<bool> {
+        let incompatible = self.incompatible_outputs(policy).await?;
+        let compatible = incompatible.is_empty();
+        let changed = {
+            let mut incompatible_policies = self.incompatible_policies.lock().unwrap();
+            if compatible {
+                incompatible_policies.remove(&policy.id)
+            } else {
+                incompatible_policies.insert(policy.id.clone())
+            }
+        };
+        if !changed {
+            return Ok(compatible);
         }
+        if compatible {
+            info!(
+                "extraction policy {} matches its indexes again, resuming task creation",
+                policy.name
+            );
+        }
+        for output in &incompatible {
+            warn!(
+                "not creating tasks for extraction policy {}: {}",
+                policy.name, output
+            );
+        }
+        self.shared_state
+            .mark_change_events_as_processed(
+                vec![],
+                vec![StateChange::new(
+                    policy.id.clone(),
+                    internal_api::ChangeType::OutputCompatibilityChanged { compatible },
+                    self.clock.now_secs(),
+                )],
+            )
+            .await?;
+        Ok(compatible)
     }
 
-    async fn tables_for_policies(
+    pub async fn tables_for_policies(
         &self,
         policies: &[internal_api::ExtractionPolicy],
     ) -> Result<Vec<String>> {
@@ -65,7 +190,7 @@ impl Scheduler {
                 indexify_internal_api::ChangeType::TaskCompleted {
                     root_content_id: id,
                 },
-                timestamp_secs(),
+                self.clock.now_secs(),
             )]),
             _ => Ok(Vec::new()),
         }
@@ -105,11 +230,52 @@ impl Scheduler {
             .match_extraction_policies_for_content(&content)
             .await?;
         let tables = self.tables_for_policies(&extraction_policies).await?;
+        let mut new_tasks_by_extractor: HashMap<String, u64> = HashMap::new();
+        let mut paused_extractors = HashSet::new();
+        let mut superseded_tasks = Vec::new();
         for extraction_policy in extraction_policies {
-            let task = self
-                .create_task(&extraction_policy.id, &content, &tables)
-                .await?;
-            tasks.push(task);
+            if extraction_policy.version_strategy == ExtractionPolicyVersionStrategy::LatestOnly {
+                superseded_tasks.extend(
+                    self.superseded_tasks(&extraction_policy.id, &content.id)
+                        .await,
+                );
+            }
+            for version in self
+                .content_versions_to_extract(&extraction_policy, &content)
+                .await?
+            {
+                let new_tasks = new_tasks_by_extractor
+                    .entry(extraction_policy.extractor.clone())
+                    .or_default();
+                if self
+                    .pending_task_cap_reached(&extraction_policy.extractor, *new_tasks)
+                    .await?
+                {
+                    paused_extractors.insert(extraction_policy.extractor.clone());
+                    continue;
+                }
+                if !self.check_output_compatibility(&extraction_policy).await? {
+                    continue;
+                }
+                *new_tasks += 1;
+                let task = self
+                    .create_task(&extraction_policy.id, &version, &tables)
+                    .await?;
+                tasks.push(task);
+            }
+        }
+        //  The change is left unprocessed, its tasks are all created once the
+        //  extractors have executors again
+        if !paused_extractors.is_empty() {
+            info!(
+                "deferring tasks for content {}, extractors {} have no executors",
+                content.id,
+                paused_extractors.into_iter().collect::<Vec<_>>().join(", ")
+            );
+            return Ok(());
+        }
+        if !superseded_tasks.is_empty() {
+            self.shared_state.cancel_tasks(superseded_tasks).await?;
         }
         if tasks.is_empty() {
             return self
@@ -134,6 +300,137 @@ impl Scheduler {
         }
     }
 
+    /// Versions of the content a new version makes the extraction policy run
+    /// on. Versions which are tombstoned or which the policy already ran on
+    /// are skipped, except for the latest version of latest only policies.
+    async fn content_versions_to_extract(
+        &self,
+        extraction_policy: &internal_api::ExtractionPolicy,
+        content: &internal_api::ContentMetadata,
+    ) -> Result<Vec<internal_api::ContentMetadata>> {
+        let (previous_versions, includes_latest) = match extraction_policy.version_strategy {
+            ExtractionPolicyVersionStrategy::LatestOnly => return Ok(vec![content.clone()]),
+            ExtractionPolicyVersionStrategy::EveryVersion => (1..content.id.version, true),
+            //  The first version is only left when it was written along with later
+            //  versions before the scheduler got to it
+            ExtractionPolicyVersionStrategy::FirstVersionOnly => {
+                (1..content.id.version.min(2), content.id.version == 1)
+            }
+        };
+        let previous_ids: Vec<ContentMetadataId> = previous_versions
+            .map(|version| ContentMetadataId::new_with_version(&content.id.id, version))
+            .collect();
+        let mut versions = self
+            .shared_state
+            .state_machine
+            .get_content_from_ids_with_version(&previous_ids)
+            .await?;
+        if includes_latest {
+            versions.push(content.clone());
+        }
+        versions.retain(|version| {
+            !version
+                .extraction_policy_ids
+                .contains_key(&extraction_policy.id)
+        });
+        Ok(versions)
+    }
+
+    /// Unfinished tasks of the extraction policy on versions of the content
+    /// older than `content_id`
+    async fn superseded_tasks(
+        &self,
+        extraction_policy_id: &str,
+        content_id: &ContentMetadataId,
+    ) -> Vec<String> {
+        let mut task_ids = Vec::new();
+        for version in 1..content_id.version {
+            let mut pending = self
+                .shared_state
+                .state_machine
+                .get_pending_tasks_for_content_version(&ContentMetadataId::new_with_version(
+                    &content_id.id,
+                    version,
+                ))
+                .await;
+            if let Some(tasks) = pending.remove(extraction_policy_id) {
+                task_ids.extend(tasks);
+            }
+        }
+        task_ids
+    }
+
+    /// Applies the extraction policy again to the content of a tree which
+    /// matches it. Returns the number of tasks created.
+    pub async fn rebuild_content_tree(
+        &self,
+        extraction_policy: &internal_api::ExtractionPolicy,
+        content_tree: &[internal_api::ContentMetadata],
+    ) -> Result<usize> {
+        if let Some(output) = self.incompatible_outputs(extraction_policy).await?.first() {
+            return Err(anyhow!(
+                "extraction policy {} can't be applied: {}",
+                extraction_policy.name,
+                output
+            ));
+        }
+        let tables = self
+            .tables_for_policies(std::slice::from_ref(extraction_policy))
+            .await?;
+        let mut tasks = Vec::new();
+        for content in content_tree {
+            let matches = self
+                .shared_state
+                .match_extraction_policies_for_content(content)
+                .await?
+                .iter()
+                .any(|policy| policy.id == extraction_policy.id);
+            if matches {
+                tasks.push(
+                    self.create_task(&extraction_policy.id, content, &tables)
+                        .await?,
+                );
+            }
+        }
+        let content_ids = content_tree.iter().map(|c| c.id.id.clone()).collect();
+        self.shared_state
+            .rebuild_content_tree(&extraction_policy.id, content_ids, tasks.clone())
+            .await?;
+        let task_count = tasks.len();
+        let allocation_plan = self.allocate_tasks(tasks).await?;
+        if !allocation_plan.0.is_empty() {
+            self.shared_state.assign_tasks(allocation_plan.0).await?;
+        }
+        Ok(task_count)
+    }
+
+    /// Returns true when the extractor has no executors and already has at
+    /// least `max_pending_tasks_without_executors` unfinished tasks, counting
+    /// `new_tasks` which are about to be created.
+    pub async fn pending_task_cap_reached(&self, extractor: &str, new_tasks: u64) -> Result<bool> {
+        let Some(cap) = self
+            .shared_state
+            .policy_status_config
+            .max_pending_tasks_without_executors
+        else {
+            return Ok(false);
+        };
+        if !self
+            .shared_state
+            .get_executors_for_extractor(extractor)
+            .await?
+            .is_empty()
+        {
+            return Ok(false);
+        }
+        let pending = self
+            .shared_state
+            .unfinished_tasks_by_extractor(extractor)
+            .await?
+            .len() as u64;
+        Ok(pending + new_tasks >= cap)
+    }
+
     pub async fn allocate_tasks(
         &self,
         tasks: Vec<internal_api::Task>,
@@ -220,9 +517,17 @@ impl Scheduler {
             output_index_table_mapping: output_mapping.clone(),
             namespace: extraction_policy.namespace.clone(),
             content_metadata: content.clone(),
-            input_params: extraction_policy.input_params.clone(),
+            input_params: if self.shared_state.embed_task_input_params {
+                extraction_policy.input_params.clone()
+            } else {
+                serde_json::Value::Null
+            },
+            extraction_policy_version: extraction_policy.version,
+            resolved_policy_version: None,
             outcome: internal_api::TaskOutcome::Unknown,
             index_tables: index_tables.to_vec(),
+            output_content_ids: vec![],
+            usage: internal_api::TaskUsage::default(),
         };
         info!("created task: {:?}", task);
         Ok(task)
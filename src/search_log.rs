@@ -0,0 +1,320 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::server_config::SearchLogConfig;
+
+const CURRENT_LOG_FILE: &str = "search_log.jsonl";
+const PREVIOUS_LOG_FILE: &str = "search_log.1.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchLogResult {
+    pub content_id: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchLogEntry {
+    pub ts_secs: u64,
+    pub namespace: String,
+    pub index: String,
+    pub filters: Vec<String>,
+    pub k: u64,
+    pub query_hash: String,
+    /// Raw query text, only present when `store_query_text` is enabled.
+    pub query: Option<String>,
+    pub results: Vec<SearchLogResult>,
+    pub latency_ms: u64,
+}
+
+impl SearchLogEntry {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        namespace: &str,
+        index: &str,
+        query: &str,
+        k: u64,
+        filters: Vec<String>,
+        results: Vec<SearchLogResult>,
+        latency_ms: u64,
+        store_query_text: bool,
+    ) -> Self {
+        let ts_secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            ts_secs,
+            namespace: namespace.to_string(),
+            index: index.to_string(),
+            filters,
+            k,
+            query_hash: hash_query(query),
+            query: store_query_text.then(|| query.to_string()),
+            results,
+            latency_ms,
+        }
+    }
+
+    pub fn result_ids(&self) -> Vec<String> {
+        self.results.iter().map(|r| r.content_id.clone()).collect()
+    }
+}
+
+pub fn hash_query(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+/// Returns true if a request should be logged for the given sample rate and a
+/// uniformly distributed roll in [0, 1).
+pub fn sampled(sample_rate: f64, roll: f64) -> bool {
+    sample_rate > 0.0 && roll < sample_rate
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResultDiff {
+    /// Jaccard similarity of the two result sets, 1.0 when both are empty.
+    pub jaccard: f64,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Rank change of results present in both sets. Positive values mean the
+    /// result moved down.
+    pub position_deltas: HashMap<String, i64>,
+}
+
+pub fn diff_results(before: &[String], after: &[String]) -> SearchResultDiff {
+    let before_set: HashSet<&String> = before.iter().collect();
+    let after_set: HashSet<&String> = after.iter().collect();
+    let union = before_set.union(&after_set).count();
+    let intersection = before_set.intersection(&after_set).count();
+    let jaccard = if union == 0 {
+        1.0
+    } else {
+        intersection as f64 / union as f64
+    };
+
+    let added = after
+        .iter()
+        .filter(|id| !before_set.contains(id))
+        .cloned()
+        .collect();
+    let removed = before
+        .iter()
+        .filter(|id| !after_set.contains(id))
+        .cloned()
+        .collect();
+
+    let after_positions: HashMap<&String, usize> =
+        after.iter().enumerate().map(|(i, id)| (id, i)).collect();
+    let mut position_deltas = HashMap::new();
+    for (i, id) in before.iter().enumerate() {
+        if let Some(j) = after_positions.get(id) {
+            position_deltas.insert(id.clone(), *j as i64 - i as i64);
+        }
+    }
+
+    SearchResultDiff {
+        jaccard,
+        added,
+        removed,
+        position_deltas,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchReplayResult {
+    pub entry: SearchLogEntry,
+    pub diff: Option<SearchResultDiff>,
+    pub error: Option<String>,
+}
+
+struct SearchLogState {
+    writer: Option<File>,
+    lines_in_current: usize,
+    recent: VecDeque<SearchLogEntry>,
+}
+
+/// SearchLog keeps a sampled, bounded log of search requests. Entries are
+/// appended to a file which is rotated once it holds `max_entries` lines, so at
+/// most two files are kept on disk.
+pub struct SearchLog {
+    config: SearchLogConfig,
+    state: Mutex<SearchLogState>,
+}
+
+impl SearchLog {
+    pub fn new(config: SearchLogConfig) -> Result<Self> {
+        let mut state = SearchLogState {
+            writer: None,
+            lines_in_current: 0,
+            recent: VecDeque::new(),
+        };
+        if config.sample_rate > 0.0 {
+            fs::create_dir_all(&config.path)
+                .map_err(|e| anyhow!("unable to create search log dir: {}", e))?;
+            let dir = Path::new(&config.path);
+            for entry in read_entries(&dir.join(PREVIOUS_LOG_FILE))? {
+                push_bounded(&mut state.recent, entry, config.max_entries);
+            }
+            let current = read_entries(&dir.join(CURRENT_LOG_FILE))?;
+            state.lines_in_current = current.len();
+            for entry in current {
+                push_bounded(&mut state.recent, entry, config.max_entries);
+            }
+            state.writer = Some(open_append(&dir.join(CURRENT_LOG_FILE))?);
+        }
+        Ok(Self {
+            config,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn store_query_text(&self) -> bool {
+        self.config.store_query_text
+    }
+
+    pub fn should_sample(&self) -> bool {
+        sampled(self.config.sample_rate, rand::random::<f64>())
+    }
+
+    pub fn record(&self, entry: SearchLogEntry) -> Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| anyhow!("search log lock poisoned: {}", e))?;
+        if state.writer.is_none() {
+            return Ok(());
+        }
+        if state.lines_in_current >= self.config.max_entries {
+            let dir = PathBuf::from(&self.config.path);
+            fs::rename(dir.join(CURRENT_LOG_FILE), dir.join(PREVIOUS_LOG_FILE))?;
+            state.writer = Some(open_append(&dir.join(CURRENT_LOG_FILE))?);
+            state.lines_in_current = 0;
+        }
+        let line = serde_json::to_string(&entry)?;
+        if let Some(writer) = state.writer.as_mut() {
+            writeln!(writer, "{}", line)?;
+        }
+        state.lines_in_current += 1;
+        push_bounded(&mut state.recent, entry, self.config.max_entries);
+        Ok(())
+    }
+
+    /// Returns up to `limit` of the most recent entries, newest first.
+    pub fn recent(&self, limit: usize) -> Vec<SearchLogEntry> {
+        match self.state.lock() {
+            Ok(state) => state.recent.iter().rev().take(limit).cloned().collect(),
+            Err(_) => vec![],
+        }
+    }
+}
+
+fn push_bounded(recent: &mut VecDeque<SearchLogEntry>, entry: SearchLogEntry, max: usize) {
+    recent.push_back(entry);
+    while recent.len() > max {
+        recent.pop_front();
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("unable to open search log {}: {}", path.display(), e))
+}
+
+fn read_entries(path: &Path) -> Result<Vec<SearchLogEntry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        // Skip partially written lines instead of failing startup
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sampling_gate() {
+        assert!(!sampled(0.0, 0.0));
+        assert!(!sampled(0.0, 0.5));
+        assert!(sampled(1.0, 0.999));
+        assert!(sampled(0.25, 0.1));
+        assert!(!sampled(0.25, 0.25));
+        assert!(!sampled(0.25, 0.9));
+    }
+
+    #[test]
+    fn test_diff_identical_results() {
+        let diff = diff_results(&ids(&["a", "b", "c"]), &ids(&["a", "b", "c"]));
+        assert_eq!(diff.jaccard, 1.0);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert!(diff.position_deltas.values().all(|d| *d == 0));
+    }
+
+    #[test]
+    fn test_diff_changed_results() {
+        let diff = diff_results(&ids(&["a", "b", "c", "d"]), &ids(&["c", "a", "e"]));
+        // intersection {a, c}, union {a, b, c, d, e}
+        assert_eq!(diff.jaccard, 2.0 / 5.0);
+        assert_eq!(diff.added, ids(&["e"]));
+        assert_eq!(diff.removed, ids(&["b", "d"]));
+        assert_eq!(diff.position_deltas.get("a"), Some(&1));
+        assert_eq!(diff.position_deltas.get("c"), Some(&-2));
+        assert_eq!(diff.position_deltas.len(), 2);
+    }
+
+    #[test]
+    fn test_diff_empty_results() {
+        assert_eq!(diff_results(&[], &[]).jaccard, 1.0);
+        assert_eq!(diff_results(&ids(&["a"]), &[]).jaccard, 0.0);
+    }
+
+    #[test]
+    fn test_search_log_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SearchLogConfig {
+            sample_rate: 1.0,
+            path: dir.path().to_str().unwrap().to_string(),
+            max_entries: 2,
+            store_query_text: false,
+        };
+        let log = SearchLog::new(config.clone()).unwrap();
+        for i in 0..5 {
+            let entry =
+                SearchLogEntry::new("ns", "idx", &format!("q{}", i), 3, vec![], vec![], 1, false);
+            log.record(entry).unwrap();
+        }
+        let recent = log.recent(10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].query_hash, hash_query("q4"));
+        assert!(recent[0].query.is_none());
+
+        // Reloading picks up the rotated and current files, bounded by max_entries
+        let log = SearchLog::new(config).unwrap();
+        assert_eq!(log.recent(10).len(), 2);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+
+use crate::api::{SearchProfile, SearchRequest};
+
+/// Settings a search runs with once the request is merged with its profile
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSearch {
+    pub index: String,
+    pub k: u64,
+    pub filters: Vec<String>,
+    pub min_score: Option<f32>,
+}
+
+/// Merges a search request with the profile it references. Settings on the
+/// request take precedence; filters are merged per key, with a request
+/// filter replacing the profile's filter on the same key. The filter
+/// expression of the request replaces all other filters. An invalid profile
+/// can still be used when the request names the index to search.
+pub fn resolve(
+    request: &SearchRequest,
+    profile: Option<&SearchProfile>,
+    default_k: u64,
+) -> Result<ResolvedSearch> {
+    let mut resolved = match profile {
+        None => ResolvedSearch {
+            index: request.index.clone(),
+            k: request.k.unwrap_or(default_k),
+            filters: request.filters.clone(),
+            min_score: request.min_score,
+        },
+        Some(profile) => {
+            if let (true, Some(reason)) = (request.index.is_empty(), &profile.invalid_reason) {
+                return Err(anyhow!(
+                    "search profile {} is invalid: {}",
+                    profile.name,
+                    reason
+                ));
+            }
+            let index = if request.index.is_empty() {
+                profile.index.clone()
+            } else {
+                request.index.clone()
+            };
+            ResolvedSearch {
+                index,
+                k: request.k.or(profile.k).unwrap_or(default_k),
+                filters: merge_filters(&profile.filters, &request.filters),
+                min_score: request.min_score.or(profile.min_score),
+            }
+        }
+    };
+    if resolved.index.is_empty() {
+        return Err(anyhow!("index is required when no profile sets it"));
+    }
+    if let Some(filter) = &request.filter {
+        resolved.filters = vec![filter.clone()];
+    }
+    Ok(resolved)
+}
+
+fn filter_key(filter: &str) -> &str {
+    filter.split('=').next().unwrap_or(filter)
+}
+
+pub fn merge_filters(profile: &[String], request: &[String]) -> Vec<String> {
+    let overridden: HashSet<&str> = request.iter().map(|f| filter_key(f)).collect();
+    profile
+        .iter()
+        .filter(|f| !overridden.contains(filter_key(f)))
+        .chain(request)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn request() -> SearchRequest {
+        SearchRequest {
+            index: "".to_string(),
+            query: "query".to_string(),
+            k: None,
+            filters: vec![],
+            filter: None,
+            include_content: None,
+            min_score: None,
+            profile: Some("profile".to_string()),
+            scoring: None,
+            explain: None,
+            as_user: None,
+            groups: vec![],
+            no_cache: false,
+        }
+    }
+
+    fn profile() -> SearchProfile {
+        SearchProfile {
+            name: "profile".to_string(),
+            index: "profile_index".to_string(),
+            filters: strings(&["lang=en", "source=web"]),
+            k: Some(5),
+            min_score: Some(0.5),
+            invalid_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_profile_fills_unset_settings() {
+        let resolved = resolve(&request(), Some(&profile()), 10).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedSearch {
+                index: "profile_index".to_string(),
+                k: 5,
+                filters: strings(&["lang=en", "source=web"]),
+                min_score: Some(0.5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_request_overrides_profile() {
+        let request = SearchRequest {
+            index: "request_index".to_string(),
+            k: Some(20),
+            filters: strings(&["lang=fr", "author=bob"]),
+            min_score: Some(0.8),
+            ..request()
+        };
+        let resolved = resolve(&request, Some(&profile()), 10).unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedSearch {
+                index: "request_index".to_string(),
+                k: 20,
+                filters: strings(&["source=web", "lang=fr", "author=bob"]),
+                min_score: Some(0.8),
+            }
+        );
+    }
+
+    #[test]
+    fn test_filter_expression_replaces_filters() {
+        let request = SearchRequest {
+            filters: strings(&["author=bob"]),
+            filter: Some("lang = en OR lang = fr".to_string()),
+            ..request()
+        };
+        let resolved = resolve(&request, Some(&profile()), 10).unwrap();
+        assert_eq!(resolved.filters, strings(&["lang = en OR lang = fr"]));
+    }
+
+    #[test]
+    fn test_defaults_without_profile() {
+        let request = SearchRequest {
+            index: "request_index".to_string(),
+            profile: None,
+            ..request()
+        };
+        let resolved = resolve(&request, None, 10).unwrap();
+        assert_eq!(resolved.k, 10);
+        assert_eq!(resolved.min_score, None);
+
+        let request = SearchRequest {
+            profile: None,
+            ..request()
+        };
+        assert!(resolve(&request, None, 10).is_err());
+    }
+
+    #[test]
+    fn test_invalid_profile() {
+        let profile = SearchProfile {
+            invalid_reason: Some("index profile_index not found".to_string()),
+            ..profile()
+        };
+        let err = resolve(&request(), Some(&profile), 10).unwrap_err();
+        assert!(err.to_string().contains("index profile_index not found"));
+
+        //  The rest of the profile still applies when the request names an index
+        let request = SearchRequest {
+            index: "request_index".to_string(),
+            ..request()
+        };
+        let resolved = resolve(&request, Some(&profile), 10).unwrap();
+        assert_eq!(resolved.index, "request_index");
+        assert_eq!(resolved.k, 5);
+    }
+}
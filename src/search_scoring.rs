@@ -0,0 +1,736 @@
+use std::{collections::HashMap, fmt};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    api::{ScoreExplanation, ScoreTerm},
+    vector_index::ScoredText,
+};
+
+/// Number of results fetched from the index per requested result when a
+/// scoring expression reorders them
+pub const SCORING_OVERFETCH_FACTOR: u64 = 4;
+
+/// Expression combining the similarity of a result with its labels into the
+/// score results are ranked by, e.g.
+/// `0.8*similarity + 0.2*recency(labels.updated_at, half_life=30d) +
+/// boost(source=="handbook", 0.1)`.
+///
+/// Supported functions:
+/// - `recency(label, half_life=duration)` decays from 1 to 0 as the unix
+///   timestamp in seconds stored in the label gets older, halving every half
+///   life. Durations are a number with an optional `s`, `m`, `h` or `d` unit
+///   and default to seconds.
+/// - `boost(label=="value", weight)` is the weight when the label matches and 0
+///   otherwise, `!=` is supported as well.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoringExpr {
+    Number(f64),
+    Similarity,
+    Recency {
+        label: String,
+        half_life_secs: f64,
+    },
+    Boost {
+        label: String,
+        value: String,
+        equal: bool,
+        weight: f64,
+    },
+    Neg(Box<ScoringExpr>),
+    Binary {
+        op: BinaryOp,
+        lhs: Box<ScoringExpr>,
+        rhs: Box<ScoringExpr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div => 2,
+        }
+    }
+}
+
+impl fmt::Display for ScoringExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoringExpr::Number(n) => write!(f, "{}", n),
+            ScoringExpr::Similarity => write!(f, "similarity"),
+            ScoringExpr::Recency {
+                label,
+                half_life_secs,
+            } => write!(
+                f,
+                "recency(labels.{}, half_life={}s)",
+                label, half_life_secs
+            ),
+            ScoringExpr::Boost {
+                label,
+                value,
+                equal,
+                weight,
+            } => write!(
+                f,
+                "boost(labels.{}{}{:?}, {})",
+                label,
+                if *equal { "==" } else { "!=" },
+                value,
+                weight
+            ),
+            ScoringExpr::Neg(expr) => write!(f, "-{}", Parenthesized(expr, 3)),
+            ScoringExpr::Binary { op, lhs, rhs } => write!(
+                f,
+                "{} {} {}",
+                Parenthesized(lhs, op.precedence()),
+                op.symbol(),
+                // The right hand side binds tighter so a - (b - c) keeps its
+                // parentheses
+                Parenthesized(rhs, op.precedence() + 1)
+            ),
+        }
+    }
+}
+
+/// Displays an expression, in parentheses when it binds looser than the
+/// given precedence
+struct Parenthesized<'a>(&'a ScoringExpr, u8);
+
+impl fmt::Display for Parenthesized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ScoringExpr::Binary { op, .. } if op.precedence() < self.1 => write!(f, "({})", self.0),
+            expr => write!(f, "{}", expr),
+        }
+    }
+}
+
+/// Values a scoring expression is evaluated against for one search result
+pub struct ScoringInput<'a> {
+    pub similarity: f64,
+    pub labels: &'a HashMap<String, String>,
+    /// Current time as seconds since the unix epoch
+    pub now: u64,
+}
+
+/// Returns the weight of a timestamp with the given age, halving every half
+/// life. Timestamps in the future count as fresh.
+pub fn recency_decay(age_secs: f64, half_life_secs: f64) -> f64 {
+    if age_secs <= 0.0 {
+        return 1.0;
+    }
+    0.5_f64.powf(age_secs / half_life_secs)
+}
+
+impl ScoringExpr {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            return Err(anyhow!("unexpected {} in scoring expression", token));
+        }
+        Ok(expr)
+    }
+
+    pub fn evaluate(&self, input: &ScoringInput) -> f64 {
+        match self {
+            ScoringExpr::Number(n) => *n,
+            ScoringExpr::Similarity => input.similarity,
+            ScoringExpr::Recency {
+                label,
+                half_life_secs,
+            } => input
+                .labels
+                .get(label)
+                .and_then(|value| value.trim().parse::<f64>().ok())
+                .map(|ts| recency_decay(input.now as f64 - ts, *half_life_secs))
+                .unwrap_or(0.0),
+            ScoringExpr::Boost {
+                label,
+                value,
+                equal,
+                weight,
+            } => {
+                let matches = input.labels.get(label) == Some(value);
+                if matches == *equal {
+                    *weight
+                } else {
+                    0.0
+                }
+            }
+            ScoringExpr::Neg(expr) => -expr.evaluate(input),
+            ScoringExpr::Binary { op, lhs, rhs } => {
+                let (lhs, rhs) = (lhs.evaluate(input), rhs.evaluate(input));
+                match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div if rhs == 0.0 => 0.0,
+                    BinaryOp::Div => lhs / rhs,
+                }
+            }
+        }
+    }
+
+    /// Splits the expression into the terms of its top level sum, each with
+    /// the sign it is added with
+    fn terms(&self) -> Vec<(f64, &ScoringExpr)> {
+        match self {
+            ScoringExpr::Binary {
+                op: op @ (BinaryOp::Add | BinaryOp::Sub),
+                lhs,
+                rhs,
+            } => {
+                let mut terms = lhs.terms();
+                let sign = if *op == BinaryOp::Add { 1.0 } else { -1.0 };
+                terms.push((sign, rhs.as_ref()));
+                terms
+            }
+            expr => vec![(1.0, expr)],
+        }
+    }
+
+    /// Evaluates the expression along with the contribution of each term of
+    /// its top level sum
+    pub fn explain(&self, input: &ScoringInput) -> ScoreExplanation {
+        let terms: Vec<ScoreTerm> = self
+            .terms()
+            .into_iter()
+            .map(|(sign, term)| ScoreTerm {
+                expression: term.to_string(),
+                value: sign * term.evaluate(input),
+            })
+            .collect();
+        ScoreExplanation {
+            similarity: input.similarity,
+            score: self.evaluate(input),
+            terms,
+        }
+    }
+}
+
+/// Re-ranks search results by the score of the expression and keeps the
+/// best k, each with the breakdown of its score. The confidence score of the
+/// results is replaced by the computed score.
+pub fn rank(
+    expr: &ScoringExpr,
+    results: Vec<ScoredText>,
+    k: usize,
+    now: u64,
+) -> Vec<(ScoredText, ScoreExplanation)> {
+    let mut ranked: Vec<_> = results
+        .into_iter()
+        .map(|mut result| {
+            let explanation = expr.explain(&ScoringInput {
+                similarity: result.confidence_score as f64,
+                labels: &result.labels,
+                now,
+            });
+            result.confidence_score = explanation.score as f32;
+            (result, explanation)
+        })
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| b.score.total_cmp(&a.score));
+    ranked.truncate(k);
+    ranked
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Str(String),
+    Op(char),
+    Eq,
+    NotEq,
+    Assign,
+    LParen,
+    RParen,
+    Comma,
+    Dot,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "number {}", n),
+            Token::Ident(ident) => write!(f, "'{}'", ident),
+            Token::Str(s) => write!(f, "string {:?}", s),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::Eq => write!(f, "'=='"),
+            Token::NotEq => write!(f, "'!='"),
+            Token::Assign => write!(f, "'='"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+            Token::Dot => write!(f, "'.'"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                let number = number
+                    .parse()
+                    .map_err(|_| anyhow!("invalid number {} in scoring expression", number))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(ch) => s.push(ch),
+                        None => return Err(anyhow!("unterminated string in scoring expression")),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '=' | '!' => {
+                chars.next();
+                match (c, chars.peek()) {
+                    ('=', Some('=')) => {
+                        chars.next();
+                        tokens.push(Token::Eq);
+                    }
+                    ('!', Some('=')) => {
+                        chars.next();
+                        tokens.push(Token::NotEq);
+                    }
+                    ('=', _) => tokens.push(Token::Assign),
+                    _ => return Err(anyhow!("unexpected '!' in scoring expression")),
+                }
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Op(c));
+            }
+            '(' | ')' | ',' | '.' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => Token::Dot,
+                });
+            }
+            c => {
+                return Err(anyhow!(
+                    "unexpected character '{}' in scoring expression",
+                    c
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("unexpected end of scoring expression"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(anyhow!(
+                "expected {} but found {} in scoring expression",
+                expected,
+                token
+            ));
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<ScoringExpr> {
+        let mut lhs = self.product()?;
+        while let Some(Token::Op(c @ ('+' | '-'))) = self.peek() {
+            let op = if *c == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            self.pos += 1;
+            let rhs = self.product()?;
+            lhs = ScoringExpr::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<ScoringExpr> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(c @ ('*' | '/'))) = self.peek() {
+            let op = if *c == '*' {
+                BinaryOp::Mul
+            } else {
+                BinaryOp::Div
+            };
+            self.pos += 1;
+            let rhs = self.unary()?;
+            lhs = ScoringExpr::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<ScoringExpr> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            return Ok(ScoringExpr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<ScoringExpr> {
+        match self.next()? {
+            Token::Number(n) => Ok(ScoringExpr::Number(n)),
+            Token::LParen => {
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Token::Ident(ident) if ident == "similarity" => Ok(ScoringExpr::Similarity),
+            Token::Ident(ident) if ident == "recency" => {
+                self.expect(Token::LParen)?;
+                let label = self.label()?;
+                self.expect(Token::Comma)?;
+                if let Some(Token::Ident(name)) = self.peek() {
+                    if name != "half_life" {
+                        return Err(anyhow!("unknown recency argument {}", name));
+                    }
+                    self.pos += 1;
+                    self.expect(Token::Assign)?;
+                }
+                let half_life_secs = self.duration()?;
+                self.expect(Token::RParen)?;
+                Ok(ScoringExpr::Recency {
+                    label,
+                    half_life_secs,
+                })
+            }
+            Token::Ident(ident) if ident == "boost" => {
+                self.expect(Token::LParen)?;
+                let label = self.label()?;
+                let equal = match self.next()? {
+                    Token::Eq => true,
+                    Token::NotEq => false,
+                    token => {
+                        return Err(anyhow!(
+                            "expected '==' or '!=' but found {} in scoring expression",
+                            token
+                        ))
+                    }
+                };
+                let value = match self.next()? {
+                    Token::Str(s) => s,
+                    token => {
+                        return Err(anyhow!(
+                            "expected a string but found {} in scoring expression",
+                            token
+                        ))
+                    }
+                };
+                self.expect(Token::Comma)?;
+                let weight = self.number()?;
+                self.expect(Token::RParen)?;
+                Ok(ScoringExpr::Boost {
+                    label,
+                    value,
+                    equal,
+                    weight,
+                })
+            }
+            Token::Ident(ident) => Err(anyhow!(
+                "unknown identifier {} in scoring expression",
+                ident
+            )),
+            token => Err(anyhow!("unexpected {} in scoring expression", token)),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let negative = if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            true
+        } else {
+            false
+        };
+        match self.next()? {
+            Token::Number(n) if negative => Ok(-n),
+            Token::Number(n) => Ok(n),
+            token => Err(anyhow!(
+                "expected a number but found {} in scoring expression",
+                token
+            )),
+        }
+    }
+
+    /// Parses a label name, written either as `labels.name` or `name`
+    fn label(&mut self) -> Result<String> {
+        let name = match self.next()? {
+            Token::Ident(name) => name,
+            token => {
+                return Err(anyhow!(
+                    "expected a label but found {} in scoring expression",
+                    token
+                ))
+            }
+        };
+        if name != "labels" || self.peek() != Some(&Token::Dot) {
+            return Ok(name);
+        }
+        self.pos += 1;
+        match self.next()? {
+            Token::Ident(name) => Ok(name),
+            token => Err(anyhow!(
+                "expected a label but found {} in scoring expression",
+                token
+            )),
+        }
+    }
+
+    fn duration(&mut self) -> Result<f64> {
+        let value = self.number()?;
+        if value <= 0.0 {
+            return Err(anyhow!("half life must be positive"));
+        }
+        let multiplier = match self.peek() {
+            Some(Token::Ident(unit)) => {
+                let multiplier = match unit.as_str() {
+                    "s" => 1.0,
+                    "m" => 60.0,
+                    "h" => 3600.0,
+                    "d" => 86400.0,
+                    unit => return Err(anyhow!("unknown duration unit {}", unit)),
+                };
+                self.pos += 1;
+                multiplier
+            }
+            _ => 1.0,
+        };
+        Ok(value * multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: f64 = 86400.0;
+
+    fn labels(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr = ScoringExpr::parse(
+            r#"0.8*similarity + 0.2*recency(labels.updated_at, half_life=30d) + boost(source=="handbook", 0.1)"#,
+        )
+        .unwrap();
+        assert_eq!(
+            expr.to_string(),
+            r#"0.8 * similarity + 0.2 * recency(labels.updated_at, half_life=2592000s) + boost(labels.source=="handbook", 0.1)"#
+        );
+
+        //  Precedence and parentheses
+        let expr = ScoringExpr::parse("1 - (2 - 3) * -similarity / 2").unwrap();
+        assert_eq!(expr.to_string(), "1 - (2 - 3) * -similarity / 2");
+        let input = ScoringInput {
+            similarity: 0.5,
+            labels: &HashMap::new(),
+            now: 0,
+        };
+        assert_eq!(expr.evaluate(&input), 0.75);
+
+        let expr =
+            ScoringExpr::parse("recency(created, 12h) + boost(tier != 'free', -0.5)").unwrap();
+        assert_eq!(
+            expr,
+            ScoringExpr::Binary {
+                op: BinaryOp::Add,
+                lhs: Box::new(ScoringExpr::Recency {
+                    label: "created".to_string(),
+                    half_life_secs: 12.0 * 3600.0,
+                }),
+                rhs: Box::new(ScoringExpr::Boost {
+                    label: "tier".to_string(),
+                    value: "free".to_string(),
+                    equal: false,
+                    weight: -0.5,
+                }),
+            }
+        );
+
+        for invalid in [
+            "",
+            "similarity +",
+            "0.8 * score",
+            "recency(updated_at)",
+            "recency(updated_at, half_life=0d)",
+            "recency(updated_at, half_life=3w)",
+            "boost(source = 'handbook', 0.1)",
+            "boost(source == 'handbook, 0.1)",
+            "(similarity",
+            "similarity similarity",
+            "similarity; 1",
+        ] {
+            assert!(ScoringExpr::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_recency_decay() {
+        assert_eq!(recency_decay(0.0, 30.0 * DAY), 1.0);
+        assert_eq!(recency_decay(-DAY, 30.0 * DAY), 1.0);
+        assert_eq!(recency_decay(30.0 * DAY, 30.0 * DAY), 0.5);
+        assert_eq!(recency_decay(60.0 * DAY, 30.0 * DAY), 0.25);
+        assert!((recency_decay(15.0 * DAY, 30.0 * DAY) - 0.5_f64.sqrt()).abs() < 1e-12);
+
+        let expr = ScoringExpr::parse("recency(labels.updated_at, half_life=30d)").unwrap();
+        let now = 100 * DAY as u64;
+        let updated = (now - 30 * DAY as u64).to_string();
+        let evaluate = |labels: &HashMap<String, String>| {
+            expr.evaluate(&ScoringInput {
+                similarity: 1.0,
+                labels,
+                now,
+            })
+        };
+        assert_eq!(evaluate(&labels(&[("updated_at", &updated)])), 0.5);
+        //  Missing or unparseable timestamps get no recency
+        assert_eq!(evaluate(&labels(&[])), 0.0);
+        assert_eq!(evaluate(&labels(&[("updated_at", "yesterday")])), 0.0);
+    }
+
+    #[test]
+    fn test_explain() {
+        let expr = ScoringExpr::parse(
+            r#"0.8*similarity + 0.2*recency(updated_at, half_life=1d) - boost(source=="spam", 0.5)"#,
+        )
+        .unwrap();
+        let now = 10 * DAY as u64;
+        let labels = labels(&[
+            ("updated_at", &(now - DAY as u64).to_string()),
+            ("source", "spam"),
+        ]);
+        let explanation = expr.explain(&ScoringInput {
+            similarity: 0.5,
+            labels: &labels,
+            now,
+        });
+        assert_eq!(explanation.similarity, 0.5);
+        assert_eq!(
+            explanation.terms,
+            vec![
+                ScoreTerm {
+                    expression: "0.8 * similarity".to_string(),
+                    value: 0.4,
+                },
+                ScoreTerm {
+                    expression: "0.2 * recency(labels.updated_at, half_life=86400s)".to_string(),
+                    value: 0.1,
+                },
+                ScoreTerm {
+                    expression: r#"boost(labels.source=="spam", 0.5)"#.to_string(),
+                    value: -0.5,
+                },
+            ]
+        );
+        let sum: f64 = explanation.terms.iter().map(|t| t.value).sum();
+        assert!((explanation.score - sum).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rank() {
+        let result = |content_id: &str, score: f32, source: &str| ScoredText {
+            content_id: content_id.to_string(),
+            confidence_score: score,
+            labels: labels(&[("source", source)]),
+            ..Default::default()
+        };
+        let results = vec![
+            result("a", 0.9, "web"),
+            result("b", 0.8, "handbook"),
+            result("c", 0.7, "handbook"),
+        ];
+        let expr = ScoringExpr::parse(r#"similarity + boost(source=="handbook", 0.15)"#).unwrap();
+        let ranked = rank(&expr, results, 2, 0);
+        let ids: Vec<_> = ranked.iter().map(|(r, _)| r.content_id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert!((ranked[0].0.confidence_score - 0.95).abs() < 1e-6);
+        assert!((ranked[0].1.similarity - 0.8).abs() < 1e-6);
+    }
+}
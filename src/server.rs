@@ -1,10 +1,16 @@
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, Result};
 use axum::{
     body::Body,
-    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, StatusCode},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Extension,
@@ -15,7 +21,11 @@ use axum_otel_metrics::HttpMetricsLayerBuilder;
 use axum_server::{tls_rustls::RustlsConfig, Handle};
 use axum_tracing_opentelemetry::middleware::OtelAxumLayer;
 use axum_typed_websockets::WebSocketUpgrade;
-use hyper::{header::CONTENT_TYPE, Method};
+use futures::FutureExt;
+use hyper::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    Method,
+};
 use indexify_internal_api as internal_api;
 use indexify_proto::indexify_coordinator::{
     self,
@@ -39,21 +49,63 @@ use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
     api::{self, *},
-    blob_storage::{BlobStorage, ContentReader},
+    blob_storage::{
+        scavenger::BlobScavenger,
+        signing::{BlobUrlSigner, SignatureError, BLOB_SERVER_PATH},
+        BlobStorage,
+        ContentReader,
+    },
     caching::caches_extension::Caches,
     coordinator_client::CoordinatorClient,
-    data_manager::DataManager,
+    data_manager::{DataManager, GcTaskResult},
     extractor_router::ExtractorRouter,
     ingest_extracted_content::IngestExtractedContentState,
     metadata_storage::{self, MetadataReaderTS, MetadataStorageTS},
     metrics,
-    server_config::ServerConfig,
+    search_log::{SearchLog, SearchLogEntry, SearchLogResult},
+    search_profiles,
+    search_scoring::{self, ScoringExpr},
+    server_config::{AclConfig, ServerConfig, StateChangeAdminConfig, SyncExtractionConfig},
+    sync_extraction::{self, SyncExtraction},
     tls::build_mtls_config,
-    vector_index::VectorIndexManager,
+    utils,
+    vector_index::{VectorIndexError, VectorIndexManager},
     vectordbs,
 };
 
 const DEFAULT_SEARCH_LIMIT: u64 = 5;
+const DEFAULT_SEARCH_LOG_LIMIT: usize = 100;
+
+/// Header carrying the idempotency key of ingestion requests
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+fn validate_idempotency_key(key: &str) -> Result<(), IndexifyAPIError> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "idempotency key must be between 1 and {} bytes",
+                MAX_IDEMPOTENCY_KEY_LEN
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, IndexifyAPIError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY) else {
+        return Ok(None);
+    };
+    let key = value.to_str().map_err(|_| {
+        IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            "idempotency key must be visible ascii",
+        )
+    })?;
+    validate_idempotency_key(key)?;
+    Ok(Some(key.to_string()))
+}
 
 #[derive(RustEmbed)]
 #[folder = "ui/build"]
@@ -66,6 +118,33 @@ pub struct NamespaceEndpointState {
     pub content_reader: Arc<ContentReader>,
     pub registry: Arc<prometheus::Registry>,
     pub metrics: Arc<metrics::server::Metrics>,
+    pub search_log: Arc<SearchLog>,
+    pub sync_extraction: SyncExtractionConfig,
+    pub acl: AclConfig,
+}
+
+impl NamespaceEndpointState {
+    /// Whether content can be read by the user a request is made on behalf
+    /// of. Requests not made on behalf of a user can read all content.
+    fn permits(
+        &self,
+        access: Option<&internal_api::AccessContext>,
+        content: &api::ContentMetadata,
+    ) -> bool {
+        access.map_or(true, |access| {
+            access.permits(content.acl.as_ref(), self.acl.allow_unlabeled)
+        })
+    }
+
+    /// Resolves the namespace of a request, which may be an alias. Handlers
+    /// resolve it once, before anything else, so a request is served from a
+    /// single namespace while the alias is moved.
+    async fn resolve_namespace(&self, namespace: &str) -> Result<String, IndexifyAPIError> {
+        self.data_manager
+            .resolve_namespace(namespace)
+            .await
+            .map_err(IndexifyAPIError::internal_error)
+    }
 }
 
 #[derive(OpenApi)]
@@ -74,25 +153,52 @@ pub struct NamespaceEndpointState {
             create_namespace,
             list_namespaces,
             get_namespace,
+            clone_namespace,
+            set_namespace_retention,
+            set_namespace_alias,
             add_texts,
             list_indexes,
+            namespace_overview,
+            namespace_usage,
             index_search,
+            create_search_profile,
+            list_search_profiles,
+            create_search_feedback,
+            list_search_feedback,
+            get_search_profile,
+            delete_search_profile,
+            create_subscription,
+            list_subscriptions,
+            delete_subscription,
             list_extractors,
             list_executors,
             list_content,
             get_content_metadata,
+            content_exists,
+            fetch_content,
             upload_file,
+            rebuild_content_tree,
             list_tasks,
             extract_content
         ),
         components(
-            schemas(CreateNamespace, CreateNamespaceResponse, IndexDistance,
+            schemas(CreateNamespace, CreateNamespaceResponse, CloneNamespaceRequest, CloneNamespaceResponse, RetentionRules,
+                SetNamespaceAliasRequest, SetNamespaceAliasResponse, IndexDistance,
                 TextAddRequest, TextAdditionResponse, Text, IndexSearchResponse,
-                DocumentFragment, ListIndexesResponse, ExtractorOutputSchema, Index, SearchRequest, ListNamespacesResponse, ListExtractorsResponse
+                DocumentFragment, ListIndexesResponse, IndexOverview, NamespaceOverview, UsageCounters, UsageBucket, NamespaceUsageResponse, ExtractorOutputSchema, Index, SearchRequest, ListNamespacesResponse, ListExtractorsResponse
             , ExtractorDescription, DataNamespace, ExtractionPolicy, ExtractionPolicyRequest, ExtractionPolicyResponse, Executor,
             MetadataResponse, ExtractedMetadata, ListExecutorsResponse, EmbeddingSchema, ExtractResponse, ExtractRequest,
             Content, Feature, FeatureType, GetContentMetadataResponse, ListTasksResponse, internal_api::Task, internal_api::TaskOutcome,
             internal_api::Content, internal_api::ContentMetadata, ListContentResponse, GetNamespaceResponse, ExtractionPolicyResponse,
+            ContentExistsEntry, ContentExistsRequest, ContentExistsResponse, internal_api::ContentExistence,
+            FetchContentRequest, FetchContentResponse, FetchUrlResult,
+            CreateSearchProfile, SearchProfile, ListSearchProfilesResponse,
+            CreateSearchFeedback, ListSearchFeedbackResponse, internal_api::SearchFeedback, internal_api::FeedbackVerdict,
+            internal_api::SearchFeedbackCounts,
+            CreateSubscriptionRequest, Subscription, ListSubscriptionsResponse,
+            internal_api::IndexAttributeSchema, internal_api::AttributeOptions, internal_api::ContentAcl, AccessParams,
+            internal_api::TombstoneReason,
+            ScoreExplanation, ScoreTerm, MatchedFilter, RebuildContentTreeParams, RebuildContentTreeResponse,
         )
         ),
         tags(
@@ -133,6 +239,9 @@ impl Server {
             VectorIndexManager::new(coordinator_client.clone(), vector_db.clone())
                 .map_err(|e| anyhow!("unable to create vector index {}", e))?,
         );
+        vector_index_manager
+            .index_events()
+            .start(shutdown_rx.clone());
         let metadata_index_manager: MetadataStorageTS =
             metadata_storage::from_config(&self.config.metadata_storage)?;
         let metadata_reader: MetadataReaderTS =
@@ -147,6 +256,9 @@ impl Server {
             blob_storage.clone(),
             coordinator_client.clone(),
         ));
+        data_manager
+            .clone()
+            .start_forwarding_index_events(shutdown_rx.clone());
         let ingestion_server_id = nanoid::nanoid!(16);
 
         self.start_gc_tasks_stream(
@@ -155,18 +267,29 @@ impl Server {
             data_manager.clone(),
             shutdown_rx.clone(),
         );
+        if self.config.blob_scavenger.enabled {
+            BlobScavenger::new(
+                self.config.blob_scavenger.clone(),
+                blob_storage.clone(),
+                coordinator_client.clone(),
+            )
+            .start(shutdown_rx.clone());
+        }
         let namespace_endpoint_state = NamespaceEndpointState {
             data_manager: data_manager.clone(),
             coordinator_client: coordinator_client.clone(),
             content_reader: Arc::new(ContentReader::new(self.config.clone())),
             registry,
             metrics: Arc::new(crate::metrics::server::Metrics::new()),
+            search_log: Arc::new(SearchLog::new(self.config.search_log.clone())?),
+            sync_extraction: self.config.sync_extraction.clone(),
+            acl: self.config.acl.clone(),
         };
         let caches = Caches::new(self.config.cache.clone());
         let cors = CorsLayer::new()
             .allow_methods([Method::GET, Method::POST])
             .allow_origin(Any)
-            .allow_headers([CONTENT_TYPE]);
+            .allow_headers([CONTENT_TYPE, HeaderName::from_static(IDEMPOTENCY_KEY)]);
 
         let metrics = HttpMetricsLayerBuilder::new().build();
         let app = Router::new()
@@ -183,6 +306,14 @@ impl Server {
                 "/namespaces/:namespace/indexes",
                 get(list_indexes).with_state(namespace_endpoint_state.clone()),
             )
+            .route(
+                "/namespaces/:namespace/overview",
+                get(namespace_overview).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/usage",
+                get(namespace_usage).with_state(namespace_endpoint_state.clone()),
+            )
             .route(
                 "/namespaces/:namespace/add_texts",
                 post(add_texts).with_state(namespace_endpoint_state.clone()),
@@ -203,6 +334,14 @@ impl Server {
                 "/namespaces/:namespace/content/:content_id",
                 get(get_content_metadata).with_state(namespace_endpoint_state.clone()),
             )
+            .route(
+                "/namespaces/:namespace/content/exists",
+                post(content_exists).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/content/fetch",
+                post(fetch_content).with_state(namespace_endpoint_state.clone()),
+            )
             .route(
                 "/namespaces/:namespace/content/:content_id/labels",
                 put(update_labels).with_state(namespace_endpoint_state.clone()),
@@ -223,6 +362,10 @@ impl Server {
                 "/namespaces/:namespace/content/:content_id/content-tree",
                 get(get_content_tree_metadata).with_state(namespace_endpoint_state.clone()),
             )
+            .route(
+                "/namespaces/:namespace/content/:content_id/rebuild",
+                post(rebuild_content_tree).with_state(namespace_endpoint_state.clone()),
+            )
             .route(
                 "/namespaces/:namespace/upload_file",
                 post(upload_file).with_state(namespace_endpoint_state.clone()),
@@ -241,6 +384,42 @@ impl Server {
                 "/namespaces/:namespace/search",
                 post(index_search).with_state(namespace_endpoint_state.clone()),
             )
+            .route(
+                "/namespaces/:namespace/search_feedback",
+                post(create_search_feedback).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/search_feedback",
+                get(list_search_feedback).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/search_profiles",
+                post(create_search_profile).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/search_profiles",
+                get(list_search_profiles).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/search_profiles/:name",
+                get(get_search_profile).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/search_profiles/:name",
+                delete(delete_search_profile).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/subscriptions",
+                post(create_subscription).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/subscriptions",
+                get(list_subscriptions).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/subscriptions/:subscription_id",
+                delete(delete_subscription).with_state(namespace_endpoint_state.clone()),
+            )
             .route(
                 "/namespaces/:namespace/sql_query",
                 post(run_sql_query).with_state(namespace_endpoint_state.clone()),
@@ -261,6 +440,18 @@ impl Server {
                 "/namespaces/:namespace",
                 get(get_namespace).with_state(namespace_endpoint_state.clone()),
             )
+            .route(
+                "/namespaces/:namespace/retention",
+                put(set_namespace_retention).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespaces/:namespace/clone",
+                post(clone_namespace).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/namespace_aliases/:alias",
+                put(set_namespace_alias).with_state(namespace_endpoint_state.clone()),
+            )
             .route(
                 "/executors",
                 get(list_executors).with_state(namespace_endpoint_state.clone()),
@@ -289,6 +480,34 @@ impl Server {
                 "/task_assignments",
                 get(list_task_assignments).with_state(namespace_endpoint_state.clone()),
             )
+            .route(
+                "/admin/usage",
+                get(usage_rollup).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/admin/search_log",
+                get(list_search_log).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/admin/search_log/replay",
+                post(replay_search_log).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/admin/vector_totals",
+                get(namespace_vector_totals).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/admin/query_embedding_cache/flush",
+                post(flush_query_embedding_cache).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/admin/subscriptions/:subscription_id/dead_letters",
+                get(list_dead_letters).with_state(namespace_endpoint_state.clone()),
+            )
+            .route(
+                "/admin/subscriptions/:subscription_id/dead_letters/requeue",
+                post(requeue_dead_letters).with_state(namespace_endpoint_state.clone()),
+            )
             .route(
                 "/metrics/raft",
                 get(get_raft_metrics_snapshot).with_state(namespace_endpoint_state.clone()),
@@ -297,6 +516,14 @@ impl Server {
                 "/metrics/ingest",
                 get(ingest_metrics).with_state(namespace_endpoint_state.clone()),
             )
+            .merge(blob_server_routes(
+                &self.config,
+                namespace_endpoint_state.clone(),
+            ))
+            .merge(state_change_admin_routes(
+                &self.config,
+                namespace_endpoint_state.clone(),
+            ))
             .route("/ui", get(ui_index_handler))
             .route("/ui/*rest", get(ui_handler))
             .layer(OtelAxumLayer::default())
@@ -321,6 +548,8 @@ impl Server {
             .create_namespace(&DataNamespace {
                 name: "default".to_string(),
                 extraction_graphs: vec![],
+                warnings: vec![],
+                retention: Default::default(),
             })
             .await
         {
@@ -366,6 +595,7 @@ impl Server {
         let mut attempt = 0;
         let delay = 2;
         let ingestion_server_id = ingestion_server_id.to_string();
+        let max_tasks_per_batch = self.config.gc_vector_deletes.max_tasks_per_batch.max(1);
 
         tokio::spawn(async move {
             loop {
@@ -390,6 +620,7 @@ impl Server {
                     task_id: "".to_string(),
                     completed: false,
                     ingestion_server_id: ingestion_server_id.clone(),
+                    completed_output_tables: vec![],
                 };
                 let request = tonic::Request::new(async_stream::stream! {
                     loop {
@@ -411,23 +642,45 @@ impl Server {
                         let mut stream = response.into_inner();
 
                         while let Ok(Some(command)) = stream.message().await {
-                            if let Some(gc_task) = command.gc_task {
-                                if let Err(e) = data_manager.perform_gc_task(&gc_task).await {
-                                    tracing::error!(
-                                        "Failed to delete content for task {:?}: {}",
-                                        gc_task,
-                                        e
-                                    );
-                                    continue;
+                            //  Tasks which already arrived are run together, so the
+                            //  vectors of their content are removed with one delete per
+                            //  index
+                            let mut gc_tasks: Vec<_> = command.gc_task.into_iter().collect();
+                            while gc_tasks.len() < max_tasks_per_batch {
+                                match stream.message().now_or_never() {
+                                    Some(Ok(Some(command))) => gc_tasks.extend(command.gc_task),
+                                    _ => break,
                                 }
-                                if let Err(e) = ack_tx
-                                    .send(GcTaskAcknowledgement {
+                            }
+                            let results = data_manager.perform_gc_tasks(&gc_tasks).await;
+                            for (gc_task, result) in gc_tasks.into_iter().zip(results) {
+                                let ack = match result {
+                                    GcTaskResult::Completed => GcTaskAcknowledgement {
                                         task_id: gc_task.task_id.clone(),
                                         completed: true,
                                         ingestion_server_id: ingestion_server_id.clone(),
-                                    })
-                                    .await
-                                {
+                                        completed_output_tables: vec![],
+                                    },
+                                    //  The coordinator records the progress and sends
+                                    //  the task again
+                                    GcTaskResult::Incomplete {
+                                        completed_output_tables,
+                                    } => GcTaskAcknowledgement {
+                                        task_id: gc_task.task_id.clone(),
+                                        completed: false,
+                                        ingestion_server_id: ingestion_server_id.clone(),
+                                        completed_output_tables,
+                                    },
+                                    GcTaskResult::Failed(e) => {
+                                        tracing::error!(
+                                            "Failed to delete content for task {:?}: {}",
+                                            gc_task,
+                                            e
+                                        );
+                                        continue;
+                                    }
+                                };
+                                if let Err(e) = ack_tx.send(ack).await {
                                     tracing::error!(
                                         "Failed to send ack for task {:?}: {}",
                                         gc_task,
@@ -484,6 +737,8 @@ async fn create_namespace(
     let data_namespace = api::DataNamespace {
         name: payload.name.clone(),
         extraction_graphs: payload.extraction_graphs.clone(),
+        warnings: vec![],
+        retention: payload.retention,
     };
     state
         .data_manager
@@ -538,6 +793,7 @@ async fn get_namespace(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
 ) -> Result<Json<GetNamespaceResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let data_namespace = state.data_manager.get(&namespace).await.map_err(|e| {
         IndexifyAPIError::new(
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -549,6 +805,82 @@ async fn get_namespace(
     }))
 }
 
+#[utoipa::path(
+    put,
+    path = "/namespaces/{namespace}/retention",
+    request_body = RetentionRules,
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Retention rules of the namespace replaced"),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to set the retention rules")
+    ),
+)]
+#[axum::debug_handler]
+async fn set_namespace_retention(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<RetentionRules>,
+) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    state
+        .data_manager
+        .set_namespace_retention(&namespace, payload)
+        .await
+        .map_err(IndexifyAPIError::internal_error)
+}
+
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/clone",
+    request_body = CloneNamespaceRequest,
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Namespace cloned successfully", body = CloneNamespaceResponse),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to clone namespace")
+    ),
+)]
+#[axum::debug_handler]
+async fn clone_namespace(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<CloneNamespaceRequest>,
+) -> Result<Json<CloneNamespaceResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let response = state
+        .data_manager
+        .clone_namespace(&namespace, payload)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    put,
+    path = "/namespace_aliases/{alias}",
+    request_body = SetNamespaceAliasRequest,
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Alias points at the namespace", body = SetNamespaceAliasResponse),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to set the alias")
+    ),
+)]
+#[axum::debug_handler]
+async fn set_namespace_alias(
+    Path(alias): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<SetNamespaceAliasRequest>,
+) -> Result<Json<SetNamespaceAliasResponse>, IndexifyAPIError> {
+    state
+        .data_manager
+        .set_namespace_alias(&alias, &payload.namespace)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(SetNamespaceAliasResponse {
+        alias,
+        namespace: payload.namespace,
+    }))
+}
+
 #[utoipa::path(
     post,
     path = "/namespace/{namespace}/extraction_graph",
@@ -567,6 +899,7 @@ async fn create_extraction_graph(
     State(state): State<NamespaceEndpointState>,
     Json(payload): Json<ExtractionGraphRequest>,
 ) -> Result<Json<ExtractionGraphResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let indexes = state
         .data_manager
         .create_extraction_graph(&namespace, payload)
@@ -582,6 +915,7 @@ async fn create_extraction_graph(
     post,
     path = "/namespaces/{namespace}/add_texts",
     request_body = TextAddRequest,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Texts added again with the same key return the content created the first time, the key of each text is the header value followed by `:` and the position of the text")),
     tag = "indexify",
     responses(
         (status = 200, description = "Texts were successfully added to the namespace", body = TextAdditionResponse),
@@ -592,15 +926,42 @@ async fn create_extraction_graph(
 async fn add_texts(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
+    headers: HeaderMap,
     Json(payload): Json<TextAddRequest>,
 ) -> Result<Json<TextAdditionResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     if payload.extraction_graph_names.is_empty() {
         return Err(IndexifyAPIError::new(
             StatusCode::BAD_REQUEST,
             "extraction_graph_names must not be empty",
         ));
     }
-    for document in &payload.documents {
+    let header_key = idempotency_key(&headers)?;
+    let mut keys = Vec::new();
+    for (i, document) in payload.documents.iter().enumerate() {
+        let key = match (&document.idempotency_key, &header_key) {
+            (Some(key), _) => Some(key.clone()),
+            (None, Some(key)) => Some(format!("{}:{}", key, i)),
+            (None, None) => None,
+        };
+        if let Some(key) = &key {
+            validate_idempotency_key(key)?;
+        }
+        keys.push(key);
+    }
+    for (document, key) in payload.documents.iter().zip(&keys) {
+        //  Texts added before with the key are not added again
+        if let Some(key) = key {
+            if state
+                .data_manager
+                .get_ingestion_key(&namespace, key)
+                .await
+                .map_err(IndexifyAPIError::internal_error)?
+                .is_some()
+            {
+                continue;
+            }
+        }
         if let Some(id) = &document.id {
             if !DataManager::is_hex_string(id) {
                 return Err(IndexifyAPIError::new(
@@ -625,7 +986,8 @@ async fn add_texts(
     let content: Vec<api::ContentWithId> = payload
         .documents
         .iter()
-        .map(|d| api::ContentWithId {
+        .zip(keys)
+        .map(|(d, idempotency_key)| api::ContentWithId {
             id: d.id.clone().unwrap_or_else(DataManager::make_id),
             content: api::Content {
                 content_type: mime::TEXT_PLAIN.to_string(),
@@ -634,28 +996,69 @@ async fn add_texts(
                 features: vec![],
             },
             extraction_graph_names: payload.extraction_graph_names.clone(),
+            acl: d.acl.clone(),
+            idempotency_key,
         })
         .collect();
-    let content_ids = content.iter().map(|c| c.id.clone()).collect();
-    state
-        .data_manager
-        .add_texts(&namespace, content, payload.extraction_graph_names)
-        .await
-        .map_err(|e| {
-            IndexifyAPIError::new(
-                StatusCode::BAD_REQUEST,
-                &format!("failed to add text: {}", e),
-            )
-        })?;
-    Ok(Json(TextAdditionResponse { content_ids }))
+    let sync = payload.sync.unwrap_or(false);
+    let fits_sync_limit = content
+        .iter()
+        .all(|c| c.content.bytes.len() <= state.sync_extraction.max_content_size_bytes);
+    let add_error = |e: anyhow::Error| {
+        IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            &format!("failed to add text: {}", e),
+        )
+    };
+    if !sync || !fits_sync_limit {
+        let content_ids = state
+            .data_manager
+            .add_texts(&namespace, content, payload.extraction_graph_names)
+            .await
+            .map_err(add_error)?;
+        return Ok(Json(TextAdditionResponse {
+            content_ids,
+            sync_fallback: sync.then(|| {
+                format!(
+                    "texts larger than {} bytes are extracted asynchronously",
+                    state.sync_extraction.max_content_size_bytes
+                )
+            }),
+            ..Default::default()
+        }));
+    }
+    let deadline = Duration::from_millis(state.sync_extraction.deadline_ms);
+    let (content_ids, extraction) = sync_extraction::add_texts(
+        &state,
+        &namespace,
+        content,
+        payload.extraction_graph_names,
+        deadline,
+    )
+    .await
+    .map_err(add_error)?;
+    let mut response = TextAdditionResponse {
+        content_ids,
+        ..Default::default()
+    };
+    match extraction {
+        SyncExtraction::Extracted(derived_content_ids) => {
+            response.derived_content_ids = derived_content_ids
+        }
+        SyncExtraction::Fallback(reason) => response.sync_fallback = Some(reason),
+    }
+    Ok(Json(response))
 }
 
 #[axum::debug_handler]
 async fn ingest_remote_file(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
+    headers: HeaderMap,
     Json(payload): Json<IngestRemoteFile>,
 ) -> Result<Json<IngestRemoteFileResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let idempotency_key = idempotency_key(&headers)?;
     let content_id = state
         .data_manager
         .ingest_remote_file(
@@ -665,6 +1068,8 @@ async fn ingest_remote_file(
             &payload.mime_type,
             payload.labels,
             &payload.extraction_graph_names,
+            payload.acl,
+            idempotency_key.as_deref(),
         )
         .await
         .map_err(|e| {
@@ -693,6 +1098,7 @@ async fn update_labels(
     State(state): State<NamespaceEndpointState>,
     Json(body): Json<UpdateLabelsRequest>,
 ) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     state
         .data_manager
         .update_labels(&namespace, &content_id, body.labels)
@@ -716,8 +1122,10 @@ async fn list_content(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
     filter: Query<super::api::ListContentFilters>,
+    Query(access): Query<AccessParams>,
 ) -> Result<Json<ListContentResponse>, IndexifyAPIError> {
-    let content_list = state
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let mut content_list = state
         .data_manager
         .list_content(
             &namespace,
@@ -727,6 +1135,8 @@ async fn list_content(
         )
         .await
         .map_err(IndexifyAPIError::internal_error)?;
+    let access = access.access_context();
+    content_list.retain(|content| state.permits(access.as_ref(), content));
     Ok(Json(ListContentResponse { content_list }))
 }
 
@@ -746,9 +1156,11 @@ async fn delete_content(
     State(state): State<NamespaceEndpointState>,
     Json(body): Json<super::api::TombstoneContentRequest>,
 ) -> Result<Json<()>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let request = indexify_coordinator::TombstoneContentRequest {
         namespace: namespace.clone(),
         content_ids: body.content_ids.clone(),
+        reason: body.reason.clone().unwrap_or_default(),
     };
 
     state
@@ -778,6 +1190,7 @@ async fn delete_content(
     get,
     path = "/namespaces/{namespace}/content/{content_id}",
     tag = "indexify",
+    params(GetContentMetadataParams),
     responses(
         (status = 200, description = "Reads a specific content in the namespace", body = GetRawContentResponse),
         (status = BAD_REQUEST, description = "Unable to read content")
@@ -787,21 +1200,116 @@ async fn delete_content(
 async fn get_content_metadata(
     Path((namespace, content_id)): Path<(String, String)>,
     State(state): State<NamespaceEndpointState>,
+    Query(access): Query<AccessParams>,
+    Query(params): Query<GetContentMetadataParams>,
 ) -> Result<Json<GetContentMetadataResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let content_list = state
         .data_manager
-        .get_content_metadata(&namespace, vec![content_id])
+        .get_content_metadata_with_tombstoned(
+            &namespace,
+            vec![content_id],
+            params.include_tombstoned,
+        )
         .await
         .map_err(IndexifyAPIError::internal_error)?;
+    //  Content the user can't read is reported as missing
+    let access = access.access_context();
     let content_metadata = content_list
-        .first()
+        .iter()
+        .find(|content| state.permits(access.as_ref(), content))
         .ok_or_else(|| IndexifyAPIError::new(StatusCode::NOT_FOUND, "content not found"))?;
+    let feedback = if params.include_feedback {
+        Some(
+            state
+                .data_manager
+                .get_search_feedback_counts(&namespace, &content_metadata.id)
+                .await
+                .map_err(IndexifyAPIError::internal_error)?,
+        )
+    } else {
+        None
+    };
 
     Ok(Json(GetContentMetadataResponse {
         content_metadata: content_metadata.clone(),
+        feedback,
     }))
 }
 
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/content/exists",
+    tag = "indexify",
+    request_body = ContentExistsRequest,
+    responses(
+        (status = 200, description = "Reports for each entry whether the content is known, live or tombstoned", body = ContentExistsResponse),
+        (status = BAD_REQUEST, description = "An entry has neither an id nor a hash")
+    ),
+)]
+#[axum::debug_handler]
+async fn content_exists(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<ContentExistsRequest>,
+) -> Result<Json<ContentExistsResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    if payload
+        .entries
+        .iter()
+        .any(|entry| entry.id.is_none() && entry.hash.is_none())
+    {
+        return Err(IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            "each entry needs either an id or a hash",
+        ));
+    }
+    let results = state
+        .data_manager
+        .content_exists(&namespace, payload.entries)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(ContentExistsResponse { results }))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/content/fetch",
+    tag = "indexify",
+    request_body = FetchContentRequest,
+    responses(
+        (status = 200, description = "Fetches the urls and creates content from them, reporting the content id or the error of each url", body = FetchContentResponse),
+        (status = BAD_REQUEST, description = "No urls were given")
+    ),
+)]
+#[axum::debug_handler]
+async fn fetch_content(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<FetchContentRequest>,
+) -> Result<Json<FetchContentResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    if payload.urls.is_empty() {
+        return Err(IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            "at least one url is required",
+        ));
+    }
+    let results = state
+        .data_manager
+        .fetch_urls(
+            &namespace,
+            payload.urls,
+            payload.labels,
+            payload.extraction_graph_names,
+            payload.acl,
+        )
+        .await;
+    Ok(Json(FetchContentResponse { results }))
+}
+
 #[tracing::instrument]
 #[utoipa::path(
     get,
@@ -816,6 +1324,7 @@ async fn wait_content_extraction(
     Path((namespace, content_id)): Path<(String, String)>,
     State(state): State<NamespaceEndpointState>,
 ) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     state
         .data_manager
         .wait_content_extraction(&content_id)
@@ -837,6 +1346,7 @@ async fn active_content(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
 ) -> Result<Json<Vec<String>>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let res = state
         .data_manager
         .list_active_contents(&namespace)
@@ -859,29 +1369,63 @@ async fn active_content(
 async fn get_content_tree_metadata(
     Path((namespace, content_id)): Path<(String, String)>,
     State(state): State<NamespaceEndpointState>,
+    Query(access): Query<AccessParams>,
 ) -> Result<Json<GetContentTreeMetadataResponse>, IndexifyAPIError> {
-    let content_tree_metadata = state
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let mut content_tree_metadata = state
         .data_manager
         .get_content_tree_metadata(&namespace, content_id)
         .await
         .map_err(IndexifyAPIError::internal_error)?;
+    let access = access.access_context();
+    content_tree_metadata.retain(|content| state.permits(access.as_ref(), content));
     Ok(Json(GetContentTreeMetadataResponse {
         content_tree_metadata,
     }))
 }
 
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/content/{content_id}/rebuild",
+    params(RebuildContentTreeParams),
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Deleted the vectors of the content tree from the index and created the tasks which extract them again", body = RebuildContentTreeResponse),
+        (status = BAD_REQUEST, description = "Unable to rebuild the content tree")
+    )
+)]
+#[axum::debug_handler]
+async fn rebuild_content_tree(
+    Path((namespace, content_id)): Path<(String, String)>,
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<RebuildContentTreeParams>,
+) -> Result<Json<RebuildContentTreeResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let task_count = state
+        .data_manager
+        .rebuild_content_tree(&namespace, &content_id, &params.index)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(RebuildContentTreeResponse { task_count }))
+}
+
 #[axum::debug_handler]
 async fn download_content(
     Path((namespace, content_id)): Path<(String, String)>,
     State(state): State<NamespaceEndpointState>,
+    Query(access): Query<AccessParams>,
 ) -> Result<Response<Body>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let content_list = state
         .data_manager
         .get_content_metadata(&namespace, vec![content_id])
         .await;
     let content_list = content_list.map_err(IndexifyAPIError::internal_error)?;
+    let access = access.access_context();
     let content_metadata = content_list
-        .first()
+        .iter()
+        .find(|content| state.permits(access.as_ref(), content))
         .ok_or(anyhow!("content not found"))
         .map_err(|e| IndexifyAPIError::not_found(&e.to_string()))?
         .clone();
@@ -903,20 +1447,150 @@ async fn download_content(
         .map_err(|e| IndexifyAPIError::new(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))
 }
 
+/// Routes of the blob server, which serves blobs stored on local disk to
+/// executors holding urls signed by the coordinator. Empty when url signing
+/// is disabled.
+fn blob_server_routes(config: &ServerConfig, state: NamespaceEndpointState) -> Router {
+    let Some(signer) = BlobUrlSigner::from_server_config(config) else {
+        return Router::new();
+    };
+    Router::new().route(
+        BLOB_SERVER_PATH,
+        get(download_blob)
+            .with_state(state)
+            .route_layer(middleware::from_fn_with_state(
+                Arc::new(signer),
+                verify_blob_url,
+            )),
+    )
+}
+
+/// Routes of the endpoints which reprocess or discard state changes, only
+/// served when they are enabled and only to requests with the admin api key
+fn state_change_admin_routes(config: &ServerConfig, state: NamespaceEndpointState) -> Router {
+    let Some(admin_config) = config.state_change_admin.clone() else {
+        return Router::new();
+    };
+    Router::new()
+        .route(
+            "/admin/state_changes/:state_change_id/reprocess",
+            post(reprocess_state_change).with_state(state.clone()),
+        )
+        .route(
+            "/admin/state_changes/:state_change_id/discard",
+            post(discard_state_change).with_state(state),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(admin_config),
+            verify_admin_api_key,
+        ))
+}
+
+async fn verify_admin_api_key(
+    State(admin_config): State<Arc<StateChangeAdminConfig>>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Result<Response, IndexifyAPIError> {
+    let authorized = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|api_key| api_key == admin_config.api_key);
+    if !authorized {
+        return Err(IndexifyAPIError::new(
+            StatusCode::UNAUTHORIZED,
+            "missing or invalid admin api key",
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
 #[derive(Debug, serde::Deserialize)]
-struct UploadFileQueryParams {
-    id: Option<String>,
-    extraction_graph_names: Option<String>,
+struct BlobUrlParams {
+    url: String,
+    expires: u64,
+    signature: String,
 }
 
-#[tracing::instrument]
-#[utoipa::path(
-    post,
-    path = "/namespaces/{namespace}/upload_file",
-    request_body(content_type = "multipart/form-data", content = Vec<u8>),
-    tag = "indexify",
-    responses(
-        (status = 200, description = "Uploads a file to the namespace"),
+async fn verify_blob_url(
+    State(signer): State<Arc<BlobUrlSigner>>,
+    Query(params): Query<BlobUrlParams>,
+    request: Request,
+    next: Next,
+) -> Result<Response, IndexifyAPIError> {
+    signer
+        .verify(
+            &params.url,
+            params.expires,
+            &params.signature,
+            utils::timestamp_secs(),
+        )
+        .map_err(|e| match e {
+            SignatureError::Expired => IndexifyAPIError::new(StatusCode::GONE, &e.to_string()),
+            SignatureError::Invalid => IndexifyAPIError::new(StatusCode::FORBIDDEN, &e.to_string()),
+        })?;
+    Ok(next.run(request).await)
+}
+
+async fn download_blob(
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<BlobUrlParams>,
+) -> Result<Response<Body>, IndexifyAPIError> {
+    if !params.url.starts_with("file://") {
+        return Err(IndexifyAPIError::not_found("blob not found"));
+    }
+    Response::builder()
+        .header("Content-Type", "application/octet-stream")
+        .body(Body::from_stream(async_stream::stream! {
+            let reader = state.content_reader.get(&params.url);
+            let mut content_stream = reader.get(&params.url);
+            while let Some(buf) = content_stream.next().await {
+                yield buf;
+            }
+        }))
+        .map_err(|e| IndexifyAPIError::new(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct UploadFileQueryParams {
+    id: Option<String>,
+    extraction_graph_names: Option<String>,
+    /// Comma separated users allowed to read the file
+    acl_users: Option<String>,
+    /// Comma separated groups allowed to read the file
+    acl_groups: Option<String>,
+}
+
+impl UploadFileQueryParams {
+    fn acl(&self) -> Option<internal_api::ContentAcl> {
+        let split = |names: &Option<String>| -> Vec<String> {
+            names
+                .iter()
+                .flat_map(|names| names.split(','))
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        };
+        if self.acl_users.is_none() && self.acl_groups.is_none() {
+            return None;
+        }
+        Some(internal_api::ContentAcl {
+            users: split(&self.acl_users),
+            groups: split(&self.acl_groups),
+        })
+    }
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/upload_file",
+    request_body(content_type = "multipart/form-data", content = Vec<u8>),
+    params(("Idempotency-Key" = Option<String>, Header, description = "Uploads with the same key return the content created by the first upload")),
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Uploads a file to the namespace"),
         (status = BAD_REQUEST, description = "Unable to upload file")
     ),
 )]
@@ -925,8 +1599,10 @@ async fn upload_file(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
     Query(params): Query<UploadFileQueryParams>,
+    headers: HeaderMap,
     mut files: Multipart,
 ) -> Result<Json<UploadFileResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let mut labels = HashMap::new();
 
     let extraction_graph_names = params
@@ -950,6 +1626,22 @@ async fn upload_file(
         ));
     }
 
+    let idempotency_key = idempotency_key(&headers)?;
+    let _guard = match &idempotency_key {
+        Some(key) => Some(state.data_manager.lock_ingestion_key(&namespace, key).await),
+        None => None,
+    };
+    if let Some(key) = &idempotency_key {
+        if let Some(content_id) = state
+            .data_manager
+            .get_ingestion_key(&namespace, key)
+            .await
+            .map_err(IndexifyAPIError::internal_error)?
+        {
+            return Ok(Json(UploadFileResponse { content_id }));
+        }
+    }
+
     //  check if the id already exists for content metadata
     let retrieved_content = state
         .data_manager
@@ -981,7 +1673,7 @@ async fn upload_file(
             info!("writing to blob store, file name = {:?}", name);
 
             let stream = field.map(|res| res.map_err(|err| anyhow::anyhow!(err)));
-            let content_metadata = state
+            let mut content_metadata = state
                 .data_manager
                 .upload_file(
                     &namespace,
@@ -999,10 +1691,12 @@ async fn upload_file(
                         &format!("failed to upload file: {}", e),
                     )
                 })?;
+            content_metadata.acl = params.acl().map(Into::into);
             let size_bytes = content_metadata.size_bytes;
-            state
+            let storage_url = content_metadata.storage_url.clone();
+            let content_id = state
                 .data_manager
-                .create_content_metadata(content_metadata)
+                .create_content_metadata(content_metadata, idempotency_key.as_deref())
                 .await
                 .map_err(|e| {
                     IndexifyAPIError::new(
@@ -1010,6 +1704,13 @@ async fn upload_file(
                         &format!("failed to create content for file: {}", e),
                     )
                 })?;
+            if content_id != id {
+                //  An upload with the same key on another server was first
+                if let Err(e) = state.data_manager.delete_file(&storage_url).await {
+                    tracing::error!("unable to delete unreferenced file {}: {}", storage_url, e);
+                }
+                return Ok(Json(UploadFileResponse { content_id }));
+            }
             state.metrics.node_content_uploads.add(1, &[]);
             state
                 .metrics
@@ -1049,6 +1750,7 @@ async fn update_content(
     State(state): State<NamespaceEndpointState>,
     mut files: Multipart,
 ) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     //  check that the content exists
     let content_metadata = state
         .data_manager
@@ -1084,7 +1786,7 @@ async fn update_content(
         info!("writing to blob store, file name = {:?}", name);
 
         let stream = file.map(|res| res.map_err(|err| anyhow::anyhow!(err)));
-        let new_content_metadata = state
+        let mut new_content_metadata = state
             .data_manager
             .upload_file(
                 &namespace,
@@ -1115,9 +1817,11 @@ async fn update_content(
             return Ok(());
         }
 
+        //  New versions keep the ACL of the content
+        new_content_metadata.acl = content_metadata.acl.clone().map(Into::into);
         state
             .data_manager
-            .create_content_metadata(new_content_metadata)
+            .create_content_metadata(new_content_metadata, None)
             .await
             .map_err(|e| {
                 IndexifyAPIError::new(
@@ -1229,6 +1933,7 @@ async fn list_tasks(
     State(state): State<NamespaceEndpointState>,
     Query(query): Query<ListTasks>,
 ) -> Result<Json<ListTasksResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let tasks = state
         .coordinator_client
         .get()
@@ -1306,6 +2011,7 @@ async fn list_indexes(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
 ) -> Result<Json<ListIndexesResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let indexes = state
         .data_manager
         .list_indexes(&namespace)
@@ -1316,6 +2022,104 @@ async fn list_indexes(
     Ok(Json(ListIndexesResponse { indexes }))
 }
 
+#[tracing::instrument]
+#[utoipa::path(
+    get,
+    path = "/namespaces/{namespace}/overview",
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Indexes of a namespace with their extraction policies and extractors", body = NamespaceOverview),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to build the namespace overview")
+    ),
+)]
+#[axum::debug_handler]
+async fn namespace_overview(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<NamespaceOverview>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let overview = state
+        .data_manager
+        .namespace_overview(&namespace)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(overview))
+}
+
+/// Number of days covered by usage reports when no start date is given
+const DEFAULT_USAGE_DAYS: u64 = 30;
+
+fn usage_range(params: &UsageParams) -> Result<(u64, u64), IndexifyAPIError> {
+    let parse = |date: &str| {
+        utils::date_to_day(date)
+            .map_err(|e| IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()))
+    };
+    let to_day = match &params.to {
+        Some(to) => parse(to)?,
+        None => utils::timestamp_secs() / utils::SECS_PER_DAY,
+    };
+    let from_day = match &params.from {
+        Some(from) => parse(from)?,
+        None => to_day.saturating_sub(DEFAULT_USAGE_DAYS - 1),
+    };
+    if from_day > to_day {
+        return Err(IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            "from must not be after to",
+        ));
+    }
+    Ok((from_day, to_day))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    get,
+    path = "/namespaces/{namespace}/usage",
+    tag = "indexify",
+    params(UsageParams),
+    responses(
+        (status = 200, description = "Daily usage of the extractors of a namespace", body = NamespaceUsageResponse),
+        (status = BAD_REQUEST, description = "Invalid date range"),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to read the usage of the namespace")
+    ),
+)]
+#[axum::debug_handler]
+async fn namespace_usage(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<UsageParams>,
+) -> Result<Response, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let (from_day, to_day) = usage_range(&params)?;
+    let usage = state
+        .data_manager
+        .namespace_usage(&namespace, from_day, to_day)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    match params.format.as_deref() {
+        Some("csv") => Ok(([(CONTENT_TYPE, "text/csv")], usage.to_csv()).into_response()),
+        Some("json") | None => Ok(Json(usage).into_response()),
+        Some(format) => Err(IndexifyAPIError::new(
+            StatusCode::BAD_REQUEST,
+            &format!("unsupported format {}", format),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn usage_rollup(
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<UsageParams>,
+) -> Result<Json<UsageRollupResponse>, IndexifyAPIError> {
+    let (from_day, to_day) = usage_range(&params)?;
+    let rollup = state
+        .data_manager
+        .usage_rollup(from_day, to_day)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(rollup))
+}
+
 #[utoipa::path(
     post,
     path = "/namespace/{namespace}/search",
@@ -1331,21 +2135,92 @@ async fn index_search(
     State(state): State<NamespaceEndpointState>,
     Json(query): Json<SearchRequest>,
 ) -> Result<Json<IndexSearchResponse>, IndexifyAPIError> {
-    let results = state
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let start = Instant::now();
+    let profile = match &query.profile {
+        Some(name) => Some(
+            state
+                .data_manager
+                .get_search_profile(&namespace, name)
+                .await
+                .map_err(IndexifyAPIError::internal_error)?
+                .ok_or_else(|| {
+                    IndexifyAPIError::not_found(&format!("search profile {} not found", name))
+                })?,
+        ),
+        None => None,
+    };
+    let search = search_profiles::resolve(&query, profile.as_ref(), DEFAULT_SEARCH_LIMIT)
+        .map_err(|e| IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()))?;
+    let scoring = query
+        .scoring
+        .as_deref()
+        .map(ScoringExpr::parse)
+        .transpose()
+        .map_err(|e| IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()))?;
+    //  Fetch more candidates when the scoring expression may reorder them
+    let fetch_k = match scoring {
+        Some(_) => search.k * search_scoring::SCORING_OVERFETCH_FACTOR,
+        None => search.k,
+    };
+    let mut results = state
         .data_manager
         .search(
             &namespace,
-            &query.index,
+            &search.index,
             &query.query,
-            query.k.unwrap_or(DEFAULT_SEARCH_LIMIT),
-            query.filters,
+            fetch_k,
+            search.filters.clone(),
             query.include_content.unwrap_or(true),
+            query.access_context().as_ref(),
+            query.no_cache,
         )
         .await
-        .map_err(IndexifyAPIError::internal_error)?;
+        .map_err(|e| match e.downcast_ref::<VectorIndexError>() {
+            Some(_) => IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()),
+            None => IndexifyAPIError::internal_error(e),
+        })?;
+    if let Some(min_score) = search.min_score {
+        results.retain(|r| r.confidence_score >= min_score);
+    }
+    let mut explanations = Vec::new();
+    if let Some(scoring) = &scoring {
+        let ranked =
+            search_scoring::rank(scoring, results, search.k as usize, utils::timestamp_secs());
+        results = Vec::with_capacity(ranked.len());
+        for (result, explanation) in ranked {
+            if query.explain.unwrap_or(false) {
+                explanations.push(explanation);
+            }
+            results.push(result);
+        }
+    }
+    if state.search_log.should_sample() {
+        let entry = SearchLogEntry::new(
+            &namespace,
+            &search.index,
+            &query.query,
+            search.k,
+            search.filters,
+            results
+                .iter()
+                .map(|r| SearchLogResult {
+                    content_id: r.content_id.clone(),
+                    score: r.confidence_score,
+                })
+                .collect(),
+            start.elapsed().as_millis() as u64,
+            state.search_log.store_query_text(),
+        );
+        if let Err(e) = state.search_log.record(entry) {
+            tracing::warn!("unable to record search log entry: {}", e);
+        }
+    }
+    let explain = query.explain.unwrap_or(false);
     let document_fragments: Vec<DocumentFragment> = results
         .iter()
-        .map(|text| DocumentFragment {
+        .enumerate()
+        .map(|(i, text)| DocumentFragment {
             content_id: text.content_id.clone(),
             mime_type: text.mime_type.clone(),
             text: text.text.clone(),
@@ -1353,6 +2228,16 @@ async fn index_search(
             confidence_score: text.confidence_score,
             root_content_metadata: text.root_content_metadata.clone().map(|r| r.into()),
             content_metadata: text.content_metadata.clone().into(),
+            score_explanation: explanations.get(i).cloned(),
+            matched_filters: if explain {
+                text.matched_filters
+                    .iter()
+                    .cloned()
+                    .map(Into::into)
+                    .collect()
+            } else {
+                Vec::new()
+            },
         })
         .collect();
     Ok(Json(IndexSearchResponse {
@@ -1360,12 +2245,329 @@ async fn index_search(
     }))
 }
 
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/search_profiles",
+    tag = "indexify",
+    request_body = CreateSearchProfile,
+    responses(
+        (status = 200, description = "Search profile created or replaced"),
+        (status = BAD_REQUEST, description = "The index doesn't exist or a filter is invalid")
+    ),
+)]
+#[axum::debug_handler]
+async fn create_search_profile(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<CreateSearchProfile>,
+) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    state
+        .data_manager
+        .create_search_profile(&namespace, payload)
+        .await
+        .map_err(|e| IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/search_feedback",
+    tag = "indexify",
+    request_body = CreateSearchFeedback,
+    responses(
+        (status = 200, description = "Feedback stored with its id and creation time", body = SearchFeedback),
+        (status = BAD_REQUEST, description = "Neither the query nor its hash was given")
+    ),
+)]
+#[axum::debug_handler]
+async fn create_search_feedback(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<CreateSearchFeedback>,
+) -> Result<Json<internal_api::SearchFeedback>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let feedback = state
+        .data_manager
+        .create_search_feedback(&namespace, payload)
+        .await
+        .map_err(|e| IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()))?;
+    Ok(Json(feedback))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    get,
+    path = "/namespaces/{namespace}/search_feedback",
+    tag = "indexify",
+    params(ListSearchFeedbackParams),
+    responses(
+        (status = 200, description = "A page of the search feedback of the namespace, oldest first", body = ListSearchFeedbackResponse),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to list search feedback")
+    ),
+)]
+#[axum::debug_handler]
+async fn list_search_feedback(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<ListSearchFeedbackParams>,
+) -> Result<Json<ListSearchFeedbackResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let response = state
+        .data_manager
+        .list_search_feedback(&namespace, params)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(response))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    get,
+    path = "/namespaces/{namespace}/search_profiles",
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Search profiles of the namespace", body = ListSearchProfilesResponse),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to list search profiles")
+    ),
+)]
+#[axum::debug_handler]
+async fn list_search_profiles(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<ListSearchProfilesResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let profiles = state
+        .data_manager
+        .list_search_profiles(&namespace)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(ListSearchProfilesResponse { profiles }))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    get,
+    path = "/namespaces/{namespace}/search_profiles/{name}",
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Search profile", body = SearchProfile),
+        (status = NOT_FOUND, description = "Search profile not found")
+    ),
+)]
+#[axum::debug_handler]
+async fn get_search_profile(
+    Path((namespace, name)): Path<(String, String)>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<SearchProfile>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let profile = state
+        .data_manager
+        .get_search_profile(&namespace, &name)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?
+        .ok_or_else(|| IndexifyAPIError::not_found("search profile not found"))?;
+    Ok(Json(profile))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    delete,
+    path = "/namespaces/{namespace}/search_profiles/{name}",
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Search profile deleted"),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to delete search profile")
+    ),
+)]
+#[axum::debug_handler]
+async fn delete_search_profile(
+    Path((namespace, name)): Path<(String, String)>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    state
+        .data_manager
+        .delete_search_profile(&namespace, &name)
+        .await
+        .map_err(IndexifyAPIError::internal_error)
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    post,
+    path = "/namespaces/{namespace}/subscriptions",
+    tag = "indexify",
+    request_body = CreateSubscriptionRequest,
+    responses(
+        (status = 200, description = "Subscription created, index events of the namespace are posted to its url", body = Subscription),
+        (status = BAD_REQUEST, description = "The url isn't an http or https url")
+    ),
+)]
+#[axum::debug_handler]
+async fn create_subscription(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<CreateSubscriptionRequest>,
+) -> Result<Json<Subscription>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let subscription = state
+        .data_manager
+        .create_subscription(&namespace, &payload.url)
+        .await
+        .map_err(|e| IndexifyAPIError::new(StatusCode::BAD_REQUEST, &e.to_string()))?;
+    Ok(Json(subscription))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    get,
+    path = "/namespaces/{namespace}/subscriptions",
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Subscriptions of the namespace", body = ListSubscriptionsResponse),
+        (status = INTERNAL_SERVER_ERROR, description = "Unable to list subscriptions")
+    ),
+)]
+#[axum::debug_handler]
+async fn list_subscriptions(
+    Path(namespace): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<ListSubscriptionsResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    let subscriptions = state
+        .data_manager
+        .list_subscriptions(&namespace)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(ListSubscriptionsResponse { subscriptions }))
+}
+
+#[tracing::instrument]
+#[utoipa::path(
+    delete,
+    path = "/namespaces/{namespace}/subscriptions/{subscription_id}",
+    tag = "indexify",
+    responses(
+        (status = 200, description = "Subscription deleted along with its pending and dead lettered deliveries"),
+        (status = NOT_FOUND, description = "Subscription not found")
+    ),
+)]
+#[axum::debug_handler]
+async fn delete_subscription(
+    Path((namespace, subscription_id)): Path<(String, String)>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<(), IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
+    state
+        .data_manager
+        .delete_subscription(&namespace, &subscription_id)
+        .await
+        .map_err(|e| IndexifyAPIError::not_found(&e.to_string()))
+}
+
+#[axum::debug_handler]
+async fn list_search_log(
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<SearchLogParams>,
+) -> Result<Json<SearchLogResponse>, IndexifyAPIError> {
+    let entries = state
+        .search_log
+        .recent(params.limit.unwrap_or(DEFAULT_SEARCH_LOG_LIMIT));
+    Ok(Json(SearchLogResponse { entries }))
+}
+
+#[axum::debug_handler]
+async fn replay_search_log(
+    State(state): State<NamespaceEndpointState>,
+    Query(params): Query<SearchLogParams>,
+) -> Result<Json<ReplaySearchLogResponse>, IndexifyAPIError> {
+    let entries = state
+        .search_log
+        .recent(params.limit.unwrap_or(DEFAULT_SEARCH_LOG_LIMIT));
+    let results = state.data_manager.replay_searches(entries).await;
+    Ok(Json(ReplaySearchLogResponse { results }))
+}
+
+#[axum::debug_handler]
+async fn namespace_vector_totals(
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<NamespaceVectorTotalsResponse>, IndexifyAPIError> {
+    let totals = state
+        .data_manager
+        .namespace_vector_totals()
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(totals))
+}
+
+#[axum::debug_handler]
+async fn flush_query_embedding_cache(
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<FlushQueryEmbeddingCacheResponse>, IndexifyAPIError> {
+    let flushed = state.data_manager.flush_query_embedding_cache();
+    Ok(Json(FlushQueryEmbeddingCacheResponse { flushed }))
+}
+
+#[axum::debug_handler]
+async fn list_dead_letters(
+    Path(subscription_id): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<ListDeadLettersResponse>, IndexifyAPIError> {
+    let dead_letters = state
+        .data_manager
+        .list_dead_letters(&subscription_id)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(ListDeadLettersResponse { dead_letters }))
+}
+
+#[axum::debug_handler]
+async fn requeue_dead_letters(
+    Path(subscription_id): Path<String>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<Json<RequeueDeadLettersResponse>, IndexifyAPIError> {
+    let requeued = state
+        .data_manager
+        .requeue_dead_letters(&subscription_id)
+        .await
+        .map_err(IndexifyAPIError::internal_error)?;
+    Ok(Json(RequeueDeadLettersResponse { requeued }))
+}
+
+#[axum::debug_handler]
+async fn reprocess_state_change(
+    Path(state_change_id): Path<u64>,
+    State(state): State<NamespaceEndpointState>,
+) -> Result<(), IndexifyAPIError> {
+    state
+        .data_manager
+        .reprocess_state_change(state_change_id)
+        .await
+        .map_err(IndexifyAPIError::internal_error)
+}
+
+#[axum::debug_handler]
+async fn discard_state_change(
+    Path(state_change_id): Path<u64>,
+    State(state): State<NamespaceEndpointState>,
+    Json(payload): Json<DiscardStateChangeRequest>,
+) -> Result<(), IndexifyAPIError> {
+    state
+        .data_manager
+        .discard_state_change(state_change_id, &payload.note)
+        .await
+        .map_err(IndexifyAPIError::internal_error)
+}
+
 #[axum::debug_handler]
 async fn run_sql_query(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
     Json(query): Json<SQLQuery>,
 ) -> Result<Json<SqlQueryResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let results = state
         .data_manager
         .query_content_source(&namespace, &query.query)
@@ -1399,6 +2601,7 @@ async fn list_schemas(
     Path(namespace): Path<String>,
     State(state): State<NamespaceEndpointState>,
 ) -> Result<Json<GetStructuredDataSchemasResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let results = state
         .coordinator_client
         .get()
@@ -1455,6 +2658,7 @@ async fn get_extracted_metadata(
     Path((namespace, content_id)): Path<(String, String)>,
     State(state): State<NamespaceEndpointState>,
 ) -> Result<Json<MetadataResponse>, IndexifyAPIError> {
+    let namespace = state.resolve_namespace(&namespace).await?;
     let extracted_metadata = state
         .data_manager
         .metadata_lookup(&namespace, &content_id)
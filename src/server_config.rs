@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fmt, fs,
     net::{AddrParseError, IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
@@ -64,6 +65,7 @@ pub enum IndexStoreKind {
     PgVector,
     //OpenSearchKnn,
     Lancedb,
+    InMemory,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,12 +99,20 @@ impl Default for QdrantConfig {
 #[serde(rename_all = "snake_case")]
 pub struct TurboClientConfig {
     pub api_key: String,
+    /// Number of embeddings written in a single upsert request
+    #[serde(default = "default_turbopuffer_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_turbopuffer_batch_size() -> usize {
+    256
 }
 
 impl Default for TurboClientConfig {
     fn default() -> Self {
         Self {
             api_key: "api-key".into(),
+            batch_size: default_turbopuffer_batch_size(),
         }
     }
 }
@@ -329,6 +339,11 @@ pub struct CoordinatorTls {
     pub cert_file: String,
     pub key_file: String,
     pub ca_file: Option<String>,
+    /// verify_executor_identity requires the executor id sent on
+    /// registration, heartbeats and task updates to match the CN or a SAN of
+    /// the client certificate. Only takes effect when ca_file is set.
+    #[serde(default)]
+    pub verify_executor_identity: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -385,12 +400,540 @@ pub struct ServerCacheConfig {
 pub struct StateStoreConfig {
     /// path is the path to the sled database.
     pub path: Option<String>,
+
+    /// content_version_separator separates the id and the version in the
+    /// keys of versioned content. The state store records the separator it
+    /// was created with and refuses to start with a different one.
+    #[serde(default = "default_content_version_separator")]
+    pub content_version_separator: String,
+
+    /// value_size_limits is the configuration for limiting the size of the
+    /// values read from the state store.
+    #[serde(default)]
+    pub value_size_limits: ValueSizeLimitsConfig,
+
+    /// multi_get_batch_size is the maximum number of keys read from the state
+    /// store in a single multi get, larger reads are split into sequential
+    /// batches. Batching is disabled when it is 0.
+    #[serde(default = "default_multi_get_batch_size")]
+    pub multi_get_batch_size: usize,
+
+    /// reverse_index_eviction bounds the memory used by the in-memory
+    /// reverse indexes of the state store. Every reverse index is kept
+    /// entirely in memory when it is not set.
+    #[serde(default)]
+    pub reverse_index_eviction: ReverseIndexEvictionConfig,
+}
+
+fn default_content_version_separator() -> String {
+    indexify_internal_api::DEFAULT_CONTENT_VERSION_SEPARATOR.to_string()
+}
+
+pub const DEFAULT_MULTI_GET_BATCH_SIZE: usize = 1024;
+
+fn default_multi_get_batch_size() -> usize {
+    DEFAULT_MULTI_GET_BATCH_SIZE
 }
 
 impl Default for StateStoreConfig {
     fn default() -> Self {
         Self {
             path: Some("/tmp/indexify/internal_state".to_string()),
+            content_version_separator: default_content_version_separator(),
+            value_size_limits: ValueSizeLimitsConfig::default(),
+            multi_get_batch_size: default_multi_get_batch_size(),
+            reverse_index_eviction: ReverseIndexEvictionConfig::default(),
+        }
+    }
+}
+
+/// ReverseIndexEvictionConfig selects the reverse indexes which keep only
+/// their most recently used entries in memory. Evicted entries are spilled to
+/// the state store and read back when they are used again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReverseIndexEvictionConfig {
+    /// content_children_table_capacity is the number of parent contents
+    /// whose children are kept in memory. The index is unbounded when it is
+    /// not set.
+    #[serde(default)]
+    pub content_children_table_capacity: Option<usize>,
+}
+
+/// ValueSizeLimitsConfig is a struct that contains the limits on the size of
+/// the values read from the state store. Values are checked before they are
+/// deserialized, and reads of larger values fail.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValueSizeLimitsConfig {
+    /// default_max_bytes is the limit of the column families which don't
+    /// have their own. There is no limit when it is not set.
+    #[serde(default)]
+    pub default_max_bytes: Option<usize>,
+
+    /// max_bytes is the limit of each column family, by column family name,
+    /// e.g. ContentTable or Namespaces.
+    #[serde(default)]
+    pub max_bytes: HashMap<String, usize>,
+}
+
+impl ValueSizeLimitsConfig {
+    pub fn limit(&self, column_family: &str) -> Option<usize> {
+        self.max_bytes
+            .get(column_family)
+            .copied()
+            .or(self.default_max_bytes)
+    }
+}
+
+/// SearchLogConfig is a struct that contains the configuration for sampled
+/// logging of search requests, used to replay queries when debugging
+/// relevance regressions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchLogConfig {
+    /// sample_rate is the fraction of search requests to log, between 0 and 1.
+    /// Logging is disabled when it is 0.
+    #[serde(default)]
+    pub sample_rate: f64,
+
+    /// path is the directory the search log files are written to.
+    pub path: String,
+
+    /// max_entries is the number of entries a log file holds before it is
+    /// rotated.
+    pub max_entries: usize,
+
+    /// store_query_text stores the raw query text next to its hash. Logged
+    /// queries can only be replayed when this is set.
+    #[serde(default)]
+    pub store_query_text: bool,
+}
+
+impl Default for SearchLogConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 0.0,
+            path: "/tmp/indexify/search_log".to_string(),
+            max_entries: 10_000,
+            store_query_text: false,
+        }
+    }
+}
+
+/// PolicyStatusConfig is a struct that contains the configuration for tracking
+/// whether extraction policies have executors available to run them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyStatusConfig {
+    /// debounce_secs is how long an extractor has to stay with or without
+    /// executors before the transition is recorded as a state change.
+    pub debounce_secs: u64,
+
+    /// max_pending_tasks_without_executors is the number of unfinished tasks
+    /// the scheduler creates for an extractor with no executors before it
+    /// stops creating more. There is no limit when it is not set.
+    #[serde(default)]
+    pub max_pending_tasks_without_executors: Option<u64>,
+}
+
+impl Default for PolicyStatusConfig {
+    fn default() -> Self {
+        Self {
+            debounce_secs: 30,
+            max_pending_tasks_without_executors: None,
+        }
+    }
+}
+
+/// ReverseIndexLimitsConfig is a struct that contains the limits on the size
+/// of the in-memory reverse indexes of the state machine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReverseIndexLimitsConfig {
+    /// soft_limit_entries is the number of entries in a reverse index above
+    /// which a warning is raised and content ingestion is throttled.
+    #[serde(default)]
+    pub soft_limit_entries: Option<usize>,
+
+    /// soft_limit_bytes is the estimated size in bytes of a reverse index
+    /// above which a warning is raised and content ingestion is throttled.
+    #[serde(default)]
+    pub soft_limit_bytes: Option<usize>,
+
+    /// unassigned_tasks_hard_limit is the number of unassigned tasks above
+    /// which no new tasks are created. The state changes which would create
+    /// tasks stay unprocessed until the number of unassigned tasks drops.
+    #[serde(default)]
+    pub unassigned_tasks_hard_limit: Option<usize>,
+}
+
+/// BlobUrlSigningConfig is a struct that contains the configuration for the
+/// signed urls executors download the content of tasks from, instead of
+/// downloading it through an ingestion server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobUrlSigningConfig {
+    /// key signs the urls of blobs stored on local disk, the coordinator and
+    /// the ingestion servers must use the same key. Blobs on S3 are presigned
+    /// with the AWS credentials of the coordinator instead.
+    pub key: String,
+
+    /// ttl_secs is how long signed urls are valid for. It should be longer
+    /// than tasks usually take, executors refresh the urls of longer tasks.
+    #[serde(default = "default_signed_url_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// blob_server_url is the url of an ingestion server, which serves the
+    /// blobs stored on local disk to the holders of signed urls.
+    pub blob_server_url: String,
+}
+
+fn default_signed_url_ttl_secs() -> u64 {
+    60 * 60
+}
+
+/// StateChangeAdminConfig is a struct that contains the configuration for the
+/// admin endpoints which reprocess or discard the state changes the scheduler
+/// fails to handle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateChangeAdminConfig {
+    /// api_key is the bearer token requests to the endpoints must carry.
+    pub api_key: String,
+}
+
+/// BlobScavengerConfig is a struct that contains the configuration for the
+/// job which finds blobs that are not referenced by any content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlobScavengerConfig {
+    /// enabled runs the job on the ingestion server.
+    pub enabled: bool,
+
+    /// delete removes the orphaned blobs. They are only reported when it is
+    /// not set.
+    pub delete: bool,
+
+    /// safety_window_secs is how old an unreferenced blob has to be before
+    /// it is considered orphaned, so that blobs whose content is still being
+    /// created are left alone.
+    pub safety_window_secs: u64,
+
+    /// page_size is the number of blobs listed and checked at a time.
+    pub page_size: usize,
+
+    /// max_blobs_per_sec is the rate at which blobs are checked.
+    pub max_blobs_per_sec: u64,
+
+    /// interval_secs is the time between two passes over blob storage.
+    pub interval_secs: u64,
+
+    /// cursor_path is the file where the position of the current pass is
+    /// saved, so that a restarted server resumes from it.
+    pub cursor_path: String,
+}
+
+impl Default for BlobScavengerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delete: false,
+            safety_window_secs: 24 * 60 * 60,
+            page_size: 1000,
+            max_blobs_per_sec: 100,
+            interval_secs: 60 * 60,
+            cursor_path: "/tmp/indexify/blob_scavenger_cursor".to_string(),
+        }
+    }
+}
+
+/// SyncExtractionConfig is a struct that contains the configuration for
+/// synchronous extraction of small texts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncExtractionConfig {
+    /// max_content_size_bytes is the size of the largest text which is
+    /// extracted synchronously.
+    pub max_content_size_bytes: usize,
+
+    /// deadline_ms is how long a synchronous request waits for the executors
+    /// to extract the texts before falling back to the asynchronous
+    /// pipeline.
+    pub deadline_ms: u64,
+}
+
+impl Default for SyncExtractionConfig {
+    fn default() -> Self {
+        Self {
+            max_content_size_bytes: 64 * 1024,
+            deadline_ms: 5000,
+        }
+    }
+}
+
+/// AclConfig is a struct that contains the configuration for the access
+/// checks of searches and content reads made on behalf of a user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AclConfig {
+    /// allow_unlabeled lets users read content which was ingested without an
+    /// ACL. Such content is hidden from them when it is not set.
+    pub allow_unlabeled: bool,
+}
+
+impl Default for AclConfig {
+    fn default() -> Self {
+        Self {
+            allow_unlabeled: true,
+        }
+    }
+}
+
+/// IndexEventsConfig is a struct that contains the configuration for the
+/// events published when the contents of an index change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexEventsConfig {
+    /// coalesce_interval_ms is the minimum time between two events for
+    /// vectors added to the same index. Additions within the interval are
+    /// published together.
+    pub coalesce_interval_ms: u64,
+
+    /// channel_capacity is the number of events buffered for a subscriber
+    /// which falls behind.
+    pub channel_capacity: usize,
+}
+
+impl Default for IndexEventsConfig {
+    fn default() -> Self {
+        Self {
+            coalesce_interval_ms: 1000,
+            channel_capacity: 1000,
+        }
+    }
+}
+
+/// DeliveryConfig is a struct that contains the configuration for the
+/// delivery of index events to the webhooks of subscriptions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeliveryConfig {
+    /// interval_ms is the time between two passes of the delivery worker
+    /// over the pending deliveries.
+    pub interval_ms: u64,
+
+    /// max_per_second_per_endpoint is the number of deliveries made to an
+    /// endpoint, the scheme, host and port of a webhook url, per second.
+    pub max_per_second_per_endpoint: u32,
+
+    /// initial_backoff_secs is the time before a failed delivery is
+    /// retried, it doubles with every further failed attempt.
+    pub initial_backoff_secs: u64,
+
+    /// max_backoff_secs caps the time between two attempts of a delivery.
+    pub max_backoff_secs: u64,
+
+    /// max_attempts is the number of attempts after which a delivery is
+    /// moved to the dead letters.
+    pub max_attempts: u32,
+
+    /// max_age_secs is the time after being enqueued after which a delivery
+    /// which still fails is moved to the dead letters.
+    pub max_age_secs: u64,
+
+    /// timeout_ms is the time a webhook has to answer a delivery.
+    pub timeout_ms: u64,
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 1000,
+            max_per_second_per_endpoint: 10,
+            initial_backoff_secs: 1,
+            max_backoff_secs: 10 * 60,
+            max_attempts: 20,
+            max_age_secs: 24 * 60 * 60,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+/// ReadinessConfig is a struct that contains the configuration for the
+/// readiness checks of the coordinator after it starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadinessConfig {
+    /// replay_timeout_secs is the time the coordinator waits for the log it
+    /// had when it started to be applied. Entries which were never committed
+    /// are dropped instead of applied, the coordinator goes on with the spot
+    /// check after the timeout.
+    pub replay_timeout_secs: u64,
+
+    /// spot_check_sample_size is the number of rows of each forward index
+    /// checked against the reverse indexes before the coordinator is ready.
+    pub spot_check_sample_size: usize,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            replay_timeout_secs: 10 * 60,
+            spot_check_sample_size: 100,
+        }
+    }
+}
+
+/// RetentionConfig is a struct that contains the configuration for the sweep
+/// expiring content by the retention rules of the namespaces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// sweep_interval_secs is the time between two sweeps of the leader.
+    pub sweep_interval_secs: u64,
+
+    /// batch_size is the maximum number of content trees tombstoned by a
+    /// single request.
+    pub batch_size: usize,
+
+    /// grace_period_secs is the age under which root content never expires,
+    /// so content which was just ingested isn't expired by the count rule
+    /// before it is extracted.
+    pub grace_period_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            sweep_interval_secs: 5 * 60,
+            batch_size: 100,
+            grace_period_secs: 60 * 60,
+        }
+    }
+}
+
+/// UrlFetchConfig is a struct that contains the configuration for the
+/// ingestion of content the server fetches from urls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UrlFetchConfig {
+    /// max_size_bytes is the largest response body which is ingested, larger
+    /// ones fail the fetch of their url.
+    pub max_size_bytes: u64,
+
+    /// timeout_secs is the time a url has to be fetched in, redirects
+    /// included.
+    pub timeout_secs: u64,
+
+    /// max_redirects is the number of redirects followed for a url.
+    pub max_redirects: usize,
+
+    /// allowed_schemes are the url schemes which can be fetched.
+    pub allowed_schemes: Vec<String>,
+
+    /// allowed_hosts are the only hosts which can be fetched from when not
+    /// empty.
+    pub allowed_hosts: Vec<String>,
+
+    /// denied_hosts are hosts which can't be fetched from.
+    pub denied_hosts: Vec<String>,
+
+    /// allow_private_addresses allows fetching from hosts which resolve to
+    /// loopback, private, link local or otherwise non public addresses.
+    pub allow_private_addresses: bool,
+}
+
+impl Default for UrlFetchConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: 100 * 1024 * 1024,
+            timeout_secs: 60,
+            max_redirects: 5,
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            allowed_hosts: vec![],
+            denied_hosts: vec![],
+            allow_private_addresses: false,
+        }
+    }
+}
+
+/// QueryEmbeddingCacheConfig is a struct that contains the configuration for
+/// the cache of the embeddings of search queries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryEmbeddingCacheConfig {
+    /// max_entries is the number of query embeddings cached, the least
+    /// recently used ones are evicted. The cache is disabled when it is 0.
+    pub max_entries: usize,
+
+    /// ttl_secs is how long the embedding of a query is used after it was
+    /// computed.
+    pub ttl_secs: u64,
+
+    /// lowercase makes queries which only differ in case share an
+    /// embedding.
+    pub lowercase: bool,
+}
+
+impl Default for QueryEmbeddingCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            ttl_secs: 600,
+            lowercase: false,
+        }
+    }
+}
+
+/// LanguageDetectionConfig is a struct that contains the configuration for
+/// the detection of the language of ingested text content, which is written
+/// to the `language` label as an ISO 639-3 code, e.g. `eng` or `deu`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageDetectionConfig {
+    /// enabled turns on language detection for ingested text content.
+    pub enabled: bool,
+
+    /// sample_bytes is the number of bytes from the start of the content the
+    /// language is detected from.
+    pub sample_bytes: usize,
+
+    /// skip_namespaces are the namespaces whose content isn't labeled with
+    /// its language.
+    pub skip_namespaces: Vec<String>,
+}
+
+impl Default for LanguageDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_bytes: 16 * 1024,
+            skip_namespaces: vec![],
+        }
+    }
+}
+
+/// GcVectorDeletesConfig is a struct that contains the configuration for the
+/// removal of vectors by the garbage collection tasks of ingestion servers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GcVectorDeletesConfig {
+    /// max_tasks_per_batch is the number of garbage collection tasks whose
+    /// vectors are removed together, with one delete per index.
+    pub max_tasks_per_batch: usize,
+
+    /// max_concurrent_indexes is the number of indexes vectors are removed
+    /// from at the same time.
+    pub max_concurrent_indexes: usize,
+
+    /// max_attempts is the number of times the removal of vectors from an
+    /// index is tried before the tasks are left to be resumed.
+    pub max_attempts: u32,
+
+    /// retry_delay_ms is the delay before the first retry, it doubles with
+    /// every attempt.
+    pub retry_delay_ms: u64,
+}
+
+impl Default for GcVectorDeletesConfig {
+    fn default() -> Self {
+        Self {
+            max_tasks_per_batch: 256,
+            max_concurrent_indexes: 8,
+            max_attempts: 3,
+            retry_delay_ms: 100,
         }
     }
 }
@@ -445,6 +988,93 @@ pub struct ServerConfig {
     pub cache: ServerCacheConfig,
     #[serde(default)]
     pub state_store: StateStoreConfig,
+    /// search_log is the configuration for sampled search request logging.
+    #[serde(default)]
+    pub search_log: SearchLogConfig,
+    /// repair_dangling_indexes removes reverse index entries which point at
+    /// records that no longer exist when they are found on reads.
+    #[serde(default)]
+    pub repair_dangling_indexes: bool,
+    /// policy_status is the configuration for extraction policy health
+    /// tracking.
+    #[serde(default)]
+    pub policy_status: PolicyStatusConfig,
+    /// sync_extraction is the configuration for synchronous extraction of
+    /// texts.
+    #[serde(default)]
+    pub sync_extraction: SyncExtractionConfig,
+    /// acl is the configuration for the access checks of requests made on
+    /// behalf of users.
+    #[serde(default)]
+    pub acl: AclConfig,
+    /// index_events is the configuration for the events published when the
+    /// contents of an index change.
+    #[serde(default)]
+    pub index_events: IndexEventsConfig,
+    /// reverse_index_limits is the configuration for limiting the size of
+    /// the in-memory reverse indexes.
+    #[serde(default)]
+    pub reverse_index_limits: ReverseIndexLimitsConfig,
+    /// blob_scavenger is the configuration for the detection of blobs which
+    /// are not referenced by any content.
+    #[serde(default)]
+    pub blob_scavenger: BlobScavengerConfig,
+    /// embed_task_input_params copies the input params of the extraction
+    /// policy into every task, for executors which don't fetch the policy
+    /// version referenced by the task.
+    #[serde(default)]
+    pub embed_task_input_params: bool,
+    /// ingestion_idempotency_key_ttl_secs is how long the content created by
+    /// an ingestion request with an Idempotency-Key header is returned for
+    /// requests with the same key, instead of creating content again.
+    #[serde(default = "default_ingestion_idempotency_key_ttl_secs")]
+    pub ingestion_idempotency_key_ttl_secs: u64,
+    /// blob_url_signing attaches signed urls of their content to the tasks
+    /// of executors. Executors download content through an ingestion server
+    /// when it is not set.
+    #[serde(default)]
+    pub blob_url_signing: Option<BlobUrlSigningConfig>,
+    /// query_embedding_cache is the configuration for the cache of the
+    /// embeddings of search queries.
+    #[serde(default)]
+    pub query_embedding_cache: QueryEmbeddingCacheConfig,
+    /// gc_vector_deletes is the configuration for the removal of vectors by
+    /// garbage collection tasks.
+    #[serde(default)]
+    pub gc_vector_deletes: GcVectorDeletesConfig,
+    /// language_detection is the configuration for labeling ingested text
+    /// content with its language.
+    #[serde(default)]
+    pub language_detection: LanguageDetectionConfig,
+    /// delivery is the configuration for the delivery of index events to
+    /// the webhooks of subscriptions.
+    #[serde(default)]
+    pub delivery: DeliveryConfig,
+
+    /// readiness is the configuration for the readiness checks of the
+    /// coordinator after it starts.
+    #[serde(default)]
+    pub readiness: ReadinessConfig,
+
+    /// retention is the configuration for the sweep expiring content by the
+    /// retention rules of the namespaces.
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// url_fetch is the configuration for the ingestion of content fetched
+    /// from urls by the server.
+    #[serde(default)]
+    pub url_fetch: UrlFetchConfig,
+    /// state_change_admin enables the admin endpoints which reprocess or
+    /// discard state changes. The scheduler then skips the state changes it
+    /// failed to handle too many times, until they are reprocessed or
+    /// discarded. When it is not set the endpoints aren't served and failing
+    /// state changes are retried on every run of the scheduler.
+    #[serde(default)]
+    pub state_change_admin: Option<StateChangeAdminConfig>,
+}
+
+fn default_ingestion_idempotency_key_ttl_secs() -> u64 {
+    24 * 60 * 60
 }
 
 impl Default for ServerConfig {
@@ -471,6 +1101,25 @@ impl Default for ServerConfig {
             node_id: 0,
             cache: ServerCacheConfig::default(),
             state_store: StateStoreConfig::default(),
+            search_log: SearchLogConfig::default(),
+            repair_dangling_indexes: false,
+            policy_status: PolicyStatusConfig::default(),
+            sync_extraction: SyncExtractionConfig::default(),
+            acl: AclConfig::default(),
+            index_events: IndexEventsConfig::default(),
+            reverse_index_limits: ReverseIndexLimitsConfig::default(),
+            blob_scavenger: BlobScavengerConfig::default(),
+            embed_task_input_params: false,
+            ingestion_idempotency_key_ttl_secs: default_ingestion_idempotency_key_ttl_secs(),
+            blob_url_signing: None,
+            query_embedding_cache: QueryEmbeddingCacheConfig::default(),
+            gc_vector_deletes: GcVectorDeletesConfig::default(),
+            language_detection: LanguageDetectionConfig::default(),
+            delivery: DeliveryConfig::default(),
+            readiness: ReadinessConfig::default(),
+            retention: RetentionConfig::default(),
+            url_fetch: UrlFetchConfig::default(),
+            state_change_admin: None,
         }
     }
 }
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentEvent {
+    /// Only the labels of the content tree changed. Subscribers interested in
+    /// the content itself keep following the ContentUpdated state changes.
+    MetadataChanged {
+        content_id: String,
+        changed_keys: Vec<String>,
+    },
+}
+
+/// Publishes lightweight events about content, once the write they describe
+/// was committed
+pub struct ContentEvents {
+    events_tx: broadcast::Sender<ContentEvent>,
+}
+
+impl ContentEvents {
+    pub fn new() -> Self {
+        let (events_tx, _) = broadcast::channel(100);
+        Self { events_tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ContentEvent> {
+        self.events_tx.subscribe()
+    }
+
+    /// Publishes the labels of `updates` which differ from `labels`, nothing
+    /// when every label already had its value
+    pub fn labels_updated(
+        &self,
+        content_id: &str,
+        labels: &HashMap<String, String>,
+        updates: &HashMap<String, String>,
+    ) {
+        let mut changed_keys: Vec<String> = updates
+            .iter()
+            .filter(|(key, value)| labels.get(*key) != Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        if changed_keys.is_empty() {
+            return;
+        }
+        changed_keys.sort();
+        //  Sending only fails when there are no subscribers
+        let _ = self.events_tx.send(ContentEvent::MetadataChanged {
+            content_id: content_id.to_string(),
+            changed_keys,
+        });
+    }
+}
+
+impl Default for ContentEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
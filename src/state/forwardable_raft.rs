@@ -50,9 +50,13 @@ impl ForwardableRaft {
             return self.network.forward(&leader_address.addr, request).await;
         }
 
-        self.raft.client_write(request).await?;
+        let resp = self.raft.client_write(request).await?;
+        if let Some(err) = resp.data.error {
+            return Err(anyhow::anyhow!(err));
+        }
         let response = StateMachineUpdateResponse {
             handled_by: self.id,
+            written_content: resp.data.written_content,
         };
         Ok(response)
     }
@@ -4,13 +4,22 @@ use async_trait::async_trait;
 use indexify_proto::indexify_raft::{raft_api_server::RaftApi, RaftReply, RaftRequest};
 use openraft::{
     error::{CheckIsLeaderError, ForwardToLeader, RaftError},
+    raft::InstallSnapshotRequest,
     BasicNode,
 };
 use requests::{RequestPayload, StateMachineUpdateRequest, StateMachineUpdateResponse};
 use tonic::{Request, Status};
 use tracing::info;
 
-use super::{raft_client::RaftClient, NodeId};
+use super::{
+    raft_client::RaftClient,
+    store::{
+        snapshot_chunks::{ChunkedSnapshot, InstallSnapshotChunksRequest},
+        StateMachineStore,
+    },
+    NodeId,
+    TypeConfig,
+};
 use crate::{
     grpc_helper::GrpcHelper,
     metrics::{raft_metrics, CounterGuard},
@@ -21,6 +30,7 @@ pub struct RaftGrpcServer {
     id: NodeId,
     raft: Arc<Raft>,
     raft_client: Arc<RaftClient>,
+    state_machine: Arc<StateMachineStore>,
     address: String,
     coordinator_address: String,
 }
@@ -30,6 +40,7 @@ impl RaftGrpcServer {
         id: NodeId,
         raft: Arc<Raft>,
         raft_client: Arc<RaftClient>,
+        state_machine: Arc<StateMachineStore>,
         address: String,
         coordinator_addr: String,
     ) -> Self {
@@ -37,6 +48,7 @@ impl RaftGrpcServer {
             id,
             raft,
             raft_client,
+            state_machine,
             address,
             coordinator_address: coordinator_addr,
         }
@@ -78,6 +90,7 @@ impl RaftGrpcServer {
         if nodes_in_cluster.contains_key(&node_id) {
             let response = StateMachineUpdateResponse {
                 handled_by: self.id,
+                written_content: vec![],
             };
             return GrpcHelper::ok_response(response);
         }
@@ -114,6 +127,7 @@ impl RaftGrpcServer {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.raft
             .client_write(state_machine_req)
@@ -130,6 +144,7 @@ impl RaftGrpcServer {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.raft
             .client_write(state_machine_req)
@@ -140,6 +155,7 @@ impl RaftGrpcServer {
 
         let response = StateMachineUpdateResponse {
             handled_by: self.id,
+            written_content: vec![],
         };
         GrpcHelper::ok_response(response)
     }
@@ -158,15 +174,45 @@ impl RaftGrpcServer {
         &self,
         request: StateMachineUpdateRequest,
     ) -> Result<tonic::Response<RaftReply>, Status> {
-        let response = StateMachineUpdateResponse {
-            handled_by: self.id,
-        };
-        self.raft
+        let resp = self
+            .raft
             .client_write(request)
             .await
             .map_err(|e| GrpcHelper::internal_err(e.to_string()))?;
+        if let Some(err) = resp.data.error {
+            return Err(GrpcHelper::internal_err(err));
+        }
+        let response = StateMachineUpdateResponse {
+            handled_by: self.id,
+            written_content: resp.data.written_content,
+        };
         GrpcHelper::ok_response(response)
     }
+
+    async fn handle_install_snapshot(
+        &self,
+        remote_addr: &str,
+        is_req: InstallSnapshotRequest<TypeConfig>,
+    ) -> Result<tonic::Response<RaftReply>, Status> {
+        let snapshot_size = is_req.data.len() as u64;
+        let resp = self.raft.install_snapshot(is_req).await.map_err(|e| {
+            raft_metrics::network::incr_snapshot_recv_failure(remote_addr);
+            GrpcHelper::internal_err(e.to_string())
+        });
+
+        if resp.is_ok() {
+            raft_metrics::network::incr_snapshot_recv_success(remote_addr);
+            raft_metrics::network::add_snapshot_size(snapshot_size);
+            raft_metrics::network::set_last_snapshot_creation_time(std::time::Instant::now());
+        } else {
+            raft_metrics::network::incr_snapshot_recv_failure(remote_addr);
+        }
+
+        match resp {
+            Ok(resp) => GrpcHelper::ok_response(resp),
+            Err(e) => Err(e),
+        }
+    }
 }
 
 #[async_trait]
@@ -228,26 +274,64 @@ impl RaftApi for RaftGrpcServer {
         self.incr_recv_bytes(&request);
 
         let remote_addr = self.get_request_addr(&request);
-        let is_req: openraft::raft::InstallSnapshotRequest<super::TypeConfig> =
-            GrpcHelper::parse_req(request)?;
-        let snapshot_size = is_req.data.len() as u64;
-        let resp = self.raft.install_snapshot(is_req).await.map_err(|e| {
-            raft_metrics::network::incr_snapshot_recv_failure(&remote_addr);
-            GrpcHelper::internal_err(e.to_string())
-        });
+        let is_req: InstallSnapshotRequest<TypeConfig> = GrpcHelper::parse_req(request)?;
+        self.handle_install_snapshot(&remote_addr, is_req).await
+    }
 
-        if resp.is_ok() {
-            raft_metrics::network::incr_snapshot_recv_success(&remote_addr);
-            raft_metrics::network::add_snapshot_size(snapshot_size);
-            raft_metrics::network::set_last_snapshot_creation_time(std::time::Instant::now());
-        } else {
-            raft_metrics::network::incr_snapshot_recv_failure(&remote_addr);
-        }
+    async fn get_snapshot_chunks(
+        &self,
+        request: Request<RaftRequest>,
+    ) -> Result<tonic::Response<RaftReply>, Status> {
+        self.incr_recv_bytes(&request);
 
-        match resp {
-            Ok(resp) => GrpcHelper::ok_response(resp),
-            Err(e) => Err(e),
-        }
+        let manifest = self
+            .state_machine
+            .current_chunked_snapshot()
+            .map_err(|e| GrpcHelper::internal_err(e.to_string()))?
+            .map(|(_, snapshot)| snapshot.manifest);
+        GrpcHelper::ok_response(manifest)
+    }
+
+    async fn install_snapshot_chunks(
+        &self,
+        request: Request<RaftRequest>,
+    ) -> Result<tonic::Response<RaftReply>, Status> {
+        let request_addr = self.get_request_addr(&request);
+        let _guard_inflight = {
+            CounterGuard::new(&request_addr, move |addr, cnt| {
+                raft_metrics::network::incr_snapshot_recv_inflight(addr, cnt);
+            })
+        };
+        self.incr_recv_bytes(&request);
+
+        let remote_addr = self.get_request_addr(&request);
+        let req: InstallSnapshotChunksRequest = GrpcHelper::parse_req(request)?;
+
+        //  The current snapshot may have been replaced since the leader asked
+        // for its manifest, in which case the leader falls back to sending the
+        // full snapshot
+        let local = self
+            .state_machine
+            .current_chunked_snapshot()
+            .map_err(|e| GrpcHelper::internal_err(e.to_string()))?;
+        let snapshot = ChunkedSnapshot::assemble(
+            req.manifest,
+            &req.chunks,
+            local.as_ref().map(|(_, snapshot)| snapshot),
+        )
+        .map_err(GrpcHelper::invalid_arg)?;
+        let data = snapshot
+            .encode()
+            .map_err(|e| GrpcHelper::internal_err(e.to_string()))?;
+
+        let is_req = InstallSnapshotRequest {
+            vote: req.vote,
+            meta: req.meta,
+            offset: 0,
+            data,
+            done: true,
+        };
+        self.handle_install_snapshot(&remote_addr, is_req).await
     }
 
     async fn vote(
@@ -303,6 +387,7 @@ impl RaftApi for RaftGrpcServer {
                 },
                 new_state_changes: vec![],
                 state_changes_processed: vec![],
+                idempotency_key: None,
             })
             .map_err(|e| GrpcHelper::internal_err(e.to_string()))?;
 
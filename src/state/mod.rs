@@ -7,7 +7,7 @@ use std::{
     io::Cursor,
     path::Path,
     sync::Arc,
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
 use anyhow::{anyhow, Result};
@@ -19,6 +19,7 @@ use indexify_proto::{
 };
 use internal_api::{
     ContentMetadataId,
+    ContentVersionSeparator,
     ExtractionGraph,
     ExtractionPolicy,
     StateChange,
@@ -52,26 +53,44 @@ use tokio::{
 use tracing::{error, info, warn};
 
 use self::{
+    content_events::ContentEvents,
     forwardable_raft::ForwardableRaft,
-    store::{requests::CreateOrUpdateContentEntry, StateMachineColumns, StateMachineStore},
+    readiness::Readiness,
+    reverse_index_limits::ReverseIndexLimits,
+    store::{
+        requests::CreateOrUpdateContentEntry,
+        state_machine_objects::{DeliveryQueueStats, ExtractionPoliciesLookup, IngestionKeyRecord},
+        StateMachineColumns,
+        StateMachineStore,
+    },
 };
 use crate::{
-    coordinator_filters::matches_mime_type,
+    blob_storage::signing::BlobUrlSigner,
+    coordinator_filters::{matches_mime_type, matches_policy_label},
     garbage_collector::GarbageCollector,
     metrics::{
         coordinator::Metrics,
         raft_metrics::{self, network::MetricsSnapshot},
     },
-    server_config::ServerConfig,
+    server_config::{
+        DeliveryConfig,
+        PolicyStatusConfig,
+        ReadinessConfig,
+        RetentionConfig,
+        ServerConfig,
+    },
     state::{grpc_config::GrpcConfig, raft_client::RaftClient, store::new_storage},
     utils::timestamp_secs,
 };
 
+pub mod content_events;
 pub mod forwardable_raft;
 pub mod grpc_config;
 pub mod grpc_server;
 pub mod network;
 pub mod raft_client;
+pub mod readiness;
+pub mod reverse_index_limits;
 pub mod store;
 
 pub type NodeId = u64;
@@ -140,6 +159,26 @@ pub struct App {
     pub garbage_collector: Arc<GarbageCollector>,
     pub registry: Arc<prometheus::Registry>,
     pub metrics: Metrics,
+    /// Remove reverse index entries that point at missing records when they
+    /// are encountered on reads
+    repair_dangling_indexes: bool,
+    pub policy_status_config: PolicyStatusConfig,
+    pub reverse_index_limits: ReverseIndexLimits,
+    /// Copy the input params of the extraction policy into new tasks instead
+    /// of only referencing the policy version
+    pub embed_task_input_params: bool,
+    /// How long ingestion requests with an idempotency key are remembered
+    pub ingestion_key_ttl: Duration,
+    /// Signs the content urls attached to tasks, None when disabled
+    pub blob_url_signer: Option<BlobUrlSigner>,
+    pub delivery_config: DeliveryConfig,
+    pub readiness: Arc<Readiness>,
+    readiness_config: ReadinessConfig,
+    pub retention_config: RetentionConfig,
+    pub content_events: ContentEvents,
+    /// Whether operators can reprocess or discard state changes, only then
+    /// does the scheduler skip the state changes it keeps failing to handle
+    pub state_change_admin: bool,
 }
 
 #[derive(Clone)]
@@ -148,6 +187,14 @@ pub struct RaftConfigOverrides {
     max_in_snapshot_log_to_keep: Option<u64>,
 }
 
+/// Result of writing a batch of content
+#[derive(Debug, Default)]
+pub struct CreatedContent {
+    pub statuses: Vec<CreateContentStatus>,
+    /// Ids and versions of the content written to the state machine
+    pub written: Vec<ContentMetadataId>,
+}
+
 fn add_update_entry(
     update_entries: &mut Vec<CreateOrUpdateContentEntry>,
     state_changes: &mut Vec<StateChange>,
@@ -176,6 +223,9 @@ impl App {
         coordinator_addr: &str,
         registry: Arc<prometheus::Registry>,
     ) -> Result<Arc<Self>> {
+        let content_version_separator =
+            ContentVersionSeparator::new(&server_config.state_store.content_version_separator)?;
+
         let mut raft_config = openraft::Config {
             heartbeat_interval: 500,
             election_timeout_min: 1500,
@@ -212,11 +262,24 @@ impl App {
         let db_path: &Path = Path::new(&db_path_str);
         let sm_blob_store_path: &Path = Path::new(&sm_blob_store_path_str);
 
-        let (log_store, state_machine) = new_storage(db_path, sm_blob_store_path).await;
+        let (log_store, state_machine) = new_storage(
+            db_path,
+            sm_blob_store_path,
+            server_config.state_store.value_size_limits.clone(),
+            server_config.state_store.multi_get_batch_size,
+            content_version_separator,
+        )
+        .await;
+        state_machine.bound_reverse_indexes(&server_config.state_store.reverse_index_eviction);
+        //  Migrations run before the raft node serves anything, a failed
+        //  migration aborts startup
+        state_machine
+            .run_migrations()
+            .map_err(|e| anyhow!("unable to migrate the state store: {}", e))?;
         let state_change_rx = state_machine.state_change_rx.clone();
 
         let raft_client = Arc::new(RaftClient::new());
-        let network = Network::new(Arc::clone(&raft_client));
+        let network = Network::new(Arc::clone(&raft_client), Some(Arc::clone(&state_machine)));
 
         let raft = openraft::Raft::new(
             server_config.node_id,
@@ -249,6 +312,7 @@ impl App {
             server_config.node_id,
             Arc::new(raft.clone()),
             Arc::clone(&raft_client),
+            Arc::clone(&state_machine),
             addr.to_string(),
             server_config.coordinator_addr.clone(),
         ))
@@ -280,8 +344,29 @@ impl App {
             garbage_collector,
             registry,
             metrics,
+            repair_dangling_indexes: server_config.repair_dangling_indexes,
+            policy_status_config: server_config.policy_status.clone(),
+            reverse_index_limits: ReverseIndexLimits::new(
+                server_config.reverse_index_limits.clone(),
+            ),
+            embed_task_input_params: server_config.embed_task_input_params,
+            ingestion_key_ttl: Duration::from_secs(
+                server_config.ingestion_idempotency_key_ttl_secs,
+            ),
+            blob_url_signer: BlobUrlSigner::from_server_config(&server_config),
+            delivery_config: server_config.delivery.clone(),
+            readiness: Arc::new(Readiness::new()),
+            readiness_config: server_config.readiness.clone(),
+            retention_config: server_config.retention.clone(),
+            content_events: ContentEvents::new(),
+            state_change_admin: server_config.state_change_admin.is_some(),
         });
 
+        //  Entries in the log when the node starts are replayed before it
+        //  serves requests
+        let replay_target = app.forwardable_raft.raft.metrics().borrow().last_log_index;
+        app.track_replay(replay_target);
+
         let raft_clone = app.forwardable_raft.clone();
 
         let mut rx = app.shutdown_rx.clone();
@@ -371,6 +456,48 @@ impl App {
         Ok(())
     }
 
+    /// Marks the state machine ready once the log is applied up to the
+    /// `target` index and the reverse indexes pass the spot check
+    pub fn track_replay(&self, target: Option<u64>) {
+        self.readiness.replay_started();
+        let readiness = self.readiness.clone();
+        let state_machine = self.state_machine.clone();
+        let config = self.readiness_config.clone();
+        let mut metrics_rx = self.forwardable_raft.raft.metrics();
+        let mut shutdown_rx = self.shutdown_rx.clone();
+        tokio::spawn(async move {
+            let replayed = async {
+                loop {
+                    let last_applied = metrics_rx
+                        .borrow_and_update()
+                        .last_applied
+                        .map(|log_id| log_id.index);
+                    if last_applied >= target || metrics_rx.changed().await.is_err() {
+                        return;
+                    }
+                }
+            };
+            let replay_timeout = Duration::from_secs(config.replay_timeout_secs);
+            tokio::select! {
+                _ = shutdown_rx.changed() => return,
+                result = tokio::time::timeout(replay_timeout, replayed) => {
+                    if result.is_err() {
+                        warn!(
+                            "log wasn't applied up to index {:?} within {:?}, checking the reverse indexes",
+                            target, replay_timeout
+                        );
+                    }
+                }
+            }
+            readiness.replay_finished();
+            match state_machine.spot_check_reverse_indexes(config.spot_check_sample_size) {
+                Ok(None) => readiness.check_passed(),
+                Ok(Some(reason)) => readiness.check_failed(&reason),
+                Err(e) => readiness.check_failed(&e.to_string()),
+            }
+        });
+    }
+
     pub async fn unprocessed_state_change_events(&self) -> Result<Vec<StateChange>> {
         let mut state_changes = vec![];
         let ids = self.state_machine.get_unprocessed_state_changes().await;
@@ -403,11 +530,52 @@ impl App {
             payload: RequestPayload::MarkStateChangesProcessed { state_changes },
             new_state_changes,
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         let _resp = self.forwardable_raft.client_write(req).await?;
         Ok(())
     }
 
+    fn get_state_change(&self, state_change_id: StateChangeId) -> Result<StateChange> {
+        self.state_machine
+            .get_from_cf::<StateChange, _>(
+                StateMachineColumns::StateChanges,
+                &state_change_id.to_key(),
+            )?
+            .ok_or_else(|| anyhow!("state change {} not found", state_change_id))
+    }
+
+    /// Marks a state change unprocessed again. The change is published once
+    /// the write is committed, which wakes up the scheduler.
+    pub async fn reprocess_state_change(&self, state_change_id: StateChangeId) -> Result<()> {
+        self.get_state_change(state_change_id)?;
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::ReprocessStateChange { state_change_id },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    /// Marks a state change processed without handling it, with the note of
+    /// the operator logged
+    pub async fn discard_state_change(
+        &self,
+        state_change_id: StateChangeId,
+        note: &str,
+    ) -> Result<()> {
+        let state_change = self.get_state_change(state_change_id)?;
+        self.mark_change_events_as_processed(vec![state_change.clone()], Vec::new())
+            .await?;
+        warn!(
+            "discarded state change {}, type: {}, object: {}: {}",
+            state_change.id, state_change.change_type, state_change.object_id, note
+        );
+        Ok(())
+    }
+
     /// This method uses the content id to fetch the associated extraction
     /// policies based on certain filters and checks which policies can be
     /// applied to the content It's the mirror equivalent to
@@ -438,12 +606,11 @@ impl App {
             if content_metadata.source.to_string() != extraction_policy.content_source.to_string() {
                 continue;
             }
-            if !extraction_policy.filters.iter().all(|(name, value)| {
-                content_metadata
-                    .labels
-                    .get(name)
-                    .map_or(false, |v| v == value)
-            }) {
+            if !extraction_policy
+                .filters
+                .iter()
+                .all(|(name, value)| matches_policy_label(value, content_metadata.labels.get(name)))
+            {
                 continue;
             }
             let extractor = self.extractor_with_name(&extraction_policy.extractor)?;
@@ -468,14 +635,70 @@ impl App {
         Ok(extraction_policy)
     }
 
+    pub async fn update_extraction_policy_params(
+        &self,
+        extraction_policy_id: &str,
+        input_params: serde_json::Value,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::UpdateExtractionPolicyParams {
+                extraction_policy_id: extraction_policy_id.to_string(),
+                input_params,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub async fn delete_extraction_policy(
+        &self,
+        namespace: &str,
+        extraction_policy_id: &str,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::DeleteExtractionPolicy {
+                namespace: namespace.to_string(),
+                extraction_policy_id: extraction_policy_id.to_string(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
     pub async fn get_extraction_policies_from_ids(
         &self,
         extraction_policy_ids: HashSet<String>,
-    ) -> Result<Option<Vec<ExtractionPolicy>>> {
+    ) -> Result<ExtractionPoliciesLookup> {
         self.state_machine
             .get_extraction_policies_from_ids(extraction_policy_ids)
     }
 
+    /// Checks the sizes of the reverse indexes against their soft limits and
+    /// returns the names of the ones over the limit
+    pub fn check_reverse_index_limits(&self) -> Vec<&'static str> {
+        self.reverse_index_limits
+            .check_soft_limits(&self.state_machine.reverse_index_sizes())
+    }
+
+    /// Returns true when tasks for the state change should not be created
+    /// yet because there are too many unassigned tasks
+    pub fn defer_task_creation(&self, state_change: &StateChange) -> bool {
+        let unassigned_tasks = self
+            .state_machine
+            .data
+            .indexify_state
+            .unassigned_tasks
+            .count();
+        self.reverse_index_limits
+            .defer_task_creation(state_change.id, unassigned_tasks)
+    }
+
     pub async fn unassigned_tasks(&self) -> Result<Vec<internal_api::Task>> {
         let mut tasks = vec![];
         for task_id in self.state_machine.get_unassigned_tasks().await.iter() {
@@ -553,6 +776,7 @@ impl App {
                 timestamp_secs(),
             )],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.forwardable_raft
             .client_write(req)
@@ -561,6 +785,44 @@ impl App {
         Ok(())
     }
 
+    /// Move all unfinished tasks of an extractor which are assigned to
+    /// executors back to the unassigned queue
+    pub async fn requeue_tasks_for_extractor(&self, extractor: &str) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::RequeueTasksForExtractor {
+                extractor: extractor.to_string(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft
+            .client_write(req)
+            .await
+            .map_err(|e| anyhow!("unable to requeue tasks for extractor {}", e))?;
+        Ok(())
+    }
+
+    /// Move a task to another state of its lifecycle
+    pub async fn transition_task(
+        &self,
+        task_id: &str,
+        to: internal_api::TaskLifecycleState,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::TransitionTask {
+                task_id: task_id.to_string(),
+                to,
+                update_time: SystemTime::now(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
     pub async fn create_extraction_graph(
         &self,
         extraction_graph: ExtractionGraph,
@@ -582,6 +844,7 @@ impl App {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.forwardable_raft
             .client_write(req)
@@ -603,6 +866,18 @@ impl App {
         &self,
         task: internal_api::Task,
         executor_id: Option<String>,
+    ) -> Result<()> {
+        self.update_task_at(task, executor_id, SystemTime::now())
+            .await
+    }
+
+    /// Updates a task as of the given time, which decides the day its usage
+    /// is counted on when it finishes
+    pub async fn update_task_at(
+        &self,
+        task: internal_api::Task,
+        executor_id: Option<String>,
+        update_time: SystemTime,
     ) -> Result<()> {
         let root_content_id = if let Some(root_id) = &task.content_metadata.root_content_id {
             self.state_machine
@@ -627,10 +902,11 @@ impl App {
             payload: RequestPayload::UpdateTask {
                 task,
                 executor_id,
-                update_time: SystemTime::now(),
+                update_time,
             },
             new_state_changes,
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         let _resp = self.forwardable_raft.client_write(req).await?;
         Ok(())
@@ -644,6 +920,7 @@ impl App {
             payload: RequestPayload::CreateOrAssignGarbageCollectionTask { gc_tasks },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.forwardable_raft.client_write(request).await?;
         Ok(())
@@ -658,6 +935,7 @@ impl App {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.forwardable_raft.client_write(req).await?;
         Ok(())
@@ -667,14 +945,9 @@ impl App {
         &self,
         extractor: &str,
     ) -> Result<internal_api::ExtractorDescription> {
-        let extractor = self
-            .state_machine
-            .get_from_cf::<internal_api::ExtractorDescription, _>(
-                StateMachineColumns::Extractors,
-                extractor,
-            )?
-            .ok_or_else(|| anyhow!("Extractor with name {} not found", extractor))?;
-        Ok(extractor)
+        self.state_machine
+            .get_extractor(extractor)?
+            .ok_or_else(|| anyhow!("Extractor with name {} not found", extractor))
     }
 
     pub async fn list_extraction_policy(&self, namespace: &str) -> Result<Vec<ExtractionPolicy>> {
@@ -688,25 +961,207 @@ impl App {
                 .into_iter()
                 .collect_vec()
         };
-        let extraction_policies = self
+        let lookup = self
             .state_machine
-            .get_extraction_policies_from_ids(extraction_policy_ids.into_iter().collect())?
-            .unwrap_or_else(Vec::new);
-        Ok(extraction_policies)
+            .get_extraction_policies_from_ids(extraction_policy_ids.into_iter().collect())?;
+        if !lookup.missing.is_empty() {
+            warn!(
+                "namespace {} references missing extraction policies: {:?}",
+                namespace, lookup.missing
+            );
+        }
+        Ok(lookup.found)
     }
 
     pub async fn create_namespace(&self, namespace: &str) -> Result<()> {
         let req = StateMachineUpdateRequest {
             payload: RequestPayload::CreateNamespace {
                 name: namespace.to_string(),
+                structured_data_schema: None,
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         let _resp = self.forwardable_raft.client_write(req).await?;
         Ok(())
     }
 
+    pub async fn set_namespace_alias(&self, alias: &str, namespace: &str) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::SetNamespaceAlias {
+                alias: alias.to_string(),
+                namespace: namespace.to_string(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub async fn set_namespace_retention(
+        &self,
+        namespace: &str,
+        rules: internal_api::RetentionRules,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::SetNamespaceRetention {
+                namespace: namespace.to_string(),
+                rules,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub fn get_namespace_retention_rules(
+        &self,
+    ) -> Result<HashMap<String, internal_api::RetentionRules>> {
+        self.state_machine.get_namespace_retention_rules()
+    }
+
+    /// Returns the ids of the extraction policies of the namespace in an
+    /// order they can be executed in, every policy after the policies whose
+    /// output it extracts from
+    pub fn get_policy_execution_order(&self, namespace: &str) -> Result<Vec<String>> {
+        let graph = self.state_machine.get_policy_dependency_graph(namespace)?;
+        store::state_machine_objects::toposort_policies(&graph)
+    }
+
+    /// Returns the latest versions of the root content of the namespace which
+    /// isn't tombstoned
+    pub async fn list_live_roots(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<internal_api::ContentMetadata>> {
+        let content_ids = self
+            .state_machine
+            .get_content_namespace_table()
+            .get(namespace)
+            .into_iter()
+            .flatten()
+            .map(|id| id.id.clone())
+            .collect();
+        Ok(self
+            .state_machine
+            .get_content_from_ids(content_ids)
+            .await?
+            .into_iter()
+            .filter(|content| content.parent_id.is_none() && content.latest && !content.tombstoned)
+            .collect())
+    }
+
+    pub fn has_unfinished_tasks(&self, root_content_id: &str) -> bool {
+        self.state_machine.has_unfinished_tasks(root_content_id)
+    }
+
+    /// Returns the namespace an alias points to, names which aren't aliases
+    /// are returned as they are
+    pub fn resolve_namespace(&self, name: &str) -> Result<String> {
+        Ok(self
+            .state_machine
+            .get_namespace_alias(name)?
+            .unwrap_or_else(|| name.to_string()))
+    }
+
+    pub async fn create_subscription(
+        &self,
+        subscription: internal_api::Subscription,
+    ) -> Result<()> {
+        self.write_delivery_update(RequestPayload::CreateSubscription { subscription })
+            .await
+    }
+
+    pub async fn delete_subscription(&self, subscription_id: &str) -> Result<()> {
+        self.write_delivery_update(RequestPayload::DeleteSubscription {
+            subscription_id: subscription_id.to_string(),
+        })
+        .await
+    }
+
+    pub fn get_subscription(
+        &self,
+        subscription_id: &str,
+    ) -> Result<Option<internal_api::Subscription>> {
+        self.state_machine.get_subscription(subscription_id)
+    }
+
+    pub fn list_subscriptions(&self, namespace: &str) -> Result<Vec<internal_api::Subscription>> {
+        self.state_machine.list_subscriptions(namespace)
+    }
+
+    pub async fn enqueue_deliveries(&self, deliveries: Vec<internal_api::Delivery>) -> Result<()> {
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+        self.write_delivery_update(RequestPayload::EnqueueDeliveries { deliveries })
+            .await
+    }
+
+    /// Returns the first `limit` pending deliveries of the subscription, in
+    /// the order they have to be delivered
+    pub fn list_pending_deliveries(
+        &self,
+        subscription_id: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<internal_api::Delivery>> {
+        self.state_machine
+            .list_pending_deliveries(subscription_id, limit)
+    }
+
+    pub fn list_dead_letters(&self, subscription_id: &str) -> Result<Vec<internal_api::Delivery>> {
+        self.state_machine.list_dead_letters(subscription_id)
+    }
+
+    pub async fn ack_delivery(&self, delivery: &internal_api::Delivery) -> Result<()> {
+        self.write_delivery_update(RequestPayload::AckDelivery {
+            subscription_id: delivery.subscription_id.clone(),
+            sequence: delivery.sequence,
+        })
+        .await
+    }
+
+    pub async fn retry_delivery(&self, delivery: internal_api::Delivery) -> Result<()> {
+        self.write_delivery_update(RequestPayload::RetryDelivery { delivery })
+            .await
+    }
+
+    pub async fn dead_letter_delivery(&self, delivery: internal_api::Delivery) -> Result<()> {
+        self.write_delivery_update(RequestPayload::DeadLetterDelivery { delivery })
+            .await
+    }
+
+    /// Moves the dead letters of the subscription back to its queue and
+    /// returns how many were moved
+    pub async fn requeue_dead_letters(&self, subscription_id: &str) -> Result<usize> {
+        let dead_letters = self.list_dead_letters(subscription_id)?.len();
+        self.write_delivery_update(RequestPayload::RequeueDeadLetters {
+            subscription_id: subscription_id.to_string(),
+        })
+        .await?;
+        Ok(dead_letters)
+    }
+
+    pub fn delivery_queue_stats(&self) -> Result<Vec<DeliveryQueueStats>> {
+        self.state_machine.delivery_queue_stats()
+    }
+
+    async fn write_delivery_update(&self, payload: RequestPayload) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload,
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
     pub async fn list_namespaces(&self) -> Result<Vec<internal_api::Namespace>> {
         //  Fetch the namespaces from the db
         let namespaces: Vec<String> = self
@@ -722,6 +1177,7 @@ impl App {
         for namespace_name in namespaces {
             let ns = self.state_machine.get_namespace(&namespace_name).await?;
             if let Some(ns) = ns {
+                self.repair_namespace(&ns).await?;
                 result_namespaces.push(ns);
             }
         }
@@ -730,7 +1186,47 @@ impl App {
     }
 
     pub async fn namespace(&self, namespace: &str) -> Result<Option<internal_api::Namespace>> {
-        self.state_machine.get_namespace(namespace).await
+        let ns = self.state_machine.get_namespace(namespace).await?;
+        if let Some(ns) = &ns {
+            self.repair_namespace(ns).await?;
+        }
+        Ok(ns)
+    }
+
+    /// Repairs the reverse indexes of a namespace read with warnings, when
+    /// dangling index entries are repaired
+    async fn repair_namespace(&self, namespace: &internal_api::Namespace) -> Result<()> {
+        if self.repair_dangling_indexes && !namespace.warnings.is_empty() {
+            self.remove_dangling_extraction_policies(&namespace.name)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Removes the extraction policies which no longer exist from the reverse
+    /// index of the namespace
+    pub async fn remove_dangling_extraction_policies(&self, namespace: &str) -> Result<()> {
+        let policy_ids = self
+            .state_machine
+            .data
+            .indexify_state
+            .extraction_policies_table
+            .get(&namespace.to_string());
+        let lookup = self.get_extraction_policies_from_ids(policy_ids).await?;
+        if lookup.missing.is_empty() {
+            return Ok(());
+        }
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::RemoveDanglingExtractionPolicies {
+                namespace: namespace.to_string(),
+                extraction_policy_ids: lookup.missing,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
     }
 
     // TODO: edwin
@@ -739,26 +1235,97 @@ impl App {
         addr: &str,
         executor_id: &str,
         extractors: Vec<internal_api::ExtractorDescription>,
+    ) -> Result<()> {
+        self.register_executor_with_capabilities(addr, executor_id, extractors, HashMap::new())
+            .await
+    }
+
+    pub async fn register_executor_with_capabilities(
+        &self,
+        addr: &str,
+        executor_id: &str,
+        extractors: Vec<internal_api::ExtractorDescription>,
+        capabilities: HashMap<String, String>,
+    ) -> Result<()> {
+        self.register_executor_at(
+            addr,
+            executor_id,
+            extractors,
+            capabilities,
+            timestamp_secs(),
+        )
+        .await
+    }
+
+    /// Registers an executor as last seen at `ts_secs`, a time of the
+    /// coordinator's clock rather than one reported by the executor
+    pub async fn register_executor_at(
+        &self,
+        addr: &str,
+        executor_id: &str,
+        extractors: Vec<internal_api::ExtractorDescription>,
+        capabilities: HashMap<String, String>,
+        ts_secs: u64,
     ) -> Result<()> {
         let state_change = StateChange::new(
             executor_id.to_string(),
             internal_api::ChangeType::ExecutorAdded,
-            timestamp_secs(),
+            ts_secs,
         );
         let req = StateMachineUpdateRequest {
             payload: RequestPayload::RegisterExecutor {
                 addr: addr.to_string(),
                 executor_id: executor_id.to_string(),
                 extractors,
-                ts_secs: timestamp_secs(),
+                ts_secs,
+                capabilities,
             },
             new_state_changes: vec![state_change.clone()],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         let _resp = self.forwardable_raft.client_write(req).await?;
         Ok(())
     }
 
+    /// Updates the description of an extractor an executor runs in place,
+    /// without requeueing the executor's tasks. Changes to outputs which
+    /// indexes are built from are rejected.
+    pub async fn update_executor_extractor(
+        &self,
+        executor_id: &str,
+        extractor: internal_api::ExtractorDescription,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::UpdateExecutorExtractor {
+                executor_id: executor_id.to_string(),
+                extractor,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    /// Renames a registered extractor. The executors running it, the
+    /// extraction policies and indexes using it and its unfinished tasks
+    /// refer to the new name afterwards.
+    pub async fn rename_extractor(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::RenameExtractor {
+                old_name: old_name.to_string(),
+                new_name: new_name.to_string(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
     pub async fn list_extractors(&self) -> Result<Vec<internal_api::ExtractorDescription>> {
         let extractors: Vec<internal_api::ExtractorDescription> = self
             .state_machine
@@ -809,17 +1376,86 @@ impl App {
                 state_change_id,
                 processed_at: timestamp_secs(),
             }],
+            idempotency_key: None,
         };
         self.forwardable_raft.client_write(req).await?;
         Ok(())
     }
 
-    pub async fn create_content_batch(
-        &self,
-        content_metadata: Vec<internal_api::ContentMetadata>,
+    /// Assigns tasks to executors outside of the processing of a state
+    /// change
+    pub async fn assign_tasks(&self, assignments: HashMap<TaskId, ExecutorId>) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::AssignTask { assignments },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub async fn create_content_batch(
+        &self,
+        content_metadata: Vec<internal_api::ContentMetadata>,
     ) -> Result<Vec<CreateContentStatus>> {
+        Ok(self
+            .create_content_batch_with_versions(content_metadata)
+            .await?
+            .statuses)
+    }
+
+    /// Creates the content like `create_content_batch` and also returns the
+    /// ids and versions of the content which were written, as resolved while
+    /// creating new versions and reparenting children.
+    pub async fn create_content_batch_with_versions(
+        &self,
+        content_metadata: Vec<internal_api::ContentMetadata>,
+    ) -> Result<CreatedContent> {
+        self.write_content_batch(content_metadata, None).await
+    }
+
+    /// Creates content for an ingestion request with an idempotency key. The
+    /// content is only created by the first request with the key in the
+    /// namespace, until the key expires the record of that request is
+    /// returned instead.
+    pub async fn create_content_with_ingestion_key(
+        &self,
+        content: internal_api::ContentMetadata,
+        key: &str,
+        now: u64,
+    ) -> Result<IngestionKeyRecord> {
+        let namespace = content.namespace.clone();
+        if let Some(record) = self.state_machine.get_ingestion_key(&namespace, key)? {
+            if !record.expired(now) {
+                return Ok(record);
+            }
+        }
+        let record = IngestionKeyRecord {
+            namespace: namespace.clone(),
+            key: key.to_string(),
+            content_id: content.id.id.clone(),
+            created: true,
+            created_at: now,
+            expires_at: now + self.ingestion_key_ttl.as_secs(),
+        };
+        self.write_content_batch(vec![content], Some(record))
+            .await?;
+
+        //  A concurrent request with the same key may have been applied first,
+        //  in which case this one didn't create anything
+        self.state_machine
+            .get_ingestion_key(&namespace, key)?
+            .ok_or_else(|| anyhow!("ingestion key {} was not recorded", key))
+    }
+
+    async fn write_content_batch(
+        &self,
+        content_metadata: Vec<internal_api::ContentMetadata>,
+        mut ingestion_key: Option<IngestionKeyRecord>,
+    ) -> Result<CreatedContent> {
         if content_metadata.is_empty() {
-            return Ok(Vec::new());
+            return Ok(CreatedContent::default());
         }
         let ns = &content_metadata.first().unwrap().namespace.clone();
         let extraction_graph_names = &content_metadata
@@ -943,24 +1579,34 @@ impl App {
             };
         }
 
+        if let (Some(record), Some(status)) = (ingestion_key.as_mut(), statuses.first()) {
+            record.created = *status == CreateContentStatus::Created;
+        }
         let req = StateMachineUpdateRequest {
             payload: RequestPayload::CreateOrUpdateContent {
                 entries: update_entries,
+                ingestion_key,
             },
             new_state_changes: state_changes,
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
-        self.forwardable_raft
+        let response = self
+            .forwardable_raft
             .client_write(req)
             .await
             .map_err(|e| anyhow!("unable to create new content metadata: {}", e.to_string()))?;
 
-        Ok(statuses)
+        Ok(CreatedContent {
+            statuses,
+            written: response.written_content,
+        })
     }
 
     async fn tombstone_content_root_batch(
         &self,
         roots: Vec<internal_api::ContentMetadata>,
+        reason: internal_api::TombstoneReason,
         state_changes_processed: Vec<StateChangeProcessed>,
     ) -> Result<(), anyhow::Error> {
         let mut state_changes = vec![];
@@ -991,9 +1637,11 @@ impl App {
         let req = StateMachineUpdateRequest {
             payload: RequestPayload::TombstoneContentTree {
                 content_metadata: updated_content,
+                reason,
             },
             new_state_changes: state_changes,
             state_changes_processed,
+            idempotency_key: None,
         };
 
         self.forwardable_raft
@@ -1009,6 +1657,7 @@ impl App {
     pub async fn tombstone_content_batch(
         &self,
         content_ids: &[String],
+        reason: internal_api::TombstoneReason,
     ) -> Result<(), anyhow::Error> {
         let mut roots = Vec::new();
 
@@ -1026,12 +1675,14 @@ impl App {
                 .ok_or_else(|| anyhow!("Content with id {} not found", content_id))?;
             roots.push(root);
         }
-        self.tombstone_content_root_batch(roots, Vec::new()).await
+        self.tombstone_content_root_batch(roots, reason, Vec::new())
+            .await
     }
 
     pub async fn tombstone_content_batch_with_version(
         &self,
         content_ids: &[ContentMetadataId],
+        reason: internal_api::TombstoneReason,
         state_changes_processed: Vec<StateChangeProcessed>,
     ) -> Result<(), anyhow::Error> {
         let mut roots = Vec::new();
@@ -1043,7 +1694,7 @@ impl App {
                 .ok_or_else(|| anyhow!("Content with id {} not found", content_id))?;
             roots.push(root);
         }
-        self.tombstone_content_root_batch(roots, state_changes_processed)
+        self.tombstone_content_root_batch(roots, reason, state_changes_processed)
             .await
     }
 
@@ -1072,6 +1723,21 @@ impl App {
             .get_content_tree_metadata_with_version(content_id)
     }
 
+    pub fn check_content_exists(
+        &self,
+        namespace: &str,
+        keys: &[internal_api::ContentLookupKey],
+    ) -> Result<Vec<internal_api::ContentExistence>> {
+        self.state_machine.check_content_exists(namespace, keys)
+    }
+
+    pub fn get_referenced_storage_urls(
+        &self,
+        storage_urls: &HashSet<String>,
+    ) -> Result<HashSet<String>> {
+        self.state_machine.get_referenced_storage_urls(storage_urls)
+    }
+
     pub async fn create_tasks(
         &self,
         tasks: Vec<internal_api::Task>,
@@ -1084,11 +1750,47 @@ impl App {
                 state_change_id,
                 processed_at: timestamp_secs(),
             }],
+            idempotency_key: None,
         };
         let _resp = self.forwardable_raft.client_write(req).await?;
         Ok(())
     }
 
+    /// Cancels tasks which no executor picked up yet, the others are left
+    /// to finish
+    pub async fn cancel_tasks(&self, task_ids: Vec<TaskId>) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::CancelTasks { task_ids },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    /// Clears the completion of the extraction policy on the content and
+    /// creates the tasks which apply it again
+    pub async fn rebuild_content_tree(
+        &self,
+        extraction_policy_id: &str,
+        content_ids: Vec<String>,
+        tasks: Vec<internal_api::Task>,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::RebuildContentTree {
+                extraction_policy_id: extraction_policy_id.to_string(),
+                content_ids,
+                tasks,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
     pub async fn list_tasks(
         &self,
         namespace: &str,
@@ -1133,6 +1835,7 @@ impl App {
         if content.root_content_id.is_some() {
             return Err(anyhow!("cannot update labels for non-root content"));
         }
+        let previous_labels = content.labels.clone();
         let contents = self
             .state_machine
             .get_content_tree_metadata(content_id)
@@ -1152,11 +1855,17 @@ impl App {
         )];
 
         let req = StateMachineUpdateRequest {
-            payload: RequestPayload::CreateOrUpdateContent { entries },
+            payload: RequestPayload::CreateOrUpdateContent {
+                entries,
+                ingestion_key: None,
+            },
             new_state_changes,
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.forwardable_raft.client_write(req).await?;
+        self.content_events
+            .labels_updated(content_id, &previous_labels, &labels);
         Ok(())
     }
 
@@ -1231,6 +1940,21 @@ impl App {
         Ok(indexes)
     }
 
+    /// Returns the table names of the indexes of each namespace, read from
+    /// the namespace index table
+    pub async fn list_namespace_index_tables(&self) -> Result<HashMap<String, HashSet<String>>> {
+        let namespace_indexes = self.state_machine.get_namespace_index_table().await;
+        let mut tables = HashMap::new();
+        for (namespace, index_ids) in namespace_indexes {
+            let indexes = self.state_machine.get_indexes_from_ids(index_ids).await?;
+            tables.insert(
+                namespace,
+                indexes.into_iter().map(|index| index.table_name).collect(),
+            );
+        }
+        Ok(tables)
+    }
+
     pub async fn get_index(&self, id: &str) -> Result<internal_api::Index> {
         let index = self
             .state_machine
@@ -1244,11 +1968,96 @@ impl App {
             payload: RequestPayload::SetIndex { indexes },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub async fn set_search_profile(&self, profile: internal_api::SearchProfile) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::SetSearchProfile { profile },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub async fn delete_search_profile(&self, namespace: &str, name: &str) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::DeleteSearchProfile {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub async fn delete_schema(&self, namespace: &str, schema_id: &str) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::DeleteSchema {
+                namespace: namespace.to_string(),
+                schema_id: schema_id.to_string(),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        self.forwardable_raft.client_write(req).await?;
+        Ok(())
+    }
+
+    pub fn get_search_profile(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<internal_api::SearchProfile>> {
+        self.state_machine.get_search_profile(namespace, name)
+    }
+
+    pub fn list_search_profiles(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<internal_api::SearchProfile>> {
+        self.state_machine.list_search_profiles(namespace)
+    }
+
+    pub async fn create_search_feedback(
+        &self,
+        feedback: internal_api::SearchFeedback,
+    ) -> Result<()> {
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateSearchFeedback { feedback },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
         };
         self.forwardable_raft.client_write(req).await?;
         Ok(())
     }
 
+    pub fn list_search_feedback(
+        &self,
+        query: &internal_api::SearchFeedbackQuery,
+    ) -> Result<(Vec<internal_api::SearchFeedback>, Option<String>)> {
+        self.state_machine.list_search_feedback(query)
+    }
+
+    pub fn get_search_feedback_counts(
+        &self,
+        namespace: &str,
+        content_ids: &HashSet<String>,
+    ) -> Result<HashMap<String, internal_api::SearchFeedbackCounts>> {
+        self.state_machine
+            .get_search_feedback_counts(namespace, content_ids)
+    }
+
     pub async fn list_state_changes(&self) -> Result<Vec<StateChange>> {
         let state_changes = self
             .state_machine
@@ -1285,8 +2094,10 @@ impl App {
             .get_schemas_by_namespace()
             .await
             .get(namespace)
-            .cloned()
-            .unwrap_or(HashSet::new());
+            .into_iter()
+            .flatten()
+            .map(|schema_id| schema_id.to_string())
+            .collect();
         let schemas = self.state_machine.get_schemas(schemas_for_ns).await?;
         Ok(schemas)
     }
@@ -1407,20 +2218,34 @@ async fn watch_for_leader_change(
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, sync::Arc, time::Duration};
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
 
     use indexify_internal_api::{
+        ContentExistence,
+        ContentLookupKey,
         ContentMetadata,
         ContentMetadataId,
+        ContentQuery,
+        ContentSource,
         ExtractionGraph,
+        GarbageCollectionTask,
+        ServerTaskType,
         StructuredDataSchema,
+        TaskLifecycleState,
         TaskOutcome,
+        TombstoneReason,
+        DEFAULT_SCHEMA_NAME,
     };
+    use indexify_proto::indexify_coordinator::CreateContentStatus;
 
     use crate::{
         state::{
             store::{
-                requests::{RequestPayload, StateMachineUpdateRequest},
+                requests::{CreateOrUpdateContentEntry, RequestPayload, StateMachineUpdateRequest},
                 ExecutorId,
                 TaskId,
             },
@@ -1430,6 +2255,8 @@ mod tests {
             create_test_extraction_graph,
             mock_extractor,
             test_mock_content_metadata,
+            DEFAULT_TEST_EXTRACTOR,
+            DEFAULT_TEST_NAMESPACE,
         },
         test_utils::RaftTestCluster,
     };
@@ -1441,6 +2268,7 @@ mod tests {
         let cluster = RaftTestCluster::new(3, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
         let content = ContentMetadata {
             id: ContentMetadataId::new("content_id"),
@@ -1471,6 +2299,7 @@ mod tests {
         let cluster = RaftTestCluster::new(1, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
         //  First create a task and ensure it's written
         let content = ContentMetadata {
@@ -1489,6 +2318,7 @@ mod tests {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
 
         let read_back = {
@@ -1511,6 +2341,7 @@ mod tests {
             payload: RequestPayload::AssignTask { assignments },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
 
         let read_back = |node: Arc<App>| async move {
@@ -1536,6 +2367,7 @@ mod tests {
         let cluster = RaftTestCluster::new(3, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
         //  Create a task and ensure that it can be read back
         let content = ContentMetadata {
@@ -1554,6 +2386,7 @@ mod tests {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         let read_back = {
             move |node: Arc<App>| async move {
@@ -1575,6 +2408,7 @@ mod tests {
             payload: RequestPayload::AssignTask { assignments },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         };
         let read_back = |node: Arc<App>| async move {
             match node.tasks_for_executor("executor_id", None).await {
@@ -1609,119 +2443,1084 @@ mod tests {
         Ok(())
     }
 
-    /// Test to create, register, read back and remove an executor and
-    /// associated extractors Executors are typically created along with
-    /// extractors so both need to be asserted
     #[tokio::test]
     #[tracing_test::traced_test]
-    async fn test_create_read_remove_executors() -> Result<(), anyhow::Error> {
-        let cluster = RaftTestCluster::new(3, None).await?;
+    async fn test_requeue_tasks_for_extractor() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
-        //  Create an executor and extractor and ensure they can be read back
-        let executor_id = "executor_id";
-        let extractor = indexify_internal_api::ExtractorDescription {
-            name: "extractor".into(),
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
             ..Default::default()
         };
-        let addr = "addr";
-        node.register_executor(addr, executor_id, vec![extractor.clone()])
-            .await?;
-
-        //  Read the executors from multiple functions
-        let executors = node.get_executors().await?;
-        assert_eq!(executors.len(), 1);
-
-        let executor = node.get_executor_by_id(executor_id).await?;
-        assert_eq!(executor.id, executor_id);
-
-        let executors = node.get_executors_for_extractor(&extractor.name).await?;
-        assert_eq!(executors.len(), 1);
-        assert_eq!(executors.first().unwrap().id, executor_id);
-
-        //  Read the extractors
-        let extractors = node.list_extractors().await?;
-        assert_eq!(extractors.len(), 1);
+        node.create_content_batch(vec![content.clone()]).await?;
+        let tasks = vec![
+            ("task_1", "extractor_1"),
+            ("task_2", "extractor_1"),
+            ("task_3", "extractor_2"),
+        ]
+        .into_iter()
+        .map(|(id, extractor)| indexify_internal_api::Task {
+            id: id.into(),
+            extractor: extractor.into(),
+            content_metadata: content.clone(),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+
+        let assignments: HashMap<TaskId, ExecutorId> = vec![
+            ("task_1".into(), "executor_1".into()),
+            ("task_2".into(), "executor_2".into()),
+            ("task_3".into(), "executor_1".into()),
+        ]
+        .into_iter()
+        .collect();
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::AssignTask { assignments },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+        assert!(node.unassigned_tasks().await?.is_empty());
 
-        let retrieved_extractor = node.extractor_with_name(&extractor.name)?;
-        assert_eq!(retrieved_extractor, extractor);
+        node.requeue_tasks_for_extractor("extractor_1").await?;
 
-        //  Remove the executor that was created and assert that it was removed
-        node.remove_executor(executor_id).await?;
-        let executors = node.get_executors().await?;
-        assert_eq!(executors.len(), 0);
+        let unassigned: HashSet<TaskId> = node
+            .unassigned_tasks()
+            .await?
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(
+            unassigned,
+            HashSet::from(["task_1".to_string(), "task_2".to_string()])
+        );
+        let assignments = node.task_assignments().await?;
+        assert_eq!(assignments.len(), 1);
+        assert_eq!(assignments.get("task_3"), Some(&"executor_1".to_string()));
+        let counts = node.get_executor_running_task_count().await;
+        assert_eq!(counts.get("executor_1"), Some(&1));
+        assert_eq!(counts.get("executor_2"), Some(&0));
 
         Ok(())
     }
 
     #[tokio::test]
     #[tracing_test::traced_test]
-    async fn test_create_and_read_content() -> Result<(), anyhow::Error> {
-        let content_size = 3;
-
+    async fn test_transition_task() -> Result<(), anyhow::Error> {
         let cluster = RaftTestCluster::new(1, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
-        //  Create some content
-        let mut content_metadata_vec: Vec<ContentMetadata> = Vec::new();
-        for i in 0..content_size {
-            let content_metadata = ContentMetadata {
-                id: ContentMetadataId::new(&format!("id{}", i)),
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content.clone()]).await?;
+        let tasks = ["task_1", "task_2"]
+            .into_iter()
+            .map(|id| indexify_internal_api::Task {
+                id: id.into(),
+                extractor: "extractor_1".into(),
+                content_metadata: content.clone(),
                 ..Default::default()
-            };
-            content_metadata_vec.push(content_metadata);
-        }
-        node.create_content_batch(content_metadata_vec.clone())
+            })
+            .collect();
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+        let mut extractor = mock_extractor();
+        extractor.name = "extractor_1".to_string();
+        node.register_executor("localhost:8950", "executor_1", vec![extractor])
             .await?;
+        let assigned = |executor_id: &str| TaskLifecycleState::Assigned {
+            executor_id: executor_id.to_string(),
+        };
 
-        //  Read the content back
-        let read_content = node
-            .list_content(&content_metadata_vec.first().unwrap().namespace, "", |_| {
-                true
-            })
-            .await
-            .unwrap();
-        assert_eq!(read_content.len(), content_size);
+        //  unassigned -> assigned
+        node.transition_task("task_1", assigned("executor_1"))
+            .await?;
+        node.transition_task("task_2", assigned("executor_1"))
+            .await?;
+        assert!(node.unassigned_tasks().await?.is_empty());
+        let counts = node.get_executor_running_task_count().await;
+        assert_eq!(counts.get("executor_1"), Some(&2));
 
-        //  Read back all the pieces of content
-        let read_content = node
-            .get_content_metadata_batch(
-                content_metadata_vec
-                    .iter()
-                    .map(|content| content.id.id.clone())
-                    .collect(),
-            )
+        //  assigned -> unassigned
+        node.transition_task("task_2", TaskLifecycleState::Unassigned)
             .await?;
-        assert_eq!(read_content.len(), content_size);
+        let unassigned: Vec<TaskId> = node
+            .unassigned_tasks()
+            .await?
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(unassigned, vec!["task_2".to_string()]);
+        let counts = node.get_executor_running_task_count().await;
+        assert_eq!(counts.get("executor_1"), Some(&1));
 
-        //  Read back a specific piece of content
-        let read_content = node
-            .state_machine
-            .get_latest_version_of_content(&content_metadata_vec[0].id.id)?
-            .unwrap();
-        assert_eq!(read_content, content_metadata_vec[0]);
+        //  Tasks are only assigned to executors running their extractor
+        let err = node
+            .transition_task("task_2", assigned("executor_2"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("doesn't run extractor_1"));
+        assert_eq!(node.unassigned_tasks().await?.len(), 1);
+
+        //  assigned -> finished
+        node.transition_task(
+            "task_1",
+            TaskLifecycleState::Finished {
+                outcome: TaskOutcome::Success,
+            },
+        )
+        .await?;
+        assert_eq!(
+            node.task_with_id("task_1").await?.outcome,
+            TaskOutcome::Success
+        );
+        assert!(node.task_assignments().await?.is_empty());
+        let counts = node.get_executor_running_task_count().await;
+        assert_eq!(counts.get("executor_1"), Some(&0));
+        assert_eq!(
+            node.unfinished_tasks_by_extractor("extractor_1").await?,
+            HashSet::from(["task_2".to_string()])
+        );
+
+        //  Transitions outside of the lifecycle are rejected
+        let err = node
+            .transition_task("task_1", assigned("executor_2"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("can't move from"));
+        let err = node
+            .transition_task(
+                "task_2",
+                TaskLifecycleState::Finished {
+                    outcome: TaskOutcome::Failed,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("can't move from"));
+        let err = node
+            .transition_task("task_3", TaskLifecycleState::Unassigned)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Task not found"));
+        assert!(node.task_assignments().await?.is_empty());
+        assert_eq!(node.unassigned_tasks().await?.len(), 1);
 
         Ok(())
     }
 
     #[tokio::test]
     #[tracing_test::traced_test]
-    async fn test_create_read_and_match_extraction_policies() -> Result<(), anyhow::Error> {
+    async fn test_update_executor_extractor() -> Result<(), anyhow::Error> {
         let cluster = RaftTestCluster::new(1, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
-
-        //  Create an executor and associated extractor
-        let executor_id = "executor_id";
-        let mut extractor = mock_extractor();
-        extractor.input_mime_types = vec!["*/*".into()];
-        let addr = "addr";
-        node.register_executor(addr, executor_id, vec![extractor.clone()])
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        node.register_executor("localhost:8950", "executor_1", vec![mock_extractor()])
             .await?;
 
-        let mut eg = create_test_extraction_graph("graph1", vec!["policy1"]);
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content.clone()]).await?;
+        let task = indexify_internal_api::Task {
+            id: "task_1".into(),
+            extractor: DEFAULT_TEST_EXTRACTOR.into(),
+            content_metadata: content,
+            ..Default::default()
+        };
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks: vec![task] },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::AssignTask {
+                assignments: HashMap::from([("task_1".into(), "executor_1".into())]),
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+
+        //  Updates which keep the outputs leave running tasks alone
+        let mut extractor = mock_extractor();
+        extractor.description = "updated description".to_string();
+        extractor.input_params = serde_json::json!({"chunk_size": 100});
+        node.update_executor_extractor("executor_1", extractor.clone())
+            .await?;
+        assert_eq!(node.extractor_with_name(DEFAULT_TEST_EXTRACTOR)?, extractor);
+        let executor = node.get_executor_by_id("executor_1").await?;
+        assert_eq!(executor.extractors, vec![extractor.clone()]);
+        let assignments = node.task_assignments().await?;
+        assert_eq!(assignments.get("task_1"), Some(&"executor_1".to_string()));
+        let counts = node.get_executor_running_task_count().await;
+        assert_eq!(counts.get("executor_1"), Some(&1));
+
+        //  Changing the schema of an output an index is built from is refused
+        let mut index = indexify_internal_api::Index {
+            id: "index_id".into(),
+            namespace: DEFAULT_TEST_NAMESPACE.into(),
+            extractor_name: DEFAULT_TEST_EXTRACTOR.into(),
+            graph_name: "graph".into(),
+            extraction_policy_name: "policy".into(),
+            ..Default::default()
+        };
+        index.name = index.build_name(&"test_output".to_string());
+        node.set_indexes(vec![index]).await?;
+        let mut incompatible = extractor.clone();
+        incompatible.outputs.insert(
+            "test_output".to_string(),
+            indexify_internal_api::OutputSchema::Embedding(
+                indexify_internal_api::EmbeddingSchema {
+                    dim: 512,
+                    distance: "cosine".to_string(),
+                },
+            ),
+        );
+        let err = node
+            .update_executor_extractor("executor_1", incompatible)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("can't be updated"));
+        assert_eq!(node.extractor_with_name(DEFAULT_TEST_EXTRACTOR)?, extractor);
+
+        //  Only executors running the extractor can update it
+        let err = node
+            .update_executor_extractor("executor_2", extractor)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("can't be updated"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_rename_extractor() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        node.register_executor("localhost:8950", "executor_1", vec![mock_extractor()])
+            .await?;
+        node.register_executor("localhost:8951", "executor_2", vec![mock_extractor()])
+            .await?;
+
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content.clone()]).await?;
+        let task = indexify_internal_api::Task {
+            id: "task_1".into(),
+            extractor: DEFAULT_TEST_EXTRACTOR.into(),
+            content_metadata: content,
+            ..Default::default()
+        };
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks: vec![task] },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+
+        node.rename_extractor(DEFAULT_TEST_EXTRACTOR, "renamed_extractor")
+            .await?;
+
+        let mut executors = node
+            .get_executors_for_extractor("renamed_extractor")
+            .await?;
+        executors.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(
+            executors.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(),
+            vec!["executor_1", "executor_2"]
+        );
+        for executor in &executors {
+            assert_eq!(executor.extractors.len(), 1);
+            assert_eq!(executor.extractors[0].name, "renamed_extractor");
+        }
+        assert!(node
+            .get_executors_for_extractor(DEFAULT_TEST_EXTRACTOR)
+            .await?
+            .is_empty());
+        assert_eq!(
+            node.extractor_with_name("renamed_extractor")?.name,
+            "renamed_extractor"
+        );
+        assert!(node.extractor_with_name(DEFAULT_TEST_EXTRACTOR).is_err());
+        assert_eq!(
+            node.unfinished_tasks_by_extractor("renamed_extractor")
+                .await?,
+            HashSet::from(["task_1".to_string()])
+        );
+        assert!(node
+            .unfinished_tasks_by_extractor(DEFAULT_TEST_EXTRACTOR)
+            .await?
+            .is_empty());
+        assert_eq!(
+            node.task_with_id("task_1").await?.extractor,
+            "renamed_extractor"
+        );
+
+        //  The old name is gone, and the new name can't be taken twice
+        let err = node
+            .rename_extractor(DEFAULT_TEST_EXTRACTOR, "renamed_extractor")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not registered"));
+        node.register_executor("localhost:8952", "executor_3", vec![mock_extractor()])
+            .await?;
+        let err = node
+            .rename_extractor(DEFAULT_TEST_EXTRACTOR, "renamed_extractor")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_all_executor_task_counts() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content.clone()]).await?;
+        let tasks = ["task_1", "task_2", "task_3"]
+            .into_iter()
+            .map(|id| indexify_internal_api::Task {
+                id: id.into(),
+                content_metadata: content.clone(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+        assert!(node
+            .state_machine
+            .get_all_executor_task_counts()?
+            .is_empty());
+
+        let assignments: HashMap<TaskId, ExecutorId> = vec![
+            ("task_1".into(), "executor_1".into()),
+            ("task_2".into(), "executor_2".into()),
+            ("task_3".into(), "executor_1".into()),
+        ]
+        .into_iter()
+        .collect();
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::AssignTask { assignments },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+
+        let counts = node.state_machine.get_all_executor_task_counts()?;
+        assert_eq!(
+            counts,
+            HashMap::from([("executor_1".to_string(), 2), ("executor_2".to_string(), 1)])
+        );
+        Ok(())
+    }
+
+    /// Test to create, register, read back and remove an executor and
+    /// associated extractors Executors are typically created along with
+    /// extractors so both need to be asserted
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_create_read_remove_executors() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(3, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+
+        //  Create an executor and extractor and ensure they can be read back
+        let executor_id = "executor_id";
+        let extractor = indexify_internal_api::ExtractorDescription {
+            name: "extractor".into(),
+            ..Default::default()
+        };
+        let addr = "addr";
+        node.register_executor(addr, executor_id, vec![extractor.clone()])
+            .await?;
+
+        //  Read the executors from multiple functions
+        let executors = node.get_executors().await?;
+        assert_eq!(executors.len(), 1);
+
+        let executor = node.get_executor_by_id(executor_id).await?;
+        assert_eq!(executor.id, executor_id);
+
+        let executors = node.get_executors_for_extractor(&extractor.name).await?;
+        assert_eq!(executors.len(), 1);
+        assert_eq!(executors.first().unwrap().id, executor_id);
+
+        //  Read the extractors
+        let extractors = node.list_extractors().await?;
+        assert_eq!(extractors.len(), 1);
+
+        let retrieved_extractor = node.extractor_with_name(&extractor.name)?;
+        assert_eq!(retrieved_extractor, extractor);
+
+        //  Remove the executor that was created and assert that it was removed
+        node.remove_executor(executor_id).await?;
+        let executors = node.get_executors().await?;
+        assert_eq!(executors.len(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_create_and_read_content() -> Result<(), anyhow::Error> {
+        let content_size = 3;
+
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        //  Create some content
+        let mut content_metadata_vec: Vec<ContentMetadata> = Vec::new();
+        for i in 0..content_size {
+            let content_metadata = ContentMetadata {
+                id: ContentMetadataId::new(&format!("id{}", i)),
+                ..Default::default()
+            };
+            content_metadata_vec.push(content_metadata);
+        }
+        node.create_content_batch(content_metadata_vec.clone())
+            .await?;
+
+        //  Read the content back
+        let read_content = node
+            .list_content(&content_metadata_vec.first().unwrap().namespace, "", |_| {
+                true
+            })
+            .await
+            .unwrap();
+        assert_eq!(read_content.len(), content_size);
+
+        //  Read back all the pieces of content
+        let read_content = node
+            .get_content_metadata_batch(
+                content_metadata_vec
+                    .iter()
+                    .map(|content| content.id.id.clone())
+                    .collect(),
+            )
+            .await?;
+        assert_eq!(read_content.len(), content_size);
+
+        //  Read back a specific piece of content
+        let read_content = node
+            .state_machine
+            .get_latest_version_of_content(&content_metadata_vec[0].id.id)?
+            .unwrap();
+        assert_eq!(read_content, content_metadata_vec[0]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_create_content_returns_written_versions() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            hash: "hash1".to_string(),
+            ..Default::default()
+        };
+        let created = node
+            .create_content_batch_with_versions(vec![content.clone()])
+            .await?;
+        assert_eq!(created.statuses, vec![CreateContentStatus::Created]);
+        let stored = node
+            .state_machine
+            .get_latest_version_of_content("content_id")?
+            .unwrap();
+        assert_eq!(created.written, vec![stored.id]);
+
+        //  Changed content is written as a new version along with the previous
+        // version, which is no longer the latest
+        let updated = ContentMetadata {
+            hash: "hash2".to_string(),
+            ..content
+        };
+        let created = node
+            .create_content_batch_with_versions(vec![updated])
+            .await?;
+        let stored = node
+            .state_machine
+            .get_latest_version_of_content("content_id")?
+            .unwrap();
+        assert_eq!(stored.id.version, 2);
+        assert_eq!(
+            created.written,
+            vec![
+                stored.id,
+                ContentMetadataId::new_with_version("content_id", 1)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_latest_version_is_read_past_nine_versions() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        //  The latest version is stored under the plain id, so the lookup
+        //  doesn't depend on how versioned keys sort
+        for version in 1..=12 {
+            let content = ContentMetadata {
+                id: ContentMetadataId::new("content_id"),
+                hash: format!("hash{}", version),
+                ..Default::default()
+            };
+            node.create_content_batch_with_versions(vec![content])
+                .await?;
+            let latest = node
+                .state_machine
+                .get_latest_version_of_content("content_id")?
+                .unwrap();
+            assert_eq!(latest.id.version, version);
+            assert_eq!(latest.hash, format!("hash{}", version));
+            assert!(latest.latest);
+        }
+        for version in [9, 10, 11] {
+            let content = node
+                .state_machine
+                .get_content_by_id_and_version(&ContentMetadataId::new_with_version(
+                    "content_id",
+                    version,
+                ))
+                .await?
+                .unwrap();
+            assert_eq!(content.hash, format!("hash{}", version));
+            assert!(!content.latest);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_content_by_id_prefix() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        node.create_namespace("other_namespace").await?;
+
+        let make_content = |id: &str| ContentMetadata {
+            id: ContentMetadataId::new(id),
+            root_content_id: Some(id.to_string()),
+            ..Default::default()
+        };
+        let old_version = ContentMetadata {
+            latest: false,
+            ..make_content("abc1")
+        };
+        let tombstoned = ContentMetadata {
+            tombstoned: true,
+            ..make_content("abc4")
+        };
+        let other_namespace = ContentMetadata {
+            namespace: "other_namespace".to_string(),
+            ..make_content("abc5")
+        };
+        node.create_content_batch(vec![
+            make_content("abc1"),
+            old_version,
+            make_content("abc2"),
+            make_content("abc3"),
+            tombstoned,
+            other_namespace,
+            make_content("abd1"),
+            make_content("xabc"),
+        ])
+        .await?;
+
+        let namespace = ContentMetadata::default().namespace;
+        let contents = node
+            .state_machine
+            .get_content_by_id_prefix(&namespace, "abc", 10)?;
+        let ids: Vec<_> = contents.iter().map(|c| c.id.id.clone()).collect();
+        assert_eq!(ids, vec!["abc1", "abc2", "abc3"]);
+        assert!(contents.iter().all(|c| c.latest));
+
+        let contents = node
+            .state_machine
+            .get_content_by_id_prefix(&namespace, "abc", 2)?;
+        assert_eq!(contents.len(), 2);
+
+        let contents = node
+            .state_machine
+            .get_content_by_id_prefix(&namespace, "zzz", 10)?;
+        assert!(contents.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_recently_updated_content() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let make_content = |id: &str, created_at: i64| ContentMetadata {
+            id: ContentMetadataId::new(id),
+            root_content_id: Some(id.to_string()),
+            created_at,
+            hash: format!("{}_{}", id, created_at),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![
+            make_content("content_1", 10),
+            make_content("content_2", 20),
+            make_content("content_3", 30),
+            make_content("content_4", 25),
+        ])
+        .await?;
+        //  Updating content creates a new version with a new creation time
+        node.create_content_batch(vec![
+            make_content("content_1", 40),
+            make_content("content_3", 35),
+        ])
+        .await?;
+        node.tombstone_content_batch(&["content_4".to_string()], TombstoneReason::default())
+            .await?;
+
+        let namespace = ContentMetadata::default().namespace;
+        let recent_ids = |n: usize| -> Result<Vec<String>, anyhow::Error> {
+            Ok(node
+                .state_machine
+                .get_recently_updated_content(&namespace, n)?
+                .into_iter()
+                .map(|c| c.id.id)
+                .collect())
+        };
+        assert_eq!(recent_ids(2)?, vec!["content_1", "content_3"]);
+        assert_eq!(recent_ids(10)?, vec!["content_1", "content_3", "content_2"]);
+        assert!(node
+            .state_machine
+            .get_recently_updated_content("other_namespace", 10)?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_tombstone_reason() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let make_content = |id: &str| ContentMetadata {
+            id: ContentMetadataId::new(id),
+            root_content_id: None,
+            ..Default::default()
+        };
+        node.create_content_batch(vec![make_content("deleted"), make_content("old_payload")])
+            .await?;
+
+        let reason = TombstoneReason::UserRequest("duplicate upload".to_string());
+        node.tombstone_content_batch(&["deleted".to_string()], reason.clone())
+            .await?;
+        let deleted = node
+            .state_machine
+            .get_latest_tombstoned_version("deleted")?
+            .unwrap();
+        assert!(deleted.tombstoned);
+        assert_eq!(deleted.tombstone_reason, Some(reason));
+
+        //  Payloads written before reasons were recorded apply with Unknown
+        let content = ContentMetadata {
+            tombstoned: true,
+            ..make_content("old_payload")
+        };
+        let payload: RequestPayload = serde_json::from_value(serde_json::json!({
+            "TombstoneContentTree": {
+                "content_metadata": [serde_json::to_value(&content)?],
+            }
+        }))?;
+        let request = StateMachineUpdateRequest {
+            payload,
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+        let old_payload = node
+            .state_machine
+            .get_latest_tombstoned_version("old_payload")?
+            .unwrap();
+        assert_eq!(old_payload.tombstone_reason, Some(TombstoneReason::Unknown));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_unprocessed_content() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy_1", "policy_2"]);
+        let policy_id = eg.extraction_policies[0].id.clone();
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+
+        //  One of the two policies of the graph was applied to the content
+        let partially_processed = ContentMetadata {
+            extraction_policy_ids: HashMap::from([(policy_id, 1)]),
+            ..test_mock_content_metadata("partially_processed", "", &eg.name)
+        };
+        let unprocessed = test_mock_content_metadata("unprocessed", "", &eg.name);
+        node.create_content_batch(vec![partially_processed, unprocessed])
+            .await?;
+
+        let unprocessed_ids = node
+            .state_machine
+            .get_unprocessed_content(DEFAULT_TEST_NAMESPACE)?;
+        assert_eq!(unprocessed_ids, vec![ContentMetadataId::new("unprocessed")]);
+        assert!(node
+            .state_machine
+            .get_unprocessed_content("other_namespace")?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_query_content() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let contents: Vec<ContentMetadata> = ["done", "pending", "deleted", "deleted_pending"]
+            .into_iter()
+            .map(|id| test_mock_content_metadata(id, "", "graph"))
+            .collect();
+        node.create_content_batch(contents.clone()).await?;
+        let state_change = node.unprocessed_state_change_events().await?;
+        let tasks = [&contents[1], &contents[3]]
+            .into_iter()
+            .map(|content| indexify_internal_api::Task {
+                id: format!("task_{}", content.id.id),
+                content_metadata: content.clone(),
+                ..Default::default()
+            })
+            .collect();
+        node.create_tasks(tasks, state_change[0].id).await?;
+        node.tombstone_content_batch(
+            &["deleted".to_string(), "deleted_pending".to_string()],
+            TombstoneReason::default(),
+        )
+        .await?;
+
+        let query = |finalized: Option<bool>, tombstoned: Option<bool>, limit: usize| {
+            node.state_machine
+                .query_content(
+                    DEFAULT_TEST_NAMESPACE,
+                    ContentQuery {
+                        finalized,
+                        tombstoned,
+                        limit,
+                    },
+                )
+                .unwrap()
+                .into_iter()
+                .map(|content| content.id.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            query(None, None, 0),
+            vec!["deleted", "deleted_pending", "done", "pending"]
+        );
+        assert_eq!(query(Some(true), None, 0), vec!["deleted", "done"]);
+        assert_eq!(
+            query(None, Some(true), 0),
+            vec!["deleted", "deleted_pending"]
+        );
+        assert_eq!(query(Some(true), Some(false), 0), vec!["done"]);
+        assert_eq!(query(Some(false), Some(false), 0), vec!["pending"]);
+        assert_eq!(query(Some(false), Some(true), 0), vec!["deleted_pending"]);
+        assert_eq!(query(None, Some(false), 1), vec!["done"]);
+        assert!(node
+            .state_machine
+            .query_content("other_namespace", ContentQuery::default())?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_content_from_ids_paginated() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let contents: Vec<ContentMetadata> = (0..50)
+            .map(|i| test_mock_content_metadata(&format!("content_{:02}", i), "", "graph"))
+            .collect();
+        node.create_content_batch(contents).await?;
+        //  Missing content is skipped
+        let content_ids: HashSet<String> = (0..55).map(|i| format!("content_{:02}", i)).collect();
+
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = node.state_machine.get_content_from_ids_paginated(
+                content_ids.clone(),
+                cursor,
+                10,
+            )?;
+            pages.push(page.into_iter().map(|c| c.id.id).collect::<Vec<_>>());
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert!(pages[..5].iter().all(|page| page.len() == 10));
+        let paginated: Vec<String> = pages.concat();
+        let mut expected: Vec<String> = node
+            .state_machine
+            .get_content_from_ids(content_ids)
+            .await?
+            .into_iter()
+            .map(|c| c.id.id)
+            .collect();
+        expected.sort();
+        assert_eq!(expected.len(), 50);
+        assert_eq!(paginated, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_content_by_source() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let make_content = |id: &str, source: ContentSource| ContentMetadata {
+            id: ContentMetadataId::new(id),
+            root_content_id: Some(id.to_string()),
+            source,
+            ..Default::default()
+        };
+        let connector = ContentSource::ExtractionPolicyName("connector".to_string());
+        node.create_content_batch(vec![
+            make_content("ingested_1", ContentSource::Ingestion),
+            make_content("ingested_2", ContentSource::Ingestion),
+            make_content("connector_1", connector.clone()),
+            make_content("connector_2", connector.clone()),
+            make_content("connector_3", connector),
+        ])
+        .await?;
+
+        let namespace = ContentMetadata::default().namespace;
+        let ids_for_source = |source: &str| -> Result<Vec<String>, anyhow::Error> {
+            let mut ids: Vec<_> = node
+                .state_machine
+                .get_content_by_source(&namespace, source)?
+                .into_iter()
+                .map(|c| c.id.id)
+                .collect();
+            ids.sort();
+            Ok(ids)
+        };
+        assert_eq!(ids_for_source("")?, vec!["ingested_1", "ingested_2"]);
+        assert_eq!(
+            ids_for_source("connector")?,
+            vec!["connector_1", "connector_2", "connector_3"]
+        );
+        assert!(ids_for_source("other")?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_policy_coverage() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let policy_id = eg.extraction_policies[0].id.clone();
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+
+        let completed = ContentMetadata {
+            extraction_policy_ids: HashMap::from([(policy_id.clone(), 1)]),
+            ..test_mock_content_metadata("completed", "", &eg.name)
+        };
+        let applied = test_mock_content_metadata("applied", "", &eg.name);
+        let other_graph = test_mock_content_metadata("other_graph", "", "other_graph");
+        node.create_content_batch(vec![completed, applied, other_graph])
+            .await?;
+
+        let coverage = node
+            .state_machine
+            .get_policy_coverage(DEFAULT_TEST_NAMESPACE, &policy_id)?;
+        assert_eq!(coverage.total, 3);
+        assert_eq!(coverage.applied, 2);
+        assert_eq!(coverage.completed, 1);
+        assert_eq!(coverage.applied_fraction(), 2.0 / 3.0);
+        assert_eq!(coverage.completed_fraction(), 1.0 / 3.0);
+
+        assert!(node
+            .state_machine
+            .get_policy_coverage(DEFAULT_TEST_NAMESPACE, "missing_policy")
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_check_content_exists() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let make_content = |id: &str, hash: &str| ContentMetadata {
+            id: ContentMetadataId::new(id),
+            root_content_id: None,
+            hash: hash.to_string(),
+            ..Default::default()
+        };
+        let old_version = ContentMetadata {
+            latest: false,
+            ..make_content("old", "old_hash")
+        };
+        node.create_content_batch(vec![
+            make_content("live", "live_hash"),
+            make_content("deleted", "deleted_hash"),
+            old_version,
+        ])
+        .await?;
+        node.tombstone_content_batch(&["deleted".to_string()], TombstoneReason::default())
+            .await?;
+
+        let namespace = ContentMetadata::default().namespace;
+        let keys = vec![
+            ContentLookupKey::Id("live".to_string()),
+            ContentLookupKey::Hash("live_hash".to_string()),
+            ContentLookupKey::Id("deleted".to_string()),
+            ContentLookupKey::Hash("deleted_hash".to_string()),
+            ContentLookupKey::Hash("old_hash".to_string()),
+            ContentLookupKey::Id("missing".to_string()),
+            ContentLookupKey::Hash("missing_hash".to_string()),
+        ];
+        let live = ContentExistence::Exists {
+            content_id: "live".to_string(),
+            version: 1,
+            hash: "live_hash".to_string(),
+        };
+        let deleted = ContentExistence::Tombstoned {
+            content_id: "deleted".to_string(),
+        };
+        let results = node.check_content_exists(&namespace, &keys)?;
+        assert_eq!(
+            results,
+            vec![
+                live.clone(),
+                live,
+                deleted.clone(),
+                deleted,
+                ContentExistence::Unknown,
+                ContentExistence::Unknown,
+                ContentExistence::Unknown,
+            ]
+        );
+
+        let results = node.check_content_exists("other_namespace", &keys[..1])?;
+        assert_eq!(results, vec![ContentExistence::Unknown]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_create_read_and_match_extraction_policies() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        //  Create an executor and associated extractor
+        let executor_id = "executor_id";
+        let mut extractor = mock_extractor();
+        extractor.input_mime_types = vec!["*/*".into()];
+        let addr = "addr";
+        node.register_executor(addr, executor_id, vec![extractor.clone()])
+            .await?;
+
+        let mut eg = create_test_extraction_graph("graph1", vec!["policy1"]);
 
         eg.extraction_policies[0].filters = HashMap::from([
             ("label1".to_string(), "value1".to_string()),
@@ -1729,8 +3528,12 @@ mod tests {
             ("label3".to_string(), "value3".to_string()),
         ]);
 
-        node.create_extraction_graph(eg.clone(), StructuredDataSchema::default(), vec![])
-            .await?;
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
 
         //  Read the policy back using namespace
         let read_policy = node.list_extraction_policy(&eg.namespace).await?;
@@ -1740,29 +3543,144 @@ mod tests {
         let read_policy = node.get_extraction_policy(&eg.extraction_policies[0].id)?;
         assert_eq!(read_policy, eg.extraction_policies[0]);
 
-        //  Create some content
-        let content_labels = vec![
-            ("label1".to_string(), "value1".to_string()),
-            ("label2".to_string(), "value2".to_string()),
-            ("label3".to_string(), "value3".to_string()),
-        ];
-        let mut content_metadata = test_mock_content_metadata("test_content_id1", "", &eg.name);
-        content_metadata.labels = content_labels.into_iter().collect();
-        content_metadata.extraction_graph_names = vec![eg.name];
-        node.create_content_batch(vec![content_metadata.clone()])
-            .await?;
+        //  Create some content
+        let content_labels = vec![
+            ("label1".to_string(), "value1".to_string()),
+            ("label2".to_string(), "value2".to_string()),
+            ("label3".to_string(), "value3".to_string()),
+        ];
+        let mut content_metadata = test_mock_content_metadata("test_content_id1", "", &eg.name);
+        content_metadata.labels = content_labels.into_iter().collect();
+        content_metadata.extraction_graph_names = vec![eg.name];
+        node.create_content_batch(vec![content_metadata.clone()])
+            .await?;
+
+        //  Fetch the policy based on the content id and check that the retrieved policy
+        // is correct
+        let matched_policies = node
+            .match_extraction_policies_for_content(&content_metadata)
+            .await?;
+        assert_eq!(matched_policies.len(), 1);
+        assert_eq!(
+            matched_policies.first().unwrap(),
+            &eg.extraction_policies[0]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_match_extraction_policies_on_language() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let mut extractor = mock_extractor();
+        extractor.input_mime_types = vec!["*/*".into()];
+        node.register_executor("addr", "executor_id", vec![extractor])
+            .await?;
+
+        //  One embedding model per group of languages
+        let mut eg = create_test_extraction_graph("graph", vec!["european", "french"]);
+        eg.extraction_policies[0].filters =
+            HashMap::from([("language".to_string(), "eng|deu".to_string())]);
+        eg.extraction_policies[1].filters =
+            HashMap::from([("language".to_string(), "fra".to_string())]);
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+
+        let content = |id: &str, language: Option<&str>| {
+            let mut content = test_mock_content_metadata(id, "", &eg.name);
+            if let Some(language) = language {
+                content.labels = HashMap::from([("language".to_string(), language.to_string())]);
+            }
+            content
+        };
+        let matched = |content| {
+            let node = node.clone();
+            async move {
+                node.match_extraction_policies_for_content(&content)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|policy| policy.name)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(matched(content("en", Some("eng"))).await, vec!["european"]);
+        assert_eq!(matched(content("de", Some("deu"))).await, vec!["european"]);
+        assert_eq!(matched(content("fr", Some("fra"))).await, vec!["french"]);
+        assert!(matched(content("es", Some("spa"))).await.is_empty());
+        //  Content whose language wasn't detected
+        assert!(matched(content("unknown", None)).await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_namespace_alias_cutover() -> Result<(), anyhow::Error> {
+        use std::sync::atomic::{AtomicBool, Ordering};
 
-        //  Fetch the policy based on the content id and check that the retrieved policy
-        // is correct
-        let matched_policies = node
-            .match_extraction_policies_for_content(&content_metadata)
-            .await?;
-        assert_eq!(matched_policies.len(), 1);
-        assert_eq!(
-            matched_policies.first().unwrap(),
-            &eg.extraction_policies[0]
-        );
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace("blue").await?;
+        node.create_namespace("green").await?;
+        node.set_namespace_alias("prod", "blue").await?;
+        assert_eq!(node.resolve_namespace("prod")?, "blue");
+        //  Names which aren't aliases resolve to themselves
+        assert_eq!(node.resolve_namespace("green")?, "green");
+
+        //  Requests resolving the alias while it moves see one namespace or
+        //  the other, and only the new one once they have seen it
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let node = node.clone();
+                let done = done.clone();
+                tokio::spawn(async move {
+                    let mut resolved = Vec::new();
+                    while !done.load(Ordering::SeqCst) {
+                        resolved.push(node.resolve_namespace("prod").unwrap());
+                        tokio::task::yield_now().await;
+                    }
+                    resolved
+                })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        node.set_namespace_alias("prod", "green").await?;
+        done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            let resolved = reader.await?;
+            let moved = resolved
+                .iter()
+                .position(|namespace| namespace == "green")
+                .unwrap_or(resolved.len());
+            assert!(resolved[..moved]
+                .iter()
+                .all(|namespace| namespace == "blue"));
+            assert!(resolved[moved..]
+                .iter()
+                .all(|namespace| namespace == "green"));
+        }
+        assert_eq!(node.resolve_namespace("prod")?, "green");
 
+        //  Aliases can't shadow namespaces or point at missing ones, and
+        //  namespaces can't take the name of an alias
+        assert!(node.set_namespace_alias("blue", "green").await.is_err());
+        assert!(node
+            .set_namespace_alias("staging", "missing")
+            .await
+            .is_err());
+        assert!(node.create_namespace("prod").await.is_err());
+        assert_eq!(node.resolve_namespace("blue")?, "blue");
+        assert_eq!(node.resolve_namespace("prod")?, "green");
         Ok(())
     }
 
@@ -1828,12 +3746,525 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reject_content_parent_change() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+        let root_1 = test_mock_content_metadata("root_1", "", &eg.name);
+        let root_2 = test_mock_content_metadata("root_2", "", &eg.name);
+        let mut child = test_mock_content_metadata("child", "root_1", &eg.name);
+        child.parent_id = Some(root_1.id.clone());
+        node.create_content_batch(vec![root_1.clone(), root_2.clone()])
+            .await?;
+        node.create_content_batch(vec![child]).await?;
+
+        //  Move the child under another root without declaring the previous parent
+        let mut child = node
+            .state_machine
+            .get_latest_version_of_content("child")?
+            .unwrap();
+        child.parent_id = Some(root_2.id.clone());
+        child.root_content_id = Some(root_2.id.id.clone());
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateOrUpdateContent {
+                entries: vec![CreateOrUpdateContentEntry {
+                    content: child,
+                    previous_parent: None,
+                }],
+                ingestion_key: None,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        let err = node.forwardable_raft.client_write(req).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Immutable field parent_id of content child"));
+
+        let child = node
+            .state_machine
+            .get_latest_version_of_content("child")?
+            .unwrap();
+        assert_eq!(child.parent_id, Some(root_1.id));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_resume_gc_task() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+        let content = test_mock_content_metadata("content", "", &eg.name);
+        node.create_content_batch(vec![content.clone()]).await?;
+
+        let mut rx = node.subscribe_to_gc_task_events().await;
+        let gc_task = GarbageCollectionTask::new(
+            DEFAULT_TEST_NAMESPACE,
+            content.clone(),
+            HashSet::from(["index_1".to_string(), "index_2".to_string()]),
+            ServerTaskType::Delete,
+        );
+        node.create_gc_tasks(vec![gc_task.clone()]).await?;
+        assert_eq!(rx.recv().await?.id, gc_task.id);
+
+        //  The progress is recorded and the task is sent again
+        let mut resumed = gc_task.clone();
+        resumed.completed_output_tables = HashSet::from(["index_1".to_string()]);
+        node.update_gc_task(resumed.clone()).await?;
+        assert_eq!(rx.recv().await?, resumed);
+        assert_eq!(node.gc_task_with_id(&gc_task.id).await?, resumed);
+        assert!(node
+            .state_machine
+            .get_latest_version_of_content("content")?
+            .is_some());
+
+        //  Finished tasks aren't sent again
+        node.update_gc_task(GarbageCollectionTask {
+            outcome: TaskOutcome::Success,
+            ..resumed
+        })
+        .await?;
+        assert_eq!(
+            node.gc_task_with_id(&gc_task.id).await?.outcome,
+            TaskOutcome::Success
+        );
+        assert!(rx.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reject_parent_in_other_namespace() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        node.create_namespace("other").await?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+        let root = test_mock_content_metadata("root", "", &eg.name);
+        node.create_content_batch(vec![root.clone()]).await?;
+
+        let mut child = test_mock_content_metadata("child", "root", &eg.name);
+        child.parent_id = Some(root.id.clone());
+        child.namespace = "other".to_string();
+        child.extraction_graph_names = vec![];
+        let err = node
+            .create_content_batch(vec![child.clone()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "Content child in namespace other can't have parent root in namespace test_namespace"
+        ));
+        assert!(node
+            .state_machine
+            .get_latest_version_of_content("child")?
+            .is_none());
+
+        //  The parent is also resolved from the same request
+        let mut other_root = test_mock_content_metadata("other_root", "", &eg.name);
+        other_root.namespace = "other".to_string();
+        let mut child = test_mock_content_metadata("other_child", "other_root", &eg.name);
+        child.parent_id = Some(other_root.id.clone());
+        let req = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateOrUpdateContent {
+                entries: [other_root, child]
+                    .into_iter()
+                    .map(|content| CreateOrUpdateContentEntry {
+                        content,
+                        previous_parent: None,
+                    })
+                    .collect(),
+                ingestion_key: None,
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        let err = node.forwardable_raft.client_write(req).await.unwrap_err();
+        assert!(err.to_string().contains("can't have parent other_root"));
+
+        //  Children in the namespace of their parent are accepted
+        let mut child = test_mock_content_metadata("child", "root", &eg.name);
+        child.parent_id = Some(root.id.clone());
+        node.create_content_batch(vec![child]).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_content_subtree() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        //  root -> level_1 -> level_2 -> level_3
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let mut parent = test_mock_content_metadata("root", "", &eg.name);
+        node.create_content_batch(vec![parent.clone()]).await?;
+        for id in ["level_1", "level_2", "level_3"] {
+            let mut content = test_mock_content_metadata(id, "root", &eg.name);
+            content.parent_id = Some(parent.id.clone());
+            node.create_content_batch(vec![content.clone()]).await?;
+            parent = content;
+        }
+
+        let mut subtree: Vec<(String, u32)> = node
+            .state_machine
+            .get_content_subtree("root", 2)?
+            .into_iter()
+            .map(|(content, depth)| (content.id.id, depth))
+            .collect();
+        subtree.sort();
+        assert_eq!(
+            subtree,
+            vec![
+                ("level_1".to_string(), 1),
+                ("level_2".to_string(), 2),
+                ("root".to_string(), 0),
+            ]
+        );
+        assert_eq!(node.state_machine.get_content_subtree("root", 0)?.len(), 1);
+        assert_eq!(node.state_machine.get_content_subtree("root", 5)?.len(), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_content_for_task() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let root = test_mock_content_metadata("root", "", &eg.name);
+        node.create_content_batch(vec![root.clone()]).await?;
+        let mut task = indexify_internal_api::Task {
+            id: "task_1".into(),
+            extractor: DEFAULT_TEST_EXTRACTOR.into(),
+            content_metadata: root.clone(),
+            ..Default::default()
+        };
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks {
+                tasks: vec![task.clone()],
+            },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+        assert!(node
+            .state_machine
+            .get_content_for_task("task_1")?
+            .is_empty());
+
+        for id in ["output_1", "output_2"] {
+            let mut content = test_mock_content_metadata(id, "root", &eg.name);
+            content.parent_id = Some(root.id.clone());
+            node.create_content_batch(vec![content]).await?;
+        }
+        task.outcome = TaskOutcome::Success;
+        task.output_content_ids = vec!["output_1".into(), "output_2".into()];
+        node.update_task(task, Some("executor_1".into())).await?;
+
+        let mut outputs: Vec<String> = node
+            .state_machine
+            .get_content_for_task("task_1")?
+            .into_iter()
+            .map(|content| content.id.id)
+            .collect();
+        outputs.sort();
+        assert_eq!(
+            outputs,
+            vec!["output_1".to_string(), "output_2".to_string()]
+        );
+        assert!(node.state_machine.get_content_for_task("task_2").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reject_writes_to_missing_namespace() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let err = node
+            .create_extraction_graph(
+                eg.clone(),
+                StructuredDataSchema::new(&eg.name, &eg.namespace),
+                vec![],
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Namespace not found"));
+        assert!(node
+            .list_extraction_policy(DEFAULT_TEST_NAMESPACE)
+            .await?
+            .is_empty());
+
+        let content = ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            ..Default::default()
+        };
+        let err = node
+            .create_content_batch(vec![content.clone()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Namespace not found"));
+        assert!(node
+            .state_machine
+            .get_latest_version_of_content("content_id")?
+            .is_none());
+
+        let index = indexify_internal_api::Index {
+            id: "index_id".into(),
+            namespace: DEFAULT_TEST_NAMESPACE.into(),
+            ..Default::default()
+        };
+        let err = node.set_indexes(vec![index.clone()]).await.unwrap_err();
+        assert!(err.to_string().contains("Namespace not found"));
+        assert!(node.get_index("index_id").await.is_err());
+
+        //  The same writes are accepted once the namespace exists
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let schema = StructuredDataSchema::new(&eg.name, &eg.namespace);
+        node.create_extraction_graph(eg, schema, vec![]).await?;
+        node.create_content_batch(vec![content]).await?;
+        node.set_indexes(vec![index.clone()]).await?;
+        assert_eq!(node.get_index("index_id").await?, index);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_namespace_with_dangling_extraction_policy() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+
+        let namespace = DEFAULT_TEST_NAMESPACE;
+        node.create_namespace(namespace).await?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let structured_schema = StructuredDataSchema::new(&eg.name, &eg.namespace);
+        node.create_extraction_graph(eg.clone(), structured_schema, vec![])
+            .await?;
+
+        //  Point the reverse index at a policy that doesn't exist
+        let reverse_index = &node
+            .state_machine
+            .data
+            .indexify_state
+            .extraction_policies_table;
+        reverse_index.insert(&namespace.to_string(), "dangling_policy");
+
+        let lookup = node
+            .get_extraction_policies_from_ids(reverse_index.get(&namespace.to_string()))
+            .await?;
+        assert!(lookup.missing.contains("dangling_policy"));
+
+        //  Without repair the warning is reported on every read
+        let ns = node.namespace(namespace).await?.unwrap();
+        assert_eq!(ns.warnings.len(), 1);
+        assert!(ns.warnings[0].contains("dangling_policy"));
+        assert!(reverse_index
+            .get(&namespace.to_string())
+            .contains("dangling_policy"));
+
+        //  The repair removes the dangling id from the reverse index
+        node.remove_dangling_extraction_policies(namespace).await?;
+        assert!(!reverse_index
+            .get(&namespace.to_string())
+            .contains("dangling_policy"));
+        let ns = node.namespace(namespace).await?.unwrap();
+        assert!(ns.warnings.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_create_namespace_with_default_schema() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let default_schema = StructuredDataSchema::empty_for(DEFAULT_TEST_NAMESPACE);
+        assert_eq!(
+            default_schema.id,
+            StructuredDataSchema::empty_for(DEFAULT_TEST_NAMESPACE).id
+        );
+        assert_ne!(
+            default_schema.id,
+            StructuredDataSchema::empty_for("other").id
+        );
+        assert_eq!(
+            node.get_structured_data_schema(DEFAULT_TEST_NAMESPACE, DEFAULT_SCHEMA_NAME)
+                .await?,
+            default_schema
+        );
+        assert_eq!(
+            node.get_schemas_for_namespace(DEFAULT_TEST_NAMESPACE)
+                .await?,
+            vec![default_schema.clone()]
+        );
+
+        //  Creating the namespace again keeps the columns added to the schema
+        let eg = create_test_extraction_graph(DEFAULT_SCHEMA_NAME, vec![]);
+        let mut schema = default_schema.clone();
+        schema.columns.insert(
+            "label".to_string(),
+            indexify_internal_api::SchemaColumnType::Text.into(),
+        );
+        node.create_extraction_graph(eg, schema.clone(), vec![])
+            .await?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        assert_eq!(
+            node.get_structured_data_schema(DEFAULT_TEST_NAMESPACE, DEFAULT_SCHEMA_NAME)
+                .await?,
+            schema
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_delete_schema() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let referenced_eg = create_test_extraction_graph("graph1", vec!["policy1"]);
+        let referenced_schema =
+            StructuredDataSchema::new(&referenced_eg.name, &referenced_eg.namespace);
+        node.create_extraction_graph(referenced_eg, referenced_schema.clone(), vec![])
+            .await?;
+        let unreferenced_eg = create_test_extraction_graph("graph2", vec![]);
+        let unreferenced_schema =
+            StructuredDataSchema::new(&unreferenced_eg.name, &unreferenced_eg.namespace);
+        node.create_extraction_graph(unreferenced_eg, unreferenced_schema.clone(), vec![])
+            .await?;
+        //  Along with the namespace's default schema
+        assert_eq!(
+            node.get_schemas_for_namespace(DEFAULT_TEST_NAMESPACE)
+                .await?
+                .len(),
+            3
+        );
+
+        //  A schema which no extraction policy writes to is removed
+        node.delete_schema(DEFAULT_TEST_NAMESPACE, &unreferenced_schema.id)
+            .await?;
+        assert!(node
+            .get_structured_data_schema(DEFAULT_TEST_NAMESPACE, "graph2")
+            .await
+            .is_err());
+        let schemas = node
+            .get_schemas_for_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?;
+        assert_eq!(schemas.len(), 2);
+        assert!(schemas.iter().any(|s| s.id == referenced_schema.id));
+        assert!(!schemas.iter().any(|s| s.id == unreferenced_schema.id));
+
+        //  A schema which is still referenced is kept
+        let err = node
+            .delete_schema(DEFAULT_TEST_NAMESPACE, &referenced_schema.id)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("referenced by extraction policies"));
+        assert_eq!(
+            node.get_structured_data_schema(DEFAULT_TEST_NAMESPACE, "graph1")
+                .await?,
+            referenced_schema
+        );
+        assert_eq!(
+            node.get_schemas_for_namespace(DEFAULT_TEST_NAMESPACE)
+                .await?
+                .len(),
+            2
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reject_invalid_schema_id() -> Result<(), anyhow::Error> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        for schema_id in ["", "graph/../schema"] {
+            let schema = StructuredDataSchema {
+                id: schema_id.to_string(),
+                ..StructuredDataSchema::new(&eg.name, &eg.namespace)
+            };
+            let err = node
+                .create_extraction_graph(eg.clone(), schema, vec![])
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("Invalid schema id"));
+        }
+
+        //  Neither the graph nor the schemas were written
+        assert!(node
+            .list_extraction_policy(DEFAULT_TEST_NAMESPACE)
+            .await?
+            .is_empty());
+        let schemas = node
+            .get_schemas_for_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?;
+        assert_eq!(schemas.len(), 1);
+        assert!(schemas.iter().all(|s| !s.id.is_empty()));
+        Ok(())
+    }
+
     #[tokio::test]
     // #[tracing_test::traced_test]
     async fn test_filter_extraction_policies_for_content() -> Result<(), anyhow::Error> {
         let cluster = RaftTestCluster::new(1, None).await?;
         cluster.initialize(Duration::from_secs(2)).await?;
         let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
 
         let _namespace = "namespace";
 
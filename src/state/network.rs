@@ -15,8 +15,13 @@ use openraft::{
     BasicNode,
 };
 use tonic::IntoRequest;
+use tracing::warn;
 
-use super::store::requests::StateMachineUpdateResponse;
+use super::store::{
+    requests::StateMachineUpdateResponse,
+    snapshot_chunks::{InstallSnapshotChunksRequest, SnapshotManifest},
+    StateMachineStore,
+};
 use crate::{
     grpc_helper::GrpcHelper,
     metrics::{
@@ -35,12 +40,15 @@ use crate::{
 
 pub struct Network {
     raft_client: Arc<RaftClient>,
+    /// Used to send followers only the snapshot chunks they are missing. The
+    /// full snapshot is sent when it is not set.
+    state_machine: Option<Arc<StateMachineStore>>,
 }
 
 impl Default for Network {
     fn default() -> Self {
         let raft_client = Arc::new(RaftClient::new());
-        Self::new(raft_client)
+        Self::new(raft_client, None)
     }
 }
 
@@ -48,13 +56,20 @@ impl Clone for Network {
     fn clone(&self) -> Self {
         Network {
             raft_client: Arc::clone(&self.raft_client),
+            state_machine: self.state_machine.clone(),
         }
     }
 }
 
 impl Network {
-    pub fn new(raft_client: Arc<RaftClient>) -> Self {
-        Self { raft_client }
+    pub fn new(
+        raft_client: Arc<RaftClient>,
+        state_machine: Option<Arc<StateMachineStore>>,
+    ) -> Self {
+        Self {
+            raft_client,
+            state_machine,
+        }
     }
 
     /// This method is used when a state machine request was received by a
@@ -116,6 +131,7 @@ impl Network {
             },
             new_state_changes: vec![],
             state_changes_processed: vec![],
+            idempotency_key: None,
         })?
         .into_request();
 
@@ -148,6 +164,8 @@ impl RaftNetworkFactory<TypeConfig> for Network {
             target,
             target_node: node.clone(),
             raft_client: self.raft_client.clone(),
+            state_machine: self.state_machine.clone(),
+            chunked_snapshot_sent: None,
         }
     }
 }
@@ -156,6 +174,11 @@ pub struct NetworkConnection {
     target: NodeId,
     target_node: BasicNode,
     raft_client: Arc<RaftClient>,
+    state_machine: Option<Arc<StateMachineStore>>,
+    /// Snapshot id and response of the last snapshot installed by sending
+    /// only the missing chunks. The remaining chunks raft streams for that
+    /// snapshot are acknowledged with this response without being sent.
+    chunked_snapshot_sent: Option<(String, InstallSnapshotResponse<NodeId>)>,
 }
 
 impl NetworkConnection {
@@ -171,6 +194,91 @@ impl NetworkConnection {
         let remote_err = RemoteError::new_with_node(self.target, self.target_node.clone(), e);
         RPCError::RemoteError(remote_err)
     }
+
+    /// Installs the snapshot on the target by sending only the chunks that
+    /// are not part of the target's current snapshot. Returns None when the
+    /// full snapshot has to be streamed instead, e.g. because the target
+    /// does not support chunk transfer or the manifests are incompatible.
+    async fn install_missing_snapshot_chunks(
+        &mut self,
+        req: &InstallSnapshotRequest<TypeConfig>,
+    ) -> Option<InstallSnapshotResponse<NodeId>> {
+        if let Some((snapshot_id, resp)) = &self.chunked_snapshot_sent {
+            if req.offset > 0 && *snapshot_id == req.meta.snapshot_id {
+                return Some(resp.clone());
+            }
+        }
+        self.chunked_snapshot_sent = None;
+        if req.offset != 0 {
+            return None;
+        }
+
+        let (meta, snapshot) = match self.state_machine.as_ref()?.current_chunked_snapshot() {
+            Ok(Some(current)) => current,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!("unable to read snapshot chunks: {}", e);
+                return None;
+            }
+        };
+        if meta.snapshot_id != req.meta.snapshot_id {
+            return None;
+        }
+
+        let mut client = self.raft_client.get(&self.target_node.addr).await.ok()?;
+        let manifest_req = GrpcHelper::encode_raft_request(&()).ok()?;
+        let target_manifest = match client
+            .get_snapshot_chunks(GrpcHelper::into_req(manifest_req))
+            .await
+        {
+            Ok(resp) => {
+                serde_json::from_str::<Option<SnapshotManifest>>(&resp.into_inner().data).ok()?
+            }
+            Err(e) => {
+                warn!(
+                    "unable to get snapshot chunks from {}, sending full snapshot: {}",
+                    self.target_node.addr, e
+                );
+                return None;
+            }
+        };
+        let target_manifest = target_manifest
+            .filter(|target_manifest| snapshot.manifest.is_compatible(target_manifest))?;
+
+        let missing = snapshot.manifest.missing_from(&target_manifest);
+        let chunks = snapshot.chunks_for(&missing);
+        let chunks_req = GrpcHelper::encode_raft_request(&InstallSnapshotChunksRequest {
+            vote: req.vote,
+            meta: req.meta.clone(),
+            manifest: snapshot.manifest.clone(),
+            chunks,
+        })
+        .ok()?;
+        raft_metrics::network::incr_sent_bytes(
+            &self.target_node.addr,
+            chunks_req.data.len() as u64,
+        );
+
+        let resp = match client
+            .install_snapshot_chunks(GrpcHelper::into_req(chunks_req))
+            .await
+        {
+            Ok(resp) => {
+                serde_json::from_str::<InstallSnapshotResponse<NodeId>>(&resp.into_inner().data)
+                    .ok()?
+            }
+            Err(e) => {
+                warn!(
+                    "unable to install snapshot chunks on {}, sending full snapshot: {}",
+                    self.target_node.addr, e
+                );
+                return None;
+            }
+        };
+        raft_metrics::network::incr_snapshot_send_success(&self.target_node.addr);
+        self.chunked_snapshot_sent = Some((req.meta.snapshot_id.clone(), resp.clone()));
+        Some(resp)
+    }
 }
 
 impl RaftNetwork<TypeConfig> for NetworkConnection {
@@ -214,6 +322,10 @@ impl RaftNetwork<TypeConfig> for NetworkConnection {
             raft_metrics::network::incr_snapshot_send_inflight(addr, cnt);
         });
 
+        if let Some(resp) = self.install_missing_snapshot_chunks(&req).await {
+            return Ok(resp);
+        }
+
         let mut client = self
             .raft_client
             .get(&self.target_node.addr)
@@ -0,0 +1,246 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Mutex,
+    time::Instant,
+};
+
+use opentelemetry::{metrics::Histogram, KeyValue};
+use serde::Serialize;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Parts of the coordinator which become ready independently of each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ReadinessComponent {
+    /// Serves reads once the reverse indexes are rebuilt from the snapshot
+    /// and the log
+    StateMachine,
+    /// Serves scheduling requests once the scheduler of the leader caught up
+    /// with the state changes
+    Scheduler,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ReadinessState {
+    /// The snapshot is being installed and the log replayed
+    Replaying,
+    /// The reverse indexes are being spot checked against the forward indexes
+    Checking,
+    /// Waiting for another component to become ready
+    Waiting {
+        reason: String,
+    },
+    /// Another node is the leader, it serves the scheduling requests
+    Standby,
+    Ready,
+    /// The reverse indexes failed the spot check, the node doesn't become
+    /// ready
+    Failed {
+        reason: String,
+    },
+}
+
+impl ReadinessState {
+    pub fn accepts_requests(&self) -> bool {
+        matches!(self, ReadinessState::Ready | ReadinessState::Standby)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub components: BTreeMap<ReadinessComponent, ReadinessState>,
+}
+
+/// Tracks the readiness of the components of the coordinator after it
+/// starts. Requests which need a component which isn't ready are rejected
+/// with `Unavailable`, which clients retry.
+///
+/// The state machine goes from replaying to checking and then to ready or
+/// failed. The scheduler waits for the state machine, then is ready on the
+/// leader once it ran a scheduling pass, and on standby on the followers.
+pub struct Readiness {
+    started_at: Instant,
+    states: Mutex<BTreeMap<ReadinessComponent, ReadinessState>>,
+    ready_once: Mutex<HashSet<ReadinessComponent>>,
+    changes_tx: watch::Sender<()>,
+    time_to_ready: Histogram<f64>,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        let meter = opentelemetry::global::meter("indexify-coordinator");
+        let time_to_ready = meter
+            .f64_histogram("indexify.coordinator.time_to_ready")
+            .with_description("Seconds from startup until each component was first ready")
+            .init();
+        let (changes_tx, _) = watch::channel(());
+        Self {
+            started_at: Instant::now(),
+            states: Mutex::new(BTreeMap::from([
+                (ReadinessComponent::StateMachine, ReadinessState::Replaying),
+                (
+                    ReadinessComponent::Scheduler,
+                    ReadinessState::Waiting {
+                        reason: "the state machine isn't ready".to_string(),
+                    },
+                ),
+            ])),
+            ready_once: Mutex::new(HashSet::new()),
+            changes_tx,
+            time_to_ready,
+        }
+    }
+
+    /// Notified whenever the state of a component changes
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.changes_tx.subscribe()
+    }
+
+    pub fn state(&self, component: ReadinessComponent) -> ReadinessState {
+        self.states.lock().unwrap()[&component].clone()
+    }
+
+    pub fn is_ready(&self, component: ReadinessComponent) -> bool {
+        self.state(component) == ReadinessState::Ready
+    }
+
+    /// The node is ready once it serves reads, the scheduler may still be
+    /// catching up
+    pub fn report(&self) -> ReadinessReport {
+        let components = self.states.lock().unwrap().clone();
+        ReadinessReport {
+            ready: components[&ReadinessComponent::StateMachine].accepts_requests(),
+            components,
+        }
+    }
+
+    fn set(&self, component: ReadinessComponent, state: ReadinessState) {
+        {
+            let mut states = self.states.lock().unwrap();
+            if states.get(&component) == Some(&state) {
+                return;
+            }
+            info!("coordinator {} is {:?}", component, state);
+            states.insert(component, state.clone());
+        }
+        if state == ReadinessState::Ready && self.ready_once.lock().unwrap().insert(component) {
+            self.time_to_ready.record(
+                self.started_at.elapsed().as_secs_f64(),
+                &[KeyValue::new("component", component.to_string())],
+            );
+        }
+        self.changes_tx.send_replace(());
+    }
+
+    pub fn replay_started(&self) {
+        self.set(ReadinessComponent::StateMachine, ReadinessState::Replaying);
+    }
+
+    pub fn replay_finished(&self) {
+        self.set(ReadinessComponent::StateMachine, ReadinessState::Checking);
+    }
+
+    pub fn check_passed(&self) {
+        self.set(ReadinessComponent::StateMachine, ReadinessState::Ready);
+    }
+
+    pub fn check_failed(&self, reason: &str) {
+        error!("reverse indexes failed the spot check: {}", reason);
+        self.set(
+            ReadinessComponent::StateMachine,
+            ReadinessState::Failed {
+                reason: reason.to_string(),
+            },
+        );
+    }
+
+    pub fn scheduler_ready(&self) {
+        self.set(ReadinessComponent::Scheduler, ReadinessState::Ready);
+    }
+
+    pub fn scheduler_standby(&self) {
+        self.set(ReadinessComponent::Scheduler, ReadinessState::Standby);
+    }
+
+    pub fn scheduler_waiting(&self, reason: &str) {
+        self.set(
+            ReadinessComponent::Scheduler,
+            ReadinessState::Waiting {
+                reason: reason.to_string(),
+            },
+        );
+    }
+
+    /// Returns `Unavailable` unless every component accepts requests
+    pub fn check(&self, components: &[ReadinessComponent]) -> Result<(), tonic::Status> {
+        let states = self.states.lock().unwrap();
+        for component in components {
+            let state = &states[component];
+            if !state.accepts_requests() {
+                return Err(tonic::Status::unavailable(format!(
+                    "coordinator is not ready, {} is {:?}",
+                    component, state
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_transitions() {
+        let readiness = Readiness::new();
+        let mut changes_rx = readiness.subscribe();
+        let all = [
+            ReadinessComponent::StateMachine,
+            ReadinessComponent::Scheduler,
+        ];
+        let err = readiness.check(&all).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unavailable);
+        assert!(!readiness.report().ready);
+
+        readiness.replay_finished();
+        assert!(changes_rx.has_changed().unwrap());
+        changes_rx.borrow_and_update();
+        assert!(readiness.check(&all[..1]).is_err());
+
+        //  Reads are served before the scheduler caught up
+        readiness.check_passed();
+        assert!(readiness.check(&all[..1]).is_ok());
+        assert!(readiness.check(&all).is_err());
+        assert!(readiness.report().ready);
+
+        readiness.scheduler_standby();
+        assert!(readiness.check(&all).is_ok());
+        readiness.scheduler_ready();
+        assert!(readiness.check(&all).is_ok());
+
+        //  Setting the current state again doesn't notify
+        changes_rx.borrow_and_update();
+        readiness.scheduler_ready();
+        assert!(!changes_rx.has_changed().unwrap());
+
+        readiness.check_failed("content 1 is missing");
+        assert_eq!(
+            readiness.state(ReadinessComponent::StateMachine),
+            ReadinessState::Failed {
+                reason: "content 1 is missing".to_string()
+            }
+        );
+        assert!(readiness.check(&all[..1]).is_err());
+    }
+}
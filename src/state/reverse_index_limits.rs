@@ -0,0 +1,174 @@
+use std::{collections::HashSet, sync::Mutex};
+
+use indexify_internal_api::StateChangeId;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use super::store::state_machine_objects::ReverseIndexSize;
+use crate::server_config::ReverseIndexLimitsConfig;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReverseIndexLimitEvent {
+    /// A reverse index went over its soft limit, content ingestion is
+    /// throttled until it is back under the limit
+    SoftLimitExceeded { size: ReverseIndexSize },
+
+    /// A reverse index which was over its soft limit is back under it
+    SoftLimitCleared { name: &'static str },
+
+    /// Tasks for a state change were not created because there are too many
+    /// unassigned tasks. The state change stays unprocessed.
+    TaskCreationDeferred {
+        state_change_id: StateChangeId,
+        unassigned_tasks: usize,
+    },
+}
+
+/// Checks the sizes of the in-memory reverse indexes against the configured
+/// limits and publishes an event whenever a limit is crossed
+pub struct ReverseIndexLimits {
+    config: ReverseIndexLimitsConfig,
+    over_soft_limit: Mutex<HashSet<&'static str>>,
+    events_tx: broadcast::Sender<ReverseIndexLimitEvent>,
+}
+
+impl ReverseIndexLimits {
+    pub fn new(config: ReverseIndexLimitsConfig) -> Self {
+        let (events_tx, _) = broadcast::channel(100);
+        Self {
+            config,
+            over_soft_limit: Mutex::new(HashSet::new()),
+            events_tx,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ReverseIndexLimitEvent> {
+        self.events_tx.subscribe()
+    }
+
+    fn send(&self, event: ReverseIndexLimitEvent) {
+        //  Sending only fails when there are no subscribers
+        let _ = self.events_tx.send(event);
+    }
+
+    fn over_soft_limit(&self, size: &ReverseIndexSize) -> bool {
+        self.config
+            .soft_limit_entries
+            .is_some_and(|limit| size.entries > limit) ||
+            self.config
+                .soft_limit_bytes
+                .is_some_and(|limit| size.estimated_bytes > limit)
+    }
+
+    /// Returns the names of the reverse indexes over their soft limit.
+    /// Events are only published when an index crosses the limit.
+    pub fn check_soft_limits(&self, sizes: &[ReverseIndexSize]) -> Vec<&'static str> {
+        let mut over_soft_limit = self.over_soft_limit.lock().unwrap();
+        let mut names = Vec::new();
+        for size in sizes {
+            if self.over_soft_limit(size) {
+                names.push(size.name);
+                if over_soft_limit.insert(size.name) {
+                    warn!(
+                        "reverse index {} is over its soft limit with {} entries, {} bytes",
+                        size.name, size.entries, size.estimated_bytes
+                    );
+                    self.send(ReverseIndexLimitEvent::SoftLimitExceeded { size: *size });
+                }
+            } else if over_soft_limit.remove(size.name) {
+                info!("reverse index {} is back under its soft limit", size.name);
+                self.send(ReverseIndexLimitEvent::SoftLimitCleared { name: size.name });
+            }
+        }
+        names
+    }
+
+    /// Returns the names of the reverse indexes over their soft limit as of
+    /// the last check
+    pub fn throttled_indexes(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self
+            .over_soft_limit
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Returns true when no tasks should be created for the state change
+    /// because of the number of unassigned tasks
+    pub fn defer_task_creation(
+        &self,
+        state_change_id: StateChangeId,
+        unassigned_tasks: usize,
+    ) -> bool {
+        let Some(limit) = self.config.unassigned_tasks_hard_limit else {
+            return false;
+        };
+        if unassigned_tasks < limit {
+            return false;
+        }
+        warn!(
+            "deferring task creation for state change {}, {} unassigned tasks",
+            state_change_id, unassigned_tasks
+        );
+        self.send(ReverseIndexLimitEvent::TaskCreationDeferred {
+            state_change_id,
+            unassigned_tasks,
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(name: &'static str, entries: usize) -> ReverseIndexSize {
+        ReverseIndexSize {
+            name,
+            entries,
+            estimated_bytes: entries * 10,
+        }
+    }
+
+    #[test]
+    fn test_soft_limit_events() {
+        let limits = ReverseIndexLimits::new(ReverseIndexLimitsConfig {
+            soft_limit_entries: Some(2),
+            ..Default::default()
+        });
+        let mut events = limits.subscribe();
+
+        assert!(limits.check_soft_limits(&[size("a", 2)]).is_empty());
+        assert_eq!(limits.check_soft_limits(&[size("a", 3)]), vec!["a"]);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ReverseIndexLimitEvent::SoftLimitExceeded { size: size("a", 3) }
+        );
+
+        //  Only crossing the limit publishes an event
+        assert_eq!(limits.check_soft_limits(&[size("a", 4)]), vec!["a"]);
+        assert!(events.try_recv().is_err());
+
+        assert_eq!(limits.throttled_indexes(), vec!["a"]);
+        assert!(limits.check_soft_limits(&[size("a", 1)]).is_empty());
+        assert!(limits.throttled_indexes().is_empty());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ReverseIndexLimitEvent::SoftLimitCleared { name: "a" }
+        );
+    }
+
+    #[test]
+    fn test_soft_limit_bytes() {
+        let limits = ReverseIndexLimits::new(ReverseIndexLimitsConfig {
+            soft_limit_bytes: Some(25),
+            ..Default::default()
+        });
+        assert!(limits.check_soft_limits(&[size("a", 2)]).is_empty());
+        assert_eq!(limits.check_soft_limits(&[size("a", 3)]), vec!["a"]);
+    }
+}
@@ -0,0 +1,579 @@
+//! Named points in the state store at which tests inject failures, to
+//! exercise the windows between the steps of a write which a crash can leave
+//! half done. Failpoints are registered per state machine, so tests running
+//! in parallel don't affect each other. They are only compiled into test
+//! builds and builds with the `failpoints` feature.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::anyhow;
+
+use super::StateMachineError;
+
+/// After the forward indexes of a request are written to the transaction,
+/// before it is committed
+pub const APPLY_BEFORE_COMMIT: &str = "apply_before_commit";
+
+/// After the transaction of a request is committed, before the root task
+/// counts and reverse indexes are updated
+pub const APPLY_AFTER_COMMIT: &str = "apply_after_commit";
+
+/// After the reverse indexes of a request are updated
+pub const APPLY_AFTER_REVERSE_INDEXES: &str = "apply_after_reverse_indexes";
+
+/// Between marking a garbage collection task finished and deleting its
+/// content, within the same transaction
+pub const GC_MARK_FINISHED: &str = "gc_mark_finished";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailAction {
+    /// Return an error from the failpoint
+    Error,
+    /// Panic at the failpoint, like the state machine does on apply errors
+    Panic,
+}
+
+#[derive(Debug)]
+struct Failpoint {
+    action: FailAction,
+    /// Remaining number of times the failpoint fires, unlimited when None
+    remaining: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Failpoints {
+    points: Mutex<HashMap<String, Failpoint>>,
+}
+
+impl Failpoints {
+    /// Enables the failpoint until it is disabled
+    pub fn enable(&self, name: &str, action: FailAction) {
+        self.points.lock().unwrap().insert(
+            name.to_string(),
+            Failpoint {
+                action,
+                remaining: None,
+            },
+        );
+    }
+
+    /// Enables the failpoint for the next `times` evaluations
+    pub fn enable_times(&self, name: &str, action: FailAction, times: usize) {
+        self.points.lock().unwrap().insert(
+            name.to_string(),
+            Failpoint {
+                action,
+                remaining: Some(times),
+            },
+        );
+    }
+
+    pub fn disable(&self, name: &str) {
+        self.points.lock().unwrap().remove(name);
+    }
+
+    pub fn clear(&self) {
+        self.points.lock().unwrap().clear();
+    }
+
+    /// Fails when the failpoint is enabled
+    pub fn eval(&self, name: &str) -> Result<(), StateMachineError> {
+        let action = {
+            let mut points = self.points.lock().unwrap();
+            let Some(point) = points.get_mut(name) else {
+                return Ok(());
+            };
+            let action = point.action;
+            if let Some(remaining) = point.remaining.as_mut() {
+                *remaining -= 1;
+                if *remaining == 0 {
+                    points.remove(name);
+                }
+            }
+            action
+        };
+        match action {
+            FailAction::Error => Err(StateMachineError::ExternalError(anyhow!(
+                "failpoint {} triggered",
+                name
+            ))),
+            FailAction::Panic => panic!("failpoint {} triggered", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::SystemTime,
+    };
+
+    use indexify_internal_api::{
+        self as internal_api,
+        ContentMetadataId,
+        ContentVersionSeparator,
+        ServerTaskType,
+        StateChange,
+        TaskLifecycleState,
+        TaskOutcome,
+    };
+
+    use super::*;
+    use crate::{
+        server_config::{ValueSizeLimitsConfig, DEFAULT_MULTI_GET_BATCH_SIZE},
+        state::store::{
+            new_storage,
+            requests::{
+                CreateOrUpdateContentEntry,
+                RequestPayload,
+                StateChangeProcessed,
+                StateMachineUpdateRequest,
+            },
+            state_machine_objects::{AppliedUpdate, IndexifyState},
+            StateMachineColumns,
+            StateMachineStore,
+        },
+        test_util::db_utils::{mock_extractor, test_mock_content_metadata, DEFAULT_TEST_NAMESPACE},
+    };
+
+    async fn new_state_machine(name: &str) -> Arc<StateMachineStore> {
+        let path = format!("/tmp/indexify-test/failpoints/{}", name);
+        let _ = std::fs::remove_dir_all(&path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        sm
+    }
+
+    fn apply(
+        state: &IndexifyState,
+        sm: &StateMachineStore,
+        payload: RequestPayload,
+        new_state_changes: Vec<StateChange>,
+    ) -> Result<AppliedUpdate, StateMachineError> {
+        state.apply_state_machine_updates(
+            StateMachineUpdateRequest {
+                payload,
+                new_state_changes,
+                state_changes_processed: vec![],
+                idempotency_key: None,
+            },
+            &sm.db,
+        )
+    }
+
+    fn create_content(sm: &StateMachineStore, content: &internal_api::ContentMetadata) {
+        let state = &sm.data.indexify_state;
+        apply(
+            state,
+            sm,
+            RequestPayload::CreateNamespace {
+                name: DEFAULT_TEST_NAMESPACE.to_string(),
+                structured_data_schema: None,
+            },
+            vec![],
+        )
+        .unwrap();
+        apply(
+            state,
+            sm,
+            RequestPayload::CreateOrUpdateContent {
+                entries: vec![CreateOrUpdateContentEntry {
+                    content: content.clone(),
+                    previous_parent: None,
+                }],
+                ingestion_key: None,
+            },
+            vec![],
+        )
+        .unwrap();
+    }
+
+    fn test_task(content: &internal_api::ContentMetadata) -> internal_api::Task {
+        internal_api::Task {
+            id: "task_id".to_string(),
+            extractor: "extractor".to_string(),
+            extraction_policy_id: "policy_id".to_string(),
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            content_metadata: content.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn read_task(sm: &StateMachineStore) -> Option<internal_api::Task> {
+        sm.data
+            .indexify_state
+            .get_from_cf(&sm.db, StateMachineColumns::Tasks, "task_id")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_failpoint_fires_limited_times() {
+        let failpoints = Failpoints::default();
+        assert!(failpoints.eval(APPLY_BEFORE_COMMIT).is_ok());
+        failpoints.enable_times(APPLY_BEFORE_COMMIT, FailAction::Error, 2);
+        assert!(failpoints.eval(APPLY_BEFORE_COMMIT).is_err());
+        assert!(failpoints.eval(APPLY_AFTER_COMMIT).is_ok());
+        assert!(failpoints.eval(APPLY_BEFORE_COMMIT).is_err());
+        assert!(failpoints.eval(APPLY_BEFORE_COMMIT).is_ok());
+
+        failpoints.enable(APPLY_AFTER_COMMIT, FailAction::Error);
+        failpoints.clear();
+        assert!(failpoints.eval(APPLY_AFTER_COMMIT).is_ok());
+    }
+
+    /// A task creation failing before the commit must not leave the root
+    /// task count or the reverse indexes changed
+    #[tokio::test]
+    async fn test_failure_before_commit_leaves_no_trace() {
+        let sm = new_state_machine("before_commit").await;
+        let state = &sm.data.indexify_state;
+        let content = test_mock_content_metadata("content_id", "", "graph");
+        let task = test_task(&content);
+
+        state
+            .failpoints
+            .enable_times(APPLY_BEFORE_COMMIT, FailAction::Error, 1);
+        let payload = RequestPayload::CreateTasks {
+            tasks: vec![task.clone()],
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        assert!(read_task(&sm).is_none());
+        assert!(state.get_unassigned_tasks().is_empty());
+        assert!(state.are_content_tasks_completed(&content.id));
+
+        apply(state, &sm, payload, vec![]).unwrap();
+        assert_eq!(read_task(&sm).unwrap().id, task.id);
+        assert!(state.get_unassigned_tasks().contains(&task.id));
+        assert!(!state.are_content_tasks_completed(&content.id));
+    }
+
+    /// After a crash between the commit and the reverse index updates the
+    /// in memory state is rebuilt, and replaying the request must count the
+    /// task once
+    #[tokio::test]
+    async fn test_failure_after_commit_is_repaired_by_replay() {
+        let sm = new_state_machine("after_commit").await;
+        let state = &sm.data.indexify_state;
+        let content = test_mock_content_metadata("content_id", "", "graph");
+        create_content(&sm, &content);
+        let task = test_task(&content);
+
+        state
+            .failpoints
+            .enable_times(APPLY_AFTER_COMMIT, FailAction::Error, 1);
+        let payload = RequestPayload::CreateTasks {
+            tasks: vec![task.clone()],
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        assert!(read_task(&sm).is_some());
+        assert!(state.get_unassigned_tasks().is_empty());
+        assert!(state.are_content_tasks_completed(&content.id));
+
+        //  The restarted node replays the request on fresh in memory state
+        let restarted = IndexifyState::default();
+        apply(&restarted, &sm, payload, vec![]).unwrap();
+        assert_eq!(
+            restarted.get_unassigned_tasks(),
+            HashSet::from([task.id.clone()])
+        );
+        assert!(!restarted.are_content_tasks_completed(&content.id));
+
+        let finished = internal_api::Task {
+            outcome: TaskOutcome::Success,
+            ..task
+        };
+        apply(
+            &restarted,
+            &sm,
+            RequestPayload::UpdateTask {
+                task: finished,
+                executor_id: None,
+                update_time: SystemTime::now(),
+            },
+            vec![],
+        )
+        .unwrap();
+        assert!(restarted.are_content_tasks_completed(&content.id));
+    }
+
+    /// Finishing a task must not release the root content when the update
+    /// fails before the commit
+    #[tokio::test]
+    async fn test_failed_task_update_keeps_root_reference() {
+        let sm = new_state_machine("task_update").await;
+        let state = &sm.data.indexify_state;
+        let content = test_mock_content_metadata("content_id", "", "graph");
+        create_content(&sm, &content);
+        let task = test_task(&content);
+        apply(
+            state,
+            &sm,
+            RequestPayload::CreateTasks {
+                tasks: vec![task.clone()],
+            },
+            vec![],
+        )
+        .unwrap();
+
+        state
+            .failpoints
+            .enable_times(APPLY_BEFORE_COMMIT, FailAction::Error, 1);
+        let payload = RequestPayload::UpdateTask {
+            task: internal_api::Task {
+                outcome: TaskOutcome::Success,
+                ..task
+            },
+            executor_id: None,
+            update_time: SystemTime::now(),
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        assert!(!read_task(&sm).unwrap().terminal_state());
+        assert!(!state.are_content_tasks_completed(&content.id));
+        assert!(!state.get_unfinished_tasks_by_extractor()["extractor"].is_empty());
+
+        apply(state, &sm, payload, vec![]).unwrap();
+        assert!(read_task(&sm).unwrap().terminal_state());
+        assert!(state.are_content_tasks_completed(&content.id));
+    }
+
+    /// State changes stay unprocessed, and keep their reference on the root
+    /// content, when marking them processed fails
+    #[tokio::test]
+    async fn test_failed_mark_processed_keeps_state_changes() {
+        let sm = new_state_machine("mark_processed").await;
+        let state = &sm.data.indexify_state;
+        let root = ContentMetadataId::new("root");
+        let change = StateChange {
+            object_id: root.id.clone(),
+            refcnt_object_id: Some(root.id.clone()),
+            ..Default::default()
+        };
+        let applied = apply(
+            state,
+            &sm,
+            RequestPayload::CreateNamespace {
+                name: DEFAULT_TEST_NAMESPACE.to_string(),
+                structured_data_schema: None,
+            },
+            vec![change],
+        )
+        .unwrap();
+        let change_id = applied.new_state_changes[0].id;
+        assert!(!state.are_content_tasks_completed(&root));
+
+        state
+            .failpoints
+            .enable_times(APPLY_BEFORE_COMMIT, FailAction::Error, 1);
+        let payload = RequestPayload::MarkStateChangesProcessed {
+            state_changes: vec![StateChangeProcessed {
+                state_change_id: change_id,
+                processed_at: 1,
+            }],
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        assert!(state.get_unprocessed_state_changes().contains(&change_id));
+        assert!(!state.are_content_tasks_completed(&root));
+        let stored: StateChange = state
+            .get_from_cf(
+                &sm.db,
+                StateMachineColumns::StateChanges,
+                change_id.to_key(),
+            )
+            .unwrap()
+            .unwrap();
+        assert!(stored.processed_at.is_none());
+
+        apply(state, &sm, payload, vec![]).unwrap();
+        assert!(!state.get_unprocessed_state_changes().contains(&change_id));
+        assert!(state.are_content_tasks_completed(&root));
+    }
+
+    /// Marking a garbage collection task finished and deleting its content
+    /// happen in one transaction
+    #[tokio::test]
+    async fn test_gc_mark_finished_is_atomic() {
+        let sm = new_state_machine("gc_mark_finished").await;
+        let state = &sm.data.indexify_state;
+        let content = test_mock_content_metadata("content_id", "", "graph");
+        create_content(&sm, &content);
+        let gc_task = internal_api::GarbageCollectionTask::new(
+            DEFAULT_TEST_NAMESPACE,
+            content.clone(),
+            Default::default(),
+            ServerTaskType::Delete,
+        );
+        apply(
+            state,
+            &sm,
+            RequestPayload::CreateOrAssignGarbageCollectionTask {
+                gc_tasks: vec![gc_task.clone()],
+            },
+            vec![],
+        )
+        .unwrap();
+
+        state
+            .failpoints
+            .enable_times(GC_MARK_FINISHED, FailAction::Error, 1);
+        let payload = RequestPayload::UpdateGarbageCollectionTask {
+            gc_task: internal_api::GarbageCollectionTask {
+                outcome: TaskOutcome::Success,
+                ..gc_task.clone()
+            },
+            mark_finished: true,
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        let read_gc_task = || -> internal_api::GarbageCollectionTask {
+            state
+                .get_from_cf(
+                    &sm.db,
+                    StateMachineColumns::GarbageCollectionTasks,
+                    &gc_task.id,
+                )
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(read_gc_task().outcome, TaskOutcome::Unknown);
+        assert_eq!(
+            state
+                .get_content_from_ids(vec![content.id.id.clone()], &sm.db)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(state.get_content_namespace_table()[DEFAULT_TEST_NAMESPACE].contains(&content.id));
+
+        apply(state, &sm, payload, vec![]).unwrap();
+        assert_eq!(read_gc_task().outcome, TaskOutcome::Success);
+        assert!(state
+            .get_content_from_ids(vec![content.id.id.clone()], &sm.db)
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Creates the test task and assigns it to `executor_id`
+    fn create_assigned_task(
+        sm: &StateMachineStore,
+        content: &internal_api::ContentMetadata,
+        executor_id: &str,
+    ) -> internal_api::Task {
+        let state = &sm.data.indexify_state;
+        let task = test_task(content);
+        apply(
+            state,
+            sm,
+            RequestPayload::CreateTasks {
+                tasks: vec![task.clone()],
+            },
+            vec![],
+        )
+        .unwrap();
+        apply(
+            state,
+            sm,
+            RequestPayload::AssignTask {
+                assignments: HashMap::from([(task.id.clone(), executor_id.to_string())]),
+            },
+            vec![],
+        )
+        .unwrap();
+        task
+    }
+
+    /// Requeueing tasks failing before the commit must leave both the task
+    /// assignments and the reverse indexes as they were
+    #[tokio::test]
+    async fn test_failed_requeue_keeps_assignments() {
+        let sm = new_state_machine("requeue").await;
+        let state = &sm.data.indexify_state;
+        let content = test_mock_content_metadata("content_id", "", "graph");
+        create_content(&sm, &content);
+        let task = create_assigned_task(&sm, &content, "executor_id");
+
+        state
+            .failpoints
+            .enable_times(APPLY_BEFORE_COMMIT, FailAction::Error, 1);
+        let payload = RequestPayload::RequeueTasksForExtractor {
+            extractor: "extractor".to_string(),
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        assert_eq!(
+            state.get_all_task_assignments(&sm.db).unwrap(),
+            HashMap::from([(task.id.clone(), "executor_id".to_string())])
+        );
+        assert!(state.get_unassigned_tasks().is_empty());
+        assert_eq!(state.get_executor_running_task_count()["executor_id"], 1);
+
+        apply(state, &sm, payload, vec![]).unwrap();
+        assert!(state.get_all_task_assignments(&sm.db).unwrap().is_empty());
+        assert!(state.get_unassigned_tasks().contains(&task.id));
+        assert_eq!(state.get_executor_running_task_count()["executor_id"], 0);
+        assert!(state.get_unfinished_tasks_by_extractor()["extractor"].contains(&task.id));
+    }
+
+    /// Moving a task through its lifecycle failing before the commit must
+    /// leave both the task assignments and the reverse indexes as they were
+    #[tokio::test]
+    async fn test_failed_task_transition_keeps_state() {
+        let sm = new_state_machine("task_transition").await;
+        let state = &sm.data.indexify_state;
+        let content = test_mock_content_metadata("content_id", "", "graph");
+        create_content(&sm, &content);
+        let task = test_task(&content);
+        let mut extractor = mock_extractor();
+        extractor.name = task.extractor.clone();
+        apply(
+            state,
+            &sm,
+            RequestPayload::RegisterExecutor {
+                addr: "localhost:8950".to_string(),
+                executor_id: "executor_id".to_string(),
+                extractors: vec![extractor],
+                ts_secs: 0,
+                capabilities: HashMap::new(),
+            },
+            vec![],
+        )
+        .unwrap();
+        apply(
+            state,
+            &sm,
+            RequestPayload::CreateTasks {
+                tasks: vec![task.clone()],
+            },
+            vec![],
+        )
+        .unwrap();
+
+        state
+            .failpoints
+            .enable_times(APPLY_BEFORE_COMMIT, FailAction::Error, 1);
+        let payload = RequestPayload::TransitionTask {
+            task_id: task.id.clone(),
+            to: TaskLifecycleState::Assigned {
+                executor_id: "executor_id".to_string(),
+            },
+            update_time: SystemTime::now(),
+        };
+        assert!(apply(state, &sm, payload.clone(), vec![]).is_err());
+        assert!(state.get_all_task_assignments(&sm.db).unwrap().is_empty());
+        assert!(state.get_unassigned_tasks().contains(&task.id));
+        assert_eq!(state.get_executor_running_task_count()["executor_id"], 0);
+
+        apply(state, &sm, payload, vec![]).unwrap();
+        assert_eq!(
+            state.get_all_task_assignments(&sm.db).unwrap(),
+            HashMap::from([(task.id.clone(), "executor_id".to_string())])
+        );
+        assert!(!state.get_unassigned_tasks().contains(&task.id));
+        assert_eq!(state.get_executor_running_task_count()["executor_id"], 1);
+    }
+}
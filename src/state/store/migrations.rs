@@ -0,0 +1,468 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use indexify_internal_api as internal_api;
+use rocksdb::{ColumnFamily, Direction, IteratorMode, OptimisticTransactionDB, Transaction};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::{
+    serializer::JsonEncode,
+    state_machine_objects::content_lookup_keys,
+    JsonEncoder,
+    StateMachineColumns,
+    StateMachineError,
+};
+use crate::utils::timestamp_secs;
+
+/// Number of rows a migration processes per transaction
+pub const MIGRATION_BATCH_SIZE: usize = 1000;
+
+/// Rows processed by a batch of a migration
+pub struct BatchProgress {
+    pub processed: usize,
+    /// Key the next batch starts after, None once the migration is complete
+    pub cursor: Option<Vec<u8>>,
+}
+
+/// Processes the rows after the cursor, up to the batch size, writing its
+/// changes to the transaction
+type BatchFn = fn(
+    &Arc<OptimisticTransactionDB>,
+    &Transaction<OptimisticTransactionDB>,
+    Option<&[u8]>,
+    usize,
+) -> Result<BatchProgress, StateMachineError>;
+
+/// A one-off change of the data of the state store
+pub struct Migration {
+    /// Name the migration is recorded under, must never change
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run_batch: BatchFn,
+}
+
+/// The migrations of the state store in the order they run. New migrations
+/// are appended.
+pub fn registry() -> Vec<Migration> {
+    vec![Migration {
+        name: "0001_backfill_content_lookup_index",
+        description: "Adds the id and hash lookup entries of root content written before the \
+                      content lookup index existed",
+        run_batch: backfill_content_lookup_index,
+    }]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    Pending,
+    InProgress,
+    Applied,
+    Failed,
+}
+
+/// Record of a migration in the Migrations column family
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MigrationRecord {
+    status: MigrationStatus,
+    started_at: u64,
+    finished_at: Option<u64>,
+    processed: u64,
+    /// Key the migration resumes after
+    cursor: Option<Vec<u8>>,
+    error: Option<String>,
+}
+
+/// State of a migration of the store, as listed by the admin endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationInfo {
+    pub name: String,
+    pub description: String,
+    pub status: MigrationStatus,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    /// Rows processed so far
+    pub processed: u64,
+    pub error: Option<String>,
+}
+
+fn read_record(
+    db: &Arc<OptimisticTransactionDB>,
+    name: &str,
+) -> Result<Option<MigrationRecord>, StateMachineError> {
+    db.get_cf(StateMachineColumns::Migrations.cf(db), name)
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?
+        .map(|value| JsonEncoder::decode(&value))
+        .transpose()
+}
+
+fn put_record(
+    db: &Arc<OptimisticTransactionDB>,
+    txn: &Transaction<OptimisticTransactionDB>,
+    name: &str,
+    record: &MigrationRecord,
+) -> Result<(), StateMachineError> {
+    txn.put_cf(
+        StateMachineColumns::Migrations.cf(db),
+        name,
+        JsonEncoder::encode(record)?,
+    )
+    .map_err(|e| StateMachineError::TransactionError(e.to_string()))
+}
+
+/// Records the progress of a batch in the same transaction as its changes,
+/// so a migration never resumes before or after the rows it committed
+fn commit_batch(
+    db: &Arc<OptimisticTransactionDB>,
+    txn: Transaction<OptimisticTransactionDB>,
+    name: &str,
+    record: &MigrationRecord,
+    progress: BatchProgress,
+) -> Result<MigrationRecord, StateMachineError> {
+    let mut record = record.clone();
+    record.processed += progress.processed as u64;
+    record.cursor = progress.cursor;
+    if record.cursor.is_none() {
+        record.status = MigrationStatus::Applied;
+        record.finished_at = Some(timestamp_secs());
+    }
+    put_record(db, &txn, name, &record)?;
+    txn.commit()
+        .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+    Ok(record)
+}
+
+/// Runs the migrations which haven't been applied to the store, in order.
+/// Every batch commits its progress, so a migration interrupted by a crash or
+/// a failure resumes after the last batch it committed.
+pub fn run_migrations(
+    db: &Arc<OptimisticTransactionDB>,
+    migrations: &[Migration],
+    batch_size: usize,
+) -> Result<()> {
+    for migration in migrations {
+        let mut record = match read_record(db, migration.name)? {
+            Some(record) if record.status == MigrationStatus::Applied => continue,
+            Some(record) => {
+                info!(
+                    "resuming migration {} after {} rows",
+                    migration.name, record.processed
+                );
+                record
+            }
+            None => {
+                info!(
+                    "running migration {}: {}",
+                    migration.name, migration.description
+                );
+                MigrationRecord {
+                    status: MigrationStatus::InProgress,
+                    started_at: timestamp_secs(),
+                    finished_at: None,
+                    processed: 0,
+                    cursor: None,
+                    error: None,
+                }
+            }
+        };
+        record.status = MigrationStatus::InProgress;
+        record.error = None;
+        while record.status != MigrationStatus::Applied {
+            let txn = db.transaction();
+            let batch = (migration.run_batch)(db, &txn, record.cursor.as_deref(), batch_size)
+                .and_then(|progress| commit_batch(db, txn, migration.name, &record, progress));
+            match batch {
+                Ok(next) => {
+                    record = next;
+                    info!(
+                        "migration {}: {} rows processed",
+                        migration.name, record.processed
+                    );
+                }
+                Err(e) => {
+                    record.status = MigrationStatus::Failed;
+                    record.error = Some(e.to_string());
+                    let txn = db.transaction();
+                    if let Err(e) = put_record(db, &txn, migration.name, &record).and_then(|_| {
+                        txn.commit()
+                            .map_err(|e| StateMachineError::TransactionError(e.to_string()))
+                    }) {
+                        error!(
+                            "unable to record failure of migration {}: {}",
+                            migration.name, e
+                        );
+                    }
+                    return Err(anyhow!(
+                        "migration {} failed after {} rows: {}. It resumes from there on the \
+                         next start",
+                        migration.name,
+                        record.processed,
+                        e
+                    ));
+                }
+            }
+        }
+        info!("migration {} applied", migration.name);
+    }
+    Ok(())
+}
+
+/// Lists the migrations along with whether they have been applied to the
+/// store, without running any of them
+pub fn list_migrations(
+    db: &Arc<OptimisticTransactionDB>,
+    migrations: &[Migration],
+) -> Result<Vec<MigrationInfo>> {
+    let mut infos = Vec::new();
+    for migration in migrations {
+        let record = read_record(db, migration.name)?;
+        infos.push(MigrationInfo {
+            name: migration.name.to_string(),
+            description: migration.description.to_string(),
+            status: record
+                .as_ref()
+                .map_or(MigrationStatus::Pending, |r| r.status.clone()),
+            started_at: record.as_ref().map(|r| r.started_at),
+            finished_at: record.as_ref().and_then(|r| r.finished_at),
+            processed: record.as_ref().map_or(0, |r| r.processed),
+            error: record.and_then(|r| r.error),
+        });
+    }
+    Ok(infos)
+}
+
+/// Calls `f` with the rows of the column family after the cursor, up to the
+/// batch size
+pub fn scan_batch(
+    db: &Arc<OptimisticTransactionDB>,
+    cf: &ColumnFamily,
+    cursor: Option<&[u8]>,
+    batch_size: usize,
+    mut f: impl FnMut(&[u8], &[u8]) -> Result<(), StateMachineError>,
+) -> Result<BatchProgress, StateMachineError> {
+    let mode = match cursor {
+        Some(cursor) => IteratorMode::From(cursor, Direction::Forward),
+        None => IteratorMode::Start,
+    };
+    let mut processed = 0;
+    let mut last_key = None;
+    for item in db.iterator_cf(cf, mode) {
+        let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        if Some(key.as_ref()) == cursor {
+            continue;
+        }
+        if processed == batch_size {
+            return Ok(BatchProgress {
+                processed,
+                cursor: last_key,
+            });
+        }
+        f(&key, &value)?;
+        processed += 1;
+        last_key = Some(key.to_vec());
+    }
+    Ok(BatchProgress {
+        processed,
+        cursor: None,
+    })
+}
+
+/// Writes the lookup entries of root content the way they are written when
+/// content is created. Content which is no longer the latest version only
+/// fills in missing entries, like it does when a snapshot is installed.
+fn backfill_content_lookup_index(
+    db: &Arc<OptimisticTransactionDB>,
+    txn: &Transaction<OptimisticTransactionDB>,
+    cursor: Option<&[u8]>,
+    batch_size: usize,
+) -> Result<BatchProgress, StateMachineError> {
+    let lookup_cf = StateMachineColumns::ContentLookupIndex.cf(db);
+    scan_batch(
+        db,
+        StateMachineColumns::ContentTable.cf(db),
+        cursor,
+        batch_size,
+        |_, value| {
+            let content = JsonEncoder::decode::<internal_api::ContentMetadata>(value)?;
+            if content.parent_id.is_some() || !(content.latest || content.tombstoned) {
+                return Ok(());
+            }
+            let serialized_id = JsonEncoder::encode(&content.id)?;
+            for key in content_lookup_keys(&content) {
+                let exists = txn
+                    .get_cf(lookup_cf, &key)
+                    .map_err(|e| StateMachineError::TransactionError(e.to_string()))?
+                    .is_some();
+                if content.latest || !exists {
+                    txn.put_cf(lookup_cf, &key, &serialized_id)
+                        .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+                }
+            }
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use internal_api::{
+        ContentExistence,
+        ContentLookupKey,
+        ContentMetadataId,
+        ContentVersionSeparator,
+    };
+
+    use super::*;
+    use crate::{
+        server_config::{ValueSizeLimitsConfig, DEFAULT_MULTI_GET_BATCH_SIZE},
+        state::store::{new_storage, StateMachineStore},
+        test_util::db_utils::{test_mock_content_metadata, DEFAULT_TEST_NAMESPACE},
+    };
+
+    async fn store(name: &str) -> Arc<StateMachineStore> {
+        let path = format!("/tmp/indexify-test/migrations/{}", name);
+        let _ = std::fs::remove_dir_all(&path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        sm
+    }
+
+    fn status(db: &Arc<OptimisticTransactionDB>, migrations: &[Migration]) -> Vec<MigrationStatus> {
+        list_migrations(db, migrations)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.status)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fresh_store() {
+        let sm = store("fresh").await;
+        let migrations = registry();
+        assert_eq!(status(&sm.db, &migrations), vec![MigrationStatus::Pending]);
+
+        run_migrations(&sm.db, &migrations, 10).unwrap();
+        let infos = list_migrations(&sm.db, &migrations).unwrap();
+        assert_eq!(infos[0].status, MigrationStatus::Applied);
+        assert_eq!(infos[0].processed, 0);
+
+        //  Applied migrations don't run again
+        run_migrations(&sm.db, &migrations, 10).unwrap();
+        assert_eq!(list_migrations(&sm.db, &migrations).unwrap(), infos);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_content_lookup_index() {
+        let sm = store("backfill").await;
+        let content_cf = StateMachineColumns::ContentTable.cf(&sm.db);
+
+        //  Content written before the lookup index existed has no entries
+        let mut root = test_mock_content_metadata("root", "", "graph");
+        root.hash = "root_hash".to_string();
+        let mut child = test_mock_content_metadata("child", "root", "graph");
+        child.parent_id = Some(ContentMetadataId::new("root"));
+        for content in [&root, &child] {
+            sm.db
+                .put_cf(
+                    content_cf,
+                    &content.id.id,
+                    JsonEncoder::encode(content).unwrap(),
+                )
+                .unwrap();
+        }
+        let keys = vec![
+            ContentLookupKey::Id("root".to_string()),
+            ContentLookupKey::Hash("root_hash".to_string()),
+            ContentLookupKey::Id("child".to_string()),
+        ];
+        let exists = |sm: &StateMachineStore| {
+            sm.data
+                .indexify_state
+                .check_content_exists(DEFAULT_TEST_NAMESPACE, &keys, &sm.db)
+                .unwrap()
+                .into_iter()
+                .map(|e| !matches!(e, ContentExistence::Unknown))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(exists(&sm), vec![false, false, false]);
+
+        run_migrations(&sm.db, &registry(), 1).unwrap();
+        assert_eq!(exists(&sm), vec![true, true, false]);
+        let infos = list_migrations(&sm.db, &registry()).unwrap();
+        assert_eq!(infos[0].processed, 2);
+    }
+
+    static FAIL: AtomicBool = AtomicBool::new(true);
+
+    /// Counts how often each row of the namespaces column family is
+    /// processed, failing on row c while FAIL is set
+    fn count_rows(
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &Transaction<OptimisticTransactionDB>,
+        cursor: Option<&[u8]>,
+        batch_size: usize,
+    ) -> Result<BatchProgress, StateMachineError> {
+        let cf = StateMachineColumns::SearchProfiles.cf(db);
+        scan_batch(
+            db,
+            StateMachineColumns::Namespaces.cf(db),
+            cursor,
+            batch_size,
+            |key, _| {
+                if key == b"c" && FAIL.load(Ordering::SeqCst) {
+                    return Err(StateMachineError::DatabaseError("disk full".to_string()));
+                }
+                let count = txn.get_cf(cf, key).unwrap().map_or(0, |v| v[0]);
+                txn.put_cf(cf, key, [count + 1]).unwrap();
+                Ok(())
+            },
+        )
+    }
+
+    //  A failed batch leaves the same marker behind as a crash during the
+    //  batch, the progress of the last committed batch
+    #[tokio::test]
+    async fn test_resume_after_failure() {
+        let sm = store("resume").await;
+        let namespaces_cf = StateMachineColumns::Namespaces.cf(&sm.db);
+        for key in ["a", "b", "c", "d"] {
+            sm.db.put_cf(namespaces_cf, key, key).unwrap();
+        }
+        let migrations = vec![Migration {
+            name: "count_rows",
+            description: "",
+            run_batch: count_rows,
+        }];
+
+        //  The failure aborts the run and records the committed progress
+        let err = run_migrations(&sm.db, &migrations, 1).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("migration count_rows failed after 2 rows"));
+        let infos = list_migrations(&sm.db, &migrations).unwrap();
+        assert_eq!(infos[0].status, MigrationStatus::Failed);
+        assert_eq!(infos[0].error.as_deref(), Some("Database error: disk full"));
+
+        //  The next start resumes after the last committed row and processes
+        //  every row once
+        FAIL.store(false, Ordering::SeqCst);
+        run_migrations(&sm.db, &migrations, 1).unwrap();
+        let counts_cf = StateMachineColumns::SearchProfiles.cf(&sm.db);
+        for key in ["a", "b", "c", "d"] {
+            assert_eq!(sm.db.get_cf(counts_cf, key).unwrap(), Some(vec![1]));
+        }
+        let infos = list_migrations(&sm.db, &migrations).unwrap();
+        assert_eq!(infos[0].status, MigrationStatus::Applied);
+        assert_eq!(infos[0].processed, 4);
+        assert_eq!(infos[0].error, None);
+    }
+}
@@ -14,7 +14,10 @@ use flate2::bufread::ZlibDecoder;
 use indexify_internal_api::{
     ContentMetadata,
     ContentMetadataId,
+    ContentQuery,
+    ContentVersionSeparator,
     ExecutorMetadata,
+    ExtractorDescription,
     NamespaceName,
     StateChange,
     StructuredDataSchema,
@@ -58,11 +61,21 @@ use indexify_internal_api::StateChangeId;
 use self::{
     requests::RequestPayload,
     serializer::{JsonEncode, JsonEncoder},
-    state_machine_objects::{IndexifyState, IndexifyStateSnapshot},
+    snapshot_chunks::ChunkedSnapshot,
+    state_machine_objects::{
+        DeliveryQueueStats,
+        ExtractionPoliciesLookup,
+        IndexOverview,
+        IndexifyState,
+        IngestionKeyRecord,
+        PolicyCoverage,
+        ReverseIndexSize,
+    },
 };
 use super::{typ, NodeId, SnapshotData, TypeConfig};
 use crate::{
     metrics::{state_machine::Metrics, Timer},
+    server_config::{ReverseIndexEvictionConfig, ValueSizeLimitsConfig},
     utils::OptionInspectNone,
 };
 
@@ -75,12 +88,79 @@ pub type ExtractionPolicyId = String;
 pub type ExtractorName = String;
 pub type ContentType = String;
 pub type ExtractionGraphId = String;
-pub type SchemaId = String;
 
+/// Longest schema id accepted, far above the length of generated ids
+pub const MAX_SCHEMA_ID_LEN: usize = 128;
+
+/// Id of a structured data schema. Ids are validated when they are created
+/// so empty or malformed ids can't be written to the schema tables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SchemaId(String);
+
+impl SchemaId {
+    pub fn new(id: &str) -> Result<Self, StateMachineError> {
+        let invalid = |reason| StateMachineError::InvalidSchemaId {
+            schema_id: id.to_string(),
+            reason,
+        };
+        if id.is_empty() {
+            return Err(invalid("schema id is empty"));
+        }
+        if id.len() > MAX_SCHEMA_ID_LEN {
+            return Err(invalid("schema id is too long"));
+        }
+        if !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid(
+                "schema id may only contain ascii letters, digits, '-' and '_'",
+            ));
+        }
+        Ok(Self(id.to_string()))
+    }
+
+    /// Builds the id of a schema which was validated before its request was
+    /// applied, such as when the reverse indexes are updated after a commit
+    fn new_unchecked(id: &str) -> Self {
+        Self(id.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for SchemaId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(any(test, feature = "failpoints"))]
+pub mod failpoints;
+pub mod migrations;
 pub mod requests;
 pub mod serializer;
+pub mod sharded;
+pub mod snapshot_chunks;
+pub mod spilled;
 pub mod state_machine_objects;
 
+/// Evaluates a failpoint of the state, returning its error from the
+/// enclosing function. Expands to nothing when failpoints are compiled out.
+macro_rules! fail_point {
+    ($state:expr, $name:expr) => {
+        #[cfg(any(test, feature = "failpoints"))]
+        {
+            $state.failpoints.eval($name)?;
+        }
+    };
+}
+
+pub(crate) use fail_point;
+
 #[derive(Error, Debug)]
 pub enum StateMachineError {
     #[error("Database error: {0}")]
@@ -94,6 +174,123 @@ pub enum StateMachineError {
 
     #[error("External error: {0}")]
     ExternalError(#[from] anyhow::Error),
+
+    #[error("Namespace not found: {0}")]
+    NamespaceNotFound(String),
+
+    #[error("Immutable field {field} of content {content_id} cannot be changed")]
+    ImmutableContentField {
+        content_id: String,
+        field: &'static str,
+    },
+
+    #[error(
+        "Content {content_id} in namespace {namespace} can't have parent {parent_id} in namespace {parent_namespace}"
+    )]
+    ContentNamespaceMismatch {
+        content_id: String,
+        namespace: String,
+        parent_id: String,
+        parent_namespace: String,
+    },
+
+    #[error("Invalid schema id {schema_id:?}: {reason}")]
+    InvalidSchemaId {
+        schema_id: String,
+        reason: &'static str,
+    },
+
+    #[error("Schema {schema_id} is referenced by extraction policies {policies:?}")]
+    SchemaInUse {
+        schema_id: String,
+        policies: Vec<String>,
+    },
+
+    #[error("Extractor {extractor} can't be updated: {reason}")]
+    IncompatibleExtractorUpdate { extractor: String, reason: String },
+
+    #[error("Invalid namespace alias {alias}: {reason}")]
+    InvalidNamespaceAlias { alias: String, reason: &'static str },
+
+    #[error("Task not found: {0}")]
+    TaskNotFound(String),
+
+    #[error("State change not found: {0}")]
+    StateChangeNotFound(StateChangeId),
+
+    //  Worded like the error of a single missing task, which clients match on
+    #[error("Task {} not found", .task_ids.join(", "))]
+    AssignedTasksNotFound {
+        executor_id: String,
+        task_ids: Vec<String>,
+    },
+
+    #[error("Extraction policy not found: {0}")]
+    ExtractionPolicyNotFound(String),
+
+    #[error(
+        "Extraction policy {extraction_policy_id} is the content source of extraction policies {policies:?}"
+    )]
+    ExtractionPolicyInUse {
+        extraction_policy_id: String,
+        policies: Vec<String>,
+    },
+
+    #[error("Task {task_id} can't move from {from} to {to}")]
+    InvalidTaskTransition {
+        task_id: String,
+        from: String,
+        to: String,
+    },
+
+    #[error("Value of key {key} in column family {cf} is too large: {size} bytes")]
+    ValueTooLarge {
+        cf: String,
+        key: String,
+        size: usize,
+    },
+}
+
+/// Prefixes of the messages of transient RocksDB errors: Busy, which is also
+/// returned when an optimistic transaction conflicts with another write,
+/// TimedOut and TryAgain
+const RETRYABLE_ROCKSDB_ERRORS: [&str; 3] = [
+    "Resource busy",
+    "Operation timed out",
+    "Operation failed. Try again.",
+];
+
+impl StateMachineError {
+    /// Returns true when the operation which failed with this error can be
+    /// retried as is and may succeed, such as a transaction which conflicted
+    /// with a concurrent write. Errors like deserialization failures or
+    /// missing namespaces fail the same way on every attempt.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            StateMachineError::DatabaseError(message) |
+            StateMachineError::TransactionError(message) => RETRYABLE_ROCKSDB_ERRORS
+                .iter()
+                .any(|prefix| message.starts_with(prefix)),
+            StateMachineError::ExternalError(e) => e
+                .downcast_ref::<StateMachineError>()
+                .is_some_and(|e| e.is_retryable()),
+            StateMachineError::SerializationError(_) |
+            StateMachineError::NamespaceNotFound(_) |
+            StateMachineError::ImmutableContentField { .. } |
+            StateMachineError::ContentNamespaceMismatch { .. } |
+            StateMachineError::InvalidSchemaId { .. } |
+            StateMachineError::SchemaInUse { .. } |
+            StateMachineError::IncompatibleExtractorUpdate { .. } |
+            StateMachineError::InvalidNamespaceAlias { .. } |
+            StateMachineError::TaskNotFound(_) |
+            StateMachineError::StateChangeNotFound(_) |
+            StateMachineError::AssignedTasksNotFound { .. } |
+            StateMachineError::ExtractionPolicyNotFound(_) |
+            StateMachineError::ExtractionPolicyInUse { .. } |
+            StateMachineError::InvalidTaskTransition { .. } |
+            StateMachineError::ValueTooLarge { .. } => false,
+        }
+    }
 }
 
 #[derive(AsRefStr, strum::Display, strum::EnumIter)]
@@ -112,6 +309,19 @@ pub enum StateMachineColumns {
     ExtractionPoliciesAppliedOnContent, //  ContentId -> Vec<ExtractionPolicyIds>
     CoordinatorAddress,                 //  NodeId -> Coordinator address
     ExtractionGraphs,                   //  ExtractionGraphId -> ExtractionGraph
+    ContentLookupIndex,                 //  Namespace::{id,hash}::Value -> ContentMetadataId
+    SearchProfiles,                     //  Namespace::Name -> SearchProfile
+    Migrations,                         //  MigrationName -> MigrationRecord
+    IdempotencyKeys,                    //  IdempotencyKey -> IdempotencyRecord
+    UsageStats,                         //  Namespace::Day::Extractor -> UsageStats
+    IngestionKeys,                      //  Namespace::IdempotencyKey -> IngestionKeyRecord
+    NamespaceAliases,                   //  Alias -> NamespaceName
+    Subscriptions,                      //  SubscriptionId -> Subscription
+    PendingDeliveries,                  //  SubscriptionId::Sequence -> Delivery
+    DeadLetterDeliveries,               //  SubscriptionId::Sequence -> Delivery
+    NamespaceRetention,                 //  NamespaceName -> RetentionRules
+    SearchFeedback,                     //  Namespace::CreatedAt::Id -> SearchFeedback
+    SpilledReverseIndexes,              //  ReverseIndex::Key -> Entry
 }
 
 impl StateMachineColumns {
@@ -127,6 +337,14 @@ impl StateMachineColumns {
 #[derive(serde::Serialize, Deserialize, Debug, Clone)]
 pub struct Response {
     pub value: Option<String>,
+
+    /// Set when the request was rejected by validation and not applied
+    #[serde(default)]
+    pub error: Option<String>,
+
+    /// Ids and versions of the content written by the request
+    #[serde(default)]
+    pub written_content: Vec<ContentMetadataId>,
 }
 
 #[derive(serde::Serialize, Deserialize, Debug, Clone)]
@@ -150,6 +368,10 @@ pub struct StateMachineData {
     gc_tasks_tx: broadcast::Sender<indexify_internal_api::GarbageCollectionTask>,
 }
 
+/// Key in the store column family of the separator of the keys of versioned
+/// content the state store was created with
+const CONTENT_VERSION_SEPARATOR_KEY: &[u8] = b"content_version_separator";
+
 pub struct StateMachineStore {
     pub data: StateMachineData,
 
@@ -168,6 +390,9 @@ impl StateMachineStore {
     async fn new(
         db: Arc<OptimisticTransactionDB>,
         snapshot_file_path: PathBuf,
+        value_size_limits: ValueSizeLimitsConfig,
+        multi_get_batch_size: usize,
+        content_version_separator: ContentVersionSeparator,
     ) -> Result<StateMachineStore, StorageError<NodeId>> {
         let (tx, rx) = tokio::sync::watch::channel(StateChange::default());
         let (gc_tasks_tx, _) = broadcast::channel(100);
@@ -175,7 +400,12 @@ impl StateMachineStore {
             data: StateMachineData {
                 last_applied_log_id: RwLock::new(None),
                 last_membership: RwLock::new(StoredMembership::default()),
-                indexify_state: IndexifyState::default(),
+                indexify_state: IndexifyState {
+                    value_size_limits,
+                    multi_get_batch_size,
+                    content_version_separator,
+                    ..Default::default()
+                },
                 state_change_tx: Arc::new(tx),
                 gc_tasks_tx,
             },
@@ -186,11 +416,39 @@ impl StateMachineStore {
             metrics: Metrics::new(),
         };
 
+        sm.check_content_version_separator()
+            .map_err(|e| StorageError::IO {
+                source: StorageIOError::read(&e),
+            })?;
+
+        let cleared = spilled::clear_spilled(&sm.db).map_err(|e| StorageError::IO {
+            source: StorageIOError::write(&e),
+        })?;
+        debug!("cleared {} spilled reverse index entries", cleared);
+
         let snapshot = sm.get_current_snapshot_()?;
         if let Some(snap) = snapshot {
             sm.update_state_machine_(snap).await?;
         }
 
+        let expired = sm
+            .data
+            .indexify_state
+            .compact_idempotency_keys(&sm.db)
+            .map_err(|e| StorageError::IO {
+                source: StorageIOError::write(&e),
+            })?;
+        debug!("removed {} expired idempotency keys", expired);
+
+        let expired = sm
+            .data
+            .indexify_state
+            .compact_ingestion_keys(&sm.db)
+            .map_err(|e| StorageError::IO {
+                source: StorageIOError::write(&e),
+            })?;
+        debug!("removed {} expired ingestion keys", expired);
+
         Ok(sm)
     }
 
@@ -200,7 +458,7 @@ impl StateMachineStore {
         &self,
         snapshot: StoredSnapshot,
     ) -> Result<(), StorageError<NodeId>> {
-        let indexify_state_snapshot: IndexifyStateSnapshot = JsonEncoder::decode(&snapshot.data)
+        let indexify_state_snapshot = snapshot_chunks::decode_state(&snapshot.data)
             .map_err(|e| StorageIOError::read_snapshot(Some(snapshot.meta.signature()), &e))?;
 
         {
@@ -250,6 +508,19 @@ impl StateMachineStore {
         Ok(Some(snapshot))
     }
 
+    /// Returns the current snapshot along with its chunks, or None when there
+    /// is no snapshot or it was written in the plain JSON encoding
+    pub fn current_chunked_snapshot(
+        &self,
+    ) -> Result<Option<(SnapshotMeta<NodeId, Node>, ChunkedSnapshot)>> {
+        let Some(snapshot) = self.get_current_snapshot_()? else {
+            return Ok(None);
+        };
+        let chunked = ChunkedSnapshot::decode(&snapshot.data)
+            .map_err(|e| anyhow!("unable to decode snapshot chunks: {}", e))?;
+        Ok(chunked.map(|chunked| (snapshot.meta, chunked)))
+    }
+
     /// This method is called when a new snapshot is received via
     /// InstallSnapshot RPC and is used to write the snapshot to disk
     fn set_current_snapshot_(&self, snap: StoredSnapshot) -> StorageResult<()> {
@@ -308,6 +579,74 @@ impl StateMachineStore {
         self.data.gc_tasks_tx.subscribe()
     }
 
+    /// Runs the migrations which haven't been applied to the store yet
+    pub fn run_migrations(&self) -> Result<()> {
+        migrations::run_migrations(
+            &self.db,
+            &migrations::registry(),
+            migrations::MIGRATION_BATCH_SIZE,
+        )
+    }
+
+    /// Records the separator of the keys of versioned content when the store
+    /// is created and rejects a different separator afterwards, the content
+    /// table would otherwise mix keys built with both
+    fn check_content_version_separator(&self) -> Result<(), StateMachineError> {
+        let store_cf = self.db.cf_handle("store").unwrap();
+        let separator = &self.data.indexify_state.content_version_separator;
+        let recorded = self
+            .db
+            .get_cf(store_cf, CONTENT_VERSION_SEPARATOR_KEY)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        let recorded = match recorded {
+            Some(recorded) => String::from_utf8_lossy(&recorded).into_owned(),
+            None => {
+                //  Stores with content from before the separator was recorded
+                //  used the default one
+                let has_content = self
+                    .db
+                    .iterator_cf(
+                        StateMachineColumns::ContentTable.cf(&self.db),
+                        IteratorMode::Start,
+                    )
+                    .next()
+                    .is_some();
+                let recorded = if has_content {
+                    ContentVersionSeparator::default()
+                } else {
+                    separator.clone()
+                };
+                self.db
+                    .put_cf(store_cf, CONTENT_VERSION_SEPARATOR_KEY, recorded.as_str())
+                    .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+                recorded.to_string()
+            }
+        };
+        if recorded != separator.as_str() {
+            return Err(StateMachineError::DatabaseError(format!(
+                "the state store uses the content version separator {:?}, it can't be changed to {:?}",
+                recorded,
+                separator.as_str()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Bounds the number of entries of the reverse indexes kept in memory, as
+    /// configured. Every reverse index stays in memory by default.
+    pub fn bound_reverse_indexes(&self, config: &ReverseIndexEvictionConfig) {
+        if let Some(capacity) = config.content_children_table_capacity {
+            self.data
+                .indexify_state
+                .content_children_table
+                .bound(self.db.clone(), capacity);
+        }
+    }
+
+    pub fn list_migrations(&self) -> Result<Vec<migrations::MigrationInfo>> {
+        migrations::list_migrations(&self.db, &migrations::registry())
+    }
+
     //  START FORWARD INDEX READER METHODS INTERFACES
     pub fn get_latest_version_of_content(
         &self,
@@ -320,6 +659,26 @@ impl StateMachineStore {
             .map_err(|e| anyhow::anyhow!("Failed to get latest version of content: {}", e))
     }
 
+    pub fn get_latest_policy_mapping(
+        &self,
+        content_id: &str,
+    ) -> Result<Option<HashMap<ExtractionPolicyId, u64>>> {
+        self.data
+            .indexify_state
+            .get_latest_policy_mapping(content_id, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_policy_mappings_for_contents(
+        &self,
+        content_ids: HashSet<String>,
+    ) -> Result<HashMap<String, HashMap<ExtractionPolicyId, u64>>> {
+        self.data
+            .indexify_state
+            .get_policy_mappings_for_contents(content_ids, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     /// This method fetches a key from a specific column family
     pub fn get_from_cf<T, K>(
         &self,
@@ -358,6 +717,13 @@ impl StateMachineStore {
             .map_err(|e| anyhow::anyhow!("Failed to get task assignments: {}", e))
     }
 
+    pub fn get_all_executor_task_counts(&self) -> Result<HashMap<ExecutorId, usize>> {
+        self.data
+            .indexify_state
+            .get_all_executor_task_counts(&self.db)
+            .map_err(|e| anyhow::anyhow!("Failed to get executor task counts: {}", e))
+    }
+
     pub async fn get_indexes_from_ids(
         &self,
         task_ids: HashSet<String>,
@@ -371,7 +737,7 @@ impl StateMachineStore {
     pub fn get_extraction_policies_from_ids(
         &self,
         extraction_policy_ids: HashSet<String>,
-    ) -> Result<Option<Vec<indexify_internal_api::ExtractionPolicy>>> {
+    ) -> Result<ExtractionPoliciesLookup> {
         self.data
             .indexify_state
             .get_extraction_policies_from_ids(extraction_policy_ids, &self.db)
@@ -410,6 +776,18 @@ impl StateMachineStore {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    pub fn get_content_from_ids_paginated(
+        &self,
+        content_ids: HashSet<String>,
+        cursor: Option<String>,
+        limit: usize,
+    ) -> Result<(Vec<ContentMetadata>, Option<String>)> {
+        self.data
+            .indexify_state
+            .get_content_from_ids_paginated(content_ids, cursor, limit, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     pub fn list_content(
         &self,
         namespace: &str,
@@ -440,6 +818,16 @@ impl StateMachineStore {
         Ok(contents)
     }
 
+    pub async fn get_content_from_ids_with_version(
+        &self,
+        content_ids: &[ContentMetadataId],
+    ) -> Result<Vec<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .get_content_from_ids_with_version(content_ids, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     pub async fn get_content_by_id_and_version(
         &self,
         content_id: &ContentMetadataId,
@@ -450,6 +838,129 @@ impl StateMachineStore {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    pub fn get_extractor(&self, name: &str) -> Result<Option<ExtractorDescription>> {
+        self.data
+            .indexify_state
+            .get_extractor(name, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_latest_tombstoned_version(
+        &self,
+        content_id: &str,
+    ) -> Result<Option<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .get_latest_tombstoned_version(content_id, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn safe_prune_versions(&self, content_id: &str) -> Result<usize> {
+        self.data
+            .indexify_state
+            .safe_prune_versions(content_id, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_content_by_id_prefix(
+        &self,
+        namespace: &str,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .get_content_by_id_prefix(namespace, prefix, &self.db, limit)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_policy_coverage(&self, namespace: &str, policy_id: &str) -> Result<PolicyCoverage> {
+        self.data
+            .indexify_state
+            .get_policy_coverage(namespace, policy_id, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_ingestion_key(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> Result<Option<IngestionKeyRecord>> {
+        self.data
+            .indexify_state
+            .get_ingestion_key(namespace, key, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_usage_stats(
+        &self,
+        namespace: Option<&str>,
+        from_day: u64,
+        to_day: u64,
+    ) -> Result<Vec<indexify_internal_api::UsageStats>> {
+        self.data
+            .indexify_state
+            .get_usage_stats(namespace, from_day, to_day, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_namespace_index_overview(&self, namespace: &str) -> Result<Vec<IndexOverview>> {
+        self.data
+            .indexify_state
+            .get_namespace_index_overview(namespace, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_recently_updated_content(
+        &self,
+        namespace: &str,
+        n: usize,
+    ) -> Result<Vec<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .get_recently_updated_content(namespace, n, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_unprocessed_content(&self, namespace: &str) -> Result<Vec<ContentMetadataId>> {
+        self.data
+            .indexify_state
+            .get_unprocessed_content(namespace, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn query_content(
+        &self,
+        namespace: &str,
+        query: ContentQuery,
+    ) -> Result<Vec<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .query_content(namespace, query, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_content_by_source(
+        &self,
+        namespace: &str,
+        source: &str,
+    ) -> Result<Vec<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .get_content_by_source(namespace, source, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_referenced_storage_urls(
+        &self,
+        storage_urls: &HashSet<String>,
+    ) -> Result<HashSet<String>> {
+        self.data
+            .indexify_state
+            .get_referenced_storage_urls(storage_urls, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     pub fn get_content_tree_metadata(&self, content_id: &str) -> Result<Vec<ContentMetadata>> {
         self.data
             .indexify_state
@@ -457,6 +968,24 @@ impl StateMachineStore {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    pub fn get_content_for_task(&self, task_id: &str) -> Result<Vec<ContentMetadata>> {
+        self.data
+            .indexify_state
+            .get_content_for_task(task_id, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_content_subtree(
+        &self,
+        root_id: &str,
+        max_depth: u32,
+    ) -> Result<Vec<(ContentMetadata, u32)>> {
+        self.data
+            .indexify_state
+            .get_content_subtree(root_id, max_depth, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     pub fn get_content_tree_metadata_with_version(
         &self,
         content_id: &ContentMetadataId,
@@ -467,6 +996,152 @@ impl StateMachineStore {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    pub fn check_content_exists(
+        &self,
+        namespace: &str,
+        keys: &[indexify_internal_api::ContentLookupKey],
+    ) -> Result<Vec<indexify_internal_api::ContentExistence>> {
+        self.data
+            .indexify_state
+            .check_content_exists(namespace, keys, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_search_profile(
+        &self,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<indexify_internal_api::SearchProfile>> {
+        self.data
+            .indexify_state
+            .get_search_profile(namespace, name, &self.db)
+    }
+
+    pub fn get_policy_dependency_graph(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<(ExtractionPolicyId, Vec<ExtractionPolicyId>)>> {
+        self.data
+            .indexify_state
+            .get_policy_dependency_graph(namespace, &self.db)
+    }
+
+    pub fn get_namespace_retention_rules(
+        &self,
+    ) -> Result<HashMap<NamespaceName, indexify_internal_api::RetentionRules>> {
+        self.data
+            .indexify_state
+            .get_namespace_retention_rules(&self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn has_unfinished_tasks(&self, root_content_id: &str) -> bool {
+        self.data
+            .indexify_state
+            .has_unfinished_tasks(root_content_id)
+    }
+
+    pub fn get_namespace_alias(&self, alias: &str) -> Result<Option<String>> {
+        self.data
+            .indexify_state
+            .get_namespace_alias(alias, &self.db)
+    }
+
+    pub fn list_search_profiles(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<indexify_internal_api::SearchProfile>> {
+        self.data
+            .indexify_state
+            .list_search_profiles(namespace, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn list_search_feedback(
+        &self,
+        query: &indexify_internal_api::SearchFeedbackQuery,
+    ) -> Result<(Vec<indexify_internal_api::SearchFeedback>, Option<String>)> {
+        self.data
+            .indexify_state
+            .list_search_feedback(query, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_search_feedback_counts(
+        &self,
+        namespace: &str,
+        content_ids: &HashSet<String>,
+    ) -> Result<HashMap<String, indexify_internal_api::SearchFeedbackCounts>> {
+        self.data
+            .indexify_state
+            .get_search_feedback_counts(namespace, content_ids, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn get_subscription(
+        &self,
+        subscription_id: &str,
+    ) -> Result<Option<indexify_internal_api::Subscription>> {
+        self.data
+            .indexify_state
+            .get_subscription(subscription_id, &self.db)
+    }
+
+    pub fn list_subscriptions(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<indexify_internal_api::Subscription>> {
+        self.data
+            .indexify_state
+            .list_subscriptions(namespace, &self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn list_pending_deliveries(
+        &self,
+        subscription_id: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<indexify_internal_api::Delivery>> {
+        self.data
+            .indexify_state
+            .list_deliveries(
+                StateMachineColumns::PendingDeliveries,
+                subscription_id,
+                limit,
+                &self.db,
+            )
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn list_dead_letters(
+        &self,
+        subscription_id: &str,
+    ) -> Result<Vec<indexify_internal_api::Delivery>> {
+        self.data
+            .indexify_state
+            .list_deliveries(
+                StateMachineColumns::DeadLetterDeliveries,
+                subscription_id,
+                None,
+                &self.db,
+            )
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn spot_check_reverse_indexes(&self, sample_size: usize) -> Result<Option<String>> {
+        self.data
+            .indexify_state
+            .spot_check_reverse_indexes(&self.db, sample_size)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    pub fn delivery_queue_stats(&self) -> Result<Vec<DeliveryQueueStats>> {
+        self.data
+            .indexify_state
+            .delivery_queue_stats(&self.db)
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     pub async fn get_namespace(
         &self,
         namespace: &str,
@@ -522,6 +1197,10 @@ impl StateMachineStore {
     //  END FORWARD INDEX READER METHOD INTERFACES
 
     //  START REVERSE INDEX READER METHOD INTERFACES
+    pub fn reverse_index_sizes(&self) -> Vec<ReverseIndexSize> {
+        self.data.indexify_state.reverse_index_sizes()
+    }
+
     pub async fn get_unassigned_tasks(&self) -> HashSet<TaskId> {
         self.data.indexify_state.get_unassigned_tasks()
     }
@@ -536,6 +1215,12 @@ impl StateMachineStore {
         self.data.indexify_state.get_content_namespace_table()
     }
 
+    pub fn get_content_by_source_table(
+        &self,
+    ) -> HashMap<NamespaceName, HashMap<String, HashSet<String>>> {
+        self.data.indexify_state.get_content_by_source_table()
+    }
+
     pub async fn get_extraction_policies_table(&self) -> HashMap<NamespaceName, HashSet<String>> {
         self.data.indexify_state.get_extraction_policies_table()
     }
@@ -556,13 +1241,20 @@ impl StateMachineStore {
         self.data.indexify_state.get_unfinished_tasks_by_extractor()
     }
 
+    pub async fn get_pending_tasks_for_content_version(
+        &self,
+        content_id: &ContentMetadataId,
+    ) -> HashMap<ExtractionPolicyId, HashSet<TaskId>> {
+        self.data
+            .indexify_state
+            .get_pending_tasks_for_content_version(content_id)
+    }
+
     pub async fn get_executor_running_task_count(&self) -> HashMap<ExecutorId, u64> {
         self.data.indexify_state.get_executor_running_task_count()
     }
 
-    pub async fn get_schemas_by_namespace(
-        &self,
-    ) -> HashMap<NamespaceName, HashSet<ExtractionGraphId>> {
+    pub async fn get_schemas_by_namespace(&self) -> HashMap<NamespaceName, HashSet<SchemaId>> {
         self.data.indexify_state.get_schemas_by_namespace()
     }
 
@@ -609,7 +1301,8 @@ impl RaftSnapshotBuilder<TypeConfig> for Arc<StateMachineStore> {
                 .indexify_state
                 .build_snapshot(&self.db)
                 .map_err(|e| StorageIOError::read_state_machine(&e))?;
-            JsonEncoder::encode(&indexify_state_snapshot)
+            ChunkedSnapshot::from_state(&indexify_state_snapshot)
+                .and_then(|snapshot| snapshot.encode())
                 .map_err(|e| StorageIOError::read_state_machine(&e))?
         };
 
@@ -675,6 +1368,8 @@ impl RaftStateMachine<TypeConfig> for Arc<StateMachineStore> {
                 *guard = Some(ent.log_id);
             }
             let resp_value = None;
+            let mut resp_error = None;
+            let mut written_content = Vec::new();
             match ent.payload {
                 EntryPayload::Blank => {}
                 EntryPayload::Normal(req) => {
@@ -683,18 +1378,44 @@ impl RaftStateMachine<TypeConfig> for Arc<StateMachineStore> {
                         .indexify_state
                         .apply_state_machine_updates(req.clone(), &self.db)
                     {
-                        Ok(changes) => {
-                            change_events.extend(changes);
+                        Ok(applied) => {
+                            change_events.extend(applied.new_state_changes);
+                            written_content = applied.written_content;
+                        }
+                        Err(
+                            e @ (StateMachineError::NamespaceNotFound(_) |
+                            StateMachineError::ImmutableContentField { .. } |
+                            StateMachineError::ContentNamespaceMismatch { .. } |
+                            StateMachineError::InvalidSchemaId { .. } |
+                            StateMachineError::SchemaInUse { .. } |
+                            StateMachineError::IncompatibleExtractorUpdate { .. } |
+                            StateMachineError::InvalidNamespaceAlias { .. } |
+                            StateMachineError::TaskNotFound(_) |
+                            StateMachineError::StateChangeNotFound(_) |
+                            StateMachineError::ExtractionPolicyNotFound(_) |
+                            StateMachineError::ExtractionPolicyInUse { .. } |
+                            StateMachineError::InvalidTaskTransition { .. }),
+                        ) => {
+                            resp_error = Some(e.to_string());
                         }
                         Err(e) => {
                             panic!("error applying state machine update: {}", e);
                         }
                     }
 
-                    //  if the payload is a GC task, send it via channel
-                    if let RequestPayload::CreateOrAssignGarbageCollectionTask { gc_tasks } =
-                        req.payload
-                    {
+                    //  if the payload is a GC task, send it via channel, tasks
+                    //  which aren't finished are sent again to be resumed
+                    let gc_tasks = match req.payload {
+                        RequestPayload::CreateOrAssignGarbageCollectionTask { gc_tasks } => {
+                            Some(gc_tasks)
+                        }
+                        RequestPayload::UpdateGarbageCollectionTask {
+                            gc_task,
+                            mark_finished: false,
+                        } if resp_error.is_none() => Some(vec![gc_task]),
+                        _ => None,
+                    };
+                    if let Some(gc_tasks) = gc_tasks {
                         let expected_receiver_count = self.data.gc_tasks_tx.receiver_count();
                         for gc_task in gc_tasks {
                             match self.data.gc_tasks_tx.send(gc_task.clone()) {
@@ -718,7 +1439,11 @@ impl RaftStateMachine<TypeConfig> for Arc<StateMachineStore> {
                 }
             }
 
-            replies.push(Response { value: resp_value });
+            replies.push(Response {
+                value: resp_value,
+                error: resp_error,
+                written_content,
+            });
         }
 
         for change_event in change_events {
@@ -1004,6 +1729,9 @@ impl RaftLogStorage<TypeConfig> for LogStore {
 pub(crate) async fn new_storage<P: AsRef<Path>>(
     db_path: P,
     snapshot_path: P,
+    value_size_limits: ValueSizeLimitsConfig,
+    multi_get_batch_size: usize,
+    content_version_separator: ContentVersionSeparator,
 ) -> (LogStore, Arc<StateMachineStore>) {
     let mut db_opts = Options::default();
     db_opts.create_missing_column_families(true);
@@ -1033,18 +1761,57 @@ pub(crate) async fn new_storage<P: AsRef<Path>>(
 
     let snapshot_path = PathBuf::from(snapshot_path.as_ref());
 
-    let sm_store = StateMachineStore::new(db, snapshot_path).await.unwrap();
+    let sm_store = StateMachineStore::new(
+        db,
+        snapshot_path,
+        value_size_limits,
+        multi_get_batch_size,
+        content_version_separator,
+    )
+    .await
+    .unwrap();
 
     (log_store, Arc::new(sm_store))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
-
-    use indexify_internal_api::ContentMetadataId;
-
-    use crate::{state::RaftConfigOverrides, test_utils::RaftTestCluster};
+    use std::{
+        collections::{HashMap, HashSet},
+        panic::AssertUnwindSafe,
+        time::Duration,
+    };
+
+    use futures::FutureExt;
+    use indexify_internal_api::{
+        ChangeType,
+        ContentMetadataId,
+        ContentVersionSeparator,
+        ExtractorDescription,
+        StateChange,
+        StructuredDataSchema,
+        DEFAULT_CONTENT_VERSION_SEPARATOR,
+    };
+    use rocksdb::{OptimisticTransactionDB, Options};
+    use serde_json::json;
+
+    use super::{
+        new_storage,
+        requests::{CreateOrUpdateContentEntry, RequestPayload, StateMachineUpdateRequest},
+        serializer::{JsonEncode, JsonEncoder},
+        StateMachineColumns,
+        StateMachineError,
+    };
+    use crate::{
+        server_config::{ValueSizeLimitsConfig, DEFAULT_MULTI_GET_BATCH_SIZE},
+        state::RaftConfigOverrides,
+        test_util::db_utils::{
+            create_test_extraction_graph,
+            mock_extractor,
+            DEFAULT_TEST_NAMESPACE,
+        },
+        test_utils::RaftTestCluster,
+    };
 
     /// This is a dummy test which forces building a snapshot on the cluster by
     /// passing in some overrides Manually check that the snapshot file was
@@ -1082,6 +1849,11 @@ mod tests {
         let (key, value) = content_table.iter().next().unwrap();
         assert_eq!(*key, namespace);
         assert_eq!(value.len(), 1);
+        let content_by_source = new_node.state_machine.get_content_by_source_table();
+        assert_eq!(
+            content_by_source[&namespace][""],
+            HashSet::from(["content_id".to_string()])
+        );
 
         let contents = new_node.list_content(&namespace, "", |_| true).await?;
         assert_eq!(contents.len(), 1);
@@ -1091,4 +1863,447 @@ mod tests {
         assert_eq!(c.namespace, namespace);
         Ok(())
     }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_policy_state_ignores_stale_content_version() -> anyhow::Result<()> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let content_v1 = indexify_internal_api::ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            hash: "hash_1".into(),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content_v1.clone()]).await?;
+        node.create_content_batch(vec![indexify_internal_api::ContentMetadata {
+            hash: "hash_2".into(),
+            ..content_v1.clone()
+        }])
+        .await?;
+        let content_v2 = node
+            .state_machine
+            .get_latest_version_of_content("content_id")?
+            .unwrap();
+        assert_eq!(content_v2.id.version, 2);
+
+        let tasks = vec![
+            indexify_internal_api::Task {
+                id: "task_1".into(),
+                extraction_policy_id: "policy_1".into(),
+                content_metadata: content_v1,
+                ..Default::default()
+            },
+            indexify_internal_api::Task {
+                id: "task_2".into(),
+                extraction_policy_id: "policy_2".into(),
+                content_metadata: content_v2,
+                ..Default::default()
+            },
+        ];
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+
+        let mapping = node
+            .state_machine
+            .get_latest_policy_mapping("content_id")?
+            .unwrap();
+        assert!(!mapping.contains_key("policy_1"));
+        assert!(mapping.contains_key("policy_2"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_policy_mappings_for_contents() -> anyhow::Result<()> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+
+        let contents: Vec<_> = ["content_1", "content_2", "content_3"]
+            .into_iter()
+            .map(|id| indexify_internal_api::ContentMetadata {
+                id: ContentMetadataId::new(id),
+                hash: id.into(),
+                ..Default::default()
+            })
+            .collect();
+        node.create_content_batch(contents.clone()).await?;
+
+        //  content_3 has no policies applied
+        let tasks = vec![
+            indexify_internal_api::Task {
+                id: "task_1".into(),
+                extraction_policy_id: "policy_1".into(),
+                content_metadata: contents[0].clone(),
+                ..Default::default()
+            },
+            indexify_internal_api::Task {
+                id: "task_2".into(),
+                extraction_policy_id: "policy_2".into(),
+                content_metadata: contents[1].clone(),
+                ..Default::default()
+            },
+        ];
+        let request = StateMachineUpdateRequest {
+            payload: RequestPayload::CreateTasks { tasks },
+            new_state_changes: vec![],
+            state_changes_processed: vec![],
+            idempotency_key: None,
+        };
+        node.forwardable_raft.client_write(request).await?;
+
+        let mappings = node.state_machine.get_policy_mappings_for_contents(
+            ["content_1", "content_2", "content_3", "missing"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        )?;
+        assert_eq!(mappings.len(), 2);
+        assert!(mappings["content_1"].contains_key("policy_1"));
+        assert!(mappings["content_2"].contains_key("policy_2"));
+        assert!(!mappings.contains_key("content_3"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_idempotency_key_applies_request_once() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/idempotency_key";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+        let request = |key: &str| StateMachineUpdateRequest {
+            payload: RequestPayload::CreateNamespace {
+                name: DEFAULT_TEST_NAMESPACE.to_string(),
+                structured_data_schema: None,
+            },
+            new_state_changes: vec![StateChange::new(
+                DEFAULT_TEST_NAMESPACE.to_string(),
+                ChangeType::NewContent,
+                0,
+            )],
+            state_changes_processed: vec![],
+            idempotency_key: Some(key.to_string()),
+        };
+        let count_state_changes = || {
+            sm.db
+                .iterator_cf(
+                    StateMachineColumns::StateChanges.cf(&sm.db),
+                    rocksdb::IteratorMode::Start,
+                )
+                .count()
+        };
+
+        let first = state.apply_state_machine_updates(request("key_1"), &sm.db)?;
+        let second = state.apply_state_machine_updates(request("key_1"), &sm.db)?;
+        assert_eq!(first, second);
+        assert_eq!(count_state_changes(), 1);
+        assert_eq!(state.get_unprocessed_state_changes().len(), 1);
+
+        //  A different key is applied again
+        let third = state.apply_state_machine_updates(request("key_2"), &sm.db)?;
+        assert_ne!(first, third);
+        assert_eq!(count_state_changes(), 2);
+
+        //  Unexpired keys survive compaction
+        assert_eq!(state.compact_idempotency_keys(&sm.db)?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_safe_prune_versions() -> anyhow::Result<()> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        node.register_executor("localhost:8950", "executor_1", vec![mock_extractor()])
+            .await?;
+        let eg = create_test_extraction_graph("graph", vec!["embeddings"]);
+        node.create_extraction_graph(
+            eg.clone(),
+            StructuredDataSchema::new(&eg.name, &eg.namespace),
+            vec![],
+        )
+        .await?;
+        let policy_id = eg.extraction_policies[0].id.clone();
+        assert!(!eg.extraction_policies[0].output_table_mapping.is_empty());
+
+        let content = |version: u64, latest: bool| indexify_internal_api::ContentMetadata {
+            id: ContentMetadataId::new_with_version("doc", version),
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            latest,
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content(5, true)]).await?;
+
+        //  v1 was indexed and v3 has content extracted from it, v2 and v4 are
+        //  unreferenced
+        let sm = node.state_machine.clone();
+        let content_cf = StateMachineColumns::ContentTable.cf(&sm.db);
+        for version in 1..=4 {
+            let mut content = content(version, false);
+            if version == 1 {
+                content.extraction_policy_ids = HashMap::from([(policy_id.clone(), 1)]);
+            }
+            sm.db.put_cf(
+                content_cf,
+                content.id_key(&sm.data.indexify_state.content_version_separator),
+                JsonEncoder::encode(&content)?,
+            )?;
+        }
+        sm.data.indexify_state.content_children_table.insert(
+            &ContentMetadataId::new_with_version("doc", 3),
+            &ContentMetadataId::new("child"),
+        );
+
+        assert_eq!(sm.safe_prune_versions("doc")?, 2);
+        for (version, kept) in [(1, true), (2, false), (3, true), (4, false)] {
+            let id = ContentMetadataId::new_with_version("doc", version);
+            assert_eq!(
+                sm.get_content_by_id_and_version(&id).await?.is_some(),
+                kept,
+                "version {}",
+                version
+            );
+        }
+        assert!(sm.get_latest_version_of_content("doc")?.is_some());
+
+        //  Nothing is left to prune
+        assert_eq!(sm.safe_prune_versions("doc")?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_extractor() -> anyhow::Result<()> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+
+        let extractor = ExtractorDescription {
+            input_params: json!({
+                "type": "object",
+                "properties": {"chunk_size": {"type": "integer"}},
+            }),
+            ..mock_extractor()
+        };
+        node.register_executor("localhost:8950", "executor_1", vec![extractor.clone()])
+            .await?;
+
+        let sm = node.state_machine.clone();
+        assert_eq!(sm.get_extractor(&extractor.name)?, Some(extractor));
+        assert_eq!(sm.get_extractor("unknown_extractor")?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_reads_in_one_transaction() -> anyhow::Result<()> {
+        let cluster = RaftTestCluster::new(1, None).await?;
+        cluster.initialize(Duration::from_secs(2)).await?;
+        let node = cluster.get_raft_node(0)?;
+        node.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+        let content = indexify_internal_api::ContentMetadata {
+            id: ContentMetadataId::new("content_id"),
+            namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+            ..Default::default()
+        };
+        node.create_content_batch(vec![content]).await?;
+
+        let sm = node.state_machine.clone();
+        let state = &sm.data.indexify_state;
+        let txn = sm.db.transaction();
+        let namespace = state
+            .get_namespace_in_txn(DEFAULT_TEST_NAMESPACE, &txn, &sm.db)?
+            .unwrap();
+        assert_eq!(namespace.name, DEFAULT_TEST_NAMESPACE);
+        let schema_id = StructuredDataSchema::empty_for(DEFAULT_TEST_NAMESPACE).id;
+        let schemas = state.get_schemas_in_txn(HashSet::from([schema_id.clone()]), &txn, &sm.db)?;
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schemas[0].id, schema_id);
+        let contents =
+            state.get_content_from_ids_in_txn(vec!["content_id".to_string()], &txn, &sm.db)?;
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0].namespace, DEFAULT_TEST_NAMESPACE);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_content_version_separator() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/content_version_separator";
+        let _ = std::fs::remove_dir_all(path);
+        let open = |separator: &str| {
+            new_storage(
+                format!("{}/db", path),
+                format!("{}/sm-blob", path),
+                ValueSizeLimitsConfig::default(),
+                DEFAULT_MULTI_GET_BATCH_SIZE,
+                ContentVersionSeparator::new(separator).unwrap(),
+            )
+        };
+
+        let (log_store, sm) = open("#ver#").await;
+        let state = &sm.data.indexify_state;
+        for payload in [
+            RequestPayload::CreateNamespace {
+                name: DEFAULT_TEST_NAMESPACE.to_string(),
+                structured_data_schema: None,
+            },
+            RequestPayload::CreateOrUpdateContent {
+                entries: vec![CreateOrUpdateContentEntry {
+                    content: indexify_internal_api::ContentMetadata {
+                        id: ContentMetadataId::new_with_version("content", 2),
+                        namespace: DEFAULT_TEST_NAMESPACE.to_string(),
+                        latest: false,
+                        ..Default::default()
+                    },
+                    previous_parent: None,
+                }],
+                ingestion_key: None,
+            },
+        ] {
+            state.apply_state_machine_updates(
+                StateMachineUpdateRequest {
+                    payload,
+                    new_state_changes: vec![],
+                    state_changes_processed: vec![],
+                    idempotency_key: None,
+                },
+                &sm.db,
+            )?;
+        }
+        let content_cf = StateMachineColumns::ContentTable.cf(&sm.db);
+        assert!(sm.db.get_cf(content_cf, "content#ver#2")?.is_some());
+        assert!(state
+            .get_content_by_id_and_version(
+                &sm.db,
+                &ContentMetadataId::new_with_version("content", 2)
+            )?
+            .is_some());
+        drop((log_store, sm));
+
+        //  The store refuses to start with another separator
+        let reopened = AssertUnwindSafe(open(DEFAULT_CONTENT_VERSION_SEPARATOR))
+            .catch_unwind()
+            .await;
+        assert!(reopened.is_err());
+        let (_, sm) = open("#ver#").await;
+        assert!(sm
+            .data
+            .indexify_state
+            .get_content_by_id_and_version(
+                &sm.db,
+                &ContentMetadataId::new_with_version("content", 2)
+            )?
+            .is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_value_too_large() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/value_size_limits";
+        let _ = std::fs::remove_dir_all(path);
+        let limits = ValueSizeLimitsConfig {
+            default_max_bytes: Some(1024),
+            max_bytes: HashMap::from([("ContentTable".to_string(), 1024 * 1024)]),
+        };
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            limits,
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+
+        let huge = serde_json::to_vec(&"a".repeat(4096))?;
+        let namespaces_cf = StateMachineColumns::Namespaces.cf(&sm.db);
+        sm.db.put_cf(namespaces_cf, "huge", &huge)?;
+        sm.db.put_cf(namespaces_cf, "small", b"\"small\"")?;
+
+        let err = state
+            .get_from_cf::<String, _>(&sm.db, StateMachineColumns::Namespaces, "huge")
+            .unwrap_err();
+        match err.downcast_ref::<StateMachineError>() {
+            Some(StateMachineError::ValueTooLarge { cf, key, size }) => {
+                assert_eq!(cf, "Namespaces");
+                assert_eq!(key, "huge");
+                assert_eq!(*size, huge.len());
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+        assert_eq!(
+            state.get_from_cf::<String, _>(&sm.db, StateMachineColumns::Namespaces, "small")?,
+            Some("small".to_string())
+        );
+        let err = state
+            .get_all_rows_from_cf::<String>(StateMachineColumns::Namespaces, &sm.db)
+            .unwrap_err();
+        assert!(matches!(err, StateMachineError::ValueTooLarge { .. }));
+
+        //  The limit of the content table is larger
+        let content_cf = StateMachineColumns::ContentTable.cf(&sm.db);
+        sm.db.put_cf(content_cf, "huge", &huge)?;
+        assert!(state
+            .get_from_cf::<String, _>(&sm.db, StateMachineColumns::ContentTable, "huge")?
+            .is_some());
+
+        assert_eq!(state.metrics.lock().unwrap().values_too_large, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_conflict_is_retryable() {
+        let path = "/tmp/indexify-test/retryable_errors";
+        let _ = std::fs::remove_dir_all(path);
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db: OptimisticTransactionDB = OptimisticTransactionDB::open(&opts, path).unwrap();
+
+        //  Both transactions read the key, the second one to commit conflicts
+        // with the first
+        let txn1 = db.transaction();
+        let txn2 = db.transaction();
+        txn1.get_for_update("key", true).unwrap();
+        txn2.get_for_update("key", true).unwrap();
+        txn1.put("key", "value1").unwrap();
+        txn2.put("key", "value2").unwrap();
+        txn1.commit().unwrap();
+        let err = txn2
+            .commit()
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))
+            .unwrap_err();
+        assert!(err.is_retryable());
+        assert!(StateMachineError::ExternalError(err.into()).is_retryable());
+    }
+
+    #[test]
+    fn test_serialization_error_is_not_retryable() {
+        let err = StateMachineError::SerializationError("invalid json".to_string());
+        assert!(!err.is_retryable());
+        assert!(!StateMachineError::NamespaceNotFound("test".to_string()).is_retryable());
+        assert!(
+            !StateMachineError::DatabaseError("Corruption: bad block".to_string()).is_retryable()
+        );
+    }
 }
@@ -1,10 +1,13 @@
-use std::{collections::HashMap, time::SystemTime};
+use std::{
+    collections::{HashMap, HashSet},
+    time::SystemTime,
+};
 
 use indexify_internal_api as internal_api;
 use internal_api::{StateChange, StateChangeId};
 use serde::{Deserialize, Serialize};
 
-use super::{ExecutorId, TaskId};
+use super::{state_machine_objects::IngestionKeyRecord, ExecutorId, TaskId};
 use crate::state::NodeId;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -12,6 +15,10 @@ pub struct StateMachineUpdateRequest {
     pub payload: RequestPayload,
     pub new_state_changes: Vec<StateChange>,
     pub state_changes_processed: Vec<StateChangeProcessed>,
+    /// Requests with the same key are applied once while the key is
+    /// remembered, later ones return the result of the first
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -40,12 +47,27 @@ pub enum RequestPayload {
         executor_id: String,
         extractors: Vec<internal_api::ExtractorDescription>,
         ts_secs: u64,
+        #[serde(default)]
+        capabilities: HashMap<String, String>,
     },
     RemoveExecutor {
         executor_id: String,
     },
+    UpdateExecutorExtractor {
+        executor_id: String,
+        extractor: internal_api::ExtractorDescription,
+    },
+    /// Moves an extractor, and every reference to it, to a new name
+    RenameExtractor {
+        old_name: String,
+        new_name: String,
+    },
     CreateNamespace {
         name: String,
+        /// Schema the namespace starts with, the empty default schema when
+        /// not set
+        #[serde(default)]
+        structured_data_schema: Option<internal_api::StructuredDataSchema>,
     },
     CreateTasks {
         tasks: Vec<internal_api::Task>,
@@ -53,6 +75,26 @@ pub enum RequestPayload {
     AssignTask {
         assignments: HashMap<TaskId, ExecutorId>,
     },
+    /// Clears the completion of the extraction policy on the content and
+    /// creates the tasks which apply it again
+    RebuildContentTree {
+        extraction_policy_id: String,
+        content_ids: Vec<String>,
+        tasks: Vec<internal_api::Task>,
+    },
+    /// Cancels tasks which are still waiting for an executor. Tasks which
+    /// were assigned or finished in the meantime are left alone.
+    CancelTasks {
+        task_ids: Vec<TaskId>,
+    },
+    TransitionTask {
+        task_id: TaskId,
+        to: internal_api::TaskLifecycleState,
+        update_time: SystemTime,
+    },
+    RequeueTasksForExtractor {
+        extractor: String,
+    },
     CreateOrAssignGarbageCollectionTask {
         gc_tasks: Vec<internal_api::GarbageCollectionTask>,
     },
@@ -67,9 +109,16 @@ pub enum RequestPayload {
     },
     CreateOrUpdateContent {
         entries: Vec<CreateOrUpdateContentEntry>,
+        /// Recorded with the content, requests whose key was already recorded
+        /// are not applied
+        #[serde(default)]
+        ingestion_key: Option<IngestionKeyRecord>,
     },
     TombstoneContentTree {
         content_metadata: Vec<internal_api::ContentMetadata>,
+        /// Recorded with every content of the trees
+        #[serde(default)]
+        reason: internal_api::TombstoneReason,
     },
     SetIndex {
         indexes: Vec<internal_api::Index>,
@@ -82,9 +131,88 @@ pub enum RequestPayload {
     MarkStateChangesProcessed {
         state_changes: Vec<StateChangeProcessed>,
     },
+    /// Marks a state change unprocessed again, so the scheduler retries it
+    ReprocessStateChange {
+        state_change_id: StateChangeId,
+    },
+    SetSearchProfile {
+        profile: internal_api::SearchProfile,
+    },
+    DeleteSearchProfile {
+        namespace: String,
+        name: String,
+    },
+    CreateSearchFeedback {
+        feedback: internal_api::SearchFeedback,
+    },
+    /// Points the alias at the namespace, moving it when it points at
+    /// another namespace
+    SetNamespaceAlias {
+        alias: String,
+        namespace: String,
+    },
+    CreateSubscription {
+        subscription: internal_api::Subscription,
+    },
+    /// Deletes the subscription along with its pending and dead lettered
+    /// deliveries
+    DeleteSubscription {
+        subscription_id: String,
+    },
+    /// Appends the deliveries to the queues of their subscriptions in order,
+    /// their sequences are assigned when applied. Deliveries of subscriptions
+    /// which no longer exist are dropped.
+    EnqueueDeliveries {
+        deliveries: Vec<internal_api::Delivery>,
+    },
+    AckDelivery {
+        subscription_id: String,
+        sequence: u64,
+    },
+    /// Records a failed attempt of a pending delivery
+    RetryDelivery {
+        delivery: internal_api::Delivery,
+    },
+    /// Moves a pending delivery which failed permanently to the dead letters
+    DeadLetterDelivery {
+        delivery: internal_api::Delivery,
+    },
+    /// Moves the dead letters of the subscription back to its queue, with
+    /// their original sequences
+    RequeueDeadLetters {
+        subscription_id: String,
+    },
+    DeleteSchema {
+        namespace: String,
+        schema_id: String,
+    },
+    UpdateExtractionPolicyParams {
+        extraction_policy_id: String,
+        input_params: serde_json::Value,
+    },
+    /// Deletes the extraction policy, tasks already created for it are left
+    /// alone
+    DeleteExtractionPolicy {
+        namespace: String,
+        extraction_policy_id: String,
+    },
+    /// Removes extraction policies which no longer exist from the reverse
+    /// index of the namespace. Policies which exist again are kept.
+    RemoveDanglingExtractionPolicies {
+        namespace: String,
+        extraction_policy_ids: HashSet<String>,
+    },
+    /// Replaces the retention rules of the namespace
+    SetNamespaceRetention {
+        namespace: String,
+        rules: internal_api::RetentionRules,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StateMachineUpdateResponse {
     pub handled_by: NodeId,
+    /// Ids and versions of the content written by the request
+    #[serde(default)]
+    pub written_content: Vec<internal_api::ContentMetadataId>,
 }
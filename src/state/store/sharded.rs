@@ -0,0 +1,285 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// Number of shards of a sharded reverse index. A key always maps to the same
+/// shard, on every node, since the hasher isn't randomly seeded.
+pub const SHARD_COUNT: usize = 16;
+
+fn shard_index<K: Hash + ?Sized>(key: &K) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % SHARD_COUNT as u64) as usize
+}
+
+/// A reverse index split into shards with a lock each, so that writes of
+/// different keys don't wait for each other or for readers of other shards.
+///
+/// Reads spanning every shard take all the locks in shard order, the same
+/// order `write_all` takes them in, so they see a consistent index.
+#[derive(Debug)]
+pub struct Sharded<T> {
+    shards: Arc<[RwLock<T>]>,
+}
+
+impl<T> Clone for Sharded<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self.shards.clone(),
+        }
+    }
+}
+
+impl<T: Default> Default for Sharded<T> {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARD_COUNT)
+                .map(|_| RwLock::new(T::default()))
+                .collect(),
+        }
+    }
+}
+
+impl<T> Sharded<T> {
+    pub fn read<K: Hash + ?Sized>(&self, key: &K) -> RwLockReadGuard<'_, T> {
+        self.shards[shard_index(key)].read().unwrap()
+    }
+
+    pub fn write<K: Hash + ?Sized>(&self, key: &K) -> RwLockWriteGuard<'_, T> {
+        self.shards[shard_index(key)].write().unwrap()
+    }
+
+    pub fn read_all(&self) -> Vec<RwLockReadGuard<'_, T>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap())
+            .collect()
+    }
+
+    pub fn write_all(&self) -> ShardedWriteGuard<'_, T> {
+        ShardedWriteGuard {
+            guards: self
+                .shards
+                .iter()
+                .map(|shard| shard.write().unwrap())
+                .collect(),
+        }
+    }
+}
+
+/// Write locks of every shard of a sharded reverse index
+pub struct ShardedWriteGuard<'a, T> {
+    guards: Vec<RwLockWriteGuard<'a, T>>,
+}
+
+impl<T> ShardedWriteGuard<'_, T> {
+    pub fn shard<K: Hash + ?Sized>(&mut self, key: &K) -> &mut T {
+        &mut self.guards[shard_index(key)]
+    }
+
+    pub fn shards(&mut self) -> impl Iterator<Item = &mut T> {
+        self.guards.iter_mut().map(|guard| &mut **guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use indexify_internal_api::ContentMetadataId;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::state::store::state_machine_objects::{ContentNamespaceTable, UnassignedTasks};
+
+    //  The reverse indexes before they were sharded, the sharded ones must
+    //  behave the same
+    #[derive(Default)]
+    struct UnshardedContentNamespaceTable {
+        table: HashMap<String, HashSet<ContentMetadataId>>,
+    }
+
+    impl UnshardedContentNamespaceTable {
+        fn insert(&mut self, namespace: &str, content_id: &ContentMetadataId) {
+            self.table
+                .entry(namespace.to_string())
+                .or_default()
+                .insert(content_id.clone());
+        }
+
+        fn remove(&mut self, namespace: &str, content_id: &ContentMetadataId) {
+            self.table
+                .entry(namespace.to_string())
+                .or_default()
+                .remove(content_id);
+        }
+    }
+
+    fn random_content_id(rng: &mut StdRng) -> ContentMetadataId {
+        ContentMetadataId {
+            id: format!("content_{}", rng.gen_range(0..50)),
+            version: rng.gen_range(1..3),
+        }
+    }
+
+    #[test]
+    fn test_sharded_unassigned_tasks_match_unsharded() {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let sharded = UnassignedTasks::default();
+            let mut unsharded = HashSet::new();
+            for _ in 0..500 {
+                let task_id = format!("task_{}", rng.gen_range(0..100));
+                match rng.gen_range(0..10) {
+                    0 => {
+                        let tasks: HashSet<String> = (0..rng.gen_range(0..20))
+                            .map(|i| format!("task_{}", i))
+                            .collect();
+                        sharded.set(tasks.clone());
+                        unsharded = tasks;
+                    }
+                    1..=5 => {
+                        sharded.insert(&task_id);
+                        unsharded.insert(task_id);
+                    }
+                    _ => {
+                        sharded.remove(&task_id);
+                        unsharded.remove(&task_id);
+                    }
+                }
+                assert_eq!(sharded.count(), unsharded.len(), "seed {}", seed);
+            }
+            assert_eq!(sharded.inner(), unsharded, "seed {}", seed);
+            assert_eq!(UnassignedTasks::from(unsharded.clone()).inner(), unsharded);
+        }
+    }
+
+    #[test]
+    fn test_sharded_content_namespace_table_matches_unsharded() {
+        let namespaces = ["ns_1", "ns_2", "ns_3"];
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let sharded = ContentNamespaceTable::default();
+            let mut unsharded = UnshardedContentNamespaceTable::default();
+            for _ in 0..500 {
+                let namespace = namespaces[rng.gen_range(0..namespaces.len())].to_string();
+                let content_id = random_content_id(&mut rng);
+                if rng.gen_bool(0.6) {
+                    sharded.insert(&namespace, &content_id);
+                    unsharded.insert(&namespace, &content_id);
+                } else {
+                    sharded.remove(&namespace, &content_id);
+                    unsharded.remove(&namespace, &content_id);
+                }
+                assert_eq!(
+                    sharded.contains(&namespace, &content_id),
+                    unsharded.table[&namespace].contains(&content_id)
+                );
+            }
+            for namespace in namespaces {
+                assert_eq!(
+                    sharded.get(namespace),
+                    unsharded.table.get(namespace).cloned().unwrap_or_default(),
+                    "seed {}",
+                    seed
+                );
+            }
+            assert_eq!(sharded.inner(), unsharded.table, "seed {}", seed);
+            let restored = ContentNamespaceTable::from(unsharded.table.clone());
+            assert_eq!(restored.inner(), unsharded.table);
+            assert_eq!(restored.size(), sharded.size());
+        }
+    }
+
+    #[test]
+    fn test_serialization_is_unchanged() {
+        let tasks = UnassignedTasks::from(HashSet::from(["task_1".to_string()]));
+        assert_eq!(
+            serde_json::to_value(&tasks).unwrap(),
+            serde_json::json!({"unassigned_tasks": ["task_1"]})
+        );
+        let tasks: UnassignedTasks =
+            serde_json::from_value(serde_json::json!({"unassigned_tasks": ["task_2"]})).unwrap();
+        assert_eq!(tasks.inner(), HashSet::from(["task_2".to_string()]));
+    }
+
+    /// Time for writer threads to insert and remove keys while reader threads
+    /// keep listing the index
+    fn contention<I: Sync>(
+        index: &I,
+        insert: impl Fn(&I, String) + Sync,
+        remove: impl Fn(&I, &str) + Sync,
+        list: impl Fn(&I) -> usize + Sync,
+    ) -> Duration {
+        let writers = 8;
+        let readers = 2;
+        let keys_per_writer = 20_000;
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for writer in 0..writers {
+                let (insert, remove) = (&insert, &remove);
+                scope.spawn(move || {
+                    for i in 0..keys_per_writer {
+                        let key = format!("task_{}_{}", writer, i);
+                        insert(index, key.clone());
+                        if i % 2 == 0 {
+                            remove(index, &key);
+                        }
+                    }
+                });
+            }
+            for _ in 0..readers {
+                let list = &list;
+                scope.spawn(move || {
+                    for _ in 0..50 {
+                        list(index);
+                    }
+                });
+            }
+        });
+        start.elapsed()
+    }
+
+    //  Timing dependent, run with
+    //  cargo test test_benchmark_sharded_contention -- --ignored
+    #[test]
+    #[ignore]
+    fn test_benchmark_sharded_contention() {
+        let unsharded = RwLock::new(HashSet::new());
+        let unsharded_elapsed = contention(
+            &unsharded,
+            |index, key| {
+                index.write().unwrap().insert(key);
+            },
+            |index, key| {
+                index.write().unwrap().remove(key);
+            },
+            |index| index.read().unwrap().clone().len(),
+        );
+        let sharded = UnassignedTasks::default();
+        let sharded_elapsed = contention(
+            &sharded,
+            |index, key| index.insert(&key),
+            |index, key| index.remove(&key.to_string()),
+            |index| index.inner().len(),
+        );
+        assert_eq!(
+            sharded.count(),
+            unsharded.read().unwrap().len(),
+            "the indexes differ after the benchmark"
+        );
+        assert!(
+            sharded_elapsed < unsharded_elapsed,
+            "sharded into {} shards took {:?}, unsharded took {:?}",
+            SHARD_COUNT,
+            sharded_elapsed,
+            unsharded_elapsed
+        );
+    }
+}
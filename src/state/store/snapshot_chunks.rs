@@ -0,0 +1,288 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{Cursor, Read},
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use openraft::{BasicNode, SnapshotMeta, Vote};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{
+    serializer::{JsonEncode, JsonEncoder},
+    state_machine_objects::IndexifyStateSnapshot,
+    StateMachineError,
+};
+use crate::state::NodeId;
+
+/// Prefix of snapshot data in the chunked encoding. Snapshot data without it
+/// is a plain JSON encoded `IndexifyStateSnapshot` written by older nodes.
+const MAGIC: &[u8] = b"IDXCHNK1";
+
+/// Bumped whenever the way tables are split into chunks changes. Nodes only
+/// exchange chunks when both sides use the same version.
+pub const CHUNK_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotChunkInfo {
+    pub name: String,
+    pub hash: String,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    pub chunks: Vec<SnapshotChunkInfo>,
+}
+
+impl SnapshotManifest {
+    pub fn is_compatible(&self, other: &SnapshotManifest) -> bool {
+        self.format_version == other.format_version
+    }
+
+    /// Returns the chunks of this manifest whose content is not part of the
+    /// other manifest
+    pub fn missing_from(&self, other: &SnapshotManifest) -> Vec<&SnapshotChunkInfo> {
+        let present: HashSet<&str> = other.chunks.iter().map(|c| c.hash.as_str()).collect();
+        self.chunks
+            .iter()
+            .filter(|c| !present.contains(c.hash.as_str()))
+            .collect()
+    }
+}
+
+/// Sent by the leader to install a snapshot on a follower with only the
+/// chunks the follower does not already have. Chunks are keyed by hash.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstallSnapshotChunksRequest {
+    pub vote: Vote<NodeId>,
+    pub meta: SnapshotMeta<NodeId, BasicNode>,
+    pub manifest: SnapshotManifest,
+    pub chunks: BTreeMap<String, String>,
+}
+
+/// A state machine snapshot split into one chunk per table. Each chunk is the
+/// table encoded as JSON with sorted keys, so unchanged tables hash the same
+/// across snapshots and nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkedSnapshot {
+    pub manifest: SnapshotManifest,
+    chunks: Vec<String>,
+}
+
+fn chunk_hash(data: &str) -> String {
+    format!("{:x}", Sha256::digest(data.as_bytes()))
+}
+
+fn serialization_err(e: impl ToString) -> StateMachineError {
+    StateMachineError::SerializationError(e.to_string())
+}
+
+impl ChunkedSnapshot {
+    pub fn from_state(state: &IndexifyStateSnapshot) -> Result<Self, StateMachineError> {
+        let serde_json::Value::Object(tables) =
+            serde_json::to_value(state).map_err(serialization_err)?
+        else {
+            return Err(serialization_err(
+                "snapshot is not encoded as a JSON object",
+            ));
+        };
+        let mut infos = Vec::with_capacity(tables.len());
+        let mut chunks = Vec::with_capacity(tables.len());
+        for (name, table) in tables {
+            let data = serde_json::to_string(&table).map_err(serialization_err)?;
+            infos.push(SnapshotChunkInfo {
+                name,
+                hash: chunk_hash(&data),
+                len: data.len(),
+            });
+            chunks.push(data);
+        }
+        Ok(Self {
+            manifest: SnapshotManifest {
+                format_version: CHUNK_FORMAT_VERSION,
+                chunks: infos,
+            },
+            chunks,
+        })
+    }
+
+    pub fn to_state(&self) -> Result<IndexifyStateSnapshot, StateMachineError> {
+        let mut tables = serde_json::Map::new();
+        for (info, data) in self.manifest.chunks.iter().zip(&self.chunks) {
+            let table = serde_json::from_str(data).map_err(serialization_err)?;
+            tables.insert(info.name.clone(), table);
+        }
+        serde_json::from_value(serde_json::Value::Object(tables)).map_err(serialization_err)
+    }
+
+    fn chunk_by_hash(&self, hash: &str) -> Option<&String> {
+        self.manifest
+            .chunks
+            .iter()
+            .position(|c| c.hash == hash)
+            .map(|i| &self.chunks[i])
+    }
+
+    /// Returns the chunks named in `infos`, keyed by hash
+    pub fn chunks_for(&self, infos: &[&SnapshotChunkInfo]) -> BTreeMap<String, String> {
+        infos
+            .iter()
+            .filter_map(|info| {
+                self.chunk_by_hash(&info.hash)
+                    .map(|data| (info.hash.clone(), data.clone()))
+            })
+            .collect()
+    }
+
+    /// Rebuilds a snapshot from its manifest using the provided chunks and
+    /// the chunks of a local snapshot. Every chunk is checked against its
+    /// hash in the manifest.
+    pub fn assemble(
+        manifest: SnapshotManifest,
+        provided: &BTreeMap<String, String>,
+        local: Option<&ChunkedSnapshot>,
+    ) -> Result<Self, StateMachineError> {
+        let mut chunks = Vec::with_capacity(manifest.chunks.len());
+        for info in &manifest.chunks {
+            let data = provided
+                .get(&info.hash)
+                .or_else(|| local.and_then(|local| local.chunk_by_hash(&info.hash)))
+                .ok_or_else(|| {
+                    StateMachineError::DatabaseError(format!(
+                        "snapshot chunk {} not found",
+                        info.name
+                    ))
+                })?;
+            if chunk_hash(data) != info.hash {
+                return Err(StateMachineError::DatabaseError(format!(
+                    "snapshot chunk {} does not match its hash",
+                    info.name
+                )));
+            }
+            chunks.push(data.clone());
+        }
+        Ok(Self { manifest, chunks })
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, StateMachineError> {
+        let manifest = serde_json::to_vec(&self.manifest).map_err(serialization_err)?;
+        let chunks_len: usize = self.chunks.iter().map(|c| c.len()).sum();
+        let mut buf = Vec::with_capacity(MAGIC.len() + 4 + manifest.len() + chunks_len);
+        buf.extend_from_slice(MAGIC);
+        buf.write_u32::<BigEndian>(manifest.len() as u32)
+            .map_err(serialization_err)?;
+        buf.extend_from_slice(&manifest);
+        for chunk in &self.chunks {
+            buf.extend_from_slice(chunk.as_bytes());
+        }
+        Ok(buf)
+    }
+
+    /// Decodes snapshot data in the chunked encoding. Returns None for
+    /// snapshot data in the plain JSON encoding.
+    pub fn decode(data: &[u8]) -> Result<Option<Self>, StateMachineError> {
+        let Some(data) = data.strip_prefix(MAGIC) else {
+            return Ok(None);
+        };
+        let mut cursor = Cursor::new(data);
+        let manifest_len = cursor.read_u32::<BigEndian>().map_err(serialization_err)? as usize;
+        let mut manifest = vec![0; manifest_len];
+        cursor
+            .read_exact(&mut manifest)
+            .map_err(serialization_err)?;
+        let manifest: SnapshotManifest =
+            serde_json::from_slice(&manifest).map_err(serialization_err)?;
+        let mut chunks = Vec::with_capacity(manifest.chunks.len());
+        for info in &manifest.chunks {
+            let mut chunk = vec![0; info.len];
+            cursor.read_exact(&mut chunk).map_err(serialization_err)?;
+            let chunk = String::from_utf8(chunk).map_err(serialization_err)?;
+            if chunk_hash(&chunk) != info.hash {
+                return Err(serialization_err(format!(
+                    "snapshot chunk {} does not match its hash",
+                    info.name
+                )));
+            }
+            chunks.push(chunk);
+        }
+        Ok(Some(Self { manifest, chunks }))
+    }
+}
+
+/// Decodes snapshot data in either the chunked or the plain JSON encoding
+pub fn decode_state(data: &[u8]) -> Result<IndexifyStateSnapshot, StateMachineError> {
+    match ChunkedSnapshot::decode(data)? {
+        Some(snapshot) => snapshot.to_state(),
+        None => JsonEncoder::decode(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn state_with(namespaces: &[&str]) -> IndexifyStateSnapshot {
+        let mut value = serde_json::to_value(IndexifyStateSnapshot::default()).unwrap();
+        value["namespaces"] = json!(namespaces);
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_only_changed_table_is_transferred() {
+        let old = ChunkedSnapshot::from_state(&state_with(&["ns1"])).unwrap();
+        let new = ChunkedSnapshot::from_state(&state_with(&["ns1", "ns2"])).unwrap();
+        assert!(new.manifest.is_compatible(&old.manifest));
+
+        let missing = new.manifest.missing_from(&old.manifest);
+        let names: Vec<&str> = missing.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["namespaces"]);
+
+        //  The follower rebuilds the new snapshot from the transferred chunk and
+        // its own copy of the rest
+        let provided = new.chunks_for(&missing);
+        assert_eq!(provided.len(), 1);
+        let assembled = ChunkedSnapshot::assemble(new.manifest.clone(), &provided, Some(&old));
+        assert_eq!(assembled.unwrap(), new);
+
+        //  Without the local snapshot the unchanged chunks are missing
+        assert!(ChunkedSnapshot::assemble(new.manifest.clone(), &provided, None).is_err());
+    }
+
+    #[test]
+    fn test_chunks_are_deterministic() {
+        let state = || state_with(&["ns2", "ns1", "ns3"]);
+        let first = ChunkedSnapshot::from_state(&state()).unwrap();
+        let second = ChunkedSnapshot::from_state(&state()).unwrap();
+        assert_eq!(first.manifest, second.manifest);
+        assert!(second.manifest.missing_from(&first.manifest).is_empty());
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let snapshot = ChunkedSnapshot::from_state(&state_with(&["ns1"])).unwrap();
+        let data = snapshot.encode().unwrap();
+        let decoded = ChunkedSnapshot::decode(&data).unwrap().unwrap();
+        assert_eq!(decoded, snapshot);
+        assert_eq!(
+            ChunkedSnapshot::from_state(&decode_state(&data).unwrap()).unwrap(),
+            snapshot
+        );
+
+        //  Snapshots written in the plain JSON encoding are still readable
+        let legacy = JsonEncoder::encode(&state_with(&["ns1"])).unwrap();
+        assert!(ChunkedSnapshot::decode(&legacy).unwrap().is_none());
+        assert_eq!(
+            ChunkedSnapshot::from_state(&decode_state(&legacy).unwrap()).unwrap(),
+            snapshot
+        );
+
+        let mut corrupted = data.clone();
+        let last = corrupted.len() - 2;
+        corrupted[last] = b'x';
+        assert!(ChunkedSnapshot::decode(&corrupted).is_err());
+    }
+}
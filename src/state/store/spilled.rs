@@ -0,0 +1,302 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::Hash,
+    sync::Arc,
+};
+
+use rocksdb::{Direction, IteratorMode, OptimisticTransactionDB};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::error;
+
+use super::{serializer::JsonEncode, JsonEncoder, StateMachineColumns, StateMachineError};
+
+/// Where a bounded reverse index writes the entries it evicts
+struct Spill {
+    db: Arc<OptimisticTransactionDB>,
+    name: &'static str,
+    capacity: usize,
+}
+
+/// A reverse index map which is entirely in memory until it is bounded. A
+/// bounded map keeps its most recently used entries in memory, spills the
+/// least recently used ones to the state store and reads them back on a miss.
+///
+/// Every key is either in memory or spilled, never both. Spilled entries
+/// aren't part of the replicated state, like the rest of the reverse index
+/// they are rebuilt from the snapshot when the node starts.
+pub struct SpillingMap<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    //  Last use -> key, only tracked once the map is bounded
+    recency: BTreeMap<u64, K>,
+    next_use: u64,
+    spill: Option<Spill>,
+}
+
+impl<K, V> Default for SpillingMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            next_use: 0,
+            spill: None,
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SpillingMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpillingMap")
+            .field(
+                "entries",
+                &self
+                    .entries
+                    .iter()
+                    .map(|(key, (value, _))| (key, value))
+                    .collect::<HashMap<_, _>>(),
+            )
+            .field("capacity", &self.spill.as_ref().map(|spill| spill.capacity))
+            .finish()
+    }
+}
+
+impl<K, V> From<HashMap<K, V>> for SpillingMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from(entries: HashMap<K, V>) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| (key, (value, 0)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<K, V> SpillingMap<K, V>
+where
+    K: Hash + Eq + Clone + Serialize + DeserializeOwned + fmt::Debug,
+    V: Default + Clone + Serialize + DeserializeOwned + fmt::Debug,
+{
+    /// Keeps at most `capacity` entries in memory from now on, the others are
+    /// spilled under `name` to the state store
+    pub fn bound(&mut self, db: Arc<OptimisticTransactionDB>, name: &'static str, capacity: usize) {
+        self.spill = Some(Spill {
+            db,
+            name,
+            capacity: capacity.max(1),
+        });
+        //  The existing entries are used in no particular order
+        let keys: Vec<K> = self.entries.keys().cloned().collect();
+        for key in keys {
+            self.touch(&key);
+        }
+        self.evict(0);
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.spill.is_some()
+    }
+
+    /// Drops the entries in memory and their recency. Spilled entries are
+    /// deleted separately, with `clear_spilled`.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.next_use = 0;
+    }
+
+    /// Entries in memory, without reading the spilled ones or changing which
+    /// entries are kept
+    pub fn iter_in_memory(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, (value, _))| (key, value))
+    }
+
+    /// Reads an entry of an unbounded map, every entry of which is in memory
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if !self.entries.contains_key(key) {
+            let value = self.unspill(key)?;
+            self.evict(1);
+            self.entries.insert(key.clone(), (value, 0));
+        }
+        self.touch(key);
+        self.entries.get_mut(key).map(|(value, _)| value)
+    }
+
+    pub fn entry_or_default(&mut self, key: K) -> &mut V {
+        if self.get_mut(&key).is_none() {
+            self.evict(1);
+            self.entries.insert(key.clone(), (V::default(), 0));
+            self.touch(&key);
+        }
+        self.entries.get_mut(&key).map(|(value, _)| value).unwrap()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        *self.entry_or_default(key) = value;
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        match self.entries.remove(key) {
+            Some((value, last_use)) => {
+                self.recency.remove(&last_use);
+                Some(value)
+            }
+            None => self.unspill(key),
+        }
+    }
+
+    /// Every entry, the spilled ones included
+    pub fn to_map(&self) -> HashMap<K, V> {
+        let mut map: HashMap<K, V> = self
+            .iter_in_memory()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if let Some(spill) = &self.spill {
+            match spill.read_all() {
+                Ok(spilled) => map.extend(spilled),
+                Err(e) => error!("unable to read the spilled {} entries: {}", spill.name, e),
+            }
+        }
+        map
+    }
+
+    fn touch(&mut self, key: &K) {
+        if self.spill.is_none() {
+            return;
+        }
+        let Some((_, last_use)) = self.entries.get_mut(key) else {
+            return;
+        };
+        self.recency.remove(last_use);
+        self.next_use += 1;
+        *last_use = self.next_use;
+        self.recency.insert(self.next_use, key.clone());
+    }
+
+    /// Spills the least recently used entries until `room` more fit in memory
+    fn evict(&mut self, room: usize) {
+        let Some(spill) = &self.spill else {
+            return;
+        };
+        while self.entries.len() + room > spill.capacity {
+            let Some((_, key)) = self.recency.pop_first() else {
+                return;
+            };
+            let Some((value, _)) = self.entries.remove(&key) else {
+                continue;
+            };
+            if let Err(e) = spill.put(&key, &value) {
+                //  Kept in memory rather than lost, over the capacity
+                error!("unable to spill {} entry {:?}: {}", spill.name, key, e);
+                self.entries.insert(key.clone(), (value, 0));
+                self.touch(&key);
+                return;
+            }
+        }
+    }
+
+    /// Moves a spilled entry out of the state store
+    fn unspill(&self, key: &K) -> Option<V> {
+        let spill = self.spill.as_ref()?;
+        match spill.take(key) {
+            Ok(value) => value,
+            Err(e) => {
+                error!(
+                    "unable to read spilled {} entry {:?}: {}",
+                    spill.name, key, e
+                );
+                None
+            }
+        }
+    }
+}
+
+impl Spill {
+    fn prefix(&self) -> String {
+        format!("{}::", self.name)
+    }
+
+    fn key<K: Serialize + fmt::Debug>(&self, key: &K) -> Result<Vec<u8>, StateMachineError> {
+        let mut spill_key = self.prefix().into_bytes();
+        spill_key.extend(JsonEncoder::encode(key)?);
+        Ok(spill_key)
+    }
+
+    fn put<K, V>(&self, key: &K, value: &V) -> Result<(), StateMachineError>
+    where
+        K: Serialize + fmt::Debug,
+        V: Serialize + fmt::Debug,
+    {
+        let cf = StateMachineColumns::SpilledReverseIndexes.cf(&self.db);
+        self.db
+            .put_cf(cf, self.key(key)?, JsonEncoder::encode(value)?)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
+    fn take<K, V>(&self, key: &K) -> Result<Option<V>, StateMachineError>
+    where
+        K: Serialize + fmt::Debug,
+        V: DeserializeOwned,
+    {
+        let cf = StateMachineColumns::SpilledReverseIndexes.cf(&self.db);
+        let spill_key = self.key(key)?;
+        let value = self
+            .db
+            .get_cf(cf, &spill_key)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let value = JsonEncoder::decode(&value)?;
+        self.db
+            .delete_cf(cf, &spill_key)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        Ok(Some(value))
+    }
+
+    fn read_all<K, V>(&self) -> Result<Vec<(K, V)>, StateMachineError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let cf = StateMachineColumns::SpilledReverseIndexes.cf(&self.db);
+        let prefix = self.prefix();
+        let mut entries = Vec::new();
+        for item in self.db.iterator_cf(
+            cf,
+            IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+        ) {
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            let Some(key) = key.strip_prefix(prefix.as_bytes()) else {
+                break;
+            };
+            entries.push((JsonEncoder::decode(key)?, JsonEncoder::decode(&value)?));
+        }
+        Ok(entries)
+    }
+}
+
+/// Deletes the entries spilled by a previous run, the reverse indexes are
+/// rebuilt when the node starts
+pub fn clear_spilled(db: &Arc<OptimisticTransactionDB>) -> Result<usize, StateMachineError> {
+    let cf = StateMachineColumns::SpilledReverseIndexes.cf(db);
+    let mut cleared = 0;
+    for item in db.iterator_cf(cf, IteratorMode::Start) {
+        let (key, _) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        db.delete_cf(cf, key)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        cleared += 1;
+    }
+    Ok(cleared)
+}
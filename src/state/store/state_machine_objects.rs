@@ -1,19 +1,22 @@
 use core::fmt;
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     sync::{Arc, RwLock},
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
 use anyhow::{anyhow, Result};
 use indexify_internal_api::{self as internal_api, ServerTaskType};
 use internal_api::{
     ContentMetadataId,
+    ContentQuery,
+    ContentVersionSeparator,
     ExtractionGraph,
     ExtractionPolicy,
     ExtractionPolicyName,
     ExtractorDescription,
     StateChange,
+    TaskLifecycleState,
     TaskOutcome,
 };
 use itertools::Itertools;
@@ -23,9 +26,14 @@ use serde::de::DeserializeOwned;
 use tokio::sync::broadcast;
 use tracing::{error, warn};
 
+#[cfg(any(test, feature = "failpoints"))]
+use super::failpoints::{self, Failpoints};
 use super::{
+    fail_point,
     requests::{RequestPayload, StateChangeProcessed, StateMachineUpdateRequest},
     serializer::JsonEncode,
+    sharded::Sharded,
+    spilled::{self, SpillingMap},
     ExecutorId,
     ExtractionGraphId,
     ExtractionPolicyId,
@@ -38,67 +46,180 @@ use super::{
     StateMachineError,
     TaskId,
 };
-use crate::state::NodeId;
+use crate::{
+    server_config::ValueSizeLimitsConfig,
+    state::NodeId,
+    utils::{day_of, timestamp_secs},
+};
+
+fn content_id_lookup_key(namespace: &str, id: &str) -> String {
+    format!("{}::id::{}", namespace, id)
+}
+
+fn content_hash_lookup_key(namespace: &str, hash: &str) -> String {
+    format!("{}::hash::{}", namespace, hash)
+}
+
+pub(super) fn content_lookup_keys(content: &internal_api::ContentMetadata) -> Vec<String> {
+    let mut keys = vec![content_id_lookup_key(&content.namespace, &content.id.id)];
+    if !content.hash.is_empty() {
+        keys.push(content_hash_lookup_key(&content.namespace, &content.hash));
+    }
+    keys
+}
+
+/// Number of entries and estimated heap usage of an in-memory reverse index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReverseIndexSize {
+    pub name: &'static str,
+    pub entries: usize,
+    pub estimated_bytes: usize,
+}
+
+//  The estimates count the keys and values stored in a reverse index and
+// ignore the hash table overhead. They are computed under the read lock
+// without cloning the maps.
+fn estimated_string_bytes(value: &str) -> usize {
+    std::mem::size_of::<String>() + value.len()
+}
+
+fn estimated_content_id_bytes(id: &ContentMetadataId) -> usize {
+    std::mem::size_of::<ContentMetadataId>() + id.id.len()
+}
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(from = "UnassignedTasksRepr", into = "UnassignedTasksRepr")]
 pub struct UnassignedTasks {
-    unassigned_tasks: Arc<RwLock<HashSet<TaskId>>>,
+    unassigned_tasks: Sharded<HashSet<TaskId>>,
+}
+
+//  The serialized form of the reverse index from before it was sharded
+#[derive(serde::Serialize, serde::Deserialize)]
+struct UnassignedTasksRepr {
+    unassigned_tasks: HashSet<TaskId>,
 }
 
 impl UnassignedTasks {
     pub fn insert(&self, task_id: &TaskId) {
-        let mut guard = self.unassigned_tasks.write().unwrap();
+        let mut guard = self.unassigned_tasks.write(task_id);
         guard.insert(task_id.into());
     }
 
     pub fn remove(&self, task_id: &TaskId) {
-        let mut guard = self.unassigned_tasks.write().unwrap();
+        let mut guard = self.unassigned_tasks.write(task_id);
         guard.remove(task_id);
     }
 
     pub fn inner(&self) -> HashSet<TaskId> {
-        let guard = self.unassigned_tasks.read().unwrap();
-        guard.clone()
+        let guards = self.unassigned_tasks.read_all();
+        guards
+            .iter()
+            .flat_map(|guard| guard.iter().cloned())
+            .collect()
     }
 
     pub fn set(&self, tasks: HashSet<TaskId>) {
-        let mut guard = self.unassigned_tasks.write().unwrap();
-        *guard = tasks;
+        let mut guard = self.unassigned_tasks.write_all();
+        guard.shards().for_each(|shard| shard.clear());
+        for task_id in tasks {
+            guard.shard(&task_id).insert(task_id);
+        }
     }
 
     pub fn count(&self) -> usize {
-        let guard = self.unassigned_tasks.read().unwrap();
-        guard.len()
+        let guards = self.unassigned_tasks.read_all();
+        guards.iter().map(|guard| guard.len()).sum()
+    }
+
+    pub fn size(&self) -> ReverseIndexSize {
+        let guards = self.unassigned_tasks.read_all();
+        ReverseIndexSize {
+            name: "unassigned_tasks",
+            entries: guards.iter().map(|guard| guard.len()).sum(),
+            estimated_bytes: guards
+                .iter()
+                .flat_map(|guard| guard.iter())
+                .map(|id| estimated_string_bytes(id))
+                .sum(),
+        }
     }
 }
 
 impl From<HashSet<TaskId>> for UnassignedTasks {
     fn from(tasks: HashSet<TaskId>) -> Self {
-        let unassigned_tasks = Arc::new(RwLock::new(tasks));
-        Self { unassigned_tasks }
+        let unassigned_tasks = Self::default();
+        unassigned_tasks.set(tasks);
+        unassigned_tasks
+    }
+}
+
+impl From<UnassignedTasksRepr> for UnassignedTasks {
+    fn from(repr: UnassignedTasksRepr) -> Self {
+        repr.unassigned_tasks.into()
+    }
+}
+
+impl From<UnassignedTasks> for UnassignedTasksRepr {
+    fn from(tasks: UnassignedTasks) -> Self {
+        Self {
+            unassigned_tasks: tasks.inner(),
+        }
     }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 pub struct UnprocessedStateChanges {
     unprocessed_state_changes: Arc<RwLock<HashSet<StateChangeId>>>,
+    // Number of times the write lock was taken
+    #[cfg(test)]
+    #[serde(skip)]
+    write_locks: Arc<std::sync::atomic::AtomicUsize>,
 }
 
 impl UnprocessedStateChanges {
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashSet<StateChangeId>> {
+        #[cfg(test)]
+        self.write_locks
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.unprocessed_state_changes.write().unwrap()
+    }
+
     pub fn insert(&self, state_change_id: StateChangeId) {
-        let mut guard = self.unprocessed_state_changes.write().unwrap();
+        let mut guard = self.write();
         guard.insert(state_change_id);
     }
 
     pub fn remove(&self, state_change_id: &StateChangeId) {
-        let mut guard = self.unprocessed_state_changes.write().unwrap();
+        let mut guard = self.write();
         guard.remove(state_change_id);
     }
 
+    pub fn contains(&self, state_change_id: &StateChangeId) -> bool {
+        let guard = self.unprocessed_state_changes.read().unwrap();
+        guard.contains(state_change_id)
+    }
+
+    /// Removes all the state changes while holding the lock once
+    pub fn remove_all<'a>(&self, state_change_ids: impl IntoIterator<Item = &'a StateChangeId>) {
+        let mut guard = self.write();
+        for state_change_id in state_change_ids {
+            guard.remove(state_change_id);
+        }
+    }
+
     pub fn inner(&self) -> HashSet<StateChangeId> {
         let guard = self.unprocessed_state_changes.read().unwrap();
         guard.clone()
     }
+
+    pub fn size(&self) -> ReverseIndexSize {
+        let guard = self.unprocessed_state_changes.read().unwrap();
+        ReverseIndexSize {
+            name: "unprocessed_state_changes",
+            entries: guard.len(),
+            estimated_bytes: guard.len() * std::mem::size_of::<StateChangeId>(),
+        }
+    }
 }
 
 impl From<HashSet<StateChangeId>> for UnprocessedStateChanges {
@@ -106,18 +227,29 @@ impl From<HashSet<StateChangeId>> for UnprocessedStateChanges {
         let unprocessed_state_changes = Arc::new(RwLock::new(state_changes));
         Self {
             unprocessed_state_changes,
+            #[cfg(test)]
+            write_locks: Default::default(),
         }
     }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(from = "ContentNamespaceTableRepr", into = "ContentNamespaceTableRepr")]
 pub struct ContentNamespaceTable {
-    content_namespace_table: Arc<RwLock<HashMap<NamespaceName, HashSet<ContentMetadataId>>>>,
+    //  Sharded by content id rather than namespace, most content is ingested
+    //  into few namespaces
+    content_namespace_table: Sharded<HashMap<NamespaceName, HashSet<ContentMetadataId>>>,
+}
+
+//  The serialized form of the reverse index from before it was sharded
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ContentNamespaceTableRepr {
+    content_namespace_table: HashMap<NamespaceName, HashSet<ContentMetadataId>>,
 }
 
 impl ContentNamespaceTable {
     pub fn insert(&self, namespace: &NamespaceName, content_id: &ContentMetadataId) {
-        let mut guard = self.content_namespace_table.write().unwrap();
+        let mut guard = self.content_namespace_table.write(content_id);
         guard
             .entry(namespace.clone())
             .or_default()
@@ -125,25 +257,172 @@ impl ContentNamespaceTable {
     }
 
     pub fn remove(&self, namespace: &NamespaceName, content_id: &ContentMetadataId) {
-        let mut guard = self.content_namespace_table.write().unwrap();
+        let mut guard = self.content_namespace_table.write(content_id);
         guard
             .entry(namespace.clone())
             .or_default()
             .remove(content_id);
     }
 
+    pub fn get(&self, namespace: &str) -> HashSet<ContentMetadataId> {
+        let guards = self.content_namespace_table.read_all();
+        guards
+            .iter()
+            .filter_map(|guard| guard.get(namespace))
+            .flat_map(|content_ids| content_ids.iter().cloned())
+            .collect()
+    }
+
+    pub fn contains(&self, namespace: &str, content_id: &ContentMetadataId) -> bool {
+        let guard = self.content_namespace_table.read(content_id);
+        guard
+            .get(namespace)
+            .is_some_and(|content_ids| content_ids.contains(content_id))
+    }
+
     pub fn inner(&self) -> HashMap<NamespaceName, HashSet<ContentMetadataId>> {
-        let guard = self.content_namespace_table.read().unwrap();
-        guard.clone()
+        let guards = self.content_namespace_table.read_all();
+        let mut table: HashMap<NamespaceName, HashSet<ContentMetadataId>> = HashMap::new();
+        for (namespace, content_ids) in guards.iter().flat_map(|guard| guard.iter()) {
+            table
+                .entry(namespace.clone())
+                .or_default()
+                .extend(content_ids.iter().cloned());
+        }
+        table
+    }
+
+    pub fn size(&self) -> ReverseIndexSize {
+        let guards = self.content_namespace_table.read_all();
+        let mut size = ReverseIndexSize {
+            name: "content_namespace_table",
+            entries: 0,
+            estimated_bytes: 0,
+        };
+        let mut namespaces = HashSet::new();
+        for (namespace, content_ids) in guards.iter().flat_map(|guard| guard.iter()) {
+            size.entries += content_ids.len();
+            size.estimated_bytes += content_ids
+                .iter()
+                .map(estimated_content_id_bytes)
+                .sum::<usize>();
+            if namespaces.insert(namespace) {
+                size.estimated_bytes += estimated_string_bytes(namespace);
+            }
+        }
+        size
     }
 }
 
 impl From<HashMap<NamespaceName, HashSet<ContentMetadataId>>> for ContentNamespaceTable {
     fn from(content_namespace_table: HashMap<NamespaceName, HashSet<ContentMetadataId>>) -> Self {
-        let content_namespace_table = Arc::new(RwLock::new(content_namespace_table));
+        let table = Self::default();
+        {
+            let mut guard = table.content_namespace_table.write_all();
+            for (namespace, content_ids) in content_namespace_table {
+                //  Namespaces without content are kept, like they were before
+                //  the table was sharded
+                guard
+                    .shard(&namespace)
+                    .entry(namespace.clone())
+                    .or_default();
+                for content_id in content_ids {
+                    guard
+                        .shard(&content_id)
+                        .entry(namespace.clone())
+                        .or_default()
+                        .insert(content_id);
+                }
+            }
+        }
+        table
+    }
+}
+
+impl From<ContentNamespaceTableRepr> for ContentNamespaceTable {
+    fn from(repr: ContentNamespaceTableRepr) -> Self {
+        repr.content_namespace_table.into()
+    }
+}
+
+impl From<ContentNamespaceTable> for ContentNamespaceTableRepr {
+    fn from(table: ContentNamespaceTable) -> Self {
         Self {
-            content_namespace_table,
+            content_namespace_table: table.inner(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct ContentBySource {
+    content_by_source: Arc<RwLock<HashMap<NamespaceName, HashMap<String, HashSet<String>>>>>,
+}
+
+impl ContentBySource {
+    pub fn insert(&self, namespace: &NamespaceName, source: &str, content_id: &str) {
+        let mut guard = self.content_by_source.write().unwrap();
+        guard
+            .entry(namespace.clone())
+            .or_default()
+            .entry(source.to_string())
+            .or_default()
+            .insert(content_id.to_string());
+    }
+
+    /// Removes the content from every source of the namespace
+    pub fn remove(&self, namespace: &NamespaceName, content_id: &str) {
+        let mut guard = self.content_by_source.write().unwrap();
+        if let Some(sources) = guard.get_mut(namespace) {
+            sources.retain(|_, content_ids| {
+                content_ids.remove(content_id);
+                !content_ids.is_empty()
+            });
+            if sources.is_empty() {
+                guard.remove(namespace);
+            }
+        }
+    }
+
+    pub fn get(&self, namespace: &str, source: &str) -> HashSet<String> {
+        let guard = self.content_by_source.read().unwrap();
+        guard
+            .get(namespace)
+            .and_then(|sources| sources.get(source))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn inner(&self) -> HashMap<NamespaceName, HashMap<String, HashSet<String>>> {
+        let guard = self.content_by_source.read().unwrap();
+        guard.clone()
+    }
+
+    pub fn size(&self) -> ReverseIndexSize {
+        let guard = self.content_by_source.read().unwrap();
+        let mut size = ReverseIndexSize {
+            name: "content_by_source",
+            entries: 0,
+            estimated_bytes: 0,
+        };
+        for (namespace, sources) in guard.iter() {
+            size.estimated_bytes += estimated_string_bytes(namespace);
+            for (source, content_ids) in sources {
+                size.entries += content_ids.len();
+                size.estimated_bytes += estimated_string_bytes(source) +
+                    content_ids
+                        .iter()
+                        .map(|id| estimated_string_bytes(id))
+                        .sum::<usize>();
+            }
         }
+        size
+    }
+}
+
+impl From<HashMap<NamespaceName, HashMap<String, HashSet<String>>>> for ContentBySource {
+    fn from(content_by_source: HashMap<NamespaceName, HashMap<String, HashSet<String>>>) -> Self {
+        let content_by_source = Arc::new(RwLock::new(content_by_source));
+        Self { content_by_source }
     }
 }
 
@@ -211,10 +490,33 @@ impl ExtractorExecutorsTable {
             .remove(executor_id);
     }
 
+    pub fn contains(&self, extractor: &str, executor_id: &str) -> bool {
+        let guard = self.extractor_executors_table.read().unwrap();
+        guard
+            .get(extractor)
+            .is_some_and(|executor_ids| executor_ids.contains(executor_id))
+    }
+
+    pub fn get(&self, extractor: &str) -> HashSet<ExecutorId> {
+        let guard = self.extractor_executors_table.read().unwrap();
+        guard.get(extractor).cloned().unwrap_or_default()
+    }
+
     pub fn inner(&self) -> HashMap<ExtractorName, HashSet<ExecutorId>> {
         let guard = self.extractor_executors_table.read().unwrap();
         guard.clone()
     }
+
+    /// Moves the executors of an extractor to its new name
+    pub fn rename(&self, old_name: &str, new_name: &str) {
+        let mut guard = self.extractor_executors_table.write().unwrap();
+        if let Some(executor_ids) = guard.remove(old_name) {
+            guard
+                .entry(new_name.to_string())
+                .or_default()
+                .extend(executor_ids);
+        }
+    }
 }
 
 impl From<HashMap<ExtractorName, HashSet<ExecutorId>>> for ExtractorExecutorsTable {
@@ -249,6 +551,11 @@ impl NamespaceIndexTable {
         let guard = self.namespace_index_table.read().unwrap();
         guard.clone()
     }
+
+    pub fn get(&self, namespace: &str) -> HashSet<String> {
+        let guard = self.namespace_index_table.read().unwrap();
+        guard.get(namespace).cloned().unwrap_or_default()
+    }
 }
 
 impl From<HashMap<NamespaceName, HashSet<String>>> for NamespaceIndexTable {
@@ -279,11 +586,52 @@ impl UnfinishedTasksByExtractor {
         guard.entry(extractor.clone()).or_default().remove(task_id);
     }
 
+    pub fn contains(&self, extractor: &str, task_id: &str) -> bool {
+        let guard = self.unfinished_tasks_by_extractor.read().unwrap();
+        guard
+            .get(extractor)
+            .is_some_and(|task_ids| task_ids.contains(task_id))
+    }
+
+    pub fn get(&self, extractor: &str) -> HashSet<TaskId> {
+        let guard = self.unfinished_tasks_by_extractor.read().unwrap();
+        guard.get(extractor).cloned().unwrap_or_default()
+    }
+
     pub fn inner(&self) -> HashMap<ExtractorName, HashSet<TaskId>> {
         let guard = self.unfinished_tasks_by_extractor.read().unwrap();
         guard.clone()
     }
 
+    /// Moves the unfinished tasks of an extractor to its new name
+    pub fn rename(&self, old_name: &str, new_name: &str) {
+        let mut guard = self.unfinished_tasks_by_extractor.write().unwrap();
+        if let Some(task_ids) = guard.remove(old_name) {
+            guard
+                .entry(new_name.to_string())
+                .or_default()
+                .extend(task_ids);
+        }
+    }
+
+    pub fn size(&self) -> ReverseIndexSize {
+        let guard = self.unfinished_tasks_by_extractor.read().unwrap();
+        let mut size = ReverseIndexSize {
+            name: "unfinished_tasks_by_extractor",
+            entries: 0,
+            estimated_bytes: 0,
+        };
+        for (extractor, task_ids) in guard.iter() {
+            size.entries += task_ids.len();
+            size.estimated_bytes += estimated_string_bytes(extractor) +
+                task_ids
+                    .iter()
+                    .map(|id| estimated_string_bytes(id))
+                    .sum::<usize>();
+        }
+        size
+    }
+
     pub fn observe_task_counts(&self, observer: &dyn AsyncInstrument<u64>) {
         let guard = self.unfinished_tasks_by_extractor.read().unwrap();
         for (extractor, tasks) in guard.iter() {
@@ -394,19 +742,19 @@ impl ExtractionGraphTable {
             .insert(id.clone());
     }
 
-    pub fn remove(&self, namespace: &NamespaceName, id: &SchemaId) {
+    pub fn remove(&self, namespace: &NamespaceName, id: &ExtractionGraphId) {
         let mut guard = self.eg_by_namespace.write().unwrap();
         guard.entry(namespace.clone()).or_default().remove(id);
     }
 
-    pub fn inner(&self) -> HashMap<NamespaceName, HashSet<SchemaId>> {
+    pub fn inner(&self) -> HashMap<NamespaceName, HashSet<ExtractionGraphId>> {
         let guard = self.eg_by_namespace.read().unwrap();
         guard.clone()
     }
 }
 
 impl From<HashMap<NamespaceName, HashSet<ExtractionGraphId>>> for ExtractionGraphTable {
-    fn from(eg_by_namespace: HashMap<NamespaceName, HashSet<SchemaId>>) -> Self {
+    fn from(eg_by_namespace: HashMap<NamespaceName, HashSet<ExtractionGraphId>>) -> Self {
         let eg_by_namespace = Arc::new(RwLock::new(eg_by_namespace));
         Self { eg_by_namespace }
     }
@@ -450,16 +798,29 @@ impl From<HashMap<NamespaceName, HashSet<SchemaId>>> for SchemasByNamespace {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(from = "ContentChildrenTableRepr", into = "ContentChildrenTableRepr")]
 pub struct ContentChildrenTable {
-    content_children_table: Arc<RwLock<HashMap<ContentMetadataId, HashSet<ContentMetadataId>>>>,
+    content_children_table: Arc<RwLock<SpillingMap<ContentMetadataId, HashSet<ContentMetadataId>>>>,
+}
+
+//  The serialized form of the reverse index from before it could be bounded
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ContentChildrenTableRepr {
+    content_children_table: HashMap<ContentMetadataId, HashSet<ContentMetadataId>>,
 }
 
 impl ContentChildrenTable {
+    /// Keeps the children of at most `capacity` parents in memory, the others
+    /// are spilled to `db`
+    pub fn bound(&self, db: Arc<OptimisticTransactionDB>, capacity: usize) {
+        let mut guard = self.content_children_table.write().unwrap();
+        guard.bound(db, "content_children_table", capacity);
+    }
+
     pub fn insert(&self, parent_id: &ContentMetadataId, child_id: &ContentMetadataId) {
         let mut guard = self.content_children_table.write().unwrap();
         guard
-            .entry(parent_id.clone())
-            .or_default()
+            .entry_or_default(parent_id.clone())
             .insert(child_id.clone());
     }
 
@@ -479,7 +840,14 @@ impl ContentChildrenTable {
     }
 
     pub fn get_children(&self, parent_id: &ContentMetadataId) -> HashSet<ContentMetadataId> {
-        let guard = self.content_children_table.read().unwrap();
+        {
+            let guard = self.content_children_table.read().unwrap();
+            if !guard.is_bounded() {
+                return guard.peek(parent_id).cloned().unwrap_or_default();
+            }
+        }
+        //  Reading a bounded table updates which entries are kept in memory
+        let mut guard = self.content_children_table.write().unwrap();
         guard.get(parent_id).cloned().unwrap_or_default()
     }
 
@@ -495,7 +863,26 @@ impl ContentChildrenTable {
 
     pub fn inner(&self) -> HashMap<ContentMetadataId, HashSet<ContentMetadataId>> {
         let guard = self.content_children_table.read().unwrap();
-        guard.clone()
+        guard.to_map()
+    }
+
+    /// Size of the entries in memory, the spilled ones aren't counted
+    pub fn size(&self) -> ReverseIndexSize {
+        let guard = self.content_children_table.read().unwrap();
+        let mut size = ReverseIndexSize {
+            name: "content_children_table",
+            entries: 0,
+            estimated_bytes: 0,
+        };
+        for (parent_id, children) in guard.iter_in_memory() {
+            size.entries += children.len();
+            size.estimated_bytes += estimated_content_id_bytes(parent_id) +
+                children
+                    .iter()
+                    .map(estimated_content_id_bytes)
+                    .sum::<usize>();
+        }
+        size
     }
 }
 
@@ -503,13 +890,27 @@ impl From<HashMap<ContentMetadataId, HashSet<ContentMetadataId>>> for ContentChi
     fn from(
         content_children_table: HashMap<ContentMetadataId, HashSet<ContentMetadataId>>,
     ) -> Self {
-        let content_children_table = Arc::new(RwLock::new(content_children_table));
+        let content_children_table = Arc::new(RwLock::new(content_children_table.into()));
         Self {
             content_children_table,
         }
     }
 }
 
+impl From<ContentChildrenTableRepr> for ContentChildrenTable {
+    fn from(repr: ContentChildrenTableRepr) -> Self {
+        repr.content_children_table.into()
+    }
+}
+
+impl From<ContentChildrenTable> for ContentChildrenTableRepr {
+    fn from(table: ContentChildrenTable) -> Self {
+        Self {
+            content_children_table: table.inner(),
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 pub struct PendingTasksForContent {
     pending_tasks_for_content:
@@ -556,12 +957,41 @@ impl PendingTasksForContent {
         guard.get(content_id).is_none()
     }
 
+    pub fn get(
+        &self,
+        content_id: &ContentMetadataId,
+    ) -> HashMap<ExtractionPolicyId, HashSet<TaskId>> {
+        let guard = self.pending_tasks_for_content.read().unwrap();
+        guard.get(content_id).cloned().unwrap_or_default()
+    }
+
     pub fn inner(
         &self,
     ) -> HashMap<ContentMetadataId, HashMap<ExtractionPolicyId, HashSet<TaskId>>> {
         let guard = self.pending_tasks_for_content.read().unwrap();
         guard.clone()
     }
+
+    pub fn size(&self) -> ReverseIndexSize {
+        let guard = self.pending_tasks_for_content.read().unwrap();
+        let mut size = ReverseIndexSize {
+            name: "pending_tasks_for_content",
+            entries: 0,
+            estimated_bytes: 0,
+        };
+        for (content_id, policies) in guard.iter() {
+            size.estimated_bytes += estimated_content_id_bytes(content_id);
+            for (policy_id, task_ids) in policies.iter() {
+                size.entries += task_ids.len();
+                size.estimated_bytes += estimated_string_bytes(policy_id) +
+                    task_ids
+                        .iter()
+                        .map(|id| estimated_string_bytes(id))
+                        .sum::<usize>();
+            }
+        }
+        size
+    }
 }
 
 impl From<HashMap<ContentMetadataId, HashMap<ExtractionPolicyId, HashSet<TaskId>>>>
@@ -599,6 +1029,11 @@ pub struct Metrics {
 
     /// Total number of bytes in extracted contents
     pub content_extracted_bytes: u64,
+
+    /// Number of reads which failed because the value was above the size
+    /// limit of its column family
+    #[serde(default)]
+    pub values_too_large: u64,
 }
 
 impl Metrics {
@@ -617,6 +1052,165 @@ struct TaskCount {
     notify: Option<broadcast::Sender<()>>,
 }
 
+/// How long the result of a request with an idempotency key is remembered
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Result of applying a state machine update request
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AppliedUpdate {
+    pub new_state_changes: Vec<StateChange>,
+    /// Ids and versions of the content written by the request
+    pub written_content: Vec<ContentMetadataId>,
+}
+
+/// Result of a request applied with an idempotency key. Expiry is checked
+/// against the clock of each node, so nodes may forget a key at slightly
+/// different times.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct IdempotencyRecord {
+    pub applied_at: u64,
+    pub result: AppliedUpdate,
+}
+
+impl IdempotencyRecord {
+    fn expired(&self, now: u64) -> bool {
+        now.saturating_sub(self.applied_at) >= IDEMPOTENCY_KEY_TTL.as_secs()
+    }
+}
+
+/// Content created by an ingestion request with an idempotency key. Requests
+/// with the same key in the namespace before `expires_at` don't create
+/// content, they are answered with this record instead.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct IngestionKeyRecord {
+    pub namespace: String,
+    pub key: String,
+    pub content_id: String,
+    /// False if the content already existed when the first request was made
+    pub created: bool,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+impl IngestionKeyRecord {
+    pub fn cf_key(namespace: &str, key: &str) -> String {
+        format!("{}::{}", namespace, key)
+    }
+
+    pub fn expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Size of the delivery queue of a subscription
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryQueueStats {
+    pub subscription_id: String,
+    pub depth: u64,
+    pub oldest_enqueued_at: u64,
+}
+
+/// An index joined with the extraction policy writing to it and the
+/// extractor of that policy. Either is missing when the index refers to a
+/// record which doesn't exist anymore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexOverview {
+    pub index: internal_api::Index,
+    pub extraction_policy: Option<ExtractionPolicy>,
+    pub extractor: Option<ExtractorDescription>,
+}
+
+/// How much of the content of a namespace an extraction policy covers. Only
+/// the latest versions of content which isn't tombstoned are counted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolicyCoverage {
+    pub total: u64,
+    /// Content in the graph of the policy from the source the policy reads
+    pub applied: u64,
+    /// Applied content on which the policy has completed
+    pub completed: u64,
+}
+
+impl PolicyCoverage {
+    pub fn applied_fraction(&self) -> f64 {
+        fraction(self.applied, self.total)
+    }
+
+    pub fn completed_fraction(&self) -> f64 {
+        fraction(self.completed, self.total)
+    }
+}
+
+fn fraction(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    count as f64 / total as f64
+}
+
+/// Orders the policies of a dependency graph so every policy comes after the
+/// policies it depends on. Policies without an order between them are
+/// sorted by id. Fails with the policies on or behind a cycle if there is
+/// one. Dependencies missing from the graph are ignored.
+pub fn toposort_policies(
+    graph: &[(ExtractionPolicyId, Vec<ExtractionPolicyId>)],
+) -> Result<Vec<ExtractionPolicyId>> {
+    let mut remaining_dependencies: HashMap<&str, usize> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (policy_id, _) in graph {
+        remaining_dependencies.insert(policy_id, 0);
+    }
+    for (policy_id, dependencies) in graph {
+        for dependency in dependencies {
+            if remaining_dependencies.contains_key(dependency.as_str()) {
+                *remaining_dependencies.get_mut(policy_id.as_str()).unwrap() += 1;
+                dependents.entry(dependency).or_default().push(policy_id);
+            }
+        }
+    }
+
+    let mut ready: BTreeSet<&str> = remaining_dependencies
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(policy_id, _)| *policy_id)
+        .collect();
+    let mut order = Vec::with_capacity(graph.len());
+    while let Some(policy_id) = ready.pop_first() {
+        remaining_dependencies.remove(policy_id);
+        order.push(policy_id.to_string());
+        for dependent in dependents.get(policy_id).into_iter().flatten() {
+            let count = remaining_dependencies.get_mut(dependent).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.insert(dependent);
+            }
+        }
+    }
+    if !remaining_dependencies.is_empty() {
+        return Err(anyhow!(
+            "extraction policies depend on each other in a cycle: {}",
+            remaining_dependencies.keys().sorted().join(", ")
+        ));
+    }
+    Ok(order)
+}
+
+/// Result of looking up extraction policies by id, separating the policies
+/// that were found from the ids that don't exist.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionPoliciesLookup {
+    pub found: Vec<ExtractionPolicy>,
+    pub missing: HashSet<String>,
+}
+
+/// State of the state machine, the forward indexes in RocksDB and the
+/// reverse indexes built from them in memory.
+///
+/// Requests are applied by `apply_state_machine_updates` and snapshots are
+/// installed by `install_snapshot`. Both hold `write_lock` from start to end,
+/// so a snapshot is never installed over a partially applied request, nor a
+/// request applied over a partially installed snapshot. Readers don't take
+/// the lock, they see the reverse indexes one map at a time.
 #[derive(thiserror::Error, Debug, Default)]
 pub struct IndexifyState {
     // Reverse Indexes
@@ -629,6 +1223,9 @@ pub struct IndexifyState {
     /// Namespace -> Content ID
     pub content_namespace_table: ContentNamespaceTable,
 
+    /// Namespace -> Content source -> Content id
+    pub content_by_source: ContentBySource,
+
     /// Namespace -> Extraction policy id
     pub extraction_policies_table: ExtractionPoliciesTable,
 
@@ -667,16 +1264,33 @@ pub struct IndexifyState {
 
     /// Next change id
     pub change_id: std::sync::Mutex<u64>,
+
+    /// Maximum size of the values read from each column family
+    pub value_size_limits: ValueSizeLimitsConfig,
+
+    /// Maximum number of keys read by a single multi get
+    pub multi_get_batch_size: usize,
+
+    /// Separator between the id and the version in the keys of versioned
+    /// content
+    pub content_version_separator: ContentVersionSeparator,
+
+    /// Serializes applying requests and installing snapshots
+    write_lock: std::sync::Mutex<()>,
+
+    #[cfg(any(test, feature = "failpoints"))]
+    pub failpoints: Failpoints,
 }
 
 impl fmt::Display for IndexifyState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "IndexifyState {{ unassigned_tasks: {:?}, unprocessed_state_changes: {:?}, content_namespace_table: {:?}, extraction_policies_table: {:?}, extractor_executors_table: {:?}, namespace_index_table: {:?}, unfinished_tasks_by_extractor: {:?}, executor_running_task_count: {:?}, schemas_by_namespace: {:?} }}, content_children_table: {:?}",
+            "IndexifyState {{ unassigned_tasks: {:?}, unprocessed_state_changes: {:?}, content_namespace_table: {:?}, content_by_source: {:?}, extraction_policies_table: {:?}, extractor_executors_table: {:?}, namespace_index_table: {:?}, unfinished_tasks_by_extractor: {:?}, executor_running_task_count: {:?}, schemas_by_namespace: {:?}, content_children_table: {:?}, pending_tasks_for_content: {:?}, root_task_counts: {:?}, extraction_graphs_by_ns: {:?} }}",
             self.unassigned_tasks,
             self.unprocessed_state_changes,
             self.content_namespace_table,
+            self.content_by_source,
             self.extraction_policies_table,
             self.extractor_executors_table,
             self.namespace_index_table,
@@ -684,11 +1298,23 @@ impl fmt::Display for IndexifyState {
             self.executor_running_task_count,
             self.schemas_by_namespace,
             self.content_children_table,
+            self.pending_tasks_for_content,
+            self.root_task_counts,
+            self.extraction_graphs_by_ns,
         )
     }
 }
 
 impl IndexifyState {
+    /// Takes the write lock. A request which panicked while holding it
+    /// crashes the node, so a poisoned lock is taken over rather than
+    /// propagating the panic.
+    fn lock_writes(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     fn set_extraction_graph(
         &self,
         db: &Arc<OptimisticTransactionDB>,
@@ -711,6 +1337,57 @@ impl IndexifyState {
         Ok(())
     }
 
+    /// Returns the record of a request applied with the key, unless it
+    /// expired
+    fn get_idempotency_record(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, StateMachineError> {
+        let record = db
+            .get_cf(StateMachineColumns::IdempotencyKeys.cf(db), key)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?
+            .map(|value| JsonEncoder::decode::<IdempotencyRecord>(&value))
+            .transpose()?;
+        Ok(record.filter(|record| !record.expired(timestamp_secs())))
+    }
+
+    fn set_idempotency_record(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        key: Option<&str>,
+        result: &AppliedUpdate,
+    ) -> Result<(), StateMachineError> {
+        let Some(key) = key else {
+            return Ok(());
+        };
+        let record = IdempotencyRecord {
+            applied_at: timestamp_secs(),
+            result: result.clone(),
+        };
+        txn.put_cf(
+            StateMachineColumns::IdempotencyKeys.cf(db),
+            key,
+            JsonEncoder::encode(&record)?,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
+    fn set_ingestion_key(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        record: &IngestionKeyRecord,
+    ) -> Result<(), StateMachineError> {
+        txn.put_cf(
+            StateMachineColumns::IngestionKeys.cf(db),
+            IngestionKeyRecord::cf_key(&record.namespace, &record.key),
+            JsonEncoder::encode(record)?,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
     fn set_new_state_changes(
         &self,
         db: &Arc<OptimisticTransactionDB>,
@@ -719,9 +1396,6 @@ impl IndexifyState {
     ) -> Result<(), StateMachineError> {
         let mut change_id = self.get_next_change_ids(state_changes.len());
         for change in state_changes {
-            if let Some(refcnt_object_id) = change.refcnt_object_id.as_ref() {
-                self.inc_root_ref_count(refcnt_object_id);
-            }
             change.id = StateChangeId::new(change_id);
             change_id += 1;
             let serialized_change = JsonEncoder::encode(&change)?;
@@ -762,6 +1436,28 @@ impl IndexifyState {
         Ok(changes)
     }
 
+    /// Clears the processed time of a state change and returns the change
+    fn reset_state_change(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        state_change_id: &StateChangeId,
+    ) -> Result<StateChange, StateMachineError> {
+        let key = state_change_id.to_key();
+        let mut state_change = self
+            .get_from_cf::<StateChange, _>(db, StateMachineColumns::StateChanges, key)?
+            .ok_or(StateMachineError::StateChangeNotFound(*state_change_id))?;
+        state_change.processed_at = None;
+        let serialized_change = JsonEncoder::encode(&state_change)?;
+        txn.put_cf(
+            StateMachineColumns::StateChanges.cf(db),
+            key,
+            &serialized_change,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        Ok(state_change)
+    }
+
     fn set_index(
         &self,
         db: &Arc<OptimisticTransactionDB>,
@@ -775,16 +1471,256 @@ impl IndexifyState {
         Ok(())
     }
 
-    fn set_tasks(
+    fn set_search_profile(
         &self,
         db: &Arc<OptimisticTransactionDB>,
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
-        tasks: &Vec<internal_api::Task>,
+        profile: &internal_api::SearchProfile,
     ) -> Result<(), StateMachineError> {
-        // content_id -> Set(Extraction Policy Ids)
-        for task in tasks {
-            let serialized_task = JsonEncoder::encode(task)?;
-            txn.put_cf(
+        let serialized_profile = JsonEncoder::encode(profile)?;
+        txn.put_cf(
+            StateMachineColumns::SearchProfiles.cf(db),
+            profile.key(),
+            serialized_profile,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_search_profile(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        namespace: &str,
+        name: &str,
+    ) -> Result<(), StateMachineError> {
+        txn.delete_cf(
+            StateMachineColumns::SearchProfiles.cf(db),
+            internal_api::SearchProfile::key_from(namespace, name),
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn set_search_feedback(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        feedback: &internal_api::SearchFeedback,
+    ) -> Result<(), StateMachineError> {
+        let serialized_feedback = JsonEncoder::encode(feedback)?;
+        txn.put_cf(
+            StateMachineColumns::SearchFeedback.cf(db),
+            feedback.key(),
+            serialized_feedback,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn set_namespace_alias(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        alias: &str,
+        namespace: &NamespaceName,
+    ) -> Result<(), StateMachineError> {
+        txn.put_cf(
+            StateMachineColumns::NamespaceAliases.cf(db),
+            alias,
+            JsonEncoder::encode(namespace)?,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn set_namespace_retention(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        namespace: &NamespaceName,
+        rules: &internal_api::RetentionRules,
+    ) -> Result<(), StateMachineError> {
+        let cf = StateMachineColumns::NamespaceRetention.cf(db);
+        if rules.is_empty() {
+            return txn
+                .delete_cf(cf, namespace)
+                .map_err(|e| StateMachineError::DatabaseError(e.to_string()));
+        }
+        txn.put_cf(cf, namespace, JsonEncoder::encode(rules)?)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
+    fn set_subscription(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        subscription: &internal_api::Subscription,
+    ) -> Result<(), StateMachineError> {
+        txn.put_cf(
+            StateMachineColumns::Subscriptions.cf(db),
+            &subscription.id,
+            JsonEncoder::encode(subscription)?,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
+    fn delete_subscription(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        subscription_id: &str,
+    ) -> Result<(), StateMachineError> {
+        txn.delete_cf(StateMachineColumns::Subscriptions.cf(db), subscription_id)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        for column in [
+            StateMachineColumns::PendingDeliveries,
+            StateMachineColumns::DeadLetterDeliveries,
+        ] {
+            for delivery in self.list_deliveries(column, subscription_id, None, db)? {
+                txn.delete_cf(column.cf(db), delivery.key())
+                    .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn enqueue_deliveries(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        deliveries: &[internal_api::Delivery],
+    ) -> Result<(), StateMachineError> {
+        let mut subscriptions = HashMap::new();
+        for delivery in deliveries {
+            if !subscriptions.contains_key(&delivery.subscription_id) {
+                let subscription = self
+                    .get_subscription(&delivery.subscription_id, db)
+                    .map_err(StateMachineError::ExternalError)?;
+                subscriptions.insert(delivery.subscription_id.clone(), subscription);
+            }
+            let Some(subscription) = subscriptions
+                .get_mut(&delivery.subscription_id)
+                .and_then(|subscription| subscription.as_mut())
+            else {
+                continue;
+            };
+            let delivery = internal_api::Delivery {
+                sequence: subscription.next_sequence,
+                ..delivery.clone()
+            };
+            subscription.next_sequence += 1;
+            txn.put_cf(
+                StateMachineColumns::PendingDeliveries.cf(db),
+                delivery.key(),
+                JsonEncoder::encode(&delivery)?,
+            )
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        }
+        for subscription in subscriptions.values().flatten() {
+            self.set_subscription(db, txn, subscription)?;
+        }
+        Ok(())
+    }
+
+    fn is_pending_delivery(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        delivery: &internal_api::Delivery,
+    ) -> Result<bool, StateMachineError> {
+        Ok(db
+            .get_cf(
+                StateMachineColumns::PendingDeliveries.cf(db),
+                delivery.key(),
+            )
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?
+            .is_some())
+    }
+
+    /// Replaces a pending delivery. Deliveries which are no longer pending,
+    /// e.g. of a deleted subscription, aren't put back.
+    fn update_pending_delivery(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        delivery: &internal_api::Delivery,
+    ) -> Result<(), StateMachineError> {
+        if !self.is_pending_delivery(db, delivery)? {
+            return Ok(());
+        }
+        txn.put_cf(
+            StateMachineColumns::PendingDeliveries.cf(db),
+            delivery.key(),
+            JsonEncoder::encode(delivery)?,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
+    fn dead_letter_delivery(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        delivery: &internal_api::Delivery,
+    ) -> Result<(), StateMachineError> {
+        if !self.is_pending_delivery(db, delivery)? {
+            return Ok(());
+        }
+        txn.delete_cf(
+            StateMachineColumns::PendingDeliveries.cf(db),
+            delivery.key(),
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        txn.put_cf(
+            StateMachineColumns::DeadLetterDeliveries.cf(db),
+            delivery.key(),
+            JsonEncoder::encode(delivery)?,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
+    fn requeue_dead_letters(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        subscription_id: &str,
+    ) -> Result<(), StateMachineError> {
+        let dead_letters = self.list_deliveries(
+            StateMachineColumns::DeadLetterDeliveries,
+            subscription_id,
+            None,
+            db,
+        )?;
+        for delivery in dead_letters {
+            txn.delete_cf(
+                StateMachineColumns::DeadLetterDeliveries.cf(db),
+                delivery.key(),
+            )
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            let delivery = internal_api::Delivery {
+                attempts: 0,
+                next_attempt_at: 0,
+                ..delivery
+            };
+            txn.put_cf(
+                StateMachineColumns::PendingDeliveries.cf(db),
+                delivery.key(),
+                JsonEncoder::encode(&delivery)?,
+            )
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn set_tasks(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        tasks: &Vec<internal_api::Task>,
+    ) -> Result<(), StateMachineError> {
+        // content_id -> Set(Extraction Policy Ids)
+        for task in tasks {
+            let serialized_task = JsonEncoder::encode(task)?;
+            txn.put_cf(
                 StateMachineColumns::Tasks.cf(db),
                 task.id.clone(),
                 &serialized_task,
@@ -829,6 +1765,38 @@ impl IndexifyState {
         Ok(())
     }
 
+    /// Adds a finished task to the usage counters of its namespace and
+    /// extractor for the day it finished on. Tasks that were already
+    /// finished are skipped.
+    fn record_task_usage(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        task: &internal_api::Task,
+        update_time: SystemTime,
+    ) -> Result<(), StateMachineError> {
+        let stored_task = txn
+            .get_cf(StateMachineColumns::Tasks.cf(db), &task.id)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?
+            .map(|value| JsonEncoder::decode::<internal_api::Task>(&value))
+            .transpose()?;
+        if stored_task.map_or(false, |t| t.terminal_state()) {
+            return Ok(());
+        }
+        let cf = StateMachineColumns::UsageStats.cf(db);
+        let mut stats =
+            internal_api::UsageStats::new(&task.namespace, &task.extractor, day_of(update_time));
+        if let Some(value) = txn
+            .get_cf(cf, stats.key())
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?
+        {
+            stats = JsonEncoder::decode(&value)?;
+        }
+        stats.record(task);
+        txn.put_cf(cf, stats.key(), JsonEncoder::encode(&stats)?)
+            .map_err(|e| StateMachineError::DatabaseError(e.to_string()))
+    }
+
     fn set_garbage_collection_tasks(
         &self,
         db: &Arc<OptimisticTransactionDB>,
@@ -871,8 +1839,14 @@ impl IndexifyState {
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
         executor_id: &str,
     ) -> Result<HashSet<TaskId>, StateMachineError> {
+        //  Read for update, the assignments are written back in the same
+        //  transaction
         let value = txn
-            .get_cf(StateMachineColumns::TaskAssignments.cf(db), executor_id)
+            .get_for_update_cf(
+                StateMachineColumns::TaskAssignments.cf(db),
+                executor_id,
+                true,
+            )
             .map_err(|e| {
                 StateMachineError::DatabaseError(format!("Error reading task assignments: {}", e))
             })?;
@@ -954,17 +1928,81 @@ impl IndexifyState {
         db: &Arc<OptimisticTransactionDB>,
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
         contents_vec: impl IntoIterator<Item = &'a internal_api::ContentMetadata>,
-    ) -> Result<(), StateMachineError> {
+    ) -> Result<Vec<ContentMetadataId>, StateMachineError> {
+        let mut written = Vec::new();
         for content in contents_vec {
             let serialized_content = JsonEncoder::encode(content)?;
             txn.put_cf(
                 StateMachineColumns::ContentTable.cf(db),
-                content.id_key(),
+                content.id_key(&self.content_version_separator),
                 &serialized_content,
             )
             .map_err(|e| {
                 StateMachineError::DatabaseError(format!("error writing content: {}", e))
             })?;
+            self.update_content_lookup_index(db, txn, content)?;
+            written.push(content.id.clone());
+        }
+        Ok(written)
+    }
+
+    /// Keeps the id and hash lookup entries of root content pointing at the
+    /// latest version. Entries are left in place when content is tombstoned
+    /// so lookups can still report it, and are only removed on deletion.
+    fn update_content_lookup_index(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        content: &internal_api::ContentMetadata,
+    ) -> Result<(), StateMachineError> {
+        if content.parent_id.is_some() {
+            return Ok(());
+        }
+        let cf = StateMachineColumns::ContentLookupIndex.cf(db);
+        if content.latest {
+            let serialized_id = JsonEncoder::encode(&content.id)?;
+            for key in content_lookup_keys(content) {
+                txn.put_cf(cf, key, &serialized_id).map_err(|e| {
+                    StateMachineError::DatabaseError(format!(
+                        "error writing content lookup index: {}",
+                        e
+                    ))
+                })?;
+            }
+            return Ok(());
+        }
+        // An older version only gives up the hash entry, the id entry is owned
+        // by whichever version is written as latest.
+        if !content.hash.is_empty() {
+            self.remove_content_lookup_key(
+                db,
+                txn,
+                content_hash_lookup_key(&content.namespace, &content.hash),
+                &content.id,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Removes a lookup entry only if it still points at the given content, so
+    /// that entries which were taken over by newer content are left intact.
+    fn remove_content_lookup_key(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        key: String,
+        content_id: &ContentMetadataId,
+    ) -> Result<(), StateMachineError> {
+        let cf = StateMachineColumns::ContentLookupIndex.cf(db);
+        let existing = txn
+            .get_cf(cf, &key)
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+        let Some(existing) = existing else {
+            return Ok(());
+        };
+        if JsonEncoder::decode::<ContentMetadataId>(&existing)? == *content_id {
+            txn.delete_cf(cf, &key)
+                .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
         }
         Ok(())
     }
@@ -974,10 +2012,12 @@ impl IndexifyState {
         db: &Arc<OptimisticTransactionDB>,
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
         content_metadata: &Vec<indexify_internal_api::ContentMetadata>,
+        reason: &internal_api::TombstoneReason,
     ) -> Result<(), StateMachineError> {
         for content in content_metadata {
             let cf = StateMachineColumns::ContentTable.cf(db);
             let mut content = content.clone();
+            content.tombstone_reason = Some(reason.clone());
             // If updating latest version of root node, the key will change so delete from
             // previous location.
             if content.latest && content.parent_id.is_none() {
@@ -986,16 +2026,23 @@ impl IndexifyState {
                 content.latest = false;
             }
             let serialized_content = JsonEncoder::encode(&content)?;
-            txn.put_cf(cf, content.id_key(), &serialized_content)
-                .map_err(|e| {
-                    StateMachineError::DatabaseError(format!("error writing content: {}", e))
-                })?;
+            txn.put_cf(
+                cf,
+                content.id_key(&self.content_version_separator),
+                &serialized_content,
+            )
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("error writing content: {}", e))
+            })?;
         }
 
         Ok(())
     }
 
-    /// Function to delete content based on content ids
+    /// Function to delete content based on content ids. Runs when a garbage
+    /// collection task completes, only after
+    /// `DataManager::delete_content_with_vectors` removed the vectors of the
+    /// content from every output index.
     fn delete_content(
         &self,
         db: &Arc<OptimisticTransactionDB>,
@@ -1003,16 +2050,25 @@ impl IndexifyState {
         content_ids: Vec<ContentMetadataId>,
     ) -> Result<(), StateMachineError> {
         for content_id in content_ids {
-            txn.delete_cf(
-                StateMachineColumns::ContentTable.cf(db),
-                &format!("{}::v{}", content_id.id, content_id.version),
-            )
-            .map_err(|e| {
-                StateMachineError::TransactionError(format!(
-                    "error in txn while trying to delete content: {}",
-                    e
-                ))
-            })?;
+            let key = self.content_version_separator.key(&content_id);
+            let content = txn
+                .get_cf(StateMachineColumns::ContentTable.cf(db), &key)
+                .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+            if let Some(content) = content {
+                let content = JsonEncoder::decode::<internal_api::ContentMetadata>(&content)?;
+                if content.parent_id.is_none() {
+                    for lookup_key in content_lookup_keys(&content) {
+                        self.remove_content_lookup_key(db, txn, lookup_key, &content_id)?;
+                    }
+                }
+            }
+            txn.delete_cf(StateMachineColumns::ContentTable.cf(db), &key)
+                .map_err(|e| {
+                    StateMachineError::TransactionError(format!(
+                        "error in txn while trying to delete content: {}",
+                        e
+                    ))
+                })?;
         }
         Ok(())
     }
@@ -1025,12 +2081,14 @@ impl IndexifyState {
         executor_id: &str,
         extractors: &Vec<ExtractorDescription>,
         ts_secs: &u64,
+        capabilities: &HashMap<String, String>,
     ) -> Result<(), StateMachineError> {
         let serialized_executor = JsonEncoder::encode(&internal_api::ExecutorMetadata {
             id: executor_id.into(),
             last_seen: *ts_secs,
             addr: addr.clone(),
             extractors: extractors.clone(),
+            capabilities: capabilities.clone(),
         })?;
         txn.put_cf(
             StateMachineColumns::Executors.cf(db),
@@ -1041,6 +2099,21 @@ impl IndexifyState {
         Ok(())
     }
 
+    fn get_executor(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        executor_id: &str,
+    ) -> Result<Option<internal_api::ExecutorMetadata>, StateMachineError> {
+        txn.get_cf(StateMachineColumns::Executors.cf(db), executor_id)
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error reading executor: {}", e))
+            })?
+            .map(|executor| JsonEncoder::decode::<internal_api::ExecutorMetadata>(&executor))
+            .transpose()
+            .map_err(StateMachineError::from)
+    }
+
     fn delete_executor(
         &self,
         db: &Arc<OptimisticTransactionDB>,
@@ -1084,59 +2157,335 @@ impl IndexifyState {
         Ok(())
     }
 
-    fn set_extraction_policy(
-        &self,
-        db: &Arc<OptimisticTransactionDB>,
-        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
-        extraction_policy: &ExtractionPolicy,
-    ) -> Result<(), StateMachineError> {
-        let serialized_extraction_policy = JsonEncoder::encode(extraction_policy)?;
-        txn.put_cf(
-            &StateMachineColumns::ExtractionPolicies.cf(db),
-            extraction_policy.id.clone(),
-            serialized_extraction_policy,
-        )
-        .map_err(|e| {
-            StateMachineError::DatabaseError(format!("Error writing extraction policy: {}", e))
-        })?;
-        Ok(())
-    }
-
-    fn set_namespace(
+    /// Replaces the description of an extractor run by an executor, keeping
+    /// the executor's other extractors
+    fn update_executor_extractor(
         &self,
         db: &Arc<OptimisticTransactionDB>,
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
-        namespace: &NamespaceName,
+        executor_id: &str,
+        extractor: &ExtractorDescription,
     ) -> Result<(), StateMachineError> {
-        let serialized_name = JsonEncoder::encode(namespace)?;
+        let executor = txn
+            .get_cf(StateMachineColumns::Executors.cf(db), executor_id)
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error reading executor: {}", e))
+            })?
+            .ok_or_else(|| {
+                StateMachineError::DatabaseError(format!("Executor {} not found", executor_id))
+            })?;
+        let mut executor = JsonEncoder::decode::<internal_api::ExecutorMetadata>(&executor)?;
+        for existing in executor.extractors.iter_mut() {
+            if existing.name == extractor.name {
+                *existing = extractor.clone();
+            }
+        }
+        let serialized_executor = JsonEncoder::encode(&executor)?;
         txn.put_cf(
-            &StateMachineColumns::Namespaces.cf(db),
-            namespace,
-            serialized_name,
+            StateMachineColumns::Executors.cf(db),
+            executor_id,
+            serialized_executor,
         )
-        .map_err(|e| StateMachineError::DatabaseError(format!("Error writing namespace: {}", e)))?;
-        Ok(())
+        .map_err(|e| StateMachineError::DatabaseError(format!("Error writing executor: {}", e)))?;
+        self.set_extractors(db, txn, &vec![extractor.clone()])
     }
 
-    fn set_schema(
+    /// Moves an extractor to a new name, along with the executors running it,
+    /// the extraction policies and indexes using it and its unfinished tasks
+    fn rename_extractor(
         &self,
         db: &Arc<OptimisticTransactionDB>,
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
-        schema: &internal_api::StructuredDataSchema,
+        old_name: &str,
+        new_name: &str,
     ) -> Result<(), StateMachineError> {
-        let serialized_schema = JsonEncoder::encode(schema)?;
-        txn.put_cf(
-            &StateMachineColumns::StructuredDataSchemas.cf(db),
-            schema.id.clone(),
-            serialized_schema,
-        )
-        .map_err(|e| StateMachineError::DatabaseError(format!("Error writing schema: {}", e)))?;
-        Ok(())
-    }
+        let mut extractor = self
+            .get_from_cf::<ExtractorDescription, _>(db, StateMachineColumns::Extractors, old_name)?
+            .ok_or_else(|| {
+                StateMachineError::DatabaseError(format!("Extractor {} not found", old_name))
+            })?;
+        extractor.name = new_name.to_string();
+        txn.delete_cf(StateMachineColumns::Extractors.cf(db), old_name)
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error deleting extractor: {}", e))
+            })?;
+        self.set_extractors(db, txn, &vec![extractor])?;
 
-    pub fn update_content_extraction_policy_state(
-        &self,
-        db: &Arc<OptimisticTransactionDB>,
+        let executors = self.get_all_rows_from_cf::<internal_api::ExecutorMetadata>(
+            StateMachineColumns::Executors,
+            db,
+        )?;
+        for (executor_id, mut executor) in executors {
+            let mut renamed = false;
+            for extractor in executor.extractors.iter_mut() {
+                if extractor.name == old_name {
+                    extractor.name = new_name.to_string();
+                    renamed = true;
+                }
+            }
+            if !renamed {
+                continue;
+            }
+            let serialized_executor = JsonEncoder::encode(&executor)?;
+            txn.put_cf(
+                StateMachineColumns::Executors.cf(db),
+                executor_id,
+                serialized_executor,
+            )
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error writing executor: {}", e))
+            })?;
+        }
+
+        let policies = self.get_all_rows_from_cf::<ExtractionPolicy>(
+            StateMachineColumns::ExtractionPolicies,
+            db,
+        )?;
+        for (_, mut policy) in policies {
+            if policy.extractor == old_name {
+                policy.extractor = new_name.to_string();
+                self.set_extraction_policy(db, txn, &policy)?;
+            }
+        }
+
+        let indexes =
+            self.get_all_rows_from_cf::<internal_api::Index>(StateMachineColumns::IndexTable, db)?;
+        for (id, mut index) in indexes {
+            if index.extractor_name == old_name {
+                index.extractor_name = new_name.to_string();
+                self.set_index(db, txn, &index, &id)?;
+            }
+        }
+
+        let task_ids = self
+            .unfinished_tasks_by_extractor
+            .inner()
+            .remove(old_name)
+            .unwrap_or_default();
+        let mut tasks = Vec::new();
+        for task_id in task_ids {
+            if let Some(mut task) =
+                self.get_from_cf::<internal_api::Task, _>(db, StateMachineColumns::Tasks, &task_id)?
+            {
+                task.extractor = new_name.to_string();
+                tasks.push(task);
+            }
+        }
+        //  Unfinished tasks don't update the extraction state of their content
+        self.update_tasks(db, txn, tasks.iter().collect(), SystemTime::UNIX_EPOCH)
+    }
+
+    fn set_extraction_policy(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        extraction_policy: &ExtractionPolicy,
+    ) -> Result<(), StateMachineError> {
+        let serialized_extraction_policy = JsonEncoder::encode(extraction_policy)?;
+        txn.put_cf(
+            &StateMachineColumns::ExtractionPolicies.cf(db),
+            extraction_policy.id.clone(),
+            serialized_extraction_policy,
+        )
+        .map_err(|e| {
+            StateMachineError::DatabaseError(format!("Error writing extraction policy: {}", e))
+        })?;
+        Ok(())
+    }
+
+    /// Replaces the input params of an extraction policy and increments its
+    /// version. The copy of the policy in its extraction graph is updated
+    /// as well.
+    fn update_extraction_policy_params(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        extraction_policy_id: &str,
+        input_params: &serde_json::Value,
+    ) -> Result<(), StateMachineError> {
+        let value = txn
+            .get_cf(
+                StateMachineColumns::ExtractionPolicies.cf(db),
+                extraction_policy_id,
+            )
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error reading extraction policy: {}", e))
+            })?
+            .ok_or_else(|| {
+                StateMachineError::ExtractionPolicyNotFound(extraction_policy_id.to_string())
+            })?;
+        let mut extraction_policy = JsonEncoder::decode::<ExtractionPolicy>(&value)?;
+        extraction_policy.input_params = input_params.clone();
+        extraction_policy.version += 1;
+        self.set_extraction_policy(db, txn, &extraction_policy)?;
+
+        let graph_id =
+            ExtractionGraph::create_id(&extraction_policy.graph_name, &extraction_policy.namespace);
+        let graphs_cf = StateMachineColumns::ExtractionGraphs.cf(db);
+        let value = txn.get_cf(graphs_cf, &graph_id).map_err(|e| {
+            StateMachineError::DatabaseError(format!("Error reading extraction graph: {}", e))
+        })?;
+        if let Some(value) = value {
+            let mut extraction_graph = JsonEncoder::decode::<ExtractionGraph>(&value)?;
+            for ep in &mut extraction_graph.extraction_policies {
+                if ep.id == extraction_policy.id {
+                    *ep = extraction_policy.clone();
+                }
+            }
+            txn.put_cf(
+                graphs_cf,
+                &graph_id,
+                JsonEncoder::encode(&extraction_graph)?,
+            )
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error writing extraction graph: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Deletes an extraction policy along with its copy in its extraction
+    /// graph, and clears it from the policies applied on the latest versions
+    /// of the content of its namespace
+    fn delete_extraction_policy(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        namespace: &str,
+        extraction_policy_id: &str,
+    ) -> Result<(), StateMachineError> {
+        let policies_cf = StateMachineColumns::ExtractionPolicies.cf(db);
+        let value = txn
+            .get_cf(policies_cf, extraction_policy_id)
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error reading extraction policy: {}", e))
+            })?
+            .ok_or_else(|| {
+                StateMachineError::ExtractionPolicyNotFound(extraction_policy_id.to_string())
+            })?;
+        let extraction_policy = JsonEncoder::decode::<ExtractionPolicy>(&value)?;
+        if extraction_policy.namespace != namespace {
+            return Err(StateMachineError::ExtractionPolicyNotFound(
+                extraction_policy_id.to_string(),
+            ));
+        }
+        txn.delete_cf(policies_cf, extraction_policy_id)
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error deleting extraction policy: {}", e))
+            })?;
+
+        let graph_id =
+            ExtractionGraph::create_id(&extraction_policy.graph_name, &extraction_policy.namespace);
+        let graphs_cf = StateMachineColumns::ExtractionGraphs.cf(db);
+        let value = txn.get_cf(graphs_cf, &graph_id).map_err(|e| {
+            StateMachineError::DatabaseError(format!("Error reading extraction graph: {}", e))
+        })?;
+        if let Some(value) = value {
+            let mut extraction_graph = JsonEncoder::decode::<ExtractionGraph>(&value)?;
+            extraction_graph
+                .extraction_policies
+                .retain(|ep| ep.id != extraction_policy_id);
+            txn.put_cf(
+                graphs_cf,
+                &graph_id,
+                JsonEncoder::encode(&extraction_graph)?,
+            )
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!("Error writing extraction graph: {}", e))
+            })?;
+        }
+
+        //  Content ids carry the version, the latest version is stored under
+        //  the plain id
+        let content_ids: HashSet<String> = self
+            .content_namespace_table
+            .get(namespace)
+            .into_iter()
+            .map(|id| id.id)
+            .collect();
+        for content_id in content_ids {
+            self.clear_content_extraction_policy_state(db, txn, &content_id, extraction_policy_id)?;
+        }
+        Ok(())
+    }
+
+    fn set_namespace(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        namespace: &NamespaceName,
+    ) -> Result<(), StateMachineError> {
+        let serialized_name = JsonEncoder::encode(namespace)?;
+        txn.put_cf(
+            &StateMachineColumns::Namespaces.cf(db),
+            namespace,
+            serialized_name,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(format!("Error writing namespace: {}", e)))?;
+        Ok(())
+    }
+
+    fn set_schema(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        schema: &internal_api::StructuredDataSchema,
+    ) -> Result<(), StateMachineError> {
+        let schema_id = SchemaId::new(&schema.id)?;
+        let serialized_schema = JsonEncoder::encode(schema)?;
+        txn.put_cf(
+            &StateMachineColumns::StructuredDataSchemas.cf(db),
+            schema_id.as_str(),
+            serialized_schema,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(format!("Error writing schema: {}", e)))?;
+        Ok(())
+    }
+
+    /// Writes the schema a namespace is created with. The default schema
+    /// doesn't replace an existing one, so creating a namespace again keeps
+    /// the columns an extraction graph may have added to it.
+    fn set_namespace_schema(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        namespace: &str,
+        schema: Option<&internal_api::StructuredDataSchema>,
+    ) -> Result<(), StateMachineError> {
+        if let Some(schema) = schema {
+            return self.set_schema(db, txn, schema);
+        }
+        let schema = internal_api::StructuredDataSchema::empty_for(namespace);
+        let exists = txn
+            .get_cf(
+                &StateMachineColumns::StructuredDataSchemas.cf(db),
+                &schema.id,
+            )
+            .map_err(|e| StateMachineError::DatabaseError(format!("Error reading schema: {}", e)))?
+            .is_some();
+        if !exists {
+            self.set_schema(db, txn, &schema)?;
+        }
+        Ok(())
+    }
+
+    fn delete_schema(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        schema_id: &str,
+    ) -> Result<(), StateMachineError> {
+        txn.delete_cf(
+            &StateMachineColumns::StructuredDataSchemas.cf(db),
+            schema_id,
+        )
+        .map_err(|e| StateMachineError::DatabaseError(format!("Error deleting schema: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn update_content_extraction_policy_state(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
         txn: &rocksdb::Transaction<OptimisticTransactionDB>,
         content_id: &ContentMetadataId,
         extraction_policy_id: &str,
@@ -1157,6 +2506,39 @@ impl IndexifyState {
                 ))
             })?;
         let mut content_meta = JsonEncoder::decode::<internal_api::ContentMetadata>(&value)?;
+        let mut key = content_id.id.clone();
+        // Tasks for a version which has since been replaced must not mark the policy
+        // as applied on the newer version. Policies which run on versions other than
+        // the latest record it on the version the task ran on instead.
+        if content_meta.id.version > content_id.version {
+            let version_strategy = self
+                .get_from_cf::<ExtractionPolicy, _>(
+                    db,
+                    StateMachineColumns::ExtractionPolicies,
+                    extraction_policy_id,
+                )?
+                .map(|policy| policy.version_strategy)
+                .unwrap_or_default();
+            let previous =
+                if version_strategy == internal_api::ExtractionPolicyVersionStrategy::LatestOnly {
+                    None
+                } else {
+                    txn.get_cf(
+                        StateMachineColumns::ContentTable.cf(db),
+                        self.content_version_separator.key(content_id),
+                    )
+                    .map_err(|e| StateMachineError::TransactionError(e.to_string()))?
+                };
+            let Some(previous) = previous else {
+                warn!(
+                    "skipping extraction policy {} state for stale content version {}, latest is {}",
+                    extraction_policy_id, content_id, content_meta.id
+                );
+                return Ok(());
+            };
+            content_meta = JsonEncoder::decode::<internal_api::ContentMetadata>(&previous)?;
+            key = self.content_version_separator.key(content_id);
+        }
         let epoch_time = policy_completion_time
             .duration_since(SystemTime::UNIX_EPOCH)
             .map_err(|e| {
@@ -1170,18 +2552,52 @@ impl IndexifyState {
             .extraction_policy_ids
             .insert(extraction_policy_id.to_string(), epoch_time);
         let data = JsonEncoder::encode(&content_meta)?;
+        txn.put_cf(StateMachineColumns::ContentTable.cf(db), &key, data)
+            .map_err(|e| {
+                StateMachineError::DatabaseError(format!(
+                    "Error writing content policies applied on content for id {}: {}",
+                    content_id, e
+                ))
+            })?;
+
+        Ok(())
+    }
+
+    /// Removes the completion time of an extraction policy from content, so
+    /// the policy is considered not applied. Missing content and policies
+    /// which were never applied are left alone.
+    fn clear_content_extraction_policy_state(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        content_id: &str,
+        extraction_policy_id: &str,
+    ) -> Result<(), StateMachineError> {
+        let Some(value) = txn
+            .get_cf(StateMachineColumns::ContentTable.cf(db), content_id)
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?
+        else {
+            return Ok(());
+        };
+        let mut content_meta = JsonEncoder::decode::<internal_api::ContentMetadata>(&value)?;
+        if content_meta
+            .extraction_policy_ids
+            .remove(extraction_policy_id)
+            .is_none()
+        {
+            return Ok(());
+        }
         txn.put_cf(
             StateMachineColumns::ContentTable.cf(db),
-            &content_id.id,
-            data,
+            content_id,
+            JsonEncoder::encode(&content_meta)?,
         )
         .map_err(|e| {
             StateMachineError::DatabaseError(format!(
-                "Error writing content policies applied on content for id {}: {}",
+                "Error clearing content policies applied on content for id {}: {}",
                 content_id, e
             ))
         })?;
-
         Ok(())
     }
 
@@ -1207,20 +2623,22 @@ impl IndexifyState {
         Ok(())
     }
 
-    pub fn mark_state_changes_processed(
-        &self,
-        state_change: &StateChangeProcessed,
-        _processed_at: u64,
-    ) {
-        self.unprocessed_state_changes
-            .remove(&state_change.state_change_id);
+    pub fn mark_state_changes_processed(&self, state_changes: &[StateChangeProcessed]) {
+        if state_changes.is_empty() {
+            return;
+        }
+        self.unprocessed_state_changes.remove_all(
+            state_changes
+                .iter()
+                .map(|state_change| &state_change.state_change_id),
+        );
     }
 
     fn update_extraction_graph_reverse_idx(
         &self,
         extraction_graph: &ExtractionGraph,
         structured_data_schema: internal_api::StructuredDataSchema,
-    ) {
+    ) -> Result<(), StateMachineError> {
         for ep in &extraction_graph.extraction_policies {
             self.extraction_policies_table.insert(&ep.namespace, &ep.id);
         }
@@ -1228,8 +2646,416 @@ impl IndexifyState {
             .insert(&extraction_graph.namespace, &extraction_graph.id);
         self.schemas_by_namespace.insert(
             &structured_data_schema.namespace,
-            &structured_data_schema.id,
+            &SchemaId::new_unchecked(&structured_data_schema.id),
         );
+        Ok(())
+    }
+
+    /// Rejects requests which reference a namespace that has not been created
+    fn validate_namespaces(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let namespaces: HashSet<&str> = match payload {
+            RequestPayload::CreateOrUpdateContent { entries, .. } => entries
+                .iter()
+                .map(|entry| entry.content.namespace.as_str())
+                .collect(),
+            RequestPayload::CreateExtractionGraph {
+                extraction_graph,
+                structured_data_schema: _,
+                indexes,
+            } => std::iter::once(extraction_graph.namespace.as_str())
+                .chain(indexes.iter().map(|index| index.namespace.as_str()))
+                .collect(),
+            RequestPayload::SetIndex { indexes } => indexes
+                .iter()
+                .map(|index| index.namespace.as_str())
+                .collect(),
+            RequestPayload::SetSearchProfile { profile } => {
+                HashSet::from([profile.namespace.as_str()])
+            }
+            RequestPayload::CreateSearchFeedback { feedback } => {
+                HashSet::from([feedback.namespace.as_str()])
+            }
+            RequestPayload::SetNamespaceAlias { namespace, .. } => {
+                HashSet::from([namespace.as_str()])
+            }
+            RequestPayload::CreateSubscription { subscription } => {
+                HashSet::from([subscription.namespace.as_str()])
+            }
+            RequestPayload::SetNamespaceRetention { namespace, .. } |
+            RequestPayload::DeleteExtractionPolicy { namespace, .. } => {
+                HashSet::from([namespace.as_str()])
+            }
+            _ => return Ok(()),
+        };
+        let namespaces_cf = StateMachineColumns::Namespaces.cf(db);
+        for namespace in namespaces {
+            let exists = db
+                .get_cf(namespaces_cf, namespace)
+                .map_err(|e| {
+                    StateMachineError::DatabaseError(format!("Error reading namespace: {}", e))
+                })?
+                .is_some();
+            if !exists {
+                return Err(StateMachineError::NamespaceNotFound(namespace.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects extractor updates from executors which don't run the extractor
+    /// and updates which change or remove an output an index is built from
+    fn validate_extractor_update(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let RequestPayload::UpdateExecutorExtractor {
+            executor_id,
+            extractor,
+        } = payload
+        else {
+            return Ok(());
+        };
+        let reject = |reason: String| StateMachineError::IncompatibleExtractorUpdate {
+            extractor: extractor.name.clone(),
+            reason,
+        };
+        let executor = self
+            .get_from_cf::<internal_api::ExecutorMetadata, _>(
+                db,
+                StateMachineColumns::Executors,
+                executor_id,
+            )?
+            .ok_or_else(|| reject(format!("executor {} is not registered", executor_id)))?;
+        if !executor.extractors.iter().any(|e| e.name == extractor.name) {
+            return Err(reject(format!(
+                "executor {} doesn't run the extractor",
+                executor_id
+            )));
+        }
+        let Some(current) = self.get_from_cf::<ExtractorDescription, _>(
+            db,
+            StateMachineColumns::Extractors,
+            &extractor.name,
+        )?
+        else {
+            return Ok(());
+        };
+        let changed_outputs: Vec<&String> = current
+            .outputs
+            .iter()
+            .filter(|(name, schema)| extractor.outputs.get(*name) != Some(*schema))
+            .map(|(name, _)| name)
+            .collect();
+        if changed_outputs.is_empty() {
+            return Ok(());
+        }
+        let indexes =
+            self.get_all_rows_from_cf::<internal_api::Index>(StateMachineColumns::IndexTable, db)?;
+        for (_, index) in indexes {
+            if index.extractor_name != extractor.name {
+                continue;
+            }
+            if let Some(output) = changed_outputs
+                .iter()
+                .find(|output| index.name == index.build_name(output))
+            {
+                return Err(reject(format!(
+                    "the schema of output {} is used by index {}",
+                    output, index.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects renaming an extractor which isn't registered, or to the name of
+    /// another registered extractor
+    fn validate_extractor_rename(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let RequestPayload::RenameExtractor { old_name, new_name } = payload else {
+            return Ok(());
+        };
+        let reject = |reason: String| StateMachineError::IncompatibleExtractorUpdate {
+            extractor: old_name.clone(),
+            reason,
+        };
+        if new_name.is_empty() {
+            return Err(reject("the new name is empty".to_string()));
+        }
+        let exists = |name: &str| {
+            self.get_from_cf::<ExtractorDescription, _>(db, StateMachineColumns::Extractors, name)
+                .map(|extractor| extractor.is_some())
+        };
+        if !exists(old_name)? {
+            return Err(reject("the extractor is not registered".to_string()));
+        }
+        if old_name != new_name && exists(new_name)? {
+            return Err(reject(format!("extractor {} already exists", new_name)));
+        }
+        Ok(())
+    }
+
+    /// Rejects the deletion of a schema which extraction policies of its
+    /// extraction graph still write to
+    fn validate_schema_deletion(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let RequestPayload::DeleteSchema {
+            namespace,
+            schema_id,
+        } = payload
+        else {
+            return Ok(());
+        };
+        let Some(schema) = self.get_from_cf::<internal_api::StructuredDataSchema, _>(
+            db,
+            StateMachineColumns::StructuredDataSchemas,
+            schema_id,
+        )?
+        else {
+            return Ok(());
+        };
+        let policies: Vec<String> = self
+            .get_extraction_policies_from_ids(self.extraction_policies_table.get(namespace), db)?
+            .found
+            .into_iter()
+            .filter(|policy| policy.graph_name == schema.extraction_graph_name)
+            .map(|policy| policy.name)
+            .sorted()
+            .collect();
+        if !policies.is_empty() {
+            return Err(StateMachineError::SchemaInUse {
+                schema_id: schema_id.clone(),
+                policies,
+            });
+        }
+        Ok(())
+    }
+
+    /// Rejects the deletion of an extraction policy which other extraction
+    /// policies of its extraction graph take their content from
+    fn validate_extraction_policy_deletion(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let RequestPayload::DeleteExtractionPolicy {
+            namespace,
+            extraction_policy_id,
+        } = payload
+        else {
+            return Ok(());
+        };
+        let Some(extraction_policy) = self.get_from_cf::<ExtractionPolicy, _>(
+            db,
+            StateMachineColumns::ExtractionPolicies,
+            extraction_policy_id,
+        )?
+        else {
+            return Ok(());
+        };
+        let source = internal_api::ExtractionPolicyContentSource::ExtractionPolicyName(
+            extraction_policy.name,
+        );
+        let policies: Vec<String> = self
+            .get_extraction_policies_from_ids(self.extraction_policies_table.get(namespace), db)?
+            .found
+            .into_iter()
+            .filter(|policy| {
+                policy.graph_name == extraction_policy.graph_name && policy.content_source == source
+            })
+            .map(|policy| policy.name)
+            .sorted()
+            .collect();
+        if !policies.is_empty() {
+            return Err(StateMachineError::ExtractionPolicyInUse {
+                extraction_policy_id: extraction_policy_id.clone(),
+                policies,
+            });
+        }
+        Ok(())
+    }
+
+    /// Rejects requests which write a schema with an invalid id, before
+    /// anything is committed
+    fn validate_schema_ids(&self, payload: &RequestPayload) -> Result<(), StateMachineError> {
+        let schema = match payload {
+            RequestPayload::CreateExtractionGraph {
+                structured_data_schema,
+                ..
+            } => Some(structured_data_schema),
+            RequestPayload::CreateNamespace {
+                structured_data_schema,
+                ..
+            } => structured_data_schema.as_ref(),
+            _ => None,
+        };
+        if let Some(schema) = schema {
+            SchemaId::new(&schema.id)?;
+        }
+        Ok(())
+    }
+
+    /// Rejects aliases with the name of a namespace and namespaces with the
+    /// name of an alias, so a name always resolves to a single namespace
+    fn validate_namespace_aliases(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        match payload {
+            RequestPayload::SetNamespaceAlias { alias, .. } => {
+                if alias.is_empty() {
+                    return Err(StateMachineError::InvalidNamespaceAlias {
+                        alias: alias.clone(),
+                        reason: "the alias is empty",
+                    });
+                }
+                let namespace_exists = db
+                    .get_cf(StateMachineColumns::Namespaces.cf(db), alias)
+                    .map_err(|e| {
+                        StateMachineError::DatabaseError(format!("Error reading namespace: {}", e))
+                    })?
+                    .is_some();
+                if namespace_exists {
+                    return Err(StateMachineError::InvalidNamespaceAlias {
+                        alias: alias.clone(),
+                        reason: "a namespace with the name exists",
+                    });
+                }
+            }
+            RequestPayload::CreateNamespace { name, .. } => {
+                if self.get_namespace_alias(name, db)?.is_some() {
+                    return Err(StateMachineError::InvalidNamespaceAlias {
+                        alias: name.clone(),
+                        reason: "a namespace can't be created with the name of an alias",
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether content with the ingestion key of the request was already
+    /// created. Expiry is checked against the time the request was made, so
+    /// that every node decides the same.
+    fn is_ingestion_key_replay(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<bool, StateMachineError> {
+        let RequestPayload::CreateOrUpdateContent {
+            ingestion_key: Some(record),
+            ..
+        } = payload
+        else {
+            return Ok(false);
+        };
+        Ok(self
+            .get_ingestion_key(&record.namespace, &record.key, db)?
+            .map_or(false, |existing| !existing.expired(record.created_at)))
+    }
+
+    /// Rejects content updates which change the id or the parent id of stored
+    /// content. Only the versions of either may differ, unless the entry
+    /// explicitly moves the content away from its stored parent through
+    /// `previous_parent`, as deduplication against a previous tree does.
+    fn validate_content_updates(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let RequestPayload::CreateOrUpdateContent { entries, .. } = payload else {
+            return Ok(());
+        };
+        for entry in entries {
+            let content = &entry.content;
+            let existing = db
+                .get_cf(
+                    StateMachineColumns::ContentTable.cf(db),
+                    content.id_key(&self.content_version_separator),
+                )
+                .map_err(|e| {
+                    StateMachineError::DatabaseError(format!("Error reading content: {}", e))
+                })?;
+            let Some(existing) = existing else {
+                continue;
+            };
+            let existing = JsonEncoder::decode::<internal_api::ContentMetadata>(&existing)?;
+            if existing.id.id != content.id.id {
+                return Err(StateMachineError::ImmutableContentField {
+                    content_id: existing.id.id,
+                    field: "id",
+                });
+            }
+            let reparented =
+                entry.previous_parent.is_some() && entry.previous_parent == existing.parent_id;
+            if !reparented &&
+                existing.parent_id.as_ref().map(|p| &p.id) !=
+                    content.parent_id.as_ref().map(|p| &p.id)
+            {
+                return Err(StateMachineError::ImmutableContentField {
+                    content_id: existing.id.id,
+                    field: "parent_id",
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects content whose parent is in another namespace, content trees
+    /// are expected to be within a single namespace. The parent is resolved
+    /// from the same request first, then from the stored content. Content
+    /// whose parent can't be found is left to the other checks.
+    fn validate_content_namespaces(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        payload: &RequestPayload,
+    ) -> Result<(), StateMachineError> {
+        let RequestPayload::CreateOrUpdateContent { entries, .. } = payload else {
+            return Ok(());
+        };
+        let txn = db.transaction();
+        for entry in entries {
+            let content = &entry.content;
+            let Some(parent_id) = &content.parent_id else {
+                continue;
+            };
+            let parent_namespace = match entries
+                .iter()
+                .find(|parent| parent.content.id.id == parent_id.id)
+            {
+                Some(parent) => Some(parent.content.namespace.clone()),
+                None => match self.get_latest_version_of_content(&parent_id.id, db, &txn)? {
+                    Some(parent) => Some(parent.namespace),
+                    None => self
+                        .get_content_by_id_and_version(db, parent_id)?
+                        .map(|parent| parent.namespace),
+                },
+            };
+            if let Some(parent_namespace) = parent_namespace {
+                if parent_namespace != content.namespace {
+                    return Err(StateMachineError::ContentNamespaceMismatch {
+                        content_id: content.id.id.clone(),
+                        namespace: content.namespace.clone(),
+                        parent_id: parent_id.id.clone(),
+                        parent_namespace,
+                    });
+                }
+            }
+        }
+        Ok(())
     }
 
     /// This method will make all state machine forward index writes to RocksDB
@@ -1237,13 +3063,55 @@ impl IndexifyState {
         &self,
         mut request: StateMachineUpdateRequest,
         db: &Arc<OptimisticTransactionDB>,
-    ) -> Result<Vec<StateChange>, StateMachineError> {
+    ) -> Result<AppliedUpdate, StateMachineError> {
+        let _write_guard = self.lock_writes();
+        if let Some(key) = &request.idempotency_key {
+            if let Some(record) = self.get_idempotency_record(db, key)? {
+                return Ok(record.result);
+            }
+        }
+        if self.is_ingestion_key_replay(db, &request.payload)? {
+            return Ok(AppliedUpdate::default());
+        }
+        self.validate_namespaces(db, &request.payload)?;
+        self.validate_content_updates(db, &request.payload)?;
+        self.validate_content_namespaces(db, &request.payload)?;
+        self.validate_schema_deletion(db, &request.payload)?;
+        self.validate_extraction_policy_deletion(db, &request.payload)?;
+        self.validate_extractor_update(db, &request.payload)?;
+        self.validate_extractor_rename(db, &request.payload)?;
+        self.validate_namespace_aliases(db, &request.payload)?;
+        self.validate_schema_ids(&request.payload)?;
+
         let txn = db.transaction();
 
         self.set_new_state_changes(db, &txn, &mut request.new_state_changes)?;
         let mut state_changes_processed =
             self.set_processed_state_changes(db, &txn, &request.state_changes_processed)?;
 
+        let mut written_content = Vec::new();
+        //  Root task counts are in memory, they are changed once the transaction
+        //  is committed so that a failed apply leaves them untouched
+        let mut referenced_roots: Vec<String> = request
+            .new_state_changes
+            .iter()
+            .filter_map(|change| change.refcnt_object_id.clone())
+            .collect();
+        let mut released_roots = Vec::new();
+        //  Executor removed or replaced by a registration with the same id, and
+        //  its tasks which go back to the unassigned tasks
+        let mut removed_executor = None;
+        let mut reclaimed_tasks = Vec::new();
+        //  Tasks moved back to the unassigned tasks, with the executor they were
+        //  assigned to
+        let mut requeued_tasks = Vec::new();
+        //  Task moved to another state of its lifecycle, with the states it moved
+        //  from and to
+        let mut transitioned_task = None;
+        let mut cancelled_tasks = Vec::new();
+        let mut reprocessed_change = None;
+        //  Extraction policy ids removed from the reverse index of a namespace
+        let mut dangling_policies = None;
         match &request.payload {
             RequestPayload::SetIndex { indexes } => {
                 for index in indexes {
@@ -1253,7 +3121,31 @@ impl IndexifyState {
             RequestPayload::CreateTasks { tasks } => {
                 self.set_tasks(db, &txn, tasks)?;
                 for task in tasks {
-                    self.inc_root_ref_count(task.content_metadata.get_root_id());
+                    referenced_roots.push(task.content_metadata.get_root_id().to_string());
+                }
+            }
+            RequestPayload::CancelTasks { task_ids } => {
+                cancelled_tasks = self.cancel_unassigned_tasks(db, &txn, task_ids)?;
+                for task in &cancelled_tasks {
+                    released_roots.push(task.content_metadata.get_root_id().to_string());
+                }
+            }
+            RequestPayload::RebuildContentTree {
+                extraction_policy_id,
+                content_ids,
+                tasks,
+            } => {
+                for content_id in content_ids {
+                    self.clear_content_extraction_policy_state(
+                        db,
+                        &txn,
+                        content_id,
+                        extraction_policy_id,
+                    )?;
+                }
+                self.set_tasks(db, &txn, tasks)?;
+                for task in tasks {
+                    referenced_roots.push(task.content_metadata.get_root_id().to_string());
                 }
             }
             RequestPayload::CreateOrAssignGarbageCollectionTask { gc_tasks } => {
@@ -1266,7 +3158,11 @@ impl IndexifyState {
                 if *mark_finished {
                     tracing::info!("Marking garbage collection task as finished: {:?}", gc_task);
                     self.update_garbage_collection_tasks(db, &txn, &vec![gc_task])?;
+                    fail_point!(self, failpoints::GC_MARK_FINISHED);
                     self.delete_content(db, &txn, vec![gc_task.content_id.clone()])?;
+                } else {
+                    //  Progress of a task which is resumed
+                    self.update_garbage_collection_tasks(db, &txn, &vec![gc_task])?;
                 }
             }
             RequestPayload::AssignTask { assignments } => {
@@ -1295,6 +3191,11 @@ impl IndexifyState {
                 executor_id,
                 update_time,
             } => {
+                //  Read before the task is overwritten, so that replays of a finished task
+                //  are only counted once
+                if task.terminal_state() {
+                    self.record_task_usage(db, &txn, task, *update_time)?;
+                }
                 self.update_tasks(db, &txn, vec![task], *update_time)?;
 
                 if task.terminal_state() {
@@ -1313,7 +3214,7 @@ impl IndexifyState {
                             HashMap::from([(executor_id.to_string(), existing_tasks)]);
                         self.set_task_assignments(db, &txn, &new_task_assignment)?;
                     }
-                    self.dec_root_ref_count(task.content_metadata.get_root_id());
+                    released_roots.push(task.content_metadata.get_root_id().to_string());
                 }
             }
             RequestPayload::RegisterExecutor {
@@ -1321,98 +3222,485 @@ impl IndexifyState {
                 executor_id,
                 extractors,
                 ts_secs,
+                capabilities,
             } => {
+                //  A registration is a fresh start of the executor. When the id is reused
+                //  before the previous registration was removed, the tasks assigned to the
+                //  previous registration won't be finished by the restarted executor.
+                removed_executor = self.get_executor(db, &txn, executor_id)?;
+                if removed_executor.is_some() {
+                    reclaimed_tasks =
+                        self.delete_task_assignments_for_executor(db, &txn, executor_id)?;
+                }
+
                 //  Insert the executor
-                self.set_executor(db, &txn, addr.into(), executor_id, extractors, ts_secs)?;
+                self.set_executor(
+                    db,
+                    &txn,
+                    addr.into(),
+                    executor_id,
+                    extractors,
+                    ts_secs,
+                    capabilities,
+                )?;
 
                 //  Insert the associated extractors
                 self.set_extractors(db, &txn, extractors)?;
             }
+            RequestPayload::UpdateExecutorExtractor {
+                executor_id,
+                extractor,
+            } => {
+                //  Task assignments and running task counts are left untouched
+                self.update_executor_extractor(db, &txn, executor_id, extractor)?;
+            }
+            RequestPayload::RenameExtractor { old_name, new_name } => {
+                self.rename_extractor(db, &txn, old_name, new_name)?;
+            }
             RequestPayload::RemoveExecutor { executor_id } => {
-                //  NOTE: Special case where forward and reverse indexes are updated together
+                //  Get a handle on the executor and its tasks before deleting them, the
+                //  reverse indexes are updated from them once the transaction is committed
+                removed_executor = self.delete_executor(db, &txn, executor_id)?;
+                reclaimed_tasks =
+                    self.delete_task_assignments_for_executor(db, &txn, executor_id)?;
+            }
+            RequestPayload::RequeueTasksForExtractor { extractor } => {
+                requeued_tasks = self.requeue_tasks_for_extractor(db, &txn, extractor)?;
+            }
+            RequestPayload::TransitionTask {
+                task_id,
+                to,
+                update_time,
+            } => {
+                let (task, from) = self.transition_task(db, &txn, task_id, to, *update_time)?;
+                transitioned_task = Some((task, from, to.clone()));
+            }
+            RequestPayload::CreateOrUpdateContent {
+                entries,
+                ingestion_key,
+            } => {
+                written_content = self.set_content(db, &txn, entries.iter().map(|e| &e.content))?;
+                if let Some(record) = ingestion_key {
+                    self.set_ingestion_key(db, &txn, record)?;
+                }
+            }
+            RequestPayload::TombstoneContentTree {
+                content_metadata,
+                reason,
+            } => {
+                self.tombstone_content_tree(db, &txn, content_metadata, reason)?;
+            }
+            RequestPayload::CreateNamespace {
+                name,
+                structured_data_schema,
+            } => {
+                self.set_namespace(db, &txn, name)?;
+                self.set_namespace_schema(db, &txn, name, structured_data_schema.as_ref())?;
+            }
+            RequestPayload::SetSearchProfile { profile } => {
+                self.set_search_profile(db, &txn, profile)?;
+            }
+            RequestPayload::DeleteSearchProfile { namespace, name } => {
+                self.delete_search_profile(db, &txn, namespace, name)?;
+            }
+            RequestPayload::CreateSearchFeedback { feedback } => {
+                self.set_search_feedback(db, &txn, feedback)?;
+            }
+            RequestPayload::SetNamespaceAlias { alias, namespace } => {
+                self.set_namespace_alias(db, &txn, alias, namespace)?;
+            }
+            RequestPayload::CreateSubscription { subscription } => {
+                self.set_subscription(db, &txn, subscription)?;
+            }
+            RequestPayload::SetNamespaceRetention { namespace, rules } => {
+                self.set_namespace_retention(db, &txn, namespace, rules)?;
+            }
+            RequestPayload::DeleteSubscription { subscription_id } => {
+                self.delete_subscription(db, &txn, subscription_id)?;
+            }
+            RequestPayload::EnqueueDeliveries { deliveries } => {
+                self.enqueue_deliveries(db, &txn, deliveries)?;
+            }
+            RequestPayload::AckDelivery {
+                subscription_id,
+                sequence,
+            } => {
+                txn.delete_cf(
+                    StateMachineColumns::PendingDeliveries.cf(db),
+                    internal_api::Delivery::key_from(subscription_id, *sequence),
+                )
+                .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            }
+            RequestPayload::RetryDelivery { delivery } => {
+                self.update_pending_delivery(db, &txn, delivery)?;
+            }
+            RequestPayload::DeadLetterDelivery { delivery } => {
+                self.dead_letter_delivery(db, &txn, delivery)?;
+            }
+            RequestPayload::RequeueDeadLetters { subscription_id } => {
+                self.requeue_dead_letters(db, &txn, subscription_id)?;
+            }
+            RequestPayload::DeleteSchema {
+                namespace: _,
+                schema_id,
+            } => {
+                self.delete_schema(db, &txn, schema_id)?;
+            }
+            RequestPayload::MarkStateChangesProcessed { state_changes } => {
+                let payload_changes_processed =
+                    self.set_processed_state_changes(db, &txn, state_changes)?;
+                state_changes_processed.extend(payload_changes_processed);
+            }
+            RequestPayload::ReprocessStateChange { state_change_id } => {
+                let state_change = self.reset_state_change(db, &txn, state_change_id)?;
+                //  The reference to the root was released when the change was
+                //  processed, it is held again until the change is processed
+                if !self.unprocessed_state_changes.contains(state_change_id) {
+                    referenced_roots.extend(state_change.refcnt_object_id.clone());
+                }
+                reprocessed_change = Some(state_change);
+            }
+            RequestPayload::JoinCluster {
+                node_id,
+                address: _,
+                coordinator_addr,
+            } => {
+                self.set_coordinator_addr(db, &txn, *node_id, coordinator_addr)?;
+            }
+            RequestPayload::CreateExtractionGraph {
+                extraction_graph,
+                structured_data_schema,
+                indexes,
+            } => {
+                self.set_extraction_graph(db, &txn, extraction_graph, structured_data_schema)?;
+                for index in indexes {
+                    self.set_index(db, &txn, index, &index.id)?;
+                }
+            }
+            RequestPayload::UpdateExtractionPolicyParams {
+                extraction_policy_id,
+                input_params,
+            } => {
+                self.update_extraction_policy_params(db, &txn, extraction_policy_id, input_params)?;
+            }
+            RequestPayload::DeleteExtractionPolicy {
+                namespace,
+                extraction_policy_id,
+            } => {
+                self.delete_extraction_policy(db, &txn, namespace, extraction_policy_id)?;
+            }
+            RequestPayload::RemoveDanglingExtractionPolicies {
+                namespace,
+                extraction_policy_ids,
+            } => {
+                let lookup = self.get_extraction_policies_from_ids_in_txn(
+                    extraction_policy_ids.clone(),
+                    &txn,
+                    db,
+                )?;
+                dangling_policies = Some((namespace.clone(), lookup.missing));
+            }
+        };
 
-                //  Get a handle on the executor before deleting it from the DB
-                let executor_meta = self.delete_executor(db, &txn, executor_id)?;
+        self.set_idempotency_record(
+            db,
+            &txn,
+            request.idempotency_key.as_deref(),
+            &AppliedUpdate {
+                new_state_changes: request.new_state_changes.clone(),
+                written_content: written_content.clone(),
+            },
+        )?;
+        fail_point!(self, failpoints::APPLY_BEFORE_COMMIT);
+        txn.commit()
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+        fail_point!(self, failpoints::APPLY_AFTER_COMMIT);
 
-                // Remove all tasks assigned to this executor and get a handle on the task ids
-                let task_ids = self.delete_task_assignments_for_executor(db, &txn, executor_id)?;
+        for root in &referenced_roots {
+            self.inc_root_ref_count(root);
+        }
+        for root in &released_roots {
+            self.dec_root_ref_count(root);
+        }
+        if let Some(executor) = removed_executor {
+            for extractor in &executor.extractors {
+                self.extractor_executors_table
+                    .remove(&extractor.name, &executor.id);
+            }
+        }
+        for task_id in reclaimed_tasks {
+            self.unassigned_tasks.insert(&task_id);
+        }
+        for (executor_id, task_id) in requeued_tasks {
+            self.unassigned_tasks.insert(&task_id);
+            self.executor_running_task_count
+                .decrement_running_task_count(&executor_id);
+        }
+        if let Some((task, from, to)) = transitioned_task {
+            self.update_transitioned_task_indexes(&task, &from, &to);
+        }
+        if let Some((namespace, extraction_policy_ids)) = dangling_policies {
+            for id in &extraction_policy_ids {
+                self.extraction_policies_table.remove(&namespace, id);
+            }
+        }
+        for task in cancelled_tasks {
+            self.unassigned_tasks.remove(&task.id);
+            self.unfinished_tasks_by_extractor
+                .remove(&task.extractor, &task.id);
+            self.pending_tasks_for_content.remove(
+                &task.content_metadata.id,
+                &task.extraction_policy_id,
+                &task.id,
+            );
+        }
+        let unprocessed_changes = self.get_unprocessed_state_changes();
+        for state_change in state_changes_processed {
+            if unprocessed_changes.contains(&state_change.id) {
+                if let Some(refcnt_object_id) = &state_change.refcnt_object_id {
+                    self.dec_root_ref_count(&refcnt_object_id);
+                }
+            }
+        }
 
-                txn.commit()
-                    .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+        //  The reprocessed change is published like a new one to wake up the
+        //  scheduler
+        let mut new_state_changes = request.new_state_changes.clone();
+        new_state_changes.extend(reprocessed_change);
 
-                //  Remove the extractors from the executor -> extractor mapping table
-                if let Some(executor_meta) = executor_meta {
-                    for extractor in &executor_meta.extractors {
-                        self.extractor_executors_table
-                            .remove(&extractor.name, &executor_meta.id);
-                    }
-                }
+        self.update_reverse_indexes(request).map_err(|e| {
+            StateMachineError::ExternalError(anyhow!(
+                "Error while applying reverse index updates: {}",
+                e
+            ))
+        })?;
+        fail_point!(self, failpoints::APPLY_AFTER_REVERSE_INDEXES);
+
+        Ok(AppliedUpdate {
+            new_state_changes,
+            written_content,
+        })
+    }
 
-                //  Put the tasks of the deleted executor into the unassigned tasks list
-                for task_id in task_ids {
-                    self.unassigned_tasks.insert(&task_id);
+    /// Moves every unfinished task of an extractor which is assigned to an
+    /// executor back into the unassigned queue. Returns the requeued tasks
+    /// with the executor each was assigned to, the reverse indexes are
+    /// updated from them once the transaction is committed.
+    fn requeue_tasks_for_extractor(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        extractor: &str,
+    ) -> Result<Vec<(ExecutorId, TaskId)>, StateMachineError> {
+        //  Requeued tasks are still unfinished, the index is only read
+        let task_ids = self.unfinished_tasks_by_extractor.get(extractor);
+        let mut requeued = Vec::new();
+        if task_ids.is_empty() {
+            return Ok(requeued);
+        }
+
+        let executor_ids: HashSet<ExecutorId> = self
+            .get_all_task_assignments(db)?
+            .into_iter()
+            .filter(|(task_id, _)| task_ids.contains(task_id))
+            .map(|(_, executor_id)| executor_id)
+            .collect();
+        let task_assignment_cf = StateMachineColumns::TaskAssignments.cf(db);
+        for executor_id in executor_ids {
+            let mut assigned = self.get_task_assignments_for_executor(db, txn, &executor_id)?;
+            assigned.retain(|task_id| {
+                if task_ids.contains(task_id) {
+                    requeued.push((executor_id.clone(), task_id.clone()));
+                    return false;
                 }
+                true
+            });
+            if assigned.is_empty() {
+                txn.delete_cf(task_assignment_cf, &executor_id)
+                    .map_err(|e| {
+                        StateMachineError::DatabaseError(format!(
+                            "Error deleting task assignments for executor: {}",
+                            e
+                        ))
+                    })?;
+            } else {
+                let task_assignment = HashMap::from([(executor_id.clone(), assigned)]);
+                self.set_task_assignments(db, txn, &task_assignment)?;
+            }
+        }
+        Ok(requeued)
+    }
 
-                // Remove from the executor load table
-                self.executor_running_task_count.remove(executor_id);
+    /// Returns the lifecycle state of a task, derived from its outcome and
+    /// the task assignments. Tasks are only assigned to executors running
+    /// their extractor, so only the assignments of those executors are read.
+    fn task_state(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        task: &internal_api::Task,
+    ) -> Result<TaskLifecycleState, StateMachineError> {
+        if task.terminal_state() {
+            return Ok(TaskLifecycleState::Finished {
+                outcome: task.outcome,
+            });
+        }
+        for executor_id in self.extractor_executors_table.get(&task.extractor) {
+            if self
+                .get_task_assignments_for_executor(db, txn, &executor_id)?
+                .contains(&task.id)
+            {
+                return Ok(TaskLifecycleState::Assigned { executor_id });
+            }
+        }
+        Ok(TaskLifecycleState::Unassigned)
+    }
 
-                return Ok(request.new_state_changes);
+    /// Deletes the tasks which are still waiting for an executor and clears
+    /// their extraction policy from the content version they were created
+    /// for. Returns the deleted tasks.
+    fn cancel_unassigned_tasks(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        task_ids: &[TaskId],
+    ) -> Result<Vec<internal_api::Task>, StateMachineError> {
+        let mut cancelled = Vec::new();
+        for task_id in task_ids {
+            let Some(task) =
+                self.get_from_cf::<internal_api::Task, _>(db, StateMachineColumns::Tasks, task_id)?
+            else {
+                continue;
+            };
+            if self.task_state(db, txn, &task)? != TaskLifecycleState::Unassigned {
+                continue;
             }
-            RequestPayload::CreateOrUpdateContent { entries } => {
-                self.set_content(db, &txn, entries.iter().map(|e| &e.content))?;
+            txn.delete_cf(StateMachineColumns::Tasks.cf(db), task_id)
+                .map_err(|e| {
+                    StateMachineError::DatabaseError(format!("Error deleting task: {}", e))
+                })?;
+            let content_id = &task.content_metadata.id;
+            let content_key = match self.get_latest_version_of_content(&content_id.id, db, txn)? {
+                Some(latest) if latest.id.version == content_id.version => content_id.id.clone(),
+                _ => self.content_version_separator.key(content_id),
+            };
+            self.clear_content_extraction_policy_state(
+                db,
+                txn,
+                &content_key,
+                &task.extraction_policy_id,
+            )?;
+            cancelled.push(task);
+        }
+        Ok(cancelled)
+    }
+
+    /// Moves a task to another state of its lifecycle. The task and the task
+    /// assignments are written to the transaction, and transitions which
+    /// aren't part of the lifecycle, such as finished to assigned, are
+    /// rejected. Returns the task and the state it moved from, the reverse
+    /// indexes are updated from them once the transaction is committed.
+    fn transition_task(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        task_id: &str,
+        to: &TaskLifecycleState,
+        update_time: SystemTime,
+    ) -> Result<(internal_api::Task, TaskLifecycleState), StateMachineError> {
+        let mut task: internal_api::Task = self
+            .get_from_cf(db, StateMachineColumns::Tasks, task_id)?
+            .ok_or_else(|| StateMachineError::TaskNotFound(task_id.to_string()))?;
+        let from = self.task_state(db, txn, &task)?;
+        let invalid_transition = |to: String| StateMachineError::InvalidTaskTransition {
+            task_id: task_id.to_string(),
+            from: format!("{:?}", from),
+            to,
+        };
+        if !from.can_transition_to(to) {
+            return Err(invalid_transition(format!("{:?}", to)));
+        }
+        if let TaskLifecycleState::Assigned { executor_id } = to {
+            if !self
+                .extractor_executors_table
+                .contains(&task.extractor, executor_id)
+            {
+                return Err(invalid_transition(format!(
+                    "{:?}, an executor which doesn't run {}",
+                    to, task.extractor
+                )));
             }
-            RequestPayload::TombstoneContentTree { content_metadata } => {
-                self.tombstone_content_tree(db, &txn, content_metadata)?;
+        }
+
+        let task_assignment_cf = StateMachineColumns::TaskAssignments.cf(db);
+        if let TaskLifecycleState::Assigned { executor_id } = &from {
+            let mut assigned = self.get_task_assignments_for_executor(db, txn, executor_id)?;
+            assigned.remove(task_id);
+            if assigned.is_empty() {
+                txn.delete_cf(task_assignment_cf, executor_id)
+                    .map_err(|e| {
+                        StateMachineError::DatabaseError(format!(
+                            "Error deleting task assignments for executor: {}",
+                            e
+                        ))
+                    })?;
+            } else {
+                let task_assignment = HashMap::from([(executor_id.clone(), assigned)]);
+                self.set_task_assignments(db, txn, &task_assignment)?;
             }
-            RequestPayload::CreateNamespace { name } => {
-                self.set_namespace(db, &txn, name)?;
+        }
+        match to {
+            TaskLifecycleState::Assigned { executor_id } => {
+                let mut assigned = self.get_task_assignments_for_executor(db, txn, executor_id)?;
+                assigned.insert(task_id.to_string());
+                let task_assignment = HashMap::from([(executor_id.clone(), assigned)]);
+                self.set_task_assignments(db, txn, &task_assignment)?;
             }
-            RequestPayload::MarkStateChangesProcessed { state_changes } => {
-                let payload_changes_processed =
-                    self.set_processed_state_changes(db, &txn, state_changes)?;
-                state_changes_processed.extend(payload_changes_processed);
+            TaskLifecycleState::Finished { outcome } => {
+                task.outcome = *outcome;
+                self.update_tasks(db, txn, vec![&task], update_time)?;
             }
-            RequestPayload::JoinCluster {
-                node_id,
-                address: _,
-                coordinator_addr,
-            } => {
-                self.set_coordinator_addr(db, &txn, *node_id, coordinator_addr)?;
+            TaskLifecycleState::Unassigned => {}
+        }
+        Ok((task, from))
+    }
+
+    /// Updates the reverse indexes of a task moved to another state of its
+    /// lifecycle, once the move is committed
+    fn update_transitioned_task_indexes(
+        &self,
+        task: &internal_api::Task,
+        from: &TaskLifecycleState,
+        to: &TaskLifecycleState,
+    ) {
+        if let TaskLifecycleState::Assigned { executor_id } = from {
+            self.executor_running_task_count
+                .decrement_running_task_count(executor_id);
+        }
+        match to {
+            TaskLifecycleState::Unassigned => {
+                self.unassigned_tasks.insert(&task.id);
             }
-            RequestPayload::CreateExtractionGraph {
-                extraction_graph,
-                structured_data_schema,
-                indexes,
-            } => {
-                self.set_extraction_graph(db, &txn, extraction_graph, structured_data_schema)?;
-                for index in indexes {
-                    self.set_index(db, &txn, index, &index.id)?;
-                }
+            TaskLifecycleState::Assigned { executor_id } => {
+                self.unassigned_tasks.remove(&task.id);
+                self.executor_running_task_count
+                    .increment_running_task_count(executor_id);
             }
-        };
-
-        let unprocessed_changes = self.get_unprocessed_state_changes();
-        for state_change in state_changes_processed {
-            if unprocessed_changes.contains(&state_change.id) {
-                if let Some(refcnt_object_id) = &state_change.refcnt_object_id {
-                    self.dec_root_ref_count(&refcnt_object_id);
-                }
+            TaskLifecycleState::Finished { outcome } => {
+                self.metrics
+                    .lock()
+                    .unwrap()
+                    .update_task_completion(*outcome);
+                self.unfinished_tasks_by_extractor
+                    .remove(&task.extractor, &task.id);
+                self.pending_tasks_for_content.remove(
+                    &task.content_metadata.id,
+                    &task.extraction_policy_id,
+                    &task.id,
+                );
+                self.dec_root_ref_count(task.content_metadata.get_root_id());
             }
         }
-
-        let new_state_changes = request.new_state_changes.clone();
-
-        self.update_reverse_indexes(request).map_err(|e| {
-            StateMachineError::ExternalError(anyhow!(
-                "Error while applying reverse index updates: {}",
-                e
-            ))
-        })?;
-
-        txn.commit()
-            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
-
-        Ok(new_state_changes)
     }
 
     /// This method handles all reverse index writes. All reverse indexes are
@@ -1421,15 +3709,14 @@ impl IndexifyState {
         for change in request.new_state_changes {
             self.unprocessed_state_changes.insert(change.id);
         }
-        for change in request.state_changes_processed {
-            self.mark_state_changes_processed(&change, change.processed_at);
-        }
+        self.mark_state_changes_processed(&request.state_changes_processed);
         match request.payload {
             RequestPayload::RegisterExecutor {
                 addr,
                 executor_id,
                 extractors,
                 ts_secs,
+                capabilities,
             } => {
                 // Inserts the executor list of extractors to the executor -> extractor mapping
                 // table
@@ -1443,12 +3730,25 @@ impl IndexifyState {
                     last_seen: ts_secs,
                     addr: addr.clone(),
                     extractors: extractors.clone(),
+                    capabilities: capabilities.clone(),
                 };
-                // initialize executor load at 0
+                //  The load of a registered executor starts at 0, the tasks assigned to a
+                //  previous registration with the same id were unassigned
                 self.executor_running_task_count.insert(&executor_id, 0);
                 Ok(())
             }
-            RequestPayload::CreateTasks { tasks } => {
+            RequestPayload::RemoveExecutor { executor_id } => {
+                self.executor_running_task_count.remove(&executor_id);
+                Ok(())
+            }
+            RequestPayload::RenameExtractor { old_name, new_name } => {
+                self.extractor_executors_table.rename(&old_name, &new_name);
+                self.unfinished_tasks_by_extractor
+                    .rename(&old_name, &new_name);
+                Ok(())
+            }
+            RequestPayload::CreateTasks { tasks } |
+            RequestPayload::RebuildContentTree { tasks, .. } => {
                 for task in tasks {
                     self.unassigned_tasks.insert(&task.id);
                     self.unfinished_tasks_by_extractor
@@ -1470,6 +3770,7 @@ impl IndexifyState {
                 }
                 Ok(())
             }
+            RequestPayload::RequeueTasksForExtractor { extractor: _ } => Ok(()),
             RequestPayload::CreateOrAssignGarbageCollectionTask { gc_tasks: _ } => Ok(()),
             RequestPayload::UpdateGarbageCollectionTask {
                 gc_task,
@@ -1477,13 +3778,22 @@ impl IndexifyState {
             } => {
                 if mark_finished && gc_task.task_type == ServerTaskType::Delete {
                     self.content_children_table.remove_all(&gc_task.content_id);
+                    if gc_task.latest {
+                        self.content_by_source
+                            .remove(&gc_task.namespace, &gc_task.content_id.id);
+                    }
                 }
                 Ok(())
             }
-            RequestPayload::CreateOrUpdateContent { entries } => {
+            RequestPayload::CreateOrUpdateContent { entries, .. } => {
                 for entry in entries {
                     self.content_namespace_table
                         .insert(&entry.content.namespace, &entry.content.id);
+                    self.content_by_source.insert(
+                        &entry.content.namespace,
+                        &String::from(&entry.content.source),
+                        &entry.content.id.id,
+                    );
                     let mut guard = self.metrics.lock().unwrap();
                     if let Some(prev_parent) = entry.previous_parent {
                         self.content_children_table
@@ -1506,14 +3816,38 @@ impl IndexifyState {
                 structured_data_schema,
                 indexes,
             } => {
-                self.update_extraction_graph_reverse_idx(&extraction_graph, structured_data_schema);
+                self.update_extraction_graph_reverse_idx(
+                    &extraction_graph,
+                    structured_data_schema,
+                )?;
                 for index in indexes {
                     self.namespace_index_table
                         .insert(&index.namespace, &index.id);
                 }
                 Ok(())
             }
-            RequestPayload::CreateNamespace { name: _ } => Ok(()),
+            RequestPayload::DeleteSchema {
+                namespace,
+                schema_id,
+            } => {
+                //  Schemas with invalid ids are never written, so there's
+                //  nothing to remove for them
+                if let Ok(schema_id) = SchemaId::new(&schema_id) {
+                    self.schemas_by_namespace.remove(&namespace, &schema_id);
+                }
+                Ok(())
+            }
+            RequestPayload::CreateNamespace {
+                name,
+                structured_data_schema,
+            } => {
+                let schema_id = structured_data_schema
+                    .map(|schema| schema.id)
+                    .unwrap_or_else(|| internal_api::StructuredDataSchema::empty_for(&name).id);
+                self.schemas_by_namespace
+                    .insert(&name, &SchemaId::new_unchecked(&schema_id));
+                Ok(())
+            }
             RequestPayload::UpdateTask {
                 task,
                 executor_id,
@@ -1537,9 +3871,19 @@ impl IndexifyState {
                 Ok(())
             }
             RequestPayload::MarkStateChangesProcessed { state_changes } => {
-                for state_change in state_changes {
-                    self.mark_state_changes_processed(&state_change, state_change.processed_at);
-                }
+                self.mark_state_changes_processed(&state_changes);
+                Ok(())
+            }
+            RequestPayload::ReprocessStateChange { state_change_id } => {
+                self.unprocessed_state_changes.insert(state_change_id);
+                Ok(())
+            }
+            RequestPayload::DeleteExtractionPolicy {
+                namespace,
+                extraction_policy_id,
+            } => {
+                self.extraction_policies_table
+                    .remove(&namespace, &extraction_policy_id);
                 Ok(())
             }
             _ => Ok(()),
@@ -1560,6 +3904,194 @@ impl IndexifyState {
             .transpose()
     }
 
+    /// Returns the extraction policies applied on the latest version of the
+    /// content along with their completion times
+    pub fn get_latest_policy_mapping(
+        &self,
+        content_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<HashMap<ExtractionPolicyId, u64>>, StateMachineError> {
+        let txn = db.transaction();
+        Ok(self
+            .get_latest_version_of_content(content_id, db, &txn)?
+            .map(|content| content.extraction_policy_ids))
+    }
+
+    /// Returns the extraction policies applied on the latest versions of the
+    /// contents, read with batched multi gets. Content which doesn't exist or
+    /// has no policies applied is left out.
+    pub fn get_policy_mappings_for_contents(
+        &self,
+        content_ids: HashSet<String>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<HashMap<String, HashMap<ExtractionPolicyId, u64>>, StateMachineError> {
+        let txn = db.transaction();
+        let content_ids: Vec<String> = content_ids.into_iter().collect();
+        let contents = self.multi_get_in_txn::<internal_api::ContentMetadata>(
+            StateMachineColumns::ContentTable,
+            &content_ids,
+            &txn,
+            db,
+        )?;
+        Ok(content_ids
+            .into_iter()
+            .zip(contents)
+            .filter_map(|(content_id, content)| {
+                content
+                    .map(|content| content.extraction_policy_ids)
+                    .filter(|mapping| !mapping.is_empty())
+                    .map(|mapping| (content_id, mapping))
+            })
+            .collect())
+    }
+
+    /// Deletes the expired idempotency keys and returns the number of keys
+    /// deleted
+    pub fn compact_idempotency_keys(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<usize, StateMachineError> {
+        let now = timestamp_secs();
+        self.compact_expired_keys(db, StateMachineColumns::IdempotencyKeys, |value| {
+            Ok(JsonEncoder::decode::<IdempotencyRecord>(value)?.expired(now))
+        })
+    }
+
+    /// Deletes the expired ingestion keys and returns the number of keys
+    /// deleted
+    pub fn compact_ingestion_keys(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<usize, StateMachineError> {
+        let now = timestamp_secs();
+        self.compact_expired_keys(db, StateMachineColumns::IngestionKeys, |value| {
+            Ok(JsonEncoder::decode::<IngestionKeyRecord>(value)?.expired(now))
+        })
+    }
+
+    fn compact_expired_keys(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        column: StateMachineColumns,
+        expired: impl Fn(&[u8]) -> Result<bool, StateMachineError>,
+    ) -> Result<usize, StateMachineError> {
+        let cf = column.cf(db);
+        let txn = db.transaction();
+        let mut deleted = 0;
+        for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item.map_err(|e| {
+                StateMachineError::DatabaseError(format!("unable to read idempotency key: {}", e))
+            })?;
+            if expired(&value)? {
+                txn.delete_cf(cf, &key).map_err(|e| {
+                    StateMachineError::TransactionError(format!(
+                        "error in txn while trying to delete idempotency key: {}",
+                        e
+                    ))
+                })?;
+                deleted += 1;
+            }
+        }
+        txn.commit()
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+        Ok(deleted)
+    }
+
+    /// Deletes the versions of content other than the latest one which are no
+    /// longer referenced and returns the number of versions deleted. A
+    /// version is referenced when
+    /// - an extraction policy writing to an index was applied to it, since
+    ///   indexes key embeddings by content id and its embeddings are only
+    ///   removed when the version is garbage collected
+    /// - content was extracted from it
+    /// - tasks extracting it are still pending
+    pub fn safe_prune_versions(
+        &self,
+        content_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<usize, StateMachineError> {
+        let prefix = format!("{}{}", content_id, self.content_version_separator);
+        let iter = db.iterator_cf(
+            StateMachineColumns::ContentTable.cf(db),
+            rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut versions = Vec::new();
+        for item in iter {
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let content = JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
+            //  Ids containing the separator share the prefix
+            if content.id.id == content_id && !content.latest {
+                versions.push(content);
+            }
+        }
+
+        let txn = db.transaction();
+        let policy_ids = versions
+            .iter()
+            .flat_map(|content| content.extraction_policy_ids.keys().cloned())
+            .collect();
+        let indexing_policies: HashSet<ExtractionPolicyId> = self
+            .get_extraction_policies_from_ids_in_txn(policy_ids, &txn, db)?
+            .found
+            .into_iter()
+            .filter(|policy| !policy.output_table_mapping.is_empty())
+            .map(|policy| policy.id)
+            .collect();
+        let unreferenced: Vec<ContentMetadataId> = versions
+            .into_iter()
+            .filter(|content| {
+                !content
+                    .extraction_policy_ids
+                    .keys()
+                    .any(|policy_id| indexing_policies.contains(policy_id)) &&
+                    self.content_children_table
+                        .get_children(&content.id)
+                        .is_empty() &&
+                    self.pending_tasks_for_content
+                        .are_content_tasks_completed(&content.id)
+            })
+            .map(|content| content.id)
+            .collect();
+        let pruned = unreferenced.len();
+        self.delete_content(db, &txn, unreferenced)?;
+        txn.commit()
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+        Ok(pruned)
+    }
+
+    /// Rejects values above the size limit of their column family, so they
+    /// are never deserialized
+    fn check_value_size(
+        &self,
+        column: &StateMachineColumns,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), StateMachineError> {
+        let Some(limit) = self.value_size_limits.limit(column.as_ref()) else {
+            return Ok(());
+        };
+        if value.len() <= limit {
+            return Ok(());
+        }
+        self.metrics.lock().unwrap().values_too_large += 1;
+        let key = String::from_utf8_lossy(key).to_string();
+        warn!(
+            "value of key {} in column family {} is too large: {} bytes, limit: {} bytes",
+            key,
+            column,
+            value.len(),
+            limit
+        );
+        Err(StateMachineError::ValueTooLarge {
+            cf: column.to_string(),
+            key,
+            size: value.len(),
+        })
+    }
+
     /// This method fetches a key from a specific column family
     pub fn get_from_cf<T, K>(
         &self,
@@ -1571,62 +4103,302 @@ impl IndexifyState {
         T: DeserializeOwned,
         K: AsRef<[u8]>,
     {
-        let result_bytes = match db.get_cf(column.cf(db), key)? {
+        let result_bytes = match db.get_cf(column.cf(db), key.as_ref())? {
             Some(bytes) => bytes,
             None => return Ok(None),
         };
+        self.check_value_size(&column, key.as_ref(), &result_bytes)?;
         let result = JsonEncoder::decode::<T>(&result_bytes)
             .map_err(|e| anyhow::anyhow!("Deserialization error: {}", e))?;
 
         Ok(Some(result))
     }
 
-    /// This method is used to get the tasks assigned to an executor
-    /// It does this by looking up the TaskAssignments CF to get the task id's
-    /// and then using those id's to look up tasks via Tasks CF
-    pub fn get_tasks_for_executor(
+    /// This method is used to get the tasks assigned to an executor
+    /// It does this by looking up the TaskAssignments CF to get the task id's
+    /// and then using those id's to look up tasks via Tasks CF
+    pub fn get_tasks_for_executor(
+        &self,
+        executor_id: &str,
+        limit: Option<u64>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::Task>, StateMachineError> {
+        //  NOTE: Don't do deserialization within the transaction
+        let txn = db.transaction();
+        let task_ids_bytes = txn
+            .get_cf(StateMachineColumns::TaskAssignments.cf(db), executor_id)
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+
+        let task_ids: Vec<String> = match task_ids_bytes {
+            Some(task_id_bytes) => {
+                self.check_value_size(
+                    &StateMachineColumns::TaskAssignments,
+                    executor_id.as_bytes(),
+                    &task_id_bytes,
+                )?;
+                JsonEncoder::decode(&task_id_bytes)
+                    .map_err(StateMachineError::from)
+                    .unwrap_or_else(|e| {
+                        error!("Failed to deserialize task id: {}", e);
+                        Vec::new()
+                    })
+            }
+            None => Vec::new(),
+        };
+
+        let limit = limit.unwrap_or(task_ids.len() as u64) as usize;
+        let task_ids: Vec<String> = task_ids.into_iter().take(limit).collect();
+
+        let tasks_cf = StateMachineColumns::Tasks.cf(db);
+        let values = self.multi_get_cf(&txn, task_ids.iter().map(|task_id| (tasks_cf, task_id)));
+        let mut tasks_bytes = Vec::with_capacity(task_ids.len());
+        let mut missing = Vec::new();
+        for (task_id, value) in task_ids.iter().zip(values) {
+            match value.map_err(|e| StateMachineError::TransactionError(e.to_string()))? {
+                Some(task_bytes) => tasks_bytes.push((task_id, task_bytes)),
+                None => missing.push(task_id.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(StateMachineError::AssignedTasksNotFound {
+                executor_id: executor_id.to_string(),
+                task_ids: missing,
+            });
+        }
+
+        tasks_bytes
+            .into_iter()
+            .map(|(task_id, task_bytes)| {
+                self.check_value_size(
+                    &StateMachineColumns::Tasks,
+                    task_id.as_bytes(),
+                    &task_bytes,
+                )?;
+                JsonEncoder::decode(&task_bytes).map_err(StateMachineError::from)
+            })
+            .collect()
+    }
+
+    /// Reads the keys with multi gets of at most multi_get_batch_size keys
+    fn multi_get_cf<'b, K, W>(
+        &self,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        keys: impl IntoIterator<Item = (&'b W, K)>,
+    ) -> Vec<Result<Option<Vec<u8>>, rocksdb::Error>>
+    where
+        K: AsRef<[u8]>,
+        W: rocksdb::AsColumnFamilyRef + 'b,
+    {
+        multi_get_cf_chunked(txn, keys, self.multi_get_batch_size)
+    }
+
+    /// This method will fetch indexes based on the id's of the indexes provided
+    fn multi_get_in_txn<T: DeserializeOwned>(
+        &self,
+        cf: StateMachineColumns,
+        keys: &[String],
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<Option<T>>, StateMachineError> {
+        let cf_handle = cf.cf(db);
+        let values = self.multi_get_cf(txn, keys.iter().map(|key| (cf_handle, key)));
+        let mut rows = Vec::with_capacity(keys.len());
+        for (key, value) in keys.iter().zip(values) {
+            let value = value.map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+            let row = match value {
+                Some(value) => {
+                    self.check_value_size(&cf, key.as_bytes(), &value)?;
+                    Some(JsonEncoder::decode::<T>(&value)?)
+                }
+                None => None,
+            };
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Computes the coverage of an extraction policy over the content of a
+    /// namespace. Content counts as applied when it belongs to the graph of
+    /// the policy and comes from the source of the policy, and as completed
+    /// when the policy mapping of the content has a completion time for it.
+    pub fn get_policy_coverage(
+        &self,
+        namespace: &str,
+        policy_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<PolicyCoverage, StateMachineError> {
+        let txn = db.transaction();
+        let policy = self
+            .get_extraction_policies_from_ids_in_txn(
+                HashSet::from([policy_id.to_string()]),
+                &txn,
+                db,
+            )?
+            .found
+            .into_iter()
+            .next()
+            .ok_or_else(|| StateMachineError::ExtractionPolicyNotFound(policy_id.to_string()))?;
+        //  Content ids carry the version, the latest version is stored under
+        //  the plain id
+        let content_ids: HashSet<String> = self
+            .content_namespace_table
+            .get(namespace)
+            .into_iter()
+            .map(|id| id.id)
+            .collect();
+        let mut coverage = PolicyCoverage::default();
+        for content in self.get_content_from_ids_in_txn(content_ids, &txn, db)? {
+            if content.namespace != namespace || !content.latest || content.tombstoned {
+                continue;
+            }
+            coverage.total += 1;
+            if !content.extraction_graph_names.contains(&policy.graph_name) ||
+                content.source.to_string() != policy.content_source.to_string()
+            {
+                continue;
+            }
+            coverage.applied += 1;
+            if content.extraction_policy_ids.contains_key(policy_id) {
+                coverage.completed += 1;
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Returns the usage counters of a namespace, or of all namespaces, for
+    /// the days in the inclusive range, ordered by namespace, day and
+    /// extractor
+    pub fn get_usage_stats(
+        &self,
+        namespace: Option<&str>,
+        from_day: u64,
+        to_day: u64,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<internal_api::UsageStats>, StateMachineError> {
+        let cf = StateMachineColumns::UsageStats.cf(db);
+        let iter = match namespace {
+            Some(namespace) => db.iterator_cf(
+                cf,
+                rocksdb::IteratorMode::From(
+                    internal_api::UsageStats::key_prefix(namespace, from_day).as_bytes(),
+                    rocksdb::Direction::Forward,
+                ),
+            ),
+            None => db.iterator_cf(cf, rocksdb::IteratorMode::Start),
+        };
+        let mut stats = Vec::new();
+        for item in iter {
+            let (_, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            let bucket = JsonEncoder::decode::<internal_api::UsageStats>(&value)?;
+            if let Some(namespace) = namespace {
+                if bucket.namespace != namespace || bucket.day > to_day {
+                    break;
+                }
+            }
+            if bucket.day < from_day || bucket.day > to_day {
+                continue;
+            }
+            stats.push(bucket);
+        }
+        Ok(stats)
+    }
+
+    /// Returns the record of the ingestion key, including expired records
+    /// which haven't been compacted yet
+    pub fn get_ingestion_key(
+        &self,
+        namespace: &str,
+        key: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<IngestionKeyRecord>, StateMachineError> {
+        db.get_cf(
+            StateMachineColumns::IngestionKeys.cf(db),
+            IngestionKeyRecord::cf_key(namespace, key),
+        )
+        .map_err(|e| StateMachineError::DatabaseError(e.to_string()))?
+        .map(|value| JsonEncoder::decode::<IngestionKeyRecord>(&value))
+        .transpose()
+    }
+
+    /// Joins the indexes of a namespace with their extraction policies and
+    /// extractors. Only the indexes of the namespace are read, with a single
+    /// multi get per column family. Indexes whose policy or extractor is
+    /// missing are returned without it.
+    pub fn get_namespace_index_overview(
         &self,
-        executor_id: &str,
-        limit: Option<u64>,
+        namespace: &str,
         db: &Arc<OptimisticTransactionDB>,
-    ) -> Result<Vec<indexify_internal_api::Task>, StateMachineError> {
-        //  NOTE: Don't do deserialization within the transaction
+    ) -> Result<Vec<IndexOverview>, StateMachineError> {
         let txn = db.transaction();
-        let task_ids_bytes = txn
-            .get_cf(StateMachineColumns::TaskAssignments.cf(db), executor_id)
-            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+        let index_ids = self
+            .namespace_index_table
+            .get(namespace)
+            .into_iter()
+            .collect_vec();
+        let indexes: Vec<internal_api::Index> = self
+            .multi_get_in_txn(StateMachineColumns::IndexTable, &index_ids, &txn, db)?
+            .into_iter()
+            .flatten()
+            .collect();
 
-        let task_ids: Vec<String> = task_ids_bytes
-            .map(|task_id_bytes| {
-                JsonEncoder::decode(&task_id_bytes)
-                    .map_err(StateMachineError::from)
-                    .unwrap_or_else(|e| {
-                        error!("Failed to deserialize task id: {}", e);
-                        Vec::new()
-                    })
+        let policy_ids = indexes
+            .iter()
+            .map(|index| {
+                ExtractionPolicy::create_id(
+                    &index.graph_name,
+                    &index.extraction_policy_name,
+                    &index.namespace,
+                )
             })
-            .unwrap_or_else(Vec::new);
+            .collect_vec();
+        let policies: Vec<Option<ExtractionPolicy>> = self.multi_get_in_txn(
+            StateMachineColumns::ExtractionPolicies,
+            &policy_ids,
+            &txn,
+            db,
+        )?;
 
-        // FIXME Use MULTIGET
-        let limit = limit.unwrap_or(task_ids.len() as u64) as usize;
+        let extractor_names = indexes
+            .iter()
+            .zip(&policies)
+            .map(|(index, policy)| {
+                policy
+                    .as_ref()
+                    .map_or(&index.extractor_name, |policy| &policy.extractor)
+            })
+            .unique()
+            .cloned()
+            .collect_vec();
+        let extractors: HashMap<String, ExtractorDescription> = extractor_names
+            .iter()
+            .cloned()
+            .zip(self.multi_get_in_txn::<ExtractorDescription>(
+                StateMachineColumns::Extractors,
+                &extractor_names,
+                &txn,
+                db,
+            )?)
+            .filter_map(|(name, extractor)| extractor.map(|extractor| (name, extractor)))
+            .collect();
 
-        let tasks: Result<Vec<indexify_internal_api::Task>, StateMachineError> = task_ids
+        let mut overview = indexes
             .into_iter()
-            .take(limit)
-            .map(|task_id| {
-                let task_bytes = txn
-                    .get_cf(StateMachineColumns::Tasks.cf(db), task_id.as_bytes())
-                    .map_err(|e| StateMachineError::TransactionError(e.to_string()))?
-                    .ok_or_else(|| {
-                        StateMachineError::DatabaseError(format!("Task {} not found", task_id))
-                    })?;
-                JsonEncoder::decode(&task_bytes).map_err(StateMachineError::from)
+            .zip(policies)
+            .map(|(index, extraction_policy)| {
+                let extractor_name = extraction_policy
+                    .as_ref()
+                    .map_or(&index.extractor_name, |policy| &policy.extractor);
+                IndexOverview {
+                    extractor: extractors.get(extractor_name).cloned(),
+                    index,
+                    extraction_policy,
+                }
             })
-            .collect();
-        tasks
+            .collect_vec();
+        overview.sort_by(|a, b| a.index.name.cmp(&b.index.name));
+        Ok(overview)
     }
 
-    /// This method will fetch indexes based on the id's of the indexes provided
     pub fn get_indexes_from_ids(
         &self,
         task_ids: HashSet<TaskId>,
@@ -1650,6 +4422,20 @@ impl IndexifyState {
 
     /// This method will fetch the executors from RocksDB CF based on the
     /// executor id's provided
+    /// Returns the description of a registered extractor, whose input params
+    /// describe the parameters it expects
+    pub fn get_extractor(
+        &self,
+        name: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<ExtractorDescription>, StateMachineError> {
+        let txn = db.transaction();
+        txn.get_cf(StateMachineColumns::Extractors.cf(db), name)
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?
+            .map(|data| JsonEncoder::decode::<ExtractorDescription>(&data))
+            .transpose()
+    }
+
     pub fn get_executors_from_ids(
         &self,
         executor_ids: HashSet<String>,
@@ -1687,7 +4473,7 @@ impl IndexifyState {
         let content_metadata_bytes = txn
             .get_cf(
                 StateMachineColumns::ContentTable.cf(db),
-                format!("{}::v{}", content_id.id, content_id.version),
+                self.content_version_separator.key(content_id),
             )
             .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
         if content_metadata_bytes.is_none() {
@@ -1699,6 +4485,234 @@ impl IndexifyState {
         Ok(Some(content_metadata))
     }
 
+    /// Returns the latest tombstoned version of root content. Tombstoned
+    /// roots are moved to the key of their version, so unlike live content
+    /// they can't be read by the plain id.
+    pub fn get_latest_tombstoned_version(
+        &self,
+        content_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        let prefix = format!("{}{}", content_id, self.content_version_separator);
+        let iter = db.iterator_cf(
+            StateMachineColumns::ContentTable.cf(db),
+            rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut latest: Option<indexify_internal_api::ContentMetadata> = None;
+        for item in iter {
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let content = JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
+            //  Ids containing the separator share the prefix
+            if content.id.id != content_id || !content.tombstoned {
+                continue;
+            }
+            if latest
+                .as_ref()
+                .map_or(true, |latest| latest.id.version < content.id.version)
+            {
+                latest = Some(content);
+            }
+        }
+        Ok(latest)
+    }
+
+    /// This method returns the latest version of live content in a namespace
+    /// whose id starts with the given prefix. Older versions are stored under
+    /// versioned keys and are skipped, since the latest version is keyed by
+    /// the plain id.
+    pub fn get_content_by_id_prefix(
+        &self,
+        namespace: &str,
+        prefix: &str,
+        db: &Arc<OptimisticTransactionDB>,
+        limit: usize,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        let iter = db.iterator_cf(
+            StateMachineColumns::ContentTable.cf(db),
+            rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut contents = Vec::new();
+        for item in iter {
+            if contents.len() >= limit {
+                break;
+            }
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            if self
+                .content_version_separator
+                .is_versioned_key(&String::from_utf8_lossy(&key))
+            {
+                continue;
+            }
+            let content = JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
+            if content.namespace != namespace || content.tombstoned || !content.latest {
+                continue;
+            }
+            contents.push(content);
+        }
+        Ok(contents)
+    }
+
+    /// Returns the latest version of live content in a namespace which came
+    /// from the given source, either ingestion ("") or an extraction policy
+    pub fn get_content_by_source(
+        &self,
+        namespace: &str,
+        source: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        let content_ids = self.content_by_source.get(namespace, source);
+        let contents = self
+            .get_content_from_ids(content_ids, db)?
+            .into_iter()
+            .filter(|content| {
+                //  The source of a newer version can differ from the one indexed
+                content.namespace == namespace &&
+                    String::from(&content.source) == source &&
+                    content.latest &&
+                    !content.tombstoned
+            })
+            .collect();
+        Ok(contents)
+    }
+
+    /// Returns up to `n` of the latest versions of live content in a
+    /// namespace, most recently updated first. Content with the same update
+    /// time is ordered by id.
+    pub fn get_recently_updated_content(
+        &self,
+        namespace: &str,
+        n: usize,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        //  Content ids carry the version, the latest version is stored under
+        //  the plain id
+        let content_ids: HashSet<String> = self
+            .content_namespace_table
+            .get(namespace)
+            .into_iter()
+            .map(|id| id.id)
+            .collect();
+        let mut contents: Vec<_> = self
+            .get_content_from_ids(content_ids, db)?
+            .into_iter()
+            .filter(|content| {
+                content.namespace == namespace && content.latest && !content.tombstoned
+            })
+            .collect();
+        contents.sort_by(|a, b| {
+            b.updated_at()
+                .cmp(&a.updated_at())
+                .then_with(|| a.id.id.cmp(&b.id.id))
+        });
+        contents.truncate(n);
+        Ok(contents)
+    }
+
+    /// Returns the ids of the latest versions of live content in a namespace
+    /// which no extraction policy has been applied to yet, ordered by id
+    pub fn get_unprocessed_content(
+        &self,
+        namespace: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<ContentMetadataId>, StateMachineError> {
+        //  Content ids carry the version, the latest version is stored under
+        //  the plain id
+        let content_ids: HashSet<String> = self
+            .content_namespace_table
+            .get(namespace)
+            .into_iter()
+            .map(|id| id.id)
+            .collect();
+        let mut unprocessed: Vec<_> = self
+            .get_content_from_ids(content_ids, db)?
+            .into_iter()
+            .filter(|content| {
+                content.namespace == namespace &&
+                    content.latest &&
+                    !content.tombstoned &&
+                    content.extraction_policy_ids.is_empty()
+            })
+            .map(|content| content.id)
+            .collect();
+        unprocessed.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(unprocessed)
+    }
+
+    /// Returns the content of a namespace in the lifecycle states selected by
+    /// the query, ordered by id and version. Every version of the content is
+    /// read in a single scan of the content table.
+    pub fn query_content(
+        &self,
+        namespace: &str,
+        query: ContentQuery,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        let iter = db.iterator_cf(
+            StateMachineColumns::ContentTable.cf(db),
+            rocksdb::IteratorMode::Start,
+        );
+        let mut contents = Vec::new();
+        for item in iter {
+            let (_, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            let content = JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
+            if content.namespace != namespace ||
+                query
+                    .tombstoned
+                    .is_some_and(|tombstoned| tombstoned != content.tombstoned) ||
+                query.finalized.is_some_and(|finalized| {
+                    finalized != self.are_content_tasks_completed(&content.id)
+                })
+            {
+                continue;
+            }
+            contents.push(content);
+        }
+        contents.sort_by(|a, b| {
+            a.id.id
+                .cmp(&b.id.id)
+                .then_with(|| a.id.version.cmp(&b.id.version))
+        });
+        if query.limit > 0 {
+            contents.truncate(query.limit);
+        }
+        Ok(contents)
+    }
+
+    /// Returns the storage urls which are referenced by any content. Every
+    /// version of every content is checked, including tombstoned content
+    /// which has not been garbage collected yet.
+    pub fn get_referenced_storage_urls(
+        &self,
+        storage_urls: &HashSet<String>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<HashSet<String>, StateMachineError> {
+        let mut referenced = HashSet::new();
+        if storage_urls.is_empty() {
+            return Ok(referenced);
+        }
+        let iter = db.iterator_cf(
+            StateMachineColumns::ContentTable.cf(db),
+            rocksdb::IteratorMode::Start,
+        );
+        for item in iter {
+            let (_, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            let content = JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
+            if storage_urls.contains(&content.storage_url) {
+                referenced.insert(content.storage_url);
+                if referenced.len() == storage_urls.len() {
+                    break;
+                }
+            }
+        }
+        Ok(referenced)
+    }
+
     /// This method will fetch content based on the id's provided. It will look
     /// for the latest version for each piece of content It will skip any
     /// that cannot be found and expect the consumer to decide what to do in
@@ -1707,18 +4721,73 @@ impl IndexifyState {
         &self,
         content_ids: impl IntoIterator<Item = String>,
         db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        self.get_content_from_ids_in_txn(content_ids, &db.transaction(), db)
+    }
+
+    /// Reads the given versions of content with a single multi get, skipping
+    /// content which can't be found or is tombstoned. The latest version of
+    /// content is stored under its id and older versions under their
+    /// versioned key, so both keys are read for each content.
+    pub fn get_content_from_ids_with_version(
+        &self,
+        content_ids: &[ContentMetadataId],
+        db: &Arc<OptimisticTransactionDB>,
     ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
         let txn = db.transaction();
-        let mut contents = Vec::new();
         let cf_handle = StateMachineColumns::ContentTable.cf(db);
-        let cf_keys = content_ids
-            .into_iter()
-            .map(|id| (cf_handle, id))
+        let keys = content_ids
+            .iter()
+            .flat_map(|content_id| {
+                [
+                    self.content_version_separator.key(content_id),
+                    content_id.id.clone(),
+                ]
+            })
             .collect_vec();
-        let results = txn.multi_get_cf(cf_keys);
-        for res in results {
+        let results = self.multi_get_cf(&txn, keys.iter().map(|key| (cf_handle, key)));
+        let mut contents = Vec::new();
+        for (content_id, values) in content_ids.iter().zip(results.chunks(2)) {
+            for value in values {
+                let value = value.as_ref().map_err(|e| {
+                    StateMachineError::DatabaseError(format!("error reading content: {}", e))
+                })?;
+                let Some(value) = value else {
+                    continue;
+                };
+                let content = JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(value)?;
+                if content.id == *content_id {
+                    if !content.tombstoned {
+                        contents.push(content);
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Reads content like get_content_from_ids within the transaction of
+    /// the caller, so several reads can share it
+    pub fn get_content_from_ids_in_txn(
+        &self,
+        content_ids: impl IntoIterator<Item = String>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        let mut contents = Vec::new();
+        let cf_handle = StateMachineColumns::ContentTable.cf(db);
+        let content_ids = content_ids.into_iter().collect_vec();
+        let cf_keys = content_ids.iter().map(|id| (cf_handle, id)).collect_vec();
+        let results = self.multi_get_cf(txn, cf_keys);
+        for (id, res) in content_ids.iter().zip(results) {
             match res {
                 Ok(Some(value)) => {
+                    self.check_value_size(
+                        &StateMachineColumns::ContentTable,
+                        id.as_bytes(),
+                        &value,
+                    )?;
                     contents.push(
                         JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?,
                     );
@@ -1735,6 +4804,124 @@ impl IndexifyState {
         Ok(contents)
     }
 
+    /// Reads a page of the content get_content_from_ids reads. Content ids
+    /// are read in sorted order, after the id of the cursor, until `limit`
+    /// pieces of content are found. Returns the id of the last content id
+    /// read as the cursor of the next page, None once all ids were read.
+    pub fn get_content_from_ids_paginated(
+        &self,
+        content_ids: impl IntoIterator<Item = String>,
+        cursor: Option<String>,
+        limit: usize,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<(Vec<indexify_internal_api::ContentMetadata>, Option<String>), StateMachineError>
+    {
+        let content_ids: Vec<String> = content_ids
+            .into_iter()
+            .filter(|id| cursor.as_ref().map_or(true, |cursor| id > cursor))
+            .sorted()
+            .dedup()
+            .collect();
+        let limit = limit.max(1);
+        let txn = db.transaction();
+        let mut contents = Vec::new();
+        let mut read = 0;
+        //  Missing and tombstoned content is skipped, so more ids are read
+        //  until the page is full
+        while contents.len() < limit && read < content_ids.len() {
+            let end = (read + limit - contents.len()).min(content_ids.len());
+            let batch = content_ids[read..end].iter().cloned();
+            read = end;
+            contents.extend(
+                self.get_content_from_ids_in_txn(batch, &txn, db)?
+                    .into_iter()
+                    .filter(|content| !content.tombstoned),
+            );
+        }
+        let next_cursor = (read < content_ids.len()).then(|| content_ids[read - 1].clone());
+        Ok((contents, next_cursor))
+    }
+
+    /// Resolves each lookup key to the root content it was last ingested as.
+    /// Uses one multi get against the lookup index and one against the
+    /// content table regardless of the number of keys.
+    pub fn check_content_exists(
+        &self,
+        namespace: &str,
+        keys: &[internal_api::ContentLookupKey],
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<internal_api::ContentExistence>, StateMachineError> {
+        let txn = db.transaction();
+        let lookup_cf = StateMachineColumns::ContentLookupIndex.cf(db);
+        let lookup_keys = keys.iter().map(|key| {
+            let key = match key {
+                internal_api::ContentLookupKey::Id(id) => content_id_lookup_key(namespace, id),
+                internal_api::ContentLookupKey::Hash(hash) => {
+                    content_hash_lookup_key(namespace, hash)
+                }
+            };
+            (lookup_cf, key)
+        });
+        let mut content_ids = Vec::new();
+        for res in self.multi_get_cf(&txn, lookup_keys) {
+            let value = res.map_err(|e| {
+                StateMachineError::DatabaseError(format!("error reading content lookup: {}", e))
+            })?;
+            content_ids.push(
+                value
+                    .map(|value| JsonEncoder::decode::<ContentMetadataId>(&value))
+                    .transpose()?,
+            );
+        }
+
+        // The latest version is keyed by the plain id, tombstoned versions by the
+        // versioned key, so both are fetched for every resolved id.
+        let content_cf = StateMachineColumns::ContentTable.cf(db);
+        let content_keys = content_ids.iter().flatten().flat_map(|id| {
+            [
+                (content_cf, id.id.clone()),
+                (content_cf, self.content_version_separator.key(id)),
+            ]
+        });
+        let mut contents = Vec::new();
+        for res in self.multi_get_cf(&txn, content_keys) {
+            let value = res.map_err(|e| {
+                StateMachineError::DatabaseError(format!("error reading content: {}", e))
+            })?;
+            contents.push(
+                value
+                    .map(|value| JsonEncoder::decode::<internal_api::ContentMetadata>(&value))
+                    .transpose()?,
+            );
+        }
+
+        let mut contents = contents.into_iter().tuples::<(_, _)>();
+        let mut results = Vec::with_capacity(keys.len());
+        for content_id in content_ids {
+            let Some(content_id) = content_id else {
+                results.push(internal_api::ContentExistence::Unknown);
+                continue;
+            };
+            let (latest, versioned) = contents.next().unwrap_or_default();
+            let content = [latest, versioned]
+                .into_iter()
+                .flatten()
+                .find(|content| content.id == content_id);
+            results.push(match content {
+                Some(content) if content.tombstoned => internal_api::ContentExistence::Tombstoned {
+                    content_id: content.id.id,
+                },
+                Some(content) => internal_api::ContentExistence::Exists {
+                    content_id: content.id.id,
+                    version: content.id.version,
+                    hash: content.hash,
+                },
+                None => internal_api::ContentExistence::Unknown,
+            });
+        }
+        Ok(results)
+    }
+
     // Root of the tree can be either latest version, or an overwritten/deleted
     // content with a version supplied. The rest of the elements in the tree are
     // always version 1.
@@ -1746,7 +4933,11 @@ impl IndexifyState {
     ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
         let txn = db.transaction();
         let mut collected_content_metadata = Vec::new();
-        let content_key = internal_api::ContentMetadata::make_id_key(content_id, version);
+        let content_key = internal_api::ContentMetadata::make_id_key(
+            content_id,
+            version,
+            &self.content_version_separator,
+        );
         let cf_handle = StateMachineColumns::ContentTable.cf(db);
         let val = txn
             .get_cf(cf_handle, content_key)
@@ -1768,29 +4959,94 @@ impl IndexifyState {
             queue.extend(children.into_iter());
         }
 
-        let content_metadata_bytes = txn.multi_get_cf(cf_ids);
+        let content_metadata_bytes = self.multi_get_cf(&txn, cf_ids);
+
+        for res in content_metadata_bytes {
+            if let Ok(Some(value)) = res {
+                let content =
+                    JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
+                collected_content_metadata.push(content);
+            }
+        }
+
+        Ok(collected_content_metadata)
+    }
+
+    /// This method will fetch all pieces of content metadata for the tree
+    /// rooted at latest version of content_id.
+    pub fn get_content_tree_metadata(
+        &self,
+        content_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        self.get_content_tree_metadata_inner(content_id, None, db)
+    }
+
+    /// Returns the latest version of the content extracted by a task, as
+    /// recorded when the task finished
+    pub fn get_content_for_task(
+        &self,
+        task_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
+        let task: internal_api::Task =
+            self.get_from_cf(db, StateMachineColumns::Tasks, task_id)?
+                .ok_or_else(|| StateMachineError::TaskNotFound(task_id.to_string()))?;
+        self.get_content_from_ids(task.output_content_ids, db)
+    }
+
+    /// This method will fetch the pieces of content metadata for the tree
+    /// rooted at latest version of root_id, down to max_depth levels below
+    /// the root. Each piece is returned with its depth, the root being at 0.
+    pub fn get_content_subtree(
+        &self,
+        root_id: &str,
+        max_depth: u32,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<(indexify_internal_api::ContentMetadata, u32)>, StateMachineError> {
+        let txn = db.transaction();
+        let mut collected_content_metadata = Vec::new();
+        let cf_handle = StateMachineColumns::ContentTable.cf(db);
+        let val = txn
+            .get_cf(cf_handle, root_id)
+            .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+
+        let content = match val {
+            None => return Ok(collected_content_metadata),
+            Some(bytes) => JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&bytes)?,
+        };
+
+        let mut cf_ids = Vec::new();
+        let mut depths = Vec::new();
+
+        let mut queue = VecDeque::new();
+        queue.push_back((content.id.clone(), 0));
+        collected_content_metadata.push((content, 0));
+        while let Some((current_root, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            let children = self.content_children_table.get_children(&current_root);
+            for child in children {
+                cf_ids.push((cf_handle, child.id.clone()));
+                depths.push(depth + 1);
+                queue.push_back((child, depth + 1));
+            }
+        }
+
+        let content_metadata_bytes = self.multi_get_cf(&txn, cf_ids);
 
-        for res in content_metadata_bytes {
+        for (res, depth) in content_metadata_bytes.into_iter().zip(depths) {
             if let Ok(Some(value)) = res {
                 let content =
                     JsonEncoder::decode::<indexify_internal_api::ContentMetadata>(&value)?;
-                collected_content_metadata.push(content);
+                collected_content_metadata.push((content, depth));
             }
         }
 
         Ok(collected_content_metadata)
     }
 
-    /// This method will fetch all pieces of content metadata for the tree
-    /// rooted at latest version of content_id.
-    pub fn get_content_tree_metadata(
-        &self,
-        content_id: &str,
-        db: &Arc<OptimisticTransactionDB>,
-    ) -> Result<Vec<indexify_internal_api::ContentMetadata>, StateMachineError> {
-        self.get_content_tree_metadata_inner(content_id, None, db)
-    }
-
     /// This method will fetch all pieces of content metadata for the tree
     /// rooted at overwritten/deleted content_id. It will look for a specfic
     /// version of the node.
@@ -1809,35 +5065,48 @@ impl IndexifyState {
         &self,
         extraction_policy_ids: HashSet<String>,
         db: &Arc<OptimisticTransactionDB>,
-    ) -> Result<Option<Vec<ExtractionPolicy>>, StateMachineError> {
-        let txn = db.transaction();
+    ) -> Result<ExtractionPoliciesLookup, StateMachineError> {
+        self.get_extraction_policies_from_ids_in_txn(extraction_policy_ids, &db.transaction(), db)
+    }
 
-        let mut policies = Vec::new();
-        for id in extraction_policy_ids.iter() {
-            let bytes_opt = txn
-                .get_cf(
+    pub fn get_extraction_policies_from_ids_in_txn(
+        &self,
+        extraction_policy_ids: HashSet<String>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<ExtractionPoliciesLookup, StateMachineError> {
+        let ids = extraction_policy_ids.into_iter().collect_vec();
+        let keys = ids
+            .iter()
+            .map(|id| {
+                (
                     StateMachineColumns::ExtractionPolicies.cf(db),
                     id.as_bytes(),
                 )
-                .map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+            })
+            .collect_vec();
+        let values = self.multi_get_cf(txn, keys);
 
-            if let Some(bytes) = bytes_opt {
-                let policy = serde_json::from_slice::<ExtractionPolicy>(&bytes).map_err(|e| {
-                    StateMachineError::SerializationError(format!(
-                        "get_extraction_policies from id: unable to deserialize json, {}",
-                        e
-                    ))
-                })?;
-                policies.push(policy);
+        let mut lookup = ExtractionPoliciesLookup::default();
+        for (id, value) in ids.into_iter().zip(values) {
+            let bytes = value.map_err(|e| StateMachineError::TransactionError(e.to_string()))?;
+            match bytes {
+                Some(bytes) => {
+                    let policy =
+                        serde_json::from_slice::<ExtractionPolicy>(&bytes).map_err(|e| {
+                            StateMachineError::SerializationError(format!(
+                                "get_extraction_policies from id: unable to deserialize json, {}",
+                                e
+                            ))
+                        })?;
+                    lookup.found.push(policy);
+                }
+                None => {
+                    lookup.missing.insert(id);
+                }
             }
-            // If None, the policy is not found; we simply skip it.
-        }
-
-        if policies.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(policies))
         }
+        Ok(lookup)
     }
 
     pub fn get_extraction_policy_by_names(
@@ -1868,6 +5137,37 @@ impl IndexifyState {
         Ok(policies)
     }
 
+    /// Counts the tasks assigned to each executor from the task assignments
+    /// CF. Unlike the in-memory running task count this is authoritative, so
+    /// the two can be compared to detect drift.
+    pub fn get_all_executor_task_counts(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<HashMap<ExecutorId, usize>, StateMachineError> {
+        let mut counts = HashMap::new();
+        let iter = db.iterator_cf(
+            StateMachineColumns::TaskAssignments.cf(db),
+            rocksdb::IteratorMode::Start,
+        );
+        for item in iter {
+            let (key, value) = item.map_err(|e| {
+                StateMachineError::DatabaseError(format!(
+                    "unable to get values from task assignment {}",
+                    e
+                ))
+            })?;
+            let executor_id = String::from_utf8(key.to_vec()).map_err(|e| {
+                StateMachineError::DatabaseError(format!(
+                    "unable to get executor id from task assignment {}",
+                    e
+                ))
+            })?;
+            let task_ids: HashSet<TaskId> = JsonEncoder::decode(&value)?;
+            counts.insert(executor_id, task_ids.len());
+        }
+        Ok(counts)
+    }
+
     /// This method gets all task assignments stored in the relevant CF
     pub fn get_all_task_assignments(
         &self,
@@ -1891,6 +5191,7 @@ impl IndexifyState {
                     e
                 ))
             })?;
+            self.check_value_size(&StateMachineColumns::TaskAssignments, &key, &value)?;
             let task_ids: HashSet<TaskId> = JsonEncoder::decode(&value).map_err(|e| {
                 StateMachineError::DatabaseError(format!(
                     "unable to decoded task hashset from task assignment {}",
@@ -1913,43 +5214,374 @@ impl IndexifyState {
     }
 
     /// This method will get the namespace based on the key provided
+    /// Returns the namespace with its extraction graphs. Extraction policies
+    /// referenced by the reverse index but missing from the db are reported as
+    /// warnings.
     pub fn get_namespace(
         &self,
         namespace: &str,
         db: &Arc<OptimisticTransactionDB>,
     ) -> Result<Option<indexify_internal_api::Namespace>> {
-        let ns_name = match self.get_from_cf(db, StateMachineColumns::Namespaces, namespace)? {
-            Some(name) => name,
+        self.get_namespace_in_txn(namespace, &db.transaction(), db)
+    }
+
+    pub fn get_namespace_in_txn(
+        &self,
+        namespace: &str,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<indexify_internal_api::Namespace>> {
+        let ns_name = match txn.get_cf(StateMachineColumns::Namespaces.cf(db), namespace)? {
+            Some(bytes) => {
+                self.check_value_size(
+                    &StateMachineColumns::Namespaces,
+                    namespace.as_bytes(),
+                    &bytes,
+                )?;
+                JsonEncoder::decode::<String>(&bytes)?
+            }
             None => return Ok(None),
         };
+        let mut warnings = Vec::new();
+        let policy_ids = self.extraction_policies_table.get(&namespace.to_string());
+        let lookup = self.get_extraction_policies_from_ids_in_txn(policy_ids, txn, db)?;
+        if !lookup.missing.is_empty() {
+            let missing = lookup.missing.iter().sorted().join(", ");
+            warn!(
+                "namespace {} references extraction policies that do not exist: {}",
+                namespace, missing
+            );
+            warnings.push(format!("extraction policies not found: {}", missing));
+        }
         let extraction_graphs_ids = self
             .extraction_graphs_by_ns
             .get(&namespace.to_string())
             .into_iter()
             .collect_vec();
         let extraction_graphs = self
-            .get_extraction_graphs(&extraction_graphs_ids, db)?
+            .get_extraction_graphs_in_txn(&extraction_graphs_ids, txn, db)?
             .into_iter()
             .flatten()
             .collect();
 
+        let retention =
+            match txn.get_cf(StateMachineColumns::NamespaceRetention.cf(db), namespace)? {
+                Some(bytes) => JsonEncoder::decode(&bytes)?,
+                None => Default::default(),
+            };
+
         Ok(Some(indexify_internal_api::Namespace {
             name: ns_name,
             extraction_graphs,
+            warnings,
+            retention,
         }))
     }
 
+    /// Returns the retention rules of every namespace which has any
+    pub fn get_namespace_retention_rules(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<HashMap<NamespaceName, internal_api::RetentionRules>, StateMachineError> {
+        Ok(self
+            .get_all_rows_from_cf::<internal_api::RetentionRules>(
+                StateMachineColumns::NamespaceRetention,
+                db,
+            )?
+            .into_iter()
+            .collect())
+    }
+
+    /// Returns true while tasks of the content tree of the root are
+    /// unfinished
+    pub fn has_unfinished_tasks(&self, root_content_id: &str) -> bool {
+        self.root_task_counts
+            .read()
+            .unwrap()
+            .contains_key(root_content_id)
+    }
+
+    pub fn get_search_profile(
+        &self,
+        namespace: &str,
+        name: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<internal_api::SearchProfile>> {
+        self.get_from_cf(
+            db,
+            StateMachineColumns::SearchProfiles,
+            internal_api::SearchProfile::key_from(namespace, name),
+        )
+    }
+
+    /// Returns every extraction policy of the namespace with the policies it
+    /// depends on, the policy of its graph named by its content source.
+    /// Policies applied to ingested content have no dependencies.
+    pub fn get_policy_dependency_graph(
+        &self,
+        namespace: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<(ExtractionPolicyId, Vec<ExtractionPolicyId>)>> {
+        let policies = self
+            .get_extraction_policies_from_ids(self.extraction_policies_table.get(namespace), db)?
+            .found;
+        let ids_by_name: HashMap<(&str, &str), &str> = policies
+            .iter()
+            .map(|policy| {
+                (
+                    (policy.graph_name.as_str(), policy.name.as_str()),
+                    policy.id.as_str(),
+                )
+            })
+            .collect();
+        Ok(policies
+            .iter()
+            .map(|policy| {
+                let dependencies = match &policy.content_source {
+                    internal_api::ExtractionPolicyContentSource::Ingestion => vec![],
+                    internal_api::ExtractionPolicyContentSource::ExtractionPolicyName(name) => {
+                        ids_by_name
+                            .get(&(policy.graph_name.as_str(), name.as_str()))
+                            .map(|id| id.to_string())
+                            .into_iter()
+                            .collect()
+                    }
+                };
+                (policy.id.clone(), dependencies)
+            })
+            .sorted()
+            .collect())
+    }
+
+    /// Returns the namespace the alias points to
+    pub fn get_namespace_alias(
+        &self,
+        alias: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<NamespaceName>> {
+        self.get_from_cf(db, StateMachineColumns::NamespaceAliases, alias)
+    }
+
+    pub fn get_subscription(
+        &self,
+        subscription_id: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Option<internal_api::Subscription>> {
+        self.get_from_cf(db, StateMachineColumns::Subscriptions, subscription_id)
+    }
+
+    /// Returns the subscriptions of a namespace, all of them when the
+    /// namespace is empty
+    pub fn list_subscriptions(
+        &self,
+        namespace: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<internal_api::Subscription>, StateMachineError> {
+        let subscriptions = self.get_all_rows_from_cf::<internal_api::Subscription>(
+            StateMachineColumns::Subscriptions,
+            db,
+        )?;
+        Ok(subscriptions
+            .into_iter()
+            .map(|(_, subscription)| subscription)
+            .filter(|subscription| namespace.is_empty() || subscription.namespace == namespace)
+            .collect())
+    }
+
+    /// Returns the deliveries of the subscription in the column, pending or
+    /// dead lettered, ordered by sequence
+    pub fn list_deliveries(
+        &self,
+        column: StateMachineColumns,
+        subscription_id: &str,
+        limit: Option<usize>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<internal_api::Delivery>, StateMachineError> {
+        let prefix = internal_api::Delivery::key_prefix(subscription_id);
+        let iter = db.iterator_cf(
+            column.cf(db),
+            rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut deliveries = Vec::new();
+        for item in iter {
+            if limit.is_some_and(|limit| deliveries.len() >= limit) {
+                break;
+            }
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let delivery = JsonEncoder::decode::<internal_api::Delivery>(&value)?;
+            //  Subscription ids containing the separator share the key prefix
+            if delivery.subscription_id == subscription_id {
+                deliveries.push(delivery);
+            }
+        }
+        Ok(deliveries)
+    }
+
+    /// Returns the number of pending deliveries of every subscription with
+    /// pending deliveries, along with the time the oldest was enqueued
+    pub fn delivery_queue_stats(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<DeliveryQueueStats>, StateMachineError> {
+        let mut stats: BTreeMap<String, DeliveryQueueStats> = BTreeMap::new();
+        for item in db.iterator_cf(
+            StateMachineColumns::PendingDeliveries.cf(db),
+            rocksdb::IteratorMode::Start,
+        ) {
+            let (_, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            let delivery = JsonEncoder::decode::<internal_api::Delivery>(&value)?;
+            let queue = stats
+                .entry(delivery.subscription_id.clone())
+                .or_insert_with(|| DeliveryQueueStats {
+                    subscription_id: delivery.subscription_id.clone(),
+                    depth: 0,
+                    oldest_enqueued_at: delivery.enqueued_at,
+                });
+            queue.depth += 1;
+            queue.oldest_enqueued_at = queue.oldest_enqueued_at.min(delivery.enqueued_at);
+        }
+        Ok(stats.into_values().collect())
+    }
+
+    /// Returns the search profiles of a namespace ordered by name
+    pub fn list_search_profiles(
+        &self,
+        namespace: &str,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<internal_api::SearchProfile>, StateMachineError> {
+        let prefix = internal_api::SearchProfile::key_from(namespace, "");
+        let iter = db.iterator_cf(
+            StateMachineColumns::SearchProfiles.cf(db),
+            rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut profiles = Vec::new();
+        for item in iter {
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let profile = JsonEncoder::decode::<internal_api::SearchProfile>(&value)?;
+            //  Namespaces containing the separator share the key prefix
+            if profile.namespace == namespace {
+                profiles.push(profile);
+            }
+        }
+        Ok(profiles)
+    }
+
+    /// Returns a page of the feedback of a namespace in the order it was
+    /// given, created within the time range and on the content if set. The
+    /// key of the last feedback of the page is returned when there
+    /// may be more, the next page starts after it.
+    pub fn list_search_feedback(
+        &self,
+        query: &internal_api::SearchFeedbackQuery,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<(Vec<internal_api::SearchFeedback>, Option<String>), StateMachineError> {
+        let namespace = query.namespace.as_str();
+        let content_id = query.content_id.as_deref();
+        let (start_time, end_time) = (query.start_time, query.end_time);
+        let cursor = query.cursor.as_deref();
+        let limit = query.limit;
+        let namespace_prefix = internal_api::SearchFeedback::namespace_prefix(namespace);
+        let start = internal_api::SearchFeedback::key_prefix(namespace, start_time.unwrap_or(0));
+        let start = match cursor {
+            Some(cursor) if cursor.as_bytes() >= start.as_bytes() => cursor.to_string(),
+            _ => start,
+        };
+        let end =
+            end_time.map(|end_time| internal_api::SearchFeedback::key_prefix(namespace, end_time));
+        let iter = db.iterator_cf(
+            StateMachineColumns::SearchFeedback.cf(db),
+            rocksdb::IteratorMode::From(start.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut feedback = Vec::new();
+        let mut last_key = None;
+        for item in iter {
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(namespace_prefix.as_bytes()) ||
+                end.as_ref().is_some_and(|end| *key >= *end.as_bytes())
+            {
+                break;
+            }
+            //  The cursor is the last key of the previous page
+            if Some(&*key) == cursor.map(str::as_bytes) {
+                continue;
+            }
+            let entry = JsonEncoder::decode::<internal_api::SearchFeedback>(&value)?;
+            //  Namespaces containing the separator share the key prefix
+            if entry.namespace != namespace ||
+                content_id.is_some_and(|content_id| entry.content_id != content_id)
+            {
+                continue;
+            }
+            if feedback.len() == limit {
+                return Ok((feedback, last_key));
+            }
+            last_key = Some(String::from_utf8_lossy(&key).into_owned());
+            feedback.push(entry);
+        }
+        Ok((feedback, None))
+    }
+
+    /// Counts the positive and negative feedback given on each of the content
+    /// ids in the namespace. Every feedback of the namespace is read.
+    pub fn get_search_feedback_counts(
+        &self,
+        namespace: &str,
+        content_ids: &HashSet<String>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<HashMap<String, internal_api::SearchFeedbackCounts>, StateMachineError> {
+        let prefix = internal_api::SearchFeedback::namespace_prefix(namespace);
+        let iter = db.iterator_cf(
+            StateMachineColumns::SearchFeedback.cf(db),
+            rocksdb::IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+        let mut counts: HashMap<String, internal_api::SearchFeedbackCounts> = content_ids
+            .iter()
+            .map(|content_id| (content_id.clone(), Default::default()))
+            .collect();
+        for item in iter {
+            let (key, value) = item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let feedback = JsonEncoder::decode::<internal_api::SearchFeedback>(&value)?;
+            if feedback.namespace != namespace {
+                continue;
+            }
+            if let Some(counts) = counts.get_mut(&feedback.content_id) {
+                match feedback.verdict {
+                    internal_api::FeedbackVerdict::Positive => counts.positive += 1,
+                    internal_api::FeedbackVerdict::Negative => counts.negative += 1,
+                }
+            }
+        }
+        Ok(counts)
+    }
+
     pub fn get_schemas(
         &self,
         ids: HashSet<String>,
         db: &Arc<OptimisticTransactionDB>,
     ) -> Result<Vec<internal_api::StructuredDataSchema>> {
-        let txn = db.transaction();
+        self.get_schemas_in_txn(ids, &db.transaction(), db)
+    }
+
+    pub fn get_schemas_in_txn(
+        &self,
+        ids: HashSet<String>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<internal_api::StructuredDataSchema>> {
         let keys = ids
             .iter()
             .map(|id| (StateMachineColumns::StructuredDataSchemas.cf(db), id))
             .collect_vec();
-        let schema_bytes = txn.multi_get_cf(keys);
+        let schema_bytes = self.multi_get_cf(txn, keys);
         let mut schemas = vec![];
         for schema in schema_bytes {
             let schema = schema
@@ -1965,13 +5597,22 @@ impl IndexifyState {
         &self,
         extraction_graph_ids: &Vec<ExtractionGraphId>,
         db: &Arc<OptimisticTransactionDB>,
+    ) -> Result<Vec<Option<ExtractionGraph>>, StateMachineError> {
+        self.get_extraction_graphs_in_txn(extraction_graph_ids, &db.transaction(), db)
+    }
+
+    pub fn get_extraction_graphs_in_txn(
+        &self,
+        extraction_graph_ids: &Vec<ExtractionGraphId>,
+        txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+        db: &Arc<OptimisticTransactionDB>,
     ) -> Result<Vec<Option<ExtractionGraph>>, StateMachineError> {
         let cf = StateMachineColumns::ExtractionGraphs.cf(db);
         let keys: Vec<(&rocksdb::ColumnFamily, &[u8])> = extraction_graph_ids
             .iter()
             .map(|egid| (cf, egid.as_bytes()))
             .collect();
-        let serialized_graphs = db.multi_get_cf(keys);
+        let serialized_graphs = self.multi_get_cf(txn, keys);
         let mut graphs: Vec<Option<ExtractionGraph>> = Vec::new();
         for serialized_graph in serialized_graphs {
             match serialized_graph {
@@ -2005,7 +5646,7 @@ impl IndexifyState {
         let cf = StateMachineColumns::ExtractionGraphs.cf(db);
         let keys: Vec<(&rocksdb::ColumnFamily, &[u8])> =
             eg_ids.iter().map(|egid| (cf, egid.as_bytes())).collect();
-        let serialized_graphs = db.multi_get_cf(keys);
+        let serialized_graphs = self.multi_get_cf(&db.transaction(), keys);
         let mut graphs: Vec<Option<ExtractionGraph>> = Vec::new();
         for serialized_graph in serialized_graphs {
             match serialized_graph {
@@ -2039,6 +5680,76 @@ impl IndexifyState {
     }
 
     /// Test utility method to get all key-value pairs from a column family
+    /// Checks the reverse indexes against the first `sample_size` rows of
+    /// the forward indexes they are built from. Returns the first entry
+    /// missing from a reverse index, None when the sample is consistent.
+    pub fn spot_check_reverse_indexes(
+        &self,
+        db: &Arc<OptimisticTransactionDB>,
+        sample_size: usize,
+    ) -> Result<Option<String>, StateMachineError> {
+        fn sample<T: DeserializeOwned>(
+            db: &Arc<OptimisticTransactionDB>,
+            column: StateMachineColumns,
+            sample_size: usize,
+        ) -> Result<Vec<T>, StateMachineError> {
+            db.iterator_cf(column.cf(db), rocksdb::IteratorMode::Start)
+                .take(sample_size)
+                .map(|item| {
+                    let (_, value) =
+                        item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))?;
+                    JsonEncoder::decode(&value)
+                })
+                .collect()
+        }
+
+        for content in sample::<internal_api::ContentMetadata>(
+            db,
+            StateMachineColumns::ContentTable,
+            sample_size,
+        )? {
+            if !self
+                .content_namespace_table
+                .contains(&content.namespace, &content.id)
+            {
+                return Ok(Some(format!(
+                    "content {} is missing from the content of namespace {}",
+                    content.id, content.namespace
+                )));
+            }
+        }
+        for task in sample::<internal_api::Task>(db, StateMachineColumns::Tasks, sample_size)? {
+            if !task.terminal_state() &&
+                !self
+                    .unfinished_tasks_by_extractor
+                    .contains(&task.extractor, &task.id)
+            {
+                return Ok(Some(format!(
+                    "task {} is missing from the unfinished tasks of extractor {}",
+                    task.id, task.extractor
+                )));
+            }
+        }
+        for executor in sample::<internal_api::ExecutorMetadata>(
+            db,
+            StateMachineColumns::Executors,
+            sample_size,
+        )? {
+            for extractor in &executor.extractors {
+                if !self
+                    .extractor_executors_table
+                    .contains(&extractor.name, &executor.id)
+                {
+                    return Ok(Some(format!(
+                        "executor {} is missing from the executors of extractor {}",
+                        executor.id, extractor.name
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+
     pub fn get_all_rows_from_cf<V>(
         &self,
         column: StateMachineColumns,
@@ -2058,6 +5769,7 @@ impl IndexifyState {
         iter.map(|item| {
             item.map_err(|e| StateMachineError::DatabaseError(e.to_string()))
                 .and_then(|(key, value)| {
+                    self.check_value_size(&column, &key, &value)?;
                     match column {
                         StateMachineColumns::StateChanges => {
                             // let key = u64::from_be_bytes(key).to_string();
@@ -2105,6 +5817,18 @@ impl IndexifyState {
     //  END READER METHODS FOR ROCKSDB FORWARD INDEXES
 
     //  START READER METHODS FOR REVERSE INDEXES
+    pub fn reverse_index_sizes(&self) -> Vec<ReverseIndexSize> {
+        vec![
+            self.unassigned_tasks.size(),
+            self.unprocessed_state_changes.size(),
+            self.content_namespace_table.size(),
+            self.content_by_source.size(),
+            self.unfinished_tasks_by_extractor.size(),
+            self.content_children_table.size(),
+            self.pending_tasks_for_content.size(),
+        ]
+    }
+
     pub fn get_unassigned_tasks(&self) -> HashSet<TaskId> {
         self.unassigned_tasks.inner()
     }
@@ -2119,6 +5843,12 @@ impl IndexifyState {
         self.content_namespace_table.inner()
     }
 
+    pub fn get_content_by_source_table(
+        &self,
+    ) -> HashMap<NamespaceName, HashMap<String, HashSet<String>>> {
+        self.content_by_source.inner()
+    }
+
     pub fn get_extraction_policies_table(&self) -> HashMap<NamespaceName, HashSet<String>> {
         self.extraction_policies_table.inner()
     }
@@ -2135,6 +5865,14 @@ impl IndexifyState {
         self.unfinished_tasks_by_extractor.inner()
     }
 
+    /// Unfinished tasks of a content version, by extraction policy
+    pub fn get_pending_tasks_for_content_version(
+        &self,
+        content_id: &ContentMetadataId,
+    ) -> HashMap<ExtractionPolicyId, HashSet<TaskId>> {
+        self.pending_tasks_for_content.get(content_id)
+    }
+
     pub fn get_executor_running_task_count(&self) -> HashMap<ExecutorId, u64> {
         self.executor_running_task_count.inner()
     }
@@ -2244,8 +5982,8 @@ impl IndexifyState {
             StateMachineColumns::GarbageCollectionTasks,
             db,
         )?;
-        let task_assignments =
-            self.get_all_rows_from_cf::<HashSet<TaskId>>(StateMachineColumns::TaskAssignments, db)?;
+        let task_assignments = self
+            .get_all_rows_from_cf::<BTreeSet<TaskId>>(StateMachineColumns::TaskAssignments, db)?;
         let state_changes: HashMap<StateChangeId, StateChange> = self
             .get_all_rows_from_cf::<StateChange>(StateMachineColumns::StateChanges, db)?
             .into_iter()
@@ -2270,7 +6008,7 @@ impl IndexifyState {
         )?;
         let extractors =
             self.get_all_rows_from_cf::<ExtractorDescription>(StateMachineColumns::Extractors, db)?;
-        let namespaces: HashSet<String> = self
+        let namespaces: BTreeSet<String> = self
             .get_all_rows_from_cf::<NamespaceName>(StateMachineColumns::Namespaces, db)?
             .into_iter()
             .map(|(key, _)| key)
@@ -2294,6 +6032,40 @@ impl IndexifyState {
             .collect::<Result<_, StateMachineError>>()?;
         let extraction_graphs = self
             .get_all_rows_from_cf::<ExtractionGraph>(StateMachineColumns::ExtractionGraphs, db)?;
+        let search_profiles = self.get_all_rows_from_cf::<internal_api::SearchProfile>(
+            StateMachineColumns::SearchProfiles,
+            db,
+        )?;
+        let idempotency_keys = self
+            .get_all_rows_from_cf::<IdempotencyRecord>(StateMachineColumns::IdempotencyKeys, db)?;
+        let usage_stats = self.get_all_rows_from_cf::<internal_api::UsageStats>(
+            StateMachineColumns::UsageStats,
+            db,
+        )?;
+        let ingestion_keys = self
+            .get_all_rows_from_cf::<IngestionKeyRecord>(StateMachineColumns::IngestionKeys, db)?;
+        let namespace_aliases =
+            self.get_all_rows_from_cf::<NamespaceName>(StateMachineColumns::NamespaceAliases, db)?;
+        let subscriptions = self.get_all_rows_from_cf::<internal_api::Subscription>(
+            StateMachineColumns::Subscriptions,
+            db,
+        )?;
+        let pending_deliveries = self.get_all_rows_from_cf::<internal_api::Delivery>(
+            StateMachineColumns::PendingDeliveries,
+            db,
+        )?;
+        let dead_letter_deliveries = self.get_all_rows_from_cf::<internal_api::Delivery>(
+            StateMachineColumns::DeadLetterDeliveries,
+            db,
+        )?;
+        let namespace_retention = self.get_all_rows_from_cf::<internal_api::RetentionRules>(
+            StateMachineColumns::NamespaceRetention,
+            db,
+        )?;
+        let search_feedback = self.get_all_rows_from_cf::<internal_api::SearchFeedback>(
+            StateMachineColumns::SearchFeedback,
+            db,
+        )?;
         let metrics = self.metrics.lock().unwrap().clone();
 
         let snapshot = IndexifyStateSnapshot {
@@ -2310,6 +6082,16 @@ impl IndexifyState {
             structured_data_schemas: structured_data_schemas.into_iter().collect(),
             coordinator_address: coordinator_address.into_iter().collect(),
             extraction_graphs: extraction_graphs.into_iter().collect(),
+            search_profiles: search_profiles.into_iter().collect(),
+            idempotency_keys: idempotency_keys.into_iter().collect(),
+            usage_stats: usage_stats.into_iter().collect(),
+            ingestion_keys: ingestion_keys.into_iter().collect(),
+            namespace_aliases: namespace_aliases.into_iter().collect(),
+            subscriptions: subscriptions.into_iter().collect(),
+            pending_deliveries: pending_deliveries.into_iter().collect(),
+            dead_letter_deliveries: dead_letter_deliveries.into_iter().collect(),
+            namespace_retention: namespace_retention.into_iter().collect(),
+            search_feedback: search_feedback.into_iter().collect(),
             metrics,
         };
         Ok(snapshot)
@@ -2335,6 +6117,7 @@ impl IndexifyState {
                 .map_err(|e| StateMachineError::TransactionError(e.to_string()))
         }
 
+        let _write_guard = self.lock_writes();
         let txn = db.transaction();
 
         //  Build the rocksdb forward indexes
@@ -2358,9 +6141,27 @@ impl IndexifyState {
             let cf = StateMachineColumns::StateChanges.cf(db);
             put_cf(&txn, cf, state_change_id.to_key(), &state_change)?;
         }
-        for (content_id, content) in &snapshot.content_table {
+        for content in snapshot.content_table.values() {
             let cf = StateMachineColumns::ContentTable.cf(db);
-            put_cf(&txn, cf, &content_id.id, &content)?;
+            put_cf(
+                &txn,
+                cf,
+                content.id_key(&self.content_version_separator),
+                &content,
+            )?;
+        }
+        // Tombstoned roots keep the lookup entries they had as latest version,
+        // live latest versions are written last so they take precedence.
+        let lookup_roots = snapshot
+            .content_table
+            .values()
+            .filter(|content| content.parent_id.is_none() && (content.latest || content.tombstoned))
+            .sorted_by_key(|content| content.latest);
+        for content in lookup_roots {
+            let cf = StateMachineColumns::ContentLookupIndex.cf(db);
+            for key in content_lookup_keys(content) {
+                put_cf(&txn, cf, key, &content.id)?;
+            }
         }
         for (extraction_policy_id, extraction_policy_ids) in &snapshot.extraction_policies {
             let cf = StateMachineColumns::ExtractionPolicies.cf(db);
@@ -2386,9 +6187,53 @@ impl IndexifyState {
             let cf = StateMachineColumns::CoordinatorAddress.cf(db);
             put_cf(&txn, cf, &node_id.to_string(), &addr)?;
         }
+        for (extraction_graph_id, extraction_graph) in &snapshot.extraction_graphs {
+            let cf = StateMachineColumns::ExtractionGraphs.cf(db);
+            put_cf(&txn, cf, extraction_graph_id, &extraction_graph)?;
+        }
+        for (key, profile) in &snapshot.search_profiles {
+            let cf = StateMachineColumns::SearchProfiles.cf(db);
+            put_cf(&txn, cf, key, &profile)?;
+        }
+        for (key, record) in &snapshot.idempotency_keys {
+            let cf = StateMachineColumns::IdempotencyKeys.cf(db);
+            put_cf(&txn, cf, key, &record)?;
+        }
+        for (key, stats) in &snapshot.usage_stats {
+            let cf = StateMachineColumns::UsageStats.cf(db);
+            put_cf(&txn, cf, key, &stats)?;
+        }
+        for (key, record) in &snapshot.ingestion_keys {
+            let cf = StateMachineColumns::IngestionKeys.cf(db);
+            put_cf(&txn, cf, key, &record)?;
+        }
+        for (alias, namespace) in &snapshot.namespace_aliases {
+            let cf = StateMachineColumns::NamespaceAliases.cf(db);
+            put_cf(&txn, cf, alias, &namespace)?;
+        }
+        for (id, subscription) in &snapshot.subscriptions {
+            let cf = StateMachineColumns::Subscriptions.cf(db);
+            put_cf(&txn, cf, id, &subscription)?;
+        }
+        for (key, delivery) in &snapshot.pending_deliveries {
+            let cf = StateMachineColumns::PendingDeliveries.cf(db);
+            put_cf(&txn, cf, key, &delivery)?;
+        }
+        for (key, delivery) in &snapshot.dead_letter_deliveries {
+            let cf = StateMachineColumns::DeadLetterDeliveries.cf(db);
+            put_cf(&txn, cf, key, &delivery)?;
+        }
+        for (namespace, rules) in &snapshot.namespace_retention {
+            let cf = StateMachineColumns::NamespaceRetention.cf(db);
+            put_cf(&txn, cf, namespace, &rules)?;
+        }
+        for (key, feedback) in &snapshot.search_feedback {
+            let cf = StateMachineColumns::SearchFeedback.cf(db);
+            put_cf(&txn, cf, key, &feedback)?;
+        }
 
         //  Build the in-memory reverse indexes
-        let mut unassigned_tasks = self.unassigned_tasks.unassigned_tasks.write().unwrap();
+        let mut unassigned_tasks = self.unassigned_tasks.unassigned_tasks.write_all();
         let mut unprocessed_state_changes_guard = self
             .unprocessed_state_changes
             .unprocessed_state_changes
@@ -2397,8 +6242,8 @@ impl IndexifyState {
         let mut content_namespace_table_guard = self
             .content_namespace_table
             .content_namespace_table
-            .write()
-            .unwrap();
+            .write_all();
+        let mut content_by_source = self.content_by_source.content_by_source.write().unwrap();
         let mut extraction_policies_table = self
             .extraction_policies_table
             .extraction_policies_table
@@ -2434,32 +6279,53 @@ impl IndexifyState {
             .content_children_table
             .write()
             .unwrap();
+        //  Entries spilled before the snapshot would outlive it, a bounded
+        //  table is rebuilt from the content of the snapshot alone
+        if content_children_table.is_bounded() {
+            spilled::clear_spilled(db)?;
+            content_children_table.clear();
+        }
         let mut pending_tasks_for_content = self
             .pending_tasks_for_content
             .pending_tasks_for_content
             .write()
             .unwrap();
+        let mut root_task_counts = self.root_task_counts.write().unwrap();
+        let mut extraction_graphs_by_ns = self
+            .extraction_graphs_by_ns
+            .eg_by_namespace
+            .write()
+            .unwrap();
 
         for (task_id, task) in &snapshot.tasks {
             if !task.terminal_state() {
-                unassigned_tasks.insert(task_id.clone());
+                unassigned_tasks.shard(task_id).insert(task_id.clone());
             }
         }
         for task_ids in snapshot.task_assignments.values() {
             for task_id in task_ids {
-                unassigned_tasks.remove(task_id);
+                unassigned_tasks.shard(task_id).remove(task_id);
             }
         }
 
-        for state_change_id in snapshot.state_changes.keys() {
-            unprocessed_state_changes_guard.insert(*state_change_id);
+        for (state_change_id, state_change) in &snapshot.state_changes {
+            if state_change.processed_at.is_none() {
+                unprocessed_state_changes_guard.insert(*state_change_id);
+            }
         }
 
         for (content_id, content) in &snapshot.content_table {
             content_namespace_table_guard
+                .shard(content_id)
                 .entry(content.namespace.clone())
                 .or_default()
                 .insert(content_id.clone());
+            content_by_source
+                .entry(content.namespace.clone())
+                .or_default()
+                .entry(String::from(&content.source))
+                .or_default()
+                .insert(content_id.id.clone());
         }
 
         for (extraction_policy_id, extraction_policy) in &snapshot.extraction_policies {
@@ -2501,17 +6367,20 @@ impl IndexifyState {
         }
 
         for (schema_id, schema) in &snapshot.structured_data_schemas {
-            schemas_by_namespace
-                .entry(schema.namespace.clone())
-                .or_default()
-                .insert(schema_id.clone());
+            //  Schemas written with invalid ids before ids were validated are
+            //  left out of the index
+            if let Ok(schema_id) = SchemaId::new(schema_id) {
+                schemas_by_namespace
+                    .entry(schema.namespace.clone())
+                    .or_default()
+                    .insert(schema_id);
+            }
         }
 
         for (content_id, content) in &snapshot.content_table {
             if let Some(parent_id) = &content.parent_id {
                 content_children_table
-                    .entry(parent_id.clone())
-                    .or_default()
+                    .entry_or_default(parent_id.clone())
                     .insert(content_id.clone());
             }
         }
@@ -2531,6 +6400,43 @@ impl IndexifyState {
             }
         }
 
+        //  Unfinished tasks and unprocessed state changes each hold a reference to
+        //  their root content, as they do when they are applied
+        let referenced_roots = snapshot
+            .tasks
+            .values()
+            .filter(|task| !task.terminal_state())
+            .map(|task| task.content_metadata.get_root_id().to_string())
+            .chain(
+                snapshot
+                    .state_changes
+                    .values()
+                    .filter(|change| change.processed_at.is_none())
+                    .filter_map(|change| change.refcnt_object_id.clone()),
+            );
+        for root in referenced_roots {
+            root_task_counts
+                .entry(root)
+                .and_modify(|c| c.count += 1)
+                .or_insert(TaskCount {
+                    count: 1,
+                    notify: None,
+                });
+        }
+
+        for (extraction_graph_id, extraction_graph) in &snapshot.extraction_graphs {
+            extraction_graphs_by_ns
+                .entry(extraction_graph.namespace.clone())
+                .or_default()
+                .insert(extraction_graph_id.clone());
+        }
+
+        //  New state changes continue after the ones in the snapshot
+        if let Some(last_id) = snapshot.state_changes.keys().map(|id| u64::from(*id)).max() {
+            let mut change_id = self.change_id.lock().unwrap();
+            *change_id = (*change_id).max(last_id + 1);
+        }
+
         //  set the metrics
         *self.metrics.lock().unwrap() = snapshot.metrics;
 
@@ -2541,27 +6447,125 @@ impl IndexifyState {
     //  END SNAPSHOT METHODS
 }
 
+/// Sets are kept ordered so that building a snapshot twice from the same
+/// state produces the same bytes, which differential snapshot transfer relies
+/// on to find the tables a follower already has.
+/// Reads the keys with sequential multi gets of at most batch_size keys each,
+/// a single multi get of thousands of keys can stall RocksDB. The keys are
+/// read in a single multi get when batch_size is 0.
+fn multi_get_cf_chunked<'b, K, W>(
+    txn: &rocksdb::Transaction<OptimisticTransactionDB>,
+    keys: impl IntoIterator<Item = (&'b W, K)>,
+    batch_size: usize,
+) -> Vec<Result<Option<Vec<u8>>, rocksdb::Error>>
+where
+    K: AsRef<[u8]>,
+    W: rocksdb::AsColumnFamilyRef + 'b,
+{
+    if batch_size == 0 {
+        return txn.multi_get_cf(keys);
+    }
+    let mut values = Vec::new();
+    for batch in &keys.into_iter().chunks(batch_size) {
+        values.extend(txn.multi_get_cf(batch));
+    }
+    values
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
 pub struct IndexifyStateSnapshot {
     executors: HashMap<ExecutorId, internal_api::ExecutorMetadata>,
     tasks: HashMap<TaskId, internal_api::Task>,
     gc_tasks: HashMap<internal_api::GarbageCollectionTaskId, internal_api::GarbageCollectionTask>,
-    task_assignments: HashMap<ExecutorId, HashSet<TaskId>>,
+    task_assignments: HashMap<ExecutorId, BTreeSet<TaskId>>,
     state_changes: HashMap<StateChangeId, StateChange>,
     content_table: HashMap<ContentMetadataId, internal_api::ContentMetadata>,
     extraction_policies: HashMap<ExtractionPolicyId, ExtractionPolicy>,
     extractors: HashMap<ExtractorName, ExtractorDescription>,
-    namespaces: HashSet<NamespaceName>,
+    namespaces: BTreeSet<NamespaceName>,
     index_table: HashMap<String, internal_api::Index>,
     structured_data_schemas: HashMap<String, internal_api::StructuredDataSchema>,
     coordinator_address: HashMap<NodeId, String>,
     extraction_graphs: HashMap<ExtractionGraphId, ExtractionGraph>,
+    #[serde(default)]
+    search_profiles: HashMap<String, internal_api::SearchProfile>,
+    #[serde(default)]
+    idempotency_keys: HashMap<String, IdempotencyRecord>,
+    #[serde(default)]
+    usage_stats: HashMap<String, internal_api::UsageStats>,
+    #[serde(default)]
+    ingestion_keys: HashMap<String, IngestionKeyRecord>,
+    #[serde(default)]
+    namespace_aliases: HashMap<String, NamespaceName>,
+    #[serde(default)]
+    subscriptions: HashMap<String, internal_api::Subscription>,
+    #[serde(default)]
+    pending_deliveries: HashMap<String, internal_api::Delivery>,
+    #[serde(default)]
+    dead_letter_deliveries: HashMap<String, internal_api::Delivery>,
+    #[serde(default)]
+    namespace_retention: HashMap<NamespaceName, internal_api::RetentionRules>,
+    #[serde(default)]
+    search_feedback: HashMap<String, internal_api::SearchFeedback>,
     metrics: Metrics,
 }
 
 #[cfg(test)]
 mod tests {
+    use strum::IntoEnumIterator;
+
     use super::*;
+    use crate::{
+        server_config::DEFAULT_MULTI_GET_BATCH_SIZE,
+        state::store::{new_storage, requests::CreateOrUpdateContentEntry},
+        test_util::db_utils::{
+            create_test_extraction_graph,
+            mock_extractor,
+            test_mock_content_metadata,
+            DEFAULT_TEST_NAMESPACE,
+        },
+    };
+
+    fn dependency_graph(edges: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+        edges
+            .iter()
+            .map(|(policy_id, dependencies)| {
+                (
+                    policy_id.to_string(),
+                    dependencies.iter().map(|d| d.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_toposort_policies() {
+        //  embed extracts from the chunks of chunk, listed before it
+        let graph = dependency_graph(&[("embed", &["chunk"]), ("chunk", &[])]);
+        assert_eq!(toposort_policies(&graph).unwrap(), vec!["chunk", "embed"]);
+
+        let graph = dependency_graph(&[
+            ("summarize", &["ocr"]),
+            ("embed", &["chunk"]),
+            ("chunk", &["ocr"]),
+            ("ocr", &[]),
+            ("dangling", &["deleted"]),
+        ]);
+        assert_eq!(
+            toposort_policies(&graph).unwrap(),
+            vec!["dangling", "ocr", "chunk", "embed", "summarize"]
+        );
+    }
+
+    #[test]
+    fn test_toposort_policies_detects_cycles() {
+        let graph = dependency_graph(&[("a", &["b"]), ("b", &["a"]), ("c", &["b"]), ("d", &[])]);
+        let err = toposort_policies(&graph).unwrap_err();
+        assert!(err.to_string().ends_with("cycle: a, b, c"), "{}", err);
+
+        let graph = dependency_graph(&[("a", &["a"])]);
+        assert!(toposort_policies(&graph).is_err());
+    }
 
     #[test]
     fn test_increment_running_task_count() {
@@ -2584,4 +6588,1093 @@ mod tests {
         executor_running_task_count.decrement_running_task_count(&executor_id);
         assert_eq!(executor_running_task_count.get(&executor_id).unwrap(), 0);
     }
+
+    #[test]
+    fn test_mark_state_changes_processed_in_batch() {
+        let state = IndexifyState::default();
+        let state_changes: Vec<_> = (0..1000)
+            .map(|id| StateChangeProcessed {
+                state_change_id: StateChangeId::new(id),
+                processed_at: 0,
+            })
+            .collect();
+        for state_change in &state_changes {
+            state
+                .unprocessed_state_changes
+                .insert(state_change.state_change_id);
+        }
+        //  An unrelated change stays unprocessed
+        state
+            .unprocessed_state_changes
+            .insert(StateChangeId::new(1000));
+
+        let locks = &state.unprocessed_state_changes.write_locks;
+        let before = locks.load(std::sync::atomic::Ordering::Relaxed);
+        state
+            .update_reverse_indexes(StateMachineUpdateRequest {
+                payload: RequestPayload::MarkStateChangesProcessed { state_changes },
+                new_state_changes: vec![],
+                state_changes_processed: vec![],
+                idempotency_key: None,
+            })
+            .unwrap();
+        assert_eq!(locks.load(std::sync::atomic::Ordering::Relaxed) - before, 1);
+        assert_eq!(
+            state.get_unprocessed_state_changes(),
+            HashSet::from([StateChangeId::new(1000)])
+        );
+    }
+
+    /// Reads every row of a column family, values are compared decoded since
+    /// maps inside them may be encoded in any order
+    fn read_column(
+        db: &Arc<OptimisticTransactionDB>,
+        column: &StateMachineColumns,
+    ) -> Vec<(Vec<u8>, serde_json::Value)> {
+        db.iterator_cf(column.cf(db), rocksdb::IteratorMode::Start)
+            .map(|row| {
+                let (key, value) = row.unwrap();
+                (key.to_vec(), serde_json::from_slice(&value).unwrap())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_multi_get_cf_chunked() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/multi_get_cf_chunked";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let cf = StateMachineColumns::ContentTable.cf(&sm.db);
+        //  Every third key is missing
+        let keys = (0..5000).map(|i| format!("key_{}", i)).collect_vec();
+        for key in keys.iter().step_by(3) {
+            sm.db.put_cf(cf, key, format!("value_{}", key))?;
+        }
+
+        let txn = sm.db.transaction();
+        let values = multi_get_cf_chunked(&txn, keys.iter().map(|key| (cf, key)), 1000);
+        assert_eq!(values.len(), keys.len());
+        for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+            let expected = (i % 3 == 0).then(|| format!("value_{}", key).into_bytes());
+            assert_eq!(value?, expected);
+        }
+        assert_eq!(
+            multi_get_cf_chunked(&txn, keys.iter().map(|key| (cf, key)), 0)
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?,
+            multi_get_cf_chunked(&txn, keys.iter().map(|key| (cf, key)), 1000)
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_content_children_table_eviction() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/content_children_table_eviction";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let parent = |i: usize| ContentMetadataId::new(&format!("parent_{}", i));
+        let child = |i: usize, j: usize| ContentMetadataId::new(&format!("child_{}_{}", i, j));
+        let spilled = || read_column(&sm.db, &StateMachineColumns::SpilledReverseIndexes).len();
+        let in_memory = |table: &ContentChildrenTable| -> HashSet<ContentMetadataId> {
+            let guard = table.content_children_table.read().unwrap();
+            guard
+                .iter_in_memory()
+                .map(|(parent_id, _)| parent_id.clone())
+                .collect()
+        };
+
+        let table = ContentChildrenTable::default();
+        let mut expected: HashMap<ContentMetadataId, HashSet<ContentMetadataId>> = HashMap::new();
+        let mut insert = |table: &ContentChildrenTable, i: usize| {
+            for j in 0..2 {
+                table.insert(&parent(i), &child(i, j));
+                expected.entry(parent(i)).or_default().insert(child(i, j));
+            }
+        };
+        for i in 0..3 {
+            insert(&table, i);
+        }
+
+        //  Bounding the table spills the entries over the capacity
+        table.bound(sm.db.clone(), 2);
+        assert_eq!(in_memory(&table).len(), 2);
+        assert_eq!(spilled(), 1);
+        for i in 3..10 {
+            insert(&table, i);
+        }
+        assert_eq!(in_memory(&table), HashSet::from([parent(8), parent(9)]));
+        assert_eq!(spilled(), 8);
+        assert_eq!(table.size().entries, 4);
+        assert_eq!(table.inner(), expected);
+
+        //  A miss reads the entry back, evicting the least recently used one
+        assert_eq!(table.get_children(&parent(0)), expected[&parent(0)]);
+        assert_eq!(in_memory(&table), HashSet::from([parent(0), parent(9)]));
+        assert_eq!(spilled(), 8);
+
+        //  Writes to spilled entries
+        table.remove(&parent(1), &child(1, 0));
+        table.remove(&parent(1), &child(1, 1));
+        expected.remove(&parent(1));
+        table.remove_all(&parent(2));
+        expected.remove(&parent(2));
+        table.replace_parent(&parent(3), &parent(10));
+        let children = expected.remove(&parent(3)).unwrap();
+        expected.insert(parent(10), children);
+        table.insert(&parent(4), &child(4, 2));
+        expected.get_mut(&parent(4)).unwrap().insert(child(4, 2));
+
+        assert_eq!(in_memory(&table).len(), 2);
+        assert_eq!(in_memory(&table).len() + spilled(), expected.len());
+        assert_eq!(table.inner(), expected);
+        for (parent_id, children) in &expected {
+            assert_eq!(&table.get_children(parent_id), children);
+        }
+        assert!(table.get_children(&parent(1)).is_empty());
+        assert!(table.get_children(&parent(2)).is_empty());
+
+        //  The serialized table includes the spilled entries
+        let restored: ContentChildrenTable = serde_json::from_value(serde_json::to_value(&table)?)?;
+        assert_eq!(restored.inner(), expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_install_snapshot_into_bounded_content_children_table() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/install_snapshot_bounded_content_children_table";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+        let spilled = || read_column(&sm.db, &StateMachineColumns::SpilledReverseIndexes).len();
+
+        //  Entries from before the snapshot, most of them spilled
+        state.content_children_table.bound(sm.db.clone(), 2);
+        for i in 0..5 {
+            state.content_children_table.insert(
+                &ContentMetadataId::new(&format!("stale_parent_{}", i)),
+                &ContentMetadataId::new(&format!("stale_child_{}", i)),
+            );
+        }
+        assert_eq!(spilled(), 3);
+
+        let mut content_table = HashMap::new();
+        let mut expected: HashMap<ContentMetadataId, HashSet<ContentMetadataId>> = HashMap::new();
+        for i in 0..3 {
+            let parent = test_mock_content_metadata(&format!("parent_{}", i), "", "graph");
+            for j in 0..2 {
+                let mut child = test_mock_content_metadata(
+                    &format!("child_{}_{}", i, j),
+                    &parent.id.id,
+                    "graph",
+                );
+                child.parent_id = Some(parent.id.clone());
+                expected
+                    .entry(parent.id.clone())
+                    .or_default()
+                    .insert(child.id.clone());
+                content_table.insert(child.id.clone(), child);
+            }
+            content_table.insert(parent.id.clone(), parent);
+        }
+        state.install_snapshot(
+            &sm.db,
+            IndexifyStateSnapshot {
+                content_table,
+                ..Default::default()
+            },
+        )?;
+
+        //  Only the children of the snapshot are left, still bounded
+        let in_memory = state
+            .content_children_table
+            .content_children_table
+            .read()
+            .unwrap()
+            .iter_in_memory()
+            .count();
+        assert_eq!(in_memory, 2);
+        assert_eq!(spilled(), 1);
+        assert_eq!(state.content_children_table.inner(), expected);
+        for (parent_id, children) in &expected {
+            assert_eq!(
+                &state.content_children_table.get_children(parent_id),
+                children
+            );
+        }
+        assert!(state
+            .content_children_table
+            .get_children(&ContentMetadataId::new("stale_parent_0"))
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_content_from_ids_with_version() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/get_content_from_ids_with_version";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+        let cf = StateMachineColumns::ContentTable.cf(&sm.db);
+        let put = |content: &indexify_internal_api::ContentMetadata| {
+            sm.db.put_cf(
+                cf,
+                content.id_key(&state.content_version_separator),
+                JsonEncoder::encode(content).unwrap(),
+            )
+        };
+        let content =
+            |id: &str, version: u64, latest: bool| indexify_internal_api::ContentMetadata {
+                id: ContentMetadataId::new_with_version(id, version),
+                namespace: DEFAULT_TEST_NAMESPACE.into(),
+                latest,
+                ..Default::default()
+            };
+
+        //  Latest versions, previous versions, tombstoned content, versions
+        //  which don't exist and content which doesn't exist
+        let mut content_ids = Vec::new();
+        for i in 0..500 {
+            let id = format!("content_{}", i);
+            match i % 5 {
+                0 => {
+                    put(&content(&id, 1, true))?;
+                    content_ids.push(ContentMetadataId::new_with_version(&id, 1));
+                }
+                1 => {
+                    put(&content(&id, 1, false))?;
+                    put(&content(&id, 2, true))?;
+                    content_ids.push(ContentMetadataId::new_with_version(&id, 1));
+                }
+                2 => {
+                    let mut tombstoned = content(&id, 1, true);
+                    tombstoned.tombstoned = true;
+                    put(&tombstoned)?;
+                    content_ids.push(ContentMetadataId::new_with_version(&id, 1));
+                }
+                3 => {
+                    put(&content(&id, 1, true))?;
+                    content_ids.push(ContentMetadataId::new_with_version(&id, 3));
+                }
+                _ => content_ids.push(ContentMetadataId::new_with_version(&id, 1)),
+            }
+        }
+
+        //  Matches reading the versions one by one
+        let mut one_by_one = Vec::new();
+        for content_id in &content_ids {
+            let found = match state.get_content_by_id_and_version(&sm.db, content_id)? {
+                Some(content) => Some(content),
+                None => state
+                    .get_latest_version_of_content(&content_id.id, &sm.db, &sm.db.transaction())?
+                    .filter(|content| content.id == *content_id),
+            };
+            one_by_one.extend(found.filter(|content| !content.tombstoned));
+        }
+        let multi_get = state.get_content_from_ids_with_version(&content_ids, &sm.db)?;
+
+        assert_eq!(multi_get.len(), 200);
+        assert_eq!(multi_get, one_by_one);
+        assert!(multi_get
+            .iter()
+            .all(|content| content.id.version == 1 && !content.tombstoned));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_tasks_for_executor() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/get_tasks_for_executor";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+        let apply = |payload: RequestPayload| {
+            state.apply_state_machine_updates(
+                StateMachineUpdateRequest {
+                    payload,
+                    new_state_changes: vec![],
+                    state_changes_processed: vec![],
+                    idempotency_key: None,
+                },
+                &sm.db,
+            )
+        };
+        apply(RequestPayload::CreateNamespace {
+            name: DEFAULT_TEST_NAMESPACE.into(),
+            structured_data_schema: None,
+        })?;
+        let content = test_mock_content_metadata("content", "", "graph");
+        let tasks = (0..300)
+            .map(|i| internal_api::Task {
+                id: format!("task_{}", i),
+                namespace: DEFAULT_TEST_NAMESPACE.into(),
+                content_metadata: content.clone(),
+                ..Default::default()
+            })
+            .collect_vec();
+        apply(RequestPayload::CreateTasks {
+            tasks: tasks.clone(),
+        })?;
+        apply(RequestPayload::AssignTask {
+            assignments: tasks
+                .iter()
+                .map(|task| (task.id.clone(), "executor".to_string()))
+                .collect(),
+        })?;
+
+        //  Tasks are returned in the order of the assignments
+        let assigned: Vec<TaskId> = JsonEncoder::decode(
+            &sm.db
+                .get_cf(StateMachineColumns::TaskAssignments.cf(&sm.db), "executor")?
+                .unwrap(),
+        )?;
+        let ids =
+            |tasks: Vec<internal_api::Task>| tasks.into_iter().map(|task| task.id).collect_vec();
+        assert_eq!(
+            ids(state.get_tasks_for_executor("executor", None, &sm.db)?),
+            assigned
+        );
+        assert_eq!(
+            ids(state.get_tasks_for_executor("executor", Some(100), &sm.db)?),
+            assigned[..100]
+        );
+        assert_eq!(
+            ids(state.get_tasks_for_executor("executor", Some(1000), &sm.db)?),
+            assigned
+        );
+        assert!(state
+            .get_tasks_for_executor("other_executor", None, &sm.db)?
+            .is_empty());
+
+        //  Every missing task is reported
+        let tasks_cf = StateMachineColumns::Tasks.cf(&sm.db);
+        sm.db.delete_cf(tasks_cf, &assigned[10])?;
+        sm.db.delete_cf(tasks_cf, &assigned[20])?;
+        match state.get_tasks_for_executor("executor", None, &sm.db) {
+            Err(StateMachineError::AssignedTasksNotFound {
+                executor_id,
+                task_ids,
+            }) => {
+                assert_eq!(executor_id, "executor");
+                assert_eq!(task_ids, vec![assigned[10].clone(), assigned[20].clone()]);
+            }
+            result => panic!("expected missing tasks, got {:?}", result.map(ids)),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_get_tasks_for_executor_matches_single_gets() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/get_tasks_for_executor_single_gets";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+        let apply = |payload: RequestPayload| {
+            state.apply_state_machine_updates(
+                StateMachineUpdateRequest {
+                    payload,
+                    new_state_changes: vec![],
+                    state_changes_processed: vec![],
+                    idempotency_key: None,
+                },
+                &sm.db,
+            )
+        };
+        apply(RequestPayload::CreateNamespace {
+            name: DEFAULT_TEST_NAMESPACE.into(),
+            structured_data_schema: None,
+        })?;
+        let tasks = (0..100)
+            .map(|i| internal_api::Task {
+                id: format!("task_{}", i),
+                namespace: DEFAULT_TEST_NAMESPACE.into(),
+                content_metadata: test_mock_content_metadata(
+                    &format!("content_{}", i),
+                    "",
+                    "graph",
+                ),
+                ..Default::default()
+            })
+            .collect_vec();
+        apply(RequestPayload::CreateTasks {
+            tasks: tasks.clone(),
+        })?;
+        apply(RequestPayload::AssignTask {
+            assignments: tasks
+                .iter()
+                .map(|task| (task.id.clone(), "executor".to_string()))
+                .collect(),
+        })?;
+
+        let assigned: Vec<TaskId> = JsonEncoder::decode(
+            &sm.db
+                .get_cf(StateMachineColumns::TaskAssignments.cf(&sm.db), "executor")?
+                .unwrap(),
+        )?;
+        //  The tasks read one get at a time, like before multi gets were used
+        let single_gets = |limit: Option<usize>| -> Result<Vec<internal_api::Task>, String> {
+            let limit = limit.unwrap_or(assigned.len());
+            assigned
+                .iter()
+                .take(limit)
+                .map(|task_id| -> Result<internal_api::Task, String> {
+                    let task_bytes = sm
+                        .db
+                        .get_cf(StateMachineColumns::Tasks.cf(&sm.db), task_id)
+                        .unwrap()
+                        .ok_or_else(|| format!("Task {} not found", task_id))?;
+                    Ok(JsonEncoder::decode(&task_bytes).unwrap())
+                })
+                .collect()
+        };
+        for limit in [None, Some(0), Some(1), Some(50), Some(100), Some(150)] {
+            assert_eq!(
+                state.get_tasks_for_executor("executor", limit.map(|l| l as u64), &sm.db)?,
+                single_gets(limit).unwrap(),
+                "limit {:?}",
+                limit
+            );
+        }
+
+        //  A missing task fails with the same message
+        sm.db
+            .delete_cf(StateMachineColumns::Tasks.cf(&sm.db), "task_42")?;
+        assert_eq!(
+            state
+                .get_tasks_for_executor("executor", None, &sm.db)
+                .unwrap_err()
+                .to_string(),
+            single_gets(None).unwrap_err()
+        );
+        //  Tasks past the limit aren't read
+        let missing_position = assigned.iter().position(|id| id == "task_42").unwrap();
+        assert_eq!(
+            state.get_tasks_for_executor("executor", Some(missing_position as u64), &sm.db)?,
+            single_gets(Some(missing_position)).unwrap()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_remove_executor_requeues_its_tasks() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/remove_executor";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, sm) = new_storage(
+            format!("{}/db", path),
+            format!("{}/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &sm.data.indexify_state;
+        let apply = |payload: RequestPayload| {
+            state.apply_state_machine_updates(
+                StateMachineUpdateRequest {
+                    payload,
+                    new_state_changes: vec![],
+                    state_changes_processed: vec![],
+                    idempotency_key: None,
+                },
+                &sm.db,
+            )
+        };
+        apply(RequestPayload::CreateNamespace {
+            name: DEFAULT_TEST_NAMESPACE.into(),
+            structured_data_schema: None,
+        })?;
+        let extractor = mock_extractor();
+        apply(RequestPayload::RegisterExecutor {
+            addr: "localhost:8970".into(),
+            executor_id: "executor".into(),
+            extractors: vec![extractor.clone()],
+            ts_secs: 0,
+            capabilities: HashMap::new(),
+        })?;
+        let content = test_mock_content_metadata("content", "", "graph");
+        let task_ids: HashSet<TaskId> = (0..3).map(|i| format!("task_{}", i)).collect();
+        apply(RequestPayload::CreateTasks {
+            tasks: task_ids
+                .iter()
+                .map(|id| internal_api::Task {
+                    id: id.clone(),
+                    extractor: extractor.name.clone(),
+                    namespace: DEFAULT_TEST_NAMESPACE.into(),
+                    content_metadata: content.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+        })?;
+        apply(RequestPayload::AssignTask {
+            assignments: task_ids
+                .iter()
+                .map(|id| (id.clone(), "executor".to_string()))
+                .collect(),
+        })?;
+        assert!(state.get_unassigned_tasks().is_empty());
+        assert_eq!(state.get_executor_running_task_count()["executor"], 3);
+
+        apply(RequestPayload::RemoveExecutor {
+            executor_id: "executor".into(),
+        })?;
+        assert_eq!(state.get_unassigned_tasks(), task_ids);
+        assert!(!state
+            .get_extractor_executors_table()
+            .get(&extractor.name)
+            .is_some_and(|executors| executors.contains("executor")));
+        assert!(!state
+            .get_executor_running_task_count()
+            .contains_key("executor"));
+        assert!(state
+            .get_tasks_for_executor("executor", None, &sm.db)?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_snapshot_round_trips_all_state() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/snapshot_round_trip";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, source) = new_storage(
+            format!("{}/source/db", path),
+            format!("{}/source/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let state = &source.data.indexify_state;
+        let mut next_key = 0;
+        let mut apply = |payload: RequestPayload, new_state_changes: Vec<StateChange>| {
+            next_key += 1;
+            state.apply_state_machine_updates(
+                StateMachineUpdateRequest {
+                    payload,
+                    new_state_changes,
+                    state_changes_processed: vec![],
+                    idempotency_key: Some(format!("key_{}", next_key)),
+                },
+                &source.db,
+            )
+        };
+
+        //  Populate every column family and reverse index
+        apply(
+            RequestPayload::JoinCluster {
+                node_id: 1,
+                address: "localhost:8950".into(),
+                coordinator_addr: "localhost:8960".into(),
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::CreateNamespace {
+                name: DEFAULT_TEST_NAMESPACE.into(),
+                structured_data_schema: None,
+            },
+            vec![],
+        )?;
+        let eg = create_test_extraction_graph("graph", vec!["policy"]);
+        let policy = eg.extraction_policies[0].clone();
+        apply(
+            RequestPayload::CreateExtractionGraph {
+                extraction_graph: eg.clone(),
+                structured_data_schema: internal_api::StructuredDataSchema::new(
+                    &eg.name,
+                    &eg.namespace,
+                ),
+                indexes: vec![internal_api::Index {
+                    id: "index".into(),
+                    namespace: DEFAULT_TEST_NAMESPACE.into(),
+                    ..Default::default()
+                }],
+            },
+            vec![],
+        )?;
+        let executor_added = apply(
+            RequestPayload::RegisterExecutor {
+                addr: "localhost:8970".into(),
+                executor_id: "executor".into(),
+                extractors: vec![mock_extractor()],
+                ts_secs: 0,
+                capabilities: HashMap::new(),
+            },
+            vec![
+                StateChange::new(
+                    "executor".into(),
+                    internal_api::ChangeType::ExecutorAdded,
+                    0,
+                ),
+                StateChange::new_with_refcnt(
+                    "root".into(),
+                    internal_api::ChangeType::NewContent,
+                    0,
+                    "root".into(),
+                ),
+            ],
+        )?;
+        apply(
+            RequestPayload::MarkStateChangesProcessed {
+                state_changes: vec![StateChangeProcessed {
+                    state_change_id: executor_added.new_state_changes[0].id,
+                    processed_at: 1,
+                }],
+            },
+            vec![],
+        )?;
+
+        let root_v1 = test_mock_content_metadata("root", "", &eg.name);
+        apply(
+            RequestPayload::CreateOrUpdateContent {
+                entries: vec![CreateOrUpdateContentEntry {
+                    content: root_v1.clone(),
+                    previous_parent: None,
+                }],
+                ingestion_key: Some(IngestionKeyRecord {
+                    namespace: DEFAULT_TEST_NAMESPACE.into(),
+                    key: "ingestion_key".into(),
+                    content_id: root_v1.id.id.clone(),
+                    created: true,
+                    created_at: 0,
+                    expires_at: u64::MAX,
+                }),
+            },
+            vec![],
+        )?;
+        let root_v2 = internal_api::ContentMetadata {
+            id: ContentMetadataId::new_with_version("root", 2),
+            hash: "root_2".into(),
+            ..root_v1.clone()
+        };
+        let mut child = test_mock_content_metadata("child", "root", &eg.name);
+        child.parent_id = Some(root_v2.id.clone());
+        apply(
+            RequestPayload::CreateOrUpdateContent {
+                entries: vec![
+                    CreateOrUpdateContentEntry {
+                        content: internal_api::ContentMetadata {
+                            latest: false,
+                            ..root_v1.clone()
+                        },
+                        previous_parent: None,
+                    },
+                    CreateOrUpdateContentEntry {
+                        content: root_v2.clone(),
+                        previous_parent: None,
+                    },
+                    CreateOrUpdateContentEntry {
+                        content: child.clone(),
+                        previous_parent: None,
+                    },
+                ],
+                ingestion_key: None,
+            },
+            vec![],
+        )?;
+
+        let task = |id: &str, content: &internal_api::ContentMetadata| internal_api::Task {
+            id: id.into(),
+            extractor: policy.extractor.clone(),
+            extraction_policy_id: policy.id.clone(),
+            extraction_graph_name: eg.name.clone(),
+            namespace: DEFAULT_TEST_NAMESPACE.into(),
+            content_metadata: content.clone(),
+            ..Default::default()
+        };
+        apply(
+            RequestPayload::CreateTasks {
+                tasks: vec![
+                    task("task_1", &root_v2),
+                    task("task_2", &child),
+                    task("task_3", &child),
+                ],
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::AssignTask {
+                assignments: HashMap::from([
+                    ("task_1".into(), "executor".into()),
+                    ("task_2".into(), "executor".into()),
+                ]),
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::UpdateTask {
+                task: internal_api::Task {
+                    outcome: TaskOutcome::Success,
+                    ..task("task_2", &child)
+                },
+                executor_id: Some("executor".into()),
+                update_time: SystemTime::now(),
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::CreateOrAssignGarbageCollectionTask {
+                gc_tasks: vec![internal_api::GarbageCollectionTask::new(
+                    DEFAULT_TEST_NAMESPACE,
+                    root_v1.clone(),
+                    HashSet::new(),
+                    ServerTaskType::Delete,
+                )],
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::SetSearchProfile {
+                profile: internal_api::SearchProfile {
+                    namespace: DEFAULT_TEST_NAMESPACE.into(),
+                    name: "profile".into(),
+                    index: "index".into(),
+                    filters: vec![],
+                    k: None,
+                    min_score: None,
+                },
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::SetNamespaceAlias {
+                alias: "alias".into(),
+                namespace: DEFAULT_TEST_NAMESPACE.into(),
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::CreateSubscription {
+                subscription: internal_api::Subscription {
+                    id: "subscription".into(),
+                    namespace: DEFAULT_TEST_NAMESPACE.into(),
+                    url: "http://localhost/events".into(),
+                    ..Default::default()
+                },
+            },
+            vec![],
+        )?;
+        let delivery = internal_api::Delivery {
+            subscription_id: "subscription".into(),
+            payload: "{}".into(),
+            ..Default::default()
+        };
+        apply(
+            RequestPayload::EnqueueDeliveries {
+                deliveries: vec![delivery.clone(), delivery.clone()],
+            },
+            vec![],
+        )?;
+        apply(RequestPayload::DeadLetterDelivery { delivery }, vec![])?;
+        apply(
+            RequestPayload::SetNamespaceRetention {
+                namespace: DEFAULT_TEST_NAMESPACE.into(),
+                rules: internal_api::RetentionRules {
+                    max_age_secs: Some(30 * 24 * 60 * 60),
+                    max_items: None,
+                },
+            },
+            vec![],
+        )?;
+        apply(
+            RequestPayload::CreateSearchFeedback {
+                feedback: internal_api::SearchFeedback {
+                    id: "feedback".into(),
+                    namespace: DEFAULT_TEST_NAMESPACE.into(),
+                    query_hash: "query_hash".into(),
+                    content_id: "content".into(),
+                    chunk_id: None,
+                    verdict: internal_api::FeedbackVerdict::Positive,
+                    note: None,
+                    created_at: 1,
+                    actor: None,
+                },
+            },
+            vec![],
+        )?;
+
+        //  Install the snapshot into an empty store
+        let snapshot = state.build_snapshot(&source.db)?;
+        let snapshot: IndexifyStateSnapshot =
+            JsonEncoder::decode(&JsonEncoder::encode(&snapshot)?)?;
+        let (_, restored) = new_storage(
+            format!("{}/restored/db", path),
+            format!("{}/restored/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        restored
+            .data
+            .indexify_state
+            .install_snapshot(&restored.db, snapshot)?;
+
+        //  Columns which are not part of the snapshot, every other column has to
+        //  be populated above and restored
+        let excluded = [
+            //  Legacy, only deleted from
+            StateMachineColumns::ExtractionPoliciesAppliedOnContent.to_string(),
+            //  Records of the migrations run on this node
+            StateMachineColumns::Migrations.to_string(),
+            //  Entries evicted from the reverse indexes, rebuilt from the snapshot
+            StateMachineColumns::SpilledReverseIndexes.to_string(),
+        ];
+        for column in StateMachineColumns::iter() {
+            if excluded.contains(&column.to_string()) {
+                continue;
+            }
+            let rows = read_column(&source.db, &column);
+            assert!(!rows.is_empty(), "column {} is not populated", column);
+            assert_eq!(
+                read_column(&restored.db, &column),
+                rows,
+                "column {} differs after installing the snapshot",
+                column
+            );
+        }
+
+        //  Listing the fields makes adding a reverse index without restoring it
+        //  from the snapshot a compile error
+        let IndexifyState {
+            unassigned_tasks,
+            unprocessed_state_changes,
+            content_namespace_table,
+            content_by_source,
+            extraction_policies_table,
+            extractor_executors_table,
+            namespace_index_table,
+            unfinished_tasks_by_extractor,
+            executor_running_task_count,
+            schemas_by_namespace,
+            content_children_table,
+            pending_tasks_for_content,
+            root_task_counts,
+            metrics,
+            extraction_graphs_by_ns,
+            change_id,
+            value_size_limits: _,
+            multi_get_batch_size: _,
+            content_version_separator: _,
+            write_lock: _,
+            failpoints: _,
+        } = &restored.data.indexify_state;
+        let root_counts = |counts: &RwLock<HashMap<String, TaskCount>>| {
+            counts
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(root, count)| (root.clone(), count.count))
+                .collect::<HashMap<_, _>>()
+        };
+        assert_eq!(unassigned_tasks.inner(), state.unassigned_tasks.inner());
+        assert_eq!(
+            unprocessed_state_changes.inner(),
+            state.unprocessed_state_changes.inner()
+        );
+        assert_eq!(
+            content_namespace_table.inner(),
+            state.content_namespace_table.inner()
+        );
+        assert_eq!(content_by_source.inner(), state.content_by_source.inner());
+        assert_eq!(
+            extraction_policies_table.inner(),
+            state.extraction_policies_table.inner()
+        );
+        assert_eq!(
+            extractor_executors_table.inner(),
+            state.extractor_executors_table.inner()
+        );
+        assert_eq!(
+            namespace_index_table.inner(),
+            state.namespace_index_table.inner()
+        );
+        assert_eq!(
+            unfinished_tasks_by_extractor.inner(),
+            state.unfinished_tasks_by_extractor.inner()
+        );
+        assert_eq!(
+            executor_running_task_count.inner(),
+            state.executor_running_task_count.inner()
+        );
+        assert_eq!(
+            schemas_by_namespace.inner(),
+            state.schemas_by_namespace.inner()
+        );
+        assert_eq!(
+            content_children_table.inner(),
+            state.content_children_table.inner()
+        );
+        assert_eq!(
+            pending_tasks_for_content.inner(),
+            state.pending_tasks_for_content.inner()
+        );
+        assert_eq!(
+            root_counts(root_task_counts),
+            root_counts(&state.root_task_counts)
+        );
+        assert_eq!(
+            serde_json::to_value(&*metrics.lock().unwrap())?,
+            serde_json::to_value(&*state.metrics.lock().unwrap())?
+        );
+        assert_eq!(
+            extraction_graphs_by_ns.inner(),
+            state.extraction_graphs_by_ns.inner()
+        );
+        assert_eq!(*change_id.lock().unwrap(), *state.change_id.lock().unwrap());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_snapshot_install_is_exclusive_with_apply() -> anyhow::Result<()> {
+        let path = "/tmp/indexify-test/snapshot_install_exclusive";
+        let _ = std::fs::remove_dir_all(path);
+        let (_, source) = new_storage(
+            format!("{}/source/db", path),
+            format!("{}/source/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let (_, target) = new_storage(
+            format!("{}/target/db", path),
+            format!("{}/target/sm-blob", path),
+            ValueSizeLimitsConfig::default(),
+            DEFAULT_MULTI_GET_BATCH_SIZE,
+            ContentVersionSeparator::default(),
+        )
+        .await;
+        let apply = |sm: &StateMachineStore, payload: RequestPayload| {
+            sm.data.indexify_state.apply_state_machine_updates(
+                StateMachineUpdateRequest {
+                    payload,
+                    new_state_changes: vec![],
+                    state_changes_processed: vec![],
+                    idempotency_key: None,
+                },
+                &sm.db,
+            )
+        };
+        let create_content = |sm: &StateMachineStore, id: &str| {
+            apply(
+                sm,
+                RequestPayload::CreateOrUpdateContent {
+                    entries: vec![CreateOrUpdateContentEntry {
+                        content: test_mock_content_metadata(id, "", "graph"),
+                        previous_parent: None,
+                    }],
+                    ingestion_key: None,
+                },
+            )
+        };
+        for sm in [&source, &target] {
+            apply(
+                sm,
+                RequestPayload::CreateNamespace {
+                    name: DEFAULT_TEST_NAMESPACE.into(),
+                    structured_data_schema: None,
+                },
+            )?;
+        }
+        for i in 0..10 {
+            create_content(&source, &format!("snapshot_{}", i))?;
+        }
+
+        //  Snapshots are installed while requests are applied
+        std::thread::scope(|scope| -> anyhow::Result<()> {
+            let installer = scope.spawn(|| -> anyhow::Result<()> {
+                for _ in 0..20 {
+                    let snapshot = source.data.indexify_state.build_snapshot(&source.db)?;
+                    target
+                        .data
+                        .indexify_state
+                        .install_snapshot(&target.db, snapshot)?;
+                }
+                Ok(())
+            });
+            let applier = scope.spawn(|| -> anyhow::Result<()> {
+                for i in 0..50 {
+                    create_content(&target, &format!("applied_{}", i))?;
+                }
+                Ok(())
+            });
+            installer.join().expect("installing snapshots panicked")?;
+            applier.join().expect("applying requests panicked")?;
+            Ok(())
+        })?;
+
+        //  The reverse indexes hold every content of the forward index, none
+        //  was lost to an install interleaving with an apply
+        let state = &target.data.indexify_state;
+        let contents: HashSet<ContentMetadataId> = state
+            .get_all_rows_from_cf::<internal_api::ContentMetadata>(
+                StateMachineColumns::ContentTable,
+                &target.db,
+            )?
+            .into_iter()
+            .map(|(_, content)| content.id)
+            .collect();
+        assert_eq!(contents.len(), 60);
+        assert_eq!(
+            state
+                .content_namespace_table
+                .inner()
+                .get(DEFAULT_TEST_NAMESPACE)
+                .cloned()
+                .unwrap_or_default(),
+            contents
+        );
+        Ok(())
+    }
 }
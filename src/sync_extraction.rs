@@ -0,0 +1,211 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use indexify_internal_api as internal_api;
+use indexify_proto::indexify_coordinator::ContentTaskAssignment;
+use tracing::warn;
+
+use crate::{
+    api::{self, BeginExtractedContentIngest},
+    extractor_router::ExtractorRouter,
+    ingest_extracted_content::IngestExtractedContentState,
+    server::NamespaceEndpointState,
+};
+
+/// Interval at which the coordinator is asked whether the tasks of a text
+/// are assigned to executors yet
+const ASSIGNMENT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Outcome of extracting texts synchronously
+#[derive(Debug, PartialEq)]
+pub enum SyncExtraction {
+    /// Ids of the content derived from each text, keyed by the id of the text
+    Extracted(HashMap<String, Vec<String>>),
+    /// Reason the texts were handed over to the asynchronous pipeline
+    Fallback(String),
+}
+
+/// Response of the executor a task is assigned to
+struct TaskOutput {
+    assignment: ContentTaskAssignment,
+    response: internal_api::ExtractResponse,
+    execution_time_ms: u64,
+}
+
+/// Adds texts and extracts them synchronously. The scheduler creates the
+/// tasks of the texts and assigns them to executors like for any content,
+/// but the coordinator holds them back from executor heartbeats. Each task is
+/// run by asking the executor it is assigned to to extract the text, and its
+/// outputs are ingested the way the executor would have, so the records
+/// written are the same as on the asynchronous path. Content derived from the
+/// texts is extracted asynchronously.
+///
+/// When the tasks don't complete within the deadline or an executor fails,
+/// the tasks are released to the executors and extraction carries on
+/// asynchronously. Returns the ids of the texts along with the outcome.
+pub async fn add_texts(
+    state: &NamespaceEndpointState,
+    namespace: &str,
+    texts: Vec<api::ContentWithId>,
+    extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
+    deadline: Duration,
+) -> Result<(Vec<String>, SyncExtraction)> {
+    let text_ids: Vec<String> = texts.iter().map(|text| text.id.clone()).collect();
+    //  The hold outlasts the deadline, it only expires on its own when the
+    //  server fails before releasing the tasks
+    state
+        .data_manager
+        .hold_content_tasks(&text_ids, deadline * 2)
+        .await?;
+    let res = add_and_extract(state, namespace, texts, extraction_graph_names, deadline).await;
+    if let Err(e) = state
+        .data_manager
+        .hold_content_tasks(&text_ids, Duration::ZERO)
+        .await
+    {
+        warn!(
+            "unable to release the tasks of texts {}: {}",
+            text_ids.join(", "),
+            e
+        );
+    }
+    res
+}
+
+async fn add_and_extract(
+    state: &NamespaceEndpointState,
+    namespace: &str,
+    texts: Vec<api::ContentWithId>,
+    extraction_graph_names: Vec<internal_api::ExtractionGraphName>,
+    deadline: Duration,
+) -> Result<(Vec<String>, SyncExtraction)> {
+    let text_ids: Vec<String> = texts.iter().map(|text| text.id.clone()).collect();
+    let contents: Vec<api::Content> = texts.iter().map(|text| text.content.clone()).collect();
+    let content_ids = state
+        .data_manager
+        .add_texts(namespace, texts, extraction_graph_names)
+        .await?;
+    if content_ids != text_ids {
+        return Ok((
+            content_ids,
+            SyncExtraction::Fallback(
+                "texts were added before with the same idempotency keys".to_string(),
+            ),
+        ));
+    }
+    let outputs = match tokio::time::timeout(deadline, run_tasks(state, &text_ids, &contents)).await
+    {
+        Ok(Ok(outputs)) => outputs,
+        Ok(Err(e)) => {
+            return Ok((
+                content_ids,
+                SyncExtraction::Fallback(format!(
+                    "synchronous extraction failed: {}, continuing asynchronously",
+                    e
+                )),
+            ))
+        }
+        Err(_) => {
+            return Ok((
+                content_ids,
+                SyncExtraction::Fallback(format!(
+                    "extraction did not complete within {}ms, continuing asynchronously",
+                    deadline.as_millis()
+                )),
+            ))
+        }
+    };
+    //  The outputs are written even past the deadline, the tasks are held
+    //  until then so executors don't run them again
+    let mut derived_content_ids = HashMap::new();
+    for (text_id, task_outputs) in text_ids.iter().zip(outputs) {
+        let mut derived = Vec::new();
+        for output in task_outputs {
+            let task_id = output
+                .assignment
+                .task
+                .as_ref()
+                .map(|task| task.id.clone())
+                .unwrap_or_default();
+            match ingest(state, output).await {
+                Ok(ids) => derived.extend(ids),
+                Err(e) => {
+                    return Ok((
+                        content_ids,
+                        SyncExtraction::Fallback(format!(
+                            "unable to write the outputs of task {}: {}, continuing asynchronously",
+                            task_id, e
+                        )),
+                    ))
+                }
+            }
+        }
+        derived_content_ids.insert(text_id.clone(), derived);
+    }
+    Ok((content_ids, SyncExtraction::Extracted(derived_content_ids)))
+}
+
+/// Waits for the tasks of each text to be assigned and asks the executors
+/// they are assigned to to run them
+async fn run_tasks(
+    state: &NamespaceEndpointState,
+    text_ids: &[String],
+    contents: &[api::Content],
+) -> Result<Vec<Vec<TaskOutput>>> {
+    let extractor_router = ExtractorRouter::new(state.coordinator_client.clone())?;
+    let mut outputs = Vec::new();
+    for (text_id, content) in text_ids.iter().zip(contents) {
+        let assignments = loop {
+            if let Some(assignments) = state.data_manager.content_task_assignments(text_id).await? {
+                break assignments;
+            }
+            tokio::time::sleep(ASSIGNMENT_POLL_INTERVAL).await;
+        };
+        let mut task_outputs = Vec::new();
+        for assignment in assignments {
+            let task = assignment
+                .task
+                .as_ref()
+                .ok_or_else(|| anyhow!("assignment of text {} has no task", text_id))?;
+            let input_params = serde_json::from_str(&task.input_params)?;
+            let start = Instant::now();
+            let response = extractor_router
+                .extract_content_with_executor(
+                    &assignment.executor_addr,
+                    &task.extractor,
+                    content.clone(),
+                    Some(input_params),
+                )
+                .await?;
+            task_outputs.push(TaskOutput {
+                assignment,
+                response,
+                execution_time_ms: start.elapsed().as_millis() as u64,
+            });
+        }
+        outputs.push(task_outputs);
+    }
+    Ok(outputs)
+}
+
+/// Ingests the outputs of a task and marks it as completed, returns the ids of
+/// the content written
+async fn ingest(state: &NamespaceEndpointState, output: TaskOutput) -> Result<Vec<String>> {
+    let task = output
+        .assignment
+        .task
+        .ok_or_else(|| anyhow!("assignment has no task"))?;
+    let payload = BeginExtractedContentIngest {
+        task_id: task.id,
+        executor_id: output.assignment.executor_id,
+        task_outcome: internal_api::TaskOutcome::Success,
+        policy_version: None,
+        execution_time_ms: Some(output.execution_time_ms),
+    };
+    IngestExtractedContentState::new(state.clone())
+        .ingest_extract_response(payload, output.response)
+        .await
+}
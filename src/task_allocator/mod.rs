@@ -1,9 +1,13 @@
 use std::collections::HashSet;
 
 use anyhow::Result;
+use indexify_internal_api as internal_api;
 
-use self::planner::plan::TaskAllocationPlan;
-use crate::state::{store::TaskId, SharedState};
+use self::planner::{plan::TaskAllocationPlan, SchedulingExplanation};
+use crate::state::{
+    store::{StateMachineColumns, TaskId},
+    SharedState,
+};
 
 pub mod planner;
 
@@ -40,4 +44,25 @@ impl TaskAllocator {
             .await?;
         self.planner.plan_allocations(task_ids).await
     }
+
+    /// Explains why the task is or isn't assigned to an executor, returns
+    /// None when there is no task with the id.
+    pub async fn explain_task(&self, task_id: &str) -> Result<Option<SchedulingExplanation>> {
+        let Some(task) = self
+            .shared_state
+            .state_machine
+            .get_from_cf::<internal_api::Task, _>(StateMachineColumns::Tasks, task_id)?
+        else {
+            return Ok(None);
+        };
+        if task.terminal_state() {
+            return Ok(Some(SchedulingExplanation::Finished {
+                outcome: task.outcome,
+            }));
+        }
+        if let Some(executor_id) = self.shared_state.task_assignments().await?.remove(task_id) {
+            return Ok(Some(SchedulingExplanation::Assigned { executor_id }));
+        }
+        self.planner.explain_allocation(&task).await.map(Some)
+    }
 }
@@ -3,10 +3,18 @@ use std::{
     collections::{BinaryHeap, HashMap, HashSet},
 };
 
-use indexify_internal_api::ExecutorMetadata;
+use indexify_internal_api::{self as internal_api, ExecutorMetadata, ExtractorDescription};
 use tracing::error;
 
-use super::{plan::TaskAllocationPlan, AllocationPlanner, AllocationPlannerResult};
+use super::{
+    plan::TaskAllocationPlan,
+    AllocationPlanner,
+    AllocationPlannerResult,
+    CandidateEvaluation,
+    CandidateRejection,
+    SchedulingExplanation,
+    UnmetRequirement,
+};
 use crate::state::{
     store::{ExecutorId, ExtractorName, StateMachineColumns, TaskId},
     SharedState,
@@ -122,36 +130,69 @@ impl LoadAwareDistributor {
         result
     }
 
-    /// This method creates a mapping from extractor names to min-heaps
-    /// (priority queues) of executors, sorted by their current load.
-    ///
-    /// The load of an executor is determined by the number of tasks it is
-    /// currently running, allowing for efficient selection of the least loaded
-    /// executor for task allocation. "Pop"-ing from the heap will yield the
-    /// executor with the least load, and "push"-ing an updated
-    /// load back into the heap will maintain the min-heap property.
-    ///
-    /// # Returns
-    /// Returns a `HashMap` where each key is a `String` representing the
-    /// extractor name, and each value is a `BinaryHeap<Reverse<ExecutorLoad>>`
-    /// representing the priority queue of executors by their load for that
-    /// extractor.
+    /// Returns true if the executor serves the extractor and advertises every
+    /// capability the extractor requires. Numeric requirements such as memory
+    /// are treated as minimums, all other requirements must match exactly.
+    pub fn executor_can_run(
+        &self,
+        executor: &ExecutorMetadata,
+        extractor: &ExtractorDescription,
+    ) -> bool {
+        self.reject_executor(executor, extractor).is_none()
+    }
+
+    /// Returns why the tasks of the extractor can't be assigned to the
+    /// executor, or None when they can.
+    pub fn reject_executor(
+        &self,
+        executor: &ExecutorMetadata,
+        extractor: &ExtractorDescription,
+    ) -> Option<CandidateRejection> {
+        if !executor.extractors.iter().any(|e| e.name == extractor.name) {
+            return Some(CandidateRejection::ExtractorNotServed);
+        }
+        let mut unmet: Vec<UnmetRequirement> = extractor
+            .requirements
+            .iter()
+            .filter(|(key, required)| match executor.capabilities.get(*key) {
+                Some(available) => match (available.parse::<f64>(), required.parse::<f64>()) {
+                    (Ok(available), Ok(required)) => available < required,
+                    _ => available != *required,
+                },
+                None => true,
+            })
+            .map(|(key, required)| UnmetRequirement {
+                name: key.clone(),
+                required: required.clone(),
+                available: executor.capabilities.get(key).cloned(),
+            })
+            .collect();
+        if unmet.is_empty() {
+            return None;
+        }
+        unmet.sort_by(|a, b| a.name.cmp(&b.name));
+        Some(CandidateRejection::UnmetRequirements {
+            requirements: unmet,
+        })
+    }
+
+    /// Evaluates every executor for each extractor it runs, recording the
+    /// current load of the executor and why the planner rejects it, if it
+    /// does.
     ///
     /// # Errors
     /// Logs an error if an executor referenced in the running task count is not
     /// found in the executors table, indicating a potential inconsistency
     /// in the application's state management.
-    async fn initialize_executor_load_min_heaps_by_extractor(
+    async fn evaluate_candidates_by_extractor(
         &self,
-    ) -> HashMap<ExtractorName, MinHeap<ExecutorLoad>> {
-        let mut executors_load_min_heap: HashMap<ExtractorName, MinHeap<ExecutorLoad>> =
+    ) -> HashMap<ExtractorName, Vec<CandidateEvaluation>> {
+        let mut candidates_by_extractor: HashMap<ExtractorName, Vec<CandidateEvaluation>> =
             HashMap::new();
         // Retrieve the current running task count for each executor from the shared
         // state.
         let executor_running_task_count = self.shared_state.get_executor_running_task_count().await;
 
-        // Populate the executors' load heap for each extractor based on the current
-        // running tasks.
         for executor_id in executor_running_task_count.keys() {
             let executor = self
                 .shared_state
@@ -166,27 +207,31 @@ impl LoadAwareDistributor {
                 .unwrap_or(None);
             match executor {
                 Some(executor) => {
-                    let extractor_names = executor
-                        .extractors
-                        .into_iter()
-                        .map(|e| e.name)
-                        .collect::<Vec<String>>();
-
                     let running_task_count = executor_running_task_count
                         .get(executor_id)
                         .cloned()
                         .unwrap_or_default() as usize;
-
-                    // Update or create the heap for the extractor and add the executor's load.
-                    for extractor_name in extractor_names {
-                        executors_load_min_heap
-                            .entry(extractor_name)
+                    for e in &executor.extractors {
+                        // Requirements are read from the registered extractor description,
+                        // falling back to the one reported by the executor.
+                        let extractor = self
+                            .shared_state
+                            .state_machine
+                            .get_from_cf::<ExtractorDescription, _>(
+                                StateMachineColumns::Extractors,
+                                &e.name,
+                            )
+                            .ok()
+                            .flatten()
+                            .unwrap_or_else(|| e.clone());
+                        candidates_by_extractor
+                            .entry(e.name.clone())
                             .or_default()
-                            // use `Reverse` here to make it a min-heap
-                            .push(Reverse(ExecutorLoad {
+                            .push(CandidateEvaluation {
                                 executor_id: executor_id.clone(),
                                 running_task_count,
-                            }));
+                                rejection: self.reject_executor(&executor, &extractor),
+                            });
                     }
                 }
                 None => {
@@ -199,6 +244,44 @@ impl LoadAwareDistributor {
                 }
             }
         }
+        candidates_by_extractor
+    }
+
+    /// This method creates a mapping from extractor names to min-heaps
+    /// (priority queues) of executors, sorted by their current load.
+    ///
+    /// The load of an executor is determined by the number of tasks it is
+    /// currently running, allowing for efficient selection of the least loaded
+    /// executor for task allocation. "Pop"-ing from the heap will yield the
+    /// executor with the least load, and "push"-ing an updated
+    /// load back into the heap will maintain the min-heap property. Executors
+    /// rejected by `evaluate_candidates_by_extractor` are left out.
+    ///
+    /// # Returns
+    /// Returns a `HashMap` where each key is a `String` representing the
+    /// extractor name, and each value is a `BinaryHeap<Reverse<ExecutorLoad>>`
+    /// representing the priority queue of executors by their load for that
+    /// extractor.
+    async fn initialize_executor_load_min_heaps_by_extractor(
+        &self,
+    ) -> HashMap<ExtractorName, MinHeap<ExecutorLoad>> {
+        let mut executors_load_min_heap: HashMap<ExtractorName, MinHeap<ExecutorLoad>> =
+            HashMap::new();
+        for (extractor_name, candidates) in self.evaluate_candidates_by_extractor().await {
+            for candidate in candidates {
+                if candidate.rejection.is_some() {
+                    continue;
+                }
+                executors_load_min_heap
+                    .entry(extractor_name.clone())
+                    .or_default()
+                    // use `Reverse` here to make it a min-heap
+                    .push(Reverse(ExecutorLoad {
+                        executor_id: candidate.executor_id,
+                        running_task_count: candidate.running_task_count,
+                    }));
+            }
+        }
         executors_load_min_heap
     }
 }
@@ -285,6 +368,41 @@ impl AllocationPlanner for LoadAwareDistributor {
 
         Ok(plan)
     }
+
+    /// Evaluates the executors of the extractor of the task like
+    /// `plan_allocations`, the task would be assigned to the least loaded
+    /// executor which isn't rejected.
+    async fn explain_allocation(
+        &self,
+        task: &internal_api::Task,
+    ) -> Result<SchedulingExplanation, anyhow::Error> {
+        let mut candidates = self
+            .evaluate_candidates_by_extractor()
+            .await
+            .remove(&task.extractor)
+            .unwrap_or_default();
+        if candidates.is_empty() {
+            return Ok(SchedulingExplanation::NoExecutors {
+                extractor: task.extractor.clone(),
+            });
+        }
+        candidates.sort_by(|a, b| a.executor_id.cmp(&b.executor_id));
+        let executor_id = candidates
+            .iter()
+            .filter(|c| c.rejection.is_none())
+            .min_by_key(|c| c.running_task_count)
+            .map(|c| c.executor_id.clone());
+        Ok(match executor_id {
+            Some(executor_id) => SchedulingExplanation::Assignable {
+                executor_id,
+                candidates,
+            },
+            None => SchedulingExplanation::NoEligibleExecutors {
+                extractor: task.extractor.clone(),
+                candidates,
+            },
+        })
+    }
 }
 
 #[cfg(test)]
@@ -299,7 +417,7 @@ mod tests {
     use crate::{
         server_config::ServerConfig,
         state::App,
-        test_util::db_utils::{mock_extractor, mock_extractors},
+        test_util::db_utils::{mock_extractor, mock_extractors, DEFAULT_TEST_NAMESPACE},
     };
 
     fn create_task(
@@ -318,8 +436,12 @@ mod tests {
             namespace: "default".to_string(),
             content_metadata: content,
             input_params: json!(null),
+            extraction_policy_version: 1,
+            resolved_policy_version: None,
             outcome: internal_api::TaskOutcome::Unknown,
             index_tables: vec![],
+            output_content_ids: vec![],
+            usage: internal_api::TaskUsage::default(),
         }
     }
 
@@ -437,6 +559,9 @@ mod tests {
         .await
         .unwrap();
         shared_state.initialize_raft().await.unwrap();
+        shared_state
+            .create_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?;
 
         // Add extractors and extractor bindings and ensure that we are creating tasks
         shared_state
@@ -475,6 +600,86 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_allocate_task_requires_capabilities() -> Result<(), anyhow::Error> {
+        let config = Arc::new(ServerConfig::default());
+        let _ = std::fs::remove_dir_all(config.state_store.clone().path.unwrap());
+        let garbage_collector = crate::garbage_collector::GarbageCollector::new();
+        let shared_state = App::new(
+            config.clone(),
+            None,
+            Arc::clone(&garbage_collector),
+            &config.coordinator_addr,
+            Arc::new(crate::metrics::init_provider()),
+        )
+        .await
+        .unwrap();
+        shared_state.initialize_raft().await.unwrap();
+        shared_state
+            .create_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?;
+
+        let gpu_extractor = {
+            let mut extractor = mock_extractor();
+            extractor.name = "MockGpuExtractor".to_string();
+            extractor.requirements = HashMap::from([("gpu".to_string(), "true".to_string())]);
+            extractor
+        };
+
+        //  Both executors serve the extractor but only one has a GPU
+        shared_state
+            .register_executor(
+                "localhost:8956",
+                "cpu_executor",
+                vec![gpu_extractor.clone()],
+            )
+            .await?;
+        shared_state
+            .register_executor_with_capabilities(
+                "localhost:8957",
+                "gpu_executor",
+                vec![gpu_extractor.clone()],
+                HashMap::from([("gpu".to_string(), "true".to_string())]),
+            )
+            .await?;
+
+        let mut tasks = Vec::new();
+        let mut content = Vec::new();
+        for i in 1..=5 {
+            let content_metadata = ContentMetadata {
+                id: ContentMetadataId::new(&format!("content_id_{}", i)),
+                ..Default::default()
+            };
+            tasks.push(create_task(
+                &format!("test-gpu-task-{}", i),
+                "MockGpuExtractor",
+                "MockGpuExtractionGraph",
+                "gpu-binding",
+                content_metadata.clone(),
+            ));
+            content.push(content_metadata);
+        }
+        shared_state.create_content_batch(content).await?;
+        let state_changes = shared_state.unprocessed_state_change_events().await?;
+        shared_state
+            .create_tasks(tasks.clone(), state_changes.last().unwrap().id)
+            .await?;
+
+        let distributor = LoadAwareDistributor::new(shared_state.clone());
+        let result = distributor
+            .plan_allocations(tasks.iter().map(|t| t.id.clone()).collect())
+            .await?;
+
+        assert_eq!(result.0.len(), tasks.len());
+        assert!(result
+            .0
+            .values()
+            .all(|executor_id| executor_id == "gpu_executor"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_round_robin_distribution() -> Result<(), anyhow::Error> {
@@ -491,6 +696,9 @@ mod tests {
         .await
         .unwrap();
         shared_state.initialize_raft().await.unwrap();
+        shared_state
+            .create_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?;
 
         let text_extractor = {
             let mut extractor = mock_extractor();
@@ -728,6 +936,9 @@ mod tests {
         .await
         .unwrap();
         shared_state.initialize_raft().await.unwrap();
+        shared_state
+            .create_namespace(DEFAULT_TEST_NAMESPACE)
+            .await?;
 
         let text_extractor = {
             let mut extractor = mock_extractor();
@@ -2,13 +2,74 @@ pub mod load_aware_distributor;
 pub mod plan;
 use std::collections::HashSet;
 
+use indexify_internal_api as internal_api;
 use plan::TaskAllocationPlan;
+use serde::Serialize;
 
-use crate::state::store::TaskId;
+use crate::state::store::{ExecutorId, ExtractorName, TaskId};
 
 pub type AllocationPlannerResult = Result<TaskAllocationPlan, anyhow::Error>;
 
+/// A capability required by an extractor which an executor doesn't advertise
+/// or advertises with a different or, for numeric capabilities, lower value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnmetRequirement {
+    pub name: String,
+    pub required: String,
+    pub available: Option<String>,
+}
+
+/// Why the planner doesn't assign the tasks of an extractor to an executor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum CandidateRejection {
+    /// The executor doesn't run the extractor
+    ExtractorNotServed,
+    /// The executor doesn't meet the requirements of the extractor
+    UnmetRequirements { requirements: Vec<UnmetRequirement> },
+}
+
+/// An executor running an extractor, as evaluated by the planner
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CandidateEvaluation {
+    pub executor_id: ExecutorId,
+    pub running_task_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection: Option<CandidateRejection>,
+}
+
+/// Why a task is or isn't assigned to an executor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SchedulingExplanation {
+    /// The task finished, finished tasks aren't assigned
+    Finished { outcome: internal_api::TaskOutcome },
+    /// The task is assigned to the executor
+    Assigned { executor_id: ExecutorId },
+    /// No executor runs the extractor of the task
+    NoExecutors { extractor: ExtractorName },
+    /// Executors run the extractor of the task but the planner rejects all
+    /// of them
+    NoEligibleExecutors {
+        extractor: ExtractorName,
+        candidates: Vec<CandidateEvaluation>,
+    },
+    /// The planner would assign the task to the executor the next time it
+    /// allocates the task
+    Assignable {
+        executor_id: ExecutorId,
+        candidates: Vec<CandidateEvaluation>,
+    },
+}
+
 #[async_trait::async_trait]
 pub trait AllocationPlanner {
     async fn plan_allocations(&self, tasks: HashSet<TaskId>) -> AllocationPlannerResult;
+
+    /// Evaluates the executors for an unassigned task like plan_allocations
+    /// would, without changing anything
+    async fn explain_allocation(
+        &self,
+        task: &internal_api::Task,
+    ) -> Result<SchedulingExplanation, anyhow::Error>;
 }
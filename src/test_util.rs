@@ -73,6 +73,9 @@ pub mod db_utils {
                     "test_table".to_string(),
                 )]),
                 content_source: internal_api::ExtractionPolicyContentSource::Ingestion,
+                index_attributes: Default::default(),
+                version: 1,
+                version_strategy: Default::default(),
             };
             extraction_policies.push(ep);
         }
@@ -120,6 +123,9 @@ pub mod db_utils {
                         )
                     }
                 },
+                index_attributes: Default::default(),
+                version: 1,
+                version_strategy: Default::default(),
             };
             extraction_policies.push(ep);
         }
@@ -145,6 +151,7 @@ pub mod db_utils {
             input_params: json!({}),
             outputs,
             input_mime_types: vec!["*/*".to_string()],
+            requirements: HashMap::new(),
         }
     }
 
@@ -255,3 +262,244 @@ pub mod db_utils {
         Ok(())
     }
 }
+
+/// Harness to script scheduling scenarios against a coordinator whose clock
+/// only moves when the scenario says so.
+#[cfg(test)]
+pub mod simulation {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fs,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    };
+
+    use indexify_internal_api as internal_api;
+
+    use super::db_utils::DEFAULT_TEST_NAMESPACE;
+    use crate::{
+        coordinator::Coordinator,
+        coordinator_client::CoordinatorClient,
+        garbage_collector::GarbageCollector,
+        server_config::ServerConfig,
+        state::{
+            store::{ExecutorId, TaskId},
+            App,
+        },
+        utils::Clock,
+    };
+
+    /// Clock which only moves when it is set explicitly.
+    #[derive(Debug, Default)]
+    pub struct ManualClock {
+        now: AtomicU64,
+    }
+
+    impl ManualClock {
+        pub fn set(&self, now: u64) {
+            self.now.store(now, Ordering::SeqCst);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now_secs(&self) -> u64 {
+            self.now.load(Ordering::SeqCst)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub enum Event {
+        RegisterExecutor {
+            executor_id: ExecutorId,
+            extractors: Vec<internal_api::ExtractorDescription>,
+        },
+        RemoveExecutor(ExecutorId),
+        CreateExtractionGraph(internal_api::ExtractionGraph),
+        CreateContent(internal_api::ContentMetadata),
+        CheckExtractorAvailability,
+    }
+
+    /// Task assignments after the scheduler ran on the events of `time`.
+    #[derive(Debug, Clone)]
+    pub struct Snapshot {
+        pub time: u64,
+        pub assignments: HashMap<TaskId, ExecutorId>,
+        pub unassigned: usize,
+    }
+
+    impl Snapshot {
+        pub fn tasks_by_executor(&self) -> HashMap<ExecutorId, usize> {
+            let mut counts = HashMap::new();
+            for executor_id in self.assignments.values() {
+                *counts.entry(executor_id.clone()).or_default() += 1;
+            }
+            counts
+        }
+    }
+
+    #[derive(Default)]
+    pub struct ScenarioBuilder {
+        config: ServerConfig,
+        events: BTreeMap<u64, Vec<Event>>,
+    }
+
+    impl ScenarioBuilder {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn config(mut self, config: ServerConfig) -> Self {
+            self.config = config;
+            self
+        }
+
+        pub fn at(mut self, time: u64, event: Event) -> Self {
+            self.events.entry(time).or_default().push(event);
+            self
+        }
+
+        pub fn executor(
+            self,
+            time: u64,
+            executor_id: &str,
+            extractors: Vec<internal_api::ExtractorDescription>,
+        ) -> Self {
+            self.at(
+                time,
+                Event::RegisterExecutor {
+                    executor_id: executor_id.to_string(),
+                    extractors,
+                },
+            )
+        }
+
+        pub fn executor_dies(self, time: u64, executor_id: &str) -> Self {
+            self.at(time, Event::RemoveExecutor(executor_id.to_string()))
+        }
+
+        pub fn extraction_graph(self, time: u64, graph: internal_api::ExtractionGraph) -> Self {
+            self.at(time, Event::CreateExtractionGraph(graph))
+        }
+
+        pub fn content(self, time: u64, content: internal_api::ContentMetadata) -> Self {
+            self.at(time, Event::CreateContent(content))
+        }
+
+        pub fn check_extractor_availability(self, time: u64) -> Self {
+            self.at(time, Event::CheckExtractorAvailability)
+        }
+
+        /// Starts a single node coordinator at time zero with the test
+        /// namespace created. No scripted event is applied yet.
+        pub async fn build(self) -> Result<Simulation, anyhow::Error> {
+            let config = Arc::new(self.config);
+            let _ = fs::remove_dir_all(config.state_store.clone().path.unwrap());
+            let garbage_collector = GarbageCollector::new();
+            let coordinator_client = CoordinatorClient::new(Arc::clone(&config));
+            let shared_state = App::new(
+                config.clone(),
+                None,
+                garbage_collector.clone(),
+                &config.coordinator_addr,
+                Arc::new(crate::metrics::init_provider()),
+            )
+            .await?;
+            shared_state.initialize_raft().await?;
+            let clock = Arc::new(ManualClock::default());
+            let coordinator = Coordinator::new_with_clock(
+                shared_state.clone(),
+                coordinator_client,
+                garbage_collector,
+                clock.clone(),
+            );
+            coordinator.create_namespace(DEFAULT_TEST_NAMESPACE).await?;
+            Ok(Simulation {
+                coordinator,
+                shared_state,
+                clock,
+                events: self.events,
+                history: Vec::new(),
+            })
+        }
+    }
+
+    pub struct Simulation {
+        pub coordinator: Arc<Coordinator>,
+        pub shared_state: Arc<App>,
+        pub clock: Arc<ManualClock>,
+        events: BTreeMap<u64, Vec<Event>>,
+        history: Vec<Snapshot>,
+    }
+
+    impl Simulation {
+        /// Applies every scripted event up to and including `time` in order,
+        /// running the scheduler after each point in time and recording the
+        /// resulting assignments.
+        pub async fn run_until(&mut self, time: u64) -> Result<&Snapshot, anyhow::Error> {
+            let pending = self.events.split_off(&(time + 1));
+            let due = std::mem::replace(&mut self.events, pending);
+            for (at, events) in due {
+                self.clock.set(at);
+                for event in events {
+                    self.apply(event).await?;
+                }
+                self.coordinator.run_scheduler().await?;
+                self.record(at).await?;
+            }
+            if self.history.last().map_or(true, |s| s.time != time) {
+                self.clock.set(time);
+                self.record(time).await?;
+            }
+            Ok(self.history.last().unwrap())
+        }
+
+        /// Applies every scripted event and returns the assignment history.
+        pub async fn run(&mut self) -> Result<&[Snapshot], anyhow::Error> {
+            if let Some(&last) = self.events.keys().last() {
+                self.run_until(last).await?;
+            }
+            Ok(&self.history)
+        }
+
+        async fn apply(&self, event: Event) -> Result<(), anyhow::Error> {
+            match event {
+                Event::RegisterExecutor {
+                    executor_id,
+                    extractors,
+                } => {
+                    self.coordinator
+                        .register_executor("localhost:8950", &executor_id, extractors)
+                        .await
+                }
+                Event::RemoveExecutor(executor_id) => {
+                    self.coordinator.remove_executor(&executor_id).await
+                }
+                Event::CreateExtractionGraph(graph) => self
+                    .coordinator
+                    .create_extraction_graph(graph)
+                    .await
+                    .map(|_| ()),
+                Event::CreateContent(content) => self
+                    .coordinator
+                    .create_content_metadata(vec![content])
+                    .await
+                    .map(|_| ()),
+                Event::CheckExtractorAvailability => {
+                    self.coordinator.check_extractor_availability().await
+                }
+            }
+        }
+
+        async fn record(&mut self, time: u64) -> Result<(), anyhow::Error> {
+            let snapshot = Snapshot {
+                time,
+                assignments: self.shared_state.task_assignments().await?,
+                unassigned: self.shared_state.unassigned_tasks().await?.len(),
+            };
+            self.history.push(snapshot);
+            Ok(())
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use futures::Future;
 
 use crate::{
@@ -18,6 +20,15 @@ use crate::{
         NodeId,
         RaftConfigOverrides,
     },
+    vectordbs::{
+        in_memory::InMemoryDb,
+        CreateIndexParams,
+        Filter,
+        SearchResult,
+        VectorChunk,
+        VectorDb,
+        VectorDbCapabilities,
+    },
 };
 
 const BASE_PORT: usize = 18950;
@@ -52,6 +63,7 @@ impl RaftTestCluster {
                 raft_port: port + 1,
                 state_store: StateStoreConfig {
                     path: Some(format!("/tmp/indexify-test/raft/{}/{}", append, i)),
+                    ..Default::default()
                 },
                 seed_node: seed_node.clone(),
                 ..Default::default()
@@ -82,6 +94,7 @@ impl RaftTestCluster {
                     "/tmp/indexify-test/raft/{}/{}",
                     self.append, new_node_id
                 )),
+                ..Default::default()
             },
             seed_node,
             ..Default::default()
@@ -382,3 +395,75 @@ impl RaftTestCluster {
         Ok(Arc::clone(node))
     }
 }
+
+/// An in memory vector database whose deletes and counts fail on some of
+/// the indexes
+#[cfg(test)]
+pub struct FlakyDb {
+    pub db: InMemoryDb,
+    pub failing: Mutex<HashSet<String>>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl VectorDb for FlakyDb {
+    async fn create_index(&self, index: CreateIndexParams) -> Result<()> {
+        self.db.create_index(index).await
+    }
+
+    async fn add_embedding(&self, index: &str, chunks: Vec<VectorChunk>) -> Result<()> {
+        self.db.add_embedding(index, chunks).await
+    }
+
+    async fn remove_embedding(&self, index: &str, content_id: &str) -> Result<()> {
+        if self.failing.lock().unwrap().contains(index) {
+            return Err(anyhow!("unavailable"));
+        }
+        self.db.remove_embedding(index, content_id).await
+    }
+
+    async fn get_points(&self, index: &str, content_ids: Vec<String>) -> Result<Vec<VectorChunk>> {
+        self.db.get_points(index, content_ids).await
+    }
+
+    async fn update_metadata(
+        &self,
+        index: &str,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        self.db.update_metadata(index, content_id, metadata).await
+    }
+
+    async fn search(
+        &self,
+        index: String,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Vec<Filter>,
+        include_metadata: bool,
+    ) -> Result<Vec<SearchResult>> {
+        self.db
+            .search(index, query_embedding, k, filters, include_metadata)
+            .await
+    }
+
+    async fn drop_index(&self, index: &str) -> Result<()> {
+        self.db.drop_index(index).await
+    }
+
+    async fn num_vectors(&self, index: &str) -> Result<u64> {
+        if self.failing.lock().unwrap().contains(index) {
+            return Err(anyhow!("unavailable"));
+        }
+        self.db.num_vectors(index).await
+    }
+
+    fn name(&self) -> String {
+        "flaky".into()
+    }
+
+    fn capabilities(&self) -> VectorDbCapabilities {
+        self.db.capabilities()
+    }
+}
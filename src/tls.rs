@@ -11,6 +11,7 @@ use rustls::{
     RootCertStore,
 };
 use rustls_pemfile::certs;
+use x509_parser::extensions::GeneralName;
 
 use crate::server_config::TlsConfig;
 
@@ -86,3 +87,88 @@ pub fn build_mtls_config(tls_config: &TlsConfig) -> Result<Arc<rustls::ServerCon
 
     Ok(Arc::new(config))
 }
+
+/// Returns the identities presented by a DER encoded certificate: the
+/// subject common names followed by the DNS and URI subject alternative
+/// names.
+pub fn certificate_identities(der: &[u8]) -> Result<Vec<String>, io::Error> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let mut identities = Vec::new();
+    for cn in cert.subject().iter_common_name() {
+        if let Ok(cn) = cn.as_str() {
+            identities.push(cn.to_string());
+        }
+    }
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(name) | GeneralName::URI(name) => {
+                    identities.push(name.to_string())
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(identities)
+}
+
+/// Checks that the executor id claimed by a peer is bound to the leaf
+/// certificate it presented during the TLS handshake.
+pub fn verify_executor_identity(
+    peer_certs: Option<&[tonic::transport::Certificate]>,
+    executor_id: &str,
+) -> Result<(), tonic::Status> {
+    let leaf = peer_certs
+        .and_then(|certs| certs.first())
+        .ok_or_else(|| tonic::Status::unauthenticated("client certificate is required"))?;
+    let identities = certificate_identities(leaf.get_ref()).map_err(|e| {
+        tonic::Status::unauthenticated(format!("invalid client certificate: {}", e))
+    })?;
+    if identities.iter().any(|identity| identity == executor_id) {
+        return Ok(());
+    }
+    Err(tonic::Status::permission_denied(format!(
+        "executor id {} does not match client certificate identities {:?}",
+        executor_id, identities
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use rcgen::{CertificateParams, DnType, KeyPair};
+
+    use super::*;
+
+    fn test_cert_der(common_name: &str, sans: Vec<String>) -> Vec<u8> {
+        let mut params = CertificateParams::new(sans).unwrap();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, common_name);
+        let key_pair = KeyPair::generate().unwrap();
+        params.self_signed(&key_pair).unwrap().der().to_vec()
+    }
+
+    #[test]
+    fn test_certificate_identities() {
+        let der = test_cert_der("executor-cn", vec!["executor-1".to_string()]);
+        let identities = certificate_identities(&der).unwrap();
+        assert_eq!(identities, vec!["executor-cn", "executor-1"]);
+    }
+
+    #[test]
+    fn test_verify_executor_identity() {
+        // tonic exposes peer certificates as DER bytes
+        let der = test_cert_der("executor-cn", vec!["executor-1".to_string()]);
+        let certs = vec![tonic::transport::Certificate::from_pem(der)];
+
+        assert!(verify_executor_identity(Some(&certs), "executor-1").is_ok());
+        assert!(verify_executor_identity(Some(&certs), "executor-cn").is_ok());
+
+        let err = verify_executor_identity(Some(&certs), "executor-2").unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        let err = verify_executor_identity(None, "executor-1").unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+}
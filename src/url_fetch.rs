@@ -0,0 +1,433 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use bytes::{Bytes, BytesMut};
+use mime::Mime;
+use reqwest::{header, redirect};
+use url::{Host, Url};
+
+use crate::server_config::UrlFetchConfig;
+
+/// Content types servers send when they don't know the type of a file
+const GENERIC_CONTENT_TYPES: [&str; 2] = ["application/octet-stream", "binary/octet-stream"];
+
+/// Content fetched from a url
+#[derive(Debug)]
+pub struct FetchedContent {
+    /// Url the content was fetched from, after following redirects
+    pub url: Url,
+    pub bytes: Bytes,
+    pub mime: Mime,
+}
+
+/// Fetches content from urls on behalf of clients. Every url, including the
+/// ones redirected to, is checked against the allowed schemes and hosts, and
+/// hosts resolving to private addresses are blocked unless allowed, so that
+/// clients can't make the server reach internal services. Connections are
+/// made to the addresses which were checked, not to a second resolution of
+/// the host.
+pub struct UrlFetcher {
+    config: UrlFetchConfig,
+}
+
+impl UrlFetcher {
+    pub fn new(config: UrlFetchConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn fetch(&self, url: &str) -> Result<FetchedContent> {
+        let url = Url::parse(url).map_err(|e| anyhow!("invalid url {}: {}", url, e))?;
+        tokio::time::timeout(
+            Duration::from_secs(self.config.timeout_secs),
+            self.fetch_following_redirects(url),
+        )
+        .await
+        .map_err(|_| anyhow!("timed out after {}s", self.config.timeout_secs))?
+    }
+
+    async fn fetch_following_redirects(&self, mut url: Url) -> Result<FetchedContent> {
+        let mut redirects = 0;
+        loop {
+            let client = self.client_for(&url).await?;
+            let response = client.get(url.clone()).send().await?;
+            if response.status().is_redirection() {
+                if redirects == self.config.max_redirects {
+                    return Err(anyhow!(
+                        "too many redirects, at most {} are followed",
+                        self.config.max_redirects
+                    ));
+                }
+                let location = response
+                    .headers()
+                    .get(header::LOCATION)
+                    .ok_or_else(|| anyhow!("redirect from {} has no location", url))?
+                    .to_str()?;
+                url = url.join(location)?;
+                redirects += 1;
+                continue;
+            }
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "fetching {} failed with status {}",
+                    url,
+                    response.status()
+                ));
+            }
+            return self.read_body(url, response).await;
+        }
+    }
+
+    /// Checks the url and returns a client which connects to the addresses
+    /// its host resolved to
+    async fn client_for(&self, url: &Url) -> Result<reqwest::Client> {
+        if !self
+            .config
+            .allowed_schemes
+            .iter()
+            .any(|scheme| scheme.eq_ignore_ascii_case(url.scheme()))
+        {
+            return Err(anyhow!("scheme {} is not allowed", url.scheme()));
+        }
+        let host = url
+            .host()
+            .ok_or_else(|| anyhow!("url {} has no host", url))?;
+        let host_name = match &host {
+            Host::Domain(domain) => domain.to_lowercase(),
+            Host::Ipv4(ip) => ip.to_string(),
+            Host::Ipv6(ip) => ip.to_string(),
+        };
+        if self
+            .config
+            .denied_hosts
+            .iter()
+            .any(|denied| denied.eq_ignore_ascii_case(&host_name)) ||
+            (!self.config.allowed_hosts.is_empty() &&
+                !self
+                    .config
+                    .allowed_hosts
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(&host_name)))
+        {
+            return Err(anyhow!("host {} is not allowed", host_name));
+        }
+
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| anyhow!("url {} has no port", url))?;
+        let addrs: Vec<SocketAddr> = match &host {
+            Host::Domain(domain) => tokio::net::lookup_host((*domain, port)).await?.collect(),
+            Host::Ipv4(ip) => vec![SocketAddr::new(IpAddr::V4(*ip), port)],
+            Host::Ipv6(ip) => vec![SocketAddr::new(IpAddr::V6(*ip), port)],
+        };
+        if addrs.is_empty() {
+            return Err(anyhow!("host {} has no addresses", host_name));
+        }
+        if !self.config.allow_private_addresses {
+            if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+                return Err(anyhow!(
+                    "host {} resolves to the private address {}",
+                    host_name,
+                    addr.ip()
+                ));
+            }
+        }
+
+        let mut builder = reqwest::Client::builder().redirect(redirect::Policy::none());
+        if let Host::Domain(domain) = host {
+            builder = builder.resolve_to_addrs(domain, &addrs);
+        }
+        Ok(builder.build()?)
+    }
+
+    async fn read_body(&self, url: Url, mut response: reqwest::Response) -> Result<FetchedContent> {
+        let too_large = || {
+            anyhow!(
+                "content is larger than the limit of {} bytes",
+                self.config.max_size_bytes
+            )
+        };
+        if response
+            .content_length()
+            .is_some_and(|len| len > self.config.max_size_bytes)
+        {
+            return Err(too_large());
+        }
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok());
+        let mut bytes = BytesMut::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (bytes.len() + chunk.len()) as u64 > self.config.max_size_bytes {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let bytes = bytes.freeze();
+        let mime = match content_type {
+            Some(mime) if !GENERIC_CONTENT_TYPES.contains(&mime.essence_str()) => mime,
+            _ => sniff_content_type(&bytes)
+                .or_else(|| mime_guess::from_path(url.path()).first())
+                .unwrap_or(mime::APPLICATION_OCTET_STREAM),
+        };
+        Ok(FetchedContent { url, bytes, mime })
+    }
+}
+
+/// Whether the address can be reached from the internet, addresses of the
+/// host itself, of private networks and reserved ranges are not
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            !(ip.is_private() ||
+                ip.is_loopback() ||
+                ip.is_link_local() ||
+                ip.is_broadcast() ||
+                ip.is_documentation() ||
+                ip.is_unspecified() ||
+                ip.is_multicast() ||
+                //  Shared address space of carrier grade NATs, 100.64.0.0/10
+                (octets[0] == 100 && (octets[1] & 0xc0) == 64) ||
+                octets[0] == 0)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(ip));
+            }
+            let first_segment = ip.segments()[0];
+            !(ip.is_loopback() ||
+                ip.is_unspecified() ||
+                ip.is_multicast() ||
+                //  Unique local addresses, fc00::/7
+                (first_segment & 0xfe00) == 0xfc00 ||
+                //  Link local addresses, fe80::/10
+                (first_segment & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Recognizes common file formats by their leading bytes, and text
+fn sniff_content_type(bytes: &[u8]) -> Option<Mime> {
+    const SIGNATURES: [(&[u8], &str); 7] = [
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"ID3", "audio/mpeg"),
+    ];
+    if let Some((_, mime)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+    {
+        return mime.parse().ok();
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+        return Some("audio/wav".parse().unwrap());
+    }
+    if bytes.len() >= 8 && &bytes[4..8] == b"ftyp" {
+        return Some("video/mp4".parse().unwrap());
+    }
+    let sample = &bytes[..bytes.len().min(1024)];
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        //  The sample may end within a character
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&sample[..e.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+    if text.is_empty() || text.contains('\0') {
+        return None;
+    }
+    let start = text.trim_start().to_lowercase();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        return Some(mime::TEXT_HTML);
+    }
+    if (start.starts_with('{') || start.starts_with('[')) &&
+        serde_json::from_slice::<serde_json::Value>(bytes).is_ok()
+    {
+        return Some(mime::APPLICATION_JSON);
+    }
+    Some(mime::TEXT_PLAIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        response::{IntoResponse, Redirect, Response},
+        routing::get,
+    };
+
+    use super::*;
+
+    /// Serves `/bytes/:size` bodies of the size without a content type,
+    /// `/redirect/:n` redirecting n times before serving `/bytes/10`,
+    /// `/localhost/:port` redirecting to localhost and `/pdf` with a generic
+    /// content type
+    async fn start_server() -> SocketAddr {
+        async fn bytes(Path(size): Path<usize>) -> Response {
+            vec![b'a'; size].into_response()
+        }
+        async fn redirect(Path(n): Path<usize>) -> Redirect {
+            if n == 0 {
+                Redirect::temporary("/bytes/10")
+            } else {
+                Redirect::temporary(&format!("/redirect/{}", n - 1))
+            }
+        }
+        async fn localhost(Path(port): Path<u16>) -> Redirect {
+            Redirect::temporary(&format!("http://localhost:{}/bytes/10", port))
+        }
+        async fn pdf() -> (HeaderMap, &'static [u8]) {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_TYPE,
+                "application/octet-stream".parse().unwrap(),
+            );
+            (headers, b"%PDF-1.7 rest of the document")
+        }
+        async fn missing() -> StatusCode {
+            StatusCode::NOT_FOUND
+        }
+        let router = axum::Router::new()
+            .route("/bytes/:size", get(bytes))
+            .route("/redirect/:n", get(redirect))
+            .route("/localhost/:port", get(localhost))
+            .route("/pdf", get(pdf))
+            .route("/missing", get(missing));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        addr
+    }
+
+    fn test_fetcher(config: UrlFetchConfig) -> UrlFetcher {
+        UrlFetcher::new(UrlFetchConfig {
+            max_size_bytes: 100,
+            max_redirects: 2,
+            allow_private_addresses: true,
+            ..config
+        })
+    }
+
+    #[tokio::test]
+    async fn test_size_limit() {
+        let addr = start_server().await;
+        let fetcher = test_fetcher(UrlFetchConfig::default());
+        let content = fetcher
+            .fetch(&format!("http://{}/bytes/100", addr))
+            .await
+            .unwrap();
+        assert_eq!(content.bytes.len(), 100);
+        let err = fetcher
+            .fetch(&format!("http://{}/bytes/101", addr))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("larger than the limit"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_redirects() {
+        let addr = start_server().await;
+        let fetcher = test_fetcher(UrlFetchConfig::default());
+        let content = fetcher
+            .fetch(&format!("http://{}/redirect/1", addr))
+            .await
+            .unwrap();
+        assert_eq!(content.url.path(), "/bytes/10");
+        assert_eq!(content.bytes.len(), 10);
+
+        let err = fetcher
+            .fetch(&format!("http://{}/redirect/2", addr))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("too many redirects"), "{}", err);
+
+        let err = fetcher
+            .fetch(&format!("http://{}/missing", addr))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_hosts_are_checked_after_redirects() {
+        let addr = start_server().await;
+        let fetcher = test_fetcher(UrlFetchConfig::default());
+        let url = format!("http://{}/localhost/{}", addr, addr.port());
+        assert_eq!(
+            fetcher.fetch(&url).await.unwrap().url.host_str(),
+            Some("localhost")
+        );
+
+        let fetcher = test_fetcher(UrlFetchConfig {
+            denied_hosts: vec!["LocalHost".to_string()],
+            ..Default::default()
+        });
+        let err = fetcher.fetch(&url).await.unwrap_err();
+        assert!(err.to_string().contains("not allowed"), "{}", err);
+
+        let fetcher = test_fetcher(UrlFetchConfig {
+            allowed_schemes: vec!["https".to_string()],
+            ..Default::default()
+        });
+        let err = fetcher
+            .fetch(&format!("http://{}/bytes/1", addr))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("scheme http"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_private_addresses_are_blocked() {
+        let addr = start_server().await;
+        let fetcher = UrlFetcher::new(UrlFetchConfig::default());
+        for url in [
+            format!("http://{}/bytes/1", addr),
+            format!("http://localhost:{}/bytes/1", addr.port()),
+            format!("http://[::1]:{}/bytes/1", addr.port()),
+        ] {
+            let err = fetcher.fetch(&url).await.unwrap_err();
+            assert!(err.to_string().contains("private address"), "{}", err);
+        }
+
+        assert!(!is_public("10.1.2.3".parse().unwrap()));
+        assert!(!is_public("169.254.169.254".parse().unwrap()));
+        assert!(!is_public("100.64.0.1".parse().unwrap()));
+        assert!(!is_public("::ffff:192.168.0.1".parse().unwrap()));
+        assert!(!is_public("fd00::1".parse().unwrap()));
+        assert!(is_public("8.8.8.8".parse().unwrap()));
+        assert!(is_public("2001:4860:4860::8888".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_content_type_sniffing() {
+        let addr = start_server().await;
+        let fetcher = test_fetcher(UrlFetchConfig::default());
+        let content = fetcher
+            .fetch(&format!("http://{}/pdf", addr))
+            .await
+            .unwrap();
+        assert_eq!(content.mime.essence_str(), "application/pdf");
+
+        assert_eq!(sniff_content_type(b"hello world"), Some(mime::TEXT_PLAIN));
+        assert_eq!(
+            sniff_content_type(b"  {\"a\": 1}"),
+            Some(mime::APPLICATION_JSON)
+        );
+        assert_eq!(
+            sniff_content_type(b"<!DOCTYPE html><html></html>"),
+            Some(mime::TEXT_HTML)
+        );
+        assert_eq!(sniff_content_type(b"\x00\x01\x02"), None);
+    }
+}
@@ -9,6 +9,71 @@ pub fn timestamp_secs() -> u64 {
     duration.as_secs()
 }
 
+pub const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Days since the unix epoch of a time, in UTC
+pub fn day_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / SECS_PER_DAY)
+        .unwrap_or_default()
+}
+
+/// Formats days since the unix epoch as a `YYYY-MM-DD` date
+pub fn day_to_date(day: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = day as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the unix epoch
+pub fn date_to_day(date: &str) -> anyhow::Result<u64> {
+    let invalid = || anyhow::anyhow!("invalid date {}, expected YYYY-MM-DD", date);
+    let mut parts = date.splitn(3, '-');
+    let mut next = |max: i64| {
+        parts
+            .next()
+            .and_then(|p| p.parse::<i64>().ok())
+            .filter(|v| (1..=max).contains(v))
+            .ok_or_else(invalid)
+    };
+    let (y, m, d) = (next(9999)?, next(12)?, next(31)?);
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let day = era * 146097 + doe - 719468;
+    if day < 0 || day_to_date(day as u64) != date {
+        return Err(invalid());
+    }
+    Ok(day as u64)
+}
+
+/// Source of the current time for scheduling decisions, so that tests can
+/// drive them with simulated time.
+pub trait Clock: Send + Sync {
+    fn now_secs(&self) -> u64;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> u64 {
+        timestamp_secs()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PostgresIndexName(String);
 
@@ -61,3 +126,19 @@ impl<T> OptionInspectNone<T> for &Option<T> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_date_round_trip() {
+        assert_eq!(day_to_date(0), "1970-01-01");
+        assert_eq!(day_to_date(19782), "2024-02-29");
+        assert_eq!(date_to_day("2024-02-29").unwrap(), 19782);
+        assert_eq!(date_to_day("2024-03-01").unwrap(), 19783);
+        assert!(date_to_day("2023-02-29").is_err());
+        assert!(date_to_day("2024-13-01").is_err());
+        assert!(date_to_day("yesterday").is_err());
+    }
+}
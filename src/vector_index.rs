@@ -1,26 +1,64 @@
-use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use anyhow::{anyhow, Result};
 use bytes::Bytes;
-use futures::future::join_all;
+use futures::{future::join_all, StreamExt};
 use indexify_internal_api as internal_api;
-use indexify_proto::indexify_coordinator::Index;
-use internal_api::ExtractedEmbeddings;
-use tracing::info;
+use indexify_proto::indexify_coordinator::{self, Index};
+use internal_api::{AccessContext, ExtractedEmbeddings, IndexAttributeSchema, ACL_ATTRIBUTE};
+use tracing::{info, warn};
 
 use crate::{
     api,
     blob_storage::ContentReader,
     coordinator_client::CoordinatorClient,
     extractor_router::ExtractorRouter,
+    index_events::IndexEvents,
     metrics::{vector_storage::Metrics, Timer},
-    vectordbs::{CreateIndexParams, Filter, IndexDistance, SearchResult, VectorChunk, VectorDBTS},
+    query_embedding_cache::QueryEmbeddingCache,
+    server_config::GcVectorDeletesConfig,
+    vectordbs::{
+        filter_expr::{self, FilterParseError},
+        CreateIndexParams,
+        Filter,
+        IndexDistance,
+        MatchedFilter,
+        SearchResult,
+        VectorChunk,
+        VectorDBTS,
+    },
 };
 
+#[derive(Debug, thiserror::Error)]
+pub enum VectorIndexError {
+    #[error("attribute {0} is not filterable")]
+    AttributeNotFilterable(String),
+    #[error(transparent)]
+    InvalidFilter(#[from] FilterParseError),
+}
+
+/// Number of candidates fetched per requested result when results are
+/// filtered by access after the vector search
+const ACL_OVERFETCH_FACTOR: usize = 4;
+
 pub struct VectorIndexManager {
     vector_db: VectorDBTS,
     extractor_router: ExtractorRouter,
     content_reader: Arc<ContentReader>,
+    coordinator_client: Arc<CoordinatorClient>,
+    // Index table name -> attribute schema of the index
+    attribute_schemas: RwLock<HashMap<String, IndexAttributeSchema>>,
+    // Whether content without an ACL can be read by any user
+    allow_unlabeled: bool,
+    events: Arc<IndexEvents>,
+    query_embedding_cache: QueryEmbeddingCache,
+    gc_vector_deletes: GcVectorDeletesConfig,
     metrics: Metrics,
 }
 
@@ -30,6 +68,15 @@ impl fmt::Debug for VectorIndexManager {
     }
 }
 
+/// Vectors stored in the indexes of each namespace
+#[derive(Debug, Default)]
+pub struct NamespaceVectorTotals {
+    pub totals: HashMap<String, u64>,
+    // Index table name -> error counting its vectors
+    pub errors: HashMap<String, String>,
+}
+
+#[derive(Default)]
 pub struct ScoredText {
     pub text: String,
     pub content_id: String,
@@ -38,20 +85,77 @@ pub struct ScoredText {
     pub confidence_score: f32,
     pub root_content_metadata: Option<internal_api::ContentMetadata>,
     pub content_metadata: internal_api::ContentMetadata,
+    pub matched_filters: Vec<MatchedFilter>,
 }
 
 impl VectorIndexManager {
     pub fn new(coordinator_client: Arc<CoordinatorClient>, vector_db: VectorDBTS) -> Result<Self> {
         let extractor_router = ExtractorRouter::new(coordinator_client.clone())?;
         let content_reader = Arc::new(ContentReader::new(coordinator_client.config.clone()));
+        let allow_unlabeled = coordinator_client.config.acl.allow_unlabeled;
+        let events = Arc::new(IndexEvents::new(
+            coordinator_client.config.index_events.clone(),
+        ));
+        let query_embedding_cache =
+            QueryEmbeddingCache::new(coordinator_client.config.query_embedding_cache.clone());
         Ok(Self {
             vector_db,
             extractor_router,
             content_reader,
+            coordinator_client,
+            attribute_schemas: RwLock::new(HashMap::new()),
+            allow_unlabeled,
+            events,
+            query_embedding_cache,
+            gc_vector_deletes: coordinator_client.config.gc_vector_deletes.clone(),
             metrics: Metrics::new(),
         })
     }
 
+    /// Removes the cached embeddings of search queries, returns the number
+    /// removed
+    pub fn flush_query_embedding_cache(&self) -> usize {
+        self.query_embedding_cache.flush()
+    }
+
+    /// Lifecycle events of the indexes, published after the vector database
+    /// writes succeeded
+    pub fn index_events(&self) -> Arc<IndexEvents> {
+        self.events.clone()
+    }
+
+    /// Returns the attribute schema of the index stored in the table. Schemas
+    /// are fetched from the coordinator on first use and cached.
+    async fn attribute_schema(
+        &self,
+        namespace: &str,
+        table_name: &str,
+    ) -> Result<IndexAttributeSchema> {
+        if let Some(schema) = self.attribute_schemas.read().unwrap().get(table_name) {
+            return Ok(schema.clone());
+        }
+        let req = indexify_coordinator::ListIndexesRequest {
+            namespace: namespace.to_string(),
+        };
+        let indexes = self
+            .coordinator_client
+            .get()
+            .await?
+            .list_indexes(req)
+            .await?
+            .into_inner()
+            .indexes;
+        let mut attribute_schemas = self.attribute_schemas.write().unwrap();
+        for index in indexes {
+            let schema = IndexAttributeSchema::from_json_str(&index.attribute_schema)?;
+            attribute_schemas.insert(index.table_name, schema);
+        }
+        Ok(attribute_schemas
+            .entry(table_name.to_string())
+            .or_default()
+            .clone())
+    }
+
     pub async fn create_index(
         &self,
         index_name: &str,
@@ -65,11 +169,46 @@ impl VectorIndexManager {
         };
         info!("Creating index: {:?}", create_index_params);
         self.vector_db.create_index(create_index_params).await?;
+        self.events.index_created(index_name);
         Ok(index_name.to_string())
     }
 
+    pub async fn num_vectors(&self, index_name: &str) -> Result<u64> {
+        self.vector_db.num_vectors(index_name).await
+    }
+
+    /// Sums the vectors of the indexes of each namespace, given as the table
+    /// names of the indexes of each namespace. An index whose vectors can't be
+    /// counted is left out of the total of its namespace and reported in the
+    /// errors.
+    pub async fn namespace_vector_totals(
+        &self,
+        namespace_indexes: &HashMap<String, HashSet<String>>,
+    ) -> NamespaceVectorTotals {
+        let mut totals = NamespaceVectorTotals::default();
+        for (namespace, tables) in namespace_indexes {
+            let total = totals.totals.entry(namespace.clone()).or_default();
+            for table in tables {
+                match self.num_vectors(table).await {
+                    Ok(count) => *total += count,
+                    Err(e) => {
+                        warn!(
+                            "unable to count the vectors of index {} of namespace {}: {}",
+                            table, namespace, e
+                        );
+                        totals.errors.insert(table.clone(), e.to_string());
+                    }
+                }
+            }
+        }
+        totals
+    }
+
     pub async fn drop_index(&self, index_name: &str) -> Result<()> {
-        self.vector_db.drop_index(index_name).await
+        self.attribute_schemas.write().unwrap().remove(index_name);
+        self.vector_db.drop_index(index_name).await?;
+        self.events.index_dropped(index_name);
+        Ok(())
     }
 
     pub async fn add_embedding(
@@ -79,30 +218,106 @@ impl VectorIndexManager {
     ) -> Result<()> {
         let _timer = Timer::start(&self.metrics.vector_upsert);
         let mut vector_chunks = Vec::new();
-        embeddings.iter().for_each(|embedding| {
+        for embedding in &embeddings {
+            let schema = self
+                .attribute_schema(&embedding.content_metadata.namespace, vector_index_name)
+                .await?;
+            let mut metadata = stored_attributes(&schema, embedding.metadata.clone());
+            metadata.remove(ACL_ATTRIBUTE);
+            let acl = embedding.content_metadata.acl.as_ref().or_else(|| {
+                embedding
+                    .root_content_metadata
+                    .as_ref()
+                    .and_then(|root| root.acl.as_ref())
+            });
+            if let Some(acl) = acl {
+                metadata.insert(ACL_ATTRIBUTE.to_string(), acl.principals().into());
+            }
             let vector_chunk = VectorChunk::new(
                 embedding.content_id.clone(),
                 embedding.embedding.clone(),
-                embedding.metadata.clone(),
+                metadata,
                 embedding.root_content_metadata.clone(),
                 &embedding.content_metadata,
             );
             vector_chunks.push(vector_chunk);
-        });
+        }
+        let count = vector_chunks.len() as u64;
         self.vector_db
             .add_embedding(vector_index_name, vector_chunks)
             .await?;
+        self.events.vectors_added(vector_index_name, count);
         Ok(())
     }
 
-    pub async fn remove_embedding(&self, vector_index_name: &str, content_id: &str) -> Result<()> {
+    /// Removes the vectors of the content from the index. Content without
+    /// vectors in the index is skipped, so removing again is a no-op.
+    pub async fn remove_embeddings(
+        &self,
+        vector_index_name: &str,
+        content_ids: Vec<String>,
+    ) -> Result<()> {
         let _timer = Timer::start(&self.metrics.vector_delete);
+        let count = content_ids.len() as u64;
         self.vector_db
-            .remove_embedding(vector_index_name, content_id)
+            .remove_embeddings(vector_index_name, content_ids)
             .await?;
+        self.events.vectors_deleted(vector_index_name, count);
         Ok(())
     }
 
+    /// Removes the vectors of garbage collected content, given as the content
+    /// ids to remove from each index. Indexes are removed from concurrently
+    /// up to the configured limit and each index is retried on its own, so an
+    /// index which can't be written doesn't hold back the others. Returns the
+    /// outcome for every index.
+    pub async fn remove_embeddings_from_indexes(
+        &self,
+        deletions: HashMap<String, Vec<String>>,
+    ) -> HashMap<String, Result<()>> {
+        futures::stream::iter(deletions)
+            .map(|(index, content_ids)| async move {
+                let result = self
+                    .remove_embeddings_with_retries(&index, content_ids)
+                    .await;
+                (index, result)
+            })
+            .buffer_unordered(self.gc_vector_deletes.max_concurrent_indexes.max(1))
+            .collect()
+            .await
+    }
+
+    async fn remove_embeddings_with_retries(
+        &self,
+        index: &str,
+        content_ids: Vec<String>,
+    ) -> Result<()> {
+        let count = content_ids.len() as u64;
+        let mut delay = Duration::from_millis(self.gc_vector_deletes.retry_delay_ms);
+        let mut attempt = 1;
+        self.metrics.gc_index_deletes_in_flight.add(1, &[]);
+        let result = loop {
+            match self.remove_embeddings(index, content_ids.clone()).await {
+                Ok(()) => break Ok(()),
+                Err(e) if attempt >= self.gc_vector_deletes.max_attempts => break Err(e),
+                Err(e) => {
+                    warn!(
+                        "attempt {} to remove {} vectors from index {} failed: {}",
+                        attempt, count, index, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+            }
+        };
+        self.metrics.gc_index_deletes_in_flight.add(-1, &[]);
+        if result.is_ok() {
+            self.metrics.gc_vectors_deleted.add(count, &[]);
+        }
+        result
+    }
+
     pub async fn get_points(
         &self,
         index: &str,
@@ -113,16 +328,24 @@ impl VectorIndexManager {
 
     pub async fn update_metadata(
         &self,
+        namespace: &str,
         index: &str,
         content_id: String,
         metadata: HashMap<String, serde_json::Value>,
     ) -> Result<()> {
         let _timer = Timer::start(&self.metrics.vector_metadata_update);
+        let schema = self.attribute_schema(namespace, index).await?;
+        let mut metadata = stored_attributes(&schema, metadata);
+        //  The ACL attribute is only written from the content's ACL
+        metadata.remove(ACL_ATTRIBUTE);
         self.vector_db
             .update_metadata(index, content_id, metadata)
             .await
     }
 
+    /// Searches the index for the query. The embedding of the query is read
+    /// from the query embedding cache unless `no_cache` is set.
+    #[allow(clippy::too_many_arguments)]
     pub async fn search(
         &self,
         index: Index,
@@ -130,6 +353,8 @@ impl VectorIndexManager {
         k: usize,
         filters: Vec<String>,
         include_content: bool,
+        access: Option<&AccessContext>,
+        no_cache: bool,
     ) -> Result<Vec<ScoredText>> {
         let _timer = Timer::start(&self.metrics.vector_search);
 
@@ -140,17 +365,47 @@ impl VectorIndexManager {
             labels: HashMap::new(),
         };
         info!("Extracting searching from index {:?}", index);
-        let filters = filters
-            .into_iter()
-            .map(|f| Filter::from_str(f.as_str()))
-            .collect::<Result<Vec<Filter>>>()?;
-
-        let embedding = self.generate_embedding(&index.extractor, content).await?;
+        let schema = IndexAttributeSchema::from_json_str(&index.attribute_schema)?;
+        let filters = parse_filters(&schema, filters)?;
 
-        let search_result = self
-            .search_vector_db(index.table_name, embedding.values, k as u64, filters)
+        //  The schema of the index changes with the model of its extractor
+        let extractor = index.extractor.as_str();
+        let embedding = self
+            .query_embedding_cache
+            .get_or_embed(extractor, &index.schema, query, no_cache, || async move {
+                self.generate_embedding(extractor, content)
+                    .await
+                    .map(|embedding| embedding.values)
+            })
             .await?;
 
+        //  The vector databases can only filter on equality, so results the
+        //  user can't read are dropped after the search
+        let fetch_k = match access {
+            Some(_) => k * ACL_OVERFETCH_FACTOR,
+            None => k,
+        };
+        let mut search_result = Vec::new();
+        let alternatives = filters.len();
+        for filters in filters {
+            search_result.extend(
+                self.search_vector_db(
+                    index.table_name.clone(),
+                    embedding.clone(),
+                    fetch_k as u64,
+                    filters,
+                )
+                .await?,
+            );
+        }
+        if alternatives > 1 {
+            search_result = merge_alternatives(search_result, fetch_k);
+        }
+        if let Some(access) = access {
+            search_result.retain(|result| permitted(result, access, self.allow_unlabeled));
+            search_result.truncate(k);
+        }
+
         let mut content_byte_map = HashMap::new();
         if include_content {
             content_byte_map = self.retrieve_content_blob(&search_result).await?;
@@ -173,8 +428,13 @@ impl VectorIndexManager {
             let mut labels = HashMap::new();
             labels.extend(result.content_metadata.labels.clone());
             for (k, v) in result.metadata {
-                labels.insert(k, v.to_string());
+                if k != ACL_ATTRIBUTE {
+                    labels.insert(k, v.to_string());
+                }
             }
+            let mut content_metadata = result.content_metadata.clone();
+            retain_returned(&schema, &mut labels);
+            retain_returned(&schema, &mut content_metadata.labels);
             let search_result = ScoredText {
                 text,
                 content_id: result.content_id.clone(),
@@ -182,7 +442,8 @@ impl VectorIndexManager {
                 labels,
                 confidence_score: result.confidence_score,
                 root_content_metadata: result.root_content_metadata,
-                content_metadata: result.content_metadata.clone(),
+                content_metadata,
+                matched_filters: result.matched_filters,
             };
             index_search_results.push(search_result);
         }
@@ -218,7 +479,10 @@ impl VectorIndexManager {
         filters: Vec<Filter>,
     ) -> Result<Vec<SearchResult>> {
         let _timer = Timer::start(&self.metrics.vector_search_db);
-        let search_result = self.vector_db.search(index, embedding, k, filters).await?;
+        let search_result = self
+            .vector_db
+            .search(index, embedding, k, filters, true)
+            .await?;
         Ok(search_result)
     }
 
@@ -252,3 +516,518 @@ impl VectorIndexManager {
         Ok(content_byte_map)
     }
 }
+
+/// Drops the attributes the index doesn't store
+fn stored_attributes(
+    schema: &IndexAttributeSchema,
+    mut metadata: HashMap<String, serde_json::Value>,
+) -> HashMap<String, serde_json::Value> {
+    metadata.retain(|k, _| schema.is_stored(k));
+    metadata
+}
+
+/// Drops the attributes the index doesn't return in search results
+fn retain_returned<V>(schema: &IndexAttributeSchema, attributes: &mut HashMap<String, V>) {
+    attributes.retain(|k, _| schema.is_returned(k));
+}
+
+/// Whether the user can read the content a search result was found in
+fn permitted(result: &SearchResult, access: &AccessContext, allow_unlabeled: bool) -> bool {
+    access.permits_content(
+        &result.content_metadata,
+        result.root_content_metadata.as_ref(),
+        allow_unlabeled,
+    )
+}
+
+/// Parses the filter expressions of a search into the alternatives which are
+/// searched separately
+fn parse_filters(schema: &IndexAttributeSchema, filters: Vec<String>) -> Result<Vec<Vec<Filter>>> {
+    let alternatives = filter_expr::parse_all(&filters).map_err(VectorIndexError::from)?;
+    //  Filters on the ACL attribute could be used to probe which content
+    //  other users can read
+    if let Some(filter) = alternatives
+        .iter()
+        .flatten()
+        .find(|f| f.key == ACL_ATTRIBUTE || !schema.is_filterable(&f.key))
+    {
+        return Err(VectorIndexError::AttributeNotFilterable(filter.key.clone()).into());
+    }
+    Ok(alternatives)
+}
+
+/// Merges the results of the searches of the alternatives of a filter,
+/// keeping the `k` best scoring results found by any of them
+fn merge_alternatives(results: Vec<SearchResult>, k: usize) -> Vec<SearchResult> {
+    let mut merged: Vec<SearchResult> = Vec::with_capacity(results.len());
+    for result in results {
+        if !merged.iter().any(|r| r.content_id == result.content_id) {
+            merged.push(result);
+        }
+    }
+    merged.sort_by(|a, b| b.confidence_score.total_cmp(&a.confidence_score));
+    merged.truncate(k);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use async_trait::async_trait;
+    use internal_api::AttributeOptions;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        index_events::IndexEventKind,
+        server_config::{GcVectorDeletesConfig, IndexEventsConfig, ServerConfig},
+        test_utils::FlakyDb,
+        vectordbs::{in_memory::InMemoryDb, VectorDb, VectorDbCapabilities},
+    };
+
+    fn schema() -> IndexAttributeSchema {
+        IndexAttributeSchema {
+            attributes: BTreeMap::from([
+                (
+                    "secret".to_string(),
+                    AttributeOptions {
+                        filterable: false,
+                        returned: false,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "scratch".to_string(),
+                    AttributeOptions {
+                        stored: false,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_non_filterable_attribute_is_rejected() {
+        let err = parse_filters(&schema(), vec!["secret=1".to_string()]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VectorIndexError>(),
+            Some(VectorIndexError::AttributeNotFilterable(key)) if key == "secret"
+        ));
+
+        let filters = parse_filters(&schema(), vec!["topic=news".to_string()]).unwrap();
+        assert_eq!(filters.len(), 1);
+
+        //  Indexes without a schema accept filters on every attribute
+        assert!(parse_filters(
+            &IndexAttributeSchema::default(),
+            vec!["secret=1".to_string()]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_merge_alternatives() {
+        let results = vec![
+            SearchResult::id_only("a".to_string(), 0.5),
+            SearchResult::id_only("b".to_string(), 0.9),
+            SearchResult::id_only("b".to_string(), 0.9),
+            SearchResult::id_only("c".to_string(), 0.7),
+        ];
+        let merged = merge_alternatives(results, 2);
+        assert_eq!(
+            merged
+                .iter()
+                .map(|r| r.content_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_attribute_projection() {
+        let mut labels = HashMap::from([
+            ("secret".to_string(), "1".to_string()),
+            ("topic".to_string(), "news".to_string()),
+        ]);
+        retain_returned(&schema(), &mut labels);
+        assert_eq!(
+            labels,
+            HashMap::from([("topic".to_string(), "news".to_string())])
+        );
+
+        let metadata = HashMap::from([
+            ("scratch".to_string(), json!(1)),
+            ("secret".to_string(), json!(2)),
+        ]);
+        let stored = stored_attributes(&schema(), metadata);
+        assert_eq!(stored, HashMap::from([("secret".to_string(), json!(2))]));
+    }
+    #[test]
+    fn test_acl_filtering() {
+        let acl = internal_api::ContentAcl {
+            users: vec!["alice".to_string()],
+            groups: vec![],
+        };
+        let mut labeled = SearchResult::id_only("labeled".to_string(), 1.0);
+        labeled.content_metadata.acl = Some(acl);
+        let unlabeled = SearchResult::id_only("unlabeled".to_string(), 0.5);
+        let alice = AccessContext {
+            user: Some("alice".to_string()),
+            groups: vec![],
+        };
+        let bob = AccessContext {
+            user: Some("bob".to_string()),
+            groups: vec![],
+        };
+
+        assert!(permitted(&labeled, &alice, false));
+        assert!(!permitted(&labeled, &bob, true));
+        assert!(permitted(&unlabeled, &bob, true));
+        assert!(!permitted(&unlabeled, &bob, false));
+
+        //  The ACL attribute can't be used in user filters
+        let err = parse_filters(
+            &IndexAttributeSchema::default(),
+            vec![format!("{}=user:bob", ACL_ATTRIBUTE)],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VectorIndexError>(),
+            Some(VectorIndexError::AttributeNotFilterable(key)) if key == ACL_ATTRIBUTE
+        ));
+    }
+
+    /// A vector database whose writes all fail
+    struct FailingDb;
+
+    #[async_trait]
+    impl VectorDb for FailingDb {
+        async fn create_index(&self, _index: CreateIndexParams) -> Result<()> {
+            Err(anyhow!("unavailable"))
+        }
+
+        async fn add_embedding(&self, _index: &str, _chunks: Vec<VectorChunk>) -> Result<()> {
+            Err(anyhow!("unavailable"))
+        }
+
+        async fn remove_embedding(&self, _index: &str, _content_id: &str) -> Result<()> {
+            Err(anyhow!("unavailable"))
+        }
+
+        async fn get_points(
+            &self,
+            _index: &str,
+            _content_ids: Vec<String>,
+        ) -> Result<Vec<VectorChunk>> {
+            unimplemented!()
+        }
+
+        async fn update_metadata(
+            &self,
+            _index: &str,
+            _content_id: String,
+            _metadata: HashMap<String, serde_json::Value>,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        async fn search(
+            &self,
+            _index: String,
+            _query_embedding: Vec<f32>,
+            _k: u64,
+            _filters: Vec<Filter>,
+            _include_metadata: bool,
+        ) -> Result<Vec<SearchResult>> {
+            unimplemented!()
+        }
+
+        async fn drop_index(&self, _index: &str) -> Result<()> {
+            Err(anyhow!("unavailable"))
+        }
+
+        async fn num_vectors(&self, _index: &str) -> Result<u64> {
+            unimplemented!()
+        }
+
+        fn name(&self) -> String {
+            "failing".into()
+        }
+
+        fn capabilities(&self) -> VectorDbCapabilities {
+            VectorDbCapabilities {
+                filters: false,
+                neq_matches_missing_key: false,
+                search_within_root: false,
+                search_grouped_by_root: false,
+                replace_metadata: false,
+                num_vectors: false,
+                update_missing_content_fails: false,
+            }
+        }
+    }
+
+    fn manager(vector_db: VectorDBTS) -> VectorIndexManager {
+        let config = ServerConfig {
+            index_events: IndexEventsConfig {
+                coalesce_interval_ms: 60_000,
+                ..Default::default()
+            },
+            gc_vector_deletes: GcVectorDeletesConfig {
+                retry_delay_ms: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let coordinator_client = Arc::new(CoordinatorClient::new(Arc::new(config)));
+        let manager = VectorIndexManager::new(coordinator_client, vector_db).unwrap();
+        //  Cached so that adding embeddings doesn't need a coordinator
+        manager
+            .attribute_schemas
+            .write()
+            .unwrap()
+            .insert("index_1".to_string(), IndexAttributeSchema::default());
+        manager
+    }
+
+    fn embedding(content_id: &str) -> ExtractedEmbeddings {
+        ExtractedEmbeddings {
+            content_id: content_id.to_string(),
+            embedding: vec![1.0, 2.0],
+            metadata: HashMap::new(),
+            root_content_metadata: None,
+            content_metadata: internal_api::ContentMetadata::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_index_events_follow_successful_writes() {
+        let schema = internal_api::EmbeddingSchema {
+            dim: 2,
+            distance: "cosine".to_string(),
+        };
+
+        //  Failed writes publish nothing
+        let failing = manager(Arc::new(FailingDb));
+        let mut rx = failing.index_events().subscribe();
+        assert!(failing
+            .create_index("index_1", schema.clone())
+            .await
+            .is_err());
+        assert!(failing
+            .add_embedding("index_1", vec![embedding("a")])
+            .await
+            .is_err());
+        assert!(failing
+            .remove_embeddings("index_1", vec!["a".to_string()])
+            .await
+            .is_err());
+        assert!(failing.drop_index("index_1").await.is_err());
+        assert!(rx.try_recv().is_err());
+
+        let manager = manager(Arc::new(InMemoryDb::new()));
+        let mut rx = manager.index_events().subscribe();
+        manager.create_index("index_1", schema).await.unwrap();
+        manager
+            .add_embedding("index_1", vec![embedding("a"), embedding("b")])
+            .await
+            .unwrap();
+        manager
+            .add_embedding("index_1", vec![embedding("c")])
+            .await
+            .unwrap();
+        manager
+            .remove_embeddings("index_1", vec!["a".to_string()])
+            .await
+            .unwrap();
+        manager.drop_index("index_1").await.unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            assert_eq!(event.index, "index_1");
+            events.push((event.sequence, event.kind));
+        }
+        //  The second addition is coalesced until the deletion is published
+        assert_eq!(
+            events,
+            vec![
+                (1, IndexEventKind::Created),
+                (2, IndexEventKind::VectorsAdded { count: 2 }),
+                (3, IndexEventKind::VectorsAdded { count: 1 }),
+                (4, IndexEventKind::VectorsDeleted { count: 1 }),
+                (5, IndexEventKind::Dropped),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_embeddings_is_idempotent() {
+        let manager = manager(Arc::new(InMemoryDb::new()));
+        let schema = internal_api::EmbeddingSchema {
+            dim: 2,
+            distance: "cosine".to_string(),
+        };
+        manager.create_index("index_1", schema).await.unwrap();
+        manager
+            .add_embedding(
+                "index_1",
+                vec![embedding("root"), embedding("child"), embedding("other")],
+            )
+            .await
+            .unwrap();
+
+        let tree = vec!["root".to_string(), "child".to_string()];
+        for _ in 0..2 {
+            manager
+                .remove_embeddings("index_1", tree.clone())
+                .await
+                .unwrap();
+            let points = manager
+                .get_points(
+                    "index_1",
+                    vec!["root".to_string(), "child".to_string(), "other".to_string()],
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                points
+                    .iter()
+                    .map(|p| p.content_id.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["other"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_embeddings_from_indexes() {
+        let db = Arc::new(FlakyDb {
+            db: InMemoryDb::new(),
+            failing: Default::default(),
+        });
+        let manager = manager(db.clone());
+        let indexes = ["index_1", "index_2", "index_3"];
+        for index in indexes {
+            manager
+                .attribute_schemas
+                .write()
+                .unwrap()
+                .insert(index.to_string(), IndexAttributeSchema::default());
+            manager
+                .create_index(
+                    index,
+                    internal_api::EmbeddingSchema {
+                        dim: 2,
+                        distance: "cosine".to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+            manager
+                .add_embedding(index, vec![embedding("a"), embedding("b"), embedding("c")])
+                .await
+                .unwrap();
+        }
+        let remaining = |index: &'static str| {
+            let manager = &manager;
+            async move {
+                let points = manager
+                    .get_points(
+                        index,
+                        vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                    )
+                    .await
+                    .unwrap();
+                points.len()
+            }
+        };
+        let deletions: HashMap<String, Vec<String>> = indexes
+            .iter()
+            .map(|index| (index.to_string(), vec!["a".to_string(), "b".to_string()]))
+            .collect();
+
+        //  A failing index doesn't hold back the others
+        db.failing.lock().unwrap().insert("index_2".to_string());
+        let results = manager
+            .remove_embeddings_from_indexes(deletions.clone())
+            .await;
+        assert!(results["index_1"].is_ok());
+        assert!(results["index_2"].is_err());
+        assert!(results["index_3"].is_ok());
+        assert_eq!(remaining("index_1").await, 1);
+        assert_eq!(remaining("index_2").await, 3);
+        assert_eq!(remaining("index_3").await, 1);
+
+        //  Resuming only removes from the index which failed
+        db.failing.lock().unwrap().clear();
+        let resumed: HashMap<String, Vec<String>> = deletions
+            .into_iter()
+            .filter(|(index, _)| index == "index_2")
+            .collect();
+        let results = manager.remove_embeddings_from_indexes(resumed).await;
+        assert_eq!(results.len(), 1);
+        assert!(results["index_2"].is_ok());
+        assert_eq!(remaining("index_2").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_vector_totals() {
+        let db = Arc::new(FlakyDb {
+            db: InMemoryDb::new(),
+            failing: Default::default(),
+        });
+        let manager = manager(db.clone());
+        for (index, count) in [("index_1", 2), ("index_2", 3), ("index_3", 4)] {
+            manager
+                .attribute_schemas
+                .write()
+                .unwrap()
+                .insert(index.to_string(), IndexAttributeSchema::default());
+            manager
+                .create_index(
+                    index,
+                    internal_api::EmbeddingSchema {
+                        dim: 2,
+                        distance: "cosine".to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+            let embeddings = (0..count)
+                .map(|i| embedding(&format!("{}_{}", index, i)))
+                .collect();
+            manager.add_embedding(index, embeddings).await.unwrap();
+        }
+        let namespace_indexes = HashMap::from([
+            (
+                "namespace_1".to_string(),
+                HashSet::from(["index_1".to_string(), "index_2".to_string()]),
+            ),
+            (
+                "namespace_2".to_string(),
+                HashSet::from(["index_3".to_string()]),
+            ),
+            ("namespace_3".to_string(), HashSet::new()),
+        ]);
+
+        let totals = manager.namespace_vector_totals(&namespace_indexes).await;
+        assert_eq!(
+            totals.totals,
+            HashMap::from([
+                ("namespace_1".to_string(), 5),
+                ("namespace_2".to_string(), 4),
+                ("namespace_3".to_string(), 0),
+            ])
+        );
+        assert!(totals.errors.is_empty());
+
+        //  An index which can't be counted is reported and left out
+        db.failing.lock().unwrap().insert("index_2".to_string());
+        let totals = manager.namespace_vector_totals(&namespace_indexes).await;
+        assert_eq!(totals.totals["namespace_1"], 2);
+        assert_eq!(totals.totals["namespace_2"], 4);
+        assert_eq!(totals.errors.keys().collect::<Vec<_>>(), vec!["index_2"]);
+    }
+}
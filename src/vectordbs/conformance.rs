@@ -0,0 +1,524 @@
+//! Conformance tests every vector database has to pass. Each case runs on a
+//! fresh index. Cases of behaviors a database doesn't list in its
+//! capabilities are skipped and reported instead of silently passing.
+
+use std::future::Future;
+
+use super::{
+    CreateIndexParams,
+    Filter,
+    FilterOperator,
+    IndexDistance,
+    VectorChunk,
+    VectorDBTS,
+    VectorDbCapabilities,
+};
+use crate::{
+    data_manager::DataManager,
+    test_util::db_utils::{create_metadata, test_mock_content_metadata},
+};
+
+/// The cases skipped because the database doesn't support them
+#[derive(Debug, Default)]
+pub struct ConformanceReport {
+    pub skipped: Vec<&'static str>,
+}
+
+/// Returns the value of the environment variable configuring a backend,
+/// None and a note when it isn't set so the backend isn't tested
+pub fn backend_config(var: &str) -> Option<String> {
+    let value = std::env::var(var).ok();
+    if value.is_none() {
+        println!("{} is not set, skipping the conformance tests", var);
+    }
+    value
+}
+
+/// Runs all the conformance cases against the database. The indexes of the
+/// cases are named after `index_prefix` and dropped once the case passed.
+pub async fn run(vector_db: VectorDBTS, index_prefix: &str) -> ConformanceReport {
+    let capabilities = vector_db.capabilities();
+    let mut harness = Harness {
+        vector_db,
+        index_prefix: index_prefix.to_string(),
+        report: ConformanceReport::default(),
+    };
+    harness.case("create_index", true, create_index).await;
+    harness.case("upsert", true, upsert).await;
+    harness
+        .case("metadata_round_trip", true, metadata_round_trip)
+        .await;
+    harness.case("exact_k_search", true, exact_k_search).await;
+    harness
+        .case("search_without_metadata", true, search_without_metadata)
+        .await;
+    harness
+        .case("filters", capabilities.filters, |db, index| {
+            filters(db, index, capabilities)
+        })
+        .await;
+    harness.case("update_metadata", true, update_metadata).await;
+    harness
+        .case(
+            "replace_metadata",
+            capabilities.replace_metadata,
+            replace_metadata,
+        )
+        .await;
+    harness.case("delete", true, delete).await;
+    harness
+        .case("num_vectors", capabilities.num_vectors, num_vectors)
+        .await;
+    harness
+        .case(
+            "search_within_root",
+            capabilities.search_within_root,
+            search_within_root,
+        )
+        .await;
+    harness
+        .case(
+            "search_grouped_by_root",
+            capabilities.search_grouped_by_root,
+            search_grouped_by_root,
+        )
+        .await;
+    harness.case("drop_index", true, drop_index).await;
+    harness
+        .case("missing_content", true, |db, index| {
+            missing_content(db, index, capabilities)
+        })
+        .await;
+    harness.report
+}
+
+struct Harness {
+    vector_db: VectorDBTS,
+    index_prefix: String,
+    report: ConformanceReport,
+}
+
+impl Harness {
+    async fn case<F, Fut>(&mut self, name: &'static str, supported: bool, case: F)
+    where
+        F: FnOnce(VectorDBTS, String) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        if !supported {
+            println!(
+                "skipping conformance case {} unsupported by {}",
+                name,
+                self.vector_db.name()
+            );
+            self.report.skipped.push(name);
+            return;
+        }
+        let index = format!("{}-{}", self.index_prefix, name.replace('_', "-"));
+        //  Left over by an earlier failed run, which may not have existed
+        let _ = self.vector_db.drop_index(&index).await;
+        create(&self.vector_db, &index).await;
+        case(self.vector_db.clone(), index.clone()).await;
+        self.vector_db.drop_index(&index).await.unwrap();
+    }
+}
+
+async fn create(vector_db: &VectorDBTS, index: &str) {
+    vector_db
+        .create_index(CreateIndexParams {
+            vectordb_index_name: index.to_string(),
+            vector_dim: 2,
+            distance: IndexDistance::Cosine,
+            unique_params: None,
+        })
+        .await
+        .unwrap();
+}
+
+fn chunk(embedding: Vec<f32>, metadata: &[(&str, &str)], root_id: &str) -> VectorChunk {
+    let content_id = DataManager::make_id();
+    VectorChunk {
+        content_metadata: test_mock_content_metadata(&content_id, root_id, "graph1"),
+        root_content_metadata: Some(test_mock_content_metadata(root_id, "", "graph1")),
+        content_id,
+        embedding,
+        metadata: create_metadata(metadata.iter().copied()),
+    }
+}
+
+fn ids(chunks: &[VectorChunk]) -> Vec<String> {
+    chunks.iter().map(|c| c.content_id.clone()).collect()
+}
+
+fn filter(key: &str, value: &str, operator: FilterOperator) -> Filter {
+    Filter {
+        key: key.to_string(),
+        value: value.to_string(),
+        operator,
+    }
+}
+
+/// Number of the content ids stored in the index, for databases which
+/// don't implement num_vectors
+async fn stored(vector_db: &VectorDBTS, index: &str, content_ids: Vec<String>) -> usize {
+    vector_db
+        .get_points(index, content_ids)
+        .await
+        .unwrap()
+        .len()
+}
+
+async fn search_ids(
+    vector_db: &VectorDBTS,
+    index: &str,
+    k: u64,
+    filters: Vec<Filter>,
+) -> Vec<String> {
+    vector_db
+        .search(index.to_string(), vec![0., 1.], k, filters, true)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.content_id)
+        .collect()
+}
+
+async fn create_index(vector_db: VectorDBTS, index: String) {
+    //  Creating an existing index keeps its content
+    let chunk = chunk(vec![0., 1.], &[("key1", "value1")], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    create(&vector_db, &index).await;
+    assert_eq!(stored(&vector_db, &index, vec![chunk.content_id]).await, 1);
+}
+
+async fn upsert(vector_db: VectorDBTS, index: String) {
+    let mut chunk = chunk(vec![0., 1.], &[("key1", "value1")], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    chunk.metadata = create_metadata([("key1", "value2")]);
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    let points = vector_db
+        .get_points(&index, vec![chunk.content_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 1);
+    assert_eq!(points[0].metadata, chunk.metadata);
+}
+
+async fn metadata_round_trip(vector_db: VectorDBTS, index: String) {
+    let chunks = vec![
+        chunk(vec![0., 1.], &[("key1", "value1"), ("key2", "value2")], "1"),
+        chunk(vec![1., 0.], &[("key1", "value3")], "2"),
+    ];
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    let points = vector_db.get_points(&index, ids(&chunks)).await.unwrap();
+    assert_eq!(points.len(), chunks.len());
+    for chunk in chunks {
+        let point = points
+            .iter()
+            .find(|p| p.content_id == chunk.content_id)
+            .unwrap();
+        assert_eq!(point.embedding, chunk.embedding);
+        assert_eq!(point.metadata, chunk.metadata);
+        assert_eq!(point.content_metadata, chunk.content_metadata);
+        assert_eq!(point.root_content_metadata, chunk.root_content_metadata);
+    }
+}
+
+async fn exact_k_search(vector_db: VectorDBTS, index: String) {
+    //  Each chunk is further away from the query than the one before
+    let chunks: Vec<_> = (0..5)
+        .map(|i| {
+            let angle = (i as f32 * 20.0).to_radians();
+            chunk(vec![angle.sin(), angle.cos()], &[], "1")
+        })
+        .collect();
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        search_ids(&vector_db, &index, 3, vec![]).await,
+        ids(&chunks[..3])
+    );
+    assert_eq!(search_ids(&vector_db, &index, 10, vec![]).await.len(), 5);
+}
+
+async fn search_without_metadata(vector_db: VectorDBTS, index: String) {
+    let chunk = chunk(vec![0., 1.], &[("key1", "value1")], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    let results = vector_db
+        .search(index.clone(), vec![0., 1.], 1, vec![], false)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].content_id, chunk.content_id);
+    assert!(results[0].metadata.is_empty());
+    assert!(results[0].root_content_metadata.is_none());
+}
+
+async fn filters(vector_db: VectorDBTS, index: String, capabilities: VectorDbCapabilities) {
+    let chunks = vec![
+        chunk(vec![0., 1.], &[("key1", "value1"), ("key2", "value2")], "1"),
+        chunk(
+            vec![0.1, 1.],
+            &[("key1", "value3"), ("key2", "value4")],
+            "1",
+        ),
+        chunk(vec![0.2, 1.], &[("key2", "value2")], "1"),
+    ];
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    let search = |filters: Vec<Filter>| {
+        let vector_db = vector_db.clone();
+        let index = index.clone();
+        async move { search_ids(&vector_db, &index, 10, filters).await }
+    };
+
+    assert_eq!(
+        search(vec![filter("key1", "value1", FilterOperator::Eq)]).await,
+        ids(&chunks[..1])
+    );
+    let mut not_value1 = ids(&chunks[1..2]);
+    if capabilities.neq_matches_missing_key {
+        not_value1.push(chunks[2].content_id.clone());
+    }
+    assert_eq!(
+        search(vec![filter("key1", "value1", FilterOperator::Neq)]).await,
+        not_value1
+    );
+
+    //  All the filters have to match
+    assert_eq!(
+        search(vec![
+            filter("key1", "value1", FilterOperator::Eq),
+            filter("key2", "value2", FilterOperator::Eq),
+        ])
+        .await,
+        ids(&chunks[..1])
+    );
+    assert!(search(vec![
+        filter("key1", "value1", FilterOperator::Eq),
+        filter("key2", "value4", FilterOperator::Eq),
+    ])
+    .await
+    .is_empty());
+}
+
+async fn update_metadata(vector_db: VectorDBTS, index: String) {
+    let chunk = chunk(vec![0., 1.], &[("key1", "value1"), ("key2", "value2")], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    vector_db
+        .update_metadata(
+            &index,
+            chunk.content_id.clone(),
+            create_metadata([("key2", "value3"), ("key3", "value4")]),
+        )
+        .await
+        .unwrap();
+    let points = vector_db
+        .get_points(&index, vec![chunk.content_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 1);
+    assert_eq!(
+        points[0].metadata,
+        create_metadata([("key1", "value1"), ("key2", "value3"), ("key3", "value4")])
+    );
+    assert_eq!(points[0].embedding, chunk.embedding);
+    assert_eq!(points[0].content_metadata, chunk.content_metadata);
+}
+
+async fn replace_metadata(vector_db: VectorDBTS, index: String) {
+    let chunk = chunk(vec![0., 1.], &[("key1", "value1"), ("key2", "value2")], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    let metadata = create_metadata([("key3", "value3")]);
+    vector_db
+        .replace_metadata(&index, chunk.content_id.clone(), metadata.clone())
+        .await
+        .unwrap();
+    let points = vector_db
+        .get_points(&index, vec![chunk.content_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 1);
+    assert_eq!(points[0].metadata, metadata);
+    assert_eq!(points[0].embedding, chunk.embedding);
+    assert_eq!(points[0].content_metadata, chunk.content_metadata);
+}
+
+async fn delete(vector_db: VectorDBTS, index: String) {
+    let chunks: Vec<_> = (0..4)
+        .map(|i| chunk(vec![i as f32, 1.], &[], "1"))
+        .collect();
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    vector_db
+        .remove_embedding(&index, &chunks[0].content_id)
+        .await
+        .unwrap();
+    vector_db
+        .remove_embeddings(&index, ids(&chunks[1..3]))
+        .await
+        .unwrap();
+    let points = vector_db.get_points(&index, ids(&chunks)).await.unwrap();
+    assert_eq!(ids(&points), ids(&chunks[3..]));
+    assert_eq!(
+        search_ids(&vector_db, &index, 10, vec![]).await,
+        ids(&chunks[3..])
+    );
+}
+
+async fn num_vectors(vector_db: VectorDBTS, index: String) {
+    assert_eq!(vector_db.num_vectors(&index).await.unwrap(), 0);
+    let chunks: Vec<_> = (0..3)
+        .map(|i| chunk(vec![i as f32, 1.], &[], "1"))
+        .collect();
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    vector_db
+        .add_embedding(&index, chunks[..1].to_vec())
+        .await
+        .unwrap();
+    assert_eq!(vector_db.num_vectors(&index).await.unwrap(), 3);
+    vector_db
+        .remove_embedding(&index, &chunks[0].content_id)
+        .await
+        .unwrap();
+    assert_eq!(vector_db.num_vectors(&index).await.unwrap(), 2);
+}
+
+async fn search_within_root(vector_db: VectorDBTS, index: String) {
+    let chunks = vec![
+        chunk(vec![0., 1.], &[], "1"),
+        chunk(vec![0.5, 1.], &[], "1"),
+        chunk(vec![0., 1.], &[], "2"),
+    ];
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    let search = |root_id: &'static str| {
+        let vector_db = vector_db.clone();
+        let index = index.clone();
+        async move {
+            vector_db
+                .search_within_root(index, root_id, vec![0., 1.], 10, vec![])
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|r| r.content_id)
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(search("1").await, ids(&chunks[..2]));
+    assert_eq!(search("2").await, ids(&chunks[2..]));
+    assert!(search("3").await.is_empty());
+}
+
+async fn search_grouped_by_root(vector_db: VectorDBTS, index: String) {
+    let chunks = vec![
+        chunk(vec![0., 1.], &[], "1"),
+        chunk(vec![0.1, 1.], &[], "1"),
+        chunk(vec![0.5, 1.], &[], "2"),
+        chunk(vec![1., 0.], &[], "3"),
+    ];
+    vector_db
+        .add_embedding(&index, chunks.clone())
+        .await
+        .unwrap();
+    let search = |k: u64| {
+        let vector_db = vector_db.clone();
+        let index = index.clone();
+        async move {
+            vector_db
+                .search_grouped_by_root(index, vec![0., 1.], k, vec![])
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|r| r.content_id)
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        search(2).await,
+        vec![chunks[0].content_id.clone(), chunks[2].content_id.clone()]
+    );
+    assert_eq!(search(10).await.len(), 3);
+}
+
+async fn drop_index(vector_db: VectorDBTS, index: String) {
+    let chunk = chunk(vec![0., 1.], &[], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    vector_db.drop_index(&index).await.unwrap();
+
+    //  Content of a dropped index doesn't come back with a new index
+    create(&vector_db, &index).await;
+    assert_eq!(stored(&vector_db, &index, vec![chunk.content_id]).await, 0);
+}
+
+async fn missing_content(vector_db: VectorDBTS, index: String, capabilities: VectorDbCapabilities) {
+    let chunk = chunk(vec![0., 1.], &[("key1", "value1")], "1");
+    vector_db
+        .add_embedding(&index, vec![chunk.clone()])
+        .await
+        .unwrap();
+    let missing_id = DataManager::make_id();
+
+    //  Missing content is left out of the points
+    let points = vector_db
+        .get_points(&index, vec![chunk.content_id.clone(), missing_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(ids(&points), vec![chunk.content_id.clone()]);
+
+    //  Removing missing content succeeds
+    vector_db
+        .remove_embedding(&index, &missing_id)
+        .await
+        .unwrap();
+    vector_db
+        .remove_embeddings(&index, vec![missing_id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(stored(&vector_db, &index, vec![chunk.content_id]).await, 1);
+
+    //  Updating missing content never creates it
+    let result = vector_db
+        .update_metadata(
+            &index,
+            missing_id.clone(),
+            create_metadata([("key1", "value2")]),
+        )
+        .await;
+    assert_eq!(result.is_err(), capabilities.update_missing_content_fails);
+    assert_eq!(stored(&vector_db, &index, vec![missing_id]).await, 0);
+}
@@ -0,0 +1,345 @@
+use super::{Filter, FilterOperator};
+
+/// Most alternatives a filter may expand to. Every alternative is searched
+/// separately since the vector databases only combine filters with AND.
+const MAX_FILTER_ALTERNATIVES: usize = 16;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+#[error("invalid filter at position {position}: {message}")]
+pub struct FilterParseError {
+    /// Offset of the offending character in the filter
+    pub position: usize,
+    pub message: String,
+}
+
+impl FilterParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Eq,
+    Neq,
+    LParen,
+    RParen,
+    And,
+    Or,
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "()=!<>'\"".contains(c)
+}
+
+fn tokenize(chars: &[char]) -> Result<Vec<(usize, Token)>, FilterParseError> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let token = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                i += 1;
+                Token::LParen
+            }
+            ')' => {
+                i += 1;
+                Token::RParen
+            }
+            '=' => {
+                i += 1;
+                if chars.get(i) == Some(&'=') {
+                    i += 1;
+                }
+                Token::Eq
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                i += 2;
+                Token::Neq
+            }
+            '!' => return Err(FilterParseError::new(start, "expected !=")),
+            c @ ('<' | '>') => {
+                return Err(FilterParseError::new(
+                    start,
+                    format!("operator {} is not supported, use = or !=", c),
+                ))
+            }
+            quote @ ('\'' | '"') => {
+                i += 1;
+                let mut value = String::new();
+                loop {
+                    match chars.get(i) {
+                        None => return Err(FilterParseError::new(start, "unterminated string")),
+                        Some('\\') if i + 1 < chars.len() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(c) if *c == quote => {
+                            i += 1;
+                            break;
+                        }
+                        Some(c) => {
+                            value.push(*c);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Quoted(value)
+            }
+            _ => {
+                while i < chars.len() && !is_delimiter(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if word.eq_ignore_ascii_case("and") {
+                    Token::And
+                } else if word.eq_ignore_ascii_case("or") {
+                    Token::Or
+                } else {
+                    Token::Word(word)
+                }
+            }
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+/// Alternatives of a filter, each one a conjunction of filters
+type Alternatives = Vec<Vec<Filter>>;
+
+/// Combines alternatives with AND, distributing it over their alternatives
+fn and(
+    left: Alternatives,
+    right: Alternatives,
+    position: usize,
+) -> Result<Alternatives, FilterParseError> {
+    if left.len() * right.len() > MAX_FILTER_ALTERNATIVES {
+        return Err(FilterParseError::new(
+            position,
+            format!(
+                "filter expands to more than {} alternatives",
+                MAX_FILTER_ALTERNATIVES
+            ),
+        ));
+    }
+    Ok(left
+        .iter()
+        .flat_map(|l| {
+            right
+                .iter()
+                .map(move |r| l.iter().chain(r).cloned().collect())
+        })
+        .collect())
+}
+
+/// Recursive descent parser of the grammar
+///
+/// ```text
+/// expr        := conjunction (OR conjunction)*
+/// conjunction := primary (AND primary)*
+/// primary     := '(' expr ')' | operand ('=' | '==' | '!=') operand
+/// operand     := word | quoted string
+/// ```
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(position, _)| *position)
+    }
+
+    fn advance(&mut self) {
+        self.next += 1;
+    }
+
+    fn expected(&self, what: &str) -> FilterParseError {
+        FilterParseError::new(self.position(), format!("expected {}", what))
+    }
+
+    fn expr(&mut self) -> Result<Alternatives, FilterParseError> {
+        let mut alternatives = self.conjunction()?;
+        while self.peek() == Some(&Token::Or) {
+            let position = self.position();
+            self.advance();
+            alternatives.extend(self.conjunction()?);
+            if alternatives.len() > MAX_FILTER_ALTERNATIVES {
+                return Err(FilterParseError::new(
+                    position,
+                    format!(
+                        "filter expands to more than {} alternatives",
+                        MAX_FILTER_ALTERNATIVES
+                    ),
+                ));
+            }
+        }
+        Ok(alternatives)
+    }
+
+    fn conjunction(&mut self) -> Result<Alternatives, FilterParseError> {
+        let mut alternatives = self.primary()?;
+        while self.peek() == Some(&Token::And) {
+            let position = self.position();
+            self.advance();
+            let right = self.primary()?;
+            alternatives = and(alternatives, right, position)?;
+        }
+        Ok(alternatives)
+    }
+
+    fn operand(&mut self, what: &str) -> Result<String, FilterParseError> {
+        match self.peek() {
+            Some(Token::Word(value) | Token::Quoted(value)) => {
+                let value = value.clone();
+                self.advance();
+                Ok(value)
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Alternatives, FilterParseError> {
+        if self.peek() == Some(&Token::LParen) {
+            self.advance();
+            let alternatives = self.expr()?;
+            if self.peek() != Some(&Token::RParen) {
+                return Err(self.expected(")"));
+            }
+            self.advance();
+            return Ok(alternatives);
+        }
+        let key = self.operand("a key or (")?;
+        let operator = match self.peek() {
+            Some(Token::Eq) => FilterOperator::Eq,
+            Some(Token::Neq) => FilterOperator::Neq,
+            _ => return Err(self.expected("= or !=")),
+        };
+        self.advance();
+        let value = self.operand("a value")?;
+        Ok(vec![vec![Filter {
+            key,
+            value,
+            operator,
+        }]])
+    }
+}
+
+/// Parses a filter expression such as `mime_type = 'text/plain' AND (lang =
+/// en OR lang = fr)` into its alternatives. Results have to match all the
+/// filters of at least one alternative.
+pub fn parse(filter: &str) -> Result<Vec<Vec<Filter>>, FilterParseError> {
+    let chars: Vec<char> = filter.chars().collect();
+    let mut parser = Parser {
+        tokens: tokenize(&chars)?,
+        next: 0,
+        end: chars.len(),
+    };
+    let alternatives = parser.expr()?;
+    if parser.peek().is_some() {
+        return Err(parser.expected("AND or OR"));
+    }
+    Ok(alternatives)
+}
+
+/// Parses filter expressions which all have to match
+pub fn parse_all(filters: &[String]) -> Result<Vec<Vec<Filter>>, FilterParseError> {
+    let mut alternatives = vec![vec![]];
+    for filter in filters {
+        alternatives = and(alternatives, parse(filter)?, 0)?;
+    }
+    Ok(alternatives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(key: &str, operator: FilterOperator, value: &str) -> Filter {
+        Filter {
+            key: key.to_string(),
+            value: value.to_string(),
+            operator,
+        }
+    }
+
+    #[test]
+    fn test_parse_expressions() {
+        use FilterOperator::{Eq, Neq};
+
+        assert_eq!(
+            parse("lang=en").unwrap(),
+            vec![vec![filter("lang", Eq, "en")]]
+        );
+        assert_eq!(
+            parse("mime_type = 'text/plain' AND size == 1000").unwrap(),
+            vec![vec![
+                filter("mime_type", Eq, "text/plain"),
+                filter("size", Eq, "1000"),
+            ]]
+        );
+        assert_eq!(
+            parse(r#"lang = en or "source" != 'it\'s web'"#).unwrap(),
+            vec![
+                vec![filter("lang", Eq, "en")],
+                vec![filter("source", Neq, "it's web")],
+            ]
+        );
+        //  AND binds tighter than OR, parentheses override it
+        assert_eq!(
+            parse("a=1 OR b=2 AND c=3").unwrap(),
+            vec![
+                vec![filter("a", Eq, "1")],
+                vec![filter("b", Eq, "2"), filter("c", Eq, "3")],
+            ]
+        );
+        assert_eq!(
+            parse("(a=1 OR b=2) AND c=3").unwrap(),
+            vec![
+                vec![filter("a", Eq, "1"), filter("c", Eq, "3")],
+                vec![filter("b", Eq, "2"), filter("c", Eq, "3")],
+            ]
+        );
+        assert_eq!(
+            parse_all(&["a=1 OR a=2".to_string(), "b!=3".to_string()]).unwrap(),
+            vec![
+                vec![filter("a", Eq, "1"), filter("b", Neq, "3")],
+                vec![filter("a", Eq, "2"), filter("b", Neq, "3")],
+            ]
+        );
+        assert_eq!(parse_all(&[]).unwrap(), vec![Vec::<Filter>::new()]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("lang = en AND (size = 10").unwrap_err();
+        assert_eq!(err, FilterParseError::new(24, "expected )"));
+        assert_eq!(err.to_string(), "invalid filter at position 24: expected )");
+
+        assert_eq!(parse("lang en").unwrap_err().position, 5);
+        assert_eq!(parse("size > 1000").unwrap_err().position, 5);
+        assert_eq!(parse("lang = 'en").unwrap_err().position, 7);
+        assert_eq!(parse("a=1 b=2").unwrap_err().position, 4);
+        assert_eq!(parse("").unwrap_err().position, 0);
+
+        let too_many = vec!["a=1 OR a=2 OR a=3 OR a=4 OR a=5".to_string(); 2];
+        assert!(parse_all(&too_many).is_err());
+    }
+}
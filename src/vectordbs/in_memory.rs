@@ -0,0 +1,368 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use super::{group_by_root, CreateIndexParams, VectorDb, VectorDbCapabilities};
+use crate::vectordbs::{Filter, SearchResult, VectorChunk};
+
+/// A vector database which keeps all embeddings in memory and searches them
+/// exhaustively. Meant for local development and tests.
+#[derive(Debug, Default)]
+pub struct InMemoryDb {
+    indexes: RwLock<HashMap<String, HashMap<String, VectorChunk>>>,
+}
+
+impl InMemoryDb {
+    pub fn new() -> InMemoryDb {
+        Self::default()
+    }
+
+    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm_a == 0.0 || norm_b == 0.0 {
+            return 0.0;
+        }
+        dot / (norm_a * norm_b)
+    }
+
+    /// Scores the chunks of the index `in_scope` keeps which match all the
+    /// filters, with the conditions each of them satisfied
+    fn search_chunks(
+        &self,
+        index: &str,
+        query_embedding: &[f32],
+        k: u64,
+        filters: &[Filter],
+        in_scope: impl Fn(&VectorChunk) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        let indexes = self.indexes.read().unwrap();
+        let chunks = indexes
+            .get(index)
+            .ok_or_else(|| anyhow!("index not found: {}", index))?;
+        let mut results: Vec<SearchResult> = chunks
+            .values()
+            .filter(|chunk| in_scope(chunk))
+            .filter_map(|chunk| {
+                let matched_filters = filters
+                    .iter()
+                    .map(|filter| filter.evaluate(&chunk.metadata))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(SearchResult {
+                    content_id: chunk.content_id.clone(),
+                    confidence_score: Self::cosine_similarity(query_embedding, &chunk.embedding),
+                    metadata: chunk.metadata.clone(),
+                    root_content_metadata: chunk.root_content_metadata.clone(),
+                    content_metadata: chunk.content_metadata.clone(),
+                    matched_filters,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.confidence_score.total_cmp(&a.confidence_score));
+        results.truncate(k as usize);
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl VectorDb for InMemoryDb {
+    fn name(&self) -> String {
+        "in_memory".into()
+    }
+
+    fn capabilities(&self) -> VectorDbCapabilities {
+        VectorDbCapabilities {
+            filters: true,
+            neq_matches_missing_key: true,
+            search_within_root: true,
+            search_grouped_by_root: true,
+            replace_metadata: true,
+            num_vectors: true,
+            update_missing_content_fails: true,
+        }
+    }
+
+    async fn create_index(&self, index: CreateIndexParams) -> Result<()> {
+        self.indexes
+            .write()
+            .unwrap()
+            .entry(index.vectordb_index_name)
+            .or_default();
+        Ok(())
+    }
+
+    async fn add_embedding(&self, index: &str, chunks: Vec<VectorChunk>) -> Result<()> {
+        let mut indexes = self.indexes.write().unwrap();
+        let index = indexes.entry(index.to_string()).or_default();
+        for chunk in chunks {
+            index.insert(chunk.content_id.clone(), chunk);
+        }
+        Ok(())
+    }
+
+    async fn remove_embedding(&self, index: &str, content_id: &str) -> Result<()> {
+        if let Some(index) = self.indexes.write().unwrap().get_mut(index) {
+            index.remove(content_id);
+        }
+        Ok(())
+    }
+
+    async fn get_points(&self, index: &str, content_ids: Vec<String>) -> Result<Vec<VectorChunk>> {
+        let indexes = self.indexes.read().unwrap();
+        let Some(index) = indexes.get(index) else {
+            return Ok(Vec::new());
+        };
+        Ok(content_ids
+            .iter()
+            .filter_map(|id| index.get(id).cloned())
+            .collect())
+    }
+
+    async fn update_metadata(
+        &self,
+        index: &str,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        let mut indexes = self.indexes.write().unwrap();
+        let chunk = indexes
+            .get_mut(index)
+            .and_then(|index| index.get_mut(&content_id))
+            .ok_or_else(|| anyhow!("content {} not found in index {}", content_id, index))?;
+        chunk.metadata.extend(metadata);
+        Ok(())
+    }
+
+    async fn replace_metadata(
+        &self,
+        index: &str,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        let mut indexes = self.indexes.write().unwrap();
+        let chunk = indexes
+            .get_mut(index)
+            .and_then(|index| index.get_mut(&content_id))
+            .ok_or_else(|| anyhow!("content {} not found in index {}", content_id, index))?;
+        chunk.metadata = metadata;
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        index: String,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Vec<Filter>,
+        include_metadata: bool,
+    ) -> Result<Vec<SearchResult>> {
+        let results = self.search_chunks(&index, &query_embedding, k, &filters, |_| true)?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.project(include_metadata))
+            .collect())
+    }
+
+    async fn search_within_root(
+        &self,
+        index: String,
+        root_id: &str,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Vec<Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_chunks(&index, &query_embedding, k, &filters, |chunk| {
+            chunk
+                .root_content_metadata
+                .as_ref()
+                .is_some_and(|root| root.id.id == root_id)
+        })
+    }
+
+    async fn search_grouped_by_root(
+        &self,
+        index: String,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Vec<Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        //  Every chunk is scored anyway, so all of them are grouped
+        let results = self.search_chunks(&index, &query_embedding, u64::MAX, &filters, |_| true)?;
+        Ok(group_by_root(results, k))
+    }
+
+    async fn drop_index(&self, index: &str) -> Result<()> {
+        self.indexes.write().unwrap().remove(index);
+        Ok(())
+    }
+
+    async fn num_vectors(&self, index: &str) -> Result<u64> {
+        Ok(self
+            .indexes
+            .read()
+            .unwrap()
+            .get(index)
+            .map(|index| index.len() as u64)
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::InMemoryDb;
+    use crate::{
+        test_util::db_utils::{create_metadata, test_mock_content_metadata},
+        vectordbs::{
+            conformance,
+            tests::{
+                basic_search,
+                crud_operations,
+                insertion_idempotent,
+                merge_metadata,
+                remove_embeddings,
+                replace_metadata,
+                search_filters,
+                search_grouped_by_root,
+                search_within_root,
+                search_without_metadata,
+                store_metadata,
+            },
+            Filter,
+            FilterOperator,
+            MatchedFilter,
+            VectorChunk,
+            VectorDBTS,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_crud_operations() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        crud_operations(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_remove_embeddings() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        remove_embeddings(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_basic() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        basic_search(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_without_metadata() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        search_without_metadata(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_store_metadata() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        store_metadata(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_merge_metadata() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        merge_metadata(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_replace_metadata() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        replace_metadata(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_insertion_idempotent() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        insertion_idempotent(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_filters() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        search_filters(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_within_root() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        search_within_root(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_by_root() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        search_grouped_by_root(vector_db, "test").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_explains_matched_filters() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        let chunks = [("1", "handbook"), ("2", "web")].map(|(content_id, source)| VectorChunk {
+            content_id: content_id.to_string(),
+            embedding: vec![0., 2.],
+            metadata: create_metadata(vec![("source", source), ("lang", "en")]),
+            root_content_metadata: None,
+            content_metadata: test_mock_content_metadata(content_id, "1", "graph1"),
+        });
+        vector_db
+            .add_embedding("test", chunks.to_vec())
+            .await
+            .unwrap();
+
+        let source = Filter {
+            key: "source".to_string(),
+            value: "handbook".to_string(),
+            operator: FilterOperator::Eq,
+        };
+        let author = Filter {
+            key: "author".to_string(),
+            value: "bob".to_string(),
+            operator: FilterOperator::Neq,
+        };
+        let results = vector_db
+            .search(
+                "test".to_string(),
+                vec![0., 2.],
+                10,
+                vec![source.clone(), author.clone()],
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content_id, "1");
+        assert_eq!(
+            results[0].matched_filters,
+            vec![
+                MatchedFilter {
+                    filter: source,
+                    attribute_value: Some(json!("handbook")),
+                },
+                MatchedFilter {
+                    filter: author,
+                    attribute_value: None,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        let vector_db: VectorDBTS = Arc::new(InMemoryDb::new());
+        let report = conformance::run(vector_db, "conformance").await;
+        assert!(report.skipped.is_empty());
+    }
+}
@@ -28,7 +28,15 @@ use lancedb::{
 };
 use tracing;
 
-use super::{CreateIndexParams, Filter, FilterOperator, SearchResult, VectorChunk, VectorDb};
+use super::{
+    CreateIndexParams,
+    Filter,
+    FilterOperator,
+    SearchResult,
+    VectorChunk,
+    VectorDb,
+    VectorDbCapabilities,
+};
 use crate::server_config::LancedbConfig;
 
 fn from_filter_to_str(filters: Vec<Filter>) -> String {
@@ -194,6 +202,18 @@ impl VectorDb for LanceDb {
         "lancedb".into()
     }
 
+    fn capabilities(&self) -> VectorDbCapabilities {
+        VectorDbCapabilities {
+            filters: true,
+            neq_matches_missing_key: false,
+            search_within_root: false,
+            search_grouped_by_root: false,
+            replace_metadata: false,
+            num_vectors: true,
+            update_missing_content_fails: false,
+        }
+    }
+
     #[tracing::instrument]
     async fn create_index(&self, index: CreateIndexParams) -> Result<()> {
         let schema = Arc::new(Schema::new(vec![
@@ -391,6 +411,7 @@ impl VectorDb for LanceDb {
         query_embedding: Vec<f32>,
         k: u64,
         filters: Vec<Filter>,
+        include_metadata: bool,
     ) -> Result<Vec<SearchResult>> {
         // FIXME remove the hardcoding to cosine
         // We need to pass the distance metric from
@@ -426,13 +447,15 @@ impl VectorDb for LanceDb {
                 .unwrap();
 
             for (chunk, distance) in izip!(vector_chunks, distance_values) {
-                results.push(SearchResult {
+                let result = SearchResult {
                     content_id: chunk.content_id,
                     confidence_score: *distance,
                     metadata: chunk.metadata,
                     content_metadata: chunk.content_metadata,
                     root_content_metadata: chunk.root_content_metadata,
-                });
+                    matched_filters: Vec::new(),
+                };
+                results.push(result.project(include_metadata));
             }
         }
         Ok(results)
@@ -1,13 +1,20 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use indexify_internal_api::ContentMetadata;
+use indexify_internal_api::{ContentMetadata, ContentMetadataId, ContentSource};
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
 use crate::server_config::{IndexStoreKind, VectorIndexConfig};
 
+#[cfg(test)]
+pub mod conformance;
+pub mod filter_expr;
+pub mod in_memory;
 pub mod lancedb;
 //pub mod open_search;
 pub mod pg_vector;
@@ -48,6 +55,78 @@ pub struct SearchResult {
     pub metadata: HashMap<String, serde_json::Value>,
     pub root_content_metadata: Option<ContentMetadata>,
     pub content_metadata: ContentMetadata,
+    /// The filter conditions the result satisfied, empty for databases which
+    /// don't explain their matches
+    pub matched_filters: Vec<MatchedFilter>,
+}
+
+impl SearchResult {
+    /// A result with only the content id and the score, returned by searches
+    /// which don't include metadata
+    pub fn id_only(content_id: String, confidence_score: f32) -> Self {
+        let content_metadata = ContentMetadata {
+            id: ContentMetadataId::new(&content_id),
+            parent_id: None,
+            root_content_id: None,
+            latest: true,
+            namespace: String::new(),
+            name: String::new(),
+            content_type: String::new(),
+            labels: HashMap::new(),
+            storage_url: String::new(),
+            created_at: 0,
+            source: ContentSource::Ingestion,
+            size_bytes: 0,
+            tombstoned: false,
+            hash: String::new(),
+            extraction_policy_ids: HashMap::new(),
+            extraction_graph_names: Vec::new(),
+            acl: None,
+            tombstone_reason: None,
+        };
+        Self {
+            content_id,
+            confidence_score,
+            metadata: HashMap::new(),
+            root_content_metadata: None,
+            content_metadata,
+            matched_filters: Vec::new(),
+        }
+    }
+
+    /// Drops everything but the content id and the score when metadata is
+    /// not included, for databases which can't leave it out of the query
+    pub fn project(self, include_metadata: bool) -> Self {
+        if include_metadata {
+            return self;
+        }
+        Self::id_only(self.content_id, self.confidence_score)
+    }
+
+    /// Id of the root content the result was extracted from, its own id when
+    /// it is a root
+    pub fn root_id(&self) -> &str {
+        self.root_content_metadata
+            .as_ref()
+            .map(|root| root.id.id.as_str())
+            .or(self.content_metadata.root_content_id.as_deref())
+            .unwrap_or(&self.content_id)
+    }
+}
+
+/// Number of chunks fetched per requested document by grouped searches of
+/// databases which can't group results in the query
+pub const GROUPED_SEARCH_OVERFETCH: u64 = 5;
+
+/// Keeps the best chunk of every root content from results ordered best
+/// first, and the first `k` of them
+pub fn group_by_root(results: Vec<SearchResult>, k: u64) -> Vec<SearchResult> {
+    let mut roots = HashSet::new();
+    results
+        .into_iter()
+        .filter(|result| roots.insert(result.root_id().to_string()))
+        .take(k as usize)
+        .collect()
 }
 
 pub type VectorDBTS = Arc<dyn VectorDb + Sync + Send>;
@@ -78,13 +157,15 @@ impl VectorChunk {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Display, Debug, Clone, PartialEq)]
 pub enum FilterOperator {
+    #[strum(serialize = "=")]
     Eq,
+    #[strum(serialize = "!=")]
     Neq,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Filter {
     pub key: String,
     pub value: String,
@@ -92,23 +173,78 @@ pub struct Filter {
 }
 
 impl Filter {
-    pub fn from_str(filter: &str) -> Result<Self> {
-        // FIXME this is a very basic implementation, we should add more operators
-        let parts: Vec<&str> = filter.split('=').collect();
-        if parts.len() != 2 {
-            return Err(anyhow::anyhow!("Invalid filter: {}", filter));
+    /// Parses a filter expression like `mime_type = 'text/plain' AND size =
+    /// 1000` into the filters which all have to match. Expressions with
+    /// alternatives are parsed with `parse_alternatives`.
+    pub fn parse(filter: &str) -> Result<Vec<Filter>> {
+        let mut alternatives = Self::parse_alternatives(filter)?;
+        if alternatives.len() > 1 {
+            return Err(anyhow::anyhow!(
+                "filter {} has alternatives, only AND is supported here",
+                filter
+            ));
         }
-        let key = parts[0].to_string();
-        let value = parts[1].to_string();
-        let operator = FilterOperator::Eq;
-        Ok(Self {
-            key,
-            value,
-            operator,
+        Ok(alternatives.pop().unwrap_or_default())
+    }
+
+    /// Parses a filter expression combining comparisons with AND, OR and
+    /// parentheses into its alternatives. Results have to match all the
+    /// filters of at least one alternative.
+    pub fn parse_alternatives(filter: &str) -> Result<Vec<Vec<Filter>>> {
+        Ok(filter_expr::parse(filter)?)
+    }
+
+    /// Evaluates the filter against the metadata of a chunk, with the
+    /// condition it satisfied when it matches. Values which aren't strings
+    /// are compared with their json encoding.
+    pub fn evaluate(&self, metadata: &HashMap<String, serde_json::Value>) -> Option<MatchedFilter> {
+        let attribute_value = metadata.get(&self.key);
+        let equal = match attribute_value {
+            Some(serde_json::Value::String(value)) => *value == self.value,
+            Some(value) => value.to_string() == self.value,
+            None => false,
+        };
+        let matches = match self.operator {
+            FilterOperator::Eq => equal,
+            FilterOperator::Neq => !equal,
+        };
+        matches.then(|| MatchedFilter {
+            filter: self.clone(),
+            attribute_value: attribute_value.cloned(),
         })
     }
 }
 
+/// A filter condition a search result satisfied, with the value of the
+/// attribute it was compared to. The value is None for `Neq` conditions on
+/// attributes the result doesn't have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedFilter {
+    pub filter: Filter,
+    pub attribute_value: Option<serde_json::Value>,
+}
+
+/// The optional behaviors of a vector database. The conformance tests read
+/// them to skip the cases of unsupported behaviors explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorDbCapabilities {
+    /// Searches accept filters
+    pub filters: bool,
+    /// `Neq` filters match content which doesn't have the key at all
+    pub neq_matches_missing_key: bool,
+    /// `search_within_root` is implemented
+    pub search_within_root: bool,
+    /// `search_grouped_by_root` is implemented
+    pub search_grouped_by_root: bool,
+    /// `replace_metadata` is implemented
+    pub replace_metadata: bool,
+    /// `num_vectors` is implemented
+    pub num_vectors: bool,
+    /// `update_metadata` fails for content missing from the index instead of
+    /// doing nothing
+    pub update_missing_content_fails: bool,
+}
+
 /// A trait that defines the interface for interacting with a vector database.
 /// The vector database is responsible for storing and querying vector
 /// embeddings.
@@ -125,10 +261,32 @@ pub trait VectorDb {
     /// content_id key
     async fn remove_embedding(&self, index: &str, content_id: &str) -> Result<()>;
 
+    /// Removes the vector embeddings of all the specified content ids from
+    /// the index. Removal continues past failures, the returned error lists
+    /// every content id whose embeddings could not be removed.
+    async fn remove_embeddings(&self, index: &str, content_ids: Vec<String>) -> Result<()> {
+        let mut failures = Vec::new();
+        for content_id in content_ids {
+            if let Err(e) = self.remove_embedding(index, &content_id).await {
+                failures.push(format!("{}: {}", content_id, e));
+            }
+        }
+        if !failures.is_empty() {
+            return Err(anyhow::anyhow!(
+                "unable to remove embeddings from {}: {}",
+                index,
+                failures.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
     /// Retrieves the vector embeddings for the specified content IDs
     async fn get_points(&self, index: &str, content_ids: Vec<String>) -> Result<Vec<VectorChunk>>;
 
-    /// Update metadata for the specified content ID
+    /// Merges metadata into the metadata stored for the specified content ID.
+    /// Keys present in `metadata` overwrite the stored values, all other
+    /// stored keys are kept.
     async fn update_metadata(
         &self,
         index: &str,
@@ -136,16 +294,66 @@ pub trait VectorDb {
         metadata: HashMap<String, serde_json::Value>,
     ) -> Result<()>;
 
+    /// Replaces the metadata stored for the specified content ID, dropping
+    /// any stored key not present in `metadata`.
+    async fn replace_metadata(
+        &self,
+        _index: &str,
+        _content_id: String,
+        _metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "replacing metadata is not supported by {}",
+            self.name()
+        ))
+    }
+
     /// Searches for the nearest neighbors of a query vector in the specified
-    /// index.
+    /// index. When `include_metadata` is false the results only carry the
+    /// content id and the score.
     async fn search(
         &self,
         index: String,
         query_embedding: Vec<f32>,
         k: u64,
         filters: Vec<Filter>,
+        include_metadata: bool,
     ) -> Result<Vec<SearchResult>>;
 
+    /// Searches for the nearest neighbors of a query vector in the specified
+    /// index, considering only content derived from the root content
+    /// `root_id`.
+    async fn search_within_root(
+        &self,
+        _index: String,
+        _root_id: &str,
+        _query_embedding: Vec<f32>,
+        _k: u64,
+        _filters: Vec<Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        Err(anyhow::anyhow!(
+            "search within root content is not supported by {}",
+            self.name()
+        ))
+    }
+
+    /// Searches for the `k` root contents with the nearest chunks to a query
+    /// vector in the specified index. Every root content is returned once,
+    /// as its best scoring chunk, for document level retrieval over chunk
+    /// level embeddings.
+    async fn search_grouped_by_root(
+        &self,
+        _index: String,
+        _query_embedding: Vec<f32>,
+        _k: u64,
+        _filters: Vec<Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        Err(anyhow::anyhow!(
+            "grouped search by root content is not supported by {}",
+            self.name()
+        ))
+    }
+
     /// Deletes the specified vector index from the vector database.
     async fn drop_index(&self, index: &str) -> Result<()>;
 
@@ -154,6 +362,9 @@ pub trait VectorDb {
 
     fn name(&self) -> String;
 
+    /// The optional behaviors the database supports
+    fn capabilities(&self) -> VectorDbCapabilities;
+
     //  TODO: Add delete content using namespace and content id
 }
 
@@ -171,6 +382,7 @@ pub async fn create_vectordb(config: VectorIndexConfig) -> Result<VectorDBTS> {
         IndexStoreKind::Lancedb => Ok(Arc::new(
             lancedb::LanceDb::new(&config.lancedb_config.unwrap()).await?,
         )),
+        IndexStoreKind::InMemory => Ok(Arc::new(in_memory::InMemoryDb::new())),
     }
 }
 
@@ -215,6 +427,35 @@ mod tests {
         assert_eq!(num_elements, 0);
     }
 
+    pub async fn remove_embeddings(vector_db: VectorDBTS, index_name: &str) {
+        let mut chunks = Vec::new();
+        for content_id in ["1", "2", "3", "4"] {
+            chunks.push(VectorChunk {
+                content_id: content_id.into(),
+                embedding: vec![0., 2.],
+                metadata: create_metadata(vec![("key1", "value1")]),
+                root_content_metadata: None,
+                content_metadata: test_mock_content_metadata(content_id, "", "graph1"),
+            });
+        }
+        vector_db.add_embedding(index_name, chunks).await.unwrap();
+        assert_eq!(vector_db.num_vectors(index_name).await.unwrap(), 4);
+
+        vector_db
+            .remove_embeddings(
+                index_name,
+                vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(vector_db.num_vectors(index_name).await.unwrap(), 1);
+        let points = vector_db
+            .get_points(index_name, vec!["4".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(points.len(), 1);
+    }
+
     pub async fn basic_search(vector_db: VectorDBTS, index_name: &str) {
         let metadata1 = create_metadata(vec![("key1", "value1"), ("key2", "value2")]);
         let chunk = VectorChunk {
@@ -230,12 +471,45 @@ mod tests {
             .unwrap();
 
         let results = vector_db
-            .search(index_name.into(), vec![10., 8.], 1, vec![])
+            .search(index_name.into(), vec![10., 8.], 1, vec![], true)
             .await
             .unwrap();
         assert_eq!(results.len(), 1);
     }
 
+    pub async fn search_without_metadata(vector_db: VectorDBTS, index_name: &str) {
+        let content_id = make_id();
+        let chunk = VectorChunk {
+            content_id: content_id.clone(),
+            embedding: vec![0., 2.],
+            metadata: create_metadata(vec![("key1", "value1")]),
+            root_content_metadata: Some(test_mock_content_metadata("1", "", "graph1")),
+            content_metadata: test_mock_content_metadata(&content_id, "1", "graph1"),
+        };
+        vector_db
+            .add_embedding(index_name, vec![chunk])
+            .await
+            .unwrap();
+
+        let full = vector_db
+            .search(index_name.into(), vec![0., 2.], 1, vec![], true)
+            .await
+            .unwrap();
+        let projected = vector_db
+            .search(index_name.into(), vec![0., 2.], 1, vec![], false)
+            .await
+            .unwrap();
+        assert_eq!(projected.len(), 1);
+        let result = &projected[0];
+        assert_eq!(result.content_id, content_id);
+        assert_eq!(result.confidence_score, full[0].confidence_score);
+        assert_eq!(result.content_metadata.id.id, content_id);
+        assert!(result.metadata.is_empty());
+        assert!(result.root_content_metadata.is_none());
+        assert!(result.content_metadata.labels.is_empty());
+        assert!(result.content_metadata.storage_url.is_empty());
+    }
+
     fn make_id() -> String {
         DataManager::make_id()
     }
@@ -295,6 +569,74 @@ mod tests {
         assert_eq!(result[0].metadata, new_metadata);
     }
 
+    pub async fn merge_metadata(vector_db: VectorDBTS, index_name: &str) {
+        let content_id = make_id();
+        let chunk = VectorChunk {
+            content_id: content_id.clone(),
+            embedding: vec![0.1, 0.2],
+            metadata: create_metadata(vec![("key1", "value1"), ("key2", "value2")]),
+            root_content_metadata: Some(test_mock_content_metadata("0", "1", "graph1")),
+            content_metadata: test_mock_content_metadata("0", "1", "graph1"),
+        };
+        vector_db
+            .add_embedding(index_name, vec![chunk.clone()])
+            .await
+            .unwrap();
+
+        vector_db
+            .update_metadata(
+                index_name,
+                content_id.clone(),
+                create_metadata(vec![("key2", "value3"), ("key3", "value4")]),
+            )
+            .await
+            .unwrap();
+        let result = vector_db
+            .get_points(index_name, vec![content_id])
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].metadata,
+            create_metadata(vec![
+                ("key1", "value1"),
+                ("key2", "value3"),
+                ("key3", "value4")
+            ])
+        );
+        assert_eq!(result[0].content_metadata, chunk.content_metadata);
+        assert_eq!(result[0].embedding, chunk.embedding);
+    }
+
+    pub async fn replace_metadata(vector_db: VectorDBTS, index_name: &str) {
+        let content_id = make_id();
+        let chunk = VectorChunk {
+            content_id: content_id.clone(),
+            embedding: vec![0.1, 0.2],
+            metadata: create_metadata(vec![("key1", "value1"), ("key2", "value2")]),
+            root_content_metadata: Some(test_mock_content_metadata("0", "1", "graph1")),
+            content_metadata: test_mock_content_metadata("0", "1", "graph1"),
+        };
+        vector_db
+            .add_embedding(index_name, vec![chunk.clone()])
+            .await
+            .unwrap();
+
+        let new_metadata = create_metadata(vec![("key3", "value3")]);
+        vector_db
+            .replace_metadata(index_name, content_id.clone(), new_metadata.clone())
+            .await
+            .unwrap();
+        let result = vector_db
+            .get_points(index_name, vec![content_id])
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].metadata, new_metadata);
+        assert_eq!(result[0].content_metadata, chunk.content_metadata);
+        assert_eq!(result[0].embedding, chunk.embedding);
+    }
+
     pub async fn insertion_idempotent(vector_db: VectorDBTS, index_name: &str) {
         let metadata1 = HashMap::from([
             ("key1".to_string(), json!("value1")),
@@ -353,6 +695,7 @@ mod tests {
                     value: "value1".to_string(),
                     operator: FilterOperator::Eq,
                 }],
+                true,
             )
             .await
             .unwrap();
@@ -369,6 +712,7 @@ mod tests {
                     value: "value1".to_string(),
                     operator: FilterOperator::Neq,
                 }],
+                true,
             )
             .await
             .unwrap();
@@ -392,6 +736,7 @@ mod tests {
                         operator: FilterOperator::Eq,
                     },
                 ],
+                true,
             )
             .await
             .unwrap();
@@ -415,6 +760,7 @@ mod tests {
                         operator: FilterOperator::Eq,
                     },
                 ],
+                true,
             )
             .await
             .unwrap();
@@ -422,11 +768,106 @@ mod tests {
 
         assert_eq!(
             vector_db
-                .search(index_name.to_string(), vec![0., 2.], 2, vec![])
+                .search(index_name.to_string(), vec![0., 2.], 2, vec![], true)
                 .await
                 .unwrap()
                 .len(),
             2
         );
     }
+
+    pub async fn search_within_root(vector_db: VectorDBTS, index_name: &str) {
+        let metadata = create_metadata(vec![("key1", "value1")]);
+        let mut chunks = Vec::new();
+        for (root_id, content_id, embedding) in [
+            ("1", make_id(), vec![0., 2.]),
+            ("1", make_id(), vec![1., 2.]),
+            ("2", make_id(), vec![0., 2.]),
+        ] {
+            chunks.push(VectorChunk {
+                content_id: content_id.clone(),
+                embedding,
+                metadata: metadata.clone(),
+                root_content_metadata: Some(test_mock_content_metadata(root_id, "", "graph1")),
+                content_metadata: test_mock_content_metadata(&content_id, root_id, "graph1"),
+            });
+        }
+        vector_db
+            .add_embedding(index_name, chunks.clone())
+            .await
+            .unwrap();
+
+        let res = vector_db
+            .search_within_root(index_name.to_string(), "1", vec![0., 2.], 10, vec![])
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 2);
+        assert!(res
+            .iter()
+            .all(|r| r.content_metadata.root_content_id == Some("1".to_string())));
+
+        let res = vector_db
+            .search_within_root(index_name.to_string(), "2", vec![0., 2.], 10, vec![])
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].content_id, chunks[2].content_id);
+
+        let res = vector_db
+            .search_within_root(index_name.to_string(), "3", vec![0., 2.], 10, vec![])
+            .await
+            .unwrap();
+        assert!(res.is_empty());
+    }
+
+    pub async fn search_grouped_by_root(vector_db: VectorDBTS, index_name: &str) {
+        let mut chunks = Vec::new();
+        for (root_id, embedding) in [
+            ("1", vec![0., 2.]),
+            ("1", vec![0.2, 2.]),
+            ("1", vec![0.4, 2.]),
+            ("2", vec![1., 2.]),
+            ("2", vec![0.1, 2.]),
+            ("3", vec![2., 0.]),
+        ] {
+            let content_id = make_id();
+            chunks.push(VectorChunk {
+                content_id: content_id.clone(),
+                embedding,
+                metadata: create_metadata(vec![("key1", "value1")]),
+                root_content_metadata: Some(test_mock_content_metadata(root_id, "", "graph1")),
+                content_metadata: test_mock_content_metadata(&content_id, root_id, "graph1"),
+            });
+        }
+        vector_db
+            .add_embedding(index_name, chunks.clone())
+            .await
+            .unwrap();
+
+        //  One result per document, its best chunk, best documents first
+        let res = vector_db
+            .search_grouped_by_root(index_name.to_string(), vec![0., 2.], 2, vec![])
+            .await
+            .unwrap();
+        let results: Vec<_> = res
+            .iter()
+            .map(|r| (r.root_id(), r.content_id.clone()))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("1", chunks[0].content_id.clone()),
+                ("2", chunks[4].content_id.clone())
+            ]
+        );
+
+        //  Fewer documents than requested
+        let res = vector_db
+            .search_grouped_by_root(index_name.to_string(), vec![0., 2.], 10, vec![])
+            .await
+            .unwrap();
+        let mut roots: Vec<_> = res.iter().map(|r| r.root_id().to_string()).collect();
+        roots.sort();
+        assert_eq!(roots, vec!["1", "2", "3"]);
+    }
 }
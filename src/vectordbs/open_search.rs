@@ -156,6 +156,7 @@ impl VectorDb for OpenSearchKnn {
         query_embedding: Vec<f32>,
         k: u64,
         _filters: Vec<super::Filter>,
+        _include_metadata: bool,
     ) -> Result<Vec<SearchResult>> {
         let response = self
             .create_client()?
@@ -332,7 +333,7 @@ mod tests {
         }
 
         let results = opensearch
-            .search(TEST_INDEX_NAME.into(), vec![10., 8.], 1, vec![])
+            .search(TEST_INDEX_NAME.into(), vec![10., 8.], 1, vec![], true)
             .await
             .unwrap();
         assert_eq!(results.len(), 1);
@@ -6,7 +6,7 @@ use indexify_internal_api::ContentMetadata;
 use pgvector::Vector;
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres, Row};
 
-use super::{CreateIndexParams, SearchResult, VectorChunk, VectorDb};
+use super::{CreateIndexParams, SearchResult, VectorChunk, VectorDb, VectorDbCapabilities};
 use crate::{server_config::PgVectorConfig, utils::PostgresIndexName, vectordbs::FilterOperator};
 
 #[derive(Debug)]
@@ -153,6 +153,26 @@ impl VectorDb for PgVector {
         index: &str,
         content_id: String,
         metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        let index = PostgresIndexName::new(index);
+        let query = format!(
+            "UPDATE {} SET metadata = COALESCE(metadata, '{{}}'::jsonb) || $2 WHERE content_id = $1",
+            index
+        );
+        let metadata = serde_json::to_value(metadata)?;
+        let _rows_affected = sqlx::query(&query)
+            .bind(content_id)
+            .bind(metadata)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn replace_metadata(
+        &self,
+        index: &str,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
     ) -> Result<()> {
         let index = PostgresIndexName::new(index);
         let query = format!("UPDATE {} SET metadata = $2 WHERE content_id = $1", index);
@@ -183,6 +203,7 @@ impl VectorDb for PgVector {
         query_embedding: Vec<f32>,
         k: u64,
         filters: Vec<super::Filter>,
+        include_metadata: bool,
     ) -> Result<Vec<SearchResult>> {
         let index = PostgresIndexName::new(&index);
         let mut query = format!(
@@ -219,6 +240,10 @@ impl VectorDb for PgVector {
         for row in rows {
             let content_id: String = row.get(0);
             let confidence_score: f32 = row.get(1);
+            if !include_metadata {
+                results.push(SearchResult::id_only(content_id, confidence_score));
+                continue;
+            }
             let metadata: serde_json::Value = row.get(2);
             let root_content_metadata: serde_json::Value = row.get(3);
             let content_metadata: serde_json::Value = row.get(4);
@@ -234,6 +259,7 @@ impl VectorDb for PgVector {
                 confidence_score,
                 metadata,
                 root_content_metadata,
+                matched_filters: Vec::new(),
                 content_metadata,
             });
         }
@@ -261,6 +287,18 @@ impl VectorDb for PgVector {
     fn name(&self) -> String {
         "pg_vector".into()
     }
+
+    fn capabilities(&self) -> VectorDbCapabilities {
+        VectorDbCapabilities {
+            filters: true,
+            neq_matches_missing_key: false,
+            search_within_root: false,
+            search_grouped_by_root: false,
+            replace_metadata: true,
+            num_vectors: true,
+            update_missing_content_fails: false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -271,6 +309,7 @@ mod tests {
     use crate::{
         server_config::PgVectorConfig,
         vectordbs::{
+            conformance,
             pg_vector::PgVector,
             tests::{basic_search, crud_operations, insertion_idempotent, search_filters},
             IndexDistance,
@@ -425,4 +464,26 @@ mod tests {
             .unwrap();
         search_filters(vector_db, index_name).await;
     }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        let Some(addr) = conformance::backend_config("INDEXIFY_TEST_PG_VECTOR_ADDR") else {
+            return;
+        };
+        let vector_db: VectorDBTS = Arc::new(
+            PgVector::new(PgVectorConfig {
+                addr,
+                m: 16,
+                efconstruction: 64,
+                efsearch: 40,
+            })
+            .await
+            .unwrap(),
+        );
+        let report = conformance::run(vector_db, "conformance").await;
+        assert_eq!(
+            report.skipped,
+            vec!["search_within_root", "search_grouped_by_root"]
+        );
+    }
 }
@@ -27,7 +27,7 @@ use qdrant_client::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::{CreateIndexParams, VectorDb};
+use super::{CreateIndexParams, VectorDb, VectorDbCapabilities};
 use crate::{
     server_config::QdrantConfig,
     vectordbs::{FilterOperator, IndexDistance, SearchResult, VectorChunk},
@@ -118,6 +118,18 @@ impl VectorDb for QdrantDb {
         "qdrant".into()
     }
 
+    fn capabilities(&self) -> VectorDbCapabilities {
+        VectorDbCapabilities {
+            filters: true,
+            neq_matches_missing_key: true,
+            search_within_root: false,
+            search_grouped_by_root: false,
+            replace_metadata: true,
+            num_vectors: true,
+            update_missing_content_fails: true,
+        }
+    }
+
     #[tracing::instrument]
     async fn create_index(&self, index: CreateIndexParams) -> Result<()> {
         let result = self
@@ -226,6 +238,8 @@ impl VectorDb for QdrantDb {
             .map_err(|e| anyhow!("unable to read metadata: {}", e.to_string()))?;
         let point_id = hex_to_u64(&content_id).unwrap();
         let points: Vec<PointId> = vec![point_id.into()];
+        // set_payload only overwrites the given keys, which leaves the rest of the
+        // payload including the indexify system payload in place
         let _result = self
             .create_client()?
             .set_payload(&index, None, &points.into(), metadata, None, None)
@@ -234,6 +248,23 @@ impl VectorDb for QdrantDb {
         Ok(())
     }
 
+    async fn replace_metadata(
+        &self,
+        index: &str,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        // Overwriting the payload would drop the indexify system payload, so the
+        // point is read back and upserted with the new metadata instead
+        let mut chunk = self
+            .get_points(index, vec![content_id.clone()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("content {} not found in index {}", content_id, index))?;
+        chunk.metadata = metadata;
+        self.add_embedding(index, vec![chunk]).await
+    }
+
     #[tracing::instrument]
     async fn remove_embedding(&self, index: &str, content_id: &str) -> Result<()> {
         let points_selector: PointsSelector = PointsSelector {
@@ -263,6 +294,7 @@ impl VectorDb for QdrantDb {
         query_embedding: Vec<f32>,
         k: u64,
         filters: Vec<super::Filter>,
+        include_metadata: bool,
     ) -> Result<Vec<SearchResult>> {
         let mut filter = None;
         if !filters.is_empty() {
@@ -287,7 +319,7 @@ impl VectorDb for QdrantDb {
                 vector: query_embedding,
                 limit: k,
                 with_payload: Some(WithPayloadSelector {
-                    selector_options: Some(SelectorOptions::Enable(true)),
+                    selector_options: Some(SelectorOptions::Enable(include_metadata)),
                 }),
                 filter,
                 ..Default::default()
@@ -296,6 +328,13 @@ impl VectorDb for QdrantDb {
             .map_err(|e| anyhow!("unable to read index: {}", e.to_string()))?;
         let mut documents: Vec<SearchResult> = Vec::new();
         for point in result.result {
+            if !include_metadata {
+                documents.push(SearchResult::id_only(
+                    content_id_from_point_id(point.id)?,
+                    point.score,
+                ));
+                continue;
+            }
             let (metadata, indexify_payload) = extract_metadata_from_payload(point.payload)?;
             // TODO similarity score
             documents.push(SearchResult {
@@ -304,6 +343,7 @@ impl VectorDb for QdrantDb {
                 metadata,
                 content_metadata: indexify_payload.content_metadata.clone(),
                 root_content_metadata: indexify_payload.root_content_metadata.clone(),
+                matched_filters: Vec::new(),
             });
         }
         Ok(documents)
@@ -345,7 +385,15 @@ mod tests {
     use crate::{
         server_config::QdrantConfig,
         vectordbs::{
-            tests::{basic_search, insertion_idempotent, search_filters, store_metadata},
+            conformance,
+            tests::{
+                basic_search,
+                insertion_idempotent,
+                merge_metadata,
+                replace_metadata,
+                search_filters,
+                store_metadata,
+            },
             IndexDistance,
             VectorDBTS,
         },
@@ -388,6 +436,42 @@ mod tests {
         store_metadata(qdrant, "metadata-index").await;
     }
 
+    #[tokio::test]
+    async fn test_merge_metadata() {
+        let qdrant: VectorDBTS = Arc::new(QdrantDb::new(QdrantConfig {
+            addr: "http://localhost:6334".into(),
+        }));
+        qdrant.drop_index("merge-metadata-index").await.unwrap();
+        qdrant
+            .create_index(CreateIndexParams {
+                vectordb_index_name: "merge-metadata-index".into(),
+                vector_dim: 2,
+                distance: IndexDistance::Cosine,
+                unique_params: None,
+            })
+            .await
+            .unwrap();
+        merge_metadata(qdrant, "merge-metadata-index").await;
+    }
+
+    #[tokio::test]
+    async fn test_replace_metadata() {
+        let qdrant: VectorDBTS = Arc::new(QdrantDb::new(QdrantConfig {
+            addr: "http://localhost:6334".into(),
+        }));
+        qdrant.drop_index("replace-metadata-index").await.unwrap();
+        qdrant
+            .create_index(CreateIndexParams {
+                vectordb_index_name: "replace-metadata-index".into(),
+                vector_dim: 2,
+                distance: IndexDistance::Cosine,
+                unique_params: None,
+            })
+            .await
+            .unwrap();
+        replace_metadata(qdrant, "replace-metadata-index").await;
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_insertion_idempotent() {
@@ -427,4 +511,17 @@ mod tests {
             .unwrap();
         search_filters(vector_db, index_name).await;
     }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        let Some(addr) = conformance::backend_config("INDEXIFY_TEST_QDRANT_ADDR") else {
+            return;
+        };
+        let qdrant: VectorDBTS = Arc::new(QdrantDb::new(QdrantConfig { addr }));
+        let report = conformance::run(qdrant, "conformance").await;
+        assert_eq!(
+            report.skipped,
+            vec!["search_within_root", "search_grouped_by_root"]
+        );
+    }
 }
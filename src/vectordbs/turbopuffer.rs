@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::RwLock};
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
@@ -9,7 +9,16 @@ use turbopuffer_client::Client;
 use serde::{Serialize, Deserialize};
 use serde_json::{json, Value};
 
-use super::{CreateIndexParams, VectorDb};
+use super::{
+    group_by_root,
+    CreateIndexParams,
+    Filter,
+    FilterOperator,
+    IndexDistance,
+    VectorDb,
+    VectorDbCapabilities,
+    GROUPED_SEARCH_OVERFETCH,
+};
 use crate::{
     server_config::TurboClientConfig,
     vectordbs::{SearchResult, VectorChunk},
@@ -37,27 +46,196 @@ fn hex_to_u64(hex: &str) -> Result<u64, std::num::ParseIntError> {
     u64::from_str_radix(hex, 16)
 }
 
+/// Attribute holding the id of the root content, kept next to the nested
+/// content metadata so searches can filter on it.
+const ROOT_CONTENT_ID_ATTRIBUTE: &str = "root_content_id";
+
+/// Attributes written by indexify next to the metadata of the content, they
+/// can't be filtered on like metadata.
+const SYSTEM_ATTRIBUTES: [&str; 3] = [
+    ROOT_CONTENT_ID_ATTRIBUTE,
+    "content_metadata",
+    "root_content_metadata",
+];
+
+/// Filter values are strings, while metadata keeps its json type and
+/// TurboPuffer compares values of the same type only. Values which read as
+/// a number or a boolean are compared as one.
+fn filter_value(value: &str) -> Value {
+    match serde_json::from_str::<Value>(value) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_))) => value,
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// Translates filters to TurboPuffer's `[attribute, operator, value]`
+/// filters, combined with `extra` by an `And`. None when there is nothing to
+/// filter on.
+fn to_turbopuffer_filters(filters: Vec<Filter>, extra: Option<Value>) -> Result<Option<Value>> {
+    let mut conditions = Vec::new();
+    for filter in filters {
+        if SYSTEM_ATTRIBUTES.contains(&filter.key.as_str()) {
+            return Err(anyhow!(
+                "unable to filter on {}, it isn't a metadata attribute",
+                filter.key
+            ));
+        }
+        let operator = match filter.operator {
+            FilterOperator::Eq => "Eq",
+            FilterOperator::Neq => "NotEq",
+        };
+        conditions.push(json!([filter.key, operator, filter_value(&filter.value)]));
+    }
+    conditions.extend(extra);
+    Ok(match conditions.len() {
+        0 => None,
+        1 => conditions.pop(),
+        _ => Some(json!(["And", conditions])),
+    })
+}
+
 fn extract_metadata_from_attributes(
-    attributes: HashMap<String, Value>
+    mut attributes: HashMap<String, Value>
 ) -> Result<(HashMap<String, serde_json::Value>, IndexifyPayload)> {
-    
-    let value = serde_json::to_value(attributes).map_err(|e| anyhow!("{}", e.to_string()))?;
-    let payload: HashMap<String, Value>= serde_json::from_value(value.clone()).map_err(|e| anyhow!("{}", e.to_string()))?;
-    let indexify_payload: IndexifyPayload =  serde_json::from_value(value.clone()).map_err(|e| anyhow!("{}", e.to_string()))?;
+    attributes.remove(ROOT_CONTENT_ID_ATTRIBUTE);
+    let content_metadata = attributes
+        .remove("content_metadata")
+        .ok_or(anyhow!("no indexify system payload found"))?;
+    let root_content_metadata = attributes
+        .remove("root_content_metadata")
+        .unwrap_or(Value::Null);
+    let indexify_payload = IndexifyPayload::new(
+        serde_json::from_value(content_metadata).map_err(|e| anyhow!("{}", e.to_string()))?,
+        serde_json::from_value(root_content_metadata).map_err(|e| anyhow!("{}", e.to_string()))?,
+    );
+
+    Ok((attributes, indexify_payload))
+}
+
+/// Builds the payload upserting the chunks in a single request. Attributes
+/// are sent by column, with a null for the chunks which don't have one.
+fn upsert_payload(chunks: Vec<VectorChunk>) -> Value {
+    let mut ids = Vec::with_capacity(chunks.len());
+    let mut vectors = Vec::with_capacity(chunks.len());
+    let mut attributes: HashMap<String, Vec<Value>> = HashMap::new();
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let mut chunk_attributes = chunk.metadata;
+        chunk_attributes.insert(
+            ROOT_CONTENT_ID_ATTRIBUTE.to_string(),
+            json!(chunk.content_metadata.root_content_id),
+        );
+        chunk_attributes.insert(
+            "content_metadata".to_string(),
+            json!(chunk.content_metadata),
+        );
+        chunk_attributes.insert(
+            "root_content_metadata".to_string(),
+            json!(chunk.root_content_metadata),
+        );
+        for (key, value) in chunk_attributes {
+            let column = attributes.entry(key).or_insert_with(|| vec![Value::Null; i]);
+            column.push(value);
+        }
+        //  Pad the columns of the attributes this chunk doesn't have
+        for column in attributes.values_mut() {
+            column.resize(i + 1, Value::Null);
+        }
+        ids.push(chunk.content_id);
+        vectors.push(chunk.embedding);
+    }
+    json!({
+        "ids": ids,
+        "vectors": vectors,
+        "attributes": attributes,
+    })
+}
 
-    Ok((payload, indexify_payload))
+/// Upserts the chunks in requests of at most `batch_size` chunks, stopping at
+/// the first request which fails
+async fn upsert_in_batches<F, Fut>(
+    chunks: Vec<VectorChunk>,
+    batch_size: usize,
+    mut upsert: F,
+) -> Result<()>
+where
+    F: FnMut(Value) -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let total = chunks.len();
+    let mut chunks = chunks.into_iter().peekable();
+    let mut upserted = 0;
+    while chunks.peek().is_some() {
+        let batch: Vec<VectorChunk> = chunks.by_ref().take(batch_size.max(1)).collect();
+        let batch_len = batch.len();
+        upsert(upsert_payload(batch)).await.map_err(|e| {
+            anyhow!(
+                "Failed to upsert chunks {}..{} of {}: {}",
+                upserted,
+                upserted + batch_len,
+                total,
+                e
+            )
+        })?;
+        upserted += batch_len;
+    }
+    Ok(())
+}
+
+/// Distance metric TurboPuffer ranks documents of an index by
+fn distance_metric(distance: &IndexDistance) -> Result<&'static str> {
+    match distance {
+        IndexDistance::Cosine => Ok("cosine_distance"),
+        IndexDistance::Euclidean => Ok("euclidean_squared"),
+        IndexDistance::Dot => Err(anyhow!(
+            "turbopuffer doesn't support the dot product distance"
+        )),
+    }
+}
+
+/// Configuration an index was created with. TurboPuffer creates namespaces
+/// on their first upsert, so it's kept locally to check embeddings against.
+#[derive(Debug, Clone, Copy)]
+struct IndexConfig {
+    dim: u64,
+    distance_metric: &'static str,
 }
 
 #[derive(Debug)]
 pub struct TurboPuffer {
     turbo_config: TurboClientConfig,
+    indexes: RwLock<HashMap<String, IndexConfig>>,
 }
 
 impl TurboPuffer {
     pub fn new(config: TurboClientConfig) -> TurboPuffer {
         Self {
             turbo_config: config,
+            indexes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn index_config(&self, index: &str) -> Option<IndexConfig> {
+        self.indexes.read().unwrap().get(index).copied()
+    }
+
+    /// Checks the embeddings of the chunks have the dimension the index was
+    /// created with. Indexes created by another process aren't checked.
+    fn check_dimensions(&self, index: &str, chunks: &[VectorChunk]) -> Result<()> {
+        let Some(config) = self.index_config(index) else {
+            return Ok(());
+        };
+        for chunk in chunks {
+            if chunk.embedding.len() as u64 != config.dim {
+                return Err(anyhow!(
+                    "embedding of content {} has {} dimensions, index {} was created with {}",
+                    chunk.content_id,
+                    chunk.embedding.len(),
+                    index,
+                    config.dim
+                ));
+            }
         }
+        Ok(())
     }
 
     pub fn create_client(&self) -> Result<Client> {
@@ -65,6 +243,85 @@ impl TurboPuffer {
 
         Ok(client)
     }
+
+    // TurboPuffer has no partial attribute updates, so metadata updates read the
+    // whole document back and upsert it again
+    async fn get_chunk(&self, index: &str, content_id: &str) -> Result<VectorChunk> {
+        self.get_points(index, vec![content_id.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("content {} not found in index {}", content_id, index))
+    }
+
+    async fn upsert_chunks(&self, index: &str, chunks: Vec<VectorChunk>) -> Result<()> {
+        let client = self.create_client()?;
+        let ns = client.namespace(index);
+
+        upsert_in_batches(chunks, self.turbo_config.batch_size, |payload| {
+            let ns = &ns;
+            async move {
+                ns.upsert(&payload)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| anyhow!(e.to_string()))
+            }
+        })
+        .await
+    }
+
+    async fn query(
+        &self,
+        index: &str,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Option<Value>,
+        include_attributes: bool,
+    ) -> Result<Vec<SearchResult>> {
+        let client = self.create_client()?;
+
+        let ns = client.namespace(index);
+
+        let distance_metric = self
+            .index_config(index)
+            .map_or("cosine_distance", |config| config.distance_metric);
+        let mut query = json!({
+            "top_k": k,
+            "vector": query_embedding,
+            "distance_metric": distance_metric,
+            "include_vectors": false,
+            "include_attributes": include_attributes,
+        });
+        if let Some(filters) = filters {
+            query["filters"] = filters;
+        }
+
+        let res = ns
+            .query(&query)
+            .await
+            .map_err(|e| anyhow!("Failed to search: {}", e.to_string()))?;
+
+        let mut documents: Vec<SearchResult> = Vec::new();
+
+        for doc in res.vectors {
+            if !include_attributes {
+                documents.push(SearchResult::id_only(doc.id.to_string(), doc.dist));
+                continue;
+            }
+            let attributes = doc.attributes.unwrap();
+            let ( payload, indexify_payload ) = extract_metadata_from_attributes(attributes)?;
+
+            documents.push(SearchResult {
+                content_id: doc.id.to_string(),
+                metadata: payload,
+                confidence_score: doc.dist,
+                content_metadata: indexify_payload.content_metadata,
+                root_content_metadata: indexify_payload.root_content_metadata,
+                matched_filters: Vec::new(),
+            })
+        }
+
+        Ok(documents)
+    }
 }
 
 #[async_trait]
@@ -73,46 +330,69 @@ impl VectorDb for TurboPuffer {
         "turbopuffer".to_string()
     }
 
+    fn capabilities(&self) -> VectorDbCapabilities {
+        VectorDbCapabilities {
+            filters: true,
+            neq_matches_missing_key: false,
+            search_within_root: true,
+            search_grouped_by_root: true,
+            replace_metadata: true,
+            num_vectors: false,
+            update_missing_content_fails: true,
+        }
+    }
+
     #[tracing::instrument]
-    async fn create_index(&self, _index: CreateIndexParams) -> Result<()> {
+    async fn create_index(&self, index: CreateIndexParams) -> Result<()> {
+        let config = IndexConfig {
+            dim: index.vector_dim,
+            distance_metric: distance_metric(&index.distance)?,
+        };
+        self.indexes
+            .write()
+            .unwrap()
+            .insert(index.vectordb_index_name, config);
         Ok(())
     }
 
     #[tracing::instrument]
     async fn add_embedding(&self, index: &str, chunks: Vec<VectorChunk>) -> Result<()> {
+        self.check_dimensions(index, &chunks)?;
+        self.upsert_chunks(index, chunks).await
+    }
 
-        let payload: Vec<serde_json::Value> = chunks
-            .iter()
-            .map(|chunk| {
-                let ids = vec![chunk.content_id.clone()];
-
-                json!({
-                    "ids": ids,
-                    "vectors": chunk.embedding,
-                    "attributes": &chunk.content_metadata,
-                })
-            })
-            .collect();
+    #[tracing::instrument]
+    async fn remove_embedding(&self, index: &str, content_id: &str) -> Result<()> {
+        self.remove_embeddings(index, vec![content_id.to_string()])
+            .await
+    }
 
+    // TurboPuffer deletes documents upserted without a vector, so all the
+    // documents are removed in a single request
+    #[tracing::instrument]
+    async fn remove_embeddings(&self, index: &str, content_ids: Vec<String>) -> Result<()> {
+        if content_ids.is_empty() {
+            return Ok(());
+        }
         let client = self.create_client()?;
-
         let ns = client.namespace(index);
 
-        for payload in payload.iter() {
-             ns
-                .upsert(payload)
-                .await
-                .map_err(|e| anyhow!("Failed to upsert: {}", e.to_string()));
-        }
+        let payload = json!({
+            "ids": content_ids,
+            "vectors": vec![Value::Null; content_ids.len()],
+        });
+        ns.upsert(&payload).await.map_err(|e| {
+            anyhow!(
+                "unable to remove embeddings from {} for content ids {}: {}",
+                index,
+                content_ids.join(", "),
+                e.to_string()
+            )
+        })?;
 
         Ok(())
     }
 
-    #[tracing::instrument]
-    async fn remove_embedding(&self, index: &str, content_id: &str) -> Result<()> {
-        todo!()
-    }
-
     #[tracing::instrument]
     async fn get_points(&self, index: &str, content_ids: Vec<String>) -> Result<Vec<VectorChunk>> {
         let client = self.create_client()?;
@@ -155,10 +435,24 @@ impl VectorDb for TurboPuffer {
     async fn update_metadata(
         &self,
         index: &str,
-        _content_id: String,
-        _metadata: HashMap<String, serde_json::Value>,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
     ) -> Result<()> {
-        todo!()
+        let mut chunk = self.get_chunk(index, &content_id).await?;
+        chunk.metadata.extend(metadata);
+        self.upsert_chunks(index, vec![chunk]).await
+    }
+
+    #[tracing::instrument]
+    async fn replace_metadata(
+        &self,
+        index: &str,
+        content_id: String,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        let mut chunk = self.get_chunk(index, &content_id).await?;
+        chunk.metadata = metadata;
+        self.upsert_chunks(index, vec![chunk]).await
     }
 
     async fn search(
@@ -167,49 +461,44 @@ impl VectorDb for TurboPuffer {
         query_embedding: Vec<f32>,
         k: u64,
         filters: Vec<super::Filter>,
+        include_metadata: bool,
     ) -> Result<Vec<SearchResult>> {
-        if !filters.is_empty() {
-            // TOOD: Create filter struct
-            unimplemented!();
-        }
-
-        let client = self.create_client()?;
-
-        let ns = client.namespace(&index);
-
-        let query = json!({
-            "top_k": k,
-            "vector": query_embedding,
-            "distance_metric": "cosine_distance",
-            // "filters": filters,
-            "include_vectors": false,
-            "include_attributes": true,
-        });
-
-        let res = ns
-            .query(&query)
-            .await
-            .map_err(|e| anyhow!("Failed to search: {}", e.to_string()))?;
-
-        let mut documents: Vec<SearchResult> = Vec::new();
-
-        for doc in res.vectors {
-            let attributes = doc.attributes.unwrap();
-            let ( payload, indexify_payload ) = extract_metadata_from_attributes(attributes)?;
+        let filters = to_turbopuffer_filters(filters, None)?;
+        self.query(&index, query_embedding, k, filters, include_metadata).await
+    }
 
-            documents.push(SearchResult {
-                content_id: doc.id.to_string(),
-                metadata: payload,
-                confidence_score: doc.dist,
-                content_metadata: indexify_payload.content_metadata,
-                root_content_metadata: indexify_payload.root_content_metadata,
-            })
-        }
+    async fn search_within_root(
+        &self,
+        index: String,
+        root_id: &str,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Vec<super::Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        // Every document stores the id of its root content as a separate attribute
+        let root_filter = json!([ROOT_CONTENT_ID_ATTRIBUTE, "Eq", root_id]);
+        let filters = to_turbopuffer_filters(filters, Some(root_filter))?;
+        self.query(&index, query_embedding, k, filters, true).await
+    }
 
-        Ok(documents)
+    // TurboPuffer can't group documents in a query, so chunks are over-fetched
+    // and grouped here. A document whose best chunk ranks below the fetched
+    // chunks is missed when a few documents have many matching chunks.
+    async fn search_grouped_by_root(
+        &self,
+        index: String,
+        query_embedding: Vec<f32>,
+        k: u64,
+        filters: Vec<super::Filter>,
+    ) -> Result<Vec<SearchResult>> {
+        let filters = to_turbopuffer_filters(filters, None)?;
+        let fetched = k.saturating_mul(GROUPED_SEARCH_OVERFETCH);
+        let results = self.query(&index, query_embedding, fetched, filters, true).await?;
+        Ok(group_by_root(results, k))
     }
 
     async fn drop_index(&self, index: &str) -> Result<()> {
+        self.indexes.write().unwrap().remove(index);
         let client = self.create_client()?;
 
         client
@@ -234,26 +523,65 @@ mod tests {
     use crate::{
         server_config::TurboClientConfig,
         vectordbs::{
-            tests::{basic_search, store_metadata},
+            conformance,
+            tests::{
+                basic_search,
+                merge_metadata,
+                replace_metadata,
+                search_grouped_by_root,
+                search_within_root,
+                search_without_metadata,
+                store_metadata,
+            },
+            CreateIndexParams,
+            Filter,
+            FilterOperator,
+            IndexDistance,
             VectorDBTS,
+            VectorDb,
         },
     };
 
-    use super::TurboPuffer;
+    use anyhow::anyhow;
+    use serde_json::{json, Value};
+
+    use super::{
+        to_turbopuffer_filters,
+        upsert_in_batches,
+        upsert_payload,
+        TurboPuffer,
+        ROOT_CONTENT_ID_ATTRIBUTE,
+        SYSTEM_ATTRIBUTES,
+    };
+    use crate::{
+        test_util::db_utils::{create_metadata, test_mock_content_metadata},
+        vectordbs::VectorChunk,
+    };
 
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_search_basic(){
-        let turbo_client: VectorDBTS= Arc::new(TurboPuffer::new (TurboClientConfig { api_key: "test".into() }));
+        let turbo_client: VectorDBTS= Arc::new(TurboPuffer::new (TurboClientConfig { api_key: "test".into(), ..Default::default() }));
 
 
         basic_search(turbo_client, "test").await;
     }
 
+    #[tokio::test]
+    async fn test_search_without_metadata() {
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        }));
+
+        search_without_metadata(turbo_client, "test").await;
+    }
+
     #[tokio::test]
     async fn test_store_metadata() {
-        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig{ 
-            api_key: "test".into()
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
         }));
 
         turbo_client.drop_index("test").await.unwrap();
@@ -261,4 +589,291 @@ mod tests {
         store_metadata(turbo_client, "test").await;
     }
 
+    #[tokio::test]
+    async fn test_merge_metadata() {
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        }));
+
+        turbo_client.drop_index("test-merge").await.unwrap();
+
+        merge_metadata(turbo_client, "test-merge").await;
+    }
+
+    #[tokio::test]
+    async fn test_replace_metadata() {
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        }));
+
+        turbo_client.drop_index("test-replace").await.unwrap();
+
+        replace_metadata(turbo_client, "test-replace").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_within_root() {
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        }));
+
+        turbo_client.drop_index("test-root").await.unwrap();
+
+        search_within_root(turbo_client, "test-root").await;
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_by_root() {
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        }));
+
+        turbo_client.drop_index("test-grouped").await.unwrap();
+
+        search_grouped_by_root(turbo_client, "test-grouped").await;
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        let Some(api_key) = conformance::backend_config("INDEXIFY_TEST_TURBOPUFFER_API_KEY") else {
+            return;
+        };
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig { api_key, ..Default::default() }));
+        let report = conformance::run(turbo_client, "conformance").await;
+        assert_eq!(report.skipped, vec!["num_vectors"]);
+    }
+
+    fn chunks(count: usize) -> Vec<VectorChunk> {
+        (0..count)
+            .map(|i| {
+                let content_id = format!("{:x}", i + 1);
+                VectorChunk {
+                    embedding: vec![1., i as f32],
+                    metadata: create_metadata(vec![("key1", "value1")]),
+                    root_content_metadata: None,
+                    content_metadata: test_mock_content_metadata(&content_id, "", "graph1"),
+                    content_id,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_upsert_payload_pads_missing_attributes() {
+        let mut chunks = chunks(3);
+        chunks[0].metadata.clear();
+        chunks[2]
+            .metadata
+            .insert("key2".to_string(), json!("value2"));
+        let payload = upsert_payload(chunks);
+        assert_eq!(payload["ids"], json!(["1", "2", "3"]));
+        assert_eq!(payload["vectors"], json!([[1., 0.], [1., 1.], [1., 2.]]));
+        assert_eq!(
+            payload["attributes"]["key1"],
+            json!([null, "value1", "value1"])
+        );
+        assert_eq!(payload["attributes"]["key2"], json!([null, null, "value2"]));
+        assert_eq!(
+            payload["attributes"]["content_metadata"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_in_batches() {
+        let mut payloads: Vec<Value> = Vec::new();
+        upsert_in_batches(chunks(1000), 256, |payload| {
+            payloads.push(payload);
+            async { Ok(()) }
+        })
+        .await
+        .unwrap();
+        let batch_sizes: Vec<usize> = payloads
+            .iter()
+            .map(|payload| payload["ids"].as_array().unwrap().len())
+            .collect();
+        assert_eq!(batch_sizes, vec![256, 256, 256, 232]);
+        let ids: Vec<&str> = payloads
+            .iter()
+            .flat_map(|payload| payload["ids"].as_array().unwrap())
+            .map(|id| id.as_str().unwrap())
+            .collect();
+        let expected: Vec<String> = (1..=1000).map(|i| format!("{:x}", i)).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_batch_failure_is_returned() {
+        let mut requests = 0;
+        let err = upsert_in_batches(chunks(1000), 256, |_| {
+            requests += 1;
+            let failed = requests == 2;
+            async move {
+                if failed {
+                    return Err(anyhow!("service unavailable"));
+                }
+                Ok(())
+            }
+        })
+        .await
+        .unwrap_err();
+        //  The batches after the failed one aren't sent
+        assert_eq!(requests, 2);
+        assert_eq!(
+            err.to_string(),
+            "Failed to upsert chunks 256..512 of 1000: service unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_1000_chunks() {
+        let Some(api_key) = conformance::backend_config("INDEXIFY_TEST_TURBOPUFFER_API_KEY") else {
+            return;
+        };
+        let turbo_client: VectorDBTS = Arc::new(TurboPuffer::new(TurboClientConfig {
+            api_key,
+            ..Default::default()
+        }));
+        let index = "test-upsert-batches";
+        let chunks = chunks(1000);
+        let ids: Vec<String> = chunks.iter().map(|c| c.content_id.clone()).collect();
+        turbo_client.add_embedding(index, chunks).await.unwrap();
+        let points = turbo_client.get_points(index, ids.clone()).await.unwrap();
+        let mut found: Vec<String> = points.into_iter().map(|p| p.content_id).collect();
+        found.sort();
+        let mut ids = ids;
+        ids.sort();
+        assert_eq!(found, ids);
+        turbo_client.drop_index(index).await.unwrap();
+    }
+
+    fn index_params(name: &str, vector_dim: u64, distance: IndexDistance) -> CreateIndexParams {
+        CreateIndexParams {
+            vectordb_index_name: name.to_string(),
+            vector_dim,
+            distance,
+            unique_params: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embedding_dimension_mismatch() {
+        let turbo_client = TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        });
+        turbo_client
+            .create_index(index_params("test-dim", 384, IndexDistance::Cosine))
+            .await
+            .unwrap();
+        let mut chunk = chunks(1).pop().unwrap();
+        chunk.embedding = vec![0.; 512];
+        let err = turbo_client
+            .add_embedding("test-dim", vec![chunk.clone()])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "embedding of content 1 has 512 dimensions, index test-dim was created with 384"
+        );
+
+        chunk.embedding = vec![0.; 384];
+        assert!(turbo_client.check_dimensions("test-dim", &[chunk]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_dot_product_distance_is_rejected() {
+        let turbo_client = TurboPuffer::new(TurboClientConfig {
+            api_key: "test".into(),
+            ..Default::default()
+        });
+        assert!(turbo_client
+            .create_index(index_params("test-dot", 384, IndexDistance::Dot))
+            .await
+            .is_err());
+        turbo_client
+            .create_index(index_params("test-l2", 2, IndexDistance::Euclidean))
+            .await
+            .unwrap();
+        assert_eq!(
+            turbo_client.index_config("test-l2").unwrap().distance_metric,
+            "euclidean_squared"
+        );
+    }
+
+    fn filter(key: &str, value: &str, operator: FilterOperator) -> Filter {
+        Filter {
+            key: key.to_string(),
+            value: value.to_string(),
+            operator,
+        }
+    }
+
+    #[test]
+    fn test_equality_filters() {
+        let filters = to_turbopuffer_filters(
+            vec![filter("page_count", "42", FilterOperator::Eq)],
+            None,
+        )
+        .unwrap();
+        assert_eq!(filters, Some(json!(["page_count", "Eq", 42])));
+
+        let filters = to_turbopuffer_filters(
+            vec![
+                filter("page_count", "4.5", FilterOperator::Neq),
+                filter("mime", "text/plain", FilterOperator::Eq),
+                filter("reviewed", "true", FilterOperator::Eq),
+            ],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            filters,
+            Some(json!([
+                "And",
+                [
+                    ["page_count", "NotEq", 4.5],
+                    ["mime", "Eq", "text/plain"],
+                    ["reviewed", "Eq", true],
+                ]
+            ]))
+        );
+
+        assert_eq!(to_turbopuffer_filters(vec![], None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_filters_are_combined_with_the_root_filter() {
+        let root_filter = json!([ROOT_CONTENT_ID_ATTRIBUTE, "Eq", "root"]);
+        assert_eq!(
+            to_turbopuffer_filters(vec![], Some(root_filter.clone())).unwrap(),
+            Some(root_filter.clone())
+        );
+        assert_eq!(
+            to_turbopuffer_filters(
+                vec![filter("page_count", "7", FilterOperator::Eq)],
+                Some(root_filter.clone())
+            )
+            .unwrap(),
+            Some(json!(["And", [["page_count", "Eq", 7], root_filter]]))
+        );
+    }
+
+    #[test]
+    fn test_system_attributes_cant_be_filtered_on() {
+        for key in SYSTEM_ATTRIBUTES {
+            assert!(
+                to_turbopuffer_filters(vec![filter(key, "value", FilterOperator::Eq)], None)
+                    .is_err()
+            );
+        }
+    }
+
 }
\ No newline at end of file